@@ -528,7 +528,7 @@ impl AdvancedBackendDemo {
 }
 
 fn main() {
-    artifice_logging::init_from_env().expect("Failed to initialize logger");
+    let _log_guard = artifice_logging::init_from_env().expect("Failed to initialize logger");
     info!("Starting Advanced Backend Switching Demo");
 
     let app = AdvancedBackendDemo::new();
@@ -581,7 +581,9 @@ fn main() {
     }
 
     // Run the engine normally
-    engine.run();
+    if let Err(e) = engine.run() {
+        error!("{}", e);
+    }
 
     info!("Advanced Backend Switching Demo completed successfully");
 }
@@ -3,11 +3,10 @@ extern crate gl;
 extern crate glfw;
 extern crate artifice_logging;
 
-use std::ffi::CString;
-
 use artifice_engine::events::{
     Event, EventType, KeyAction, KeyCode,
 };
+use artifice_engine::renderer::Shader;
 use artifice_engine::{Engine, Application};
 use artifice_engine::window::{HotReloadConfig, HotReloadStatus};
 use artifice_engine::io::MetricsConfig;
@@ -16,12 +15,11 @@ use artifice_logging::{error, info, warn};
 pub struct BackendSwitchingDemo {
     vertex_array: u32,
     vertex_buffer: u32,
-    shader_program: u32,
+    shader: Option<Shader>,
     rotation: f32,
     current_backend: String,
     switch_requested: Option<String>,
     background_color: (f32, f32, f32),
-    triangle_color_location: i32,
     color_cycle_time: f32,
 }
 
@@ -30,12 +28,11 @@ impl Application for BackendSwitchingDemo {
         BackendSwitchingDemo {
             vertex_array: 0,
             vertex_buffer: 0,
-            shader_program: 0,
+            shader: None,
             rotation: 0.0,
             current_backend: "glfw".to_string(),
             switch_requested: None,
             background_color: (0.2, 0.3, 0.3),
-            triangle_color_location: -1,
             color_cycle_time: 0.0,
         }
     }
@@ -79,69 +76,32 @@ impl Application for BackendSwitchingDemo {
             );
             gl::EnableVertexAttribArray(0);
 
-            // Create and compile the vertex shader
-            let vertex_shader = gl::CreateShader(gl::VERTEX_SHADER);
-            let vertex_shader_source = CString::new(
-                "#version 330 core
-                layout (location = 0) in vec3 aPos;
-                uniform float rotation;
-
-                void main() {
-                    float angle = rotation;
-                    float x = aPos.x * cos(angle) - aPos.y * sin(angle);
-                    float y = aPos.x * sin(angle) + aPos.y * cos(angle);
-                    gl_Position = vec4(x, y, aPos.z, 1.0);
-                }",
-            )
-            .unwrap();
-            gl::ShaderSource(
-                vertex_shader,
-                1,
-                &vertex_shader_source.as_ptr(),
-                std::ptr::null(),
-            );
-            gl::CompileShader(vertex_shader);
-            check_shader_compilation(vertex_shader);
-
-            // Create and compile the fragment shader
-            let fragment_shader = gl::CreateShader(gl::FRAGMENT_SHADER);
-            let fragment_shader_source = CString::new(
-                "#version 330 core
-                out vec4 FragColor;
-                uniform vec3 triangleColor;
-
-                void main() {
-                    FragColor = vec4(triangleColor, 1.0);
-                }",
-            )
-            .unwrap();
-            gl::ShaderSource(
-                fragment_shader,
-                1,
-                &fragment_shader_source.as_ptr(),
-                std::ptr::null(),
-            );
-            gl::CompileShader(fragment_shader);
-            check_shader_compilation(fragment_shader);
-
-            // Create and link the shader program
-            let shader_program = gl::CreateProgram();
-            gl::AttachShader(shader_program, vertex_shader);
-            gl::AttachShader(shader_program, fragment_shader);
-            gl::LinkProgram(shader_program);
-            check_program_linking(shader_program);
-
-            // Get uniform locations
-            let color_name = CString::new("triangleColor").unwrap();
-            self.triangle_color_location = gl::GetUniformLocation(shader_program, color_name.as_ptr());
-
-            // Delete the shaders as they're linked into the program and no longer needed
-            gl::DeleteShader(vertex_shader);
-            gl::DeleteShader(fragment_shader);
-
             self.vertex_array = vao;
             self.vertex_buffer = vbo;
-            self.shader_program = shader_program;
+        }
+
+        let shader = Shader::from_sources(
+            "#version 330 core
+            layout (location = 0) in vec3 aPos;
+            uniform float rotation;
+
+            void main() {
+                float angle = rotation;
+                float x = aPos.x * cos(angle) - aPos.y * sin(angle);
+                float y = aPos.x * sin(angle) + aPos.y * cos(angle);
+                gl_Position = vec4(x, y, aPos.z, 1.0);
+            }",
+            "#version 330 core
+            out vec4 FragColor;
+            uniform vec3 triangleColor;
+
+            void main() {
+                FragColor = vec4(triangleColor, 1.0);
+            }",
+        );
+        match shader {
+            Ok(shader) => self.shader = Some(shader),
+            Err(e) => error!("{}", e),
         }
 
         info!("OpenGL initialized successfully for backend: {}", self.current_backend);
@@ -169,32 +129,23 @@ impl Application for BackendSwitchingDemo {
                 1.0
             );
             gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
 
-            // Draw the triangle
-            gl::UseProgram(self.shader_program);
-
-            // Set rotation uniform
-            let rotation_name = CString::new("rotation").unwrap();
-            let rotation_loc = gl::GetUniformLocation(self.shader_program, rotation_name.as_ptr());
-            gl::Uniform1f(rotation_loc, self.rotation);
+        // Draw the triangle
+        let Some(shader) = self.shader.as_mut() else { return };
+        shader.set_uniform("rotation", self.rotation);
 
-            // Set triangle color with cycling effect
-            let cycle = (self.color_cycle_time * 2.0).sin() * 0.3 + 0.7;
-            match self.current_backend.as_str() {
-                "glfw" => {
-                    gl::Uniform3f(self.triangle_color_location, 1.0 * cycle, 0.5 * cycle, 0.2 * cycle);
-                }
-                "wayland" => {
-                    gl::Uniform3f(self.triangle_color_location, 0.2 * cycle, 1.0 * cycle, 0.5 * cycle);
-                }
-                "x11" => {
-                    gl::Uniform3f(self.triangle_color_location, 0.2 * cycle, 0.5 * cycle, 1.0 * cycle);
-                }
-                _ => {
-                    gl::Uniform3f(self.triangle_color_location, 0.5 * cycle, 0.5 * cycle, 0.5 * cycle);
-                }
-            }
+        // Set triangle color with cycling effect
+        let cycle = (self.color_cycle_time * 2.0).sin() * 0.3 + 0.7;
+        let color = match self.current_backend.as_str() {
+            "glfw" => [1.0, 0.5, 0.2],
+            "wayland" => [0.2, 1.0, 0.5],
+            "x11" => [0.2, 0.5, 1.0],
+            _ => [0.5, 0.5, 0.5],
+        };
+        shader.set_uniform("triangleColor", color.map(|channel| channel * cycle));
 
+        unsafe {
             gl::BindVertexArray(self.vertex_array);
             gl::DrawArrays(gl::TRIANGLES, 0, 3);
         }
@@ -205,8 +156,8 @@ impl Application for BackendSwitchingDemo {
         unsafe {
             gl::DeleteVertexArrays(1, &self.vertex_array);
             gl::DeleteBuffers(1, &self.vertex_buffer);
-            gl::DeleteProgram(self.shader_program);
         }
+        self.shader = None;
         info!("BackendSwitchingDemo shutdown complete!");
     }
 
@@ -321,7 +272,7 @@ impl BackendSwitchingDemo {
 }
 
 fn main() {
-    artifice_logging::init_from_env().expect("Failed to initialize logger");
+    let _log_guard = artifice_logging::init_from_env().expect("Failed to initialize logger");
     info!("Starting Backend Switching Demo");
 
     // Create application
@@ -364,45 +315,9 @@ fn main() {
     }
 
     // Run the engine normally - backend switching will be handled in event() method
-    engine.run();
+    if let Err(e) = engine.run() {
+        error!("{}", e);
+    }
 
     info!("Backend Switching Demo completed");
 }
-
-unsafe fn check_shader_compilation(shader: u32) {
-    let mut success = 0;
-    gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
-    if success == 0 {
-        let mut log_length = 0;
-        gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut log_length);
-        let mut log = Vec::with_capacity(log_length as usize);
-        log.set_len(log_length as usize);
-        gl::GetShaderInfoLog(
-            shader,
-            log_length,
-            std::ptr::null_mut(),
-            log.as_mut_ptr() as *mut i8,
-        );
-        let log_str = std::str::from_utf8(&log).unwrap_or("Unknown error");
-        error!("Shader compilation failed: {}", log_str);
-    }
-}
-
-unsafe fn check_program_linking(program: u32) {
-    let mut success = 0;
-    gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
-    if success == 0 {
-        let mut log_length = 0;
-        gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut log_length);
-        let mut log = Vec::with_capacity(log_length as usize);
-        log.set_len(log_length as usize);
-        gl::GetProgramInfoLog(
-            program,
-            log_length,
-            std::ptr::null_mut(),
-            log.as_mut_ptr() as *mut i8,
-        );
-        let log_str = std::str::from_utf8(&log).unwrap_or("Unknown error");
-        error!("Program linking failed: {}", log_str);
-    }
-}
\ No newline at end of file
@@ -3,18 +3,17 @@ extern crate gl;
 extern crate glfw;
 extern crate artifice_logging;
 
-use std::ffi::CString;
-
 use artifice_engine::events::{
     Event, EventType, KeyAction, KeyCode,
 };
+use artifice_engine::renderer::Shader;
 use artifice_engine::{Engine, Application};
 use artifice_logging::{error, info};
 
 pub struct TestApplication {
     vertex_array: u32,
     vertex_buffer: u32,
-    shader_program: u32,
+    shader: Option<Shader>,
     rotation: f32,
 }
 
@@ -23,7 +22,7 @@ impl Application for TestApplication {
         TestApplication {
             vertex_array: 0,
             vertex_buffer: 0,
-            shader_program: 0,
+            shader: None,
             rotation: 0.0,
         }
     }
@@ -67,64 +66,31 @@ impl Application for TestApplication {
             );
             gl::EnableVertexAttribArray(0);
 
-            // Create and compile the vertex shader
-            let vertex_shader = gl::CreateShader(gl::VERTEX_SHADER);
-            let vertex_shader_source = CString::new(
-                "#version 330 core
-                layout (location = 0) in vec3 aPos;
-                uniform float rotation;
-
-                void main() {
-                    float angle = rotation;
-                    float x = aPos.x * cos(angle) - aPos.y * sin(angle);
-                    float y = aPos.x * sin(angle) + aPos.y * cos(angle);
-                    gl_Position = vec4(x, y, aPos.z, 1.0);
-                }",
-            )
-            .unwrap();
-            gl::ShaderSource(
-                vertex_shader,
-                1,
-                &vertex_shader_source.as_ptr(),
-                std::ptr::null(),
-            );
-            gl::CompileShader(vertex_shader);
-            check_shader_compilation(vertex_shader);
-
-            // Create and compile the fragment shader
-            let fragment_shader = gl::CreateShader(gl::FRAGMENT_SHADER);
-            let fragment_shader_source = CString::new(
-                "#version 330 core
-                out vec4 FragColor;
-
-                void main() {
-                    FragColor = vec4(1.0, 0.5, 0.2, 1.0);
-                }",
-            )
-            .unwrap();
-            gl::ShaderSource(
-                fragment_shader,
-                1,
-                &fragment_shader_source.as_ptr(),
-                std::ptr::null(),
-            );
-            gl::CompileShader(fragment_shader);
-            check_shader_compilation(fragment_shader);
-
-            // Create and link the shader program
-            let shader_program = gl::CreateProgram();
-            gl::AttachShader(shader_program, vertex_shader);
-            gl::AttachShader(shader_program, fragment_shader);
-            gl::LinkProgram(shader_program);
-            check_program_linking(shader_program);
-
-            // Delete the shaders as they're linked into the program and no longer needed
-            gl::DeleteShader(vertex_shader);
-            gl::DeleteShader(fragment_shader);
-
             self.vertex_array = vao;
             self.vertex_buffer = vbo;
-            self.shader_program = shader_program;
+        }
+
+        let shader = Shader::from_sources(
+            "#version 330 core
+            layout (location = 0) in vec3 aPos;
+            uniform float rotation;
+
+            void main() {
+                float angle = rotation;
+                float x = aPos.x * cos(angle) - aPos.y * sin(angle);
+                float y = aPos.x * sin(angle) + aPos.y * cos(angle);
+                gl_Position = vec4(x, y, aPos.z, 1.0);
+            }",
+            "#version 330 core
+            out vec4 FragColor;
+
+            void main() {
+                FragColor = vec4(1.0, 0.5, 0.2, 1.0);
+            }",
+        );
+        match shader {
+            Ok(shader) => self.shader = Some(shader),
+            Err(e) => error!("{}", e),
         }
 
         info!("OpenGL initialized successfully");
@@ -145,15 +111,12 @@ impl Application for TestApplication {
         unsafe {
             gl::ClearColor(0.2, 0.3, 0.3, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
 
-            // Draw the triangle
-            gl::UseProgram(self.shader_program);
-
-            // Set rotation uniform
-            let rotation_name = CString::new("rotation").unwrap();
-            let rotation_loc = gl::GetUniformLocation(self.shader_program, rotation_name.as_ptr());
-            gl::Uniform1f(rotation_loc, self.rotation);
-
+        // Draw the triangle
+        let Some(shader) = self.shader.as_mut() else { return };
+        shader.set_uniform("rotation", self.rotation);
+        unsafe {
             gl::BindVertexArray(self.vertex_array);
             gl::DrawArrays(gl::TRIANGLES, 0, 3);
         }
@@ -164,8 +127,8 @@ impl Application for TestApplication {
         unsafe {
             gl::DeleteVertexArrays(1, &self.vertex_array);
             gl::DeleteBuffers(1, &self.vertex_buffer);
-            gl::DeleteProgram(self.shader_program);
         }
+        self.shader = None;
         info!("TestApplication shutdown complete!");
     }
 
@@ -205,12 +168,18 @@ impl Application for TestApplication {
 }
 
 fn main() {
-    artifice_logging::init_from_env().expect("Failed to initialize logger");
+    let _log_guard = artifice_logging::init_from_env().expect("Failed to initialize logger");
     info!("Program has started!");
 
     // Create and run the application using the actual engine
     let app = TestApplication::new();
-    let mut engine = Engine::new(app);
+    let mut engine = match Engine::try_new(app) {
+        Ok(engine) => engine,
+        Err(e) => {
+            error!("Cannot start: {}", e);
+            return;
+        }
+    };
 
     // Set up OpenGL debug output if available
     unsafe {
@@ -220,45 +189,9 @@ fn main() {
         }
     }
 
-    engine.run();
-
-    info!("Program has finished");
-}
-
-unsafe fn check_shader_compilation(shader: u32) {
-    let mut success = 0;
-    gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
-    if success == 0 {
-        let mut log_length = 0;
-        gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut log_length);
-        let mut log = Vec::with_capacity(log_length as usize);
-        log.set_len(log_length as usize);
-        gl::GetShaderInfoLog(
-            shader,
-            log_length,
-            std::ptr::null_mut(),
-            log.as_mut_ptr() as *mut i8,
-        );
-        let log_str = std::str::from_utf8(&log).unwrap_or("Unknown error");
-        error!("Shader compilation failed: {}", log_str);
+    if let Err(e) = engine.run() {
+        error!("{}", e);
     }
-}
 
-unsafe fn check_program_linking(program: u32) {
-    let mut success = 0;
-    gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
-    if success == 0 {
-        let mut log_length = 0;
-        gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut log_length);
-        let mut log = Vec::with_capacity(log_length as usize);
-        log.set_len(log_length as usize);
-        gl::GetProgramInfoLog(
-            program,
-            log_length,
-            std::ptr::null_mut(),
-            log.as_mut_ptr() as *mut i8,
-        );
-        let log_str = std::str::from_utf8(&log).unwrap_or("Unknown error");
-        error!("Program linking failed: {}", log_str);
-    }
+    info!("Program has finished");
 }
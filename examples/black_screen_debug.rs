@@ -514,7 +514,7 @@ impl BlackScreenDebugger {
 }
 
 fn main() {
-    artifice_logging::init_from_env().expect("Failed to initialize logger");
+    let _log_guard = artifice_logging::init_from_env().expect("Failed to initialize logger");
     
     info!("🖤 === BLACK SCREEN DEBUGGER ===");
     info!("This tool will identify why the screen goes black after X11 switch");
@@ -551,7 +551,9 @@ fn main() {
     info!("🚀 Starting black screen debugger...");
     info!("🕐 Auto-switch to X11 will happen in 3 seconds");
     
-    engine.run();
+    if let Err(e) = engine.run() {
+        error!("{}", e);
+    }
     
     info!("🏁 Black screen debugging completed");
 }
\ No newline at end of file
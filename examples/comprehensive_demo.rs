@@ -1,7 +1,7 @@
 use artifice_engine::{
     Engine, Application, Layer,
     events::{
-        Event, EventData, EventType, EventFilter, EventFilterManager,
+        Event, EventData, EventType, EventFilter, EventFilterManager, EventSender,
         EventTypeFilter, PredicateFilter, CustomEventData,
         KeyEvent, KeyCode, KeyAction, MouseButtonEvent, MouseButton,
         GamepadButtonEvent, GamepadButton
@@ -16,7 +16,7 @@ use artifice_engine::{
         HotReloadConfig, HotReloadBuilder, WindowFeature
     }
 };
-use artifice_logging::{info, debug, warn};
+use artifice_logging::{error, info, debug, warn};
 use std::time::Duration;
 use std::sync::Arc;
 
@@ -65,6 +65,9 @@ struct ComprehensiveDemo {
     // Game state
     player_score: i32,
     game_objects: Vec<GameObject>,
+
+    // Posts custom events through the engine
+    event_sender: Option<EventSender>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -140,6 +143,7 @@ impl Application for ComprehensiveDemo {
                     color: (0.0, 0.0, 1.0),
                 },
             ],
+            event_sender: None,
         }
     }
 
@@ -234,6 +238,10 @@ impl Application for ComprehensiveDemo {
         }
     }
 
+    fn set_event_sender(&mut self, sender: EventSender) {
+        self.event_sender = Some(sender);
+    }
+
     fn get_name(&self) -> &str {
         "Comprehensive Engine Demo"
     }
@@ -347,7 +355,7 @@ impl ComprehensiveDemo {
                         score: self.player_score,
                         timestamp: self.phase_timer as f64,
                     };
-                    self.send_custom_event("PlayerScore", score_event);
+                    self.send_custom_event(score_event);
                 }
                 _ => {
                     debug!("Key pressed: {:?} (modifiers: shift={}, ctrl={}, alt={})", 
@@ -403,7 +411,7 @@ impl ComprehensiveDemo {
                 }
                 GamepadButton::Start => {
                     info!("Gamepad Start - pausing game");
-                    self.send_custom_event("GameState", GameStateEvent {
+                    self.send_custom_event(GameStateEvent {
                         state: "paused".to_string(),
                         data: "user_requested".to_string(),
                     });
@@ -417,27 +425,16 @@ impl ComprehensiveDemo {
     fn handle_custom_event(&mut self, custom_event: &CustomEventData, event: &mut Event) {
         info!("Received custom event: {}", custom_event.type_name());
         
-        match custom_event.type_name() {
-            "PlayerScore" => {
-                if let Some(score_event) = custom_event.get_data::<PlayerScoreEvent>() {
-                    info!("Player {} scored {} points at time {:.2}", 
-                          score_event.player_id, score_event.score, score_event.timestamp);
-                }
-            }
-            "GameState" => {
-                if let Some(state_event) = custom_event.get_data::<GameStateEvent>() {
-                    info!("Game state changed to: {} ({})", state_event.state, state_event.data);
-                }
-            }
-            "Network" => {
-                if let Some(network_event) = custom_event.get_data::<NetworkEvent>() {
-                    info!("Network event: {} ({} bytes)", 
-                          network_event.message_type, network_event.payload.len());
-                }
-            }
-            _ => {
-                info!("Unknown custom event type: {}", custom_event.type_name());
-            }
+        if let Some(score_event) = custom_event.get_data::<PlayerScoreEvent>() {
+            info!("Player {} scored {} points at time {:.2}", 
+                  score_event.player_id, score_event.score, score_event.timestamp);
+        } else if let Some(state_event) = custom_event.get_data::<GameStateEvent>() {
+            info!("Game state changed to: {} ({})", state_event.state, state_event.data);
+        } else if let Some(network_event) = custom_event.get_data::<NetworkEvent>() {
+            info!("Network event: {} ({} bytes)", 
+                  network_event.message_type, network_event.payload.len());
+        } else {
+            info!("Unknown custom event type: {}", custom_event.type_name());
         }
         event.mark_handled();
     }
@@ -519,14 +516,14 @@ impl ComprehensiveDemo {
             score: 1337,
             timestamp: self.phase_timer as f64,
         };
-        self.send_custom_event("PlayerScore", score_event);
+        self.send_custom_event(score_event);
 
         // Game state event
         let state_event = GameStateEvent {
             state: "level_complete".to_string(),
             data: "level_3_boss_defeated".to_string(),
         };
-        self.send_custom_event("GameState", state_event);
+        self.send_custom_event(state_event);
     }
 
     fn simulate_network_events(&mut self) {
@@ -548,15 +545,17 @@ impl ComprehensiveDemo {
         ];
 
         for network_event in events {
-            self.send_custom_event("Network", network_event);
+            self.send_custom_event(network_event);
         }
     }
 
-    fn send_custom_event<T: Send + Sync + 'static>(&self, type_name: &str, data: T) {
-        let custom_data = CustomEventData::new(type_name, data);
-        let event = Event::new(EventData::Custom(custom_data));
-        // Note: In a real implementation, you would send this through the engine's event system
-        debug!("Custom event '{}' would be sent to engine", type_name);
+    fn send_custom_event<T: Send + Sync + 'static>(&self, data: T) {
+        let type_name = std::any::type_name::<T>();
+        let event = Event::custom(data);
+        match &self.event_sender {
+            Some(sender) if sender.send(event).is_ok() => debug!("Posted custom event '{}'", type_name),
+            _ => warn!("Custom event '{}' dropped: engine not running", type_name),
+        }
     }
 
     fn spawn_game_object(&mut self) {
@@ -639,7 +638,7 @@ impl Layer for MetricsOverlayLayer {
 
 fn main() {
     // Initialize logging
-    artifice_logging::init().expect("Failed to initialize logging");
+    let _log_guard = artifice_logging::init().expect("Failed to initialize logging");
 
     info!("Starting Comprehensive Engine Demo");
     info!("Controls:");
@@ -680,7 +679,7 @@ fn main() {
     let overlay_layer = Box::new(MetricsOverlayLayer {
         update_timer: 0.0,
     });
-    engine.push_layer(overlay_layer);
+    engine.push_overlay(overlay_layer);
 
     // Set up event filters based on demo requirements
     let filter_manager = engine.get_event_filter_manager_mut();
@@ -699,8 +698,10 @@ fn main() {
     let performance_filter = EventTypeFilter::new("performance_mode", vec![
         EventType::Keyboard,
         EventType::Mouse,
+        EventType::Gesture,
         EventType::Gamepad,
         EventType::Application,
+        EventType::Custom,
     ]).with_priority(10);
     filter_manager.add_filter(Box::new(performance_filter));
 
@@ -710,7 +711,9 @@ fn main() {
     info!("Engine configured and ready - starting main loop");
 
     // Run the application
-    engine.run();
+    if let Err(e) = engine.run() {
+        error!("{}", e);
+    }
 
     info!("Demo completed - goodbye!");
 }
\ No newline at end of file
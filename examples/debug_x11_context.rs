@@ -450,7 +450,7 @@ impl X11ContextDebugger {
 }
 
 fn main() {
-    artifice_logging::init_from_env().expect("Failed to initialize logger");
+    let _log_guard = artifice_logging::init_from_env().expect("Failed to initialize logger");
     
     info!("🐛 === X11 OpenGL Context Debugger ===");
     info!("This tool will comprehensively test OpenGL context functionality");
@@ -487,7 +487,9 @@ fn main() {
     info!("🚀 Starting context debugger...");
     info!("🕐 Auto-switch to X11 will happen in 3 seconds");
     
-    engine.run();
+    if let Err(e) = engine.run() {
+        error!("{}", e);
+    }
     
     info!("🏁 Context debugging completed");
 }
\ No newline at end of file
@@ -5,7 +5,7 @@ extern crate artifice_logging;
 
 use artifice_engine::events::{Event, EventType, KeyAction, KeyCode, MouseButton};
 use artifice_engine::{run_application, Application};
-use artifice_logging::{info, warn};
+use artifice_logging::{error, info, warn};
 
 pub struct EventSystemDemoApp {
     frame_count: u64,
@@ -138,6 +138,18 @@ impl Application for EventSystemDemoApp {
                 }
             }
 
+            EventType::Gesture => {
+                if let Some(pinch_event) = event.as_pinch_event() {
+                    info!("Pinch: scale {:.3} at ({:.1}, {:.1})", pinch_event.scale, pinch_event.x, pinch_event.y);
+                }
+                else if let Some(swipe_event) = event.as_swipe_event() {
+                    info!("Swipe {:?}: {:.1} px", swipe_event.direction, swipe_event.distance);
+                }
+                else if let Some(long_press_event) = event.as_long_press_event() {
+                    info!("Long press at ({:.1}, {:.1})", long_press_event.x, long_press_event.y);
+                }
+            }
+
             EventType::Gamepad => {
                 if let Some(button_event) = event.as_gamepad_button_event() {
                     let action_str = match button_event.action {
@@ -204,10 +216,13 @@ impl Application for EventSystemDemoApp {
 
 fn main() {
     // Initialize logging first
-    if let Err(e) = artifice_logging::init_from_env() {
-        eprintln!("Failed to initialize logger: {}", e);
-        return;
-    }
+    let _log_guard = match artifice_logging::init_from_env() {
+        Ok(guard) => guard,
+        Err(e) => {
+            eprintln!("Failed to initialize logger: {}", e);
+            return;
+        }
+    };
 
     info!("Starting Event System Demo");
     info!("This demo showcases the new event system architecture:");
@@ -225,7 +240,9 @@ fn main() {
     // engine.run();
     
     // But for simplicity, we'll use the convenience function
-    run_application::<EventSystemDemoApp>();
+    if let Err(e) = run_application::<EventSystemDemoApp>() {
+        error!("{}", e);
+    }
 
     info!("Event System Demo completed");
 }
@@ -0,0 +1,125 @@
+extern crate artifice_engine;
+extern crate artifice_logging;
+
+use artifice_engine::renderer::{
+    BlendMode, BufferDesc, BufferHandle, BufferKind, BufferUsage, PipelineDesc, PipelineHandle, ShaderSource,
+    SharedRenderDevice, VertexFormat, VertexLayout,
+};
+use artifice_engine::{Application, Engine};
+use artifice_logging::{error, info};
+
+/// Draws a spinning triangle without a single gl:: call
+struct DeviceDemo {
+    device: Option<SharedRenderDevice>,
+    pipeline: Option<PipelineHandle>,
+    vertices: Option<BufferHandle>,
+    rotation: f32,
+}
+
+impl Application for DeviceDemo {
+    fn new() -> Self {
+        DeviceDemo { device: None, pipeline: None, vertices: None, rotation: 0.0 }
+    }
+
+    fn set_render_device(&mut self, device: SharedRenderDevice) {
+        self.device = Some(device);
+    }
+
+    fn init(&mut self) {
+        let Some(device) = &self.device else {
+            error!("This backend has no render device");
+            return;
+        };
+        let mut device = device.lock().unwrap();
+        info!("Drawing with {} {} on {}", device.info().api, device.info().version, device.info().renderer);
+
+        // Position then colour, five floats per vertex
+        let vertices: [f32; 15] = [
+            0.0, 0.5, 1.0, 0.5, 0.2, //
+            -0.5, -0.5, 0.2, 1.0, 0.5, //
+            0.5, -0.5, 0.2, 0.5, 1.0,
+        ];
+        let bytes: Vec<u8> = vertices.iter().flat_map(|value| value.to_ne_bytes()).collect();
+        let buffer = BufferDesc { kind: BufferKind::Vertex, usage: BufferUsage::Static, size: bytes.len() };
+        match device.create_buffer(buffer, Some(&bytes)) {
+            Ok(buffer) => self.vertices = Some(buffer),
+            Err(e) => error!("{}", e),
+        }
+
+        let shader = ShaderSource::Glsl {
+            vertex: "#version 330 core
+                layout (location = 0) in vec2 a_position;
+                layout (location = 1) in vec3 a_color;
+                uniform float u_rotation;
+                out vec3 v_color;
+
+                void main() {
+                    mat2 rotation = mat2(cos(u_rotation), sin(u_rotation), -sin(u_rotation), cos(u_rotation));
+                    v_color = a_color;
+                    gl_Position = vec4(rotation * a_position, 0.0, 1.0);
+                }"
+            .to_string(),
+            fragment: "#version 330 core
+                in vec3 v_color;
+                out vec4 frag_color;
+
+                void main() {
+                    frag_color = vec4(v_color, 0.9);
+                }"
+            .to_string(),
+        };
+        let layout = VertexLayout::new(5 * 4).attribute(0, VertexFormat::Float2, 0).attribute(1, VertexFormat::Float3, 2 * 4);
+        match device.create_pipeline(PipelineDesc::new(shader, layout).blend(BlendMode::Alpha)) {
+            Ok(pipeline) => self.pipeline = Some(pipeline),
+            Err(e) => error!("{}", e),
+        }
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        self.rotation = (self.rotation + delta_time) % std::f32::consts::TAU;
+    }
+
+    fn render(&mut self) {
+        let (Some(device), Some(pipeline), Some(vertices)) = (&self.device, self.pipeline, self.vertices) else { return };
+        let mut device = device.lock().unwrap();
+        let mut frame = device.begin_frame();
+        frame.clear([0.1, 0.1, 0.12, 1.0]);
+        frame.set_pipeline(pipeline);
+        frame.set_vertex_buffer(vertices);
+        frame.set_uniform("u_rotation", self.rotation.into());
+        if let Err(e) = frame.draw(0, 3) {
+            error!("{}", e);
+        }
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(device) = &self.device {
+            let mut device = device.lock().unwrap();
+            if let Some(pipeline) = self.pipeline.take() {
+                device.destroy_pipeline(pipeline);
+            }
+            if let Some(buffer) = self.vertices.take() {
+                device.destroy_buffer(buffer);
+            }
+        }
+    }
+
+    fn get_name(&self) -> &str {
+        "Render Device Demo"
+    }
+}
+
+fn main() {
+    let _log_guard = artifice_logging::init_from_env().expect("Failed to initialize logger");
+
+    let mut engine = match Engine::try_new(DeviceDemo::new()) {
+        Ok(engine) => engine,
+        Err(e) => {
+            error!("Cannot start: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = engine.run() {
+        error!("{}", e);
+    }
+}
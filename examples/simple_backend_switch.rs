@@ -220,7 +220,7 @@ impl Application for SimpleBackendSwitchDemo {
 }
 
 fn main() {
-    artifice_logging::init_from_env().expect("Failed to initialize logger");
+    let _log_guard = artifice_logging::init_from_env().expect("Failed to initialize logger");
     info!("Starting Simple Backend Switch Demo");
 
     let app = SimpleBackendSwitchDemo::new();
@@ -255,7 +255,9 @@ fn main() {
         }
     }
 
-    engine.run();
+    if let Err(e) = engine.run() {
+        error!("{}", e);
+    }
 
     info!("Simple Backend Switch Demo completed");
 }
\ No newline at end of file
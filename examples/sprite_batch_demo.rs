@@ -0,0 +1,184 @@
+extern crate artifice_engine;
+extern crate artifice_logging;
+extern crate gl;
+extern crate glam;
+
+use artifice_engine::events::{Event, KeyAction, KeyCode};
+use artifice_engine::renderer::{Texture2D, TextureFilter};
+use artifice_engine::renderer2d::{OrthographicCamera, Renderer2D};
+use artifice_engine::{Application, Engine, Layer};
+use artifice_logging::{error, info};
+use glam::Vec2;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 600;
+
+/// Everything is drawn by `SpriteLayer`
+struct SpriteDemo;
+
+impl Application for SpriteDemo {
+    fn new() -> Self {
+        SpriteDemo
+    }
+
+    fn get_name(&self) -> &str {
+        "Sprite Batch Demo"
+    }
+}
+
+struct Bouncer {
+    position: Vec2,
+    velocity: Vec2,
+    color: [f32; 4],
+}
+
+struct SpriteLayer {
+    renderer: Option<Renderer2D>,
+    checker: Option<Texture2D>,
+    camera: OrthographicCamera,
+    bouncers: Vec<Bouncer>,
+    time: f32,
+    stats_timer: f32,
+}
+
+impl SpriteLayer {
+    fn new() -> Self {
+        // Spread the boxes over the window with a cheap deterministic pattern
+        let bouncers = (0..2000)
+            .map(|index| {
+                let seed = index as f32;
+                Bouncer {
+                    position: Vec2::new((seed * 37.0) % WIDTH as f32, (seed * 91.0) % HEIGHT as f32)
+                        - Vec2::new(WIDTH as f32, HEIGHT as f32) / 2.0,
+                    velocity: Vec2::new((seed * 0.7).sin(), (seed * 1.3).cos()) * 120.0,
+                    color: [0.5 + 0.5 * (seed * 0.1).sin(), 0.5 + 0.5 * (seed * 0.2).cos(), 0.8, 0.8],
+                }
+            })
+            .collect();
+        SpriteLayer {
+            renderer: None,
+            checker: None,
+            camera: OrthographicCamera::new(WIDTH as f32, HEIGHT as f32),
+            bouncers,
+            time: 0.0,
+            stats_timer: 0.0,
+        }
+    }
+}
+
+impl Layer for SpriteLayer {
+    fn attach(&mut self) {
+        match Renderer2D::new() {
+            Ok(renderer) => self.renderer = Some(renderer),
+            Err(e) => error!("{}", e),
+        }
+
+        let rgba: Vec<u8> = (0..8 * 8)
+            .flat_map(|pixel| if (pixel % 8 + pixel / 8) % 2 == 0 { [255, 255, 255, 255] } else { [40, 40, 40, 255] })
+            .collect();
+        match Texture2D::from_rgba(8, 8, &rgba) {
+            Ok(texture) => {
+                texture.set_filter(TextureFilter::Nearest);
+                self.checker = Some(texture);
+            }
+            Err(e) => error!("{}", e),
+        }
+    }
+
+    fn detach(&mut self) {
+        self.checker = None;
+        self.renderer = None;
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        self.time += delta_time;
+        let half = self.camera.size() / 2.0;
+        for bouncer in &mut self.bouncers {
+            bouncer.position += bouncer.velocity * delta_time;
+            if bouncer.position.x.abs() > half.x {
+                bouncer.velocity.x = -bouncer.velocity.x;
+                bouncer.position.x = bouncer.position.x.clamp(-half.x, half.x);
+            }
+            if bouncer.position.y.abs() > half.y {
+                bouncer.velocity.y = -bouncer.velocity.y;
+                bouncer.position.y = bouncer.position.y.clamp(-half.y, half.y);
+            }
+        }
+
+        self.stats_timer += delta_time;
+        if self.stats_timer > 2.0 {
+            self.stats_timer = 0.0;
+            if let Some(renderer) = &self.renderer {
+                let stats = renderer.stats();
+                info!("Drew {} quads in {} draw calls", stats.quads, stats.draw_calls);
+            }
+        }
+    }
+
+    fn render(&mut self) {
+        // Layers render before the application, so the first layer clears
+        unsafe {
+            gl::ClearColor(0.1, 0.1, 0.12, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+
+        let Some(renderer) = self.renderer.as_mut() else { return };
+        renderer.begin(&self.camera);
+
+        let half = self.camera.size() / 2.0;
+        for x in (-(half.x as i32)..half.x as i32).step_by(50) {
+            renderer.draw_line(Vec2::new(x as f32, -half.y), Vec2::new(x as f32, half.y), 1.0, [0.3, 0.3, 0.35, 1.0]);
+        }
+        for y in (-(half.y as i32)..half.y as i32).step_by(50) {
+            renderer.draw_line(Vec2::new(-half.x, y as f32), Vec2::new(half.x, y as f32), 1.0, [0.3, 0.3, 0.35, 1.0]);
+        }
+
+        for bouncer in &self.bouncers {
+            renderer.draw_rect(bouncer.position, Vec2::splat(6.0), bouncer.color);
+        }
+        if let Some(checker) = &self.checker {
+            renderer.draw_sprite(Vec2::ZERO, Vec2::splat(160.0), checker, [1.0; 4]);
+        }
+        renderer.draw_rotated_rect(Vec2::new(200.0, 0.0), Vec2::splat(80.0), self.time, [1.0, 0.5, 0.2, 1.0]);
+
+        renderer.end();
+    }
+
+    fn event(&mut self, event: &mut Event) {
+        if let Some(resize) = event.as_window_resize_event() {
+            self.camera.resize(resize.width as f32, resize.height as f32);
+        } else if let Some(key_event) = event.as_key_event() {
+            if key_event.action != KeyAction::Press {
+                return;
+            }
+            match key_event.key {
+                KeyCode::Equal => self.camera.set_zoom(self.camera.zoom() * 1.25),
+                KeyCode::Minus => self.camera.set_zoom(self.camera.zoom() / 1.25),
+                _ => return,
+            }
+            event.mark_handled();
+        }
+    }
+
+    fn get_name(&self) -> &str {
+        "Sprites"
+    }
+}
+
+fn main() {
+    let _log_guard = artifice_logging::init_from_env().expect("Failed to initialize logger");
+    info!("Press + and - to zoom");
+
+    let mut engine = match Engine::builder(SpriteDemo::new()).window_size(WIDTH, HEIGHT).title("Sprite Batch Demo").build() {
+        Ok(engine) => engine,
+        Err(e) => {
+            error!("Cannot start: {}", e);
+            return;
+        }
+    };
+    engine.push_layer(Box::new(SpriteLayer::new()));
+
+    if let Err(e) = engine.run() {
+        error!("{}", e);
+    }
+}
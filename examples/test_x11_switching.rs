@@ -355,7 +355,7 @@ impl X11SwitchTest {
 }
 
 fn main() {
-    artifice_logging::init_from_env().expect("Failed to initialize logger");
+    let _log_guard = artifice_logging::init_from_env().expect("Failed to initialize logger");
     info!("=== Starting X11 Switch Test ===");
 
     let app = X11SwitchTest::new();
@@ -380,7 +380,9 @@ fn main() {
     info!("Starting engine - will auto-switch to X11 after 2 seconds");
     info!("Or press X to manually trigger switch");
     
-    engine.run();
+    if let Err(e) = engine.run() {
+        error!("{}", e);
+    }
     
     info!("=== X11 Switch Test Completed ===");
 }
\ No newline at end of file
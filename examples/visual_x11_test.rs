@@ -346,7 +346,7 @@ impl VisualX11Test {
 }
 
 fn main() {
-    artifice_logging::init_from_env().expect("Failed to initialize logger");
+    let _log_guard = artifice_logging::init_from_env().expect("Failed to initialize logger");
     
     info!("🎨 === VISUAL X11 BACKEND TEST ===");
     info!("This test provides clear visual feedback for backend switching:");
@@ -376,7 +376,9 @@ fn main() {
     let mut engine = Engine::with_config(app, "glfw", metrics_config, hot_reload_config);
     
     info!("🚀 Starting visual test engine...");
-    engine.run();
+    if let Err(e) = engine.run() {
+        error!("{}", e);
+    }
     
     info!("🏁 Visual X11 test completed");
 }
\ No newline at end of file
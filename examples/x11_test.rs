@@ -15,7 +15,7 @@ use artifice_logging::{info, error, warn};
 #[cfg(feature = "x11")]
 fn main() {
     // Initialize logging
-    artifice_logging::init_from_env().expect("Failed to initialize logger");
+    let _log_guard = artifice_logging::init_from_env().expect("Failed to initialize logger");
     info!("Starting X11 Test");
 
     // Check if we're running under X11
@@ -0,0 +1,410 @@
+//! Loading textures, shaders and other files the application needs at runtime
+//!
+//! `AssetServer::load` hands out a typed `Handle` at once and reads and
+//! decodes the file on the job system. Once it is done an `AssetEvent`
+//! arrives through the event system, and `AssetServer::get` returns the
+//! asset. The same file loaded twice shares one asset, which is freed when
+//! its last handle is dropped.
+
+use crate::error::panic_message;
+use crate::events::{Event, EventSender};
+use crate::io::ImageBuffer;
+use crate::jobs;
+use artifice_logging::{debug, info, warn};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+/// Something the asset server can load from a file
+pub trait Asset: Sized + Send + Sync + 'static {
+    /// Decode the asset from `bytes`, the contents of the file at `path`
+    ///
+    /// Runs on a job thread, so it must not touch windows or OpenGL.
+    fn from_bytes(bytes: Vec<u8>, path: &Path) -> Result<Self, String>;
+}
+
+/// Pixels of an image file, ready to upload to the GPU
+///
+/// Decoded from PNG into 8-bit RGBA, whatever the file's colour type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Texture {
+    image: ImageBuffer,
+}
+
+impl Texture {
+    pub fn image(&self) -> &ImageBuffer {
+        &self.image
+    }
+
+    pub fn width(&self) -> u32 {
+        self.image.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.image.height()
+    }
+}
+
+impl Asset for Texture {
+    fn from_bytes(bytes: Vec<u8>, _path: &Path) -> Result<Self, String> {
+        ImageBuffer::from_png(&bytes).map(|image| Texture { image })
+    }
+}
+
+/// Pipeline stage a shader runs in, told by its file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShaderStage {
+    /// `.vert` or `.vs`
+    Vertex,
+    /// `.frag` or `.fs`
+    Fragment,
+    /// `.geom` or `.gs`
+    Geometry,
+    /// `.comp` or `.cs`
+    Compute,
+}
+
+/// Source of a GLSL shader, compiled by the renderer on the engine's thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shader {
+    stage: ShaderStage,
+    source: String,
+}
+
+impl Shader {
+    pub fn stage(&self) -> ShaderStage {
+        self.stage
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl Asset for Shader {
+    fn from_bytes(bytes: Vec<u8>, path: &Path) -> Result<Self, String> {
+        let stage = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("vert" | "vs") => ShaderStage::Vertex,
+            Some("frag" | "fs") => ShaderStage::Fragment,
+            Some("geom" | "gs") => ShaderStage::Geometry,
+            Some("comp" | "cs") => ShaderStage::Compute,
+            _ => return Err("Cannot tell the shader stage from the file extension".to_string()),
+        };
+        let source = String::from_utf8(bytes).map_err(|_| "Shader source is not UTF-8".to_string())?;
+        Ok(Shader { stage, source })
+    }
+}
+
+/// Identifies an asset for as long as the asset server keeps it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AssetId(u64);
+
+/// How far loading an asset has come
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadState {
+    Loading,
+    Loaded,
+    /// Reading or decoding the file failed, for the reason given
+    Failed(String),
+    /// The server does not know the asset, e.g. because all its handles were dropped
+    Unknown,
+}
+
+/// Sent through the event system when an asset finished loading, successfully or not
+///
+/// Handle it with `EventDispatcher::on::<AssetEvent>` or from the custom
+/// events reaching `Application::event`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetEvent {
+    pub id: AssetId,
+    /// Path of the file, relative to the asset root
+    pub path: PathBuf,
+    /// Either `Loaded` or `Failed`
+    pub state: LoadState,
+}
+
+/// Keeps an asset alive; the asset server frees it once every clone is dropped
+pub struct Handle<T> {
+    token: Arc<HandleToken>,
+    _asset: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    fn new(token: Arc<HandleToken>) -> Self {
+        Self { token, _asset: PhantomData }
+    }
+
+    pub fn id(&self) -> AssetId {
+        self.token.id
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.token.clone())
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle<{}>({})", std::any::type_name::<T>(), self.id().0)
+    }
+}
+
+/// Shared by all handles of one asset; dropping the last one queues the asset to be freed
+struct HandleToken {
+    id: AssetId,
+    released: Arc<Mutex<Vec<AssetId>>>,
+}
+
+impl Drop for HandleToken {
+    fn drop(&mut self) {
+        if let Ok(mut released) = self.released.lock() {
+            released.push(self.id);
+        }
+    }
+}
+
+struct AssetEntry {
+    path: PathBuf,
+    type_id: TypeId,
+    state: LoadState,
+    value: Option<Arc<dyn Any + Send + Sync>>,
+    token: Weak<HandleToken>,
+}
+
+#[derive(Default)]
+struct Assets {
+    entries: HashMap<AssetId, AssetEntry>,
+    by_path: HashMap<(TypeId, PathBuf), AssetId>,
+    next_id: u64,
+}
+
+/// Loads assets in the background and hands out handles to them
+///
+/// Clones share the same assets, so loader threads and layers can each
+/// hold one. The engine makes one for `EngineBuilder::asset_root`, see
+/// `Engine::assets`.
+#[derive(Clone)]
+pub struct AssetServer {
+    root: PathBuf,
+    assets: Arc<Mutex<Assets>>,
+    /// Assets whose last handle was dropped, freed by `collect_unused`
+    released: Arc<Mutex<Vec<AssetId>>>,
+    events: EventSender,
+}
+
+impl AssetServer {
+    /// Load files from under `root` and report them through `events`
+    pub fn new(root: impl Into<PathBuf>, events: EventSender) -> Self {
+        Self {
+            root: root.into(),
+            assets: Arc::new(Mutex::new(Assets::default())),
+            released: Arc::new(Mutex::new(Vec::new())),
+            events,
+        }
+    }
+
+    /// Directory that paths given to `load` are relative to
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Start loading the file at `path`, or share the asset if it is loaded or loading already
+    pub fn load<T: Asset>(&self, path: impl AsRef<Path>) -> Handle<T> {
+        let path = path.as_ref().to_path_buf();
+        let key = (TypeId::of::<T>(), path.clone());
+        let mut assets = self.lock();
+        if let Some(id) = assets.by_path.get(&key).copied() {
+            if let Some(token) = assets.entries.get(&id).and_then(|entry| entry.token.upgrade()) {
+                return Handle::new(token);
+            }
+            // Released but not collected yet; load it afresh
+            assets.entries.remove(&id);
+        }
+
+        let id = AssetId(assets.next_id);
+        assets.next_id += 1;
+        let token = Arc::new(HandleToken { id, released: self.released.clone() });
+        assets.entries.insert(
+            id,
+            AssetEntry {
+                path: path.clone(),
+                type_id: TypeId::of::<T>(),
+                state: LoadState::Loading,
+                value: None,
+                token: Arc::downgrade(&token),
+            },
+        );
+        assets.by_path.insert(key, id);
+        drop(assets);
+
+        debug!("Loading {} as {}", path.display(), std::any::type_name::<T>());
+        let server = self.clone();
+        jobs::spawn(move || server.finish_load::<T>(id, path));
+        Handle::new(token)
+    }
+
+    /// The asset, once it has loaded
+    pub fn get<T: Asset>(&self, handle: &Handle<T>) -> Option<Arc<T>> {
+        let value = self.lock().entries.get(&handle.id())?.value.clone()?;
+        value.downcast::<T>().ok()
+    }
+
+    pub fn load_state<T>(&self, handle: &Handle<T>) -> LoadState {
+        self.lock().entries.get(&handle.id()).map_or(LoadState::Unknown, |entry| entry.state.clone())
+    }
+
+    /// Free the assets whose handles have all been dropped, returning how many
+    ///
+    /// The engine calls this once per frame.
+    pub fn collect_unused(&self) -> usize {
+        let released = std::mem::take(&mut *self.released.lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+        if released.is_empty() {
+            return 0;
+        }
+        let mut assets = self.lock();
+        let mut freed = 0;
+        for id in released {
+            // Gone already if `load` found it released and loaded the file afresh
+            if let Some(entry) = assets.entries.remove(&id) {
+                debug!("Freeing unused asset {}", entry.path.display());
+                assets.by_path.remove(&(entry.type_id, entry.path));
+                freed += 1;
+            }
+        }
+        freed
+    }
+
+    /// Read and decode the asset on a job thread, then report it
+    fn finish_load<T: Asset>(&self, id: AssetId, path: PathBuf) {
+        let full_path = self.root.join(&path);
+        let loaded = fs::read(&full_path)
+            .map_err(|e| format!("Failed to read {}: {}", full_path.display(), e))
+            .and_then(|bytes| {
+                // A decoder that panics fails the load instead of leaving it loading forever
+                panic::catch_unwind(AssertUnwindSafe(|| T::from_bytes(bytes, &full_path)))
+                    .unwrap_or_else(|payload| Err(format!("decoder panicked: {}", panic_message(payload.as_ref()))))
+                    .map_err(|e| format!("{}: {}", full_path.display(), e))
+            });
+        let state = match loaded {
+            Ok(asset) => {
+                info!("Loaded asset {}", path.display());
+                self.store(id, LoadState::Loaded, Some(Arc::new(asset)))
+            }
+            Err(e) => {
+                warn!("Cannot load asset: {}", e);
+                self.store(id, LoadState::Failed(e), None)
+            }
+        };
+        if let Some(state) = state {
+            let _ = self.events.send(Event::custom(AssetEvent { id, path, state }));
+        }
+    }
+
+    /// Record the outcome of a load, unless the asset was freed meanwhile
+    fn store(&self, id: AssetId, state: LoadState, value: Option<Arc<dyn Any + Send + Sync>>) -> Option<LoadState> {
+        let mut assets = self.lock();
+        let entry = assets.entries.get_mut(&id)?;
+        entry.state = state.clone();
+        entry.value = value;
+        Some(state)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Assets> {
+        self.assets.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl fmt::Debug for AssetServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetServer").field("root", &self.root).field("assets", &self.lock().entries.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Asset whose decoder panics, as a buggy user impl would
+    struct Cursed;
+
+    impl Asset for Cursed {
+        fn from_bytes(bytes: Vec<u8>, _path: &Path) -> Result<Self, String> {
+            panic!("cannot decode {} bytes", bytes.len())
+        }
+    }
+
+    #[test]
+    fn test_load_share_and_free_assets() {
+        let root = std::env::temp_dir().join(format!("artifice-assets-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let image = ImageBuffer::new(2, 1, vec![255, 0, 0, 255, 0, 0, 255, 255]).unwrap();
+        fs::write(root.join("red.png"), image.to_png()).unwrap();
+        fs::write(root.join("flat.frag"), "void main() {}").unwrap();
+
+        let (events, posted) = EventSender::channel();
+        let server = AssetServer::new(&root, events);
+        let texture: Handle<Texture> = server.load("red.png");
+        let shader: Handle<Shader> = server.load("flat.frag");
+        let missing: Handle<Shader> = server.load("missing.vert");
+        assert_eq!(server.load::<Texture>("red.png"), texture);
+
+        let mut finished = HashMap::new();
+        for _ in 0..3 {
+            let event = posted.recv_timeout(Duration::from_secs(5)).unwrap();
+            let asset = event.as_custom::<AssetEvent>().unwrap();
+            finished.insert(asset.id, asset.state.clone());
+        }
+        assert_eq!(finished[&texture.id()], LoadState::Loaded);
+        assert!(matches!(&finished[&missing.id()], LoadState::Failed(e) if e.starts_with("Failed to read")));
+        assert_eq!(server.get(&texture).unwrap().image(), &image);
+        assert_eq!(server.get(&shader).unwrap().stage(), ShaderStage::Fragment);
+        assert_eq!(server.get(&missing), None);
+
+        // Freed once the last handle is gone
+        let copy = shader.clone();
+        drop(shader);
+        assert_eq!(server.collect_unused(), 0);
+        drop(copy);
+        assert_eq!(server.collect_unused(), 1);
+        assert_eq!(server.load_state(&texture), LoadState::Loaded);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_panicking_decoder_fails_the_load() {
+        let root = std::env::temp_dir().join(format!("artifice-assets-panic-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("cursed.bin"), [0; 3]).unwrap();
+
+        let (events, posted) = EventSender::channel();
+        let server = AssetServer::new(&root, events);
+        let cursed: Handle<Cursed> = server.load("cursed.bin");
+        let event = posted.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(event.as_custom::<AssetEvent>().unwrap().state, LoadState::Failed(_)));
+        assert!(matches!(server.load_state(&cursed), LoadState::Failed(e) if e.ends_with("decoder panicked: cannot decode 3 bytes")));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
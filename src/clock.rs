@@ -0,0 +1,175 @@
+use artifice_logging::warn;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Most `fixed_update` calls in one frame; a frame slower than this many steps drops the rest
+const MAX_FIXED_STEPS: u32 = 8;
+
+#[derive(Debug)]
+struct ClockState {
+    paused: bool,
+    time_scale: f32,
+    /// Frames to simulate while paused
+    pending_steps: u32,
+}
+
+/// Decides how much simulated time passes each frame
+///
+/// Debug tools pause the simulation, slow it down or step through it one
+/// frame at a time; rendering and events carry on regardless. Clones share
+/// the same clock, so a layer handed one by `Engine::time_control` can
+/// pause the engine it runs in.
+#[derive(Debug, Clone)]
+pub struct TimeControl {
+    state: Arc<Mutex<ClockState>>,
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        Self { state: Arc::new(Mutex::new(ClockState { paused: false, time_scale: 1.0, pending_steps: 0 })) }
+    }
+}
+
+impl TimeControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state().paused
+    }
+
+    /// Pausing or resuming drops steps that were not taken yet
+    pub fn set_paused(&self, paused: bool) {
+        let mut state = self.state();
+        state.paused = paused;
+        state.pending_steps = 0;
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.state().time_scale
+    }
+
+    /// Ignores scales that are negative or not finite
+    pub fn set_time_scale(&self, time_scale: f32) {
+        if time_scale.is_finite() && time_scale >= 0.0 {
+            self.state().time_scale = time_scale;
+        } else {
+            warn!("Ignoring time scale {}, it must be finite and not negative", time_scale);
+        }
+    }
+
+    /// Simulate one more frame while paused
+    pub fn step_frame(&self) {
+        let mut state = self.state();
+        if state.paused {
+            state.pending_steps += 1;
+        }
+    }
+
+    /// Simulated time for a frame that took `delta_time` seconds, or `None` if nothing should update
+    pub(crate) fn advance(&self, delta_time: f32) -> Option<f32> {
+        let mut state = self.state();
+        if state.paused {
+            state.pending_steps = state.pending_steps.checked_sub(1)?;
+        }
+        Some(delta_time * state.time_scale)
+    }
+
+    fn state(&self) -> MutexGuard<'_, ClockState> {
+        // The state stays consistent even if a holder panicked
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Splits simulated time into steps of equal length for `fixed_update`
+///
+/// Time left over from one frame carries into the next, so the steps keep
+/// the rate on average whatever the frame rate is.
+#[derive(Debug, Clone)]
+pub(crate) struct FixedStep {
+    step: Duration,
+    accumulated: Duration,
+}
+
+impl FixedStep {
+    pub fn new(step: Duration) -> Self {
+        Self { step: step.max(Duration::from_micros(1)), accumulated: Duration::ZERO }
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// How many steps `delta_time` seconds of simulated time add up to
+    pub fn advance(&mut self, delta_time: f32) -> u32 {
+        // A huge time scale can make the frame longer than a `Duration` holds
+        let elapsed = Duration::try_from_secs_f32(delta_time.max(0.0)).unwrap_or(Duration::MAX);
+        self.accumulated = self.accumulated.saturating_add(elapsed);
+        let steps = self.accumulated.as_nanos() / self.step.as_nanos();
+        if steps > MAX_FIXED_STEPS as u128 {
+            warn!("Simulation fell {} fixed steps behind, skipping all but {}", steps, MAX_FIXED_STEPS);
+            self.accumulated = Duration::ZERO;
+            return MAX_FIXED_STEPS;
+        }
+        let steps = steps as u32;
+        self.accumulated -= self.step * steps;
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_steps_carry_remainder() {
+        let mut fixed = FixedStep::new(Duration::from_millis(10));
+        assert_eq!(fixed.advance(0.004), 0);
+        assert_eq!(fixed.advance(0.004), 0);
+        // 12ms gathered, 2ms carried over
+        assert_eq!(fixed.advance(0.004), 1);
+        assert_eq!(fixed.advance(0.0195), 2);
+        // A long stall is cut short instead of catching up step by step
+        assert_eq!(fixed.advance(1.0), MAX_FIXED_STEPS);
+        assert_eq!(fixed.advance(0.005), 0);
+    }
+
+    #[test]
+    fn test_fixed_steps_survive_huge_time_scales() {
+        let clock = TimeControl::new();
+        clock.set_time_scale(f32::MAX);
+        let mut fixed = FixedStep::new(Duration::from_millis(10));
+        assert_eq!(fixed.advance(clock.advance(0.016).unwrap()), MAX_FIXED_STEPS);
+        assert_eq!(fixed.advance(f32::MAX), MAX_FIXED_STEPS);
+        assert_eq!(fixed.advance(f32::INFINITY), MAX_FIXED_STEPS);
+        // Nothing is left over to keep the steps maxed out
+        assert_eq!(fixed.advance(0.005), 0);
+    }
+
+    #[test]
+    fn test_pause_scale_and_step() {
+        let clock = TimeControl::new();
+        assert_eq!(clock.advance(0.02), Some(0.02));
+        clock.set_time_scale(0.25);
+        clock.set_time_scale(-1.0);
+        clock.set_time_scale(f32::NAN);
+        assert_eq!(clock.advance(0.02), Some(0.005));
+
+        // Steps only count while paused, one frame each
+        clock.step_frame();
+        let debugger = clock.clone();
+        debugger.set_paused(true);
+        assert_eq!(clock.advance(0.02), None);
+        debugger.step_frame();
+        debugger.step_frame();
+        assert_eq!(clock.advance(0.02), Some(0.005));
+        assert_eq!(clock.advance(0.02), Some(0.005));
+        assert_eq!(clock.advance(0.02), None);
+
+        debugger.step_frame();
+        debugger.set_paused(false);
+        debugger.set_paused(true);
+        assert_eq!(clock.advance(0.02), None);
+    }
+}
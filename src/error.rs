@@ -0,0 +1,59 @@
+use std::any::Any;
+
+/// Why the engine could not be created or stopped running
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineError {
+    /// No backend could open the main window; each attempt with the backend's error, in the order tried
+    WindowCreation { attempts: Vec<(String, String)> },
+    /// The main window renders with OpenGL but its functions were never loaded
+    OpenGlUnavailable { backend: String },
+    /// The application or one of its layers panicked while the engine ran
+    Panicked { message: String },
+}
+
+impl std::fmt::Display for EngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineError::WindowCreation { attempts } if attempts.is_empty() => {
+                write!(f, "No window backend is registered to open the main window")
+            }
+            EngineError::WindowCreation { attempts } => {
+                let errors: Vec<&str> = attempts.iter().map(|(_, error)| error.as_str()).collect();
+                write!(f, "No window backend could open the main window: {}", errors.join("; "))
+            }
+            EngineError::OpenGlUnavailable { backend } => {
+                write!(f, "OpenGL functions are not loaded for the main window of backend '{}'", backend)
+            }
+            EngineError::Panicked { message } => write!(f, "Application panicked: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+/// The message a panic was raised with
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_message_and_display() {
+        let payload = std::panic::catch_unwind(|| panic!("no display {}", 0)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "no display 0");
+        let payload = std::panic::catch_unwind(|| std::panic::panic_any(7)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "unknown panic");
+
+        let error = EngineError::WindowCreation {
+            attempts: vec![("x11".to_string(), "No display".to_string()), ("glfw".to_string(), "No GPU".to_string())],
+        };
+        assert_eq!(error.to_string(), "No window backend could open the main window: No display; No GPU");
+    }
+}
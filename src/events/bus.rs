@@ -0,0 +1,180 @@
+use crate::events::core::{Event, EventData, EventQueue, EventQueueConfig, OverflowPolicy};
+use artifice_logging::{debug, warn};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Cloneable handle for publishing messages of type `T` onto an `EventBus`
+///
+/// Messages travel through the bus's lock-free `EventQueue` as custom events
+/// and reach the subscribers when the main thread drains the bus.
+pub struct BusSender<T> {
+    queue: Arc<EventQueue>,
+    _message: PhantomData<fn(T)>,
+}
+
+impl<T: Any + Send + Sync> BusSender<T> {
+    /// Queue a message; gives it back if the channel is full
+    pub fn publish(&self, message: T) -> Result<(), T> {
+        self.queue.try_push(Event::custom(message)).map_err(|event| {
+            let message = match event.data {
+                EventData::Custom(custom) => custom.data.downcast::<T>().ok(),
+                _ => None,
+            };
+            message
+                .and_then(|message| Arc::try_unwrap(message).ok())
+                .expect("a rejected message comes back as the unshared T it was built from")
+        })
+    }
+}
+
+impl<T> Clone for BusSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            queue: Arc::clone(&self.queue),
+            _message: PhantomData,
+        }
+    }
+}
+
+type Subscriber = Box<dyn FnMut(&(dyn Any + Send + Sync)) + Send>;
+
+/// Queue and subscribers of one message type
+struct BusChannel {
+    name: &'static str,
+    queue: Arc<EventQueue>,
+    subscribers: Vec<Subscriber>,
+}
+
+/// Typed publish/subscribe channels from background threads to the main thread
+///
+/// Asset loaders, network code and the like publish through a `BusSender`;
+/// the engine drains the bus once per frame, handing every message to the
+/// subscribers of its type in publishing order. Messages of different types
+/// are delivered channel by channel.
+///
+/// ```ignore
+/// let sender = engine.get_event_bus_mut().channel::<TextureLoaded>();
+/// std::thread::spawn(move || { let _ = sender.publish(TextureLoaded { id: 7 }); });
+/// engine.get_event_bus_mut().subscribe(|loaded: &TextureLoaded| info!("texture {} ready", loaded.id));
+/// ```
+#[derive(Default)]
+pub struct EventBus {
+    channels: HashMap<TypeId, BusChannel>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sender for messages of type `T`, creating the channel with default settings if needed
+    pub fn channel<T: Any + Send + Sync>(&mut self) -> BusSender<T> {
+        let config = EventQueueConfig {
+            capacity: 256,
+            overflow_policy: OverflowPolicy::Grow { max_capacity: 65536 },
+        };
+        self.channel_with_config(config)
+    }
+
+    /// Sender for messages of type `T`; `config` only applies if the channel is new
+    pub fn channel_with_config<T: Any + Send + Sync>(&mut self, config: EventQueueConfig) -> BusSender<T> {
+        let channel = self.channels.entry(TypeId::of::<T>()).or_insert_with(|| {
+            debug!("Creating event bus channel for {}", std::any::type_name::<T>());
+            BusChannel {
+                name: std::any::type_name::<T>(),
+                queue: Arc::new(EventQueue::from_config(config)),
+                subscribers: Vec::new(),
+            }
+        });
+
+        BusSender {
+            queue: Arc::clone(&channel.queue),
+            _message: PhantomData,
+        }
+    }
+
+    /// Call `subscriber` on the main thread for every message of type `T`
+    pub fn subscribe<T: Any + Send + Sync>(&mut self, mut subscriber: impl FnMut(&T) + Send + 'static) {
+        // Creates the channel, so messages published before the first sender exists are kept
+        self.channel::<T>();
+        let channel = self.channels.get_mut(&TypeId::of::<T>()).expect("channel was just created");
+        channel.subscribers.push(Box::new(move |message| {
+            if let Some(message) = message.downcast_ref::<T>() {
+                subscriber(message);
+            }
+        }));
+    }
+
+    /// Deliver every queued message to its subscribers, returning how many were drained
+    pub fn drain(&mut self) -> usize {
+        let mut drained = 0;
+        for channel in self.channels.values_mut() {
+            while let Some(event) = channel.queue.try_pop() {
+                drained += 1;
+                let Some(custom) = event.as_custom_event() else { continue };
+                if channel.subscribers.is_empty() {
+                    warn!("Dropping {} message without subscribers", channel.name);
+                }
+                for subscriber in &mut channel.subscribers {
+                    subscriber(&*custom.data);
+                }
+            }
+        }
+        drained
+    }
+
+    /// Number of message types with a channel
+    pub fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("channels", &self.channels.values().map(|channel| channel.name).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_bus_delivers_messages_from_threads_in_order() {
+        #[derive(Debug, PartialEq)]
+        struct AssetLoaded(u32);
+        struct PacketReceived;
+
+        let mut bus = EventBus::new();
+        let loaded = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&loaded);
+        bus.subscribe(move |asset: &AssetLoaded| seen.lock().unwrap().push(asset.0));
+
+        let sender = bus.channel::<AssetLoaded>();
+        let packets = bus.channel::<PacketReceived>();
+        std::thread::spawn(move || {
+            for id in 0..100 {
+                sender.publish(AssetLoaded(id)).unwrap();
+            }
+            assert!(packets.publish(PacketReceived).is_ok());
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(bus.channel_count(), 2);
+        assert_eq!(bus.drain(), 101);
+        assert_eq!(*loaded.lock().unwrap(), (0..100).collect::<Vec<_>>());
+        assert_eq!(bus.drain(), 0);
+
+        // A full channel hands the message back
+        let mut tiny = EventBus::new();
+        let sender = tiny.channel_with_config::<AssetLoaded>(EventQueueConfig { capacity: 2, overflow_policy: OverflowPolicy::Drop });
+        sender.publish(AssetLoaded(1)).unwrap();
+        assert_eq!(sender.publish(AssetLoaded(2)), Err(AssetLoaded(2)));
+    }
+}
@@ -1,26 +1,142 @@
+use crate::io::WindowId;
 use artifice_logging::{debug, trace};
-use std::any::Any;
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use serde::{Deserialize, Serialize};
+use std::any::{Any, TypeId};
+use std::cell::UnsafeCell;
+use std::collections::{HashMap, VecDeque};
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
 
 /// Represents different categories of events
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EventType {
     Window,
     Keyboard,
     Mouse,
+    /// Pinches, swipes and long presses, which are not tied to a mouse
+    Gesture,
     Gamepad,
     Application,
     Custom,
 }
 
+/// Set of event kinds as a bit mask, for cheap filtering
+///
+/// Each event belongs to exactly one fine-grained category; the coarse ones
+/// such as `MOUSE` or `INPUT` are unions of those.
+///
+/// ```ignore
+/// let ui = EventCategory::MOUSE | EventCategory::WINDOW;
+/// assert!(ui.intersects(event.category()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct EventCategory(u32);
+
+impl EventCategory {
+    pub const NONE: Self = Self(0);
+    pub const KEYBOARD: Self = Self(1 << 0);
+    pub const MOUSE_MOVE: Self = Self(1 << 1);
+    pub const MOUSE_BUTTON: Self = Self(1 << 2);
+    pub const MOUSE_SCROLL: Self = Self(1 << 3);
+    /// Synthesized double clicks and drags
+    pub const MOUSE_GESTURE: Self = Self(1 << 4);
+    pub const GAMEPAD_BUTTON: Self = Self(1 << 5);
+    pub const GAMEPAD_AXIS: Self = Self(1 << 6);
+    pub const GAMEPAD_CONNECTION: Self = Self(1 << 7);
+    pub const WINDOW: Self = Self(1 << 8);
+    pub const APPLICATION: Self = Self(1 << 9);
+    pub const CUSTOM: Self = Self(1 << 10);
+    /// Pinches, swipes and long presses, whether from a trackpad, a touch screen or a held button
+    pub const GESTURE: Self = Self(1 << 11);
+
+    pub const MOUSE: Self = Self(Self::MOUSE_MOVE.0 | Self::MOUSE_BUTTON.0 | Self::MOUSE_SCROLL.0 | Self::MOUSE_GESTURE.0);
+    pub const GAMEPAD: Self = Self(Self::GAMEPAD_BUTTON.0 | Self::GAMEPAD_AXIS.0 | Self::GAMEPAD_CONNECTION.0);
+    pub const INPUT: Self = Self(Self::KEYBOARD.0 | Self::MOUSE.0 | Self::GESTURE.0 | Self::GAMEPAD.0);
+    pub const ALL: Self = Self(Self::INPUT.0 | Self::WINDOW.0 | Self::APPLICATION.0 | Self::CUSTOM.0);
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every category in `other` is in `self`
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether `self` and `other` share any category
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl std::ops::BitOr for EventCategory {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for EventCategory {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl std::ops::BitAnd for EventCategory {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl std::ops::Not for EventCategory {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0 & Self::ALL.0)
+    }
+}
+
+impl From<EventType> for EventCategory {
+    fn from(event_type: EventType) -> Self {
+        match event_type {
+            EventType::Window => Self::WINDOW,
+            EventType::Keyboard => Self::KEYBOARD,
+            EventType::Mouse => Self::MOUSE,
+            EventType::Gesture => Self::GESTURE,
+            EventType::Gamepad => Self::GAMEPAD,
+            EventType::Application => Self::APPLICATION,
+            EventType::Custom => Self::CUSTOM,
+        }
+    }
+}
+
+impl FromIterator<EventType> for EventCategory {
+    fn from_iter<I: IntoIterator<Item = EventType>>(types: I) -> Self {
+        types.into_iter().fold(Self::NONE, |mask, event_type| mask | event_type.into())
+    }
+}
+
 /// More efficient event representation using enums instead of trait objects
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EventData {
     Key(KeyEvent),
     MouseMove(MouseMoveEvent),
     MouseButton(MouseButtonEvent),
     MouseScroll(MouseScrollEvent),
+    MouseRawMotion(MouseRawMotionEvent),
+    MouseDoubleClick(MouseDoubleClickEvent),
+    MouseDrag(MouseDragEvent),
+    Pinch(PinchEvent),
+    Swipe(SwipeEvent),
+    LongPress(LongPressEvent),
     GamepadButton(GamepadButtonEvent),
     GamepadAxis(GamepadAxisEvent),
     GamepadConnection(GamepadConnectionEvent),
@@ -35,9 +151,13 @@ impl EventData {
     pub fn event_type(&self) -> EventType {
         match self {
             EventData::Key(_) => EventType::Keyboard,
-            EventData::MouseMove(_) | EventData::MouseButton(_) | EventData::MouseScroll(_) => {
-                EventType::Mouse
-            }
+            EventData::MouseMove(_)
+            | EventData::MouseButton(_)
+            | EventData::MouseScroll(_)
+            | EventData::MouseRawMotion(_)
+            | EventData::MouseDoubleClick(_)
+            | EventData::MouseDrag(_) => EventType::Mouse,
+            EventData::Pinch(_) | EventData::Swipe(_) | EventData::LongPress(_) => EventType::Gesture,
             EventData::GamepadButton(_)
             | EventData::GamepadAxis(_)
             | EventData::GamepadConnection(_) => EventType::Gamepad,
@@ -48,15 +168,89 @@ impl EventData {
             EventData::Custom(_) => EventType::Custom,
         }
     }
+
+    /// The fine-grained category of the event
+    pub fn category(&self) -> EventCategory {
+        match self {
+            EventData::Key(_) => EventCategory::KEYBOARD,
+            EventData::MouseMove(_) | EventData::MouseRawMotion(_) => EventCategory::MOUSE_MOVE,
+            EventData::MouseButton(_) => EventCategory::MOUSE_BUTTON,
+            EventData::MouseScroll(_) => EventCategory::MOUSE_SCROLL,
+            EventData::MouseDoubleClick(_) | EventData::MouseDrag(_) => EventCategory::MOUSE_GESTURE,
+            EventData::Pinch(_) | EventData::Swipe(_) | EventData::LongPress(_) => EventCategory::GESTURE,
+            EventData::GamepadButton(_) => EventCategory::GAMEPAD_BUTTON,
+            EventData::GamepadAxis(_) => EventCategory::GAMEPAD_AXIS,
+            EventData::GamepadConnection(_) => EventCategory::GAMEPAD_CONNECTION,
+            EventData::WindowResize(_) | EventData::WindowMove(_) | EventData::WindowClose(_) => {
+                EventCategory::WINDOW
+            }
+            EventData::ApplicationTick(_) => EventCategory::APPLICATION,
+            EventData::Custom(_) => EventCategory::CUSTOM,
+        }
+    }
+
+    /// The input device the event came from; synthesized and non-input events have none
+    pub fn source(&self) -> Option<EventSource> {
+        match self {
+            EventData::Key(_) => Some(EventSource::Keyboard),
+            EventData::MouseMove(_)
+            | EventData::MouseButton(_)
+            | EventData::MouseScroll(_)
+            | EventData::MouseRawMotion(_) => Some(EventSource::Mouse),
+            EventData::GamepadButton(event) => Some(EventSource::Gamepad(event.gamepad_id)),
+            EventData::GamepadAxis(event) => Some(EventSource::Gamepad(event.gamepad_id)),
+            EventData::GamepadConnection(event) => Some(EventSource::Gamepad(event.gamepad_id)),
+            _ => None,
+        }
+    }
+
+    /// How important the event is to keep when the event queue overflows
+    pub fn priority(&self) -> EventPriority {
+        match self {
+            // Superseded by the next update of the same kind
+            EventData::MouseMove(_)
+            | EventData::GamepadAxis(_)
+            | EventData::WindowMove(_)
+            | EventData::ApplicationTick(_) => EventPriority::Low,
+            EventData::MouseDrag(drag) if drag.phase == DragPhase::Update => EventPriority::Low,
+            EventData::WindowClose(_) | EventData::GamepadConnection(_) => EventPriority::High,
+            _ => EventPriority::Normal,
+        }
+    }
+}
+
+/// Input device whose events are numbered and kept in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum EventSource {
+    Keyboard,
+    Mouse,
+    Gamepad(u32),
+}
+
+/// Importance of an event, used by `OverflowPolicy::DropByPriority`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EventPriority {
+    Low,
+    Normal,
+    High,
 }
 
 /// The main Event struct that contains the actual event data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
     pub event_type: EventType,
     pub handled: bool,
     pub data: EventData,
     pub timestamp: u64,
+    /// Position in the stream of events from the same input device, set by `EventSequencer`
+    #[serde(default)]
+    pub sequence: Option<u64>,
+    /// Local player whose device produced the event, set by `PlayerInputRouter`
+    #[serde(default)]
+    pub player: Option<u32>,
+    /// Window the event came from; `None` for events not tied to a window
+    #[serde(default)]
+    pub window: Option<WindowId>,
 }
 
 impl Event {
@@ -64,6 +258,9 @@ impl Event {
         Event {
             event_type: data.event_type(),
             handled: false,
+            sequence: None,
+            player: None,
+            window: None,
             data,
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -76,6 +273,10 @@ impl Event {
         self.handled
     }
 
+    pub fn category(&self) -> EventCategory {
+        self.data.category()
+    }
+
     pub fn mark_handled(&mut self) {
         self.handled = true;
     }
@@ -109,6 +310,48 @@ impl Event {
         }
     }
 
+    pub fn as_mouse_raw_motion_event(&self) -> Option<&MouseRawMotionEvent> {
+        match &self.data {
+            EventData::MouseRawMotion(event) => Some(event),
+            _ => None,
+        }
+    }
+
+    pub fn as_mouse_double_click_event(&self) -> Option<&MouseDoubleClickEvent> {
+        match &self.data {
+            EventData::MouseDoubleClick(event) => Some(event),
+            _ => None,
+        }
+    }
+
+    pub fn as_mouse_drag_event(&self) -> Option<&MouseDragEvent> {
+        match &self.data {
+            EventData::MouseDrag(event) => Some(event),
+            _ => None,
+        }
+    }
+
+    pub fn as_pinch_event(&self) -> Option<&PinchEvent> {
+        match &self.data {
+            EventData::Pinch(event) => Some(event),
+            _ => None,
+        }
+    }
+
+    pub fn as_swipe_event(&self) -> Option<&SwipeEvent> {
+        match &self.data {
+            EventData::Swipe(event) => Some(event),
+            _ => None,
+        }
+    }
+
+    pub fn as_long_press_event(&self) -> Option<&LongPressEvent> {
+        match &self.data {
+            EventData::LongPress(event) => Some(event),
+            _ => None,
+        }
+    }
+
     pub fn as_window_resize_event(&self) -> Option<&WindowResizeEvent> {
         match &self.data {
             EventData::WindowResize(event) => Some(event),
@@ -165,87 +408,340 @@ impl Event {
         }
     }
 
+    /// Create a custom event carrying `data`, dispatched to `EventDispatcher::on::<T>` handlers
+    pub fn custom<T: Any + Send + Sync>(data: T) -> Self {
+        Event::new(EventData::Custom(CustomEventData::typed(data)))
+    }
+
+    /// The payload of a custom event, if it is a `T`
+    pub fn as_custom<T: Any>(&self) -> Option<&T> {
+        self.as_custom_event().and_then(|custom| custom.get_data::<T>())
+    }
+
     // Legacy method for backward compatibility
 }
 
-/// Lock-free ring buffer for high-performance event queuing
+/// What `EventQueue::try_push` does when the ring buffer is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Reject the new event
+    #[default]
+    Drop,
+    /// Queue extra events in an overflow buffer, up to `max_capacity` events in total
+    Grow { max_capacity: usize },
+    /// Discard the oldest queued event to make room for the new one
+    OverwriteOldest,
+    /// Reject low-priority events and queue the others in an overflow buffer of
+    /// `reserve` events, evicting its lowest-priority event once that is full
+    DropByPriority { reserve: usize },
+}
+
+/// Event queue settings chosen when the engine is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventQueueConfig {
+    pub capacity: usize,
+    pub overflow_policy: OverflowPolicy,
+}
+
+impl Default for EventQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            overflow_policy: OverflowPolicy::Drop,
+        }
+    }
+}
+
+/// How often each overflow policy action fired
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OverflowStats {
+    /// Events rejected because no room could be made
+    pub dropped: u64,
+    /// Events queued in the overflow buffer
+    pub spilled: u64,
+    /// Queued events discarded by `OverwriteOldest`
+    pub overwritten: u64,
+    /// Queued events evicted for higher-priority ones by `DropByPriority`
+    pub evicted: u64,
+}
+
+#[derive(Default)]
+struct OverflowCounters {
+    dropped: AtomicU64,
+    spilled: AtomicU64,
+    overwritten: AtomicU64,
+    evicted: AtomicU64,
+}
+
+/// Storage for one queued event
+struct EventSlot {
+    /// Twice the push position the slot expects next while empty, plus one once
+    /// its event is written; doubling keeps a one-slot ring from mistaking a
+    /// written slot for the next empty lap
+    sequence: AtomicUsize,
+    event: UnsafeCell<MaybeUninit<Event>>,
+}
+
+/// Lock-free multi-producer, multi-consumer ring buffer for event queuing
+///
+/// Any number of threads (window, audio, network) may push and pop at once:
+/// each claims a position with a CAS on `enqueue_pos` or `dequeue_pos` and
+/// owns that slot until it bumps the slot's sequence number. Events are stored
+/// inline in preallocated slots, so queuing does not allocate. Only a full
+/// ring takes the overflow path, which applies the queue's `OverflowPolicy`
+/// under a lock.
 pub struct EventQueue {
-    events: Vec<std::sync::atomic::AtomicPtr<Event>>,
-    capacity: usize,
-    write_pos: AtomicUsize,
-    read_pos: AtomicUsize,
+    slots: Box<[EventSlot]>,
+    enqueue_pos: AtomicUsize,
+    dequeue_pos: AtomicUsize,
+    policy: OverflowPolicy,
+    /// Events that did not fit in the ring, all newer than those in it
+    overflow: Mutex<VecDeque<Event>>,
+    overflow_len: AtomicUsize,
+    counters: OverflowCounters,
 }
 
 impl EventQueue {
     pub fn new(capacity: usize) -> Self {
-        let mut events = Vec::with_capacity(capacity);
-        for _ in 0..capacity {
-            events.push(std::sync::atomic::AtomicPtr::new(std::ptr::null_mut()));
-        }
+        Self::with_policy(capacity, OverflowPolicy::Drop)
+    }
+
+    pub fn with_policy(capacity: usize, policy: OverflowPolicy) -> Self {
+        // One slot of `capacity` stays unused, so a queue holds `capacity - 1` events
+        let slots = (0..capacity.saturating_sub(1).max(1))
+            .map(|position| EventSlot {
+                sequence: AtomicUsize::new(position * 2),
+                event: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
 
         EventQueue {
-            events,
-            capacity,
-            write_pos: AtomicUsize::new(0),
-            read_pos: AtomicUsize::new(0),
+            slots,
+            enqueue_pos: AtomicUsize::new(0),
+            dequeue_pos: AtomicUsize::new(0),
+            policy,
+            overflow: Mutex::new(VecDeque::new()),
+            overflow_len: AtomicUsize::new(0),
+            counters: OverflowCounters::default(),
         }
     }
 
-    pub fn try_push(&self, event: Event) -> Result<(), Event> {
-        let write_pos = self.write_pos.load(Ordering::Acquire);
-        let next_write = (write_pos + 1) % self.capacity;
+    pub fn from_config(config: EventQueueConfig) -> Self {
+        Self::with_policy(config.capacity, config.overflow_policy)
+    }
 
-        if next_write == self.read_pos.load(Ordering::Acquire) {
-            return Err(event); // Queue full
+    /// Queue an event, applying the overflow policy if the ring is full
+    ///
+    /// Returns the event back only if the policy could not make room for it.
+    pub fn try_push(&self, event: Event) -> Result<(), Event> {
+        // Once events have spilled over, later ones must queue behind them
+        if self.overflow_len.load(Ordering::Acquire) > 0 {
+            return self.push_overflow(event);
         }
 
-        let event_ptr = Box::into_raw(Box::new(event));
+        match self.push_ring(event) {
+            Ok(()) => Ok(()),
+            Err(event) => self.push_overflow(event),
+        }
+    }
 
-        match self.events[write_pos].compare_exchange_weak(
-            std::ptr::null_mut(),
-            event_ptr,
-            Ordering::Release,
-            Ordering::Relaxed,
-        ) {
-            Ok(_) => {
-                self.write_pos.store(next_write, Ordering::Release);
-                Ok(())
+    fn push_ring(&self, event: Event) -> Result<(), Event> {
+        let mut position = self.enqueue_pos.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[position % self.slots.len()];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let empty = position.wrapping_mul(2);
+
+            if sequence == empty {
+                match self.enqueue_pos.compare_exchange_weak(
+                    position,
+                    position.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: winning the position gives this thread sole access to the
+                        // slot until the sequence store below publishes the event
+                        unsafe { (*slot.event.get()).write(event) };
+                        slot.sequence.store(empty.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => position = current,
+                }
+            } else if (sequence.wrapping_sub(empty) as isize) < 0 {
+                // The slot still holds the event from one lap ago
+                return Err(event);
+            } else {
+                // Another producer took this position
+                position = self.enqueue_pos.load(Ordering::Relaxed);
             }
-            Err(_) => {
-                // Cleanup and retry
-                let event = unsafe { Box::from_raw(event_ptr) };
-                Err(*event)
+        }
+    }
+
+    fn push_overflow(&self, event: Event) -> Result<(), Event> {
+        let mut overflow = self.overflow.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // Move spilled events back into the ring as the consumer frees it up
+        while let Some(spilled) = overflow.pop_front() {
+            if let Err(spilled) = self.push_ring(spilled) {
+                overflow.push_front(spilled);
+                break;
             }
         }
+        let result = match overflow.is_empty() {
+            true => self.push_ring(event),
+            false => Err(event),
+        };
+        let result = match result {
+            Ok(()) => Ok(()),
+            Err(event) => self.apply_policy(&mut overflow, event),
+        };
+
+        self.overflow_len.store(overflow.len(), Ordering::Release);
+        result
     }
 
-    pub fn try_pop(&self) -> Option<Event> {
-        let read_pos = self.read_pos.load(Ordering::Acquire);
+    fn apply_policy(&self, overflow: &mut VecDeque<Event>, event: Event) -> Result<(), Event> {
+        let result = match self.policy {
+            OverflowPolicy::Drop => Err(event),
+            OverflowPolicy::Grow { max_capacity } => {
+                let limit = max_capacity.saturating_sub(self.slots.len());
+                self.spill(overflow, event, limit)
+            }
+            OverflowPolicy::OverwriteOldest => self.overwrite_oldest(overflow, event),
+            OverflowPolicy::DropByPriority { .. } if event.data.priority() == EventPriority::Low => Err(event),
+            OverflowPolicy::DropByPriority { reserve } => {
+                if overflow.len() >= reserve {
+                    let priority = event.data.priority();
+                    let victim = overflow
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, queued)| queued.data.priority() < priority)
+                        .min_by_key(|(_, queued)| queued.data.priority())
+                        .map(|(index, _)| index);
+                    if let Some(index) = victim {
+                        overflow.remove(index);
+                        self.counters.evicted.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                self.spill(overflow, event, reserve)
+            }
+        };
 
-        if read_pos == self.write_pos.load(Ordering::Acquire) {
-            return None; // Queue empty
+        if result.is_err() {
+            self.counters.dropped.fetch_add(1, Ordering::Relaxed);
         }
+        result
+    }
 
-        let event_ptr = self.events[read_pos].swap(std::ptr::null_mut(), Ordering::Acquire);
+    fn spill(&self, overflow: &mut VecDeque<Event>, event: Event, limit: usize) -> Result<(), Event> {
+        if overflow.len() >= limit {
+            return Err(event);
+        }
+        overflow.push_back(event);
+        self.counters.spilled.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn overwrite_oldest(&self, overflow: &mut VecDeque<Event>, mut event: Event) -> Result<(), Event> {
+        // Bounded, as the consumer may be popping at the same time
+        for _ in 0..4 {
+            let discarded = match self.pop_ring() {
+                Some(discarded) => Some(discarded),
+                None => overflow.pop_front(),
+            };
+            if discarded.is_some() {
+                self.counters.overwritten.fetch_add(1, Ordering::Relaxed);
+            }
+            match self.push_ring(event) {
+                Ok(()) => return Ok(()),
+                Err(rejected) => event = rejected,
+            }
+        }
+        Err(event)
+    }
 
-        if event_ptr.is_null() {
+    pub fn try_pop(&self) -> Option<Event> {
+        if let Some(event) = self.pop_ring() {
+            return Some(event);
+        }
+        if self.overflow_len.load(Ordering::Acquire) == 0 {
             return None;
         }
 
-        let next_read = (read_pos + 1) % self.capacity;
-        self.read_pos.store(next_read, Ordering::Release);
-
-        Some(*unsafe { Box::from_raw(event_ptr) })
+        let mut overflow = self.overflow.lock().unwrap_or_else(PoisonError::into_inner);
+        // A producer may have refilled the ring while we waited for the lock
+        let event = self.pop_ring().or_else(|| overflow.pop_front());
+        self.overflow_len.store(overflow.len(), Ordering::Release);
+        event
+    }
+
+    fn pop_ring(&self) -> Option<Event> {
+        let mut position = self.dequeue_pos.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[position % self.slots.len()];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let filled = position.wrapping_mul(2).wrapping_add(1);
+
+            if sequence == filled {
+                match self.dequeue_pos.compare_exchange_weak(
+                    position,
+                    position.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: the sequence shows the event was written, and winning the
+                        // position gives this thread sole access to it
+                        let event = unsafe { (*slot.event.get()).assume_init_read() };
+                        // Hand the slot to the producer one lap ahead
+                        let next_lap = position.wrapping_add(self.slots.len());
+                        slot.sequence.store(next_lap.wrapping_mul(2), Ordering::Release);
+                        return Some(event);
+                    }
+                    Err(current) => position = current,
+                }
+            } else if (sequence.wrapping_sub(filled) as isize) < 0 {
+                // Nothing written at this position yet
+                return None;
+            } else {
+                // Another consumer took this position
+                position = self.dequeue_pos.load(Ordering::Relaxed);
+            }
+        }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.read_pos.load(Ordering::Acquire) == self.write_pos.load(Ordering::Acquire)
+        self.dequeue_pos.load(Ordering::Acquire) == self.enqueue_pos.load(Ordering::Acquire)
+            && self.overflow_len.load(Ordering::Acquire) == 0
     }
 
+    /// Whether the ring buffer is full; the overflow policy may still accept events
     pub fn is_full(&self) -> bool {
-        let write_pos = self.write_pos.load(Ordering::Acquire);
-        let next_write = (write_pos + 1) % self.capacity;
-        next_write == self.read_pos.load(Ordering::Acquire)
+        let queued = self
+            .enqueue_pos
+            .load(Ordering::Acquire)
+            .wrapping_sub(self.dequeue_pos.load(Ordering::Acquire));
+        queued >= self.slots.len()
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Events waiting in the overflow buffer
+    pub fn overflow_len(&self) -> usize {
+        self.overflow_len.load(Ordering::Acquire)
+    }
+
+    /// Counts of the overflow actions taken so far
+    pub fn overflow_stats(&self) -> OverflowStats {
+        OverflowStats {
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            spilled: self.counters.spilled.load(Ordering::Relaxed),
+            overwritten: self.counters.overwritten.load(Ordering::Relaxed),
+            evicted: self.counters.evicted.load(Ordering::Relaxed),
+        }
     }
 }
 
@@ -261,15 +757,194 @@ impl Drop for EventQueue {
     }
 }
 
+/// Numbers input events per device as producers queue them
+///
+/// Share one sequencer between every producer pushing into an `EventQueue`;
+/// the `InputManager` uses the numbers to restore each device's order and
+/// to notice events that never arrived.
+#[derive(Debug, Default)]
+pub struct EventSequencer {
+    next: Mutex<HashMap<EventSource, u64>>,
+}
+
+impl EventSequencer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give `event` the next sequence number of its device, if it has one
+    pub fn stamp(&self, event: &mut Event) {
+        if let Some(source) = event.data.source() {
+            let mut next = self.next.lock().unwrap_or_else(PoisonError::into_inner);
+            let sequence = next.entry(source).or_insert(0);
+            event.sequence = Some(*sequence);
+            *sequence += 1;
+        }
+    }
+}
+
+/// Cloneable handle for posting events into the engine from any thread
+///
+/// Posted events go through the engine's filters and dispatch like window
+/// events, starting with the next frame.
+#[derive(Debug, Clone)]
+pub struct EventSender {
+    sender: mpsc::Sender<Event>,
+}
+
+impl EventSender {
+    /// Create a sender and the receiving end the engine drains each frame
+    pub fn channel() -> (Self, mpsc::Receiver<Event>) {
+        let (sender, receiver) = mpsc::channel();
+        (Self { sender }, receiver)
+    }
+
+    /// Queue an event; gives it back if the engine has shut down
+    pub fn send(&self, event: Event) -> Result<(), Event> {
+        self.sender.send(event).map_err(|mpsc::SendError(event)| event)
+    }
+
+    /// Queue an event built from `data`
+    pub fn post(&self, data: EventData) -> Result<(), Event> {
+        self.send(Event::new(data))
+    }
+}
+
+/// What a sticky event describes; a newer event with the same key replaces the older one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum StickyKey {
+    WindowSize(Option<WindowId>),
+    Gamepad(u32),
+}
+
+/// Latest events describing current state, replayed to late subscribers
+///
+/// Keeps the last resize of every open window and the connection event of
+/// every connected gamepad, so a handler or layer added mid-run can learn the
+/// window size without waiting for the next resize.
+#[derive(Debug, Default)]
+pub struct StickyEvents {
+    latest: HashMap<StickyKey, Event>,
+}
+
+impl StickyEvents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember `event` if it carries state; other events are ignored
+    pub fn record(&mut self, event: &Event) {
+        let key = match &event.data {
+            EventData::WindowResize(_) => StickyKey::WindowSize(event.window),
+            EventData::WindowClose(_) => {
+                self.latest.remove(&StickyKey::WindowSize(event.window));
+                return;
+            }
+            EventData::GamepadConnection(connection) if !connection.connected => {
+                self.latest.remove(&StickyKey::Gamepad(connection.gamepad_id));
+                return;
+            }
+            EventData::GamepadConnection(connection) => StickyKey::Gamepad(connection.gamepad_id),
+            _ => return,
+        };
+
+        let mut event = event.clone();
+        event.handled = false;
+        self.latest.insert(key, event);
+    }
+
+    /// Copies of the remembered events, oldest first
+    pub fn events(&self) -> Vec<Event> {
+        let mut events: Vec<Event> = self.latest.values().cloned().collect();
+        events.sort_by_key(|event| event.timestamp);
+        events
+    }
+
+    /// Copies of the remembered events of one type, oldest first
+    pub fn events_of_type(&self, event_type: EventType) -> Vec<Event> {
+        let mut events = self.events();
+        events.retain(|event| event.event_type == event_type);
+        events
+    }
+
+    pub fn len(&self) -> usize {
+        self.latest.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.latest.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.latest.clear();
+    }
+}
+
 /// Event handler trait for handling events
 pub trait EventHandler: Send + std::fmt::Debug {
     fn handle_event(&mut self, event: &mut Event);
 }
 
+/// Identifies a registered event handler so it can be removed again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// Unregisters an event handler when dropped
+///
+/// Keep it in the layer that owns the listener; the handler is removed the
+/// next time the dispatcher dispatches or is told to `unregister`.
+#[must_use = "dropping a Subscription unregisters its handler"]
+#[derive(Debug)]
+pub struct Subscription {
+    id: SubscriptionId,
+    released: Arc<Mutex<Vec<SubscriptionId>>>,
+    active: bool,
+}
+
+impl Subscription {
+    pub fn id(&self) -> SubscriptionId {
+        self.id
+    }
+
+    /// Keep the handler registered after this guard is dropped
+    pub fn forget(mut self) -> SubscriptionId {
+        self.active = false;
+        self.id
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if self.active {
+            self.released.lock().unwrap_or_else(PoisonError::into_inner).push(self.id);
+        }
+    }
+}
+
+/// Handler for custom events carrying one payload type, registered with `EventDispatcher::on`
+struct TypedHandler(Box<dyn FnMut(&(dyn Any + Send + Sync)) + Send>);
+
+impl std::fmt::Debug for TypedHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TypedHandler")
+            .field("callback", &"<closure>")
+            .finish()
+    }
+}
+
 /// Event Dispatcher - now using the more efficient event system
+///
+/// Custom events are first passed to the handlers registered with `on` for
+/// their payload type, then to the `EventType::Custom` handlers. Newly
+/// registered handlers are first sent the sticky events of their type.
 #[derive(Debug)]
 pub struct EventDispatcher {
-    handlers: HashMap<EventType, Vec<Box<dyn EventHandler>>>,
+    handlers: HashMap<EventType, Vec<(SubscriptionId, Box<dyn EventHandler>)>>,
+    typed_handlers: HashMap<TypeId, Vec<(SubscriptionId, TypedHandler)>>,
+    next_id: u64,
+    sticky: StickyEvents,
+    /// Handlers whose `Subscription` was dropped, removed before the next dispatch
+    released: Arc<Mutex<Vec<SubscriptionId>>>,
 }
 
 impl EventDispatcher {
@@ -277,21 +952,94 @@ impl EventDispatcher {
         debug!("Creating event dispatcher");
         EventDispatcher {
             handlers: HashMap::new(),
+            typed_handlers: HashMap::new(),
+            next_id: 0,
+            sticky: StickyEvents::new(),
+            released: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
-    pub fn register_handler(&mut self, event_type: EventType, handler: Box<dyn EventHandler>) {
+    fn next_subscription_id(&mut self) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    pub fn register_handler(&mut self, event_type: EventType, mut handler: Box<dyn EventHandler>) -> SubscriptionId {
         debug!("Registering handler for event type: {:?}", event_type);
+        for mut event in self.sticky.events_of_type(event_type) {
+            handler.handle_event(&mut event);
+        }
+
+        let id = self.next_subscription_id();
         self.handlers
             .entry(event_type)
             .or_insert_with(Vec::new)
-            .push(handler);
+            .push((id, handler));
+        id
+    }
+
+    /// Remove a handler; returns false if it was not registered
+    pub fn unregister(&mut self, id: SubscriptionId) -> bool {
+        self.remove_released();
+        self.remove(id)
+    }
+
+    fn remove(&mut self, id: SubscriptionId) -> bool {
+        for handlers in self.handlers.values_mut() {
+            if let Some(index) = handlers.iter().position(|(handler_id, _)| *handler_id == id) {
+                debug!("Unregistering event handler {:?}", id);
+                handlers.remove(index);
+                return true;
+            }
+        }
+        for handlers in self.typed_handlers.values_mut() {
+            if let Some(index) = handlers.iter().position(|(handler_id, _)| *handler_id == id) {
+                debug!("Unregistering typed event handler {:?}", id);
+                handlers.remove(index);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn remove_released(&mut self) {
+        let released = std::mem::take(&mut *self.released.lock().unwrap_or_else(PoisonError::into_inner));
+        for id in released {
+            self.remove(id);
+        }
+    }
+
+    /// Tie a registered handler to a guard that unregisters it when dropped
+    pub fn subscription(&self, id: SubscriptionId) -> Subscription {
+        Subscription {
+            id,
+            released: Arc::clone(&self.released),
+            active: true,
+        }
+    }
+
+    /// Number of handlers registered for `event_type`
+    pub fn handler_count(&self, event_type: EventType) -> usize {
+        let released = self.released.lock().unwrap_or_else(PoisonError::into_inner);
+        self.handlers.get(&event_type).map_or(0, |handlers| {
+            handlers.iter().filter(|(id, _)| !released.contains(id)).count()
+        })
     }
 
     pub fn dispatch_event(&mut self, event: &mut Event) {
         trace!("Dispatching event: {:?}", event.event_type);
+        self.remove_released();
+        self.sticky.record(event);
+        if let EventData::Custom(custom) = &event.data {
+            if let Some(handlers) = self.typed_handlers.get_mut(&custom.data_type_id()) {
+                for (_, handler) in handlers.iter_mut() {
+                    (handler.0)(&*custom.data);
+                }
+            }
+        }
         if let Some(handlers) = self.handlers.get_mut(&event.event_type) {
-            for handler in handlers.iter_mut() {
+            for (_, handler) in handlers.iter_mut() {
                 handler.handle_event(event);
                 if event.is_handled() {
                     break;
@@ -301,12 +1049,49 @@ impl EventDispatcher {
     }
 
     /// Register a closure as an event handler
-    pub fn add_event_listener<F>(&mut self, event_type: EventType, listener: F)
+    pub fn add_event_listener<F>(&mut self, event_type: EventType, listener: F) -> SubscriptionId
     where
         F: FnMut(&mut Event) + Send + 'static,
     {
         let handler = ClosureEventHandler::new(listener);
-        self.register_handler(event_type, Box::new(handler));
+        self.register_handler(event_type, Box::new(handler))
+    }
+
+    /// Register a closure for custom events carrying a `T`
+    ///
+    /// ```ignore
+    /// dispatcher.on::<PlayerScoreEvent>(|score| info!("{} scored {}", score.player_id, score.score));
+    /// dispatcher.dispatch_event(&mut Event::custom(PlayerScoreEvent { player_id: 1, score: 10 }));
+    /// ```
+    pub fn on<T: Any + Send + Sync>(&mut self, mut listener: impl FnMut(&T) + Send + 'static) -> SubscriptionId {
+        debug!("Registering handler for custom event: {}", std::any::type_name::<T>());
+        let id = self.next_subscription_id();
+        let handler = TypedHandler(Box::new(move |data| {
+            if let Some(data) = data.downcast_ref::<T>() {
+                listener(data);
+            }
+        }));
+        self.typed_handlers.entry(TypeId::of::<T>()).or_default().push((id, handler));
+        id
+    }
+
+    /// Latest state-carrying events seen by `dispatch_event`
+    pub fn sticky_events(&self) -> &StickyEvents {
+        &self.sticky
+    }
+
+    /// Get the sticky events (mutable), e.g. to seed the current window size
+    pub fn sticky_events_mut(&mut self) -> &mut StickyEvents {
+        &mut self.sticky
+    }
+
+    /// Register a closure that stays registered until the returned guard is dropped
+    pub fn subscribe<F>(&mut self, event_type: EventType, listener: F) -> Subscription
+    where
+        F: FnMut(&mut Event) + Send + 'static,
+    {
+        let id = self.add_event_listener(event_type, listener);
+        self.subscription(id)
     }
 }
 
@@ -364,15 +1149,20 @@ pub trait EventFilter: Send + Sync {
 /// Filter that allows only specific event types
 pub struct EventTypeFilter {
     name: String,
-    allowed_types: Vec<EventType>,
+    allowed: EventCategory,
     priority: i32,
 }
 
 impl EventTypeFilter {
     pub fn new(name: impl Into<String>, allowed_types: Vec<EventType>) -> Self {
+        Self::from_categories(name, allowed_types.into_iter().collect())
+    }
+
+    /// Allow only events in `allowed`, e.g. `EventCategory::MOUSE_BUTTON | EventCategory::KEYBOARD`
+    pub fn from_categories(name: impl Into<String>, allowed: EventCategory) -> Self {
         Self {
             name: name.into(),
-            allowed_types,
+            allowed,
             priority: 0,
         }
     }
@@ -385,7 +1175,7 @@ impl EventTypeFilter {
 
 impl EventFilter for EventTypeFilter {
     fn should_allow(&self, event: &Event) -> bool {
-        self.allowed_types.contains(&event.event_type)
+        self.allowed.intersects(event.category())
     }
 
     fn name(&self) -> &str {
@@ -400,15 +1190,20 @@ impl EventFilter for EventTypeFilter {
 /// Filter that blocks specific event types
 pub struct EventTypeBlockFilter {
     name: String,
-    blocked_types: Vec<EventType>,
+    blocked: EventCategory,
     priority: i32,
 }
 
 impl EventTypeBlockFilter {
     pub fn new(name: impl Into<String>, blocked_types: Vec<EventType>) -> Self {
+        Self::from_categories(name, blocked_types.into_iter().collect())
+    }
+
+    /// Block every event in `blocked`, e.g. `EventCategory::MOUSE_MOVE`
+    pub fn from_categories(name: impl Into<String>, blocked: EventCategory) -> Self {
         Self {
             name: name.into(),
-            blocked_types,
+            blocked,
             priority: 0,
         }
     }
@@ -421,7 +1216,7 @@ impl EventTypeBlockFilter {
 
 impl EventFilter for EventTypeBlockFilter {
     fn should_allow(&self, event: &Event) -> bool {
-        !self.blocked_types.contains(&event.event_type)
+        !self.blocked.intersects(event.category())
     }
 
     fn name(&self) -> &str {
@@ -478,10 +1273,28 @@ where
     }
 }
 
+/// How many events one filter let through or blocked
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FilterStats {
+    pub name: String,
+    /// Events the filter allowed
+    pub allowed: u64,
+    /// Events the filter rejected; later filters never saw them
+    pub blocked: u64,
+}
+
+/// A registered filter with its counters
+struct FilterEntry {
+    filter: Box<dyn EventFilter>,
+    allowed: AtomicU64,
+    blocked: AtomicU64,
+}
+
 /// Manages event filters and applies them to events
 pub struct EventFilterManager {
-    filters: Vec<Box<dyn EventFilter>>,
+    filters: Vec<FilterEntry>,
     enabled: bool,
+    trace_rejections: bool,
 }
 
 impl EventFilterManager {
@@ -489,20 +1302,31 @@ impl EventFilterManager {
         Self {
             filters: Vec::new(),
             enabled: true,
+            trace_rejections: false,
         }
     }
 
     /// Add a filter to the manager
     pub fn add_filter(&mut self, filter: Box<dyn EventFilter>) {
         debug!("Adding event filter: {}", filter.name());
-        self.filters.push(filter);
+        self.filters.push(FilterEntry {
+            filter,
+            allowed: AtomicU64::new(0),
+            blocked: AtomicU64::new(0),
+        });
         // Sort by priority (highest first)
-        self.filters.sort_by(|a, b| b.priority().cmp(&a.priority()));
+        self.filters.sort_by(|a, b| b.filter.priority().cmp(&a.filter.priority()));
+    }
+
+    /// Add a filter while building a chain, e.g. for `Engine::push_layer_with_filters`
+    pub fn with_filter(mut self, filter: Box<dyn EventFilter>) -> Self {
+        self.add_filter(filter);
+        self
     }
 
     /// Remove a filter by name
     pub fn remove_filter(&mut self, name: &str) -> bool {
-        if let Some(pos) = self.filters.iter().position(|f| f.name() == name) {
+        if let Some(pos) = self.filters.iter().position(|f| f.filter.name() == name) {
             self.filters.remove(pos);
             debug!("Removed event filter: {}", name);
             true
@@ -531,22 +1355,72 @@ impl EventFilterManager {
         self.enabled
     }
 
+    /// Log every rejected event at debug level along with the filter that rejected it
+    pub fn set_trace_rejections(&mut self, trace_rejections: bool) {
+        self.trace_rejections = trace_rejections;
+    }
+
+    /// Check if rejections are logged
+    pub fn is_tracing_rejections(&self) -> bool {
+        self.trace_rejections
+    }
+
     /// Apply all filters to an event
     pub fn should_allow_event(&self, event: &Event) -> bool {
         if !self.enabled {
             return true;
         }
 
-        for filter in &self.filters {
-            if !filter.should_allow(event) {
-                trace!("Event blocked by filter: {}", filter.name());
+        for entry in &self.filters {
+            if !entry.filter.should_allow(event) {
+                entry.blocked.fetch_add(1, Ordering::Relaxed);
+                if self.trace_rejections {
+                    debug!("Event {:?} blocked by filter '{}'", event.data, entry.filter.name());
+                } else {
+                    trace!("Event blocked by filter: {}", entry.filter.name());
+                }
                 return false;
             }
+            entry.allowed.fetch_add(1, Ordering::Relaxed);
         }
 
         true
     }
 
+    /// Name of the filter that would reject `event`, or `None` if it would pass
+    ///
+    /// Does not touch the statistics.
+    pub fn rejecting_filter(&self, event: &Event) -> Option<&str> {
+        if !self.enabled {
+            return None;
+        }
+
+        self.filters
+            .iter()
+            .find(|entry| !entry.filter.should_allow(event))
+            .map(|entry| entry.filter.name())
+    }
+
+    /// Allowed and blocked counts of each filter, in evaluation order
+    pub fn filter_stats(&self) -> Vec<FilterStats> {
+        self.filters
+            .iter()
+            .map(|entry| FilterStats {
+                name: entry.filter.name().to_string(),
+                allowed: entry.allowed.load(Ordering::Relaxed),
+                blocked: entry.blocked.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Zero the counters of every filter
+    pub fn reset_filter_stats(&self) {
+        for entry in &self.filters {
+            entry.allowed.store(0, Ordering::Relaxed);
+            entry.blocked.store(0, Ordering::Relaxed);
+        }
+    }
+
     /// Filter a list of events, returning only allowed events
     pub fn filter_events(&self, events: Vec<Event>) -> Vec<Event> {
         if !self.enabled {
@@ -561,7 +1435,7 @@ impl EventFilterManager {
 
     /// Get the names of all registered filters
     pub fn get_filter_names(&self) -> Vec<&str> {
-        self.filters.iter().map(|f| f.name()).collect()
+        self.filters.iter().map(|f| f.filter.name()).collect()
     }
 
     /// Get the number of registered filters
@@ -577,30 +1451,30 @@ impl Default for EventFilterManager {
 }
 
 /// Window Events
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WindowResizeEvent {
     pub width: u32,
     pub height: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WindowCloseEvent;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WindowMoveEvent {
     pub x: i32,
     pub y: i32,
 }
 
 /// Keyboard Events
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum KeyAction {
     Press,
     Release,
     Repeat,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyEvent {
     pub key: KeyCode,
     pub action: KeyAction,
@@ -608,27 +1482,108 @@ pub struct KeyEvent {
 }
 
 /// Mouse Events
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MouseMoveEvent {
     pub x: f64,
     pub y: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MouseButtonEvent {
     pub button: MouseButton,
     pub action: KeyAction, // Reusing KeyAction for mouse buttons
     pub mods: KeyMod,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MouseScrollEvent {
     pub x_offset: f64,
     pub y_offset: f64,
 }
 
+/// Unaccelerated mouse movement, reported while the cursor is captured
+///
+/// Unlike `MouseMoveEvent` the deltas are not bounded by the window or
+/// scaled by the desktop's pointer acceleration, which is what camera
+/// controls want.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MouseRawMotionEvent {
+    pub delta_x: f64,
+    pub delta_y: f64,
+}
+
+/// A second press of the same button close in time and position to the first,
+/// synthesized by the input manager after the raw button event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MouseDoubleClickEvent {
+    pub button: MouseButton,
+    pub x: f64,
+    pub y: f64,
+    pub mods: KeyMod,
+}
+
+/// Stage of a click-drag gesture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DragPhase {
+    /// The cursor moved past the drag threshold with the button held
+    Start,
+    Update,
+    /// The button was released
+    End,
+}
+
+/// Cursor movement with a button held, synthesized by the input manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MouseDragEvent {
+    pub button: MouseButton,
+    pub phase: DragPhase,
+    pub x: f64,
+    pub y: f64,
+    /// Movement since the previous drag event, or since the press for `Start`
+    pub delta_x: f64,
+    pub delta_y: f64,
+}
+
+/// Zoom gesture, recognized from scrolling with Control held as trackpads report pinches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinchEvent {
+    /// Factor to multiply the current zoom by; above 1.0 zooms in
+    pub scale: f64,
+    /// Cursor position the pinch is centered on
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Dominant direction of a swipe in window coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// A quick press-move-release, recognized when the button is released
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwipeEvent {
+    pub button: MouseButton,
+    pub direction: SwipeDirection,
+    /// Distance travelled in pixels
+    pub distance: f64,
+    /// Average speed in pixels per second
+    pub velocity: f64,
+}
+
+/// A button held in place for the long-press time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LongPressEvent {
+    pub button: MouseButton,
+    pub x: f64,
+    pub y: f64,
+}
+
 /// Application Events
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApplicationTickEvent {
     pub delta_time: f32,
 }
@@ -648,10 +1603,20 @@ impl CustomEventData {
         }
     }
 
+    /// Custom event data named after the Rust type of `data`
+    pub fn typed<T: Any + Send + Sync>(data: T) -> Self {
+        Self::new(std::any::type_name::<T>(), data)
+    }
+
     pub fn get_data<T: Any>(&self) -> Option<&T> {
         self.data.downcast_ref::<T>()
     }
 
+    /// `TypeId` of the payload, used to find typed handlers
+    pub fn data_type_id(&self) -> TypeId {
+        (*self.data).type_id()
+    }
+
     pub fn is_type<T: Any>(&self) -> bool {
         self.data.is::<T>()
     }
@@ -671,7 +1636,7 @@ impl Clone for CustomEventData {
 }
 
 /// Key Codes and Modifiers
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum KeyCode {
     Unknown,
     Space,
@@ -796,7 +1761,7 @@ pub enum KeyCode {
     Menu,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct KeyMod {
     pub shift: bool,
     pub control: bool,
@@ -826,7 +1791,7 @@ impl Default for KeyMod {
 }
 
 /// Mouse Buttons
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MouseButton {
     Button1,
     Button2,
@@ -842,7 +1807,7 @@ pub enum MouseButton {
 }
 
 /// Standard gamepad buttons
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum GamepadButton {
     // Face buttons (Xbox layout names)
     A, // Bottom face button
@@ -886,7 +1851,7 @@ pub enum GamepadButton {
 }
 
 /// Gamepad analog axes
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum GamepadAxis {
     // Left stick
     LeftStickX,
@@ -910,7 +1875,7 @@ pub enum GamepadAxis {
 }
 
 /// Represents a gamepad button input event
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GamepadButtonEvent {
     pub gamepad_id: u32,
     pub button: GamepadButton,
@@ -919,7 +1884,7 @@ pub struct GamepadButtonEvent {
 }
 
 /// Represents a gamepad axis input event
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GamepadAxisEvent {
     pub gamepad_id: u32,
     pub axis: GamepadAxis,
@@ -927,11 +1892,17 @@ pub struct GamepadAxisEvent {
 }
 
 /// Represents a gamepad connection/disconnection event
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GamepadConnectionEvent {
     pub gamepad_id: u32,
     pub connected: bool,
     pub name: String,
+    /// SDL-style device GUID, if the backend can tell
+    #[serde(default)]
+    pub guid: Option<String>,
+    /// Battery charge in percent when the gamepad connected, if known
+    #[serde(default)]
+    pub battery: Option<u8>,
 }
 
 #[cfg(test)]
@@ -1097,4 +2068,327 @@ mod tests {
         let event2 = Event::new(EventData::WindowClose(WindowCloseEvent));
         assert!(event2.timestamp >= event.timestamp);
     }
+
+    #[test]
+    fn test_event_queue_overflow_policies() {
+        let key = |key| Event::new(EventData::Key(KeyEvent { key, action: KeyAction::Press, mods: KeyMod::new() }));
+        let keys = |queue: &EventQueue| {
+            std::iter::from_fn(|| queue.try_pop())
+                .map(|event| event.as_key_event().map(|key| key.key))
+                .collect::<Vec<_>>()
+        };
+
+        // Grow keeps order and stops at max_capacity
+        let queue = EventQueue::with_policy(3, OverflowPolicy::Grow { max_capacity: 4 });
+        for code in [KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D] {
+            assert!(queue.try_push(key(code)).is_ok());
+        }
+        assert!(queue.try_push(key(KeyCode::E)).is_err());
+        assert_eq!(queue.overflow_stats(), OverflowStats { dropped: 1, spilled: 2, ..Default::default() });
+        assert_eq!(keys(&queue), [Some(KeyCode::A), Some(KeyCode::B), Some(KeyCode::C), Some(KeyCode::D)]);
+        assert!(queue.is_empty());
+
+        // OverwriteOldest discards from the front
+        let queue = EventQueue::with_policy(3, OverflowPolicy::OverwriteOldest);
+        for code in [KeyCode::A, KeyCode::B, KeyCode::C] {
+            assert!(queue.try_push(key(code)).is_ok());
+        }
+        assert_eq!(queue.overflow_stats().overwritten, 1);
+        assert_eq!(keys(&queue), [Some(KeyCode::B), Some(KeyCode::C)]);
+
+        // DropByPriority rejects mouse motion and evicts it from the reserve for keys
+        let queue = EventQueue::with_policy(2, OverflowPolicy::DropByPriority { reserve: 1 });
+        let motion = || Event::new(EventData::MouseMove(MouseMoveEvent { x: 0.0, y: 0.0 }));
+        assert!(queue.try_push(key(KeyCode::A)).is_ok());
+        assert!(queue.try_push(motion()).is_err());
+        assert!(queue.try_push(key(KeyCode::B)).is_ok());
+        assert!(queue.try_push(Event::new(EventData::WindowClose(WindowCloseEvent))).is_ok());
+        assert!(queue.try_push(key(KeyCode::C)).is_err());
+        assert_eq!(queue.overflow_stats(), OverflowStats { dropped: 2, spilled: 2, overwritten: 0, evicted: 1 });
+        assert_eq!(keys(&queue), [Some(KeyCode::A), None]);
+    }
+
+    #[test]
+    fn test_dispatcher_unregister_and_subscription() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = |calls: &Arc<AtomicUsize>| {
+            let calls = Arc::clone(calls);
+            move |_: &mut Event| {
+                calls.fetch_add(1, Ordering::SeqCst);
+            }
+        };
+        let mut dispatcher = EventDispatcher::new();
+        let close = || Event::new(EventData::WindowClose(WindowCloseEvent));
+
+        let id = dispatcher.add_event_listener(EventType::Window, counter(&calls));
+        let subscription = dispatcher.subscribe(EventType::Window, counter(&calls));
+        assert_eq!(dispatcher.handler_count(EventType::Window), 2);
+        dispatcher.dispatch_event(&mut close());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        assert!(dispatcher.unregister(id));
+        assert!(!dispatcher.unregister(id));
+        drop(subscription);
+        assert_eq!(dispatcher.handler_count(EventType::Window), 0);
+        dispatcher.dispatch_event(&mut close());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let kept = dispatcher.subscribe(EventType::Window, counter(&calls)).forget();
+        dispatcher.dispatch_event(&mut close());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(dispatcher.unregister(kept));
+    }
+
+    #[test]
+    fn test_event_sender_posts_across_threads() {
+        let (sender, receiver) = EventSender::channel();
+        let worker = sender.clone();
+        std::thread::spawn(move || {
+            worker.post(EventData::Custom(CustomEventData::new("score", 42u32))).unwrap();
+        })
+        .join()
+        .unwrap();
+
+        let posted: Vec<Event> = receiver.try_iter().collect();
+        assert_eq!(posted.len(), 1);
+        assert_eq!(posted[0].event_type, EventType::Custom);
+
+        drop(receiver);
+        assert!(sender.post(EventData::WindowClose(WindowCloseEvent)).is_err());
+    }
+
+    #[test]
+    fn test_typed_custom_event_handlers() {
+        #[derive(Debug)]
+        struct PlayerScoreEvent {
+            score: i32,
+        }
+
+        let total = Arc::new(AtomicUsize::new(0));
+        let mut dispatcher = EventDispatcher::new();
+        let seen = Arc::clone(&total);
+        let id = dispatcher.on::<PlayerScoreEvent>(move |event| {
+            seen.fetch_add(event.score as usize, Ordering::SeqCst);
+        });
+
+        let mut event = Event::custom(PlayerScoreEvent { score: 5 });
+        assert_eq!(event.as_custom::<PlayerScoreEvent>().unwrap().score, 5);
+        dispatcher.dispatch_event(&mut event);
+        // Payloads of other types do not reach the handler
+        dispatcher.dispatch_event(&mut Event::custom(7u32));
+        assert_eq!(total.load(Ordering::SeqCst), 5);
+
+        assert!(dispatcher.unregister(id));
+        dispatcher.dispatch_event(&mut Event::custom(PlayerScoreEvent { score: 5 }));
+        assert_eq!(total.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn test_filter_chain_per_consumer() {
+        let ui = EventFilterManager::new()
+            .with_filter(Box::new(EventTypeFilter::new("ui_input", vec![EventType::Mouse, EventType::Window])));
+        let gameplay = EventFilterManager::new()
+            .with_filter(Box::new(EventTypeFilter::new("gameplay_input", vec![EventType::Keyboard, EventType::Gamepad])));
+
+        let click = Event::new(EventData::MouseButton(MouseButtonEvent {
+            button: MouseButton::Left,
+            action: KeyAction::Press,
+            mods: KeyMod::new(),
+        }));
+        let jump = Event::new(EventData::Key(KeyEvent { key: KeyCode::Space, action: KeyAction::Press, mods: KeyMod::new() }));
+
+        assert!(ui.should_allow_event(&click) && !ui.should_allow_event(&jump));
+        assert!(gameplay.should_allow_event(&jump) && !gameplay.should_allow_event(&click));
+        assert_eq!(gameplay.get_filter_names(), ["gameplay_input"]);
+    }
+
+    #[test]
+    fn test_filter_stats_and_rejecting_filter() {
+        let mut manager = EventFilterManager::new()
+            .with_filter(Box::new(EventTypeBlockFilter::new("no_mouse", vec![EventType::Mouse]).with_priority(10)))
+            .with_filter(Box::new(PredicateFilter::new("no_escape", |event: &Event| {
+                event.as_key_event().map_or(true, |key| key.key != KeyCode::Escape)
+            })));
+        manager.set_trace_rejections(true);
+
+        let key = |key| Event::new(EventData::Key(KeyEvent { key, action: KeyAction::Press, mods: KeyMod::new() }));
+        let mouse_move = Event::new(EventData::MouseMove(MouseMoveEvent { x: 1.0, y: 2.0 }));
+
+        assert_eq!(manager.rejecting_filter(&key(KeyCode::Escape)), Some("no_escape"));
+        assert_eq!(manager.rejecting_filter(&key(KeyCode::Space)), None);
+
+        let allowed = manager.filter_events(vec![key(KeyCode::Space), key(KeyCode::Escape), mouse_move]);
+        assert_eq!(allowed.len(), 1);
+
+        let stats = manager.filter_stats();
+        assert_eq!((stats[0].name.as_str(), stats[0].allowed, stats[0].blocked), ("no_mouse", 2, 1));
+        assert_eq!((stats[1].name.as_str(), stats[1].allowed, stats[1].blocked), ("no_escape", 1, 1));
+
+        manager.reset_filter_stats();
+        assert!(manager.filter_stats().iter().all(|s| s.allowed == 0 && s.blocked == 0));
+    }
+
+    #[test]
+    fn test_event_queue_concurrent_producer_consumer() {
+        const EVENTS: usize = 10_000;
+        let queue = Arc::new(EventQueue::new(64));
+
+        let producer = {
+            let queue = Arc::clone(&queue);
+            std::thread::spawn(move || {
+                for i in 0..EVENTS {
+                    let mut event = Event::new(EventData::MouseMove(MouseMoveEvent { x: i as f64, y: 0.0 }));
+                    while let Err(rejected) = queue.try_push(event) {
+                        event = rejected;
+                        std::thread::yield_now();
+                    }
+                }
+            })
+        };
+
+        let mut next = 0;
+        while next < EVENTS {
+            match queue.try_pop() {
+                Some(event) => {
+                    assert_eq!(event.as_mouse_move_event().unwrap().x, next as f64);
+                    next += 1;
+                }
+                None => std::thread::yield_now(),
+            }
+        }
+        producer.join().unwrap();
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_event_queue_multiple_producers_and_consumers() {
+        const PRODUCERS: usize = 4;
+        const EVENTS_PER_PRODUCER: usize = 5_000;
+        let queue = Arc::new(EventQueue::new(32));
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|producer| {
+                let queue = Arc::clone(&queue);
+                std::thread::spawn(move || {
+                    for i in 0..EVENTS_PER_PRODUCER {
+                        let data = EventData::MouseMove(MouseMoveEvent { x: producer as f64, y: i as f64 });
+                        let mut event = Event::new(data);
+                        while let Err(rejected) = queue.try_push(event) {
+                            event = rejected;
+                            std::thread::yield_now();
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let received = Arc::new(AtomicUsize::new(0));
+        let consumers: Vec<_> = (0..2)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                let received = Arc::clone(&received);
+                std::thread::spawn(move || {
+                    // Each consumer must see every producer's events in order
+                    let mut last_seen = [-1.0; PRODUCERS];
+                    while received.load(Ordering::Relaxed) < PRODUCERS * EVENTS_PER_PRODUCER {
+                        match queue.try_pop() {
+                            Some(event) => {
+                                let motion = event.as_mouse_move_event().unwrap();
+                                assert!(motion.y > last_seen[motion.x as usize]);
+                                last_seen[motion.x as usize] = motion.y;
+                                received.fetch_add(1, Ordering::Relaxed);
+                            }
+                            None => std::thread::yield_now(),
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for thread in producers.into_iter().chain(consumers) {
+            thread.join().unwrap();
+        }
+        assert_eq!(received.load(Ordering::Relaxed), PRODUCERS * EVENTS_PER_PRODUCER);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_sticky_events_replayed_to_late_handlers() {
+        let mut dispatcher = EventDispatcher::new();
+        let resize = |width, height| Event::new(EventData::WindowResize(WindowResizeEvent { width, height }));
+        let connection = |gamepad_id, connected| {
+            Event::new(EventData::GamepadConnection(GamepadConnectionEvent { gamepad_id, connected, name: "pad".into(), guid: None, battery: None }))
+        };
+
+        dispatcher.dispatch_event(&mut resize(800, 600));
+        dispatcher.dispatch_event(&mut resize(1280, 720));
+        dispatcher.dispatch_event(&mut connection(0, true));
+        dispatcher.dispatch_event(&mut connection(1, true));
+        dispatcher.dispatch_event(&mut connection(1, false));
+        assert_eq!(dispatcher.sticky_events().len(), 2);
+
+        let sizes = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&sizes);
+        dispatcher.add_event_listener(EventType::Window, move |event| {
+            if let Some(resize) = event.as_window_resize_event() {
+                seen.lock().unwrap().push((resize.width, resize.height));
+            }
+        });
+        assert_eq!(*sizes.lock().unwrap(), [(1280, 720)]);
+
+        let gamepads = dispatcher.sticky_events().events_of_type(EventType::Gamepad);
+        assert_eq!(gamepads.len(), 1);
+        assert_eq!(gamepads[0].as_gamepad_connection_event().unwrap().gamepad_id, 0);
+    }
+
+    #[test]
+    fn test_sticky_window_sizes_kept_per_window() {
+        let mut sticky = StickyEvents::new();
+        let tagged = |data, window| {
+            let mut event = Event::new(data);
+            event.window = Some(WindowId(window));
+            event
+        };
+        sticky.record(&tagged(EventData::WindowResize(WindowResizeEvent { width: 800, height: 600 }), 0));
+        sticky.record(&tagged(EventData::WindowResize(WindowResizeEvent { width: 300, height: 200 }), 1));
+        assert_eq!(sticky.len(), 2);
+
+        // Closing the tool window forgets its size only
+        sticky.record(&tagged(EventData::WindowClose(WindowCloseEvent), 1));
+        let sizes = sticky.events_of_type(EventType::Window);
+        assert_eq!(sizes.len(), 1);
+        assert_eq!(sizes[0].window, Some(WindowId::MAIN));
+    }
+
+    #[test]
+    fn test_event_category_masks() {
+        let motion = Event::new(EventData::MouseMove(MouseMoveEvent { x: 0.0, y: 0.0 }));
+        let click = Event::new(EventData::MouseButton(MouseButtonEvent {
+            button: MouseButton::Left,
+            action: KeyAction::Press,
+            mods: KeyMod::new(),
+        }));
+
+        assert_eq!(motion.category(), EventCategory::MOUSE_MOVE);
+        assert!(EventCategory::INPUT.contains(EventCategory::MOUSE));
+        assert_eq!(EventCategory::from(EventType::Mouse), EventCategory::MOUSE);
+        assert_eq!([EventType::Window, EventType::Custom].into_iter().collect::<EventCategory>(), EventCategory::WINDOW | EventCategory::CUSTOM);
+        assert!(!(!EventCategory::INPUT).intersects(EventCategory::KEYBOARD));
+
+        // Fine-grained masks pick out single kinds of mouse events
+        let buttons_only = EventTypeFilter::from_categories("buttons", EventCategory::MOUSE_BUTTON | EventCategory::KEYBOARD);
+        assert!(buttons_only.should_allow(&click) && !buttons_only.should_allow(&motion));
+        let no_motion = EventTypeBlockFilter::from_categories("no_motion", EventCategory::MOUSE_MOVE);
+        assert!(no_motion.should_allow(&click) && !no_motion.should_allow(&motion));
+        assert!(EventTypeFilter::new("mouse", vec![EventType::Mouse]).should_allow(&motion));
+
+        // Touch gestures are input of their own, not mouse events
+        let pinch = Event::new(EventData::Pinch(PinchEvent { scale: 1.1, x: 0.0, y: 0.0 }));
+        assert_eq!(pinch.event_type, EventType::Gesture);
+        assert_eq!(pinch.category(), EventCategory::GESTURE);
+        assert!(!EventCategory::MOUSE.intersects(EventCategory::GESTURE));
+        assert!(EventCategory::INPUT.contains(EventCategory::GESTURE));
+        assert!(!EventTypeFilter::new("mouse", vec![EventType::Mouse]).should_allow(&pinch));
+        assert!(!EventTypeFilter::from_categories("mouse", EventCategory::MOUSE).should_allow(&pinch));
+    }
 }
@@ -1,11 +1,19 @@
+pub mod bus;
 pub mod core;
+pub mod serialization;
+pub mod tracer;
 
 // Re-export key types for easier access
 pub use core::{
-    Event, EventData, EventType, EventDispatcher, EventFilterManager,
+    Event, EventData, EventType, EventCategory, EventDispatcher, EventFilterManager, Subscription, SubscriptionId,
     KeyAction, KeyCode, KeyEvent, KeyMod, MouseButton, 
-    MouseMoveEvent, MouseButtonEvent, MouseScrollEvent,
+    MouseMoveEvent, MouseButtonEvent, MouseScrollEvent, MouseDoubleClickEvent, MouseDragEvent, DragPhase,
+    PinchEvent, SwipeEvent, SwipeDirection, LongPressEvent,
     GamepadButton, GamepadAxis, GamepadButtonEvent, GamepadAxisEvent, GamepadConnectionEvent,
     WindowResizeEvent, WindowMoveEvent, WindowCloseEvent,
-    EventFilter, EventTypeFilter, PredicateFilter, CustomEventData
-};
\ No newline at end of file
+    EventFilter, EventTypeFilter, EventTypeBlockFilter, PredicateFilter, FilterStats, CustomEventData,
+    EventQueue, EventQueueConfig, EventSender, EventSequencer, EventSource, StickyEvents, EventPriority, OverflowPolicy, OverflowStats
+};
+pub use bus::{BusSender, EventBus};
+pub use serialization::register_custom_event;
+pub use tracer::{EventTracer, TraceEntry};
@@ -0,0 +1,149 @@
+use crate::events::core::{CustomEventData, Event};
+use serde::de::{DeserializeOwned, Error as _};
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+
+type Payload = Arc<dyn Any + Send + Sync>;
+
+/// Converts one custom event payload type to and from JSON values
+struct CustomEventCodec {
+    name: String,
+    encode: fn(&(dyn Any + Send + Sync)) -> Result<serde_json::Value, serde_json::Error>,
+    decode: fn(serde_json::Value) -> Result<Payload, serde_json::Error>,
+}
+
+#[derive(Default)]
+struct CodecRegistry {
+    by_type: HashMap<TypeId, Arc<CustomEventCodec>>,
+    by_name: HashMap<String, Arc<CustomEventCodec>>,
+}
+
+fn registry() -> &'static RwLock<CodecRegistry> {
+    static REGISTRY: OnceLock<RwLock<CodecRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Make custom events carrying a `T` serializable under the stable `name`
+///
+/// Custom payloads are arbitrary Rust types, so each one that should survive
+/// recording or a trip over the network has to be registered once, with the
+/// same name on both ends. Registering a type again replaces its codec.
+///
+/// ```ignore
+/// register_custom_event::<PlayerScoreEvent>("player_score");
+/// let bytes = Event::custom(PlayerScoreEvent { player_id: 1, score: 10 }).to_bytes()?;
+/// ```
+pub fn register_custom_event<T>(name: impl Into<String>)
+where
+    T: Serialize + DeserializeOwned + Any + Send + Sync,
+{
+    let codec = Arc::new(CustomEventCodec {
+        name: name.into(),
+        encode: |data| match data.downcast_ref::<T>() {
+            Some(data) => serde_json::to_value(data),
+            None => Err(<serde_json::Error as serde::ser::Error>::custom("custom event payload has an unexpected type")),
+        },
+        decode: |value| Ok(Arc::new(serde_json::from_value::<T>(value)?) as Payload),
+    });
+
+    let mut registry = registry().write().unwrap_or_else(PoisonError::into_inner);
+    registry.by_type.insert(TypeId::of::<T>(), Arc::clone(&codec));
+    registry.by_name.insert(codec.name.clone(), codec);
+}
+
+/// Wire form of a custom event: its name, the codec that restores the payload, and the payload
+#[derive(Serialize, Deserialize)]
+struct EncodedCustomEvent {
+    type_name: String,
+    codec: String,
+    data: serde_json::Value,
+}
+
+impl Serialize for CustomEventData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let codec = registry()
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .by_type
+            .get(&self.data_type_id())
+            .cloned()
+            .ok_or_else(|| S::Error::custom(format!("no codec registered for custom event '{}'", self.type_name)))?;
+
+        let data = (codec.encode)(&*self.data).map_err(S::Error::custom)?;
+        EncodedCustomEvent {
+            type_name: self.type_name.clone(),
+            codec: codec.name.clone(),
+            data,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomEventData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = EncodedCustomEvent::deserialize(deserializer)?;
+        let codec = registry()
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .by_name
+            .get(&encoded.codec)
+            .cloned()
+            .ok_or_else(|| D::Error::custom(format!("no codec registered under '{}'", encoded.codec)))?;
+
+        Ok(CustomEventData {
+            type_name: encoded.type_name,
+            data: (codec.decode)(encoded.data).map_err(D::Error::custom)?,
+        })
+    }
+}
+
+impl Event {
+    /// Encode the event for recording or sending over the network
+    ///
+    /// Custom events need their payload type registered with `register_custom_event`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+
+    /// Decode an event produced by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::core::{EventData, GamepadAxis, GamepadAxisEvent, KeyAction, KeyCode, KeyEvent, KeyMod};
+
+    #[test]
+    fn test_event_round_trip_through_bytes() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct PlayerScoreEvent {
+            player_id: u32,
+            score: i32,
+        }
+
+        let mut mods = KeyMod::new();
+        mods.shift = true;
+        let key = Event::new(EventData::Key(KeyEvent { key: KeyCode::F3, action: KeyAction::Repeat, mods }));
+        let decoded = Event::from_bytes(&key.to_bytes().unwrap()).unwrap();
+        let decoded_key = decoded.as_key_event().unwrap();
+        assert_eq!((decoded_key.key, decoded_key.action, decoded_key.mods), (KeyCode::F3, KeyAction::Repeat, mods));
+        assert_eq!(decoded.timestamp, key.timestamp);
+
+        let axis = Event::new(EventData::GamepadAxis(GamepadAxisEvent { gamepad_id: 1, axis: GamepadAxis::LeftStickX, value: -0.5 }));
+        let decoded = Event::from_bytes(&axis.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.as_gamepad_axis_event().unwrap().value, -0.5);
+
+        // Custom payloads need a registered codec
+        let score = Event::custom(PlayerScoreEvent { player_id: 2, score: 40 });
+        assert!(score.to_bytes().is_err());
+        register_custom_event::<PlayerScoreEvent>("player_score");
+        let decoded = Event::from_bytes(&score.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.as_custom::<PlayerScoreEvent>(), Some(&PlayerScoreEvent { player_id: 2, score: 40 }));
+    }
+}
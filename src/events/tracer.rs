@@ -0,0 +1,172 @@
+use crate::events::core::{Event, EventType};
+use artifice_logging::debug;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io;
+use std::path::Path;
+
+/// What happened to one event on its way through the engine loop
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceEntry {
+    pub event_type: EventType,
+    pub timestamp: u64,
+    /// Debug representation of the event data
+    pub data: String,
+    /// Global filter that rejected the event before dispatch
+    pub dropped_by: Option<String>,
+    /// Layers whose own filter chain kept the event from them, as `layer: filter`
+    pub blocked_by: Vec<String>,
+    /// Listeners, layers and application that received the event, in order
+    pub delivered_to: Vec<String>,
+    /// Whoever marked the event handled
+    pub handled_by: Option<String>,
+}
+
+impl TraceEntry {
+    pub fn new(event: &Event) -> Self {
+        Self {
+            event_type: event.event_type,
+            timestamp: event.timestamp,
+            data: format!("{:?}", event.data),
+            dropped_by: None,
+            blocked_by: Vec::new(),
+            delivered_to: Vec::new(),
+            handled_by: None,
+        }
+    }
+
+    /// The event was rejected by the global filter `filter`
+    pub fn dropped_by(mut self, filter: &str) -> Self {
+        self.dropped_by = Some(filter.to_string());
+        self
+    }
+
+    /// `receiver` got the event and may have handled it
+    pub fn delivered(&mut self, receiver: &str, handled: bool) {
+        self.delivered_to.push(receiver.to_string());
+        if handled && self.handled_by.is_none() {
+            self.handled_by = Some(receiver.to_string());
+        }
+    }
+
+    /// `filter` in the chain of `receiver` kept the event from it
+    pub fn blocked(&mut self, receiver: &str, filter: &str) {
+        self.blocked_by.push(format!("{}: {}", receiver, filter));
+    }
+}
+
+/// Ring buffer of the latest events seen by the engine loop, for debugging
+///
+/// Disabled by default, as describing every event costs a few allocations.
+/// When a key press seems to vanish, enable it and dump the trace to see
+/// whether a filter dropped the event or a layer handled it first.
+///
+/// ```ignore
+/// engine.get_event_tracer_mut().set_enabled(true);
+/// // ... reproduce ...
+/// engine.get_event_tracer().dump_to_file("events.json")?;
+/// ```
+#[derive(Debug)]
+pub struct EventTracer {
+    entries: VecDeque<TraceEntry>,
+    capacity: usize,
+    enabled: bool,
+}
+
+impl EventTracer {
+    /// Keep the last `capacity` events once enabled
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            enabled: false,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        debug!("Event tracing {}", if enabled { "enabled" } else { "disabled" });
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Add an entry, discarding the oldest one when full
+    pub fn record(&mut self, entry: TraceEntry) {
+        if !self.enabled {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Recorded entries, oldest first
+    pub fn entries(&self) -> impl Iterator<Item = &TraceEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The recorded entries as a pretty-printed JSON array
+    pub fn dump_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.entries)
+    }
+
+    /// Write `dump_json` to `path`
+    pub fn dump_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.dump_json()?)
+    }
+}
+
+impl Default for EventTracer {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::core::{EventData, KeyAction, KeyCode, KeyEvent, KeyMod};
+
+    #[test]
+    fn test_tracer_keeps_latest_entries_and_dumps_json() {
+        let key = |key| Event::new(EventData::Key(KeyEvent { key, action: KeyAction::Press, mods: KeyMod::new() }));
+        let mut tracer = EventTracer::new(2);
+
+        tracer.record(TraceEntry::new(&key(KeyCode::A)));
+        assert!(tracer.is_empty());
+        tracer.set_enabled(true);
+
+        tracer.record(TraceEntry::new(&key(KeyCode::A)).dropped_by("no_keys"));
+        let mut entry = TraceEntry::new(&key(KeyCode::B));
+        entry.blocked("HUD", "mouse_only");
+        entry.delivered("Gameplay", true);
+        entry.delivered("Application", false);
+        tracer.record(entry);
+        tracer.record(TraceEntry::new(&key(KeyCode::C)));
+
+        assert_eq!(tracer.len(), 2);
+        let first = tracer.entries().next().unwrap();
+        assert_eq!(first.handled_by.as_deref(), Some("Gameplay"));
+        assert_eq!(first.blocked_by, ["HUD: mouse_only"]);
+
+        let json: serde_json::Value = serde_json::from_str(&tracer.dump_json().unwrap()).unwrap();
+        assert_eq!(json[0]["event_type"], "Keyboard");
+        assert_eq!(json[0]["delivered_to"][1], "Application");
+        assert!(json[1]["data"].as_str().unwrap().contains("C"));
+    }
+}
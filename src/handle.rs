@@ -0,0 +1,95 @@
+use crate::events::{Event, EventSender};
+use crate::io::Window;
+use std::fmt;
+use std::sync::mpsc;
+
+/// A closure sent to run on the engine's thread
+type MainThreadTask = Box<dyn FnOnce(&mut dyn Window) + Send>;
+
+/// What an `EngineHandle` asks of the engine, carried out at the start of a frame
+pub(crate) enum EngineCommand {
+    Run(MainThreadTask),
+    Stop,
+}
+
+/// Cloneable handle for driving the engine from any thread
+///
+/// Windows and OpenGL contexts may only be touched from the thread the
+/// engine runs on. Asset loaders and network threads send that work here
+/// instead; the engine runs it at the start of the next frame, in the order
+/// it was sent.
+#[derive(Clone)]
+pub struct EngineHandle {
+    events: EventSender,
+    commands: mpsc::Sender<EngineCommand>,
+}
+
+impl EngineHandle {
+    /// Create a handle and the receiving end the engine drains each frame
+    pub(crate) fn channel(events: EventSender) -> (Self, mpsc::Receiver<EngineCommand>) {
+        let (commands, receiver) = mpsc::channel();
+        (Self { events, commands }, receiver)
+    }
+
+    /// Run `task` on the engine's thread with the main window, whose context is current
+    ///
+    /// Returns false if the engine has shut down, in which case `task` is dropped.
+    pub fn run_on_main_thread(&self, task: impl FnOnce(&mut dyn Window) + Send + 'static) -> bool {
+        self.commands.send(EngineCommand::Run(Box::new(task))).is_ok()
+    }
+
+    /// Queue an event to be filtered and dispatched next frame; gives it back if the engine has shut down
+    pub fn post_event(&self, event: Event) -> Result<(), Event> {
+        self.events.send(event)
+    }
+
+    /// Stop the engine once the tasks sent before this have run
+    ///
+    /// Returns false if the engine has shut down already.
+    pub fn request_stop(&self) -> bool {
+        self.commands.send(EngineCommand::Stop).is_ok()
+    }
+}
+
+impl fmt::Debug for EngineHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EngineHandle").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::HeadlessWindow;
+    use std::thread;
+
+    #[test]
+    fn test_tasks_run_in_order_on_receiving_thread() {
+        let (events, _posted) = EventSender::channel();
+        let (handle, commands) = EngineHandle::channel(events);
+
+        let loader = handle.clone();
+        thread::spawn(move || {
+            assert!(loader.run_on_main_thread(|window| window.set_title("Loading")));
+            assert!(loader.run_on_main_thread(|window| window.set_title(&format!("{} done", window.title()))));
+            assert!(loader.request_stop());
+        })
+        .join()
+        .unwrap();
+
+        let mut window = HeadlessWindow::new(64, 64, "Untitled");
+        let mut stopped = false;
+        for command in commands.try_iter() {
+            match command {
+                EngineCommand::Run(task) => task(&mut window),
+                EngineCommand::Stop => stopped = true,
+            }
+        }
+        assert_eq!(window.title(), "Loading done");
+        assert!(stopped);
+
+        drop(commands);
+        assert!(!handle.run_on_main_thread(|_| {}));
+        assert!(!handle.request_stop());
+    }
+}
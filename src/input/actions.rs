@@ -0,0 +1,196 @@
+use crate::events::core::{Event, EventData, GamepadButton, KeyAction, KeyCode, MouseButton};
+use artifice_logging::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// A physical input that can trigger an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    MouseButton(MouseButton),
+    /// A button on any gamepad
+    GamepadButton(GamepadButton),
+}
+
+impl InputBinding {
+    /// The binding pressed by `event`, if it is a press
+    pub fn from_press(event: &Event) -> Option<Self> {
+        match &event.data {
+            EventData::Key(key) if key.action == KeyAction::Press => Some(Self::Key(key.key)),
+            EventData::MouseButton(button) if button.action == KeyAction::Press => {
+                Some(Self::MouseButton(button.button))
+            }
+            EventData::GamepadButton(button) if button.action == KeyAction::Press => {
+                Some(Self::GamepadButton(button.button))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of a rebind started with `ActionMap::listen`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RebindResult {
+    /// `binding` now triggers `action`, replacing its previous bindings
+    Bound { action: String, binding: InputBinding },
+    /// Escape was pressed, the bindings are unchanged
+    Cancelled { action: String },
+}
+
+/// Named actions and the inputs bound to them
+///
+/// Games ask for actions ("jump") rather than keys, so players can rebind
+/// them. To rebind, put the map into listen mode for one action; the next
+/// key, mouse or gamepad button press becomes its binding. The whole map
+/// serializes to JSON so custom controls persist across runs.
+///
+/// ```ignore
+/// let actions = input_manager.actions_mut();
+/// actions.bind("jump", InputBinding::Key(KeyCode::Space));
+/// actions.listen("jump"); // the next press rebinds "jump"
+/// actions.save_to_file("controls.json")?;
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActionMap {
+    bindings: BTreeMap<String, Vec<InputBinding>>,
+    #[serde(skip)]
+    listening: Option<String>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `binding` to the inputs that trigger `action`
+    pub fn bind(&mut self, action: impl Into<String>, binding: InputBinding) {
+        let bindings = self.bindings.entry(action.into()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Remove every binding of `action`
+    pub fn unbind(&mut self, action: &str) {
+        self.bindings.remove(action);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[InputBinding] {
+        self.bindings.get(action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Names of the actions with bindings, sorted
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.bindings.keys().map(String::as_str)
+    }
+
+    /// Actions triggered by `event`, if it presses a bound input
+    pub fn triggered_actions<'a>(&'a self, event: &Event) -> impl Iterator<Item = &'a str> {
+        let binding = InputBinding::from_press(event);
+        self.bindings
+            .iter()
+            .filter(move |(_, bindings)| binding.is_some_and(|binding| bindings.contains(&binding)))
+            .map(|(action, _)| action.as_str())
+    }
+
+    /// Bind the next physical press to `action` instead of its current bindings
+    pub fn listen(&mut self, action: impl Into<String>) {
+        let action = action.into();
+        debug!("Listening for a new binding for '{}'", action);
+        self.listening = Some(action);
+    }
+
+    /// Action waiting for its new binding, if any
+    pub fn listening(&self) -> Option<&str> {
+        self.listening.as_deref()
+    }
+
+    pub fn cancel_listen(&mut self) {
+        self.listening = None;
+    }
+
+    /// In listen mode, take a press as the new binding
+    ///
+    /// Returns `None` while not listening or if `event` presses nothing.
+    pub fn capture(&mut self, event: &Event) -> Option<RebindResult> {
+        self.listening.as_ref()?;
+        let binding = InputBinding::from_press(event)?;
+        let action = self.listening.take()?;
+
+        if binding == InputBinding::Key(KeyCode::Escape) {
+            debug!("Rebinding '{}' cancelled", action);
+            return Some(RebindResult::Cancelled { action });
+        }
+
+        info!("Bound '{}' to {:?}", action, binding);
+        self.bindings.insert(action.clone(), vec![binding]);
+        Some(RebindResult::Bound { action, binding })
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Save the bindings to a JSON file
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// Load bindings saved with `save_to_file`
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::core::{GamepadButtonEvent, KeyEvent, KeyMod};
+
+    fn key(key: KeyCode, action: KeyAction) -> Event {
+        Event::new(EventData::Key(KeyEvent { key, action, mods: KeyMod::new() }))
+    }
+
+    #[test]
+    fn test_rebind_and_round_trip() {
+        let mut actions = ActionMap::new();
+        actions.bind("jump", InputBinding::Key(KeyCode::Space));
+        actions.bind("jump", InputBinding::GamepadButton(GamepadButton::A));
+        assert_eq!(actions.triggered_actions(&key(KeyCode::Space, KeyAction::Press)).collect::<Vec<_>>(), ["jump"]);
+        assert_eq!(actions.triggered_actions(&key(KeyCode::Space, KeyAction::Release)).count(), 0);
+
+        // Nothing is captured until listening, and releases are skipped
+        assert_eq!(actions.capture(&key(KeyCode::W, KeyAction::Press)), None);
+        actions.listen("jump");
+        assert_eq!(actions.capture(&key(KeyCode::Space, KeyAction::Release)), None);
+        let pad = Event::new(EventData::GamepadButton(GamepadButtonEvent {
+            gamepad_id: 0,
+            button: GamepadButton::B,
+            action: KeyAction::Press,
+            mods: KeyMod::new(),
+        }));
+        assert_eq!(
+            actions.capture(&pad),
+            Some(RebindResult::Bound { action: "jump".into(), binding: InputBinding::GamepadButton(GamepadButton::B) })
+        );
+        assert_eq!(actions.bindings("jump"), [InputBinding::GamepadButton(GamepadButton::B)]);
+        assert_eq!(actions.listening(), None);
+
+        actions.listen("jump");
+        assert_eq!(actions.capture(&key(KeyCode::Escape, KeyAction::Press)), Some(RebindResult::Cancelled { action: "jump".into() }));
+        assert_eq!(actions.bindings("jump"), [InputBinding::GamepadButton(GamepadButton::B)]);
+
+        let restored = ActionMap::from_json(&actions.to_json().unwrap()).unwrap();
+        assert_eq!(restored.bindings("jump"), actions.bindings("jump"));
+    }
+}
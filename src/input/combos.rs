@@ -0,0 +1,250 @@
+use crate::events::core::{Event, EventData, KeyAction, KeyMod};
+use crate::input::actions::InputBinding;
+use artifice_logging::{debug, trace};
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+/// Inputs held down together, such as Ctrl+Shift+S
+#[derive(Debug, Clone)]
+pub struct Chord {
+    pub name: String,
+    pub bindings: Vec<InputBinding>,
+    /// Modifiers that must be active, regardless of which side's key provides them
+    pub mods: KeyMod,
+}
+
+impl Chord {
+    pub fn new(name: impl Into<String>, bindings: impl IntoIterator<Item = InputBinding>) -> Self {
+        Self {
+            name: name.into(),
+            bindings: bindings.into_iter().collect(),
+            mods: KeyMod::new(),
+        }
+    }
+
+    /// Also require `mods`, e.g. control and shift for Ctrl+Shift+S
+    pub fn with_mods(mut self, mods: KeyMod) -> Self {
+        self.mods = mods;
+        self
+    }
+}
+
+/// Inputs pressed one after another, such as double-tapping forward to dash
+#[derive(Debug, Clone)]
+pub struct Combo {
+    pub name: String,
+    pub steps: Vec<InputBinding>,
+    /// Longest gap between two consecutive presses of the sequence
+    pub window: Duration,
+}
+
+impl Combo {
+    pub fn new(name: impl Into<String>, steps: impl IntoIterator<Item = InputBinding>) -> Self {
+        Self {
+            name: name.into(),
+            steps: steps.into_iter().collect(),
+            window: Duration::from_millis(250),
+        }
+    }
+
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+}
+
+/// Whether a `ComboEvent` comes from a chord or a sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComboKind {
+    Chord,
+    Sequence,
+}
+
+/// Payload of the custom event emitted when a chord or combo completes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComboEvent {
+    /// Name of the chord or combo, usually the action it performs
+    pub name: String,
+    pub kind: ComboKind,
+}
+
+/// Recognizes chords and timed sequences in the raw input stream
+///
+/// Completed ones are emitted as `Event::custom(ComboEvent)` right after the
+/// press that completes them. Times come from `Event::timestamp`, so replayed
+/// input yields the same combos. A sequence's presses are consumed when it
+/// fires, so a triple tap does not dash twice.
+///
+/// ```ignore
+/// let combos = input_manager.combos_mut();
+/// combos.add_chord(Chord::new("save", [InputBinding::Key(KeyCode::S)]).with_mods(ctrl_shift));
+/// combos.add_combo(Combo::new("dash", [InputBinding::Key(KeyCode::D); 2]).with_window(Duration::from_millis(200)));
+/// ```
+#[derive(Debug, Default)]
+pub struct ComboDetector {
+    chords: Vec<Chord>,
+    combos: Vec<Combo>,
+    held: HashSet<InputBinding>,
+    /// Latest presses with their timestamps, as long as the longest combo
+    buffer: VecDeque<(InputBinding, u64)>,
+}
+
+impl ComboDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_chord(&mut self, chord: Chord) {
+        debug!("Adding chord '{}': {:?}", chord.name, chord.bindings);
+        self.chords.push(chord);
+    }
+
+    pub fn add_combo(&mut self, combo: Combo) {
+        debug!("Adding combo '{}': {:?}", combo.name, combo.steps);
+        self.combos.push(combo);
+    }
+
+    /// Remove the chords and combos called `name`
+    pub fn remove(&mut self, name: &str) {
+        self.chords.retain(|chord| chord.name != name);
+        self.combos.retain(|combo| combo.name != name);
+    }
+
+    pub fn chords(&self) -> &[Chord] {
+        &self.chords
+    }
+
+    pub fn combos(&self) -> &[Combo] {
+        &self.combos
+    }
+
+    /// Forget held inputs and buffered presses, e.g. when the window loses focus
+    pub fn reset(&mut self) {
+        self.held.clear();
+        self.buffer.clear();
+    }
+
+    /// Feed one raw event, appending any chords and combos it completes to `out`
+    pub fn process(&mut self, event: &Event, out: &mut Vec<Event>) {
+        let (binding, action, mods) = match &event.data {
+            EventData::Key(key) => (InputBinding::Key(key.key), key.action, key.mods),
+            EventData::MouseButton(button) => (InputBinding::MouseButton(button.button), button.action, button.mods),
+            EventData::GamepadButton(button) => {
+                (InputBinding::GamepadButton(button.button), button.action, button.mods)
+            }
+            _ => return,
+        };
+
+        match action {
+            KeyAction::Press => {}
+            KeyAction::Release => {
+                self.held.remove(&binding);
+                return;
+            }
+            KeyAction::Repeat => return,
+        }
+        self.held.insert(binding);
+
+        for chord in &self.chords {
+            if chord.bindings.contains(&binding)
+                && chord.bindings.iter().all(|binding| self.held.contains(binding))
+                && has_mods(mods, chord.mods)
+            {
+                trace!("Chord '{}' completed", chord.name);
+                out.push(Self::combo_event(&chord.name, ComboKind::Chord));
+            }
+        }
+
+        let longest = self.combos.iter().map(|combo| combo.steps.len()).max().unwrap_or(0);
+        if longest == 0 {
+            return;
+        }
+        self.buffer.push_back((binding, event.timestamp));
+        while self.buffer.len() > longest {
+            self.buffer.pop_front();
+        }
+
+        let completed = self.combos.iter().find(|combo| self.completes(combo));
+        if let Some(combo) = completed {
+            trace!("Combo '{}' completed", combo.name);
+            out.push(Self::combo_event(&combo.name, ComboKind::Sequence));
+            self.buffer.clear();
+        }
+    }
+
+    /// Whether the buffer ends with `combo`'s steps, each within its window of the previous one
+    fn completes(&self, combo: &Combo) -> bool {
+        let steps = combo.steps.len();
+        if steps == 0 || self.buffer.len() < steps {
+            return false;
+        }
+        let window = combo.window.as_millis() as u64;
+        let recent: Vec<_> = self.buffer.iter().skip(self.buffer.len() - steps).collect();
+        recent.iter().zip(&combo.steps).all(|((binding, _), step)| binding == step)
+            && recent.windows(2).all(|pair| pair[1].1.saturating_sub(pair[0].1) <= window)
+    }
+
+    fn combo_event(name: &str, kind: ComboKind) -> Event {
+        Event::custom(ComboEvent {
+            name: name.to_string(),
+            kind,
+        })
+    }
+}
+
+/// Whether `actual` includes every modifier set in `required`
+fn has_mods(actual: KeyMod, required: KeyMod) -> bool {
+    (!required.shift || actual.shift)
+        && (!required.control || actual.control)
+        && (!required.alt || actual.alt)
+        && (!required.super_key || actual.super_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::core::{KeyCode, KeyEvent};
+
+    fn key(key: KeyCode, action: KeyAction, mods: KeyMod, timestamp: u64) -> Event {
+        let mut event = Event::new(EventData::Key(KeyEvent { key, action, mods }));
+        event.timestamp = timestamp;
+        event
+    }
+
+    fn fired(detector: &mut ComboDetector, event: Event) -> Vec<ComboEvent> {
+        let mut out = Vec::new();
+        detector.process(&event, &mut out);
+        out.iter().filter_map(|event| event.as_custom::<ComboEvent>().cloned()).collect()
+    }
+
+    #[test]
+    fn test_chords_and_timed_sequences() {
+        let ctrl_shift = KeyMod { control: true, shift: true, ..KeyMod::new() };
+        let forward = InputBinding::Key(KeyCode::D);
+        let mut detector = ComboDetector::new();
+        detector.add_chord(Chord::new("save_all", [InputBinding::Key(KeyCode::S)]).with_mods(ctrl_shift));
+        detector.add_combo(Combo::new("dash", [forward, forward]).with_window(Duration::from_millis(200)));
+
+        // Ctrl alone is not enough
+        let ctrl = KeyMod { control: true, ..KeyMod::new() };
+        assert!(fired(&mut detector, key(KeyCode::S, KeyAction::Press, ctrl, 0)).is_empty());
+        fired(&mut detector, key(KeyCode::S, KeyAction::Release, ctrl, 10));
+        assert_eq!(
+            fired(&mut detector, key(KeyCode::S, KeyAction::Press, ctrl_shift, 20)),
+            [ComboEvent { name: "save_all".into(), kind: ComboKind::Chord }]
+        );
+
+        // Too slow, then in time; the third tap starts over
+        let none = KeyMod::new();
+        assert!(fired(&mut detector, key(KeyCode::D, KeyAction::Press, none, 1000)).is_empty());
+        fired(&mut detector, key(KeyCode::D, KeyAction::Release, none, 1050));
+        assert!(fired(&mut detector, key(KeyCode::D, KeyAction::Press, none, 1300)).is_empty());
+        fired(&mut detector, key(KeyCode::D, KeyAction::Release, none, 1350));
+        assert_eq!(
+            fired(&mut detector, key(KeyCode::D, KeyAction::Press, none, 1450)),
+            [ComboEvent { name: "dash".into(), kind: ComboKind::Sequence }]
+        );
+        fired(&mut detector, key(KeyCode::D, KeyAction::Release, none, 1500));
+        assert!(fired(&mut detector, key(KeyCode::D, KeyAction::Press, none, 1550)).is_empty());
+    }
+}
@@ -1,10 +1,15 @@
 use crate::events::core::{KeyAction, KeyMod};
-use crate::input::InputDevice;
+use crate::input::smoothing::LowPassFilter;
+use crate::input::{ButtonState, InputDevice};
 use artifice_logging::{debug, trace, warn};
 use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Standard gamepad buttons
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum GamepadButton {
     // Face buttons (Xbox layout names)
     A,              // Bottom face button
@@ -47,8 +52,43 @@ pub enum GamepadButton {
     Button20,
 }
 
+impl From<GamepadButton> for crate::events::GamepadButton {
+    fn from(button: GamepadButton) -> Self {
+        use crate::events::GamepadButton as EventButton;
+
+        match button {
+            GamepadButton::A => EventButton::A,
+            GamepadButton::B => EventButton::B,
+            GamepadButton::X => EventButton::X,
+            GamepadButton::Y => EventButton::Y,
+            GamepadButton::LeftBumper => EventButton::LeftBumper,
+            GamepadButton::RightBumper => EventButton::RightBumper,
+            GamepadButton::LeftTrigger => EventButton::LeftTrigger,
+            GamepadButton::RightTrigger => EventButton::RightTrigger,
+            GamepadButton::DPadUp => EventButton::DPadUp,
+            GamepadButton::DPadDown => EventButton::DPadDown,
+            GamepadButton::DPadLeft => EventButton::DPadLeft,
+            GamepadButton::DPadRight => EventButton::DPadRight,
+            GamepadButton::Start => EventButton::Start,
+            GamepadButton::Select => EventButton::Select,
+            GamepadButton::Guide => EventButton::Guide,
+            GamepadButton::LeftStick => EventButton::LeftStick,
+            GamepadButton::RightStick => EventButton::RightStick,
+            GamepadButton::Paddle1 => EventButton::Paddle1,
+            GamepadButton::Paddle2 => EventButton::Paddle2,
+            GamepadButton::Paddle3 => EventButton::Paddle3,
+            GamepadButton::Paddle4 => EventButton::Paddle4,
+            GamepadButton::Button16 => EventButton::Button16,
+            GamepadButton::Button17 => EventButton::Button17,
+            GamepadButton::Button18 => EventButton::Button18,
+            GamepadButton::Button19 => EventButton::Button19,
+            GamepadButton::Button20 => EventButton::Button20,
+        }
+    }
+}
+
 /// Gamepad analog axes
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum GamepadAxis {
     // Left stick
     LeftStickX,
@@ -71,6 +111,77 @@ pub enum GamepadAxis {
     Axis11,
 }
 
+impl GamepadAxis {
+    /// The other axis of the same stick
+    pub fn stick_partner(self) -> Option<GamepadAxis> {
+        match self {
+            GamepadAxis::LeftStickX => Some(GamepadAxis::LeftStickY),
+            GamepadAxis::LeftStickY => Some(GamepadAxis::LeftStickX),
+            GamepadAxis::RightStickX => Some(GamepadAxis::RightStickY),
+            GamepadAxis::RightStickY => Some(GamepadAxis::RightStickX),
+            _ => None,
+        }
+    }
+}
+
+/// How an axis value past the dead zone maps to the reported value
+///
+/// Curves work on the magnitude in 0.0..=1.0; the sign is kept.
+#[derive(Clone, Default)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    /// Raise the magnitude to this power; above 1.0 gives finer control near the center
+    Exponential(f32),
+    Custom(Arc<dyn Fn(f32) -> f32 + Send + Sync>),
+}
+
+impl ResponseCurve {
+    /// Map a magnitude in 0.0..=1.0, clamping the result to the same range
+    pub fn apply(&self, magnitude: f32) -> f32 {
+        let shaped = match self {
+            ResponseCurve::Linear => magnitude,
+            ResponseCurve::Exponential(exponent) => magnitude.powf(*exponent),
+            ResponseCurve::Custom(curve) => curve(magnitude),
+        };
+        shaped.clamp(0.0, 1.0)
+    }
+}
+
+impl fmt::Debug for ResponseCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResponseCurve::Linear => write!(f, "Linear"),
+            ResponseCurve::Exponential(exponent) => write!(f, "Exponential({})", exponent),
+            ResponseCurve::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// How the dead zone of a stick is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeadZoneMode {
+    /// Each axis is cut off on its own, which snaps diagonals to the axes near the center
+    #[default]
+    PerAxis,
+    /// The stick's distance from the center is cut off, keeping its direction
+    Radial,
+}
+
+/// Shaping of one analog axis
+#[derive(Debug, Clone, Default)]
+pub struct AxisConfig {
+    /// Dead zone of this axis, overriding the gamepad's
+    pub deadzone: Option<f32>,
+    pub inverted: bool,
+    pub curve: ResponseCurve,
+    /// Time constant of a low-pass filter on the raw value, taming noisy sticks
+    ///
+    /// Filtering happens before the dead zone, so jitter around the center
+    /// stays inside it. Longer times give steadier but laggier values.
+    pub smoothing: Option<Duration>,
+}
+
 /// Represents a gamepad input event
 #[derive(Debug, Clone)]
 pub struct GamepadButtonEvent {
@@ -94,17 +205,34 @@ pub struct GamepadConnectionEvent {
     pub name: String,
 }
 
+/// What lobby screens need to show an attached gamepad
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GamepadInfo {
+    pub id: u32,
+    pub name: String,
+    /// SDL-style device GUID, if the backend can tell
+    pub guid: Option<String>,
+    /// Battery charge in percent, if known
+    pub battery: Option<u8>,
+}
+
 /// State of a single gamepad
 #[derive(Debug, Clone)]
 pub struct GamepadState {
     pub id: u32,
     pub name: String,
+    pub guid: Option<String>,
+    pub battery: Option<u8>,
     pub connected: bool,
     button_states: HashMap<GamepadButton, bool>,
     button_just_pressed: HashMap<GamepadButton, bool>,
     button_just_released: HashMap<GamepadButton, bool>,
     axis_values: HashMap<GamepadAxis, f32>,
     deadzone: f32,
+    deadzone_mode: DeadZoneMode,
+    axis_configs: HashMap<GamepadAxis, AxisConfig>,
+    /// Filters of the axes configured with smoothing
+    axis_filters: HashMap<GamepadAxis, LowPassFilter>,
 }
 
 impl GamepadState {
@@ -112,12 +240,17 @@ impl GamepadState {
         Self {
             id,
             name,
+            guid: None,
+            battery: None,
             connected: true,
             button_states: HashMap::new(),
             button_just_pressed: HashMap::new(),
             button_just_released: HashMap::new(),
             axis_values: HashMap::new(),
             deadzone: 0.1, // Default deadzone
+            deadzone_mode: DeadZoneMode::default(),
+            axis_configs: HashMap::new(),
+            axis_filters: HashMap::new(),
         }
     }
 
@@ -131,11 +264,60 @@ impl GamepadState {
         self.deadzone
     }
 
+    /// Measure the stick dead zones per axis or radially
+    pub fn set_deadzone_mode(&mut self, mode: DeadZoneMode) {
+        self.deadzone_mode = mode;
+    }
+
+    pub fn deadzone_mode(&self) -> DeadZoneMode {
+        self.deadzone_mode
+    }
+
+    /// Set the dead zone override, inversion and response curve of an axis
+    pub fn set_axis_config(&mut self, axis: GamepadAxis, config: AxisConfig) {
+        match config.smoothing {
+            // Start from the current value so changing the time constant does not jump
+            Some(time_constant) => {
+                let value = self.raw_axis_value(axis);
+                self.axis_filters
+                    .entry(axis)
+                    .or_insert_with(|| LowPassFilter::new(time_constant, value))
+                    .set_time_constant(time_constant);
+            }
+            None => {
+                self.axis_filters.remove(&axis);
+            }
+        }
+        self.axis_configs.insert(axis, config);
+    }
+
+    /// Get the shaping of an axis, if it has been configured
+    pub fn axis_config(&self, axis: GamepadAxis) -> Option<&AxisConfig> {
+        self.axis_configs.get(&axis)
+    }
+
+    /// Dead zone that applies to an axis
+    fn axis_deadzone(&self, axis: GamepadAxis) -> f32 {
+        self.axis_config(axis)
+            .and_then(|config| config.deadzone)
+            .map_or(self.deadzone, |deadzone| deadzone.clamp(0.0, 1.0))
+    }
+
     /// Check if a button is currently pressed
     pub fn is_button_pressed(&self, button: GamepadButton) -> bool {
         self.button_states.get(&button).copied().unwrap_or(false)
     }
 
+    /// Buttons currently held, in no particular order
+    pub fn buttons_down(&self) -> impl Iterator<Item = GamepadButton> + '_ {
+        self.button_states.iter().filter(|(_, down)| **down).map(|(button, _)| *button)
+    }
+
+    /// Axes that have reported a value, with the value as `axis_value` gives it
+    pub fn axis_values(&self) -> impl Iterator<Item = (GamepadAxis, f32)> + '_ {
+        self.axis_values.keys().map(|axis| (*axis, self.axis_value(*axis)))
+    }
+
     /// Check if a button was just pressed this frame
     pub fn is_button_just_pressed(&self, button: GamepadButton) -> bool {
         self.button_just_pressed.get(&button).copied().unwrap_or(false)
@@ -146,19 +328,42 @@ impl GamepadState {
         self.button_just_released.get(&button).copied().unwrap_or(false)
     }
 
+    /// State of a button this frame
+    pub fn button_state(&self, button: GamepadButton) -> ButtonState {
+        ButtonState::from_frame(
+            self.is_button_just_pressed(button),
+            self.is_button_just_released(button),
+            self.is_button_pressed(button),
+        )
+    }
+
     /// Get the current value of an analog axis (-1.0 to 1.0)
+    ///
+    /// The dead zone is applied first, then the response curve, then inversion.
     pub fn axis_value(&self, axis: GamepadAxis) -> f32 {
-        let value = self.axis_values.get(&axis).copied().unwrap_or(0.0);
-        
-        // Apply deadzone
-        if value.abs() < self.deadzone {
-            0.0
-        } else {
+        let value = self.filtered_axis_value(axis);
+        let deadzone = self.axis_deadzone(axis);
+
+        let value = match axis.stick_partner() {
+            Some(partner) if self.deadzone_mode == DeadZoneMode::Radial => {
+                let partner_value = self.filtered_axis_value(partner);
+                let magnitude = (value * value + partner_value * partner_value).sqrt();
+                if magnitude <= deadzone {
+                    0.0
+                } else {
+                    // Rescale the distance past the dead zone, keeping the direction
+                    let scaled = ((magnitude - deadzone) / (1.0 - deadzone)).clamp(0.0, 1.0);
+                    value / magnitude * scaled
+                }
+            }
+            _ if value.abs() < deadzone => 0.0,
             // Scale the value to account for deadzone
-            let sign = value.signum();
-            let scaled = (value.abs() - self.deadzone) / (1.0 - self.deadzone);
-            sign * scaled.clamp(0.0, 1.0)
-        }
+            _ => value.signum() * ((value.abs() - deadzone) / (1.0 - deadzone)).clamp(0.0, 1.0),
+        };
+
+        let Some(config) = self.axis_config(axis) else { return value };
+        let shaped = value.signum() * config.curve.apply(value.abs());
+        if config.inverted { -shaped } else { shaped }
     }
 
     /// Get the raw axis value without deadzone applied
@@ -166,6 +371,22 @@ impl GamepadState {
         self.axis_values.get(&axis).copied().unwrap_or(0.0)
     }
 
+    /// Get the axis value after smoothing but before the dead zone and curve
+    ///
+    /// Equals `raw_axis_value` for axes without smoothing.
+    pub fn filtered_axis_value(&self, axis: GamepadAxis) -> f32 {
+        self.axis_filters
+            .get(&axis)
+            .map_or_else(|| self.raw_axis_value(axis), LowPassFilter::value)
+    }
+
+    /// Advance the smoothing of every axis by `elapsed`
+    pub fn smooth(&mut self, elapsed: Duration) {
+        for (axis, filter) in &mut self.axis_filters {
+            filter.step(self.axis_values.get(axis).copied().unwrap_or(0.0), elapsed);
+        }
+    }
+
     /// Get left stick as a 2D vector (x, y)
     pub fn left_stick(&self) -> (f32, f32) {
         (
@@ -242,6 +463,9 @@ impl GamepadState {
         self.button_just_pressed.clear();
         self.button_just_released.clear();
         self.axis_values.clear();
+        for filter in self.axis_filters.values_mut() {
+            filter.reset(0.0);
+        }
         debug!("Gamepad {} disconnected", self.id);
     }
 }
@@ -250,6 +474,8 @@ impl GamepadState {
 pub struct GamepadManager {
     gamepads: HashMap<u32, GamepadState>,
     connected: bool,
+    /// When axis smoothing last advanced
+    last_smoothed: Option<Instant>,
 }
 
 impl GamepadManager {
@@ -257,6 +483,7 @@ impl GamepadManager {
         Self {
             gamepads: HashMap::new(),
             connected: true,
+            last_smoothed: None,
         }
     }
 
@@ -286,6 +513,23 @@ impl GamepadManager {
             .collect()
     }
 
+    /// Describe the connected gamepads, ordered by ID
+    pub fn connected_gamepads(&self) -> Vec<GamepadInfo> {
+        let mut gamepads: Vec<GamepadInfo> = self
+            .gamepads
+            .values()
+            .filter(|gamepad| gamepad.connected)
+            .map(|gamepad| GamepadInfo {
+                id: gamepad.id,
+                name: gamepad.name.clone(),
+                guid: gamepad.guid.clone(),
+                battery: gamepad.battery,
+            })
+            .collect();
+        gamepads.sort_by_key(|gamepad| gamepad.id);
+        gamepads
+    }
+
     /// Get the number of connected gamepads
     pub fn connected_count(&self) -> usize {
         self.gamepads
@@ -294,6 +538,35 @@ impl GamepadManager {
             .count()
     }
 
+    /// State of a button on gamepad `id`, `Up` if it is not connected
+    pub fn button_state(&self, id: u32, button: GamepadButton) -> ButtonState {
+        self.gamepad(id)
+            .filter(|gamepad| gamepad.connected)
+            .map_or(ButtonState::Up, |gamepad| gamepad.button_state(button))
+    }
+
+    /// Check if a button on gamepad `id` went down this frame or is held from an earlier one
+    pub fn is_down(&self, id: u32, button: GamepadButton) -> bool {
+        self.button_state(id, button).is_down()
+    }
+
+    /// Check if a button on gamepad `id` went down this frame
+    pub fn just_pressed(&self, id: u32, button: GamepadButton) -> bool {
+        self.button_state(id, button) == ButtonState::Pressed
+    }
+
+    /// Check if a button on gamepad `id` went up this frame
+    pub fn just_released(&self, id: u32, button: GamepadButton) -> bool {
+        self.gamepad(id).is_some_and(|gamepad| gamepad.is_button_just_released(button))
+    }
+
+    /// Value of an axis on gamepad `id` with its dead zone applied, 0.0 if it is not connected
+    pub fn axis(&self, id: u32, axis: GamepadAxis) -> f32 {
+        self.gamepad(id)
+            .filter(|gamepad| gamepad.connected)
+            .map_or(0.0, |gamepad| gamepad.axis_value(axis))
+    }
+
     /// Check if any gamepad has a button pressed
     pub fn any_button_pressed(&self, button: GamepadButton) -> bool {
         self.gamepads
@@ -313,8 +586,17 @@ impl GamepadManager {
     /// Process a gamepad connection event
     pub fn process_connection_event(&mut self, id: u32, connected: bool, name: String) {
         if connected {
-            let gamepad = GamepadState::new(id, name.clone());
-            self.gamepads.insert(id, gamepad);
+            // Keep the dead zone and axis settings of a gamepad seen before,
+            // e.g. when a new window backend reports it again
+            match self.gamepads.get_mut(&id) {
+                Some(gamepad) => {
+                    gamepad.connected = true;
+                    gamepad.name = name.clone();
+                }
+                None => {
+                    self.gamepads.insert(id, GamepadState::new(id, name.clone()));
+                }
+            }
             debug!("Gamepad {} connected: {}", id, name);
         } else {
             if let Some(gamepad) = self.gamepads.get_mut(&id) {
@@ -354,6 +636,32 @@ impl GamepadManager {
         debug!("Set global gamepad deadzone to {:.3}", deadzone);
     }
 
+    /// Set the shaping of an axis on all connected gamepads
+    pub fn set_global_axis_config(&mut self, axis: GamepadAxis, config: AxisConfig) {
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.set_axis_config(axis, config.clone());
+        }
+        debug!("Set global gamepad {:?} config to {:?}", axis, config);
+    }
+
+    /// Set the shaping of an axis on a specific gamepad
+    pub fn set_axis_config(&mut self, id: u32, axis: GamepadAxis, config: AxisConfig) {
+        if let Some(gamepad) = self.gamepads.get_mut(&id) {
+            debug!("Set gamepad {} {:?} config to {:?}", id, axis, config);
+            gamepad.set_axis_config(axis, config);
+        }
+    }
+
+    /// Advance the axis smoothing of every gamepad by `elapsed`
+    ///
+    /// `update` does this with the time since its last call; call it directly
+    /// to step smoothing with a fixed timestep instead.
+    pub fn smooth(&mut self, elapsed: Duration) {
+        for gamepad in self.gamepads.values_mut().filter(|gamepad| gamepad.connected) {
+            gamepad.smooth(elapsed);
+        }
+    }
+
     /// Set deadzone for a specific gamepad
     pub fn set_gamepad_deadzone(&mut self, id: u32, deadzone: f32) {
         if let Some(gamepad) = self.gamepads.get_mut(&id) {
@@ -390,6 +698,11 @@ impl InputDevice for GamepadManager {
         for gamepad in self.gamepads.values_mut() {
             gamepad.update();
         }
+
+        let now = Instant::now();
+        if let Some(last) = self.last_smoothed.replace(now) {
+            self.smooth(now.duration_since(last));
+        }
     }
 
     fn is_connected(&self) -> bool {
@@ -0,0 +1,392 @@
+use crate::events::core::{
+    DragPhase, Event, EventData, KeyAction, KeyCode, LongPressEvent, MouseButton, MouseDoubleClickEvent,
+    MouseDragEvent, PinchEvent, SwipeDirection, SwipeEvent,
+};
+use artifice_logging::trace;
+use std::time::Duration;
+
+/// Tuning for double-click and drag detection
+#[derive(Debug, Clone)]
+pub struct GestureConfig {
+    /// Longest gap between two presses that still counts as a double click
+    pub double_click_time: Duration,
+    /// Distance in pixels the cursor has to move with a button held to start a drag;
+    /// also how far apart the presses of a double click may be
+    pub drag_threshold: f64,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            double_click_time: Duration::from_millis(500),
+            drag_threshold: 4.0,
+        }
+    }
+}
+
+/// A press that may become a double click or a drag
+#[derive(Debug, Clone, Copy)]
+struct Press {
+    button: MouseButton,
+    position: (f64, f64),
+    timestamp: u64,
+}
+
+/// Derives double-click and drag events from raw mouse button and move events
+///
+/// Times come from `Event::timestamp`, so feeding recorded events yields the
+/// same gestures as the live input did.
+#[derive(Debug, Default)]
+pub struct GestureSynthesizer {
+    config: GestureConfig,
+    position: (f64, f64),
+    /// Press that could be the first half of a double click
+    last_click: Option<Press>,
+    /// Button currently held, which may turn into a drag
+    held: Option<Press>,
+    dragging: bool,
+}
+
+impl GestureSynthesizer {
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> &GestureConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: GestureConfig) {
+        self.config = config;
+    }
+
+    /// Feed one raw event, appending any gestures it completes to `out`
+    pub fn process(&mut self, event: &Event, out: &mut Vec<Event>) {
+        match &event.data {
+            EventData::MouseMove(move_event) => {
+                let previous = self.position;
+                self.position = (move_event.x, move_event.y);
+
+                let Some(held) = self.held else { return };
+                if self.dragging {
+                    out.push(self.drag_event(held.button, DragPhase::Update, previous));
+                } else if distance(held.position, self.position) > self.config.drag_threshold {
+                    trace!("Drag started with {:?}", held.button);
+                    self.dragging = true;
+                    // A drag is not a click
+                    self.last_click = None;
+                    out.push(self.drag_event(held.button, DragPhase::Start, held.position));
+                }
+            }
+            EventData::MouseButton(button_event) => match button_event.action {
+                KeyAction::Press => {
+                    let press = Press {
+                        button: button_event.button,
+                        position: self.position,
+                        timestamp: event.timestamp,
+                    };
+
+                    if let Some(first) = self.last_click.take().filter(|first| self.completes_double_click(first, &press)) {
+                        trace!("Double click with {:?}", first.button);
+                        out.push(Event::new(EventData::MouseDoubleClick(MouseDoubleClickEvent {
+                            button: press.button,
+                            x: press.position.0,
+                            y: press.position.1,
+                            mods: button_event.mods.clone(),
+                        })));
+                    } else {
+                        self.last_click = Some(press);
+                    }
+
+                    if self.held.is_none() {
+                        self.held = Some(press);
+                    }
+                }
+                KeyAction::Release => {
+                    if self.held.is_some_and(|held| held.button == button_event.button) {
+                        if self.dragging {
+                            out.push(self.drag_event(button_event.button, DragPhase::End, self.position));
+                        }
+                        self.held = None;
+                        self.dragging = false;
+                    }
+                }
+                KeyAction::Repeat => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn completes_double_click(&self, first: &Press, second: &Press) -> bool {
+        first.button == second.button
+            && second.timestamp.saturating_sub(first.timestamp) <= self.config.double_click_time.as_millis() as u64
+            && distance(first.position, second.position) <= self.config.drag_threshold
+    }
+
+    fn drag_event(&self, button: MouseButton, phase: DragPhase, from: (f64, f64)) -> Event {
+        Event::new(EventData::MouseDrag(MouseDragEvent {
+            button,
+            phase,
+            x: self.position.0,
+            y: self.position.1,
+            delta_x: self.position.0 - from.0,
+            delta_y: self.position.1 - from.1,
+        }))
+    }
+}
+
+/// Thresholds for pinch, swipe and long-press recognition
+#[derive(Debug, Clone)]
+pub struct GestureRecognizerConfig {
+    /// How long a button has to be held in place for a long press
+    pub long_press_time: Duration,
+    /// Pixels the cursor may wander during a long press
+    pub long_press_tolerance: f64,
+    /// Shortest press-to-release distance in pixels that counts as a swipe
+    pub swipe_min_distance: f64,
+    /// Slowest average speed in pixels per second that counts as a swipe
+    pub swipe_min_velocity: f64,
+    /// Zoom factor per scroll unit with Control held
+    pub pinch_scale_per_scroll: f64,
+}
+
+impl Default for GestureRecognizerConfig {
+    fn default() -> Self {
+        Self {
+            long_press_time: Duration::from_millis(600),
+            long_press_tolerance: 8.0,
+            swipe_min_distance: 60.0,
+            swipe_min_velocity: 400.0,
+            pinch_scale_per_scroll: 0.1,
+        }
+    }
+}
+
+/// State of the button currently held, for swipes and long presses
+#[derive(Debug, Clone, Copy)]
+struct Hold {
+    press: Press,
+    /// Moved beyond the long-press tolerance
+    moved: bool,
+    long_press_sent: bool,
+}
+
+/// Recognizes pinch, swipe and long-press gestures in mouse and keyboard events
+///
+/// Long presses fire while the button is still held, so `poll` has to be
+/// called regularly (the input manager does so once per frame) with the
+/// current time in the same milliseconds as `Event::timestamp`.
+#[derive(Debug, Default)]
+pub struct GestureRecognizer {
+    config: GestureRecognizerConfig,
+    position: (f64, f64),
+    control_held: bool,
+    held: Option<Hold>,
+}
+
+impl GestureRecognizer {
+    pub fn new(config: GestureRecognizerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> &GestureRecognizerConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: GestureRecognizerConfig) {
+        self.config = config;
+    }
+
+    /// Feed one raw event, appending any gestures it completes to `out`
+    pub fn process(&mut self, event: &Event, out: &mut Vec<Event>) {
+        match &event.data {
+            EventData::Key(key_event) if matches!(key_event.key, KeyCode::LeftControl | KeyCode::RightControl) => {
+                self.control_held = key_event.action != KeyAction::Release;
+            }
+            EventData::MouseScroll(scroll) if self.control_held && scroll.y_offset != 0.0 => {
+                let scale = (1.0 + self.config.pinch_scale_per_scroll).powf(scroll.y_offset);
+                trace!("Pinch by {:.3}", scale);
+                out.push(Event::new(EventData::Pinch(PinchEvent {
+                    scale,
+                    x: self.position.0,
+                    y: self.position.1,
+                })));
+            }
+            EventData::MouseMove(move_event) => {
+                self.position = (move_event.x, move_event.y);
+                if let Some(hold) = self.held.as_mut() {
+                    hold.moved |= distance(hold.press.position, self.position) > self.config.long_press_tolerance;
+                }
+            }
+            EventData::MouseButton(button_event) => match button_event.action {
+                KeyAction::Press if self.held.is_none() => {
+                    self.held = Some(Hold {
+                        press: Press {
+                            button: button_event.button,
+                            position: self.position,
+                            timestamp: event.timestamp,
+                        },
+                        moved: false,
+                        long_press_sent: false,
+                    });
+                }
+                KeyAction::Release => {
+                    if let Some(hold) = self.held.filter(|hold| hold.press.button == button_event.button) {
+                        self.held = None;
+                        if let Some(swipe) = self.swipe(&hold.press, event.timestamp) {
+                            out.push(Event::new(EventData::Swipe(swipe)));
+                        }
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// Emit a long press if the held button has been still for long enough
+    pub fn poll(&mut self, now: u64, out: &mut Vec<Event>) {
+        let long_press_ms = self.config.long_press_time.as_millis() as u64;
+        let Some(hold) = self.held.as_mut() else { return };
+        if hold.moved || hold.long_press_sent || now.saturating_sub(hold.press.timestamp) < long_press_ms {
+            return;
+        }
+
+        hold.long_press_sent = true;
+        trace!("Long press with {:?}", hold.press.button);
+        out.push(Event::new(EventData::LongPress(LongPressEvent {
+            button: hold.press.button,
+            x: hold.press.position.0,
+            y: hold.press.position.1,
+        })));
+    }
+
+    fn swipe(&self, press: &Press, released_at: u64) -> Option<SwipeEvent> {
+        let (dx, dy) = (self.position.0 - press.position.0, self.position.1 - press.position.1);
+        let distance = dx.hypot(dy);
+        // Releases within the same millisecond count as one
+        let seconds = released_at.saturating_sub(press.timestamp).max(1) as f64 / 1000.0;
+        let velocity = distance / seconds;
+        if distance < self.config.swipe_min_distance || velocity < self.config.swipe_min_velocity {
+            return None;
+        }
+
+        let direction = match (dx.abs() >= dy.abs(), dx >= 0.0, dy >= 0.0) {
+            (true, true, _) => SwipeDirection::Right,
+            (true, false, _) => SwipeDirection::Left,
+            (false, _, true) => SwipeDirection::Down,
+            (false, _, false) => SwipeDirection::Up,
+        };
+        Some(SwipeEvent {
+            button: press.button,
+            direction,
+            distance,
+            velocity,
+        })
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::core::{KeyEvent, KeyMod, MouseButtonEvent, MouseMoveEvent, MouseScrollEvent};
+
+    fn at(data: EventData, timestamp: u64) -> Event {
+        Event { timestamp, ..Event::new(data) }
+    }
+
+    fn button(action: KeyAction, timestamp: u64) -> Event {
+        at(EventData::MouseButton(MouseButtonEvent { button: MouseButton::Left, action, mods: KeyMod::new() }), timestamp)
+    }
+
+    fn move_to(x: f64, y: f64) -> Event {
+        Event::new(EventData::MouseMove(MouseMoveEvent { x, y }))
+    }
+
+    #[test]
+    fn test_double_click_and_drag() {
+        let mut gestures = GestureSynthesizer::new(GestureConfig::default());
+        let mut out = Vec::new();
+        let mut feed = |gestures: &mut GestureSynthesizer, event: Event| {
+            out.clear();
+            gestures.process(&event, &mut out);
+            out.iter().map(|e| e.data.clone()).collect::<Vec<_>>()
+        };
+
+        feed(&mut gestures, move_to(10.0, 10.0));
+        assert!(feed(&mut gestures, button(KeyAction::Press, 1000)).is_empty());
+        feed(&mut gestures, button(KeyAction::Release, 1050));
+        let second = feed(&mut gestures, button(KeyAction::Press, 1200));
+        assert!(matches!(&second[..], [EventData::MouseDoubleClick(click)] if click.x == 10.0));
+        feed(&mut gestures, button(KeyAction::Release, 1250));
+
+        // Too slow for a double click
+        feed(&mut gestures, button(KeyAction::Press, 3000));
+        feed(&mut gestures, button(KeyAction::Release, 3050));
+        assert!(feed(&mut gestures, button(KeyAction::Press, 3600)).is_empty());
+
+        // Movement within the threshold is not a drag yet
+        assert!(feed(&mut gestures, move_to(12.0, 10.0)).is_empty());
+        let start = feed(&mut gestures, move_to(20.0, 10.0));
+        assert!(matches!(&start[..], [EventData::MouseDrag(drag)] if drag.phase == DragPhase::Start && drag.delta_x == 10.0));
+        let update = feed(&mut gestures, move_to(20.0, 15.0));
+        assert!(matches!(&update[..], [EventData::MouseDrag(drag)] if drag.phase == DragPhase::Update && drag.delta_y == 5.0));
+        let end = feed(&mut gestures, button(KeyAction::Release, 4000));
+        assert!(matches!(&end[..], [EventData::MouseDrag(drag)] if drag.phase == DragPhase::End));
+
+        // Moving without a button held does nothing
+        assert!(feed(&mut gestures, move_to(50.0, 50.0)).is_empty());
+    }
+
+    #[test]
+    fn test_pinch_swipe_and_long_press() {
+        let mut gestures = GestureRecognizer::new(GestureRecognizerConfig::default());
+        let mut out = Vec::new();
+        let control = |action| EventData::Key(KeyEvent { key: KeyCode::LeftControl, action, mods: KeyMod::new() });
+        let scroll = |y_offset| Event::new(EventData::MouseScroll(MouseScrollEvent { x_offset: 0.0, y_offset }));
+
+        // Scrolling only pinches with Control held
+        gestures.process(&scroll(1.0), &mut out);
+        assert!(out.is_empty());
+        gestures.process(&Event::new(control(KeyAction::Press)), &mut out);
+        gestures.process(&scroll(-2.0), &mut out);
+        let pinch = out.pop().unwrap();
+        assert!((pinch.as_pinch_event().unwrap().scale - 1.1f64.powi(-2)).abs() < 1e-9);
+        gestures.process(&Event::new(control(KeyAction::Release)), &mut out);
+
+        // 100px left in 100ms is a swipe
+        gestures.process(&move_to(200.0, 100.0), &mut out);
+        gestures.process(&button(KeyAction::Press, 1000), &mut out);
+        gestures.process(&move_to(100.0, 110.0), &mut out);
+        gestures.poll(2000, &mut out);
+        assert!(out.is_empty(), "a moved press is not a long press");
+        gestures.process(&button(KeyAction::Release, 1100), &mut out);
+        let swipe = out.pop().unwrap();
+        assert_eq!(swipe.as_swipe_event().unwrap().direction, SwipeDirection::Left);
+
+        // The same distance over two seconds is not
+        gestures.process(&button(KeyAction::Press, 5000), &mut out);
+        gestures.process(&move_to(100.0, 10.0), &mut out);
+        gestures.process(&button(KeyAction::Release, 7000), &mut out);
+        assert!(out.is_empty());
+
+        // Holding still fires one long press
+        gestures.process(&button(KeyAction::Press, 10_000), &mut out);
+        gestures.poll(10_300, &mut out);
+        assert!(out.is_empty());
+        gestures.poll(10_600, &mut out);
+        gestures.poll(11_000, &mut out);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].as_long_press_event().unwrap().y, 10.0);
+    }
+}
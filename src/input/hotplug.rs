@@ -0,0 +1,155 @@
+use crate::events::core::{Event, EventData, GamepadConnectionEvent};
+use crate::input::gamepad::GamepadInfo;
+use artifice_logging::info;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Turns periodic scans of the attached gamepads into connection events
+///
+/// Window backends own one of these and feed it whatever their platform
+/// reports, so `GamepadConnectionEvent`s look the same on every backend.
+/// Gamepads already attached when the first scan runs are reported as
+/// connected then.
+#[derive(Debug)]
+pub struct GamepadHotplug {
+    known: BTreeMap<u32, GamepadInfo>,
+    interval: Duration,
+    last_scan: Option<Instant>,
+}
+
+impl GamepadHotplug {
+    /// Scan at most once every `interval`
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            known: BTreeMap::new(),
+            interval,
+            last_scan: None,
+        }
+    }
+
+    /// Run `scan` if the interval has passed and return events for gamepads that came or went
+    pub fn poll(&mut self, scan: impl FnOnce() -> Vec<GamepadInfo>) -> Vec<Event> {
+        let now = Instant::now();
+        if self.last_scan.is_some_and(|last| now.duration_since(last) < self.interval) {
+            return Vec::new();
+        }
+        self.last_scan = Some(now);
+        self.update(scan())
+    }
+
+    /// Compare `present` with the previous scan and return the connection events
+    pub fn update(&mut self, present: Vec<GamepadInfo>) -> Vec<Event> {
+        let mut present: BTreeMap<u32, GamepadInfo> = present.into_iter().map(|gamepad| (gamepad.id, gamepad)).collect();
+        let mut events = Vec::new();
+
+        for (id, gamepad) in &self.known {
+            // A different device in the same slot counts as a disconnect and a connect
+            if present.get(id).is_none_or(|now| now.guid != gamepad.guid) {
+                info!("Gamepad {} disconnected: {}", id, gamepad.name);
+                events.push(connection_event(gamepad, false));
+            }
+        }
+        for (id, gamepad) in &present {
+            if self.known.get(id).is_none_or(|before| before.guid != gamepad.guid) {
+                info!("Gamepad {} connected: {}", id, gamepad.name);
+                events.push(connection_event(gamepad, true));
+            }
+        }
+
+        std::mem::swap(&mut self.known, &mut present);
+        events
+    }
+
+    /// Gamepads seen by the latest scan
+    pub fn gamepads(&self) -> impl Iterator<Item = &GamepadInfo> {
+        self.known.values()
+    }
+}
+
+impl Default for GamepadHotplug {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500))
+    }
+}
+
+fn connection_event(gamepad: &GamepadInfo, connected: bool) -> Event {
+    Event::new(EventData::GamepadConnection(GamepadConnectionEvent {
+        gamepad_id: gamepad.id,
+        connected,
+        name: gamepad.name.clone(),
+        guid: gamepad.guid.clone(),
+        battery: gamepad.battery,
+    }))
+}
+
+/// Gamepads exposed by the Linux joystick driver as `/sys/class/input/jsN`
+///
+/// Used by the backends without a joystick API of their own. GUIDs follow
+/// the SDL layout that GLFW also uses, so mappings keyed by GUID carry over.
+#[cfg(target_os = "linux")]
+pub fn scan_linux_joysticks() -> Vec<GamepadInfo> {
+    use std::fs;
+    use std::path::Path;
+
+    let read = |path: &Path| fs::read_to_string(path).ok().map(|text| text.trim().to_string());
+    let Ok(entries) = fs::read_dir("/sys/class/input") else { return Vec::new() };
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let id = entry.file_name().to_str()?.strip_prefix("js")?.parse().ok()?;
+            let device = entry.path().join("device");
+            let name = read(&device.join("name")).unwrap_or_else(|| format!("Joystick {}", id));
+
+            let id_field = |field: &str| read(&device.join("id").join(field)).and_then(|hex| u16::from_str_radix(&hex, 16).ok());
+            let guid = match (id_field("bustype"), id_field("vendor"), id_field("product"), id_field("version")) {
+                (Some(bus), Some(vendor), Some(product), Some(version)) => Some(
+                    [bus, vendor, product, version]
+                        .iter()
+                        .map(|field| format!("{:02x}{:02x}0000", field & 0xff, field >> 8))
+                        .collect(),
+                ),
+                _ => None,
+            };
+
+            // HID drivers that know the battery expose it on the parent device
+            let battery = fs::read_dir(device.join("device").join("power_supply"))
+                .ok()
+                .and_then(|mut supplies| supplies.find_map(|supply| read(&supply.ok()?.path().join("capacity"))))
+                .and_then(|capacity| capacity.parse().ok());
+
+            Some(GamepadInfo { id, name, guid, battery })
+        })
+        .collect()
+}
+
+/// Gamepads exposed by the Linux joystick driver; none elsewhere
+#[cfg(not(target_os = "linux"))]
+pub fn scan_linux_joysticks() -> Vec<GamepadInfo> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pad(id: u32, guid: &str) -> GamepadInfo {
+        GamepadInfo { id, name: format!("pad {}", id), guid: Some(guid.to_string()), battery: None }
+    }
+
+    #[test]
+    fn test_hotplug_reports_changes_between_scans() {
+        let mut hotplug = GamepadHotplug::new(Duration::from_secs(3600));
+        let changes = |events: Vec<Event>| -> Vec<(u32, bool)> {
+            events.iter().filter_map(|event| event.as_gamepad_connection_event()).map(|c| (c.gamepad_id, c.connected)).collect()
+        };
+
+        assert_eq!(changes(hotplug.poll(|| vec![pad(0, "a")])), [(0, true)]);
+        // Throttled until the interval passes
+        assert!(hotplug.poll(|| unreachable!()).is_empty());
+
+        assert_eq!(changes(hotplug.update(vec![pad(0, "a"), pad(1, "b")])), [(1, true)]);
+        assert_eq!(changes(hotplug.update(vec![pad(1, "c")])), [(0, false), (1, false), (1, true)]);
+        assert_eq!(hotplug.gamepads().map(|gamepad| gamepad.guid.as_deref()).collect::<Vec<_>>(), [Some("c")]);
+    }
+}
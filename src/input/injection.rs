@@ -0,0 +1,60 @@
+use crate::events::core::{Event, EventData, EventQueue, EventSequencer, KeyAction, KeyCode, KeyEvent, KeyMod};
+use artifice_logging::{trace, warn};
+use std::sync::Arc;
+
+/// Cloneable, thread-safe handle for feeding synthetic input to an `InputManager`
+///
+/// Injected events are stamped and queued exactly like the window's, so they
+/// update polled state, trigger actions, gestures and combos, and pass the
+/// engine's filters from the next `process_events` on. Headless tests and
+/// bots can drive an application without a real keyboard or gamepad.
+///
+/// ```ignore
+/// let injector = engine.input_injector();
+/// std::thread::spawn(move || {
+///     injector.tap_key(KeyCode::Space);
+///     injector.inject(EventData::MouseMove(MouseMoveEvent { x: 10.0, y: 20.0 }));
+/// });
+/// ```
+#[derive(Clone)]
+pub struct InputInjector {
+    queue: Arc<EventQueue>,
+    sequencer: Arc<EventSequencer>,
+}
+
+impl InputInjector {
+    pub fn new(queue: Arc<EventQueue>, sequencer: Arc<EventSequencer>) -> Self {
+        Self { queue, sequencer }
+    }
+
+    /// Queue an event built from `data`; gives it back if the queue is full
+    pub fn inject(&self, data: EventData) -> Result<(), Event> {
+        self.inject_event(Event::new(data))
+    }
+
+    /// Queue `event` as is, apart from its sequence number
+    pub fn inject_event(&self, mut event: Event) -> Result<(), Event> {
+        self.sequencer.stamp(&mut event);
+        trace!("Injecting {:?}", event.data);
+        self.queue.try_push(event).inspect_err(|rejected| {
+            warn!("Event queue full, dropping injected event: {:?}", rejected);
+        })
+    }
+
+    /// Queue a press and a release of `key`, which land in the same frame
+    pub fn tap_key(&self, key: KeyCode) -> Result<(), Event> {
+        self.inject_key(key, KeyAction::Press)?;
+        self.inject_key(key, KeyAction::Release)
+    }
+
+    /// Queue a key press or release without modifiers
+    pub fn inject_key(&self, key: KeyCode, action: KeyAction) -> Result<(), Event> {
+        self.inject(EventData::Key(KeyEvent { key, action, mods: KeyMod::new() }))
+    }
+}
+
+impl std::fmt::Debug for InputInjector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InputInjector").finish_non_exhaustive()
+    }
+}
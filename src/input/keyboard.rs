@@ -1,5 +1,5 @@
 use crate::events::core::{Event, EventType, KeyAction, KeyCode, KeyEvent, KeyMod};
-use crate::input::InputDevice;
+use crate::input::{ButtonState, InputDevice};
 use artifice_logging::{debug, error, info, trace, warn};
 use std::collections::{HashMap, HashSet};
 
@@ -9,6 +9,8 @@ pub struct Keyboard {
     pressed_keys: HashSet<KeyCode>,
     released_keys: HashSet<KeyCode>,
     key_mods: KeyMod,
+    /// Labels from the current keyboard layout, overriding the default names
+    key_names: HashMap<KeyCode, String>,
     is_connected: bool,
 }
 
@@ -20,6 +22,7 @@ impl Keyboard {
             pressed_keys: HashSet::new(),
             released_keys: HashSet::new(),
             key_mods: KeyMod::new(),
+            key_names: HashMap::new(),
             is_connected: true,
         }
     }
@@ -62,18 +65,108 @@ impl Keyboard {
         self.released_keys.contains(&key)
     }
 
+    /// State of a key this frame
+    pub fn state(&self, key: KeyCode) -> ButtonState {
+        ButtonState::from_frame(
+            self.is_key_just_pressed(key),
+            self.is_key_just_released(key),
+            self.is_key_pressed(key),
+        )
+    }
+
+    /// Check if a key went down this frame or is held from an earlier one
+    pub fn is_down(&self, key: KeyCode) -> bool {
+        self.state(key).is_down()
+    }
+
+    /// Check if a key went down this frame
+    pub fn just_pressed(&self, key: KeyCode) -> bool {
+        self.state(key) == ButtonState::Pressed
+    }
+
+    /// Check if a key has been down since an earlier frame
+    pub fn is_held(&self, key: KeyCode) -> bool {
+        self.state(key) == ButtonState::Held
+    }
+
+    /// Check if a key went up this frame
+    pub fn just_released(&self, key: KeyCode) -> bool {
+        self.is_key_just_released(key)
+    }
+
+    /// Keys currently held, in no particular order
+    pub fn keys_down(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.key_states.keys().copied().filter(|key| self.is_key_pressed(*key))
+    }
+
     /// Get the current key modifiers state
     pub fn get_key_mods(&self) -> &KeyMod {
         &self.key_mods
     }
 
-    /// Clear the per-frame state (called at the end of each frame)
+    /// Name of `key` for display, e.g. in a list of bindings
+    ///
+    /// Printable keys use the label of the current keyboard layout when the
+    /// window backend reported one; other keys have a fixed English name.
+    pub fn key_name(&self, key: KeyCode) -> String {
+        self.key_names.get(&key).cloned().unwrap_or_else(|| default_key_name(key))
+    }
+
+    /// Replace the layout labels used by `key_name`, as reported by `Window::key_names`
+    pub fn set_key_names(&mut self, names: HashMap<KeyCode, String>) {
+        debug!("Keyboard layout names {} keys", names.len());
+        self.key_names = names;
+    }
+
+    /// Clear the per-frame state (called before each frame's events are processed)
     pub fn clear_frame_state(&mut self) {
         self.pressed_keys.clear();
         self.released_keys.clear();
     }
 }
 
+/// Layout-independent name of `key`, such as "Left Shift" or "Keypad 5"
+fn default_key_name(key: KeyCode) -> String {
+    let fixed = match key {
+        KeyCode::Apostrophe => "'",
+        KeyCode::Comma => ",",
+        KeyCode::Minus => "-",
+        KeyCode::Period => ".",
+        KeyCode::Slash => "/",
+        KeyCode::Semicolon => ";",
+        KeyCode::Equal => "=",
+        KeyCode::LeftBracket => "[",
+        KeyCode::Backslash => "\\",
+        KeyCode::RightBracket => "]",
+        KeyCode::GraveAccent => "`",
+        KeyCode::World1 => "World 1",
+        KeyCode::World2 => "World 2",
+        _ => "",
+    };
+    if !fixed.is_empty() {
+        return fixed.to_string();
+    }
+
+    // Split the variant name into words: "PageUp" -> "Page Up", "KPAdd" -> "Keypad Add"
+    let debug = format!("{:?}", key);
+    let debug = match debug.strip_prefix("Num") {
+        Some(digit) if digit.starts_with(|c: char| c.is_ascii_digit()) => digit,
+        _ => &debug,
+    };
+    let (prefix, rest) = match debug.strip_prefix("KP") {
+        Some(rest) => ("Keypad ", rest),
+        None => ("", debug),
+    };
+    let mut name = prefix.to_string();
+    for (i, character) in rest.char_indices() {
+        if i > 0 && character.is_uppercase() {
+            name.push(' ');
+        }
+        name.push(character);
+    }
+    name
+}
+
 impl InputDevice for Keyboard {
     fn update(&mut self) {
         self.clear_frame_state();
@@ -235,3 +328,19 @@ pub mod key_translation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_names_prefer_the_layout_label() {
+        let mut keyboard = Keyboard::new();
+        let names = |keyboard: &Keyboard, keys: &[KeyCode]| -> Vec<String> { keys.iter().map(|key| keyboard.key_name(*key)).collect() };
+        let keys = [KeyCode::Semicolon, KeyCode::Num1, KeyCode::NumLock, KeyCode::LeftShift, KeyCode::KP5, KeyCode::KPAdd, KeyCode::F11];
+        assert_eq!(names(&keyboard, &keys), [";", "1", "Num Lock", "Left Shift", "Keypad 5", "Keypad Add", "F11"]);
+
+        keyboard.set_key_names(HashMap::from([(KeyCode::Semicolon, "Ö".to_string())]));
+        assert_eq!(keyboard.key_name(KeyCode::Semicolon), "Ö");
+    }
+}
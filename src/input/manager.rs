@@ -1,14 +1,35 @@
+use std::collections::HashMap;
 use std::sync::Arc;
-use crate::events::core::{Event, EventData, EventQueue};
-use crate::input::{InputDevice, keyboard::Keyboard, mouse::Mouse, gamepad::GamepadManager};
+use crate::events::core::{Event, EventData, EventQueue, EventQueueConfig, EventSequencer, EventSource, EventType};
+use crate::input::{InputDevice, keyboard::Keyboard, mouse::{Mouse, MouseSettings}, gamepad::GamepadManager};
+use crate::input::actions::ActionMap;
+use crate::input::combos::ComboDetector;
+use crate::input::injection::InputInjector;
+use crate::input::players::PlayerInputRouter;
+use crate::input::recording::{InputPlayer, InputRecorder, InputRecording};
+use crate::input::snapshot::{GamepadSnapshot, InputSnapshot};
+use crate::input::gestures::{GestureConfig, GestureRecognizer, GestureRecognizerConfig, GestureSynthesizer};
 use crate::events::{GamepadButton, GamepadAxis};
-use artifice_logging::{debug, trace, warn};
+use artifice_logging::{debug, info, trace, warn};
 
 /// Centralized input state manager
 pub struct InputManager {
     keyboard: Keyboard,
     mouse: Mouse,
     gamepad: GamepadManager,
+    gestures: GestureSynthesizer,
+    recognizer: GestureRecognizer,
+    actions: ActionMap,
+    combos: ComboDetector,
+    players: PlayerInputRouter,
+    recorder: Option<InputRecorder>,
+    /// Recording being replayed in place of live input
+    player: Option<InputPlayer>,
+    sequencer: Arc<EventSequencer>,
+    /// Sequence number expected next from each device
+    next_sequence: HashMap<EventSource, u64>,
+    /// Gaps found since the last `take_missed_events`
+    missed_events: u64,
     event_queue: Arc<EventQueue>,
 }
 
@@ -19,6 +40,16 @@ impl InputManager {
             keyboard: Keyboard::new(),
             mouse: Mouse::new(),
             gamepad: GamepadManager::new(),
+            gestures: GestureSynthesizer::default(),
+            recognizer: GestureRecognizer::default(),
+            actions: ActionMap::new(),
+            combos: ComboDetector::new(),
+            players: PlayerInputRouter::default(),
+            recorder: None,
+            player: None,
+            sequencer: Arc::new(EventSequencer::new()),
+            next_sequence: HashMap::new(),
+            missed_events: 0,
             event_queue: Arc::new(EventQueue::new(1024)), // Configurable size
         }
     }
@@ -29,17 +60,78 @@ impl InputManager {
             keyboard: Keyboard::new(),
             mouse: Mouse::new(),
             gamepad: GamepadManager::new(),
+            gestures: GestureSynthesizer::default(),
+            recognizer: GestureRecognizer::default(),
+            actions: ActionMap::new(),
+            combos: ComboDetector::new(),
+            players: PlayerInputRouter::default(),
+            recorder: None,
+            player: None,
+            sequencer: Arc::new(EventSequencer::new()),
+            next_sequence: HashMap::new(),
+            missed_events: 0,
             event_queue: Arc::new(EventQueue::new(queue_size)),
         }
     }
+
+    pub fn with_queue_config(config: EventQueueConfig) -> Self {
+        debug!("Creating input manager with event queue config: {:?}", config);
+        InputManager {
+            keyboard: Keyboard::new(),
+            mouse: Mouse::new(),
+            gamepad: GamepadManager::new(),
+            gestures: GestureSynthesizer::default(),
+            recognizer: GestureRecognizer::default(),
+            actions: ActionMap::new(),
+            combos: ComboDetector::new(),
+            players: PlayerInputRouter::default(),
+            recorder: None,
+            player: None,
+            sequencer: Arc::new(EventSequencer::new()),
+            next_sequence: HashMap::new(),
+            missed_events: 0,
+            event_queue: Arc::new(EventQueue::from_config(config)),
+        }
+    }
     
     /// Process all queued events and update input device states
+    ///
+    /// Events stamped by the shared `EventSequencer` come out in their device's
+    /// order even if several producers queued them out of order. Double-click,
+    /// drag, pinch and swipe events are inserted right after the raw event that
+    /// completes them, as are chord and combo events; long presses come last.
+    /// Gamepad axis events carry the value shaped by the gamepad's smoothing,
+    /// dead zone, response curve and inversion settings; `raw_axis_value` keeps the input.
+    /// Raw mouse motion events likewise carry the delta scaled by the mouse settings.
+    /// Events from devices assigned to a player carry that player's ID. While the action map is listening
+    /// for a new binding, the press it captures is marked handled.
+    /// While a recording plays, its events replace live keyboard, mouse and
+    /// gamepad input; window events still come through. While recording, the
+    /// raw input events are captured before any of the above is applied.
     pub fn process_events(&mut self) -> Vec<Event> {
         let mut processed_events = Vec::new();
-        
+        let mut gestures = Vec::new();
+
+        let mut queued: Vec<Event> = std::iter::from_fn(|| self.event_queue.try_pop()).collect();
+        self.restore_device_order(&mut queued);
+
+        if let Some(player) = self.player.as_mut() {
+            queued.retain(|event| !is_input(event));
+            queued.extend(player.get_current_events());
+            if !player.is_playing() {
+                info!("Playback finished, live input resumes");
+                self.player = None;
+            }
+        }
+        if let Some(recorder) = self.recorder.as_mut() {
+            for event in &queued {
+                recorder.record_event(event);
+            }
+        }
+
         // Process all queued events
-        while let Some(event) = self.event_queue.try_pop() {
-            match &event.data {
+        for mut event in queued {
+            match &mut event.data {
                 EventData::Key(key_event) => {
                     self.keyboard.process_key_event(
                         key_event.key,
@@ -60,6 +152,11 @@ impl InputManager {
                     );
                     trace!("Processed mouse button event: {:?}", button_event);
                 }
+                EventData::MouseRawMotion(motion_event) => {
+                    (motion_event.delta_x, motion_event.delta_y) =
+                        self.mouse.process_raw_motion_event(motion_event.delta_x, motion_event.delta_y);
+                    trace!("Processed raw mouse motion: ({:.1}, {:.1})", motion_event.delta_x, motion_event.delta_y);
+                }
                 EventData::MouseScroll(scroll_event) => {
                     self.mouse.process_scroll_event(scroll_event.x_offset, scroll_event.y_offset);
                     trace!("Processed mouse scroll event: ({:.1}, {:.1})", scroll_event.x_offset, scroll_event.y_offset);
@@ -83,6 +180,10 @@ impl InputManager {
                         internal_axis,
                         axis_event.value,
                     );
+                    // Hand out the value with the gamepad's dead zone and curve applied
+                    if let Some(gamepad) = self.gamepad.gamepad(axis_event.gamepad_id) {
+                        axis_event.value = gamepad.axis_value(internal_axis);
+                    }
                     trace!("Processed gamepad axis event: {:?}", axis_event);
                 }
                 EventData::GamepadConnection(connection_event) => {
@@ -91,6 +192,10 @@ impl InputManager {
                         connection_event.connected,
                         connection_event.name.clone(),
                     );
+                    if let Some(gamepad) = self.gamepad.gamepad_mut(connection_event.gamepad_id) {
+                        gamepad.guid = connection_event.guid.clone();
+                        gamepad.battery = connection_event.battery;
+                    }
                     trace!("Processed gamepad connection event: {:?}", connection_event);
                 }
                 _ => {
@@ -99,19 +204,123 @@ impl InputManager {
                 }
             }
             
+            if self.actions.capture(&event).is_some() {
+                event.mark_handled();
+            }
+            self.players.route(&mut event, &mut gestures);
+
+            self.gestures.process(&event, &mut gestures);
+            self.recognizer.process(&event, &mut gestures);
+            if !event.handled {
+                self.combos.process(&event, &mut gestures);
+            }
             processed_events.push(event);
+            processed_events.append(&mut gestures);
         }
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        self.recognizer.poll(now, &mut processed_events);
         
         processed_events
     }
     
-    /// Update all input devices (should be called once per frame)
+    /// Sort each device's sequenced events back into order, keeping the slots
+    /// they occupy in the batch, and count sequence numbers that were skipped
+    fn restore_device_order(&mut self, events: &mut Vec<Event>) {
+        let mut slots_by_source: HashMap<EventSource, Vec<usize>> = HashMap::new();
+        for (index, event) in events.iter().enumerate() {
+            if let (Some(source), Some(_)) = (event.data.source(), event.sequence) {
+                slots_by_source.entry(source).or_default().push(index);
+            }
+        }
+
+        let mut order: Vec<usize> = (0..events.len()).collect();
+        for slots in slots_by_source.values() {
+            let mut sorted = slots.clone();
+            sorted.sort_by_key(|&index| events[index].sequence);
+            for (&slot, &index) in slots.iter().zip(&sorted) {
+                order[slot] = index;
+            }
+        }
+        let mut taken: Vec<Option<Event>> = events.drain(..).map(Some).collect();
+        events.extend(order.into_iter().filter_map(|index| taken[index].take()));
+
+        for event in events.iter() {
+            let (Some(source), Some(sequence)) = (event.data.source(), event.sequence) else { continue };
+            let expected = self.next_sequence.entry(source).or_insert(0);
+            if sequence > *expected {
+                warn!("{} events from {:?} missing before sequence {}", sequence - *expected, source, sequence);
+                self.missed_events += sequence - *expected;
+            }
+            if sequence >= *expected {
+                *expected = sequence + 1;
+            } else {
+                trace!("Late event from {:?} with sequence {}", source, sequence);
+            }
+        }
+    }
+
+    /// Number of input events found missing since the last call
+    pub fn take_missed_events(&mut self) -> u64 {
+        std::mem::take(&mut self.missed_events)
+    }
+
+    /// Sequencer that producers pushing into the event queue should stamp events with
+    pub fn get_sequencer(&self) -> Arc<EventSequencer> {
+        self.sequencer.clone()
+    }
+
+    /// Start a new input frame by clearing the previous frame's transitions
+    ///
+    /// Call once per frame before `process_events`, so the pressed and
+    /// released states it produces hold until the next frame starts.
     pub fn update(&mut self) {
         self.keyboard.update();
         self.mouse.update();
         self.gamepad.update();
     }
     
+    /// Capture the held keys and buttons, mouse and gamepad axes of this frame
+    ///
+    /// Gamepad axes carry their shaped values. Idle gamepads are left out, so
+    /// a snapshot only grows with what is actually held.
+    pub fn snapshot(&self) -> InputSnapshot {
+        let gamepads = self
+            .gamepad
+            .connected_gamepad_ids()
+            .into_iter()
+            .filter_map(|id| self.gamepad.gamepad(id))
+            .map(|gamepad| GamepadSnapshot {
+                id: gamepad.id,
+                buttons: gamepad.buttons_down().collect(),
+                axes: gamepad.axis_values().filter(|(_, value)| *value != 0.0).collect(),
+            });
+
+        InputSnapshot {
+            keys: self.keyboard.keys_down().collect(),
+            mods: *self.keyboard.get_key_mods(),
+            mouse_buttons: self.mouse.buttons_down().collect(),
+            mouse_position: self.mouse.position(),
+            raw_motion: self.mouse.raw_delta(),
+            scroll: self.mouse.scroll(),
+            gamepads: gamepads.collect(),
+        }
+        .normalized()
+    }
+
+    /// Get a handle for injecting synthetic input from any thread
+    pub fn injector(&self) -> InputInjector {
+        InputInjector::new(self.event_queue.clone(), self.sequencer.clone())
+    }
+
+    /// Queue a synthetic input event; it is processed like hardware input
+    pub fn inject(&self, data: EventData) -> Result<(), Event> {
+        self.injector().inject(data)
+    }
+
     /// Get a reference to the event queue for external event producers
     pub fn get_event_queue(&self) -> Arc<EventQueue> {
         self.event_queue.clone()
@@ -142,6 +351,108 @@ impl InputManager {
         &mut self.mouse
     }
     
+    /// Get the mouse sensitivity, acceleration and inversion
+    pub fn mouse_settings(&self) -> &MouseSettings {
+        self.mouse.settings()
+    }
+
+    /// Change how mouse deltas are scaled, e.g. from an options menu
+    pub fn set_mouse_settings(&mut self, settings: MouseSettings) {
+        self.mouse.set_settings(settings);
+    }
+
+    /// Get the double-click and drag detection settings
+    pub fn gesture_config(&self) -> &GestureConfig {
+        self.gestures.config()
+    }
+
+    /// Change the double-click time and drag threshold
+    pub fn set_gesture_config(&mut self, config: GestureConfig) {
+        debug!("Setting gesture config: {:?}", config);
+        self.gestures.set_config(config);
+    }
+
+    /// Get the pinch, swipe and long-press thresholds
+    pub fn gesture_recognizer_config(&self) -> &GestureRecognizerConfig {
+        self.recognizer.config()
+    }
+
+    /// Change the pinch, swipe and long-press thresholds
+    pub fn set_gesture_recognizer_config(&mut self, config: GestureRecognizerConfig) {
+        debug!("Setting gesture recognizer config: {:?}", config);
+        self.recognizer.set_config(config);
+    }
+
+    /// Get the action bindings
+    pub fn actions(&self) -> &ActionMap {
+        &self.actions
+    }
+
+    /// Get the action bindings for binding, rebinding or loading saved controls
+    pub fn actions_mut(&mut self) -> &mut ActionMap {
+        &mut self.actions
+    }
+
+    /// Get the chord and combo detector
+    pub fn combos(&self) -> &ComboDetector {
+        &self.combos
+    }
+
+    /// Get the chord and combo detector for registering chords and combos
+    pub fn combos_mut(&mut self) -> &mut ComboDetector {
+        &mut self.combos
+    }
+
+    /// Get the assignment of devices to local players
+    pub fn players(&self) -> &PlayerInputRouter {
+        &self.players
+    }
+
+    /// Get the player router for assigning devices or starting join mode
+    pub fn players_mut(&mut self) -> &mut PlayerInputRouter {
+        &mut self.players
+    }
+
+    /// Start capturing input events into a new recording, discarding any recording in progress
+    pub fn start_recording(&mut self, name: impl Into<String>) {
+        self.start_recording_with(InputRecorder::new(name));
+    }
+
+    /// Start recording into `recorder`, e.g. one set up with `InputRecorder::with_window`
+    pub fn start_recording_with(&mut self, mut recorder: InputRecorder) {
+        recorder.start_recording();
+        if self.recorder.replace(recorder).is_some() {
+            warn!("Discarding the unfinished input recording");
+        }
+    }
+
+    /// Finish the recording in progress, if any
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        self.recorder.take().map(InputRecorder::finish)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Replay `recording` in place of live input until it ends or `stop_playback` is called
+    pub fn play_recording(&mut self, recording: InputRecording) {
+        let mut player = InputPlayer::new(recording);
+        player.start_playback();
+        self.player = Some(player);
+    }
+
+    /// Stop the playback and go back to live input
+    pub fn stop_playback(&mut self) {
+        if let Some(mut player) = self.player.take() {
+            player.stop_playback();
+        }
+    }
+
+    pub fn is_playing_back(&self) -> bool {
+        self.player.is_some()
+    }
+
     /// Get a mutable reference to the gamepad manager (for advanced usage)
     pub fn gamepad_mut(&mut self) -> &mut GamepadManager {
         &mut self.gamepad
@@ -228,4 +539,249 @@ impl Default for InputManager {
 pub struct InputQueueStats {
     pub is_empty: bool,
     pub is_full: bool,
-}
\ No newline at end of file
+}
+
+/// Whether `event` comes from a keyboard, mouse or gamepad
+fn is_input(event: &Event) -> bool {
+    matches!(event.event_type, EventType::Keyboard | EventType::Mouse | EventType::Gamepad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::core::{KeyAction, KeyCode, KeyEvent, KeyMod, MouseMoveEvent};
+    use crate::input::ButtonState;
+
+    #[test]
+    fn test_process_events_restores_device_order_and_counts_gaps() {
+        let mut input = InputManager::with_queue_size(16);
+        let queue = input.get_event_queue();
+        let sequencer = input.get_sequencer();
+
+        let stamped = |data| {
+            let mut event = Event::new(data);
+            sequencer.stamp(&mut event);
+            event
+        };
+        let key = |key| EventData::Key(KeyEvent { key, action: KeyAction::Press, mods: KeyMod::new() });
+
+        let a = stamped(key(KeyCode::A));
+        let motion = stamped(EventData::MouseMove(MouseMoveEvent { x: 1.0, y: 1.0 }));
+        let b = stamped(key(KeyCode::B));
+        let _dropped = stamped(key(KeyCode::C));
+        let d = stamped(key(KeyCode::D));
+
+        // A second producer got B in ahead of A
+        for event in [b, motion, a, d] {
+            queue.try_push(event).unwrap();
+        }
+
+        let events = input.process_events();
+        let keys: Vec<_> = events.iter().filter_map(|event| event.as_key_event()).map(|key| key.key).collect();
+        assert_eq!(keys, [KeyCode::A, KeyCode::B, KeyCode::D]);
+        assert!(events[1].as_mouse_move_event().is_some());
+        assert_eq!(input.take_missed_events(), 1);
+        assert_eq!(input.take_missed_events(), 0);
+    }
+
+    #[test]
+    fn test_polled_state_lasts_for_the_frame() {
+        let mut input = InputManager::with_queue_size(16);
+        let queue = input.get_event_queue();
+        let key = |key, action| Event::new(EventData::Key(KeyEvent { key, action, mods: KeyMod::new() }));
+        let motion = |x, y| Event::new(EventData::MouseMove(MouseMoveEvent { x, y }));
+
+        let frame = |input: &mut InputManager, events: Vec<Event>| {
+            input.update();
+            for event in events {
+                queue.try_push(event).unwrap();
+            }
+            input.process_events();
+        };
+
+        frame(&mut input, vec![key(KeyCode::W, KeyAction::Press), key(KeyCode::E, KeyAction::Press), key(KeyCode::E, KeyAction::Release), motion(2.0, 1.0), motion(5.0, 3.0)]);
+        assert!(input.keyboard().just_pressed(KeyCode::W) && input.keyboard().is_down(KeyCode::W));
+        // A tap within one frame is still seen as a press, then as a release
+        assert_eq!(input.keyboard().state(KeyCode::E), ButtonState::Pressed);
+        assert!(input.keyboard().just_released(KeyCode::E));
+        assert_eq!(input.mouse().position(), (5.0, 3.0));
+        assert_eq!(input.mouse().delta(), (5.0, 3.0));
+
+        frame(&mut input, vec![]);
+        assert_eq!(input.keyboard().state(KeyCode::W), ButtonState::Held);
+        assert_eq!(input.keyboard().state(KeyCode::E), ButtonState::Up);
+        assert_eq!(input.mouse().delta(), (0.0, 0.0));
+
+        frame(&mut input, vec![key(KeyCode::W, KeyAction::Release)]);
+        assert_eq!(input.keyboard().state(KeyCode::W), ButtonState::Released);
+        assert!(!input.keyboard().is_down(KeyCode::W));
+        assert_eq!(input.gamepad().axis(0, crate::input::GamepadAxis::LeftStickX), 0.0);
+    }
+
+    #[test]
+    fn test_raw_motion_kept_apart_from_cursor_position() {
+        use crate::events::core::MouseRawMotionEvent;
+
+        let mut input = InputManager::with_queue_size(16);
+        let queue = input.get_event_queue();
+        let raw = |delta_x, delta_y| Event::new(EventData::MouseRawMotion(MouseRawMotionEvent { delta_x, delta_y }));
+
+        for event in [raw(3.0, -1.0), raw(4.5, 2.0), Event::new(EventData::MouseMove(MouseMoveEvent { x: 10.0, y: 20.0 }))] {
+            queue.try_push(event).unwrap();
+        }
+        input.process_events();
+        assert_eq!(input.mouse().raw_delta(), (7.5, 1.0));
+        // A captured cursor still reports its position separately
+        assert_eq!(input.mouse().position(), (10.0, 20.0));
+        assert_eq!(input.mouse().delta(), (10.0, 20.0));
+
+        input.update();
+        input.process_events();
+        assert_eq!(input.mouse().raw_delta(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_axis_events_carry_shaped_values() {
+        use crate::events::core::{GamepadAxisEvent, GamepadConnectionEvent};
+        use crate::input::{AxisConfig, DeadZoneMode, ResponseCurve};
+        use crate::input::gamepad::GamepadAxis as Axis;
+
+        let mut input = InputManager::with_queue_size(16);
+        let queue = input.get_event_queue();
+        let axis = |axis, value| Event::new(EventData::GamepadAxis(GamepadAxisEvent { gamepad_id: 0, axis, value }));
+        let values = |input: &mut InputManager| -> Vec<f32> {
+            input.process_events().iter().filter_map(|event| event.as_gamepad_axis_event()).map(|axis| axis.value).collect()
+        };
+
+        queue.try_push(Event::new(EventData::GamepadConnection(GamepadConnectionEvent { gamepad_id: 0, connected: true, name: "pad".into(), guid: None, battery: None }))).unwrap();
+        input.process_events();
+        let pad = input.gamepad_mut().gamepad_mut(0).unwrap();
+        pad.set_deadzone(0.2);
+        pad.set_axis_config(Axis::LeftStickY, AxisConfig { inverted: true, curve: ResponseCurve::Exponential(2.0), ..Default::default() });
+
+        // Per axis, a small X is cut even while Y is pushed
+        queue.try_push(axis(GamepadAxis::LeftStickY, 0.6)).unwrap();
+        queue.try_push(axis(GamepadAxis::LeftStickX, 0.15)).unwrap();
+        let shaped = values(&mut input);
+        assert!((shaped[0] + 0.25).abs() < 1e-5, "{:?}", shaped);
+        assert_eq!(shaped[1], 0.0);
+        assert_eq!(input.gamepad().gamepad(0).unwrap().raw_axis_value(Axis::LeftStickY), 0.6);
+
+        // Radially the stick is well past the dead zone, so X keeps its share
+        input.gamepad_mut().gamepad_mut(0).unwrap().set_deadzone_mode(DeadZoneMode::Radial);
+        queue.try_push(axis(GamepadAxis::LeftStickX, 0.15)).unwrap();
+        let x = values(&mut input)[0];
+        assert!(x > 0.0 && x < 0.15, "{}", x);
+
+        // Radial dead zone swallows a small push in any direction
+        queue.try_push(axis(GamepadAxis::LeftStickY, 0.1)).unwrap();
+        queue.try_push(axis(GamepadAxis::LeftStickX, 0.1)).unwrap();
+        assert_eq!(values(&mut input), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_mouse_settings_scale_deltas() {
+        use crate::events::core::{MouseMoveEvent, MouseRawMotionEvent};
+        use crate::input::{MouseAcceleration, MouseSettings};
+
+        let mut input = InputManager::with_queue_size(16);
+        let queue = input.get_event_queue();
+        input.set_mouse_settings(MouseSettings {
+            sensitivity: 2.0,
+            acceleration: MouseAcceleration::Power { gain: 0.1, exponent: 1.0 },
+            invert_y: true,
+            ..Default::default()
+        });
+
+        // A 3-4-5 move is accelerated by 1.5 and doubled; the position stays on screen
+        queue.try_push(Event::new(EventData::MouseMove(MouseMoveEvent { x: 3.0, y: 4.0 }))).unwrap();
+        input.process_events();
+        assert_eq!(input.mouse().delta(), (9.0, -12.0));
+        assert_eq!(input.mouse().position(), (3.0, 4.0));
+
+        // Raw motion events carry the scaled delta
+        input.update();
+        queue.try_push(Event::new(EventData::MouseRawMotion(MouseRawMotionEvent { delta_x: 0.0, delta_y: 10.0 }))).unwrap();
+        let events = input.process_events();
+        let EventData::MouseRawMotion(motion) = &events[0].data else { panic!("{:?}", events) };
+        assert_eq!((motion.delta_x, motion.delta_y), (0.0, -40.0));
+        assert_eq!(input.mouse().raw_delta(), (0.0, -40.0));
+    }
+
+    #[test]
+    fn test_recording_and_playback_through_process_events() {
+        use crate::events::core::{KeyAction, KeyCode, KeyEvent, KeyMod, WindowCloseEvent};
+
+        let mut input = InputManager::with_queue_size(16);
+        let queue = input.get_event_queue();
+        let key = |key| Event::new(EventData::Key(KeyEvent { key, action: KeyAction::Press, mods: KeyMod::new() }));
+
+        input.start_recording("session");
+        queue.try_push(key(KeyCode::W)).unwrap();
+        queue.try_push(Event::new(EventData::WindowClose(WindowCloseEvent))).unwrap();
+        input.process_events();
+        let recording = input.stop_recording().unwrap();
+        assert_eq!(recording.events.len(), 1, "window events are not recorded");
+        assert!(!input.is_recording());
+
+        // Live keys are suppressed while the recording plays; window events still pass
+        input.play_recording(recording);
+        input.update();
+        queue.try_push(key(KeyCode::S)).unwrap();
+        queue.try_push(Event::new(EventData::WindowClose(WindowCloseEvent))).unwrap();
+        let events = input.process_events();
+        assert_eq!(events.len(), 2, "{:?}", events);
+        assert!(events.iter().any(|event| event.as_window_close_event().is_some()));
+        assert!(input.keyboard().just_pressed(KeyCode::W));
+        assert!(!input.keyboard().is_down(KeyCode::S));
+
+        // The one-event recording has ended, so live input is back
+        assert!(!input.is_playing_back());
+        queue.try_push(key(KeyCode::S)).unwrap();
+        input.process_events();
+        assert!(input.keyboard().is_down(KeyCode::S));
+    }
+
+    #[test]
+    fn test_injected_input_is_processed_like_hardware_input() {
+        let mut input = InputManager::with_queue_size(16);
+        let injector = input.injector();
+
+        std::thread::spawn(move || {
+            injector.inject_key(KeyCode::Space, KeyAction::Press).unwrap();
+            injector.inject(EventData::MouseMove(MouseMoveEvent { x: 3.0, y: 4.0 })).unwrap();
+        })
+        .join()
+        .unwrap();
+        input.inject(EventData::Key(KeyEvent { key: KeyCode::Space, action: KeyAction::Release, mods: KeyMod::new() })).unwrap();
+
+        input.update();
+        let events = input.process_events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events.iter().filter_map(|event| event.sequence).collect::<Vec<_>>(), [0, 0, 1]);
+        assert!(input.keyboard().just_pressed(KeyCode::Space));
+        assert_eq!(input.mouse().position(), (3.0, 4.0));
+        assert_eq!(input.take_missed_events(), 0);
+    }
+
+    #[test]
+    fn test_snapshot_captures_held_input() {
+        let mut input = InputManager::with_queue_size(16);
+        let injector = input.injector();
+        injector.inject_key(KeyCode::D, KeyAction::Press).unwrap();
+        injector.inject(EventData::MouseMove(MouseMoveEvent { x: 5.0, y: 6.0 })).unwrap();
+        input.update();
+        input.process_events();
+        let first = input.snapshot();
+        assert_eq!(first.keys, [KeyCode::D]);
+        assert_eq!(first.mouse_position, (5.0, 6.0));
+
+        injector.inject_key(KeyCode::D, KeyAction::Release).unwrap();
+        input.update();
+        input.process_events();
+        let second = input.snapshot();
+        assert!(second.keys.is_empty());
+        assert_eq!(second.diff(&first).keys_released, [KeyCode::D]);
+        assert_eq!(serde_json::from_str::<InputSnapshot>(&serde_json::to_string(&second).unwrap()).unwrap(), second);
+    }
+}
@@ -1,21 +1,74 @@
+pub mod actions;
+pub mod combos;
 pub mod gamepad;
+pub mod gestures;
+pub mod hotplug;
+pub mod injection;
 pub mod keyboard;
 pub mod mouse;
+pub mod players;
 pub mod manager;
 pub mod recording;
+pub mod smoothing;
+pub mod snapshot;
 
 // Re-export key types for easier access
+pub use actions::{ActionMap, InputBinding, RebindResult};
+pub use combos::{Chord, Combo, ComboDetector, ComboEvent, ComboKind};
 pub use gamepad::{
     GamepadManager, GamepadState, GamepadButton, GamepadAxis,
-    GamepadButtonEvent, GamepadAxisEvent, GamepadConnectionEvent
+    GamepadButtonEvent, GamepadAxisEvent, GamepadConnectionEvent,
+    AxisConfig, DeadZoneMode, GamepadInfo, ResponseCurve
 };
+pub use hotplug::GamepadHotplug;
+pub use injection::InputInjector;
+pub use gestures::{GestureConfig, GestureRecognizer, GestureRecognizerConfig, GestureSynthesizer};
 pub use keyboard::Keyboard;
-pub use mouse::Mouse;
+pub use mouse::{Mouse, MouseAcceleration, MouseSettings};
 pub use manager::{InputManager, InputQueueStats};
+pub use players::{PlayerInputRouter, PlayerJoined};
 pub use recording::{
     InputRecorder, InputPlayer, InputRecording, InputRecordingManager,
-    RecordedEvent, RecordingMetadata, SerializableEventData
+    RecordedEvent, RecordingError, RecordingFormat, RecordingMetadata, ENGINE_VERSION, RECORDING_FORMAT_VERSION
 };
+pub use smoothing::LowPassFilter;
+pub use snapshot::{GamepadSnapshot, InputSnapshot, InputSnapshotDiff};
+
+/// State of a key or button during the current frame
+///
+/// Transitions last for the whole frame in which their event was processed,
+/// so every layer sees the same answer whether it polls during event
+/// handling, update or render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ButtonState {
+    /// Not held
+    Up,
+    /// Went down this frame; a tap released within the same frame still reports this
+    Pressed,
+    /// Down since an earlier frame
+    Held,
+    /// Went up this frame
+    Released,
+}
+
+impl ButtonState {
+    /// Pressed this frame or held from earlier
+    pub fn is_down(self) -> bool {
+        matches!(self, ButtonState::Pressed | ButtonState::Held)
+    }
+
+    fn from_frame(just_pressed: bool, just_released: bool, down: bool) -> Self {
+        if just_pressed {
+            ButtonState::Pressed
+        } else if just_released {
+            ButtonState::Released
+        } else if down {
+            ButtonState::Held
+        } else {
+            ButtonState::Up
+        }
+    }
+}
 
 /// Input device trait for common functionality
 pub trait InputDevice {
@@ -2,9 +2,81 @@ use crate::events::core::{
     Event, EventType, KeyAction, KeyMod, MouseButton, MouseButtonEvent, MouseMoveEvent,
     MouseScrollEvent,
 };
-use crate::input::InputDevice;
+use crate::input::{ButtonState, InputDevice};
 use artifice_logging::{debug, error, info, trace, warn};
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+/// Software acceleration of mouse deltas
+///
+/// Speed is the length of one motion event's delta, in pixels or counts, so
+/// a fast flick travels further than the same distance moved slowly.
+#[derive(Clone, Default)]
+pub enum MouseAcceleration {
+    #[default]
+    None,
+    /// Multiply deltas by `1 + gain * speed^exponent`
+    Power { gain: f64, exponent: f64 },
+    /// Multiplier for a given speed
+    Custom(Arc<dyn Fn(f64) -> f64 + Send + Sync>),
+}
+
+impl MouseAcceleration {
+    /// Multiplier applied to a delta of length `speed`
+    pub fn factor(&self, speed: f64) -> f64 {
+        match self {
+            MouseAcceleration::None => 1.0,
+            MouseAcceleration::Power { gain, exponent } => 1.0 + gain * speed.powf(*exponent),
+            MouseAcceleration::Custom(curve) => curve(speed),
+        }
+    }
+}
+
+impl fmt::Debug for MouseAcceleration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MouseAcceleration::None => write!(f, "None"),
+            MouseAcceleration::Power { gain, exponent } => {
+                write!(f, "Power {{ gain: {}, exponent: {} }}", gain, exponent)
+            }
+            MouseAcceleration::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Scaling applied to mouse deltas before games read them
+///
+/// Applies to `Mouse::delta`, `Mouse::raw_delta` and `MouseRawMotion` events;
+/// the cursor position is left alone so it keeps matching the screen.
+#[derive(Debug, Clone)]
+pub struct MouseSettings {
+    pub sensitivity: f64,
+    pub acceleration: MouseAcceleration,
+    pub invert_x: bool,
+    pub invert_y: bool,
+}
+
+impl MouseSettings {
+    /// Scale one event's delta: acceleration, then sensitivity, then inversion
+    pub fn apply(&self, (delta_x, delta_y): (f64, f64)) -> (f64, f64) {
+        let speed = delta_x.hypot(delta_y);
+        let scale = self.acceleration.factor(speed) * self.sensitivity;
+        let sign = |inverted: bool| if inverted { -1.0 } else { 1.0 };
+        (delta_x * scale * sign(self.invert_x), delta_y * scale * sign(self.invert_y))
+    }
+}
+
+impl Default for MouseSettings {
+    fn default() -> Self {
+        Self {
+            sensitivity: 1.0,
+            acceleration: MouseAcceleration::None,
+            invert_x: false,
+            invert_y: false,
+        }
+    }
+}
 
 /// Mouse state tracking and input handling
 pub struct Mouse {
@@ -14,7 +86,9 @@ pub struct Mouse {
     position: (f64, f64),
     previous_position: (f64, f64),
     movement: (f64, f64),
+    raw_movement: (f64, f64),
     scroll_offset: (f64, f64),
+    settings: MouseSettings,
     is_connected: bool,
 }
 
@@ -28,7 +102,9 @@ impl Mouse {
             position: (0.0, 0.0),
             previous_position: (0.0, 0.0),
             movement: (0.0, 0.0),
+            raw_movement: (0.0, 0.0),
             scroll_offset: (0.0, 0.0),
+            settings: MouseSettings::default(),
             is_connected: true,
         }
     }
@@ -53,13 +129,15 @@ impl Mouse {
     }
 
     /// Process a mouse move event and update position
+    ///
+    /// Movement accumulates until the next `clear_frame_state`, so it covers
+    /// every move event of the frame rather than only the last one.
     pub fn process_move_event(&mut self, x: f64, y: f64) {
         self.previous_position = self.position;
         self.position = (x, y);
-        self.movement = (
-            self.position.0 - self.previous_position.0,
-            self.position.1 - self.previous_position.1,
-        );
+        let (delta_x, delta_y) = self.settings.apply((x - self.previous_position.0, y - self.previous_position.1));
+        self.movement.0 += delta_x;
+        self.movement.1 += delta_y;
 
         // Only log if significant movement happened
         if self.movement.0.abs() > 0.5 || self.movement.1.abs() > 0.5 {
@@ -73,9 +151,28 @@ impl Mouse {
         }
     }
 
+    /// Process unaccelerated motion reported while the cursor is captured, returning the scaled delta
+    pub fn process_raw_motion_event(&mut self, delta_x: f64, delta_y: f64) -> (f64, f64) {
+        let (delta_x, delta_y) = self.settings.apply((delta_x, delta_y));
+        self.raw_movement.0 += delta_x;
+        self.raw_movement.1 += delta_y;
+        (delta_x, delta_y)
+    }
+
+    pub fn settings(&self) -> &MouseSettings {
+        &self.settings
+    }
+
+    /// Change sensitivity, acceleration and inversion for later motion
+    pub fn set_settings(&mut self, settings: MouseSettings) {
+        debug!("Setting mouse settings: {:?}", settings);
+        self.settings = settings;
+    }
+
     /// Process a mouse scroll event
     pub fn process_scroll_event(&mut self, x_offset: f64, y_offset: f64) {
-        self.scroll_offset = (x_offset, y_offset);
+        self.scroll_offset.0 += x_offset;
+        self.scroll_offset.1 += y_offset;
         trace!("Mouse scroll: ({:.1}, {:.1})", x_offset, y_offset);
     }
 
@@ -87,6 +184,11 @@ impl Mouse {
         )
     }
 
+    /// Buttons currently held, in no particular order
+    pub fn buttons_down(&self) -> impl Iterator<Item = MouseButton> + '_ {
+        self.button_states.keys().copied().filter(|button| self.is_button_pressed(*button))
+    }
+
     /// Check if a mouse button was just pressed this frame
     pub fn is_button_just_pressed(&self, button: MouseButton) -> bool {
         self.pressed_buttons.contains(&button)
@@ -97,6 +199,55 @@ impl Mouse {
         self.released_buttons.contains(&button)
     }
 
+    /// State of a mouse button this frame
+    pub fn state(&self, button: MouseButton) -> ButtonState {
+        ButtonState::from_frame(
+            self.is_button_just_pressed(button),
+            self.is_button_just_released(button),
+            self.is_button_pressed(button),
+        )
+    }
+
+    /// Check if a button went down this frame or is held from an earlier one
+    pub fn is_down(&self, button: MouseButton) -> bool {
+        self.state(button).is_down()
+    }
+
+    /// Check if a button went down this frame
+    pub fn just_pressed(&self, button: MouseButton) -> bool {
+        self.state(button) == ButtonState::Pressed
+    }
+
+    /// Check if a button has been down since an earlier frame
+    pub fn is_held(&self, button: MouseButton) -> bool {
+        self.state(button) == ButtonState::Held
+    }
+
+    /// Check if a button went up this frame
+    pub fn just_released(&self, button: MouseButton) -> bool {
+        self.is_button_just_released(button)
+    }
+
+    /// Cursor position after the last move event
+    pub fn position(&self) -> (f64, f64) {
+        self.position
+    }
+
+    /// Cursor movement over the whole frame, scaled by the mouse settings
+    pub fn delta(&self) -> (f64, f64) {
+        self.movement
+    }
+
+    /// Unaccelerated movement over the whole frame; only reported while the cursor is captured
+    pub fn raw_delta(&self) -> (f64, f64) {
+        self.raw_movement
+    }
+
+    /// Scrolling over the whole frame
+    pub fn scroll(&self) -> (f64, f64) {
+        self.scroll_offset
+    }
+
     /// Get the current mouse position
     pub fn get_position(&self) -> (f64, f64) {
         self.position
@@ -107,16 +258,17 @@ impl Mouse {
         self.movement
     }
 
-    /// Get the current scroll offset
+    /// Get the scroll offset accumulated since last update
     pub fn get_scroll_offset(&self) -> (f64, f64) {
         self.scroll_offset
     }
 
-    /// Clear the per-frame state (called at the end of each frame)
+    /// Clear the per-frame state (called before each frame's events are processed)
     pub fn clear_frame_state(&mut self) {
         self.pressed_buttons.clear();
         self.released_buttons.clear();
         self.movement = (0.0, 0.0);
+        self.raw_movement = (0.0, 0.0);
         self.scroll_offset = (0.0, 0.0);
     }
 }
@@ -0,0 +1,196 @@
+use crate::events::core::{Event, EventData, EventSource};
+use crate::input::actions::InputBinding;
+use artifice_logging::{debug, info};
+use std::collections::BTreeMap;
+
+/// Payload of the custom event emitted when a device joins a player slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerJoined {
+    pub player: u32,
+    pub source: EventSource,
+}
+
+/// Assigns input devices to local players and tags their events with the player
+///
+/// Routed events get `Event::player` set to the slot their device belongs to;
+/// events from unassigned devices keep `None`, so menus can still react to
+/// anyone. Keyboard and mouse form one seat: assigning either assigns both.
+///
+/// In join mode the first press on an unassigned device takes the lowest free
+/// slot. The press is marked handled and followed by `Event::custom(PlayerJoined)`,
+/// so it does not also fire whatever it is bound to.
+///
+/// ```ignore
+/// let players = input_manager.players_mut();
+/// players.set_joining(true); // "press a button to join"
+/// // ... later, in a layer:
+/// if let Some(joined) = event.as_custom::<PlayerJoined>() { show_slot(joined.player) }
+/// if event.player == Some(1) { /* move player two */ }
+/// ```
+#[derive(Debug)]
+pub struct PlayerInputRouter {
+    assignments: BTreeMap<EventSource, u32>,
+    max_players: u32,
+    joining: bool,
+}
+
+impl PlayerInputRouter {
+    pub fn new(max_players: u32) -> Self {
+        Self {
+            assignments: BTreeMap::new(),
+            max_players,
+            joining: false,
+        }
+    }
+
+    pub fn max_players(&self) -> u32 {
+        self.max_players
+    }
+
+    /// Give `source` to `player`, taking it from any previous player
+    pub fn assign(&mut self, player: u32, source: EventSource) {
+        for source in seat(source) {
+            debug!("Assigning {:?} to player {}", source, player);
+            self.assignments.insert(source, player);
+        }
+    }
+
+    /// Return `source` to the pool of unassigned devices
+    pub fn unassign(&mut self, source: EventSource) {
+        for source in seat(source) {
+            self.assignments.remove(&source);
+        }
+    }
+
+    /// Drop every device of `player`, freeing the slot
+    pub fn remove_player(&mut self, player: u32) {
+        info!("Player {} left", player);
+        self.assignments.retain(|_, assigned| *assigned != player);
+    }
+
+    pub fn player_of(&self, source: EventSource) -> Option<u32> {
+        self.assignments.get(&source).copied()
+    }
+
+    /// Devices assigned to `player`
+    pub fn devices(&self, player: u32) -> Vec<EventSource> {
+        self.assignments
+            .iter()
+            .filter(|(_, assigned)| **assigned == player)
+            .map(|(source, _)| *source)
+            .collect()
+    }
+
+    /// Players with at least one device, in slot order
+    pub fn players(&self) -> Vec<u32> {
+        let mut players: Vec<u32> = self.assignments.values().copied().collect();
+        players.sort_unstable();
+        players.dedup();
+        players
+    }
+
+    /// Let the next press on each unassigned device join the lowest free slot
+    pub fn set_joining(&mut self, joining: bool) {
+        debug!("Player join mode {}", if joining { "started" } else { "stopped" });
+        self.joining = joining;
+    }
+
+    pub fn is_joining(&self) -> bool {
+        self.joining
+    }
+
+    /// Tag `event` with its player, appending a `PlayerJoined` event if it made a device join
+    pub fn route(&mut self, event: &mut Event, out: &mut Vec<Event>) {
+        let Some(source) = event.data.source() else { return };
+        if let Some(player) = self.player_of(source) {
+            event.player = Some(player);
+            return;
+        }
+
+        // Connection events say nothing about who holds the gamepad, and a
+        // press already taken, e.g. by rebinding, does not join
+        let joins = self.joining && !event.handled && !matches!(event.data, EventData::GamepadConnection(_));
+        if !joins || InputBinding::from_press(event).is_none() {
+            return;
+        }
+        let Some(player) = (0..self.max_players).find(|player| !self.assignments.values().any(|p| p == player)) else {
+            debug!("No free player slot for {:?}", source);
+            return;
+        };
+
+        info!("{:?} joined as player {}", source, player);
+        self.assign(player, source);
+        event.player = Some(player);
+        event.mark_handled();
+        out.push(Event::custom(PlayerJoined { player, source }));
+    }
+}
+
+impl Default for PlayerInputRouter {
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+/// Devices assigned together with `source`
+fn seat(source: EventSource) -> Vec<EventSource> {
+    match source {
+        EventSource::Keyboard | EventSource::Mouse => vec![EventSource::Keyboard, EventSource::Mouse],
+        gamepad => vec![gamepad],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::core::{
+        GamepadButton, GamepadButtonEvent, KeyAction, KeyCode, KeyEvent, KeyMod, MouseMoveEvent,
+    };
+
+    fn pad_press(gamepad_id: u32) -> Event {
+        Event::new(EventData::GamepadButton(GamepadButtonEvent {
+            gamepad_id,
+            button: GamepadButton::A,
+            action: KeyAction::Press,
+            mods: KeyMod::new(),
+        }))
+    }
+
+    #[test]
+    fn test_join_mode_assigns_slots_and_tags_events() {
+        let mut router = PlayerInputRouter::new(2);
+        let mut out = Vec::new();
+
+        // Outside join mode presses stay untagged
+        let mut event = pad_press(3);
+        router.route(&mut event, &mut out);
+        assert_eq!((event.player, event.handled, out.len()), (None, false, 0));
+
+        router.set_joining(true);
+        let mut motion = Event::new(EventData::MouseMove(MouseMoveEvent { x: 1.0, y: 1.0 }));
+        router.route(&mut motion, &mut out);
+        assert_eq!(motion.player, None, "only presses join");
+
+        let mut event = pad_press(3);
+        router.route(&mut event, &mut out);
+        assert_eq!((event.player, event.handled), (Some(0), true));
+        assert_eq!(out[0].as_custom::<PlayerJoined>(), Some(&PlayerJoined { player: 0, source: EventSource::Gamepad(3) }));
+
+        let mut key = Event::new(EventData::Key(KeyEvent { key: KeyCode::Enter, action: KeyAction::Press, mods: KeyMod::new() }));
+        router.route(&mut key, &mut out);
+        assert_eq!(key.player, Some(1));
+        assert_eq!(router.devices(1), [EventSource::Keyboard, EventSource::Mouse]);
+
+        // Slots are full; later events from assigned devices are tagged but not handled
+        let mut late = pad_press(4);
+        router.route(&mut late, &mut out);
+        assert_eq!(late.player, None);
+        let mut again = pad_press(3);
+        router.route(&mut again, &mut out);
+        assert_eq!((again.player, again.handled), (Some(0), false));
+        assert_eq!(out.len(), 2);
+
+        router.remove_player(0);
+        assert_eq!(router.players(), [1]);
+    }
+}
@@ -1,280 +1,373 @@
-use crate::events::core::{Event, EventData, KeyCode, KeyAction, MouseButton};
+use crate::events::core::{
+    Event, EventCategory, EventData, EventType, GamepadAxisEvent, GamepadButtonEvent, KeyAction, KeyCode,
+    KeyEvent, KeyMod, MouseButton, MouseButtonEvent, MouseMoveEvent, MouseRawMotionEvent, MouseScrollEvent,
+};
+use crate::input::actions::InputBinding;
 use artifice_logging::{debug, info, warn, error};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::fs::{File, OpenOptions};
 use std::io::{BufReader, BufWriter, Read, Write};
+use std::ops::{Bound, RangeBounds};
 use std::path::Path;
 use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 
+/// Recording format written by this version
+///
+/// Version 1 only stored keyboard and mouse events, with enums as numbers.
+/// Version 2 stores every input `EventData`, including gamepad events.
+pub const RECORDING_FORMAT_VERSION: u32 = 2;
+
+/// How `InputRecording::save_to_file_as` lays out a recording
+///
+/// Loading detects the layout, so files can be read without knowing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordingFormat {
+    /// Pretty-printed JSON, easy to read and diff
+    #[default]
+    Json,
+    /// Compact binary container, a small fraction of the JSON size for long sessions
+    Binary,
+    /// Binary container compressed with zstd
+    #[cfg(feature = "zstd")]
+    CompressedBinary,
+}
+
+/// Version of the engine writing recordings
+pub const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Why a recording was refused on load
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordingError {
+    /// The events do not hash to the checksum saved with them
+    ChecksumMismatch { expected: String, actual: String },
+    /// The recording was made by an engine whose input handling may differ
+    EngineVersionMismatch { recorded: String, current: String },
+    /// The metadata disagrees with the events it describes
+    EventCountMismatch { expected: usize, actual: usize },
+}
+
+impl std::fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingError::ChecksumMismatch { expected, actual } => {
+                write!(f, "Recording is corrupted: checksum is {} but the events hash to {}", expected, actual)
+            }
+            RecordingError::EngineVersionMismatch { recorded, current } => {
+                write!(f, "Recording was made with engine {} and cannot be replayed by engine {}", recorded, current)
+            }
+            RecordingError::EventCountMismatch { expected, actual } => {
+                write!(f, "Recording is corrupted: metadata lists {} events but it holds {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
 /// Represents a recorded input event with timing information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecordedEvent {
     /// Time offset from the start of the recording (in milliseconds)
     pub timestamp_ms: u64,
     /// The actual event data
-    pub event_data: SerializableEventData,
+    pub event_data: EventData,
 }
 
-/// Serializable version of EventData for recording purposes
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum SerializableEventData {
-    Key {
-        key: u32, // KeyCode as u32 for serialization
-        action: u8, // KeyAction as u8
-        shift: bool,
-        control: bool,
-        alt: bool,
-        super_key: bool,
-    },
-    MouseMove {
-        x: f64,
-        y: f64,
-    },
-    MouseButton {
-        button: u8, // MouseButton as u8
-        action: u8, // KeyAction as u8 (reused for mouse)
-        shift: bool,
-        control: bool,
-        alt: bool,
-        super_key: bool,
-    },
-    MouseScroll {
-        x_offset: f64,
-        y_offset: f64,
-    },
+/// Event data as stored by format 1, with enums as their declaration index
+#[derive(Deserialize)]
+enum LegacyEventData {
+    Key { key: u32, action: u8, shift: bool, control: bool, alt: bool, super_key: bool },
+    MouseMove { x: f64, y: f64 },
+    MouseButton { button: u8, action: u8, shift: bool, control: bool, alt: bool, super_key: bool },
+    MouseScroll { x_offset: f64, y_offset: f64 },
 }
 
-impl SerializableEventData {
-    /// Convert from EventData to SerializableEventData
-    pub fn from_event_data(data: &EventData) -> Option<Self> {
+impl LegacyEventData {
+    fn into_event_data(self) -> EventData {
+        let mods = |shift, control, alt, super_key| KeyMod { shift, control, alt, super_key, ..KeyMod::new() };
+        match self {
+            LegacyEventData::Key { key, action, shift, control, alt, super_key } => EventData::Key(KeyEvent {
+                key: from_index(key).unwrap_or(KeyCode::Unknown),
+                action: from_index(action as u32).unwrap_or(KeyAction::Press),
+                mods: mods(shift, control, alt, super_key),
+            }),
+            LegacyEventData::MouseMove { x, y } => EventData::MouseMove(MouseMoveEvent { x, y }),
+            LegacyEventData::MouseButton { button, action, shift, control, alt, super_key } => {
+                EventData::MouseButton(MouseButtonEvent {
+                    button: from_index(button as u32).unwrap_or(MouseButton::Button1),
+                    action: from_index(action as u32).unwrap_or(KeyAction::Press),
+                    mods: mods(shift, control, alt, super_key),
+                })
+            }
+            LegacyEventData::MouseScroll { x_offset, y_offset } => {
+                EventData::MouseScroll(MouseScrollEvent { x_offset, y_offset })
+            }
+        }
+    }
+}
+
+/// Unit enum variant at declaration `index`, the way format 1 stored them
+fn from_index<T: serde::de::DeserializeOwned>(index: u32) -> Option<T> {
+    use serde::de::value::{Error, U32Deserializer};
+    use serde::de::IntoDeserializer;
+    let deserializer: U32Deserializer<Error> = index.into_deserializer();
+    T::deserialize(deserializer).ok()
+}
+
+/// Binary container for recordings
+///
+/// Layout: the `MAGIC` bytes, a container version byte and a flags byte,
+/// followed by the body, zstd-compressed when `FLAG_ZSTD` is set. The body
+/// holds the metadata as length-prefixed JSON, then the event count and the
+/// events. Each event is its timestamp as a delta from the previous one, a
+/// tag byte and the tag's fields; integers are LEB128 varints, floats are
+/// little-endian and enums are their declaration index. Event kinds without a
+/// compact tag are stored as length-prefixed JSON.
+mod binary {
+    use super::*;
+    use std::io;
+
+    pub const MAGIC: &[u8] = b"AREC";
+    const CONTAINER_VERSION: u8 = 1;
+    const FLAG_ZSTD: u8 = 1;
+
+    const TAG_KEY: u8 = 0;
+    const TAG_MOUSE_MOVE: u8 = 1;
+    const TAG_MOUSE_BUTTON: u8 = 2;
+    const TAG_MOUSE_SCROLL: u8 = 3;
+    const TAG_MOUSE_RAW_MOTION: u8 = 4;
+    const TAG_GAMEPAD_BUTTON: u8 = 5;
+    const TAG_GAMEPAD_AXIS: u8 = 6;
+    const TAG_JSON: u8 = 255;
+
+    pub fn encode(
+        metadata: &RecordingMetadata,
+        events: &[RecordedEvent],
+        compress: bool,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut body = Vec::new();
+        let metadata = serde_json::to_vec(metadata)?;
+        put_varint(&mut body, metadata.len() as u64);
+        body.extend_from_slice(&metadata);
+
+        put_varint(&mut body, events.len() as u64);
+        let mut previous = 0;
+        for event in events {
+            // Recordings are in time order, so deltas stay small
+            put_varint(&mut body, event.timestamp_ms.saturating_sub(previous));
+            previous = event.timestamp_ms;
+            put_event(&mut body, &event.event_data)?;
+        }
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(CONTAINER_VERSION);
+        bytes.push(if compress { FLAG_ZSTD } else { 0 });
+        if compress {
+            #[cfg(feature = "zstd")]
+            bytes.extend(zstd::encode_all(body.as_slice(), 0)?);
+        } else {
+            bytes.extend(body);
+        }
+        Ok(bytes)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<InputRecording, Box<dyn std::error::Error>> {
+        let mut header = Reader { bytes, position: MAGIC.len() };
+        let version = header.u8()?;
+        if version != CONTAINER_VERSION {
+            return Err(format!("unsupported binary recording version {}", version).into());
+        }
+        let flags = header.u8()?;
+        let rest = &bytes[header.position..];
+
+        let body = if flags & FLAG_ZSTD != 0 {
+            decompress(rest)?
+        } else {
+            rest.to_vec()
+        };
+        let mut reader = Reader { bytes: &body, position: 0 };
+
+        let metadata_len = reader.varint()? as usize;
+        let metadata: RecordingMetadata = serde_json::from_slice(reader.take(metadata_len)?)?;
+
+        let count = reader.varint()? as usize;
+        // Every event takes at least two bytes, so a corrupt count cannot reserve much
+        let mut events = Vec::with_capacity(count.min(body.len() / 2));
+        let mut timestamp_ms = 0u64;
+        for _ in 0..count {
+            timestamp_ms += reader.varint()?;
+            events.push(RecordedEvent { timestamp_ms, event_data: read_event(&mut reader)? });
+        }
+
+        Ok(InputRecording { metadata, events })
+    }
+
+    #[cfg(feature = "zstd")]
+    fn decompress(bytes: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(zstd::decode_all(bytes)?)
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    fn decompress(_bytes: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Err("recording is compressed with zstd; enable the `zstd` feature to load it".into())
+    }
+
+    fn put_event(out: &mut Vec<u8>, data: &EventData) -> Result<(), serde_json::Error> {
         match data {
-            EventData::Key(key_event) => Some(SerializableEventData::Key {
-                key: key_event.key as u32,
-                action: key_event.action as u8,
-                shift: key_event.mods.shift,
-                control: key_event.mods.control,
-                alt: key_event.mods.alt,
-                super_key: key_event.mods.super_key,
+            EventData::Key(key) => {
+                out.push(TAG_KEY);
+                put_varint(out, key.key as u64);
+                out.extend([key.action as u8, mods_to_bits(key.mods)]);
+            }
+            EventData::MouseMove(motion) => {
+                out.push(TAG_MOUSE_MOVE);
+                out.extend(motion.x.to_le_bytes().into_iter().chain(motion.y.to_le_bytes()));
+            }
+            EventData::MouseButton(button) => {
+                out.push(TAG_MOUSE_BUTTON);
+                out.extend([button.button as u8, button.action as u8, mods_to_bits(button.mods)]);
+            }
+            EventData::MouseScroll(scroll) => {
+                out.push(TAG_MOUSE_SCROLL);
+                out.extend(scroll.x_offset.to_le_bytes().into_iter().chain(scroll.y_offset.to_le_bytes()));
+            }
+            EventData::MouseRawMotion(motion) => {
+                out.push(TAG_MOUSE_RAW_MOTION);
+                out.extend(motion.delta_x.to_le_bytes().into_iter().chain(motion.delta_y.to_le_bytes()));
+            }
+            EventData::GamepadButton(button) => {
+                out.push(TAG_GAMEPAD_BUTTON);
+                put_varint(out, button.gamepad_id as u64);
+                out.extend([button.button as u8, button.action as u8, mods_to_bits(button.mods)]);
+            }
+            EventData::GamepadAxis(axis) => {
+                out.push(TAG_GAMEPAD_AXIS);
+                put_varint(out, axis.gamepad_id as u64);
+                out.push(axis.axis as u8);
+                out.extend(axis.value.to_le_bytes());
+            }
+            other => {
+                out.push(TAG_JSON);
+                let json = serde_json::to_vec(other)?;
+                put_varint(out, json.len() as u64);
+                out.extend(json);
+            }
+        }
+        Ok(())
+    }
+
+    fn read_event(reader: &mut Reader) -> Result<EventData, Box<dyn std::error::Error>> {
+        let tag = reader.u8()?;
+        let data = match tag {
+            TAG_KEY => EventData::Key(KeyEvent {
+                key: reader.index()?,
+                action: reader.byte_index()?,
+                mods: mods_from_bits(reader.u8()?),
             }),
-            EventData::MouseMove(move_event) => Some(SerializableEventData::MouseMove {
-                x: move_event.x,
-                y: move_event.y,
+            TAG_MOUSE_MOVE => EventData::MouseMove(MouseMoveEvent { x: reader.f64()?, y: reader.f64()? }),
+            TAG_MOUSE_BUTTON => EventData::MouseButton(MouseButtonEvent {
+                button: reader.byte_index()?,
+                action: reader.byte_index()?,
+                mods: mods_from_bits(reader.u8()?),
             }),
-            EventData::MouseButton(button_event) => Some(SerializableEventData::MouseButton {
-                button: button_event.button as u8,
-                action: button_event.action as u8,
-                shift: button_event.mods.shift,
-                control: button_event.mods.control,
-                alt: button_event.mods.alt,
-                super_key: button_event.mods.super_key,
+            TAG_MOUSE_SCROLL => EventData::MouseScroll(MouseScrollEvent { x_offset: reader.f64()?, y_offset: reader.f64()? }),
+            TAG_MOUSE_RAW_MOTION => {
+                EventData::MouseRawMotion(MouseRawMotionEvent { delta_x: reader.f64()?, delta_y: reader.f64()? })
+            }
+            TAG_GAMEPAD_BUTTON => EventData::GamepadButton(GamepadButtonEvent {
+                gamepad_id: reader.varint()? as u32,
+                button: reader.byte_index()?,
+                action: reader.byte_index()?,
+                mods: mods_from_bits(reader.u8()?),
             }),
-            EventData::MouseScroll(scroll_event) => Some(SerializableEventData::MouseScroll {
-                x_offset: scroll_event.x_offset,
-                y_offset: scroll_event.y_offset,
+            TAG_GAMEPAD_AXIS => EventData::GamepadAxis(GamepadAxisEvent {
+                gamepad_id: reader.varint()? as u32,
+                axis: reader.byte_index()?,
+                value: f32::from_le_bytes(reader.array()?),
             }),
-            _ => None, // Don't record window or application events
-        }
+            TAG_JSON => {
+                let len = reader.varint()? as usize;
+                serde_json::from_slice(reader.take(len)?)?
+            }
+            tag => return Err(invalid(format!("unknown event tag {}", tag)).into()),
+        };
+        Ok(data)
     }
 
-    /// Convert to EventData for replay
-    pub fn to_event_data(&self) -> Option<EventData> {
-        use crate::events::core::{KeyEvent, MouseMoveEvent, MouseButtonEvent, MouseScrollEvent, KeyAction, KeyMod, KeyCode, MouseButton};
-        
-        match self {
-            SerializableEventData::Key { key, action, shift, control, alt, super_key } => {
-                Some(EventData::Key(KeyEvent {
-                    key: Self::u32_to_keycode(*key),
-                    action: Self::u8_to_keyaction(*action),
-                    mods: KeyMod {
-                        shift: *shift,
-                        control: *control,
-                        alt: *alt,
-                        super_key: *super_key,
-                        caps_lock: false,
-                        num_lock: false,
-                    },
-                }))
-            },
-            SerializableEventData::MouseMove { x, y } => {
-                Some(EventData::MouseMove(MouseMoveEvent {
-                    x: *x,
-                    y: *y,
-                }))
-            },
-            SerializableEventData::MouseButton { button, action, shift, control, alt, super_key } => {
-                Some(EventData::MouseButton(MouseButtonEvent {
-                    button: Self::u8_to_mousebutton(*button),
-                    action: Self::u8_to_keyaction(*action),
-                    mods: KeyMod {
-                        shift: *shift,
-                        control: *control,
-                        alt: *alt,
-                        super_key: *super_key,
-                        caps_lock: false,
-                        num_lock: false,
-                    },
-                }))
-            },
-            SerializableEventData::MouseScroll { x_offset, y_offset } => {
-                Some(EventData::MouseScroll(MouseScrollEvent {
-                    x_offset: *x_offset,
-                    y_offset: *y_offset,
-                }))
-            },
+    fn mods_to_bits(mods: KeyMod) -> u8 {
+        [mods.shift, mods.control, mods.alt, mods.super_key, mods.caps_lock, mods.num_lock]
+            .iter()
+            .enumerate()
+            .fold(0, |bits, (bit, set)| bits | (u8::from(*set) << bit))
+    }
+
+    fn mods_from_bits(bits: u8) -> KeyMod {
+        let set = |bit: u8| bits & (1 << bit) != 0;
+        KeyMod { shift: set(0), control: set(1), alt: set(2), super_key: set(3), caps_lock: set(4), num_lock: set(5) }
+    }
+
+    fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
         }
+        out.push(value as u8);
+    }
+
+    fn invalid(message: impl Into<String>) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, message.into())
     }
 
-    fn u32_to_keycode(value: u32) -> KeyCode {
-        match value {
-            0 => KeyCode::Unknown,
-            1 => KeyCode::Space,
-            2 => KeyCode::Apostrophe,
-            3 => KeyCode::Comma,
-            4 => KeyCode::Minus,
-            5 => KeyCode::Period,
-            6 => KeyCode::Slash,
-            7 => KeyCode::Num0,
-            8 => KeyCode::Num1,
-            9 => KeyCode::Num2,
-            10 => KeyCode::Num3,
-            11 => KeyCode::Num4,
-            12 => KeyCode::Num5,
-            13 => KeyCode::Num6,
-            14 => KeyCode::Num7,
-            15 => KeyCode::Num8,
-            16 => KeyCode::Num9,
-            17 => KeyCode::Semicolon,
-            18 => KeyCode::Equal,
-            19 => KeyCode::A,
-            20 => KeyCode::B,
-            21 => KeyCode::C,
-            22 => KeyCode::D,
-            23 => KeyCode::E,
-            24 => KeyCode::F,
-            25 => KeyCode::G,
-            26 => KeyCode::H,
-            27 => KeyCode::I,
-            28 => KeyCode::J,
-            29 => KeyCode::K,
-            30 => KeyCode::L,
-            31 => KeyCode::M,
-            32 => KeyCode::N,
-            33 => KeyCode::O,
-            34 => KeyCode::P,
-            35 => KeyCode::Q,
-            36 => KeyCode::R,
-            37 => KeyCode::S,
-            38 => KeyCode::T,
-            39 => KeyCode::U,
-            40 => KeyCode::V,
-            41 => KeyCode::W,
-            42 => KeyCode::X,
-            43 => KeyCode::Y,
-            44 => KeyCode::Z,
-            45 => KeyCode::LeftBracket,
-            46 => KeyCode::Backslash,
-            47 => KeyCode::RightBracket,
-            48 => KeyCode::GraveAccent,
-            49 => KeyCode::World1,
-            50 => KeyCode::World2,
-            51 => KeyCode::Escape,
-            52 => KeyCode::Enter,
-            53 => KeyCode::Tab,
-            54 => KeyCode::Backspace,
-            55 => KeyCode::Insert,
-            56 => KeyCode::Delete,
-            57 => KeyCode::Right,
-            58 => KeyCode::Left,
-            59 => KeyCode::Down,
-            60 => KeyCode::Up,
-            61 => KeyCode::PageUp,
-            62 => KeyCode::PageDown,
-            63 => KeyCode::Home,
-            64 => KeyCode::End,
-            65 => KeyCode::CapsLock,
-            66 => KeyCode::ScrollLock,
-            67 => KeyCode::NumLock,
-            68 => KeyCode::PrintScreen,
-            69 => KeyCode::Pause,
-            70 => KeyCode::F1,
-            71 => KeyCode::F2,
-            72 => KeyCode::F3,
-            73 => KeyCode::F4,
-            74 => KeyCode::F5,
-            75 => KeyCode::F6,
-            76 => KeyCode::F7,
-            77 => KeyCode::F8,
-            78 => KeyCode::F9,
-            79 => KeyCode::F10,
-            80 => KeyCode::F11,
-            81 => KeyCode::F12,
-            82 => KeyCode::F13,
-            83 => KeyCode::F14,
-            84 => KeyCode::F15,
-            85 => KeyCode::F16,
-            86 => KeyCode::F17,
-            87 => KeyCode::F18,
-            88 => KeyCode::F19,
-            89 => KeyCode::F20,
-            90 => KeyCode::F21,
-            91 => KeyCode::F22,
-            92 => KeyCode::F23,
-            93 => KeyCode::F24,
-            94 => KeyCode::F25,
-            95 => KeyCode::KP0,
-            96 => KeyCode::KP1,
-            97 => KeyCode::KP2,
-            98 => KeyCode::KP3,
-            99 => KeyCode::KP4,
-            100 => KeyCode::KP5,
-            101 => KeyCode::KP6,
-            102 => KeyCode::KP7,
-            103 => KeyCode::KP8,
-            104 => KeyCode::KP9,
-            105 => KeyCode::KPDecimal,
-            106 => KeyCode::KPDivide,
-            107 => KeyCode::KPMultiply,
-            108 => KeyCode::KPSubtract,
-            109 => KeyCode::KPAdd,
-            110 => KeyCode::KPEnter,
-            111 => KeyCode::KPEqual,
-            112 => KeyCode::LeftShift,
-            113 => KeyCode::LeftControl,
-            114 => KeyCode::LeftAlt,
-            115 => KeyCode::LeftSuper,
-            116 => KeyCode::RightShift,
-            117 => KeyCode::RightControl,
-            118 => KeyCode::RightAlt,
-            119 => KeyCode::RightSuper,
-            120 => KeyCode::Menu,
-            _ => KeyCode::Unknown,
-        }
-    }
-
-    fn u8_to_keyaction(value: u8) -> KeyAction {
-        match value {
-            0 => KeyAction::Press,
-            1 => KeyAction::Release,
-            2 => KeyAction::Repeat,
-            _ => KeyAction::Press,
-        }
-    }
-
-    fn u8_to_mousebutton(value: u8) -> MouseButton {
-        match value {
-            0 => MouseButton::Button1,
-            1 => MouseButton::Button2,
-            2 => MouseButton::Button3,
-            3 => MouseButton::Button4,
-            4 => MouseButton::Button5,
-            5 => MouseButton::Button6,
-            6 => MouseButton::Button7,
-            7 => MouseButton::Button8,
-            8 => MouseButton::Left,
-            9 => MouseButton::Right,
-            10 => MouseButton::Middle,
-            _ => MouseButton::Button1,
+    /// Cursor over a binary body that fails instead of reading past the end
+    struct Reader<'a> {
+        bytes: &'a [u8],
+        position: usize,
+    }
+
+    impl<'a> Reader<'a> {
+        fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+            let end = self.position.checked_add(len).filter(|end| *end <= self.bytes.len());
+            let end = end.ok_or_else(|| invalid("binary recording is truncated"))?;
+            let bytes = &self.bytes[self.position..end];
+            self.position = end;
+            Ok(bytes)
+        }
+
+        fn array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+            Ok(self.take(N)?.try_into().expect("slice has the requested length"))
+        }
+
+        fn u8(&mut self) -> io::Result<u8> {
+            Ok(self.take(1)?[0])
+        }
+
+        fn f64(&mut self) -> io::Result<f64> {
+            Ok(f64::from_le_bytes(self.array()?))
+        }
+
+        fn varint(&mut self) -> io::Result<u64> {
+            let mut value = 0u64;
+            for shift in (0..64).step_by(7) {
+                let byte = self.u8()?;
+                value |= u64::from(byte & 0x7f) << shift;
+                if byte & 0x80 == 0 {
+                    return Ok(value);
+                }
+            }
+            Err(invalid("varint is too long"))
+        }
+
+        /// Enum stored as a varint declaration index
+        fn index<T: serde::de::DeserializeOwned>(&mut self) -> io::Result<T> {
+            let index = self.varint()?;
+            u32::try_from(index).ok().and_then(from_index).ok_or_else(|| invalid(format!("unknown variant {}", index)))
+        }
+
+        /// Enum stored as a one-byte declaration index
+        fn byte_index<T: serde::de::DeserializeOwned>(&mut self) -> io::Result<T> {
+            let index = self.u8()?;
+            from_index(index as u32).ok_or_else(|| invalid(format!("unknown variant {}", index)))
         }
     }
 }
@@ -303,6 +396,18 @@ pub struct RecordingMetadata {
     pub event_count: usize,
     /// Version of the recording format
     pub format_version: u32,
+    /// Hash of the events, written on save and checked on load
+    #[serde(default)]
+    pub checksum: Option<String>,
+    /// Version of the engine that made the recording
+    #[serde(default)]
+    pub engine_version: Option<String>,
+    /// Window size while recording; mouse positions are relative to it
+    #[serde(default)]
+    pub window_size: Option<(u32, u32)>,
+    /// Window backend while recording
+    #[serde(default)]
+    pub backend: Option<String>,
 }
 
 impl InputRecording {
@@ -314,7 +419,11 @@ impl InputRecording {
                 created_at: chrono::Utc::now().to_rfc3339(),
                 duration_ms: 0,
                 event_count: 0,
-                format_version: 1,
+                format_version: RECORDING_FORMAT_VERSION,
+                checksum: None,
+                engine_version: Some(ENGINE_VERSION.to_string()),
+                window_size: None,
+                backend: None,
             },
             events: Vec::new(),
         }
@@ -325,34 +434,294 @@ impl InputRecording {
         self
     }
 
-    /// Save the recording to a file
+    /// Note the window the recording is made in
+    pub fn with_window(mut self, size: (u32, u32), backend: impl Into<String>) -> Self {
+        self.metadata.window_size = Some(size);
+        self.metadata.backend = Some(backend.into());
+        self
+    }
+
+    /// Save the recording to a file as JSON
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_to_file_as(path, RecordingFormat::Json)
+    }
+
+    /// Save the recording to a file in the given format
+    pub fn save_to_file_as<P: AsRef<Path>>(&self, path: P, format: RecordingFormat) -> Result<(), Box<dyn std::error::Error>> {
         let file = File::create(path)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, self)?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&self.to_bytes(format)?)?;
+        writer.flush()?;
         Ok(())
     }
 
-    /// Load a recording from a file
+    /// Load a recording from a file in any format, upgrading older JSON formats
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
         let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let recording: InputRecording = serde_json::from_reader(reader)?;
+        let mut bytes = Vec::new();
+        BufReader::new(file).read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Encode the recording in the given format, with a fresh checksum
+    pub fn to_bytes(&self, format: RecordingFormat) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let metadata = self.sealed_metadata()?;
+        match format {
+            RecordingFormat::Json => Ok(serde_json::to_vec_pretty(&RecordingRef { metadata, events: &self.events })?),
+            RecordingFormat::Binary => binary::encode(&metadata, &self.events, false),
+            #[cfg(feature = "zstd")]
+            RecordingFormat::CompressedBinary => binary::encode(&metadata, &self.events, true),
+        }
+    }
+
+    /// Decode and validate a recording in any format, telling them apart by the binary header
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let recording = if bytes.starts_with(binary::MAGIC) {
+            binary::decode(bytes)?
+        } else {
+            Self::from_value(serde_json::from_slice(bytes)?)?
+        };
+        recording.validate()?;
         Ok(recording)
     }
 
+    /// Serialize the recording to a JSON string, with a fresh checksum
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&RecordingRef { metadata: self.sealed_metadata()?, events: &self.events })
+    }
+
+    /// Parse and validate a recording from a JSON string, upgrading older formats
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let recording = Self::from_value(serde_json::from_str(json)?)?;
+        recording.validate()?;
+        Ok(recording)
+    }
+
+    /// Check the checksum, event count and engine version written with the recording
+    ///
+    /// Recordings from before these were saved pass. Engines with the same
+    /// major and minor version are compatible; for 0.x engines the patch
+    /// version must match too, since anything may change between them.
+    pub fn validate(&self) -> Result<(), RecordingError> {
+        if let Some(expected) = &self.metadata.checksum {
+            let actual = events_checksum(&self.events).unwrap_or_default();
+            if *expected != actual {
+                return Err(RecordingError::ChecksumMismatch { expected: expected.clone(), actual });
+            }
+            if self.metadata.event_count != self.events.len() {
+                return Err(RecordingError::EventCountMismatch {
+                    expected: self.metadata.event_count,
+                    actual: self.events.len(),
+                });
+            }
+        }
+
+        if let Some(recorded) = &self.metadata.engine_version {
+            let significant = |version: &str| -> Vec<String> {
+                let parts: Vec<String> = version.split(['.', '-', '+']).map(str::to_string).collect();
+                let keep = if parts.first().map(String::as_str) == Some("0") { 3 } else { 2 };
+                parts.into_iter().take(keep).collect()
+            };
+            if significant(recorded) != significant(ENGINE_VERSION) {
+                return Err(RecordingError::EngineVersionMismatch {
+                    recorded: recorded.clone(),
+                    current: ENGINE_VERSION.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Metadata as saved: up to date with the events and carrying their checksum
+    fn sealed_metadata(&self) -> Result<RecordingMetadata, serde_json::Error> {
+        let mut metadata = self.metadata.clone();
+        metadata.event_count = self.events.len();
+        metadata.checksum = Some(events_checksum(&self.events)?);
+        Ok(metadata)
+    }
+
+    fn from_value(mut value: serde_json::Value) -> Result<Self, Box<dyn std::error::Error>> {
+        let version = value["metadata"]["format_version"].as_u64().unwrap_or(1) as u32;
+        if version > RECORDING_FORMAT_VERSION {
+            return Err(format!(
+                "recording format {} is newer than the supported format {}",
+                version, RECORDING_FORMAT_VERSION
+            )
+            .into());
+        }
+
+        if version == 1 {
+            info!("Upgrading input recording from format 1");
+            if let Some(events) = value["events"].as_array_mut() {
+                for event in events {
+                    let legacy: LegacyEventData = serde_json::from_value(event["event_data"].take())?;
+                    event["event_data"] = serde_json::to_value(legacy.into_event_data())?;
+                }
+            }
+            value["metadata"]["format_version"] = RECORDING_FORMAT_VERSION.into();
+        }
+
+        Ok(serde_json::from_value(value)?)
+    }
+
     /// Get the duration of the recording
     pub fn duration(&self) -> Duration {
         Duration::from_millis(self.metadata.duration_ms)
     }
 
+    /// Keep only the events within `range`, shifting them to start at zero
+    ///
+    /// Keys and buttons held when the range starts are pressed at its start,
+    /// and those still held when it ends are released there, so the slice
+    /// replays without stuck or orphaned input.
+    pub fn trim(&mut self, range: impl RangeBounds<Duration>) {
+        let start = match range.start_bound() {
+            Bound::Included(start) => start.as_millis() as u64,
+            Bound::Excluded(start) => start.as_millis() as u64 + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end.as_millis() as u64,
+            Bound::Excluded(end) => (end.as_millis() as u64).saturating_sub(1),
+            Bound::Unbounded => u64::MAX,
+        };
+
+        let mut held = HeldButtons::default();
+        let mut events = Vec::new();
+        for event in self.events.drain(..) {
+            if event.timestamp_ms < start {
+                held.track(&event.event_data);
+                continue;
+            }
+            if event.timestamp_ms > end {
+                break;
+            }
+            if events.is_empty() {
+                events.extend(held.presses().map(|event_data| RecordedEvent { timestamp_ms: 0, event_data }));
+            }
+            held.track(&event.event_data);
+            events.push(RecordedEvent { timestamp_ms: event.timestamp_ms - start, ..event });
+        }
+        if events.is_empty() {
+            events.extend(held.presses().map(|event_data| RecordedEvent { timestamp_ms: 0, event_data }));
+        }
+
+        let last = events.last().map_or(0, |event| event.timestamp_ms);
+        events.extend(held.releases().map(|event_data| RecordedEvent { timestamp_ms: last, event_data }));
+
+        debug!("Trimmed recording '{}' to {}..{} ms", self.metadata.name, start, end);
+        self.events = events;
+        self.finalize_metadata();
+    }
+
+    /// Add the events of `other` after this recording's last event, `gap` later
+    pub fn append(&mut self, other: &InputRecording, gap: Duration) {
+        let offset = self.events.last().map_or(0, |event| event.timestamp_ms + gap.as_millis() as u64);
+        self.events.extend(other.events.iter().map(|event| RecordedEvent {
+            timestamp_ms: event.timestamp_ms + offset,
+            event_data: event.event_data.clone(),
+        }));
+        self.finalize_metadata();
+    }
+
+    /// Keep only the events for which `keep` returns true
+    pub fn retain(&mut self, keep: impl FnMut(&RecordedEvent) -> bool) {
+        self.events.retain(keep);
+        self.finalize_metadata();
+    }
+
+    /// Drop every event in any of `categories`, e.g. `EventCategory::MOUSE_MOVE`
+    pub fn remove_categories(&mut self, categories: EventCategory) {
+        self.retain(|event| !event.event_data.category().intersects(categories));
+    }
+
+    /// Replace keys according to `mapping`; unmapped keys stay as they are
+    pub fn remap_keys(&mut self, mapping: &HashMap<KeyCode, KeyCode>) {
+        for event in &mut self.events {
+            if let EventData::Key(key) = &mut event.event_data {
+                if let Some(&to) = mapping.get(&key.key) {
+                    key.key = to;
+                }
+            }
+        }
+    }
+
     /// Update metadata after recording is complete
     fn finalize_metadata(&mut self) {
         self.metadata.event_count = self.events.len();
-        if let Some(last_event) = self.events.last() {
-            self.metadata.duration_ms = last_event.timestamp_ms;
+        self.metadata.duration_ms = self.events.last().map_or(0, |event| event.timestamp_ms);
+    }
+}
+
+/// Borrowing twin of `InputRecording` for saving without cloning the events
+#[derive(Serialize)]
+struct RecordingRef<'a> {
+    metadata: RecordingMetadata,
+    events: &'a [RecordedEvent],
+}
+
+/// 64-bit FNV-1a hash of the events' JSON form, which is the same whatever format they were saved in
+fn events_checksum(events: &[RecordedEvent]) -> Result<String, serde_json::Error> {
+    struct Fnv1a(u64);
+
+    impl Write for Fnv1a {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            for &byte in bytes {
+                self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+            }
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
         }
     }
+
+    let mut hash = Fnv1a(0xcbf29ce484222325);
+    serde_json::to_writer(&mut hash, events)?;
+    Ok(format!("{:016x}", hash.0))
+}
+
+/// Keys and buttons held at some point of a recording, with the events that pressed them
+#[derive(Default)]
+struct HeldButtons {
+    held: Vec<((Option<u32>, InputBinding), EventData)>,
+}
+
+impl HeldButtons {
+    fn track(&mut self, data: &EventData) {
+        let (button, action) = match data {
+            EventData::Key(key) => ((None, InputBinding::Key(key.key)), key.action),
+            EventData::MouseButton(button) => ((None, InputBinding::MouseButton(button.button)), button.action),
+            EventData::GamepadButton(button) => {
+                ((Some(button.gamepad_id), InputBinding::GamepadButton(button.button)), button.action)
+            }
+            _ => return,
+        };
+        match action {
+            KeyAction::Press if !self.held.iter().any(|(held, _)| *held == button) => {
+                self.held.push((button, data.clone()));
+            }
+            KeyAction::Release => self.held.retain(|(held, _)| *held != button),
+            _ => {}
+        }
+    }
+
+    fn presses(&self) -> impl Iterator<Item = EventData> + '_ {
+        self.held.iter().map(|(_, press)| press.clone())
+    }
+
+    fn releases(&self) -> impl Iterator<Item = EventData> + '_ {
+        self.presses().map(|mut release| {
+            match &mut release {
+                EventData::Key(key) => key.action = KeyAction::Release,
+                EventData::MouseButton(button) => button.action = KeyAction::Release,
+                EventData::GamepadButton(button) => button.action = KeyAction::Release,
+                _ => {}
+            }
+            release
+        })
+    }
 }
 
 /// Records input events for later playback
@@ -376,6 +745,12 @@ impl InputRecorder {
         self
     }
 
+    /// Note the window the recording is made in
+    pub fn with_window(mut self, size: (u32, u32), backend: impl Into<String>) -> Self {
+        self.recording = self.recording.with_window(size, backend);
+        self
+    }
+
     /// Start recording input events
     pub fn start_recording(&mut self) {
         if self.is_recording {
@@ -420,12 +795,15 @@ impl InputRecorder {
             }
         };
 
-        if let Some(serializable_data) = SerializableEventData::from_event_data(&event.data) {
+        // Don't record window or application events, nor gestures, which are synthesized again on playback
+        if matches!(event.event_type, EventType::Keyboard | EventType::Mouse | EventType::Gamepad)
+            && event.category() != EventCategory::MOUSE_GESTURE
+        {
             let timestamp_ms = start_time.elapsed().as_millis() as u64;
             
             let recorded_event = RecordedEvent {
                 timestamp_ms,
-                event_data: serializable_data,
+                event_data: event.data.clone(),
             };
 
             self.recording.events.push(recorded_event);
@@ -552,10 +930,7 @@ impl InputPlayer {
             let recorded_event = &self.recording.events[self.current_event_index];
             
             if recorded_event.timestamp_ms <= current_time_ms {
-                if let Some(event_data) = recorded_event.event_data.to_event_data() {
-                    let event = Event::new(event_data);
-                    events.push(event);
-                }
+                events.push(Event::new(recorded_event.event_data.clone()));
                 self.current_event_index += 1;
             } else {
                 break;
@@ -739,4 +1114,166 @@ impl Default for InputRecordingManager {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::core::{GamepadAxis, GamepadAxisEvent, GamepadButton, GamepadButtonEvent};
+
+    #[test]
+    fn test_gamepad_events_survive_a_round_trip() {
+        let mut recording = InputRecording::new("pad");
+        recording.events.push(RecordedEvent {
+            timestamp_ms: 5,
+            event_data: EventData::GamepadButton(GamepadButtonEvent {
+                gamepad_id: 1,
+                button: GamepadButton::A,
+                action: KeyAction::Press,
+                mods: KeyMod::new(),
+            }),
+        });
+        recording.events.push(RecordedEvent {
+            timestamp_ms: 9,
+            event_data: EventData::GamepadAxis(GamepadAxisEvent { gamepad_id: 1, axis: GamepadAxis::LeftStickX, value: 0.5 }),
+        });
+
+        let loaded = InputRecording::from_json(&recording.to_json().unwrap()).unwrap();
+        assert!(matches!(&loaded.events[0].event_data, EventData::GamepadButton(b) if b.button == GamepadButton::A));
+        assert!(matches!(&loaded.events[1].event_data, EventData::GamepadAxis(a) if a.value == 0.5));
+    }
+
+    #[test]
+    fn test_format_1_recordings_are_upgraded() {
+        let json = r#"{
+            "metadata": { "name": "old", "description": null, "created_at": "2024-01-01T00:00:00Z",
+                          "duration_ms": 20, "event_count": 2, "format_version": 1 },
+            "events": [
+                { "timestamp_ms": 0, "event_data": { "Key": { "key": 1, "action": 1, "shift": true,
+                                                              "control": false, "alt": false, "super_key": false } } },
+                { "timestamp_ms": 20, "event_data": { "MouseButton": { "button": 9, "action": 0, "shift": false,
+                                                                       "control": false, "alt": false, "super_key": false } } }
+            ]
+        }"#;
+
+        let recording = InputRecording::from_json(json).unwrap();
+        assert_eq!(recording.metadata.format_version, RECORDING_FORMAT_VERSION);
+        let EventData::Key(key) = &recording.events[0].event_data else { panic!() };
+        assert_eq!((key.key, key.action, key.mods.shift), (KeyCode::Space, KeyAction::Release, true));
+        let EventData::MouseButton(button) = &recording.events[1].event_data else { panic!() };
+        assert_eq!((button.button, button.action), (MouseButton::Right, KeyAction::Press));
+
+        let newer = json.replace("\"format_version\": 1", "\"format_version\": 99");
+        assert!(InputRecording::from_json(&newer).is_err());
+    }
+
+    #[test]
+    fn test_binary_recordings_are_detected_and_smaller() {
+        let mut recording = InputRecording::new("binary").with_description("long session");
+        for i in 0..200u64 {
+            recording.events.push(RecordedEvent {
+                timestamp_ms: i * 16,
+                event_data: EventData::MouseMove(MouseMoveEvent { x: i as f64, y: 0.5 }),
+            });
+        }
+        recording.events.push(RecordedEvent {
+            timestamp_ms: 4000,
+            event_data: EventData::Key(KeyEvent {
+                key: KeyCode::Menu,
+                action: KeyAction::Repeat,
+                mods: KeyMod { control: true, num_lock: true, ..KeyMod::new() },
+            }),
+        });
+        recording.events.push(RecordedEvent {
+            timestamp_ms: 4001,
+            event_data: EventData::GamepadAxis(GamepadAxisEvent { gamepad_id: 300, axis: GamepadAxis::RightTriggerAnalog, value: -0.25 }),
+        });
+
+        let json = recording.to_bytes(RecordingFormat::Json).unwrap();
+        let binary = recording.to_bytes(RecordingFormat::Binary).unwrap();
+        assert!(binary.len() * 4 < json.len(), "{} vs {}", binary.len(), json.len());
+
+        let loaded = InputRecording::from_bytes(&binary).unwrap();
+        assert_eq!(loaded.metadata.description.as_deref(), Some("long session"));
+        assert_eq!(loaded.events.len(), 202);
+        assert_eq!(loaded.events[199].timestamp_ms, 199 * 16);
+        assert!(matches!(&loaded.events[150].event_data, EventData::MouseMove(m) if m.x == 150.0 && m.y == 0.5));
+        let EventData::Key(key) = &loaded.events[200].event_data else { panic!() };
+        assert_eq!((key.key, key.action, key.mods), (KeyCode::Menu, KeyAction::Repeat, KeyMod { control: true, num_lock: true, ..KeyMod::new() }));
+        let EventData::GamepadAxis(axis) = &loaded.events[201].event_data else { panic!() };
+        assert_eq!((axis.gamepad_id, axis.axis, axis.value), (300, GamepadAxis::RightTriggerAnalog, -0.25));
+
+        assert!(InputRecording::from_bytes(&binary[..binary.len() - 3]).is_err());
+    }
+
+    #[test]
+    fn test_trim_append_filter_and_remap() {
+        let key = |key, action| EventData::Key(KeyEvent { key, action, mods: KeyMod::new() });
+        let at = |timestamp_ms, event_data| RecordedEvent { timestamp_ms, event_data };
+        let mut recording = InputRecording::new("capture");
+        recording.events = vec![
+            at(0, key(KeyCode::W, KeyAction::Press)),
+            at(100, EventData::MouseMove(MouseMoveEvent { x: 1.0, y: 1.0 })),
+            at(200, key(KeyCode::Space, KeyAction::Press)),
+            at(300, key(KeyCode::W, KeyAction::Release)),
+            at(400, key(KeyCode::Space, KeyAction::Release)),
+        ];
+
+        // W is held going in and Space is still held coming out
+        let mut slice = recording.clone();
+        slice.trim(Duration::from_millis(150)..Duration::from_millis(350));
+        let summary: Vec<_> = slice
+            .events
+            .iter()
+            .map(|event| {
+                let EventData::Key(key) = &event.event_data else { panic!("{:?}", event) };
+                (event.timestamp_ms, key.key, key.action)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (0, KeyCode::W, KeyAction::Press),
+                (50, KeyCode::Space, KeyAction::Press),
+                (150, KeyCode::W, KeyAction::Release),
+                (150, KeyCode::Space, KeyAction::Release),
+            ]
+        );
+        assert_eq!(slice.metadata.duration_ms, 150);
+
+        slice.append(&recording, Duration::from_millis(1000));
+        assert_eq!(slice.events[4].timestamp_ms, 1150);
+        assert_eq!(slice.metadata.event_count, 9);
+
+        slice.remove_categories(EventCategory::MOUSE_MOVE);
+        slice.remap_keys(&HashMap::from([(KeyCode::W, KeyCode::Up)]));
+        assert_eq!(slice.metadata.event_count, 8);
+        assert!(slice.events.iter().all(|event| !matches!(&event.event_data, EventData::Key(key) if key.key == KeyCode::W)));
+    }
+
+    #[test]
+    fn test_corrupt_and_stale_recordings_are_refused() {
+        let mut recording = InputRecording::new("checked").with_window((800, 600), "glfw");
+        recording.events.push(RecordedEvent { timestamp_ms: 3, event_data: EventData::MouseMove(MouseMoveEvent { x: 1.0, y: 2.0 }) });
+
+        let json = recording.to_json().unwrap();
+        let loaded = InputRecording::from_json(&json).unwrap();
+        assert_eq!(loaded.metadata.window_size, Some((800, 600)));
+        assert_eq!(loaded.metadata.backend.as_deref(), Some("glfw"));
+        assert!(loaded.metadata.checksum.is_some());
+
+        let tampered = json.replace("\"x\": 1.0", "\"x\": 5.0");
+        let error = InputRecording::from_json(&tampered).unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(RecordingError::ChecksumMismatch { .. })), "{}", error);
+
+        let mut stale = loaded.clone();
+        stale.metadata.engine_version = Some("999.0.0".into());
+        let error = InputRecording::from_json(&serde_json::to_string(&stale).unwrap()).unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(RecordingError::EngineVersionMismatch { .. })), "{}", error);
+
+        let mut binary = recording.to_bytes(RecordingFormat::Binary).unwrap();
+        let last = binary.len() - 1;
+        binary[last] ^= 0x40;
+        assert!(InputRecording::from_bytes(&binary).is_err());
+    }
+}
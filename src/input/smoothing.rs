@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+/// Exponential low-pass filter for noisy analog values
+///
+/// Each step moves the output toward the latest input by a share that grows
+/// with the elapsed time, so results do not depend on the frame rate: after
+/// one time constant the output has covered about 63% of a sudden change,
+/// after three about 95%. Used for gamepad axes and suitable for any other
+/// analog input, such as touch pressure.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LowPassFilter {
+    time_constant: Duration,
+    value: f32,
+}
+
+impl LowPassFilter {
+    /// Create a filter whose output starts at `value`
+    pub fn new(time_constant: Duration, value: f32) -> Self {
+        Self { time_constant, value }
+    }
+
+    pub fn time_constant(&self) -> Duration {
+        self.time_constant
+    }
+
+    pub fn set_time_constant(&mut self, time_constant: Duration) {
+        self.time_constant = time_constant;
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Move toward `input` as if `elapsed` had passed, returning the new output
+    pub fn step(&mut self, input: f32, elapsed: Duration) -> f32 {
+        if self.time_constant.is_zero() {
+            self.value = input;
+        } else {
+            let share = 1.0 - (-elapsed.as_secs_f32() / self.time_constant.as_secs_f32()).exp();
+            self.value += (input - self.value) * share;
+        }
+        self.value
+    }
+
+    /// Jump straight to `value`, e.g. when the device is reconnected
+    pub fn reset(&mut self, value: f32) {
+        self.value = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_low_pass_filter_is_frame_rate_independent() {
+        let mut coarse = LowPassFilter::new(Duration::from_millis(100), 0.0);
+        let mut fine = LowPassFilter::new(Duration::from_millis(100), 0.0);
+
+        coarse.step(1.0, Duration::from_millis(100));
+        for _ in 0..10 {
+            fine.step(1.0, Duration::from_millis(10));
+        }
+        let (coarse, fine) = (coarse.value(), fine.value());
+        assert!((coarse - 0.632).abs() < 0.001, "{}", coarse);
+        assert!((coarse - fine).abs() < 1e-5, "{} vs {}", coarse, fine);
+
+        let mut instant = LowPassFilter::new(Duration::ZERO, 0.0);
+        assert_eq!(instant.step(0.7, Duration::from_millis(1)), 0.7);
+    }
+
+    #[test]
+    fn test_smoothed_gamepad_axis_settles_on_the_raw_value() {
+        use crate::input::gamepad::{AxisConfig, GamepadAxis, GamepadState};
+
+        let mut pad = GamepadState::new(0, "pad".into());
+        pad.set_deadzone(0.0);
+        let smoothing = Some(Duration::from_millis(50));
+        pad.set_axis_config(GamepadAxis::LeftStickX, AxisConfig { smoothing, ..Default::default() });
+
+        pad.process_axis_event(GamepadAxis::LeftStickX, 1.0);
+        assert_eq!(pad.axis_value(GamepadAxis::LeftStickX), 0.0, "nothing passes before time does");
+        pad.smooth(Duration::from_millis(50));
+        assert!((pad.axis_value(GamepadAxis::LeftStickX) - 0.632).abs() < 0.001);
+        pad.smooth(Duration::from_secs(1));
+        assert!((pad.axis_value(GamepadAxis::LeftStickX) - 1.0).abs() < 1e-4);
+        assert_eq!(pad.raw_axis_value(GamepadAxis::LeftStickX), 1.0);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::events::core::{KeyCode, KeyMod, MouseButton};
+use crate::input::gamepad::{GamepadAxis, GamepadButton};
+use serde::{Deserialize, Serialize};
+
+/// Input state of one frame, compact enough to send every frame
+///
+/// Lockstep and rollback netcode exchange these instead of events: feeding
+/// the same snapshots to the simulation reproduces the same frames. Lists
+/// are kept sorted, so equal states compare and serialize equal.
+///
+/// ```ignore
+/// let snapshot = input_manager.snapshot();
+/// let diff = snapshot.diff(&last_sent);
+/// if !diff.is_empty() { send(frame, &diff); }
+/// // on the peer
+/// let remote = last_received.apply(&diff);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputSnapshot {
+    /// Keys held down
+    pub keys: Vec<KeyCode>,
+    pub mods: KeyMod,
+    /// Mouse buttons held down
+    pub mouse_buttons: Vec<MouseButton>,
+    pub mouse_position: (f64, f64),
+    /// Raw mouse motion of the frame, scaled by the mouse settings
+    pub raw_motion: (f64, f64),
+    /// Scroll offset of the frame
+    pub scroll: (f64, f64),
+    /// Gamepads with something held or tilted, by ID
+    pub gamepads: Vec<GamepadSnapshot>,
+}
+
+/// State of one gamepad within an `InputSnapshot`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GamepadSnapshot {
+    pub id: u32,
+    /// Buttons held down
+    pub buttons: Vec<GamepadButton>,
+    /// Axes away from rest, with their shaped values
+    pub axes: Vec<(GamepadAxis, f32)>,
+}
+
+impl GamepadSnapshot {
+    fn is_idle(&self) -> bool {
+        self.buttons.is_empty() && self.axes.is_empty()
+    }
+}
+
+/// Changes from one `InputSnapshot` to the next
+///
+/// Presses and releases name the inputs that went down or up between the
+/// two frames. Continuous values are only present when they changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputSnapshotDiff {
+    pub keys_pressed: Vec<KeyCode>,
+    pub keys_released: Vec<KeyCode>,
+    pub mods: Option<KeyMod>,
+    pub mouse_pressed: Vec<MouseButton>,
+    pub mouse_released: Vec<MouseButton>,
+    pub mouse_position: Option<(f64, f64)>,
+    pub raw_motion: Option<(f64, f64)>,
+    pub scroll: Option<(f64, f64)>,
+    /// Gamepad buttons that went down, with the gamepad's ID
+    pub gamepad_pressed: Vec<(u32, GamepadButton)>,
+    pub gamepad_released: Vec<(u32, GamepadButton)>,
+    /// Axes whose value changed, with the gamepad's ID; 0.0 means back at rest
+    pub gamepad_axes: Vec<(u32, GamepadAxis, f32)>,
+}
+
+impl InputSnapshotDiff {
+    /// Whether nothing changed between the two snapshots
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl InputSnapshot {
+    /// Whether `key` is held
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// Whether `button` is held
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.contains(&button)
+    }
+
+    /// State of gamepad `id`, if anything on it is held or tilted
+    pub fn gamepad(&self, id: u32) -> Option<&GamepadSnapshot> {
+        self.gamepads.iter().find(|gamepad| gamepad.id == id)
+    }
+
+    /// What changed since `previous`
+    pub fn diff(&self, previous: &InputSnapshot) -> InputSnapshotDiff {
+        let mut diff = InputSnapshotDiff {
+            keys_pressed: added(&self.keys, &previous.keys),
+            keys_released: added(&previous.keys, &self.keys),
+            mods: changed(self.mods, previous.mods),
+            mouse_pressed: added(&self.mouse_buttons, &previous.mouse_buttons),
+            mouse_released: added(&previous.mouse_buttons, &self.mouse_buttons),
+            mouse_position: changed(self.mouse_position, previous.mouse_position),
+            raw_motion: changed(self.raw_motion, previous.raw_motion),
+            scroll: changed(self.scroll, previous.scroll),
+            ..InputSnapshotDiff::default()
+        };
+
+        let idle = GamepadSnapshot::default();
+        let mut ids: Vec<u32> = self.gamepads.iter().chain(&previous.gamepads).map(|gamepad| gamepad.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        for id in ids {
+            let now = self.gamepad(id).unwrap_or(&idle);
+            let before = previous.gamepad(id).unwrap_or(&idle);
+            diff.gamepad_pressed.extend(added(&now.buttons, &before.buttons).into_iter().map(|button| (id, button)));
+            diff.gamepad_released.extend(added(&before.buttons, &now.buttons).into_iter().map(|button| (id, button)));
+
+            let value = |gamepad: &GamepadSnapshot, axis| {
+                gamepad.axes.iter().find(|(a, _)| *a == axis).map_or(0.0, |(_, value)| *value)
+            };
+            for (axis, _) in now.axes.iter().chain(&before.axes) {
+                let value_now = value(now, *axis);
+                if value_now != value(before, *axis) && !diff.gamepad_axes.iter().any(|(i, a, _)| (*i, *a) == (id, *axis)) {
+                    diff.gamepad_axes.push((id, *axis, value_now));
+                }
+            }
+        }
+        diff
+    }
+
+    /// The snapshot `diff` leads to from this one
+    ///
+    /// `previous.apply(&current.diff(&previous))` equals `current`.
+    pub fn apply(&self, diff: &InputSnapshotDiff) -> InputSnapshot {
+        let mut next = self.clone();
+        next.keys.retain(|key| !diff.keys_released.contains(key));
+        next.keys.extend(&diff.keys_pressed);
+        next.mouse_buttons.retain(|button| !diff.mouse_released.contains(button));
+        next.mouse_buttons.extend(&diff.mouse_pressed);
+        next.mods = diff.mods.unwrap_or(next.mods);
+        next.mouse_position = diff.mouse_position.unwrap_or(next.mouse_position);
+        next.raw_motion = diff.raw_motion.unwrap_or(next.raw_motion);
+        next.scroll = diff.scroll.unwrap_or(next.scroll);
+
+        for (id, button) in &diff.gamepad_released {
+            gamepad_mut(&mut next.gamepads, *id).buttons.retain(|held| held != button);
+        }
+        for (id, button) in &diff.gamepad_pressed {
+            gamepad_mut(&mut next.gamepads, *id).buttons.push(*button);
+        }
+        for (id, axis, value) in &diff.gamepad_axes {
+            let axes = &mut gamepad_mut(&mut next.gamepads, *id).axes;
+            axes.retain(|(a, _)| a != axis);
+            if *value != 0.0 {
+                axes.push((*axis, *value));
+            }
+        }
+        next.normalized()
+    }
+
+    /// Sort every list and drop idle gamepads, so equal states are equal snapshots
+    pub(crate) fn normalized(mut self) -> Self {
+        self.keys.sort_unstable_by_key(|key| *key as u32);
+        self.keys.dedup();
+        self.mouse_buttons.sort_unstable_by_key(|button| *button as u32);
+        self.mouse_buttons.dedup();
+        self.gamepads.retain(|gamepad| !gamepad.is_idle());
+        self.gamepads.sort_unstable_by_key(|gamepad| gamepad.id);
+        for gamepad in &mut self.gamepads {
+            gamepad.buttons.sort_unstable_by_key(|button| *button as u32);
+            gamepad.buttons.dedup();
+            gamepad.axes.sort_unstable_by_key(|(axis, _)| *axis as u32);
+        }
+        self
+    }
+}
+
+/// `now` if it differs from `before`
+fn changed<T: PartialEq>(now: T, before: T) -> Option<T> {
+    (now != before).then_some(now)
+}
+
+/// Gamepad `id` of `gamepads`, added if missing
+fn gamepad_mut(gamepads: &mut Vec<GamepadSnapshot>, id: u32) -> &mut GamepadSnapshot {
+    let index = match gamepads.iter().position(|gamepad| gamepad.id == id) {
+        Some(index) => index,
+        None => {
+            gamepads.push(GamepadSnapshot { id, ..GamepadSnapshot::default() });
+            gamepads.len() - 1
+        }
+    };
+    &mut gamepads[index]
+}
+
+/// Items of `now` missing from `before`
+fn added<T: Copy + PartialEq>(now: &[T], before: &[T]) -> Vec<T> {
+    now.iter().filter(|item| !before.contains(item)).copied().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_and_apply_round_trip() {
+        let before = InputSnapshot {
+            keys: vec![KeyCode::W, KeyCode::A],
+            gamepads: vec![GamepadSnapshot { id: 1, buttons: vec![GamepadButton::A], axes: vec![(GamepadAxis::LeftStickX, 0.5)] }],
+            ..InputSnapshot::default()
+        }
+        .normalized();
+        let after = InputSnapshot {
+            keys: vec![KeyCode::W, KeyCode::Space],
+            mouse_position: (10.0, 20.0),
+            gamepads: vec![GamepadSnapshot { id: 0, buttons: vec![GamepadButton::B], axes: Vec::new() }],
+            ..InputSnapshot::default()
+        }
+        .normalized();
+
+        let diff = after.diff(&before);
+        assert_eq!((diff.keys_pressed.as_slice(), diff.keys_released.as_slice()), ([KeyCode::Space].as_slice(), [KeyCode::A].as_slice()));
+        assert_eq!(diff.gamepad_pressed, [(0, GamepadButton::B)]);
+        assert_eq!(diff.gamepad_released, [(1, GamepadButton::A)]);
+        assert_eq!(diff.gamepad_axes, [(1, GamepadAxis::LeftStickX, 0.0)]);
+        assert_eq!(diff.mouse_position, Some((10.0, 20.0)));
+        assert_eq!(diff.mods, None);
+
+        assert_eq!(before.apply(&diff), after);
+        assert!(after.diff(&after).is_empty());
+    }
+}
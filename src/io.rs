@@ -1,16 +1,24 @@
 #![allow(unused)]
 
+pub mod image;
 pub mod metrics;
+pub mod present;
 
-use crate::events::Event;
+use crate::events::{Event, KeyCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::any::Any;
+#[cfg(feature = "vulkan")]
+use std::ffi::CString;
 
 // Re-export key types for easier access
+pub use image::ImageBuffer;
 pub use metrics::{
     MetricsCollector, MetricsHandle, MetricsTimer, MetricsReporter, MetricsFactory,
     EventSystemMetrics, EventTypeMetrics, MetricsConfig
 };
+pub use present::{PresentStats, PresentTimer};
 
 /// Trait representing a window.
 ///
@@ -28,12 +36,358 @@ pub trait Window {
     fn size(&self) -> &Size;
     fn title(&self) -> &str;
     fn set_title(&mut self, title: &str);
+    /// Change the ID desktops match the window to its desktop entry by, see `WindowHint::AppId`
+    ///
+    /// Some desktops only read it when the window is first shown, so prefer the hint.
+    fn set_app_id(&mut self, app_id: &str) {
+        artifice_logging::warn!("Changing the app ID is not supported by this window backend, ignoring '{}'", app_id);
+    }
+    /// Replace the window's icon with `images`, the same picture at several sizes
+    ///
+    /// The window system picks the size that suits each place it shows the
+    /// icon. An empty slice restores the default icon.
+    fn set_icon(&mut self, images: &[IconImage]) {
+        artifice_logging::warn!("Window icons are not supported by this window backend, ignoring {} images", images.len());
+    }
+    /// Show or hide the window; one created with `WindowHint::Visible(false)` stays hidden until shown
+    fn set_visible(&mut self, visible: bool) {
+        artifice_logging::warn!("Showing and hiding windows is not supported by this window backend (visible: {})", visible);
+    }
+    fn is_visible(&self) -> bool {
+        true
+    }
+    /// Fade the whole window, from 0.0 for invisible to 1.0 for opaque
+    ///
+    /// For alpha that varies per pixel, create the window with `WindowHint::Transparent`.
+    fn set_opacity(&mut self, opacity: f32) {
+        artifice_logging::warn!("Window opacity is not supported by this window backend, ignoring {}", opacity);
+    }
+    fn opacity(&self) -> f32 {
+        1.0
+    }
+    /// Read back what has been drawn this frame
+    ///
+    /// Call it after rendering and before the buffers are swapped, as the
+    /// engine does for `Engine::save_screenshot`.
+    fn capture_framebuffer(&mut self) -> Result<ImageBuffer, String> {
+        Err("Framebuffer capture is not supported by this window backend".to_string())
+    }
     fn get_event_callback(&self) -> Option<Arc<Mutex<dyn FnMut(Event) + Send + 'static>>>;
     fn set_event_callback(&mut self, callback: Arc<Mutex<dyn FnMut(Event) + Send + 'static>>);
+    /// Show, hide or capture the cursor
+    ///
+    /// While captured, the cursor is hidden and held in the window and the
+    /// window reports `MouseRawMotion` events. Backends without cursor
+    /// control keep the normal cursor.
+    fn set_cursor_mode(&mut self, mode: CursorMode) {
+        artifice_logging::warn!("Cursor mode {:?} is not supported by this window backend", mode);
+    }
+    fn cursor_mode(&self) -> CursorMode {
+        CursorMode::Normal
+    }
+    /// Show `icon` while the cursor is over the window and not hidden
+    ///
+    /// Backends without a matching shape use the closest one they have.
+    fn set_cursor(&mut self, icon: CursorIcon) {
+        artifice_logging::warn!("Cursor icon {:?} is not supported by this window backend", icon);
+    }
+    fn cursor(&self) -> CursorIcon {
+        CursorIcon::Arrow
+    }
+    /// Make a cursor from `image` whose click point is `hotspot` pixels from its top left
+    ///
+    /// Returns a `CursorIcon::Custom` to pass to `set_cursor`. The cursor
+    /// belongs to this window and is gone after a backend switch.
+    fn create_cursor(&mut self, image: &CursorImage, hotspot: (u32, u32)) -> Result<CursorIcon, String> {
+        Err(format!("Custom cursors are not supported by this window backend, dropping {}x{} image", image.width(), image.height()))
+    }
+    /// Hide the cursor over the window; shorthand for `CursorMode::Hidden`
+    fn hide_cursor(&mut self) {
+        self.set_cursor_mode(CursorMode::Hidden);
+    }
+    /// Show the cursor again, releasing it if captured; shorthand for `CursorMode::Normal`
+    fn show_cursor(&mut self) {
+        self.set_cursor_mode(CursorMode::Normal);
+    }
+    /// Keep the window's size between `min` and `max` while the user resizes it
+    ///
+    /// `None` leaves that bound open.
+    fn set_size_limits(&mut self, min: Option<Size>, max: Option<Size>) {
+        artifice_logging::warn!("Size limits {:?} to {:?} are not supported by this window backend", min, max);
+    }
+    fn size_limits(&self) -> (Option<Size>, Option<Size>) {
+        (None, None)
+    }
+    /// Keep the window's width to height at `ratio` while the user resizes it; `None` unlocks it
+    fn set_aspect_ratio(&mut self, ratio: Option<(u32, u32)>) {
+        artifice_logging::warn!("Aspect ratio {:?} is not supported by this window backend", ratio);
+    }
+    fn aspect_ratio(&self) -> Option<(u32, u32)> {
+        None
+    }
+    /// Ask the window manager to maximize or restore the window
+    ///
+    /// Window managers may answer later, so `is_maximized` can lag behind.
+    fn set_maximized(&mut self, maximized: bool) {
+        artifice_logging::warn!("Maximizing ({}) is not supported by this window backend", maximized);
+    }
+    fn is_maximized(&self) -> bool {
+        false
+    }
+    /// Switch between windowed, borderless and exclusive fullscreen
+    ///
+    /// Going back to `Windowed` restores the size and position the window
+    /// had before it first left windowed mode.
+    fn set_fullscreen(&mut self, mode: FullscreenMode) {
+        artifice_logging::warn!("Fullscreen mode {:?} is not supported by this window backend", mode);
+    }
+    fn fullscreen(&self) -> FullscreenMode {
+        FullscreenMode::Windowed
+    }
+    /// Switch between windowed and borderless fullscreen, as Alt+Enter does in most games
+    fn toggle_fullscreen(&mut self) {
+        let mode = match self.fullscreen() {
+            FullscreenMode::Windowed => FullscreenMode::Borderless,
+            _ => FullscreenMode::Windowed,
+        };
+        self.set_fullscreen(mode);
+    }
+    /// Video modes monitor `monitor` can show, for exclusive fullscreen
+    ///
+    /// Monitors are numbered from 0, the primary monitor first. Empty when
+    /// the monitor does not exist or the backend cannot change video modes.
+    fn video_modes(&mut self, monitor: usize) -> Vec<VideoMode> {
+        Vec::new()
+    }
+    /// Monitor under the window's center, numbered as for `video_modes`
+    ///
+    /// `None` when the backend cannot tell, as on Wayland where clients
+    /// do not learn where their windows are.
+    fn monitor(&mut self) -> Option<usize> {
+        None
+    }
+    /// Wait for the monitor's vertical blank before each swap, to avoid tearing
+    fn set_vsync(&mut self, enabled: bool) {
+        artifice_logging::warn!("Vsync control is not supported by this window backend (vsync: {})", enabled);
+    }
+    /// Refresh rate in Hz of the monitor the window is on, `None` if unknown
+    fn refresh_rate(&mut self) -> Option<u32> {
+        None
+    }
+    /// Timing of the frames `update` has presented, `None` if the backend does not measure it
+    fn present_stats(&self) -> Option<PresentStats> {
+        None
+    }
+    /// Text on the system clipboard, or `None` if it is empty or not text
+    fn clipboard_text(&mut self) -> Option<String> {
+        artifice_logging::warn!("Clipboard is not supported by this window backend");
+        None
+    }
+    /// Put `text` on the system clipboard
+    fn set_clipboard_text(&mut self, text: &str) {
+        artifice_logging::warn!("Clipboard is not supported by this window backend, dropping {} bytes", text.len());
+    }
+    /// Text of the primary selection, which X11 fills with the last text selected and pastes on middle click
+    fn primary_selection_text(&mut self) -> Option<String> {
+        artifice_logging::warn!("Primary selection is not supported by this window backend");
+        None
+    }
+    /// Offer `text` as the primary selection, as selecting it with the mouse would
+    fn set_primary_selection_text(&mut self, text: &str) {
+        artifice_logging::warn!("Primary selection is not supported by this window backend, dropping {} bytes", text.len());
+    }
+    /// Labels of the keys whose symbol depends on the keyboard layout, as the current layout prints them
+    ///
+    /// Backends that translate keys by layout report each `KeyCode` under
+    /// the symbol it was translated from, e.g. "Ö" for the key left of the
+    /// apostrophe on a German layout.
+    fn key_names(&self) -> HashMap<KeyCode, String> {
+        HashMap::new()
+    }
+    /// Tell the input method where the text cursor is, so its candidate window opens beside it
+    ///
+    /// `position` and `size` are in window coordinates from the top left.
+    /// Call it whenever the focused text field or its cursor moves. Backends
+    /// connect to the input method on the first call, so windows that never
+    /// call it keep their key events to themselves.
+    fn set_ime_cursor_area(&mut self, position: Position, size: Size) {
+        artifice_logging::warn!("IME cursor area at {:?} of {:?} is not supported by this window backend", position, size);
+    }
     /// Enable downcasting to concrete window types for backend-specific operations
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
+/// How the window treats the mouse cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorMode {
+    /// Visible and free to leave the window
+    #[default]
+    Normal,
+    /// Invisible over the window but free to leave it
+    Hidden,
+    /// Invisible and locked to the window, reporting raw motion; for first-person cameras
+    Captured,
+    /// Visible but kept inside the window; for strategy games that scroll at the screen edge
+    Confined,
+}
+
+impl CursorMode {
+    /// Whether the cursor image shows over the window in this mode
+    pub fn shows_cursor(self) -> bool {
+        matches!(self, CursorMode::Normal | CursorMode::Confined)
+    }
+}
+
+/// How the window covers the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum FullscreenMode {
+    #[default]
+    Windowed,
+    /// Undecorated and covering the monitor the window is on, keeping its video mode
+    Borderless,
+    /// Owns monitor `monitor`, switched to `video_mode` until the window leaves this mode
+    Exclusive { monitor: usize, video_mode: VideoMode },
+}
+
+/// Resolution and refresh rate of a monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VideoMode {
+    pub width: u32,
+    pub height: u32,
+    /// In hertz
+    pub refresh_rate: u32,
+}
+
+/// Standard cursor shapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorIcon {
+    #[default]
+    Arrow,
+    /// Text insertion bar
+    IBeam,
+    /// Pointing hand, for links and buttons
+    Hand,
+    Crosshair,
+    /// Left-right arrow, for resizing horizontally
+    ResizeHorizontal,
+    /// Up-down arrow, for resizing vertically
+    ResizeVertical,
+    /// Diagonal arrow from top left to bottom right
+    ResizeNwSe,
+    /// Diagonal arrow from top right to bottom left
+    ResizeNeSw,
+    /// Four-way arrow, for moving things
+    ResizeAll,
+    NotAllowed,
+    Wait,
+    /// A cursor made from an image by `Window::create_cursor`
+    Custom(u32),
+}
+
+/// Pixels of a custom cursor, as rows of straight RGBA bytes from the top left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorImage {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+impl CursorImage {
+    /// Fails unless `rgba` holds exactly `width * height` pixels
+    pub fn new(width: u32, height: u32, rgba: Vec<u8>) -> Result<Self, String> {
+        check_rgba("Cursor", width, height, &rgba)?;
+        Ok(Self { width, height, rgba })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    /// Fails unless `hotspot` lies on the image
+    pub(crate) fn check_hotspot(&self, hotspot: (u32, u32)) -> Result<(), String> {
+        if hotspot.0 >= self.width || hotspot.1 >= self.height {
+            return Err(format!("Cursor hotspot {:?} is outside the {}x{} image", hotspot, self.width, self.height));
+        }
+        Ok(())
+    }
+
+    /// Pixels as premultiplied ARGB words, the format X11 and Wayland cursors use
+    pub(crate) fn premultiplied_argb(&self) -> Vec<u32> {
+        argb_words(&self.rgba, true)
+    }
+}
+
+/// Pixels of one size of a window icon, as rows of straight RGBA bytes from the top left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IconImage {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+impl IconImage {
+    /// Fails unless `rgba` holds exactly `width * height` pixels
+    pub fn new(width: u32, height: u32, rgba: Vec<u8>) -> Result<Self, String> {
+        check_rgba("Icon", width, height, &rgba)?;
+        Ok(Self { width, height, rgba })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    /// Pixels as straight ARGB words, the format of X11's `_NET_WM_ICON`
+    pub(crate) fn argb(&self) -> Vec<u32> {
+        argb_words(&self.rgba, false)
+    }
+
+    /// Pixels as premultiplied ARGB words, the format of Wayland shm buffers
+    pub(crate) fn premultiplied_argb(&self) -> Vec<u32> {
+        argb_words(&self.rgba, true)
+    }
+}
+
+/// Fails unless `rgba` holds exactly `width * height` pixels of a non-empty image
+fn check_rgba(kind: &str, width: u32, height: u32, rgba: &[u8]) -> Result<(), String> {
+    if width == 0 || height == 0 {
+        return Err(format!("{} image must not be empty, got {}x{}", kind, width, height));
+    }
+    let expected = (width as usize).checked_mul(height as usize).and_then(|pixels| pixels.checked_mul(4));
+    if expected != Some(rgba.len()) {
+        return Err(format!("{} image of {}x{} needs 4 RGBA bytes per pixel, got {} bytes", kind, width, height, rgba.len()));
+    }
+    Ok(())
+}
+
+fn argb_words(rgba: &[u8], premultiplied: bool) -> Vec<u32> {
+    let scale = |channel: u8, alpha: u8| match premultiplied {
+        true => (channel as u32 * alpha as u32 + 127) / 255,
+        false => channel as u32,
+    };
+    rgba.chunks_exact(4)
+        .map(|pixel| {
+            let [r, g, b, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            (a as u32) << 24 | scale(r, a) << 16 | scale(g, a) << 8 | scale(b, a)
+        })
+        .collect()
+}
+
 /// Extends the Window trait with OpenGL-specific functionality.
 pub trait OpenGLWindow: Window {
     fn make_current(&mut self);
@@ -41,18 +395,44 @@ pub trait OpenGLWindow: Window {
     fn swap_buffers(&mut self);
     /// Reload OpenGL function pointers - essential for backend switching
     fn reload_opengl_functions(&mut self);
+    /// Open another window of this backend whose context shares textures, buffers and shaders with this one's
+    fn create_shared_window(&mut self, width: u32, height: u32, title: &str, hints: &[WindowHint]) -> Result<Box<dyn Window>, String> {
+        let _ = hints;
+        Err(format!("Shared OpenGL contexts are not supported by this window backend, cannot open '{}' ({}x{})", title, width, height))
+    }
+}
+
+/// Extends the Window trait with Vulkan surface creation.
+#[cfg(feature = "vulkan")]
+pub trait VulkanWindow: Window {
+    /// Instance extensions that surfaces of this window need
+    ///
+    /// Enable them on the instance later passed to `create_surface`. Empty
+    /// when the backend finds no Vulkan loader.
+    fn required_instance_extensions(&self) -> Vec<CString>;
+    /// Create a surface presenting to this window
+    ///
+    /// The caller owns the surface and must destroy it before the window.
+    fn create_surface(&self, entry: &ash::Entry, instance: &ash::Instance) -> ash::prelude::VkResult<ash::vk::SurfaceKHR>;
 }
 
 /// Window hints for configuring window creation
 #[derive(Debug, Clone)]
 pub enum WindowHint {
+    /// Whether the user can resize the window; on by default
     Resizable(bool),
+    /// Whether the window is shown once created; on by default, see `Window::set_visible`
     Visible(bool),
+    /// Whether the window has a title bar and border; off gives e.g. a splash screen
     Decorated(bool),
+    /// Whether the window takes input focus once shown; on by default
     Focused(bool),
     AutoIconify(bool),
+    /// Keep the window above all others
     Floating(bool),
+    /// Open the window maximized
     Maximized(bool),
+    /// Give the framebuffer an alpha channel the desktop shows through
     Transparent(bool),
     Samples(u32),
     DoubleBuffer(bool),
@@ -60,6 +440,12 @@ pub enum WindowHint {
     ContextVersion(u32, u32),
     OpenGLProfile(OpenGLProfile),
     OpenGLForwardCompat(bool),
+    /// Name of the application's `.desktop` file without the extension, e.g. `org.example.Game`
+    ///
+    /// Desktops match windows to their desktop entry by it, for the right
+    /// icon and task bar grouping. Sets `WM_CLASS` on X11 and the `app_id`
+    /// on Wayland.
+    AppId(String),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -69,6 +455,48 @@ pub enum OpenGLProfile {
     Compatibility,
 }
 
+/// Identifies one of the engine's windows; events from a window carry its ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct WindowId(pub u32);
+
+impl WindowId {
+    /// The window the engine opens on start; closing it stops the engine
+    pub const MAIN: WindowId = WindowId(0);
+}
+
+/// What to open with `Engine::create_window`
+#[derive(Debug, Clone)]
+pub struct WindowDesc {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub hints: Vec<WindowHint>,
+    /// Share the main window's OpenGL context, so its textures, buffers and shaders work here too
+    pub shared_context: bool,
+}
+
+impl WindowDesc {
+    pub fn new(title: impl Into<String>, width: u32, height: u32) -> Self {
+        Self {
+            title: title.into(),
+            width,
+            height,
+            hints: Vec::new(),
+            shared_context: false,
+        }
+    }
+
+    pub fn with_hints(mut self, hints: impl IntoIterator<Item = WindowHint>) -> Self {
+        self.hints.extend(hints);
+        self
+    }
+
+    pub fn with_shared_context(mut self) -> Self {
+        self.shared_context = true;
+        self
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Size(pub u32, pub u32);
 
@@ -89,6 +517,29 @@ impl Size {
     }
 }
 
+/// `size` clamped to `min` and `max`, then shortened on one side to match `aspect`
+///
+/// For backends whose window system leaves the constraints to the client.
+pub(crate) fn constrain_size(size: Size, min: Option<Size>, max: Option<Size>, aspect: Option<(u32, u32)>) -> Size {
+    let clamp = |value: u32, min: Option<u32>, max: Option<u32>| value.max(min.unwrap_or(0)).min(max.unwrap_or(u32::MAX));
+    let mut width = clamp(size.0, min.map(|min| min.0), max.map(|max| max.0)) as u64;
+    let mut height = clamp(size.1, min.map(|min| min.1), max.map(|max| max.1)) as u64;
+    if let Some((ratio_width, ratio_height)) = aspect.filter(|&(width, height)| width > 0 && height > 0) {
+        let (ratio_width, ratio_height) = (ratio_width as u64, ratio_height as u64);
+        if width * ratio_height > height * ratio_width {
+            width = height * ratio_width / ratio_height;
+        } else {
+            height = width * ratio_height / ratio_width;
+        }
+    }
+    Size(width.max(1) as u32, height.max(1) as u32)
+}
+
+/// `opacity` clamped to 0..=1 as a fraction of `u32::MAX`, the fixed point form compositors read
+pub(crate) fn opacity_fraction(opacity: f32) -> u32 {
+    (opacity.clamp(0.0, 1.0) as f64 * u32::MAX as f64) as u32
+}
+
 impl From<(u32, u32)> for Size {
     /// Converts a `(u32, u32)` into a `Size`.
     fn from((width, height): (u32, u32)) -> Self {
@@ -143,3 +594,55 @@ impl From<Position> for (i32, i32) {
         (position.0, position.1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_image_checks_size_and_premultiplies() {
+        assert!(CursorImage::new(2, 2, vec![0; 12]).is_err());
+        assert!(CursorImage::new(0, 1, Vec::new()).is_err());
+
+        let image = CursorImage::new(2, 1, vec![255, 128, 0, 255, 255, 255, 255, 128]).unwrap();
+        assert_eq!(image.premultiplied_argb(), [0xFFFF8000, 0x80808080]);
+        assert!(image.check_hotspot((1, 0)).is_ok());
+        assert!(image.check_hotspot((0, 1)).is_err());
+    }
+
+    #[test]
+    fn test_icon_image_keeps_straight_alpha() {
+        assert!(IconImage::new(1, 1, vec![0; 3]).is_err());
+        let image = IconImage::new(2, 1, vec![255, 128, 0, 255, 255, 255, 255, 128]).unwrap();
+        assert_eq!(image.argb(), [0xFFFF8000, 0x80FFFFFF]);
+        assert_eq!(image.premultiplied_argb(), [0xFFFF8000, 0x80808080]);
+    }
+
+    #[test]
+    fn test_confined_cursor_stays_visible() {
+        assert!(CursorMode::Normal.shows_cursor());
+        assert!(CursorMode::Confined.shows_cursor());
+        assert!(!CursorMode::Hidden.shows_cursor());
+        assert!(!CursorMode::Captured.shows_cursor());
+    }
+
+    #[test]
+    fn test_opacity_fraction_spans_u32() {
+        assert_eq!(opacity_fraction(0.0), 0);
+        assert_eq!(opacity_fraction(1.0), u32::MAX);
+        assert_eq!(opacity_fraction(0.5), u32::MAX / 2);
+        // Out of range values are clamped rather than wrapped
+        assert_eq!(opacity_fraction(-0.5), 0);
+        assert_eq!(opacity_fraction(3.0), u32::MAX);
+    }
+
+    #[test]
+    fn test_constrain_size_clamps_then_keeps_aspect() {
+        let size = |size: Size| size.size();
+        assert_eq!(size(constrain_size(Size(100, 50), Some(Size(200, 10)), None, None)), (200, 50));
+        assert_eq!(size(constrain_size(Size(900, 900), None, Some(Size(800, 600)), None)), (800, 600));
+        // The long side gives way to the ratio
+        assert_eq!(size(constrain_size(Size(1000, 500), None, None, Some((16, 9)))), (888, 500));
+        assert_eq!(size(constrain_size(Size(1600, 1000), None, Some(Size(1600, 1600)), Some((16, 9)))), (1600, 900));
+    }
+}
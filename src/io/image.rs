@@ -0,0 +1,264 @@
+use std::fs;
+use std::path::Path;
+
+/// Pixels read back from a window, as rows of straight RGBA bytes from the top left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageBuffer {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+/// Largest payload of a stored deflate block
+const STORED_BLOCK: usize = 0xFFFF;
+/// Most memory `from_png` spends on one decoded image, enough for 8192x8192 RGBA
+const MAX_DECODED_BYTES: usize = 256 * 1024 * 1024;
+
+impl ImageBuffer {
+    /// Fails unless `rgba` holds exactly `width * height` pixels
+    pub fn new(width: u32, height: u32, rgba: Vec<u8>) -> Result<Self, String> {
+        super::check_rgba("Framebuffer", width, height, &rgba)?;
+        Ok(Self { width, height, rgba })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let offset = (y as usize * self.width as usize + x as usize) * 4;
+        Some([self.rgba[offset], self.rgba[offset + 1], self.rgba[offset + 2], self.rgba[offset + 3]])
+    }
+
+    /// Largest difference of any channel of any pixel, or `None` if the sizes differ
+    ///
+    /// Golden-image tests compare against a small tolerance rather than
+    /// exact equality, since drivers round blending slightly differently.
+    pub fn max_difference(&self, other: &ImageBuffer) -> Option<u8> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return None;
+        }
+        Some(self.rgba.iter().zip(&other.rgba).map(|(a, b)| a.abs_diff(*b)).max().unwrap_or(0))
+    }
+
+    /// Turn rows stored bottom first, as OpenGL reads them, into top first
+    pub(crate) fn flip_rows(&mut self) {
+        let stride = self.width as usize * 4;
+        let height = self.height as usize;
+        for row in 0..height / 2 {
+            let (top, bottom) = self.rgba.split_at_mut((height - 1 - row) * stride);
+            top[row * stride..(row + 1) * stride].swap_with_slice(&mut bottom[..stride]);
+        }
+    }
+
+    /// Encode as an uncompressed PNG
+    ///
+    /// Stored deflate blocks make encoding a plain copy, which keeps
+    /// screenshots cheap to take; the files are larger than a compressor
+    /// would make them but open in any viewer.
+    pub fn to_png(&self) -> Vec<u8> {
+        let stride = self.width as usize * 4;
+        // Each scanline starts with filter type 0 (none)
+        let mut scanlines = Vec::with_capacity((stride + 1) * self.height as usize);
+        for row in self.rgba.chunks_exact(stride) {
+            scanlines.push(0);
+            scanlines.extend_from_slice(row);
+        }
+
+        let mut zlib = vec![0x78, 0x01];
+        let blocks = scanlines.chunks(STORED_BLOCK);
+        let count = blocks.len();
+        for (index, block) in blocks.enumerate() {
+            let len = block.len() as u16;
+            zlib.push((index + 1 == count) as u8);
+            zlib.extend_from_slice(&len.to_le_bytes());
+            zlib.extend_from_slice(&(!len).to_le_bytes());
+            zlib.extend_from_slice(block);
+        }
+        zlib.extend_from_slice(&adler32(&scanlines).to_be_bytes());
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        // 8 bits per channel, RGBA, deflate, adaptive filtering, no interlace
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        write_chunk(&mut png, b"IHDR", &header);
+        write_chunk(&mut png, b"IDAT", &zlib);
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    /// Decode a PNG of any colour type and bit depth into 8-bit RGBA
+    ///
+    /// Palette, greyscale and 16-bit images are converted; interlaced ones
+    /// are put back together. Images needing more than `MAX_DECODED_BYTES`
+    /// are refused before anything is allocated for them.
+    pub fn from_png(bytes: &[u8]) -> Result<Self, String> {
+        let mut decoder = png::Decoder::new_with_limits(bytes, png::Limits { bytes: MAX_DECODED_BYTES });
+        // Palettes and low bit depths become whole bytes, 16-bit channels lose their low byte
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(|e| format!("Invalid PNG: {}", e))?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut pixels).map_err(|e| format!("Invalid PNG: {}", e))?;
+        pixels.truncate(frame.buffer_size());
+
+        let rgba = match frame.color_type {
+            png::ColorType::Rgba => pixels,
+            png::ColorType::Rgb => pixels.chunks_exact(3).flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255]).collect(),
+            png::ColorType::GrayscaleAlpha => pixels.chunks_exact(2).flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]]).collect(),
+            png::ColorType::Grayscale => pixels.iter().flat_map(|&gray| [gray, gray, gray, 255]).collect(),
+            png::ColorType::Indexed => return Err("PNG palette was not expanded".to_string()),
+        };
+        Self::new(frame.width, frame.height, rgba)
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        fs::write(path, self.to_png()).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn load_png(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_png(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Read the current context's back buffer, which holds the frame until buffers are swapped
+pub(crate) fn read_gl_framebuffer(width: u32, height: u32) -> Result<ImageBuffer, String> {
+    let mut rgba = vec![0u8; width as usize * height as usize * 4];
+    unsafe {
+        gl::ReadBuffer(gl::BACK);
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(0, 0, width as i32, height as i32, gl::RGBA, gl::UNSIGNED_BYTE, rgba.as_mut_ptr() as *mut _);
+        let error = gl::GetError();
+        if error != gl::NO_ERROR {
+            return Err(format!("Reading the framebuffer failed with OpenGL error 0x{:X}", error));
+        }
+    }
+    let mut image = ImageBuffer::new(width, height, rgba)?;
+    image.flip_rows();
+    Ok(image)
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&crc32(&[kind, data]).to_be_bytes());
+}
+
+fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_png_round_trip_and_flip() {
+        // Wide enough to span two stored blocks
+        let (width, height) = (200, 100);
+        let rgba: Vec<u8> = (0..width * height * 4).map(|i| (i % 251) as u8).collect();
+        let image = ImageBuffer::new(width, height, rgba).unwrap();
+
+        let png = image.to_png();
+        assert_eq!(&png[..8], &PNG_SIGNATURE);
+        assert_eq!(ImageBuffer::from_png(&png).unwrap(), image);
+        assert_eq!(crc32(&[b"IEND"]), 0xAE42_6082);
+
+        let mut flipped = image.clone();
+        flipped.flip_rows();
+        assert_eq!(flipped.pixel(3, 0), image.pixel(3, height - 1));
+        assert_eq!(flipped.max_difference(&image).map(|diff| diff > 0), Some(true));
+        flipped.flip_rows();
+        assert_eq!(flipped.max_difference(&image), Some(0));
+    }
+
+    /// Encode with the png crate, compressed and filtered as image editors write files
+    fn encode(width: u32, height: u32, color: png::ColorType, depth: png::BitDepth, data: &[u8], palette: Option<(&[u8], &[u8])>) -> Vec<u8> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(depth);
+        encoder.set_compression(png::Compression::Best);
+        encoder.set_adaptive_filter(png::AdaptiveFilterType::Adaptive);
+        if let Some((palette, alpha)) = palette {
+            encoder.set_palette(palette);
+            encoder.set_trns(alpha);
+        }
+        encoder.write_header().unwrap().write_image_data(data).unwrap();
+        png
+    }
+
+    #[test]
+    fn test_decode_compressed_pngs_of_every_colour_type() {
+        // A gradient, so the encoder picks different filters for different rows
+        let (width, height) = (64, 48);
+        let rgba: Vec<u8> = (0..height).flat_map(|y| (0..width).flat_map(move |x| [x as u8 * 4, y as u8 * 5, (x + y) as u8, 200])).collect();
+        let png = encode(width, height, png::ColorType::Rgba, png::BitDepth::Eight, &rgba, None);
+        assert_eq!(ImageBuffer::from_png(&png).unwrap(), ImageBuffer::new(width, height, rgba).unwrap());
+
+        let rgb16: Vec<u8> = [0xFF00u16, 0x8000, 0x0001, 0x1234, 0xABCD, 0xFFFF].iter().flat_map(|c| c.to_be_bytes()).collect();
+        let image = ImageBuffer::from_png(&encode(2, 1, png::ColorType::Rgb, png::BitDepth::Sixteen, &rgb16, None)).unwrap();
+        assert_eq!(image.rgba(), [0xFF, 0x80, 0x00, 255, 0x12, 0xAB, 0xFF, 255]);
+
+        let gray = ImageBuffer::from_png(&encode(2, 1, png::ColorType::GrayscaleAlpha, png::BitDepth::Eight, &[10, 20, 30, 40], None)).unwrap();
+        assert_eq!(gray.rgba(), [10, 10, 10, 20, 30, 30, 30, 40]);
+
+        // Two-bit palette indexes 0, 1, 2, 1, with the first entry see-through
+        let palette: &[u8] = &[0, 0, 0, 255, 0, 0, 0, 0, 255];
+        let indexed = encode(4, 1, png::ColorType::Indexed, png::BitDepth::Two, &[0b0001_1001], Some((palette, &[0])));
+        let image = ImageBuffer::from_png(&indexed).unwrap();
+        assert_eq!(image.rgba(), [0, 0, 0, 0, 255, 0, 0, 255, 0, 0, 255, 255, 255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_decode_refuses_corrupt_pngs() {
+        let png = ImageBuffer::new(1, 1, vec![1, 2, 3, 4]).unwrap().to_png();
+        assert!(ImageBuffer::from_png(&png[..png.len() - 20]).is_err());
+        assert!(ImageBuffer::from_png(b"GIF89a").is_err());
+
+        // A header claiming a huge image is refused without allocating for it
+        let mut huge = png.clone();
+        huge[16..24].copy_from_slice(&[0x7F, 0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0xFF, 0xFF]);
+        let crc = crc32(&[&huge[12..29]]);
+        huge[29..33].copy_from_slice(&crc.to_be_bytes());
+        assert!(ImageBuffer::from_png(&huge).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::events::FilterStats;
+use crate::io::PresentStats;
 use artifice_logging::{debug, info, trace, warn};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
@@ -11,6 +13,8 @@ pub struct EventSystemMetrics {
     pub events_processed: u64,
     /// Total number of events dropped due to queue full
     pub events_dropped: u64,
+    /// Input events missing from their device's sequence when later ones arrived
+    pub events_missed: u64,
     /// Average event processing time in microseconds
     pub avg_processing_time_us: f64,
     /// Peak event processing time in microseconds
@@ -23,6 +27,10 @@ pub struct EventSystemMetrics {
     pub memory_usage_bytes: u64,
     /// Metrics by event type
     pub event_type_metrics: HashMap<String, EventTypeMetrics>,
+    /// Allowed and blocked counts of the global event filters
+    pub filter_stats: Vec<FilterStats>,
+    /// Frame presentation timing of the main window, if its backend measures it
+    pub present: Option<PresentStats>,
 }
 
 /// Metrics for a specific event type
@@ -58,6 +66,7 @@ impl EventTypeMetrics {
 struct AtomicMetrics {
     events_processed: AtomicU64,
     events_dropped: AtomicU64,
+    events_missed: AtomicU64,
     total_processing_time_us: AtomicU64,
     peak_processing_time_us: AtomicU64,
     queue_size: AtomicUsize,
@@ -70,6 +79,7 @@ impl AtomicMetrics {
         Self {
             events_processed: AtomicU64::new(0),
             events_dropped: AtomicU64::new(0),
+            events_missed: AtomicU64::new(0),
             total_processing_time_us: AtomicU64::new(0),
             peak_processing_time_us: AtomicU64::new(0),
             queue_size: AtomicUsize::new(0),
@@ -83,6 +93,8 @@ impl AtomicMetrics {
 pub struct MetricsCollector {
     atomic_metrics: Arc<AtomicMetrics>,
     event_type_metrics: Arc<RwLock<HashMap<String, EventTypeMetrics>>>,
+    filter_stats: RwLock<Vec<FilterStats>>,
+    present: RwLock<Option<PresentStats>>,
     start_time: Instant,
     last_snapshot_time: Arc<Mutex<Instant>>,
     collection_enabled: Arc<std::sync::atomic::AtomicBool>,
@@ -93,6 +105,8 @@ impl MetricsCollector {
         Self {
             atomic_metrics: Arc::new(AtomicMetrics::new()),
             event_type_metrics: Arc::new(RwLock::new(HashMap::new())),
+            filter_stats: RwLock::new(Vec::new()),
+            present: RwLock::new(None),
             start_time: Instant::now(),
             last_snapshot_time: Arc::new(Mutex::new(Instant::now())),
             collection_enabled: Arc::new(std::sync::atomic::AtomicBool::new(true)),
@@ -159,6 +173,16 @@ impl MetricsCollector {
         warn!("Event dropped: {}", event_type);
     }
 
+    /// Record gaps found in the per-device input sequences
+    pub fn record_events_missed(&self, count: u64) {
+        if !self.collection_enabled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        self.atomic_metrics.events_missed.fetch_add(count, Ordering::Relaxed);
+        warn!("{} input events missing from their device sequence", count);
+    }
+
     /// Update queue metrics
     pub fn update_queue_metrics(&self, current_size: usize, capacity: usize) {
         if !self.collection_enabled.load(Ordering::Relaxed) {
@@ -178,6 +202,28 @@ impl MetricsCollector {
         self.atomic_metrics.memory_usage_bytes.store(bytes, Ordering::Relaxed);
     }
 
+    /// Replace the event filter statistics with a fresh snapshot
+    pub fn update_filter_stats(&self, stats: Vec<FilterStats>) {
+        if !self.collection_enabled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        if let Ok(mut filter_stats) = self.filter_stats.write() {
+            *filter_stats = stats;
+        }
+    }
+
+    /// Replace the frame presentation statistics with the latest from the window
+    pub fn record_present(&self, stats: PresentStats) {
+        if !self.collection_enabled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        if let Ok(mut present) = self.present.write() {
+            *present = Some(stats);
+        }
+    }
+
     /// Get current metrics snapshot
     pub fn get_metrics(&self) -> EventSystemMetrics {
         let events_processed = self.atomic_metrics.events_processed.load(Ordering::Relaxed);
@@ -216,12 +262,15 @@ impl MetricsCollector {
         EventSystemMetrics {
             events_processed,
             events_dropped: self.atomic_metrics.events_dropped.load(Ordering::Relaxed),
+            events_missed: self.atomic_metrics.events_missed.load(Ordering::Relaxed),
             avg_processing_time_us,
             peak_processing_time_us: self.atomic_metrics.peak_processing_time_us.load(Ordering::Relaxed),
             events_per_second,
             queue_utilization,
             memory_usage_bytes: self.atomic_metrics.memory_usage_bytes.load(Ordering::Relaxed),
             event_type_metrics,
+            filter_stats: self.filter_stats.read().map(|stats| stats.clone()).unwrap_or_default(),
+            present: self.present.read().map(|present| *present).unwrap_or_default(),
         }
     }
 
@@ -229,6 +278,7 @@ impl MetricsCollector {
     pub fn reset(&self) {
         self.atomic_metrics.events_processed.store(0, Ordering::Relaxed);
         self.atomic_metrics.events_dropped.store(0, Ordering::Relaxed);
+        self.atomic_metrics.events_missed.store(0, Ordering::Relaxed);
         self.atomic_metrics.total_processing_time_us.store(0, Ordering::Relaxed);
         self.atomic_metrics.peak_processing_time_us.store(0, Ordering::Relaxed);
         self.atomic_metrics.memory_usage_bytes.store(0, Ordering::Relaxed);
@@ -236,6 +286,12 @@ impl MetricsCollector {
         if let Ok(mut metrics) = self.event_type_metrics.write() {
             metrics.clear();
         }
+        if let Ok(mut filter_stats) = self.filter_stats.write() {
+            filter_stats.clear();
+        }
+        if let Ok(mut present) = self.present.write() {
+            *present = None;
+        }
 
         debug!("Event system metrics reset");
     }
@@ -256,6 +312,7 @@ impl MetricsCollector {
         info!("=== Event System Metrics Summary ===");
         info!("Events Processed: {}", metrics.events_processed);
         info!("Events Dropped: {}", metrics.events_dropped);
+        info!("Events Missed: {}", metrics.events_missed);
         info!("Events/Second: {:.2}", metrics.events_per_second);
         info!("Avg Processing Time: {:.2}μs", metrics.avg_processing_time_us);
         info!("Peak Processing Time: {}μs", metrics.peak_processing_time_us);
@@ -274,6 +331,24 @@ impl MetricsCollector {
                 );
             }
         }
+
+        if !metrics.filter_stats.is_empty() {
+            info!("--- Event Filters ---");
+            for stats in &metrics.filter_stats {
+                info!("{}: {} allowed, {} blocked", stats.name, stats.allowed, stats.blocked);
+            }
+        }
+
+        if let Some(present) = metrics.present {
+            info!("--- Frame Presentation ---");
+            match present.refresh_rate {
+                Some(rate) => info!("Refresh Rate: {}Hz", rate),
+                None => info!("Refresh Rate: unknown"),
+            }
+            info!("Frames Presented: {}", present.frames);
+            info!("Missed VSyncs: {}", present.missed_vsyncs);
+            info!("Last Swap Time: {:.2}ms", present.swap_time.as_secs_f64() * 1000.0);
+        }
         info!("=====================================");
     }
 }
@@ -0,0 +1,112 @@
+use std::time::{Duration, Instant};
+
+/// How often a backend asks the window system for the refresh rate again
+const REFRESH_RATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Timing of the frames a window has presented
+///
+/// A frame pacer compares these against the refresh rate: missed vsyncs
+/// mean frames took longer than a refresh, and a swap time close to the
+/// refresh period means the swap blocked waiting for vsync.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PresentStats {
+    /// Refresh rate in Hz of the monitor the window was on, if known
+    pub refresh_rate: Option<u32>,
+    /// Frames presented since the window opened
+    pub frames: u64,
+    /// Refreshes missed by all frames together
+    pub missed_vsyncs: u64,
+    /// Refreshes missed between the last two frames
+    pub last_missed_vsyncs: u32,
+    /// How long the last buffer swap took
+    pub swap_time: Duration,
+    /// Time between the last two presents
+    pub frame_interval: Duration,
+}
+
+/// Times a backend's buffer swaps and counts the refreshes they miss
+///
+/// Missed refreshes are only counted while the refresh rate is known. With
+/// vsync off frames come faster than the refresh, so none are missed.
+#[derive(Debug, Clone, Default)]
+pub struct PresentTimer {
+    stats: PresentStats,
+    last_present: Option<Instant>,
+    refresh_rate_checked: Option<Instant>,
+}
+
+impl PresentTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the refresh rate should be looked up again, as it changes when the window moves monitors
+    pub fn needs_refresh_rate(&self) -> bool {
+        self.refresh_rate_checked.is_none_or(|checked| checked.elapsed() >= REFRESH_RATE_INTERVAL)
+    }
+
+    pub fn set_refresh_rate(&mut self, refresh_rate: Option<u32>) {
+        self.stats.refresh_rate = refresh_rate.filter(|rate| *rate > 0);
+        self.refresh_rate_checked = Some(Instant::now());
+    }
+
+    /// Run `swap`, a buffer swap, and record its timing
+    pub fn time_swap(&mut self, swap: impl FnOnce()) {
+        let started = Instant::now();
+        swap();
+        self.record(started, Instant::now());
+    }
+
+    /// Record a swap that ran from `started` to `finished`
+    pub fn record(&mut self, started: Instant, finished: Instant) {
+        let stats = &mut self.stats;
+        stats.frames += 1;
+        stats.swap_time = finished.saturating_duration_since(started);
+        stats.last_missed_vsyncs = 0;
+        if let Some(last) = self.last_present.replace(finished) {
+            stats.frame_interval = finished.saturating_duration_since(last);
+            if let Some(rate) = stats.refresh_rate {
+                let refreshes = (stats.frame_interval.as_secs_f64() * rate as f64).round() as u32;
+                stats.last_missed_vsyncs = refreshes.saturating_sub(1);
+                stats.missed_vsyncs += stats.last_missed_vsyncs as u64;
+            }
+        }
+    }
+
+    pub fn stats(&self) -> PresentStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missed_vsyncs_counted_from_frame_intervals() {
+        let mut timer = PresentTimer::new();
+        assert!(timer.needs_refresh_rate());
+        timer.set_refresh_rate(Some(60));
+        assert!(!timer.needs_refresh_rate());
+
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        timer.record(at(0), at(2));
+        // On time, then a frame that took three refreshes
+        timer.record(at(15), at(18));
+        assert_eq!(timer.stats().last_missed_vsyncs, 0);
+        timer.record(at(60), at(68));
+
+        let stats = timer.stats();
+        assert_eq!(stats.frames, 3);
+        assert_eq!((stats.last_missed_vsyncs, stats.missed_vsyncs), (2, 2));
+        assert_eq!(stats.swap_time, Duration::from_millis(8));
+        assert_eq!(stats.frame_interval, Duration::from_millis(50));
+
+        // Without a refresh rate nothing counts as missed
+        timer.set_refresh_rate(Some(0));
+        timer.record(at(200), at(201));
+        assert_eq!(timer.stats().missed_vsyncs, 2);
+        assert_eq!(timer.stats().refresh_rate, None);
+    }
+}
@@ -0,0 +1,403 @@
+//! Shared thread pool for work the engine and applications spread over cores
+//!
+//! Every worker keeps its own queue. Jobs spawned from a worker go on its
+//! queue and run newest first, so nested jobs stay hot in its cache; idle
+//! workers steal the oldest jobs of busy ones. Threads waiting on a job run
+//! other jobs meanwhile, so joining from inside a job cannot deadlock.
+//!
+//! The free functions use one pool for the whole process, started on first
+//! use or by `EngineBuilder::worker_threads`.
+
+use artifice_logging::{debug, info, warn};
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// How long an idle thread sleeps before looking for work again, in case a wakeup was missed
+const IDLE_WAIT: Duration = Duration::from_millis(1);
+
+static GLOBAL: OnceLock<JobSystem> = OnceLock::new();
+
+thread_local! {
+    /// Pool and queue index of the worker running on this thread
+    static WORKER: Cell<Option<(*const Shared, usize)>> = const { Cell::new(None) };
+}
+
+struct Shared {
+    /// Jobs spawned from threads outside the pool
+    injector: Mutex<VecDeque<Job>>,
+    /// Each worker's own queue
+    queues: Vec<Mutex<VecDeque<Job>>>,
+    /// Jobs queued but not yet taken
+    queued: AtomicUsize,
+    /// Frame jobs not yet finished
+    frame_jobs: AtomicUsize,
+    shutdown: AtomicBool,
+    idle: Mutex<()>,
+    /// Signalled when jobs are queued or finish
+    wakeup: Condvar,
+}
+
+impl Shared {
+    fn lock(queue: &Mutex<VecDeque<Job>>) -> MutexGuard<'_, VecDeque<Job>> {
+        // Jobs run outside the lock, so a poisoned queue is still consistent
+        queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Index of the current thread's queue, if it is one of this pool's workers
+    fn worker_index(self: &Arc<Self>) -> Option<usize> {
+        WORKER.with(|worker| worker.get()).filter(|(pool, _)| *pool == Arc::as_ptr(self)).map(|(_, index)| index)
+    }
+
+    fn push(self: &Arc<Self>, job: Job) {
+        match self.worker_index() {
+            Some(index) => Self::lock(&self.queues[index]).push_back(job),
+            None => Self::lock(&self.injector).push_back(job),
+        }
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        self.notify();
+    }
+
+    /// Take the next job: the newest of our own, else the oldest queued from outside, else one stolen
+    fn take(self: &Arc<Self>) -> Option<Job> {
+        if self.queued.load(Ordering::SeqCst) == 0 {
+            return None;
+        }
+        let own = self.worker_index();
+        let job = own
+            .and_then(|index| Self::lock(&self.queues[index]).pop_back())
+            .or_else(|| Self::lock(&self.injector).pop_front())
+            .or_else(|| {
+                let start = own.map_or(0, |index| index + 1);
+                (0..self.queues.len())
+                    .map(|offset| (start + offset) % self.queues.len())
+                    .filter(|index| Some(*index) != own)
+                    .find_map(|index| Self::lock(&self.queues[index]).pop_front())
+            })?;
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        Some(job)
+    }
+
+    fn run(&self, job: Job) {
+        job();
+        self.notify();
+    }
+
+    fn notify(&self) {
+        let _idle = self.idle.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.wakeup.notify_all();
+    }
+
+    /// Run queued jobs until `done`, sleeping while there are none
+    fn help_until(self: &Arc<Self>, done: impl Fn() -> bool) {
+        while !done() {
+            if let Some(job) = self.take() {
+                self.run(job);
+                continue;
+            }
+            let idle = self.idle.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if !done() && self.queued.load(Ordering::SeqCst) == 0 {
+                let _ = self.wakeup.wait_timeout(idle, IDLE_WAIT);
+            }
+        }
+    }
+
+    fn work(self: Arc<Self>, index: usize) {
+        WORKER.with(|worker| worker.set(Some((Arc::as_ptr(&self), index))));
+        loop {
+            if let Some(job) = self.take() {
+                self.run(job);
+                continue;
+            }
+            if self.shutdown.load(Ordering::SeqCst) {
+                break;
+            }
+            let idle = self.idle.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if self.queued.load(Ordering::SeqCst) == 0 && !self.shutdown.load(Ordering::SeqCst) {
+                let _ = self.wakeup.wait_timeout(idle, IDLE_WAIT);
+            }
+        }
+        WORKER.with(|worker| worker.set(None));
+    }
+}
+
+/// A pool of worker threads that share their jobs
+pub struct JobSystem {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl JobSystem {
+    /// Start a pool with `threads` workers, at least one
+    pub fn new(threads: usize) -> Self {
+        let threads = threads.max(1);
+        let shared = Arc::new(Shared {
+            injector: Mutex::new(VecDeque::new()),
+            queues: (0..threads).map(|_| Mutex::new(VecDeque::new())).collect(),
+            queued: AtomicUsize::new(0),
+            frame_jobs: AtomicUsize::new(0),
+            shutdown: AtomicBool::new(false),
+            idle: Mutex::new(()),
+            wakeup: Condvar::new(),
+        });
+        let workers = (0..threads)
+            .map(|index| {
+                let shared = shared.clone();
+                thread::Builder::new()
+                    .name(format!("artifice-job-{}", index))
+                    .spawn(move || shared.work(index))
+                    .expect("Failed to start job worker thread")
+            })
+            .collect();
+        debug!("Started job system with {} workers", threads);
+        Self { shared, workers }
+    }
+
+    /// One worker per core, leaving one core for the engine's thread
+    pub fn default_threads() -> usize {
+        thread::available_parallelism().map_or(1, |cores| cores.get().saturating_sub(1).max(1))
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Run `job` on the pool
+    pub fn spawn<R, F>(&self, job: F) -> JobHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let slot = Arc::new(Mutex::new(None));
+        let result = slot.clone();
+        self.shared.push(Box::new(move || {
+            let outcome = panic::catch_unwind(AssertUnwindSafe(job));
+            *result.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(outcome);
+        }));
+        JobHandle { slot, shared: self.shared.clone() }
+    }
+
+    /// Run `job` on the pool and have the engine wait for it before its next update
+    ///
+    /// Suits work started while rendering a frame whose result the next
+    /// frame's update needs, such as culling or animation blending.
+    pub fn spawn_frame<R, F>(&self, job: F) -> JobHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let shared = self.shared.clone();
+        shared.frame_jobs.fetch_add(1, Ordering::SeqCst);
+        self.spawn(move || {
+            // Counts as finished even if the job panics
+            struct Finish(Arc<Shared>);
+            impl Drop for Finish {
+                fn drop(&mut self) {
+                    self.0.frame_jobs.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+            let _finish = Finish(shared);
+            job()
+        })
+    }
+
+    /// Wait until every job from `spawn_frame` has finished, helping with queued jobs meanwhile
+    pub fn join_frame(&self) {
+        self.shared.help_until(|| self.shared.frame_jobs.load(Ordering::SeqCst) == 0);
+    }
+
+    /// Call `f` with the index of every item of `items` and the item, spread over the pool
+    ///
+    /// Returns once all items are done; the calling thread works on them too.
+    /// A panic in `f` is raised again here after the other items finish.
+    pub fn par_for<T, F>(&self, items: &mut [T], f: F)
+    where
+        T: Send,
+        F: Fn(usize, &mut T) + Sync,
+    {
+        if items.is_empty() {
+            return;
+        }
+        // A few chunks per worker evens out items of uneven cost
+        let chunk_size = items.len().div_ceil(self.threads() * 4);
+        let remaining = AtomicUsize::new(items.len().div_ceil(chunk_size));
+        let panicked: Mutex<Option<Box<dyn Any + Send>>> = Mutex::new(None);
+        let (f, remaining_ref, panicked_ref) = (&f, &remaining, &panicked);
+
+        for (chunk_index, chunk) in items.chunks_mut(chunk_size).enumerate() {
+            let job: Box<dyn FnOnce() + Send + '_> = Box::new(move || {
+                let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+                    for (offset, item) in chunk.iter_mut().enumerate() {
+                        f(chunk_index * chunk_size + offset, item);
+                    }
+                }));
+                if let Err(payload) = outcome {
+                    panicked_ref.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).get_or_insert(payload);
+                }
+                remaining_ref.fetch_sub(1, Ordering::SeqCst);
+            });
+            // SAFETY: the job borrows `items`, `f` and the counters, and this
+            // function does not return until `remaining` shows every job has run
+            let job: Job = unsafe { std::mem::transmute::<Box<dyn FnOnce() + Send + '_>, Job>(job) };
+            self.shared.push(job);
+        }
+
+        self.shared.help_until(|| remaining.load(Ordering::SeqCst) == 0);
+        if let Some(payload) = panicked.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner()) {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+impl Drop for JobSystem {
+    /// Finishes the queued jobs, then stops the workers
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        self.shared.notify();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// The result of a job, once it has run
+pub struct JobHandle<R> {
+    slot: Arc<Mutex<Option<thread::Result<R>>>>,
+    shared: Arc<Shared>,
+}
+
+impl<R> JobHandle<R> {
+    pub fn is_finished(&self) -> bool {
+        self.slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).is_some()
+    }
+
+    /// Wait for the job and take its result, running other jobs meanwhile
+    ///
+    /// A panic in the job is raised again here.
+    pub fn join(self) -> R {
+        self.shared.help_until(|| self.is_finished());
+        let outcome = self.slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+        match outcome.expect("Finished job has a result") {
+            Ok(result) => result,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+/// Start the shared pool with `threads` workers
+///
+/// Fails if it is already running, e.g. because a job was spawned before.
+pub fn init(threads: usize) -> Result<(), String> {
+    let mut started = false;
+    let pool = GLOBAL.get_or_init(|| {
+        started = true;
+        JobSystem::new(threads)
+    });
+    if started {
+        info!("Job system started with {} worker threads", pool.threads());
+        Ok(())
+    } else if pool.threads() == threads.max(1) {
+        Ok(())
+    } else {
+        Err(format!("Job system already runs {} worker threads, cannot switch to {}", pool.threads(), threads))
+    }
+}
+
+/// The shared pool, started with `JobSystem::default_threads` workers if nothing started it yet
+pub fn global() -> &'static JobSystem {
+    GLOBAL.get_or_init(|| JobSystem::new(JobSystem::default_threads()))
+}
+
+/// Run `job` on the shared pool
+pub fn spawn<R, F>(job: F) -> JobHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    global().spawn(job)
+}
+
+/// Run `job` on the shared pool and have the engine wait for it before its next update
+pub fn spawn_frame<R, F>(job: F) -> JobHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    global().spawn_frame(job)
+}
+
+/// Call `f` for every item of `items` on the shared pool, see `JobSystem::par_for`
+pub fn par_for<T, F>(items: &mut [T], f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut T) + Sync,
+{
+    global().par_for(items, f)
+}
+
+/// Wait for the shared pool's frame jobs, if the pool was ever started
+pub(crate) fn join_frame() {
+    if let Some(pool) = GLOBAL.get() {
+        pool.join_frame();
+    }
+}
+
+/// Log a warning instead of failing when the engine cannot size the shared pool
+pub(crate) fn init_or_warn(threads: usize) {
+    if let Err(e) = init(threads) {
+        warn!("{}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_join_and_nested_jobs() {
+        let pool = Arc::new(JobSystem::new(2));
+        let inner = pool.clone();
+        // Jobs spawned and joined inside a job do not deadlock the pool
+        let handle = pool.spawn(move || {
+            let parts: Vec<JobHandle<u64>> = (0..8u64).map(|part| inner.spawn(move || part * 10)).collect();
+            parts.into_iter().map(JobHandle::join).sum::<u64>()
+        });
+        assert_eq!(handle.join(), 280);
+
+        let failing = pool.spawn(|| panic!("job failed"));
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| failing.join())).unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"job failed"));
+    }
+
+    #[test]
+    fn test_par_for_and_frame_jobs() {
+        let pool = JobSystem::new(3);
+        let mut values: Vec<usize> = vec![0; 1000];
+        pool.par_for(&mut values, |index, value| *value = index * 2);
+        assert!(values.iter().enumerate().all(|(index, value)| *value == index * 2));
+
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.par_for(&mut values, |index, _| assert_ne!(index, 500));
+        }));
+        assert!(outcome.is_err());
+
+        let done = Arc::new(AtomicUsize::new(0));
+        for _ in 0..4 {
+            let done = done.clone();
+            pool.spawn_frame(move || {
+                thread::sleep(Duration::from_millis(5));
+                done.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        pool.spawn_frame(|| panic!("frame job failed"));
+        pool.join_frame();
+        assert_eq!(done.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::events::EventFilterManager;
+use crate::Layer;
+
+/// A layer with the filter chain its events pass through
+pub(crate) struct LayerEntry {
+    pub layer: Box<dyn Layer>,
+    pub filters: EventFilterManager,
+    /// Disabled layers stay attached but are skipped for updates, rendering and events
+    pub enabled: bool,
+}
+
+impl LayerEntry {
+    pub fn new(layer: Box<dyn Layer>, filters: EventFilterManager) -> Self {
+        Self { layer, filters, enabled: true }
+    }
+}
+
+/// Layers in the order they update and render, bottom first
+///
+/// Overlays sit above every regular layer, so a debug UI pushed early stays
+/// on top of gameplay layers pushed after it. Events go the other way, from
+/// the top overlay down. Layers are found by `Layer::get_name`; when several
+/// share a name the topmost one is meant.
+#[derive(Default)]
+pub(crate) struct LayerStack {
+    entries: Vec<LayerEntry>,
+    /// Index of the first overlay; regular layers come before it
+    overlay_start: usize,
+}
+
+impl LayerStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put `entry` above the other regular layers, below the overlays
+    pub fn push_layer(&mut self, entry: LayerEntry) {
+        self.entries.insert(self.overlay_start, entry);
+        self.overlay_start += 1;
+    }
+
+    /// Put `entry` above everything else
+    pub fn push_overlay(&mut self, entry: LayerEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Put `entry` right below the layer called `name`, among overlays if that is one
+    ///
+    /// Gives `entry` back if there is no such layer.
+    pub fn insert_before(&mut self, name: &str, entry: LayerEntry) -> Result<(), LayerEntry> {
+        let Some(index) = self.position(name) else { return Err(entry) };
+        if index < self.overlay_start {
+            self.overlay_start += 1;
+        }
+        self.entries.insert(index, entry);
+        Ok(())
+    }
+
+    /// Take out the topmost regular layer
+    pub fn pop_layer(&mut self) -> Option<LayerEntry> {
+        let index = self.overlay_start.checked_sub(1)?;
+        self.overlay_start = index;
+        Some(self.entries.remove(index))
+    }
+
+    /// Take out the topmost overlay
+    pub fn pop_overlay(&mut self) -> Option<LayerEntry> {
+        if self.entries.len() > self.overlay_start {
+            self.entries.pop()
+        } else {
+            None
+        }
+    }
+
+    /// Take out the layer called `name`
+    pub fn remove(&mut self, name: &str) -> Option<LayerEntry> {
+        let index = self.position(name)?;
+        if index < self.overlay_start {
+            self.overlay_start -= 1;
+        }
+        Some(self.entries.remove(index))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LayerEntry> {
+        self.entries.get(self.position(name)?)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut LayerEntry> {
+        let index = self.position(name)?;
+        self.entries.get_mut(index)
+    }
+
+    /// Filter chain of the layer at `index`, counting from the bottom
+    pub fn filters_mut(&mut self, index: usize) -> Option<&mut EventFilterManager> {
+        self.entries.get_mut(index).map(|entry| &mut entry.filters)
+    }
+
+    /// Names of the layers, bottom first
+    pub fn names(&self) -> Vec<&str> {
+        self.entries.iter().map(|entry| entry.layer.get_name()).collect()
+    }
+
+    /// All layers, bottom first, whether enabled or not
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut LayerEntry> {
+        self.entries.iter_mut()
+    }
+
+    /// The enabled layers, bottom first
+    pub fn enabled_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut LayerEntry> {
+        self.entries.iter_mut().filter(|entry| entry.enabled)
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries.iter().rposition(|entry| entry.layer.get_name() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NamedLayer(&'static str);
+
+    impl Layer for NamedLayer {
+        fn get_name(&self) -> &str {
+            self.0
+        }
+    }
+
+    fn entry(name: &'static str) -> LayerEntry {
+        LayerEntry::new(Box::new(NamedLayer(name)), EventFilterManager::new())
+    }
+
+    #[test]
+    fn test_overlays_stay_above_layers() {
+        let mut stack = LayerStack::new();
+        stack.push_overlay(entry("debug ui"));
+        stack.push_layer(entry("world"));
+        stack.push_layer(entry("hud"));
+        assert_eq!(stack.names(), ["world", "hud", "debug ui"]);
+
+        assert!(stack.insert_before("hud", entry("particles")).is_ok());
+        assert!(stack.insert_before("debug ui", entry("console")).is_ok());
+        assert!(stack.insert_before("missing", entry("lost")).is_err());
+        assert_eq!(stack.names(), ["world", "particles", "hud", "console", "debug ui"]);
+
+        // Layers inserted among overlays pop as overlays
+        assert_eq!(stack.pop_layer().map(|entry| entry.layer.get_name().to_string()).as_deref(), Some("hud"));
+        assert_eq!(stack.pop_overlay().map(|entry| entry.layer.get_name().to_string()).as_deref(), Some("debug ui"));
+        assert!(stack.remove("world").is_some());
+        stack.push_layer(entry("world"));
+        assert_eq!(stack.names(), ["particles", "world", "console"]);
+
+        stack.get_mut("particles").unwrap().enabled = false;
+        let enabled: Vec<&str> = stack.enabled_mut().rev().map(|entry| entry.layer.get_name()).collect();
+        assert_eq!(enabled, ["console", "world"]);
+        assert!(stack.remove("console").is_some());
+        assert!(stack.pop_overlay().is_none());
+        assert_eq!(stack.names().len(), 2);
+    }
+}
@@ -1,24 +1,52 @@
+pub mod assets;
 pub mod events;
 pub mod input;
 pub mod window;
 pub mod io;
-
-use std::sync::{Arc, Mutex};
-use std::time::Instant;
-
-use crate::events::{Event, EventDispatcher, EventFilterManager};
-use crate::input::InputManager;
+pub mod jobs;
+pub mod renderer;
+pub mod renderer2d;
+mod clock;
+mod error;
+mod handle;
+mod layer_stack;
+
+use clock::FixedStep;
+pub use clock::TimeControl;
+pub use error::EngineError;
+use handle::EngineCommand;
+pub use handle::EngineHandle;
+
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::panic_message;
+use crate::assets::AssetServer;
+use crate::renderer::{OpenGlDevice, SharedRenderDevice};
+use crate::events::{
+    Event, EventBus, EventData, EventDispatcher, EventFilterManager, EventQueueConfig, EventSender, EventTracer, KeyAction,
+    KeyCode, TraceEntry, WindowResizeEvent,
+};
+use crate::input::{InputInjector, InputManager, InputRecorder, InputRecording};
 use crate::io::{
-    Window, MetricsCollector, MetricsReporter, MetricsConfig, MetricsFactory
+    Window, WindowDesc, WindowHint, WindowId, MetricsCollector, MetricsReporter, MetricsConfig, MetricsFactory
 };
 use crate::window::{
-    HotReloadManager, HotReloadConfig, WindowBackendRegistry
+    BackendReport, GpuResourceRegistry, HotReloadManager, HotReloadConfig, WindowBackendRegistry, WindowState, WindowStatePersistence
 };
 use crate::window::artificeglfw::GlfwWindow;
 #[cfg(feature = "x11")]
 use crate::window::x11::X11Window;
 use crate::io::OpenGLWindow;
-use artifice_logging::{debug, info, warn};
+use crate::layer_stack::{LayerEntry, LayerStack};
+#[cfg(feature = "vulkan")]
+use crate::io::VulkanWindow;
+#[cfg(all(feature = "vulkan", feature = "wayland"))]
+use crate::window::wayland::WaylandWindow;
+use artifice_logging::{debug, info, log_scope, warn};
 
 /// The core Application trait that all applications must implement
 pub trait Application: Send + 'static {
@@ -33,9 +61,17 @@ pub trait Application: Send + 'static {
     /// Called once per frame to update the application state
     fn update(&mut self, _delta_time: f32) {}
 
+    /// Called at the fixed rate set by `EngineBuilder::fixed_timestep`, before `update`
+    ///
+    /// A frame may run it several times or not at all; `delta_time` is always the timestep.
+    fn fixed_update(&mut self, _delta_time: f32) {}
+
     /// Called once per frame after update to render the application
     fn render(&mut self) {}
 
+    /// Called once per frame after `render` for each window opened with `Engine::create_window`, with its context current
+    fn render_window(&mut self, _window: WindowId) {}
+
     /// Called when the application is about to close
     fn shutdown(&mut self) {}
 
@@ -47,6 +83,22 @@ pub trait Application: Send + 'static {
         "Artifice Application"
     }
 
+    /// Name of the application's `.desktop` file without the extension, given to every window the engine opens
+    ///
+    /// Linux desktops match windows to their desktop entry by it, for the
+    /// right icon and task bar grouping. See `WindowHint::AppId`.
+    fn app_id(&self) -> Option<String> {
+        None
+    }
+
+    /// File the main window's position, size and fullscreen state are kept in between runs
+    ///
+    /// The engine restores the window from it on creation and saves it on
+    /// shutdown. `None`, the default, leaves the window where the backend puts it.
+    fn window_state_file(&self) -> Option<PathBuf> {
+        None
+    }
+
     /// Check if a backend switch is pending
     fn get_pending_backend_switch(&self) -> Option<String> {
         None
@@ -57,6 +109,23 @@ pub trait Application: Send + 'static {
 
     /// Called when a backend switch is completed
     fn on_backend_switch_completed(&mut self, _old_backend: &str, _new_backend: &str) {}
+
+    /// Called once when the engine is created, with a handle for posting events
+    fn set_event_sender(&mut self, _sender: EventSender) {}
+
+    /// Called once when the engine is created, with a handle other threads can drive the engine through
+    fn set_engine_handle(&mut self, _handle: EngineHandle) {}
+
+    /// Called once when the engine is created, with the OpenGL objects it recreates after backend switches
+    fn set_gpu_resources(&mut self, _resources: Arc<Mutex<GpuResourceRegistry>>) {}
+
+    /// Called once when the engine is created, with the server that loads its assets
+    fn set_asset_server(&mut self, _assets: AssetServer) {}
+
+    /// Called once when the engine is created, if its window has an OpenGL context, with the device to draw through
+    ///
+    /// The engine recreates the device's resources after backend switches.
+    fn set_render_device(&mut self, _device: SharedRenderDevice) {}
 }
 
 /// A layer that can be added to the application stack
@@ -70,9 +139,15 @@ pub trait Layer: Send + 'static {
     /// Called once per frame to update the layer state
     fn update(&mut self, _delta_time: f32) {}
 
+    /// Called at the fixed rate set by `EngineBuilder::fixed_timestep`, before `update`
+    fn fixed_update(&mut self, _delta_time: f32) {}
+
     /// Called once per frame after update to render the layer
     fn render(&mut self) {}
 
+    /// Called once per frame for each window opened with `Engine::create_window`, with its context current
+    fn render_window(&mut self, _window: WindowId) {}
+
     /// Called for each event that occurs
     fn event(&mut self, _event: &mut Event) {}
 
@@ -80,28 +155,213 @@ pub trait Layer: Send + 'static {
     fn get_name(&self) -> &str {
         "Layer"
     }
+
+    /// Called before `attach` with a handle for posting events
+    fn set_event_sender(&mut self, _sender: EventSender) {}
+
+    /// Called before `attach` with a handle other threads can drive the engine through
+    fn set_engine_handle(&mut self, _handle: EngineHandle) {}
+}
+
+/// A window owned by the engine, with what it takes to reopen it on another backend
+struct EngineWindow {
+    window: Box<dyn Window>,
+    hints: Vec<WindowHint>,
+    shared_context: bool,
 }
 
 /// The main engine class that runs the application
+///
+/// The engine opens the main window on start and stops once it closes. More
+/// windows, e.g. an editor's tool panels, come from `create_window`; their
+/// events carry their `WindowId` and they close on their own.
 pub struct Engine<T: Application> {
     application: Box<T>,
-    window: Box<dyn Window>,
+    /// The main window under `WindowId::MAIN`, then the ones opened by `create_window`
+    windows: BTreeMap<WindowId, EngineWindow>,
+    next_window_id: u32,
+    /// Name of the backend the windows were created with
+    backend: String,
     event_dispatcher: EventDispatcher,
     event_filter_manager: EventFilterManager,
+    event_tracer: EventTracer,
+    event_bus: EventBus,
     input_manager: InputManager,
     metrics_collector: Option<Arc<MetricsCollector>>,
     metrics_reporter: Option<MetricsReporter>,
     hot_reload_manager: HotReloadManager,
-    layers: Vec<Box<dyn Layer>>,
+    event_sender: EventSender,
+    posted_events: mpsc::Receiver<Event>,
+    engine_handle: EngineHandle,
+    /// Work sent through `engine_handle` and its clones
+    commands: mpsc::Receiver<EngineCommand>,
+    assets: AssetServer,
+    /// Device over the main window's context, if it has one
+    render_device: Option<SharedRenderDevice>,
+    layers: LayerStack,
+    application_filters: EventFilterManager,
+    /// Files to save the main window's next frame to
+    pending_screenshots: Vec<PathBuf>,
+    /// Key that saves a screenshot, and the directory it goes to
+    screenshot_key: Option<(KeyCode, PathBuf)>,
+    /// Keeps the main window's placement between runs, if the application asked for it
+    window_persistence: Option<WindowStatePersistence>,
+    /// Vsync setting to give the main window again after backend switches
+    vsync: Option<bool>,
+    /// Pause and time scale of the simulation
+    time_control: TimeControl,
+    /// Time not yet spent on `fixed_update` steps
+    fixed_step: FixedStep,
+    /// Whether to skip rendering and pace frames to the fixed timestep
+    headless: bool,
     running: bool,
     last_frame_time: Instant,
 }
 
+/// Configures and creates an `Engine`
+///
+/// Anything left unset keeps the defaults of `Engine::new`: the GLFW
+/// backend and an 800x600 window titled with the application's name.
+///
+/// ```ignore
+/// let mut engine = Engine::builder(MyApp::new())
+///     .backend("x11")
+///     .window_size(1280, 720)
+///     .title("My Game")
+///     .vsync(true)
+///     .build()?;
+/// engine.run()?;
+/// ```
+pub struct EngineBuilder<T: Application> {
+    application: T,
+    backend: String,
+    window_size: (u32, u32),
+    title: Option<String>,
+    hints: Vec<WindowHint>,
+    vsync: Option<bool>,
+    fixed_timestep: Duration,
+    headless: bool,
+    worker_threads: Option<usize>,
+    asset_root: PathBuf,
+    metrics_config: MetricsConfig,
+    hot_reload_config: HotReloadConfig,
+    queue_config: EventQueueConfig,
+}
+
+impl<T: Application> EngineBuilder<T> {
+    pub fn new(application: T) -> Self {
+        Self {
+            application,
+            backend: "glfw".to_string(),
+            window_size: (800, 600),
+            title: None,
+            hints: Vec::new(),
+            vsync: None,
+            fixed_timestep: Duration::from_secs(1) / 60,
+            headless: false,
+            worker_threads: None,
+            asset_root: PathBuf::from("assets"),
+            metrics_config: MetricsConfig::default(),
+            hot_reload_config: HotReloadConfig::default(),
+            queue_config: EventQueueConfig::default(),
+        }
+    }
+
+    /// Backend to open the main window with; the registry's default is tried if it fails
+    pub fn backend(mut self, backend: impl Into<String>) -> Self {
+        self.backend = backend.into();
+        self
+    }
+
+    pub fn window_size(mut self, width: u32, height: u32) -> Self {
+        self.window_size = (width, height);
+        self
+    }
+
+    /// Title of the main window, instead of `Application::get_name`
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Add a hint for creating the main window
+    pub fn hint(mut self, hint: WindowHint) -> Self {
+        self.hints.push(hint);
+        self
+    }
+
+    /// Turn vsync on or off, rather than leaving it to the backend and driver
+    pub fn vsync(mut self, enabled: bool) -> Self {
+        self.vsync = Some(enabled);
+        self
+    }
+
+    /// How often `fixed_update` runs, 60 times a second unless set
+    pub fn fixed_timestep(mut self, timestep: Duration) -> Self {
+        self.fixed_timestep = timestep;
+        self
+    }
+
+    /// Run without showing a window or rendering, for dedicated servers and tests
+    ///
+    /// The main window comes from the "headless" backend and `render` is
+    /// never called. Frames run once per fixed timestep instead of waiting
+    /// for vsync. With no window for the user to close, the engine stops
+    /// once a `WindowClose` event is posted through its event sender.
+    pub fn headless(mut self) -> Self {
+        self.headless = true;
+        self.backend = "headless".to_string();
+        self
+    }
+
+    /// Number of threads in the shared job pool, see `jobs`
+    ///
+    /// Defaults to one per core but one. The pool is shared by the whole
+    /// process, so this only applies if no job has been spawned yet.
+    pub fn worker_threads(mut self, threads: usize) -> Self {
+        self.worker_threads = Some(threads);
+        self
+    }
+
+    /// Directory asset paths are relative to, "assets" in the working directory unless set
+    pub fn asset_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.asset_root = root.into();
+        self
+    }
+
+    pub fn metrics(mut self, config: MetricsConfig) -> Self {
+        self.metrics_config = config;
+        self
+    }
+
+    pub fn hot_reload(mut self, config: HotReloadConfig) -> Self {
+        self.hot_reload_config = config;
+        self
+    }
+
+    pub fn event_queue(mut self, config: EventQueueConfig) -> Self {
+        self.queue_config = config;
+        self
+    }
+
+    /// Create the engine, failing if no backend can open the main window
+    pub fn build(self) -> Result<Engine<T>, EngineError> {
+        Engine::from_builder(self)
+    }
+}
+
 impl<T: Application> Engine<T> {
     /// Create a new engine instance with the given application
+    ///
+    /// Panics if no backend can open the main window; `try_new` returns the error instead.
     pub fn new(application: T) -> Self {
         Self::with_backend(application, "glfw")
     }
+
+    /// Create a new engine instance, failing if no backend can open the main window
+    pub fn try_new(application: T) -> Result<Self, EngineError> {
+        Self::builder(application).build()
+    }
     
     /// Create a new engine instance with a specific backend
     pub fn with_backend(application: T, backend: &str) -> Self {
@@ -110,21 +370,102 @@ impl<T: Application> Engine<T> {
 
     /// Create a new engine instance with full configuration
     pub fn with_config(application: T, backend: &str, metrics_config: MetricsConfig, hot_reload_config: HotReloadConfig) -> Self {
+        Self::with_queue_config(application, backend, metrics_config, hot_reload_config, EventQueueConfig::default())
+    }
+
+    /// Create a new engine instance with full configuration and a custom event queue
+    pub fn with_queue_config(
+        application: T,
+        backend: &str,
+        metrics_config: MetricsConfig,
+        hot_reload_config: HotReloadConfig,
+        queue_config: EventQueueConfig,
+    ) -> Self {
+        Self::builder(application)
+            .backend(backend)
+            .metrics(metrics_config)
+            .hot_reload(hot_reload_config)
+            .event_queue(queue_config)
+            .build()
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Configure an engine for `application` step by step
+    pub fn builder(application: T) -> EngineBuilder<T> {
+        EngineBuilder::new(application)
+    }
+
+    fn from_builder(builder: EngineBuilder<T>) -> Result<Self, EngineError> {
+        let EngineBuilder {
+            application,
+            backend,
+            window_size: (width, height),
+            title,
+            mut hints,
+            vsync,
+            fixed_timestep,
+            headless,
+            worker_threads,
+            asset_root,
+            metrics_config,
+            hot_reload_config,
+            queue_config,
+        } = builder;
         info!("Creating Engine instance with {} backend", backend);
+        if let Some(threads) = worker_threads {
+            jobs::init_or_warn(threads);
+        }
+
+        // Create hot reload manager, whose registry creates the windows
+        let hot_reload_manager = HotReloadManager::with_config(WindowBackendRegistry::new(), hot_reload_config);
 
-        // Create window backend registry
-        let mut registry = WindowBackendRegistry::new();
-        
         // Create window
-        let mut window = registry.create_window(backend, 800, 600, application.get_name())
-            .unwrap_or_else(|| {
-                warn!("Failed to create window with backend '{}', falling back to default", backend);
-                registry.create_default_window(800, 600, application.get_name())
-                    .expect("Failed to create window with default backend")
-            });
+        let mut backend = backend;
+        let title = title.unwrap_or_else(|| application.get_name().to_string());
+        if let Some(app_id) = application.app_id() {
+            if !hints.iter().any(|hint| matches!(hint, WindowHint::AppId(_))) {
+                hints.push(WindowHint::AppId(app_id));
+            }
+        }
+        let mut window = match hot_reload_manager.create_window_with_backend(&backend, width, height, &title, &hints) {
+            Ok(window) => window,
+            Err(e) => {
+                // A headless engine must not open a real window instead
+                let default = hot_reload_manager
+                    .registry()
+                    .default_backend()
+                    .cloned()
+                    .filter(|default| *default != backend && !headless);
+                warn!("{}", e);
+                let mut attempts = vec![(backend, e)];
+                let Some(default) = default else { return Err(EngineError::WindowCreation { attempts }) };
+                info!("Falling back to the default backend '{}'", default);
+                backend = default;
+                match hot_reload_manager.create_window_with_backend(&backend, width, height, &title, &hints) {
+                    Ok(window) => window,
+                    Err(e) => {
+                        attempts.push((backend, e));
+                        return Err(EngineError::WindowCreation { attempts });
+                    }
+                }
+            }
+        };
+        if let Some(vsync) = vsync {
+            window.set_vsync(vsync);
+        }
+
+        // Put the window back where it was last run, before anyone learns its size
+        let window_persistence = application.window_state_file().filter(|_| !headless).map(|path| {
+            let mut persistence = WindowStatePersistence::new(path);
+            if let Err(e) = persistence.restore(window.as_mut()) {
+                warn!("Cannot restore window state: {}", e);
+            }
+            persistence
+        });
 
         // Create input manager
-        let input_manager = InputManager::new();
+        let mut input_manager = InputManager::with_queue_config(queue_config);
+        input_manager.keyboard_mut().set_key_names(window.key_names());
 
         // Set up metrics if enabled
         let (metrics_collector, metrics_reporter) = if metrics_config.enabled {
@@ -134,80 +475,149 @@ impl<T: Application> Engine<T> {
             (None, None)
         };
 
-        // Create hot reload manager
-        let hot_reload_manager = HotReloadManager::with_config(registry, hot_reload_config);
-
         // Set up the event callback to use our lock-free queue
-        let event_queue = input_manager.get_event_queue();
-        let metrics_handle = metrics_collector.as_ref().map(|c| c.get_handle());
-        
-        let event_callback = Arc::new(Mutex::new(move |event: Event| {
-            // Record metrics if enabled
-            if let Some(ref handle) = metrics_handle {
-                let _timer = crate::io::MetricsTimer::new(handle.clone(), format!("{:?}", event.event_type));
-            }
-            
-            if let Err(rejected_event) = event_queue.try_push(event) {
-                warn!("Event queue full, dropping event: {:?}", rejected_event);
-                if let Some(ref handle) = metrics_handle {
-                    handle.record_event_dropped(&format!("{:?}", rejected_event.event_type));
-                }
-            }
-        }));
+        window.set_event_callback(Self::window_event_callback(&input_manager, metrics_collector.as_ref(), WindowId::MAIN));
+
+        // Layers pushed before the first resize still learn the window size
+        let mut event_dispatcher = EventDispatcher::new();
+        event_dispatcher.sticky_events_mut().record(&Self::window_size_event(window.as_ref(), WindowId::MAIN));
+
+        let (event_sender, posted_events) = EventSender::channel();
+        let (engine_handle, commands) = EngineHandle::channel(event_sender.clone());
+        let assets = AssetServer::new(asset_root, event_sender.clone());
+        let mut application = Box::new(application);
+        application.set_event_sender(event_sender.clone());
+        application.set_engine_handle(engine_handle.clone());
+        application.set_asset_server(assets.clone());
+        application.set_gpu_resources(hot_reload_manager.gpu_resources());
+        let render_device = Self::create_render_device(window.as_mut());
+        if let Some(device) = &render_device {
+            application.set_render_device(device.clone());
+        }
 
-        window.set_event_callback(event_callback);
+        let main_window = EngineWindow { window, hints, shared_context: false };
 
-        Engine {
-            application: Box::new(application),
-            window,
-            event_dispatcher: EventDispatcher::new(),
+        Ok(Engine {
+            application,
+            windows: BTreeMap::from([(WindowId::MAIN, main_window)]),
+            next_window_id: 1,
+            backend,
+            event_dispatcher,
             event_filter_manager: EventFilterManager::new(),
+            event_tracer: EventTracer::default(),
+            event_bus: EventBus::new(),
             input_manager,
             metrics_collector,
             metrics_reporter,
             hot_reload_manager,
-            layers: Vec::new(),
+            event_sender,
+            posted_events,
+            engine_handle,
+            commands,
+            assets,
+            render_device,
+            layers: LayerStack::new(),
+            application_filters: EventFilterManager::new(),
+            pending_screenshots: Vec::new(),
+            screenshot_key: None,
+            window_persistence,
+            vsync,
+            time_control: TimeControl::new(),
+            fixed_step: FixedStep::new(fixed_timestep),
+            headless,
             running: false,
             last_frame_time: Instant::now(),
+        })
+    }
+
+    /// Run the application until it stops or its main window closes
+    ///
+    /// A panic in the application or a layer ends the run with
+    /// `EngineError::Panicked`; `shutdown` is not called then, as the
+    /// application stopped partway through a frame.
+    pub fn run(&mut self) -> Result<(), EngineError> {
+        if as_opengl_window(self.main_window_mut()).is_some() && !gl::Clear::is_loaded() {
+            return Err(EngineError::OpenGlUnavailable { backend: self.backend.clone() });
         }
+        panic::catch_unwind(AssertUnwindSafe(|| self.run_frames())).map_err(|payload| {
+            self.running = false;
+            EngineError::Panicked { message: panic_message(payload.as_ref()) }
+        })
     }
 
-    /// Run the application
-    pub fn run(&mut self) {
+    fn run_frames(&mut self) {
         info!("Engine starting");
         self.running = true;
         self.last_frame_time = Instant::now();
 
-        // Initialize the application
-        self.application.init();
+        {
+            log_scope!("engine_init");
+
+            // Initialize the application
+            self.application.init();
 
-        // Initialize layers
-        for layer in &mut self.layers {
-            layer.attach();
+            // Initialize layers
+            for entry in self.layers.iter_mut() {
+                entry.layer.attach();
+            }
         }
 
         info!("Starting main loop");
 
         // Main loop
-        while self.running && !self.window.should_close() {
+        while self.running && !self.get_window().should_close() {
             // Calculate delta time
             let current_time = Instant::now();
             let delta_time = current_time
                 .duration_since(self.last_frame_time)
                 .as_secs_f32();
             self.last_frame_time = current_time;
+            // Time passed in the simulation, or none while it is paused
+            let simulated_delta = self.time_control.advance(delta_time);
 
             // Process window events first - this will call our callback if events occur
-            self.window.process_events();
+            for entry in self.windows.values_mut() {
+                entry.window.process_events();
+            }
 
-            // Process input events and update input state
-            let mut events = self.input_manager.process_events();
+            // Deliver messages published by background threads
+            self.event_bus.drain();
+            self.run_commands();
 
-            // Apply event filters
-            events = self.event_filter_manager.filter_events(events);
+            // Start a new input frame; the states set below last until the next one
+            self.input_manager.update();
+
+            // Events posted since the last frame go first, then input events
+            let mut events: Vec<Event> = self.posted_events.try_iter().collect();
+            events.extend(self.input_manager.process_events());
+            let missed = self.input_manager.take_missed_events();
+            if let Some(metrics) = self.metrics_collector.as_ref().filter(|_| missed > 0) {
+                metrics.record_events_missed(missed);
+            }
+
+            // Apply the global event filters; layer and application chains apply below
+            let filters = &self.event_filter_manager;
+            let tracer = &mut self.event_tracer;
+            events.retain(|event| {
+                let allowed = filters.should_allow_event(event);
+                if !allowed && tracer.is_enabled() {
+                    let filter = filters.rejecting_filter(event).unwrap_or("unknown");
+                    tracer.record(TraceEntry::new(event).dropped_by(filter));
+                }
+                allowed
+            });
+            if let Some(metrics) = self.metrics_collector.as_ref().filter(|m| m.is_enabled()) {
+                metrics.update_filter_stats(self.event_filter_manager.filter_stats());
+            }
 
             // Forward events to layers and application
             for mut event in events {
+                self.check_screenshot_key(&event);
+                if self.headless && matches!(event.data, EventData::WindowClose(_)) {
+                    self.main_window_mut().set_should_close();
+                }
+                self.track_window_state(&event);
+
                 // Record event processing metrics
                 let _timer = if let Some(ref metrics) = self.metrics_collector {
                     crate::io::MetricsTimer::new(metrics.get_handle(), format!("{:?}", event.event_type))
@@ -215,30 +625,80 @@ impl<T: Application> Engine<T> {
                     crate::io::MetricsTimer::disabled()
                 };
 
-                // Forward to layers (in reverse order)
-                for layer in self.layers.iter_mut().rev() {
-                    if !event.handled {
+                let mut trace = self.event_tracer.is_enabled().then(|| TraceEntry::new(&event));
+
+                // Forward to registered listeners
+                self.event_dispatcher.dispatch_event(&mut event);
+                if let Some(trace) = trace.as_mut() {
+                    trace.delivered("event dispatcher", event.handled);
+                }
+
+                // Forward to enabled layers (top first) whose filter chain allows it
+                for LayerEntry { layer, filters, .. } in self.layers.enabled_mut().rev() {
+                    if event.handled {
+                        break;
+                    }
+                    if filters.should_allow_event(&event) {
                         layer.event(&mut event);
+                        if let Some(trace) = trace.as_mut() {
+                            trace.delivered(layer.get_name(), event.handled);
+                        }
+                    } else if let Some(trace) = trace.as_mut() {
+                        trace.blocked(layer.get_name(), filters.rejecting_filter(&event).unwrap_or("unknown"));
                     }
                 }
 
                 // Forward to application
                 if !event.handled {
-                    self.application.event(&mut event);
+                    if self.application_filters.should_allow_event(&event) {
+                        self.application.event(&mut event);
+                        if let Some(trace) = trace.as_mut() {
+                            trace.delivered(self.application.get_name(), event.handled);
+                        }
+                    } else if let Some(trace) = trace.as_mut() {
+                        let filter = self.application_filters.rejecting_filter(&event).unwrap_or("unknown");
+                        trace.blocked(self.application.get_name(), filter);
+                    }
+                }
+
+                if let Some(trace) = trace {
+                    self.event_tracer.record(trace);
                 }
             }
 
-            // Update input devices
-            self.input_manager.update();
+            // Extra windows close once their close event has been delivered
+            let closed: Vec<WindowId> = self
+                .windows
+                .iter()
+                .filter(|(id, entry)| **id != WindowId::MAIN && entry.window.should_close())
+                .map(|(id, _)| *id)
+                .collect();
+            for id in closed {
+                info!("Window {:?} closed", id);
+                self.windows.remove(&id);
+            }
 
             // Update metrics reporter
             if let Some(ref mut reporter) = self.metrics_reporter {
                 reporter.update();
             }
 
-            // Update layers
-            for layer in &mut self.layers {
-                layer.update(delta_time);
+            // Jobs the last frame started must be done before anything updates
+            jobs::join_frame();
+            self.assets.collect_unused();
+
+            // Fixed-rate updates, then the per-frame update of layers
+            if let Some(delta_time) = simulated_delta {
+                let fixed_delta = self.fixed_step.step().as_secs_f32();
+                for _ in 0..self.fixed_step.advance(delta_time) {
+                    for entry in self.layers.enabled_mut() {
+                        entry.layer.fixed_update(fixed_delta);
+                    }
+                    self.application.fixed_update(fixed_delta);
+                }
+                for entry in self.layers.enabled_mut() {
+                    entry.layer.update(delta_time);
+                }
             }
 
             // Check for pending backend switches
@@ -260,25 +720,31 @@ impl<T: Application> Engine<T> {
             }
 
             // Update application
-            self.application.update(delta_time);
-
-            // Render layers
-            for layer in &mut self.layers {
-                layer.render();
+            if let Some(delta_time) = simulated_delta {
+                self.application.update(delta_time);
             }
 
-            // Render application
-            self.application.render();
-
-            // Update window (swap buffers)
-            self.window.update();
+            if self.headless {
+                // Nothing waits for vsync, so pace frames to the fixed timestep
+                if let Some(wait) = (current_time + self.fixed_step.step()).checked_duration_since(Instant::now()) {
+                    std::thread::sleep(wait);
+                }
+            } else {
+                self.render_frame();
+            }
         }
 
         info!("Engine shutdown initiated");
 
+        if let (Some(persistence), Some(entry)) = (self.window_persistence.as_mut(), self.windows.get_mut(&WindowId::MAIN)) {
+            if let Err(e) = persistence.save(entry.window.as_mut()) {
+                warn!("Cannot save window state: {}", e);
+            }
+        }
+
         // Detach layers in reverse order
-        for layer in self.layers.iter_mut().rev() {
-            layer.detach();
+        for entry in self.layers.iter_mut().rev() {
+            entry.layer.detach();
         }
 
         // Shutdown the application
@@ -287,35 +753,336 @@ impl<T: Application> Engine<T> {
         info!("Engine shutdown complete");
     }
 
+    /// Carry out what `EngineHandle`s asked for since the last frame
+    fn run_commands(&mut self) {
+        let commands: Vec<EngineCommand> = self.commands.try_iter().collect();
+        if commands.is_empty() {
+            return;
+        }
+        if self.windows.len() > 1 {
+            self.make_current(WindowId::MAIN);
+        }
+        for command in commands {
+            match command {
+                EngineCommand::Run(task) => task(self.main_window_mut()),
+                EngineCommand::Stop => {
+                    info!("Engine stop requested through a handle");
+                    self.running = false;
+                }
+            }
+        }
+    }
+
+    /// Render every window and present the frames
+    fn render_frame(&mut self) {
+        // Render layers
+        let multiple_windows = self.windows.len() > 1;
+        if multiple_windows {
+            self.make_current(WindowId::MAIN);
+        }
+        for entry in self.layers.enabled_mut() {
+            entry.layer.render();
+        }
+
+        // Render application
+        self.application.render();
+        self.save_pending_screenshots();
+
+        // Render the other windows, each with its own context current
+        for (&id, entry) in self.windows.iter_mut().filter(|(id, _)| **id != WindowId::MAIN) {
+            if let Some(window) = as_opengl_window(entry.window.as_mut()) {
+                window.make_current();
+            }
+            for entry in self.layers.enabled_mut() {
+                entry.layer.render_window(id);
+            }
+            self.application.render_window(id);
+        }
+
+        // Update windows (swap buffers)
+        for entry in self.windows.values_mut() {
+            entry.window.update();
+        }
+        if let Some(metrics) = self.metrics_collector.as_ref().filter(|m| m.is_enabled()) {
+            if let Some(stats) = self.windows[&WindowId::MAIN].window.present_stats() {
+                metrics.record_present(stats);
+            }
+        }
+    }
+
     /// Stop the application
     pub fn stop(&mut self) {
         info!("Engine stop requested");
         self.running = false;
     }
 
-    /// Add a layer to the application
-    pub fn push_layer(&mut self, mut layer: Box<dyn Layer>) {
-        debug!("Adding layer: {}", layer.get_name());
-        layer.attach();
-        self.layers.push(layer);
+    /// Freeze or resume the simulation
+    ///
+    /// While paused, layers and the application get no `update` calls but
+    /// still receive events and render every frame.
+    pub fn set_paused(&mut self, paused: bool) {
+        info!("Simulation {}", if paused { "paused" } else { "resumed" });
+        self.time_control.set_paused(paused);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.time_control.is_paused()
+    }
+
+    /// Multiply the delta time passed to `update`, e.g. 0.25 for slow motion
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_control.set_time_scale(time_scale);
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_control.time_scale()
+    }
+
+    /// Run `update` for exactly one more frame while paused
+    pub fn step_frame(&mut self) {
+        self.time_control.step_frame();
+    }
+
+    /// Get a handle to pause, scale or step the simulation from layers or other threads
+    pub fn time_control(&self) -> TimeControl {
+        self.time_control.clone()
+    }
+
+    /// Queue an event to be filtered and dispatched next frame
+    pub fn post_event(&self, event: Event) {
+        // The receiver lives as long as the engine, so this cannot fail
+        let _ = self.event_sender.send(event);
+    }
+
+    /// Get a handle for posting events from other threads or from outside the engine
+    pub fn event_sender(&self) -> EventSender {
+        self.event_sender.clone()
+    }
+
+    /// Get a handle for running work on the engine's thread, posting events and stopping the engine from other threads
+    pub fn handle(&self) -> EngineHandle {
+        self.engine_handle.clone()
+    }
+
+    /// Get the server loading the application's assets
+    pub fn assets(&self) -> &AssetServer {
+        &self.assets
+    }
+
+    /// Get the device drawing to the main window, if its backend has an OpenGL context
+    pub fn render_device(&self) -> Option<SharedRenderDevice> {
+        self.render_device.clone()
+    }
+
+    /// Get a handle for injecting synthetic input, e.g. from tests or bots
+    ///
+    /// Unlike events sent through `event_sender`, injected input updates the
+    /// input manager's state and bindings as if it came from the window.
+    pub fn input_injector(&self) -> InputInjector {
+        self.input_manager.injector()
+    }
+
+    /// Add a layer to the application, above the other layers but below the overlays
+    pub fn push_layer(&mut self, layer: Box<dyn Layer>) {
+        self.push_layer_with_filters(layer, EventFilterManager::new());
+    }
+
+    /// Add a layer that only receives the events its own filter chain allows
+    ///
+    /// The chain applies after the global filters, so e.g. a UI layer can take
+    /// mouse events while a gameplay layer beneath it sees only keyboard and gamepad.
+    pub fn push_layer_with_filters(&mut self, layer: Box<dyn Layer>, filters: EventFilterManager) {
+        let entry = self.attach_layer(layer, filters);
+        self.layers.push_layer(entry);
+    }
+
+    /// Add an overlay, which stays above every regular layer
+    ///
+    /// Overlays render last and see events first, which suits debug UIs and consoles.
+    pub fn push_overlay(&mut self, layer: Box<dyn Layer>) {
+        self.push_overlay_with_filters(layer, EventFilterManager::new());
+    }
+
+    /// Add an overlay that only receives the events its own filter chain allows
+    pub fn push_overlay_with_filters(&mut self, layer: Box<dyn Layer>, filters: EventFilterManager) {
+        let entry = self.attach_layer(layer, filters);
+        self.layers.push_overlay(entry);
+    }
+
+    /// Add a layer right below the layer called `name`
+    ///
+    /// The new layer is an overlay if `name` is one. Fails without attaching
+    /// the layer if there is no layer called `name`.
+    pub fn insert_layer_before(&mut self, name: &str, layer: Box<dyn Layer>) -> Result<(), String> {
+        if self.layers.get(name).is_none() {
+            return Err(format!("No layer named '{}' to insert '{}' before", name, layer.get_name()));
+        }
+        let entry = self.attach_layer(layer, EventFilterManager::new());
+        // The layer was just found, so this cannot hand the entry back
+        let _ = self.layers.insert_before(name, entry);
+        Ok(())
     }
 
-    /// Remove a layer from the application
+    /// Remove the topmost regular layer from the application
     pub fn pop_layer(&mut self) {
-        if let Some(mut layer) = self.layers.pop() {
-            debug!("Removing layer: {}", layer.get_name());
-            layer.detach();
+        if let Some(entry) = self.layers.pop_layer() {
+            Self::detach_layer(entry);
+        }
+    }
+
+    /// Remove the topmost overlay from the application
+    pub fn pop_overlay(&mut self) {
+        if let Some(entry) = self.layers.pop_overlay() {
+            Self::detach_layer(entry);
+        }
+    }
+
+    /// Detach and hand back the layer called `name`, which may be an overlay
+    pub fn remove_layer(&mut self, name: &str) -> Option<Box<dyn Layer>> {
+        self.layers.remove(name).map(Self::detach_layer)
+    }
+
+    /// Turn the layer called `name` on or off, returning false if there is no such layer
+    ///
+    /// A disabled layer stays attached but gets no updates, render calls or events.
+    pub fn set_layer_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let Some(entry) = self.layers.get_mut(name) else { return false };
+        if entry.enabled != enabled {
+            debug!("{} layer: {}", if enabled { "Enabling" } else { "Disabling" }, name);
+            entry.enabled = enabled;
+        }
+        true
+    }
+
+    /// Whether the layer called `name` is enabled, or `None` if there is no such layer
+    pub fn is_layer_enabled(&self, name: &str) -> Option<bool> {
+        self.layers.get(name).map(|entry| entry.enabled)
+    }
+
+    /// Names of the layers and overlays, bottom first
+    pub fn layer_names(&self) -> Vec<&str> {
+        self.layers.names()
+    }
+
+    fn attach_layer(&mut self, mut layer: Box<dyn Layer>, filters: EventFilterManager) -> LayerEntry {
+        debug!("Adding layer: {} ({} filters)", layer.get_name(), filters.filter_count());
+        layer.set_event_sender(self.event_sender.clone());
+        layer.set_engine_handle(self.engine_handle.clone());
+        layer.attach();
+
+        // Bring the layer up to date with the current window size and gamepads
+        for mut event in self.event_dispatcher.sticky_events().events() {
+            if filters.should_allow_event(&event) {
+                layer.event(&mut event);
+            }
         }
+        LayerEntry::new(layer, filters)
+    }
+
+    fn detach_layer(entry: LayerEntry) -> Box<dyn Layer> {
+        let mut layer = entry.layer;
+        debug!("Removing layer: {}", layer.get_name());
+        layer.detach();
+        layer
     }
 
-    /// Get the window
+    /// Get the main window
     pub fn get_window(&self) -> &dyn Window {
-        self.window.as_ref()
+        self.windows[&WindowId::MAIN].window.as_ref()
     }
 
-    /// Get the window (mutable)
+    /// Get the main window (mutable)
     pub fn get_window_mut(&mut self) -> &mut dyn Window {
-        self.window.as_mut()
+        self.main_window_mut()
+    }
+
+    /// Get the window with the given ID, if it is still open
+    pub fn get_window_by_id(&self, id: WindowId) -> Option<&dyn Window> {
+        self.windows.get(&id).map(|entry| entry.window.as_ref())
+    }
+
+    /// Get the window with the given ID (mutable), if it is still open
+    pub fn get_window_by_id_mut(&mut self, id: WindowId) -> Option<&mut dyn Window> {
+        Some(self.windows.get_mut(&id)?.window.as_mut())
+    }
+
+    /// IDs of the open windows, the main window first
+    pub fn window_ids(&self) -> Vec<WindowId> {
+        self.windows.keys().copied().collect()
+    }
+
+    /// Open another window on the current backend
+    ///
+    /// Its events reach layers and the application tagged with the returned ID,
+    /// and `render_window` is called for it every frame. With
+    /// `WindowDesc::shared_context` the window draws with objects created in the
+    /// main window's context. The window carries `Application::app_id` unless
+    /// `desc` gives its own. The main window's context is current again when
+    /// this returns.
+    pub fn create_window(&mut self, mut desc: WindowDesc) -> Result<WindowId, String> {
+        let id = WindowId(self.next_window_id);
+        if let Some(app_id) = self.application.app_id() {
+            if !desc.hints.iter().any(|hint| matches!(hint, WindowHint::AppId(_))) {
+                desc.hints.push(WindowHint::AppId(app_id));
+            }
+        }
+        let mut window = self.open_window(&self.backend.clone(), &desc)?;
+        self.next_window_id += 1;
+        info!("Opened window {:?}: {} ({}x{})", id, desc.title, desc.width, desc.height);
+
+        window.set_event_callback(Self::window_event_callback(&self.input_manager, self.metrics_collector.as_ref(), id));
+        self.event_dispatcher.sticky_events_mut().record(&Self::window_size_event(window.as_ref(), id));
+        self.windows.insert(id, EngineWindow { window, hints: desc.hints, shared_context: desc.shared_context });
+        self.make_current(WindowId::MAIN);
+        Ok(id)
+    }
+
+    /// Close a window opened with `create_window`; use `stop` to close the main one
+    pub fn close_window(&mut self, id: WindowId) -> Result<(), String> {
+        if id == WindowId::MAIN {
+            return Err("The main window closes with the engine; call stop instead".to_string());
+        }
+        self.windows.remove(&id).ok_or_else(|| format!("No open window {:?}", id))?;
+        info!("Window {:?} closed", id);
+        self.make_current(WindowId::MAIN);
+        Ok(())
+    }
+
+    /// Save the main window's next frame as a PNG at `path`
+    ///
+    /// The frame is read back after it is rendered and before it is shown,
+    /// so the file holds exactly what appears on screen.
+    pub fn save_screenshot(&mut self, path: impl Into<PathBuf>) {
+        self.pending_screenshots.push(path.into());
+    }
+
+    /// Save a screenshot into `directory` whenever `key` is pressed
+    pub fn set_screenshot_key(&mut self, key: KeyCode, directory: impl Into<PathBuf>) {
+        let directory = directory.into();
+        debug!("Screenshot key set to {:?}, saving into {}", key, directory.display());
+        self.screenshot_key = Some((key, directory));
+    }
+
+    pub fn clear_screenshot_key(&mut self) {
+        self.screenshot_key = None;
+    }
+
+    /// The main window as a Vulkan surface provider, if its backend supports Vulkan
+    #[cfg(feature = "vulkan")]
+    pub fn get_vulkan_window(&mut self) -> Option<&mut dyn VulkanWindow> {
+        let window = self.main_window_mut().as_any_mut();
+        if window.is::<GlfwWindow>() {
+            return window.downcast_mut::<GlfwWindow>().map(|window| window as &mut dyn VulkanWindow);
+        }
+        #[cfg(feature = "x11")]
+        if window.is::<X11Window>() {
+            return window.downcast_mut::<X11Window>().map(|window| window as &mut dyn VulkanWindow);
+        }
+        #[cfg(feature = "wayland")]
+        if window.is::<WaylandWindow>() {
+            return window.downcast_mut::<WaylandWindow>().map(|window| window as &mut dyn VulkanWindow);
+        }
+        None
     }
 
     /// Get the input manager
@@ -328,6 +1095,58 @@ impl<T: Application> Engine<T> {
         &mut self.input_manager
     }
 
+    /// Reload the key names of the current keyboard layout, e.g. after the user switched layouts
+    pub fn refresh_key_names(&mut self) {
+        let key_names = self.get_window().key_names();
+        self.input_manager.keyboard_mut().set_key_names(key_names);
+    }
+
+    /// Name of the window backend in use
+    pub fn backend_name(&self) -> &str {
+        &self.backend
+    }
+
+    /// Start recording the input the engine receives from the window
+    ///
+    /// The window size and backend are saved with the recording so a replay
+    /// in a different setup can be spotted.
+    pub fn start_recording(&mut self, name: impl Into<String>) {
+        let recorder = InputRecorder::new(name).with_window(self.get_window().size().size(), self.backend.clone());
+        self.input_manager.start_recording_with(recorder);
+    }
+
+    /// Finish the input recording in progress, if any
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        self.input_manager.stop_recording()
+    }
+
+    /// Feed `recording` to layers and the application in place of live input
+    ///
+    /// Played events go through the input manager like live ones, so polled
+    /// state, gestures and action bindings behave as they did when recorded.
+    pub fn play_recording(&mut self, recording: InputRecording) {
+        let metadata = &recording.metadata;
+        let size = self.get_window().size().size();
+        if metadata.window_size.is_some_and(|recorded| recorded != size) {
+            warn!(
+                "Recording '{}' was made in a {:?} window but this one is {:?}; mouse positions may be off",
+                metadata.name, metadata.window_size.unwrap_or_default(), size
+            );
+        }
+        if metadata.backend.as_ref().is_some_and(|recorded| *recorded != self.backend) {
+            warn!(
+                "Recording '{}' was made with the {} backend but this is {}",
+                metadata.name, metadata.backend.as_deref().unwrap_or_default(), self.backend
+            );
+        }
+        self.input_manager.play_recording(recording);
+    }
+
+    /// Stop the playback and return to live input
+    pub fn stop_playback(&mut self) {
+        self.input_manager.stop_playback();
+    }
+
     /// Get the keyboard
     pub fn get_keyboard(&self) -> &crate::input::keyboard::Keyboard {
         self.input_manager.keyboard()
@@ -348,6 +1167,26 @@ impl<T: Application> Engine<T> {
         self.input_manager.gamepad_mut()
     }
 
+    /// Get the event dispatcher
+    pub fn get_event_dispatcher(&self) -> &EventDispatcher {
+        &self.event_dispatcher
+    }
+
+    /// Get the event dispatcher (mutable)
+    pub fn get_event_dispatcher_mut(&mut self) -> &mut EventDispatcher {
+        &mut self.event_dispatcher
+    }
+
+    /// Get the filter chain of the layer at `index` (counting from the bottom layer, overlays last, mutable)
+    pub fn get_layer_filters_mut(&mut self, index: usize) -> Option<&mut EventFilterManager> {
+        self.layers.filters_mut(index)
+    }
+
+    /// Get the filter chain applied before events reach the application (mutable)
+    pub fn get_application_filters_mut(&mut self) -> &mut EventFilterManager {
+        &mut self.application_filters
+    }
+
     /// Get the event filter manager
     pub fn get_event_filter_manager(&self) -> &EventFilterManager {
         &self.event_filter_manager
@@ -358,6 +1197,26 @@ impl<T: Application> Engine<T> {
         &mut self.event_filter_manager
     }
 
+    /// Get the event bus
+    pub fn get_event_bus(&self) -> &EventBus {
+        &self.event_bus
+    }
+
+    /// Get the event bus (mutable), to open channels and subscribe
+    pub fn get_event_bus_mut(&mut self) -> &mut EventBus {
+        &mut self.event_bus
+    }
+
+    /// Get the event tracer
+    pub fn get_event_tracer(&self) -> &EventTracer {
+        &self.event_tracer
+    }
+
+    /// Get the event tracer (mutable), e.g. to enable tracing
+    pub fn get_event_tracer_mut(&mut self) -> &mut EventTracer {
+        &mut self.event_tracer
+    }
+
     /// Get the metrics collector
     pub fn get_metrics_collector(&self) -> Option<&Arc<MetricsCollector>> {
         self.metrics_collector.as_ref()
@@ -373,66 +1232,101 @@ impl<T: Application> Engine<T> {
         &mut self.hot_reload_manager
     }
 
+    /// Try `backend_name` out with a hidden test window, without switching to it
+    ///
+    /// The main window's context is made current again afterwards.
+    pub fn validate_backend(&mut self, backend_name: &str) -> BackendReport {
+        let report = self.hot_reload_manager.validate_backend(backend_name);
+        self.make_current(WindowId::MAIN);
+        report
+    }
+
     /// Switch to a different window backend using hot reload
+    ///
+    /// Every open window moves to the new backend; one that cannot is closed.
+    /// If the new main window cannot be created, or its OpenGL context
+    /// cannot be made current, the old main window stays and the error is
+    /// returned.
     pub fn switch_backend(&mut self, backend_name: &str) -> Result<String, String> {
         // Start the hot reload process
-        self.hot_reload_manager.start_reload(backend_name, self.window.as_ref())?;
+        let window = self.windows[&WindowId::MAIN].window.as_ref();
+        let (size, title) = (*window.size(), window.title().to_string());
+        let hints = self.windows[&WindowId::MAIN].hints.clone();
+        let started = self.hot_reload_manager.start_reload(backend_name, window);
+        // The dry run may have left its test window's context current
+        self.make_current(WindowId::MAIN);
+        started?;
+        self.hot_reload_manager.preserve_held_input(self.input_manager.snapshot());
 
         // Create new window with the target backend
-        let mut new_window = self.hot_reload_manager.create_window_with_backend(
-            backend_name,
-            self.window.size().0,
-            self.window.size().1,
-            self.window.title(),
-            &[],
-        )?;
-
-        // Set up event callback for new window
-        let event_queue = self.input_manager.get_event_queue();
-        let metrics_handle = self.metrics_collector.as_ref().map(|c| c.get_handle());
-        
-        let event_callback = Arc::new(Mutex::new(move |event: Event| {
-            if let Some(ref handle) = metrics_handle {
-                let _timer = crate::io::MetricsTimer::new(handle.clone(), format!("{:?}", event.event_type));
+        let mut new_window = match self.hot_reload_manager.create_window_with_backend(backend_name, size.0, size.1, &title, &hints) {
+            Ok(window) => window,
+            Err(e) => {
+                self.hot_reload_manager.rollback_reload(backend_name, e.clone());
+                self.make_current(WindowId::MAIN);
+                return Err(e);
             }
-            
-            if let Err(rejected_event) = event_queue.try_push(event) {
-                warn!("Event queue full, dropping event: {:?}", rejected_event);
-                if let Some(ref handle) = metrics_handle {
-                    handle.record_event_dropped(&format!("{:?}", rejected_event.event_type));
-                }
-            }
-        }));
+        };
 
-        new_window.set_event_callback(event_callback);
+        // Set up event callback for new window
+        new_window.set_event_callback(Self::window_event_callback(&self.input_manager, self.metrics_collector.as_ref(), WindowId::MAIN));
 
         // Complete the hot reload first to handle state preservation
-        let result = self.hot_reload_manager.complete_reload(backend_name, new_window.as_mut());
+        let mut result = self.hot_reload_manager.complete_reload(backend_name, new_window.as_mut());
     
-        // Replace the window
-        self.window = new_window;
+        // Replace the window, keeping the old one until the new context works
+        let old_window = std::mem::replace(&mut self.windows.get_mut(&WindowId::MAIN).unwrap().window, new_window);
+        let previous_backend = std::mem::replace(&mut self.backend, backend_name.to_string());
 
         // Critical: Reload OpenGL functions AFTER window replacement
         // This ensures the new window context is active when we reload functions
         info!("Attempting to reload OpenGL functions for backend switch to '{}'", backend_name);
         
         let opengl_reloaded = self.try_reload_opengl_functions(backend_name);
-        
+        if !opengl_reloaded && matches!(backend_name, "glfw" | "x11") {
+            let error = format!("The OpenGL context of the '{}' backend could not be made current", backend_name);
+            warn!("{}, keeping the '{}' window", error, previous_backend);
+            self.windows.get_mut(&WindowId::MAIN).unwrap().window = old_window;
+            self.backend = previous_backend;
+            self.try_reload_opengl_functions(&self.backend.clone());
+            self.hot_reload_manager.rollback_reload(backend_name, error.clone());
+            return Err(error);
+        }
+        drop(old_window);
+        if let Some(vsync) = self.vsync {
+            self.main_window_mut().set_vsync(vsync);
+        }
+        self.refresh_key_names();
+
+        // The other windows follow, sharing the new main window's context where they shared the old one
+        let others: Vec<WindowId> = self.windows.keys().copied().filter(|id| *id != WindowId::MAIN).collect();
+        for id in others {
+            if let Err(e) = self.reopen_window(id, backend_name) {
+                warn!("Window {:?} cannot move to the '{}' backend, closing it: {}", id, backend_name, e);
+                self.windows.remove(&id);
+            }
+        }
+
         // Report the result and validate OpenGL state
         match backend_name {
             "glfw" | "x11" => {
-                if !opengl_reloaded {
-                    warn!("Failed to reload OpenGL functions for backend '{}' - OpenGL may not work properly", backend_name);
-                    warn!("This usually indicates the backend doesn't support OpenGL or context creation failed");
-                } else {
-                    info!("✓ OpenGL functions successfully reloaded for backend '{}'", backend_name);
-                    self.validate_opengl_context(backend_name);
+                info!("✓ OpenGL functions successfully reloaded for backend '{}'", backend_name);
+                self.validate_opengl_context(backend_name);
+                result.errors.extend(self.hot_reload_manager.restore_gpu_resources());
+                if let Some(device) = &self.render_device {
+                    match device.lock() {
+                        Ok(mut device) => result.errors.extend(device.recreate_resources()),
+                        Err(_) => result.errors.push("Render device is poisoned".to_string()),
+                    }
                 }
             }
             _ => {
                 debug!("Backend '{}' does not require OpenGL function reloading", backend_name);
             }
         }
+        if !opengl_reloaded && self.hot_reload_manager.gpu_resources().lock().is_ok_and(|resources| !resources.is_empty()) {
+            warn!("Backend '{}' has no OpenGL context, GPU resources were not restored", backend_name);
+        }
         
         #[cfg(not(feature = "x11"))]
         {
@@ -471,13 +1365,141 @@ impl<T: Application> Engine<T> {
         }
     }
 
-    /// Try to reload OpenGL functions for the current window
+    fn main_window_mut(&mut self) -> &mut dyn Window {
+        self.windows.get_mut(&WindowId::MAIN).unwrap().window.as_mut()
+    }
+
+    /// Make `id`'s OpenGL context current, if it has one
+    fn make_current(&mut self, id: WindowId) {
+        if let Some(window) = self.windows.get_mut(&id).and_then(|entry| as_opengl_window(entry.window.as_mut())) {
+            window.make_current();
+        }
+    }
+
+    /// Create the window `desc` describes on `backend`, without registering it
+    fn open_window(&mut self, backend: &str, desc: &WindowDesc) -> Result<Box<dyn Window>, String> {
+        if !desc.shared_context {
+            return self.hot_reload_manager.create_window_with_backend(backend, desc.width, desc.height, &desc.title, &desc.hints);
+        }
+        let main = as_opengl_window(self.main_window_mut())
+            .ok_or_else(|| format!("The main window on '{}' has no OpenGL context to share", backend))?;
+        main.create_shared_window(desc.width, desc.height, &desc.title, &desc.hints)
+    }
+
+    /// Replace window `id` with one on `backend`, carrying its state over
+    fn reopen_window(&mut self, id: WindowId, backend: &str) -> Result<(), String> {
+        let entry = &self.windows[&id];
+        let state = WindowState::capture_from_window(entry.window.as_ref());
+        let desc = WindowDesc {
+            title: state.title.clone(),
+            width: state.size.0,
+            height: state.size.1,
+            hints: entry.hints.clone(),
+            shared_context: entry.shared_context,
+        };
+
+        let mut window = self.open_window(backend, &desc)?;
+        window.set_event_callback(Self::window_event_callback(&self.input_manager, self.metrics_collector.as_ref(), id));
+        state.apply_to_window(window.as_mut());
+        self.windows.get_mut(&id).unwrap().window = window;
+        self.make_current(WindowId::MAIN);
+        Ok(())
+    }
+
+    /// Callback feeding a window's events, tagged with `id`, into the input manager's queue
+    fn window_event_callback(
+        input_manager: &InputManager,
+        metrics_collector: Option<&Arc<MetricsCollector>>,
+        id: WindowId,
+    ) -> Arc<Mutex<dyn FnMut(Event) + Send + 'static>> {
+        let event_queue = input_manager.get_event_queue();
+        let sequencer = input_manager.get_sequencer();
+        let metrics_handle = metrics_collector.map(|c| c.get_handle());
+
+        Arc::new(Mutex::new(move |mut event: Event| {
+            event.window = Some(id);
+            sequencer.stamp(&mut event);
+            // Record metrics if enabled
+            if let Some(ref handle) = metrics_handle {
+                let _timer = crate::io::MetricsTimer::new(handle.clone(), format!("{:?}", event.event_type));
+            }
+
+            if let Err(rejected_event) = event_queue.try_push(event) {
+                warn!("Event queue full, dropping event: {:?}", rejected_event);
+                if let Some(ref handle) = metrics_handle {
+                    handle.record_event_dropped(&format!("{:?}", rejected_event.event_type));
+                }
+            }
+        }))
+    }
+
+    fn check_screenshot_key(&mut self, event: &Event) {
+        let Some((key, directory)) = &self.screenshot_key else { return };
+        if event.as_key_event().is_some_and(|pressed| pressed.key == *key && pressed.action == KeyAction::Press) {
+            let name = format!("screenshot-{}.png", chrono::Local::now().format("%Y%m%d-%H%M%S%.3f"));
+            self.pending_screenshots.push(directory.join(name));
+        }
+    }
+
+    /// Note the main window's geometry after it moved or resized, for saving on shutdown
+    fn track_window_state(&mut self, event: &Event) {
+        let moved = matches!(event.data, EventData::WindowMove(_) | EventData::WindowResize(_));
+        if !moved || event.window != Some(WindowId::MAIN) {
+            return;
+        }
+        if let (Some(persistence), Some(entry)) = (self.window_persistence.as_mut(), self.windows.get(&WindowId::MAIN)) {
+            persistence.track(entry.window.as_ref());
+        }
+    }
+
+    /// Capture the main window's frame for each requested screenshot; its context must be current
+    fn save_pending_screenshots(&mut self) {
+        if self.pending_screenshots.is_empty() {
+            return;
+        }
+        let image = match self.main_window_mut().capture_framebuffer() {
+            Ok(image) => image,
+            Err(e) => {
+                warn!("Cannot take screenshot: {}", e);
+                self.pending_screenshots.clear();
+                return;
+            }
+        };
+        for path in self.pending_screenshots.drain(..) {
+            match image.save_png(&path) {
+                Ok(()) => info!("Saved screenshot to {}", path.display()),
+                Err(e) => warn!("{}", e),
+            }
+        }
+    }
+
+    /// Resize event describing `window`'s current size, for the sticky events
+    fn window_size_event(window: &dyn Window, id: WindowId) -> Event {
+        let (width, height) = window.size().size();
+        let mut event = Event::new(EventData::WindowResize(WindowResizeEvent { width, height }));
+        event.window = Some(id);
+        event
+    }
+
+    /// Device over `window`'s OpenGL context, or `None` for backends without one
+    fn create_render_device(window: &mut dyn Window) -> Option<SharedRenderDevice> {
+        as_opengl_window(window)?;
+        match OpenGlDevice::new() {
+            Ok(device) => Some(Arc::new(Mutex::new(device))),
+            Err(e) => {
+                warn!("No render device: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Try to reload OpenGL functions for the main window
     /// Returns true if successful, false otherwise
     fn try_reload_opengl_functions(&mut self, backend_name: &str) -> bool {
         info!("Reloading OpenGL functions for backend: {}", backend_name);
         
         // Try GLFW backend first
-        if let Some(opengl_window) = self.window.as_any_mut().downcast_mut::<GlfwWindow>() {
+        if let Some(opengl_window) = self.main_window_mut().as_any_mut().downcast_mut::<GlfwWindow>() {
             info!("Detected GLFW window - reloading OpenGL functions");
             opengl_window.make_current();
             
@@ -494,7 +1516,7 @@ impl<T: Application> Engine<T> {
         // Try X11 backend
         #[cfg(feature = "x11")]
         {
-            if let Some(opengl_window) = self.window.as_any_mut().downcast_mut::<X11Window>() {
+            if let Some(opengl_window) = self.main_window_mut().as_any_mut().downcast_mut::<X11Window>() {
                 info!("Detected X11 window - reloading OpenGL functions");
                 opengl_window.make_current();
                 
@@ -541,11 +1563,24 @@ impl<T: Application> Engine<T> {
     }
 }
 
+/// `window` as an OpenGL window, if its backend renders with OpenGL
+fn as_opengl_window(window: &mut dyn Window) -> Option<&mut dyn OpenGLWindow> {
+    let window = window.as_any_mut();
+    if window.is::<GlfwWindow>() {
+        return window.downcast_mut::<GlfwWindow>().map(|window| window as &mut dyn OpenGLWindow);
+    }
+    #[cfg(feature = "x11")]
+    if window.is::<X11Window>() {
+        return window.downcast_mut::<X11Window>().map(|window| window as &mut dyn OpenGLWindow);
+    }
+    None
+}
+
 /// Run an application
-pub fn run_application<T: Application>() {
+pub fn run_application<T: Application>() -> Result<(), EngineError> {
     info!("Starting application");
     let app = T::new();
-    let mut engine = Engine::new(app);
+    let mut engine = Engine::try_new(app)?;
 
     // Set up OpenGL debug output if available
     unsafe {
@@ -555,6 +1590,195 @@ pub fn run_application<T: Application>() {
         }
     }
 
-    engine.run();
+    engine.run()?;
     info!("Application terminated");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::WindowCloseEvent;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    static FIXED_UPDATES: AtomicU32 = AtomicU32::new(0);
+
+    struct ServerApp {
+        sender: Option<EventSender>,
+        fixed_updates: u32,
+    }
+
+    impl Application for ServerApp {
+        fn new() -> Self {
+            Self { sender: None, fixed_updates: 0 }
+        }
+
+        fn fixed_update(&mut self, _delta_time: f32) {
+            FIXED_UPDATES.fetch_add(1, Ordering::SeqCst);
+            self.fixed_updates += 1;
+            if self.fixed_updates == 3 {
+                let _ = self.sender.as_ref().unwrap().send(Event::new(EventData::WindowClose(WindowCloseEvent)));
+            }
+        }
+
+        fn render(&mut self) {
+            panic!("A headless engine rendered");
+        }
+
+        fn set_event_sender(&mut self, sender: EventSender) {
+            self.sender = Some(sender);
+        }
+    }
+
+    struct DesktopApp;
+
+    impl Application for DesktopApp {
+        fn new() -> Self {
+            Self
+        }
+
+        fn app_id(&self) -> Option<String> {
+            Some("org.example.Game".to_string())
+        }
+    }
+
+    fn app_ids(engine: &Engine<DesktopApp>, id: WindowId) -> Vec<&str> {
+        engine.windows[&id]
+            .hints
+            .iter()
+            .filter_map(|hint| match hint {
+                WindowHint::AppId(app_id) => Some(app_id.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_windows_carry_the_application_app_id() {
+        let mut engine = Engine::builder(DesktopApp::new()).headless().build().unwrap();
+        assert_eq!(app_ids(&engine, WindowId::MAIN), ["org.example.Game"]);
+
+        let tools = engine.create_window(WindowDesc::new("Tools", 320, 240)).unwrap();
+        assert_eq!(app_ids(&engine, tools), ["org.example.Game"]);
+
+        // A window's own app ID replaces the application's
+        let editor = WindowDesc::new("Editor", 320, 240).with_hints([WindowHint::AppId("org.example.Editor".to_string())]);
+        let editor = engine.create_window(editor).unwrap();
+        assert_eq!(app_ids(&engine, editor), ["org.example.Editor"]);
+
+        let engine = Engine::builder(DesktopApp::new())
+            .headless()
+            .hint(WindowHint::AppId("org.example.Server".to_string()))
+            .build()
+            .unwrap();
+        assert_eq!(app_ids(&engine, WindowId::MAIN), ["org.example.Server"]);
+    }
+
+    #[test]
+    fn test_builder_configures_the_main_window() {
+        let engine = Engine::builder(DesktopApp::new()).headless().window_size(1280, 720).title("Server").build().unwrap();
+        assert_eq!(engine.backend_name(), "headless");
+        assert_eq!(engine.get_window().size().size(), (1280, 720));
+        assert_eq!(engine.get_window().title(), "Server");
+
+        // Without a title the window is named after the application
+        let engine = Engine::builder(DesktopApp::new()).headless().build().unwrap();
+        assert_eq!(engine.get_window().size().size(), (800, 600));
+        assert_eq!(engine.get_window().title(), "Artifice Application");
+    }
+
+    #[test]
+    fn test_headless_builder_never_falls_back_to_a_real_window() {
+        let result = Engine::builder(DesktopApp::new()).headless().backend("missing").build();
+        let Err(EngineError::WindowCreation { attempts }) = result else {
+            panic!("A headless engine opened a window on another backend");
+        };
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].0, "missing");
+    }
+
+    /// Layer writing down the calls it gets, ignoring the events that bring it up to date
+    struct RecordingLayer {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl RecordingLayer {
+        fn boxed(name: &'static str, log: &Arc<Mutex<Vec<String>>>) -> Box<dyn Layer> {
+            Box::new(Self { name, log: log.clone() })
+        }
+    }
+
+    impl Layer for RecordingLayer {
+        fn detach(&mut self) {
+            self.log.lock().unwrap().push(format!("{} detach", self.name));
+        }
+
+        fn update(&mut self, _delta_time: f32) {
+            self.log.lock().unwrap().push(format!("{} update", self.name));
+        }
+
+        fn event(&mut self, event: &mut Event) {
+            if matches!(event.data, EventData::WindowClose(_)) {
+                self.log.lock().unwrap().push(format!("{} close", self.name));
+            }
+        }
+
+        fn get_name(&self) -> &str {
+            self.name
+        }
+    }
+
+    #[test]
+    fn test_overlays_and_disabled_layers_in_the_frame() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = Engine::builder(DesktopApp::new()).headless().build().unwrap();
+        engine.push_overlay(RecordingLayer::boxed("console", &log));
+        engine.push_layer(RecordingLayer::boxed("world", &log));
+        engine.push_layer(RecordingLayer::boxed("hud", &log));
+        assert!(engine.insert_layer_before("hud", RecordingLayer::boxed("particles", &log)).is_ok());
+        assert!(engine.insert_layer_before("missing", RecordingLayer::boxed("lost", &log)).is_err());
+        assert_eq!(engine.layer_names(), ["world", "particles", "hud", "console"]);
+
+        assert!(engine.set_layer_enabled("particles", false));
+        assert!(!engine.set_layer_enabled("missing", false));
+        assert_eq!(engine.is_layer_enabled("particles"), Some(false));
+        assert_eq!(engine.is_layer_enabled("missing"), None);
+        assert!(engine.remove_layer("world").is_some());
+        assert_eq!(log.lock().unwrap().drain(..).collect::<Vec<_>>(), ["world detach"]);
+
+        // Events go top down and updates bottom up, both skipping the disabled layer
+        engine.event_sender.send(Event::new(EventData::WindowClose(WindowCloseEvent))).unwrap();
+        assert_eq!(engine.run(), Ok(()));
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["console close", "hud close", "hud update", "console update", "console detach", "hud detach", "particles detach"]
+        );
+    }
+
+    #[test]
+    fn test_handle_runs_tasks_and_stops_engine() {
+        let mut engine = Engine::builder(ServerApp::new()).headless().title("Server").build().unwrap();
+        let handle = engine.handle();
+        std::thread::spawn(move || {
+            handle.run_on_main_thread(|window| window.set_title(&format!("{} (ready)", window.title())));
+            handle.request_stop();
+        })
+        .join()
+        .unwrap();
+        assert_eq!(engine.run(), Ok(()));
+        assert_eq!(engine.get_window().title(), "Server (ready)");
+    }
+
+    #[test]
+    fn test_headless_engine_runs_fixed_updates_until_closed() {
+        let mut engine = Engine::builder(ServerApp::new())
+            .headless()
+            .fixed_timestep(Duration::from_millis(2))
+            .build()
+            .unwrap();
+        assert!(!engine.get_window().is_visible());
+        assert_eq!(engine.run(), Ok(()));
+        assert!(FIXED_UPDATES.load(Ordering::SeqCst) >= 3);
+    }
 }
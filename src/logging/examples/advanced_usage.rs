@@ -58,6 +58,11 @@ fn example_1_high_performance_concurrent() -> Result<(), LoggerError> {
         enabled: true,
         buffer_capacity: 2000,   // Large pre-allocated buffers
         string_pool_size: 1000,  // Large string pool for reuse
+        deduplicate: false,
+        channel_capacity: None,
+        backpressure: BackpressurePolicy::Block,
+        adaptive: None,
+        sharing: FileSharing::Single,
     };
     
     // Initialize the global logger with file and batching
@@ -66,7 +71,7 @@ fn example_1_high_performance_concurrent() -> Result<(), LoggerError> {
         file: true,
         colors: false,
     };
-    init_with_file_and_batching(&log_file, log_config, batch_config)?;
+    let _guard = init_with_file_and_batching(log_file, log_config, batch_config)?;
     
     // Simulate high-load concurrent scenario
     let thread_count = 8;
@@ -140,6 +145,7 @@ fn example_2_memory_optimized() -> Result<(), LoggerError> {
         enabled: true,
         buffer_capacity: 100,
         string_pool_size: 50,
+        durability: Durability::Flush,
     };
     let _logger = HighPerformanceLogger::new(log_file, hp_config)?;
     
@@ -275,18 +281,18 @@ fn example_4_performance_profiling() -> Result<(), LoggerError> {
     
     // Test different configurations to find optimal settings
     let configurations = vec![
-        ("Small Batch", BatchConfig { batch_size: 10, flush_interval_ms: 100, enabled: true, buffer_capacity: 50, string_pool_size: 25 }),
-        ("Medium Batch", BatchConfig { batch_size: 100, flush_interval_ms: 100, enabled: true, buffer_capacity: 200, string_pool_size: 100 }),
-        ("Large Batch", BatchConfig { batch_size: 500, flush_interval_ms: 100, enabled: true, buffer_capacity: 1000, string_pool_size: 500 }),
-        ("No Batching", BatchConfig { batch_size: 1, flush_interval_ms: 1, enabled: false, buffer_capacity: 10, string_pool_size: 5 }),
+        ("Small Batch", BatchConfig { batch_size: 10, flush_interval_ms: 100, enabled: true, buffer_capacity: 50, string_pool_size: 25, ..BatchConfig::default() }),
+        ("Medium Batch", BatchConfig { batch_size: 100, flush_interval_ms: 100, enabled: true, buffer_capacity: 200, string_pool_size: 100, ..BatchConfig::default() }),
+        ("Large Batch", BatchConfig { batch_size: 500, flush_interval_ms: 100, enabled: true, buffer_capacity: 1000, string_pool_size: 500, ..BatchConfig::default() }),
+        ("No Batching", BatchConfig { batch_size: 1, flush_interval_ms: 1, enabled: false, buffer_capacity: 10, string_pool_size: 5, ..BatchConfig::default() }),
     ];
     
     let message_count = 1000;
     
-    for (name, batch_config) in configurations {
+    for (name, _batch_config) in configurations {
         let log_file = format!("profile_{}.log", name.replace(" ", "_").to_lowercase());
         
-        let config = LogConfig {
+        let _config = LogConfig {
             console: false,
             file: true,
             colors: false,
@@ -298,6 +304,7 @@ fn example_4_performance_profiling() -> Result<(), LoggerError> {
             enabled: true,
             buffer_capacity: 1000,
             string_pool_size: 500,
+            durability: Durability::Flush,
         };
         let logger = HighPerformanceLogger::new("performance_test.log", hp_config)?;
         
@@ -348,7 +355,7 @@ fn example_5_custom_dod_writer() -> Result<(), Box<dyn std::error::Error>> {
     let log_file = "advanced_dod_writer.log";
     
     // Create file and DoD writer directly for maximum control
-    let file = std::fs::OpenOptions::new()
+    let _file = std::fs::OpenOptions::new()
         .create(true)
         .write(true)
         .truncate(true)
@@ -362,6 +369,7 @@ fn example_5_custom_dod_writer() -> Result<(), Box<dyn std::error::Error>> {
         enabled: true,
         buffer_capacity: 2048,
         string_pool_size: 1024,
+        durability: Durability::Flush,
     };
     let mut hp_writer = HighPerformanceFileWriter::new(file, hp_config);
     
@@ -400,62 +408,60 @@ fn example_6_adaptive_batching() -> Result<(), LoggerError> {
     
     let log_file = "advanced_adaptive.log";
     
-    // Simulate adaptive batching by changing configuration based on load
+    // The worker scales batch size and flush interval between these bounds
+    let batch_config = BatchConfig {
+        adaptive: Some(AdaptiveBatching {
+            min_batch_size: 10,
+            max_batch_size: 500,
+            min_flush_interval_ms: 10,
+            max_flush_interval_ms: 200,
+        }),
+        ..BatchConfig::default()
+    };
+    let sink = FileSink::new(log_file, batch_config)?;
+    
     let load_scenarios = vec![
-        ("Low Load", 10, 200),    // Small batches, longer intervals
-        ("Medium Load", 50, 100), // Medium batches, medium intervals  
-        ("High Load", 200, 25),   // Large batches, short intervals
-        ("Peak Load", 500, 10),   // Very large batches, very short intervals
+        ("Low Load", 100),
+        ("Medium Load", 500),
+        ("High Load", 1000),
+        ("Peak Load", 2000),
     ];
     
-    for (scenario_name, batch_size, flush_interval) in load_scenarios {
+    for (scenario_name, message_count) in load_scenarios {
         println!("Testing scenario: {}", scenario_name);
         
-        let hp_config_clone = HighPerformanceConfig {
-            batch_size: batch_size,
-            flush_interval_ms: 50,
-            enabled: true,
-            buffer_capacity: batch_size * 2,
-            string_pool_size: batch_size,
-        };
-        let logger = HighPerformanceLogger::new(&format!("adaptive_{}.log", scenario_name), hp_config_clone)?;
-        
-        let message_count = match scenario_name {
-            "Low Load" => 100,
-            "Medium Load" => 500,
-            "High Load" => 1000,
-            "Peak Load" => 2000,
-            _ => 100,
-        };
-        
         let start = Instant::now();
         
-        for i in 0..message_count {
-            let message = format!("{} message {}: processing_time={}ms", scenario_name, i, i % 10);
-            let _ = logger.log_fast(message);
-            
-            // Simulate different work patterns
-            match scenario_name {
-                "Low Load" => thread::sleep(Duration::from_millis(1)),
-                "Medium Load" => if i % 10 == 0 { thread::sleep(Duration::from_micros(100)); },
-                "High Load" => if i % 50 == 0 { thread::sleep(Duration::from_micros(50)); },
-                "Peak Load" => {}, // No delays for peak load
-                _ => {},
+        for _round in 0..20 {
+            for i in 0..message_count {
+                sink.log(&log::Record::builder()
+                    .args(format_args!("{} message {}: processing_time={}ms", scenario_name, i, i % 10))
+                    .level(log::Level::Info)
+                    .build());
+                
+                // Simulate different work patterns
+                match scenario_name {
+                    "Low Load" => thread::sleep(Duration::from_micros(500)),
+                    "Medium Load" if i % 10 == 0 => thread::sleep(Duration::from_micros(100)),
+                    "High Load" if i % 50 == 0 => thread::sleep(Duration::from_micros(50)),
+                    _ => {}, // No delays for peak load
+                }
             }
         }
         
-        flush();
-        thread::sleep(Duration::from_millis(100));
-        
         let duration = start.elapsed();
-        let throughput = message_count as f64 / duration.as_secs_f64();
+        let throughput = (message_count * 20) as f64 / duration.as_secs_f64();
+        let stats = sink.stats().unwrap_or_default();
         
         println!("  Messages: {}, Duration: {:?}, Throughput: {:.2} msg/s", 
-                message_count, duration, throughput);
-        
-        let _ = std::fs::remove_file(&log_file);
+                message_count * 20, duration, throughput);
+        println!("  Tuned to batch size {}, flush interval {:?}", 
+                stats.batch_size, stats.flush_interval);
     }
     
+    sink.shutdown();
+    let _ = std::fs::remove_file(log_file);
+    
     println!("✓ Adaptive batching example completed\n");
     Ok(())
 }
@@ -474,6 +480,7 @@ fn example_7_log_rotation() -> Result<(), Box<dyn std::error::Error>> {
         enabled: true,
         buffer_capacity: 100,
         string_pool_size: 50,
+        durability: Durability::Flush,
     };
     
     // Simulate writing to current log file using high-performance logger
@@ -538,6 +545,7 @@ fn simulate_realistic_workload() -> Result<(), LoggerError> {
         enabled: true,
         buffer_capacity: 200,
         string_pool_size: 100,
+        durability: Durability::Flush,
     };
     
     let logger = Arc::new(HighPerformanceLogger::new(log_file, hp_config)?);
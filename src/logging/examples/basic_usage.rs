@@ -37,7 +37,7 @@ fn example_1_simple_console() -> Result<(), LoggerError> {
     println!("--- Example 1: Simple Console Logging ---");
     
     // Initialize with default settings (console only, with colors)
-    let _ = init();
+    let _guard = init();
     
     // Use standard log macros
     log::info!("This is an info message");
@@ -58,7 +58,7 @@ fn example_2_file_logging() -> Result<(), LoggerError> {
     
     // Initialize with file logging
     let config = LogConfig::default();
-    let _ = init_with_file(log_file, config);
+    let _guard = init_with_file(log_file, config);
     
     log::info!("This message will be written to {}", log_file);
     log::warn!("File logging is useful for production systems");
@@ -89,7 +89,7 @@ fn example_3_console_and_file() -> Result<(), LoggerError> {
     };
     
     // Initialize with custom config and file
-    let _ = init_with_config(config);
+    let _guard = init_with_config(config);
     
     log::info!("This appears in both console and file: {}", log_file);
     log::warn!("Dual output is great for development");
@@ -115,10 +115,15 @@ fn example_4_custom_config() -> Result<(), LoggerError> {
         enabled: true,         // Enable batching
         buffer_capacity: 200,  // Pre-allocate buffer for 200 messages
         string_pool_size: 100, // Pool size for string reuse
+        deduplicate: false,    // Keep repeated messages as-is
+        channel_capacity: None,
+        backpressure: BackpressurePolicy::Block,
+        adaptive: None,
+        sharing: FileSharing::Single,
     };
     
     let config = LogConfig::default();
-    let _ = init_with_file_and_batching(log_file, config, batch_config);
+    let _guard = init_with_file_and_batching(log_file, config, batch_config);
     
     // Log many messages quickly to demonstrate batching
     for i in 0..25 {
@@ -184,7 +189,7 @@ fn example_6_environment_config() -> Result<(), LoggerError> {
     std::env::set_var("ARTIFICE_LOG_FLUSH_INTERVAL", "75");
     
     // Initialize from environment variables
-    let _ = init_from_env();
+    let _guard = init_from_env();
     
     log::trace!("This trace message might not appear depending on log level");
     log::debug!("Environment configuration loaded successfully");
@@ -235,10 +240,15 @@ fn demonstrate_performance() -> Result<(), LoggerError> {
         enabled: true,
         buffer_capacity: 2000,
         string_pool_size: 1000,
+        deduplicate: false,
+        channel_capacity: None,
+        backpressure: BackpressurePolicy::Block,
+        adaptive: None,
+        sharing: FileSharing::Single,
     };
     
     let config = LogConfig::default();
-    init_with_file_and_batching(log_file, config, batch_config)?;
+    let _guard = init_with_file_and_batching(log_file, config, batch_config)?;
     
     let message_count = 10000;
     let start = std::time::Instant::now();
@@ -70,7 +70,7 @@ fn demo_file_logger() -> Result<(), LoggerError> {
     
     let log_file = "final_demo.log";
     
-    let config = LogConfig {
+    let _config = LogConfig {
         console: true,
         file: true,
         colors: false,
@@ -82,6 +82,11 @@ fn demo_file_logger() -> Result<(), LoggerError> {
         enabled: true,
         buffer_capacity: 10,
         string_pool_size: 5,
+        deduplicate: false,
+        channel_capacity: None,
+        backpressure: BackpressurePolicy::Block,
+        adaptive: None,
+        sharing: FileSharing::Single,
     };
     
     let logger = ArtificeLogger::new()
@@ -89,7 +94,7 @@ fn demo_file_logger() -> Result<(), LoggerError> {
         .with_file(log_file)?;
     
     // Create multiple log records
-    let records = vec![
+    let records = [
         ("File logging initialized", log::Level::Info),
         ("Processing first request", log::Level::Debug),
         ("Memory usage is high", log::Level::Warn),
@@ -98,7 +103,7 @@ fn demo_file_logger() -> Result<(), LoggerError> {
         ("Connection restored", log::Level::Info),
     ];
     
-    for (i, (message, level)) in records.iter().enumerate() {
+    for (message, level) in records.iter() {
         match level {
             log::Level::Error => log::error!(target: "file_demo", "{}", message),
             log::Level::Warn => log::warn!(target: "file_demo", "{}", message),
@@ -134,6 +139,7 @@ fn demo_high_performance_writer() -> Result<(), Box<dyn std::error::Error>> {
         enabled: true,
         buffer_capacity: 20,
         string_pool_size: 10,
+        durability: Durability::Flush,
     };
     
     let logger = HighPerformanceLogger::new("high_performance_demo.log", config)?;
@@ -169,6 +175,7 @@ fn demo_high_performance_writer() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[allow(dead_code)]
 fn demo_configuration_patterns() {
     println!("--- Configuration Patterns ---");
     
@@ -185,6 +192,11 @@ fn demo_configuration_patterns() {
         enabled: true,
         buffer_capacity: 50,
         string_pool_size: 25,
+        deduplicate: false,
+        channel_capacity: None,
+        backpressure: BackpressurePolicy::Block,
+        adaptive: None,
+        sharing: FileSharing::Single,
     };
     
     // Production configuration
@@ -200,6 +212,11 @@ fn demo_configuration_patterns() {
         enabled: true,
         buffer_capacity: 2000,
         string_pool_size: 1000,
+        deduplicate: false,
+        channel_capacity: None,
+        backpressure: BackpressurePolicy::Block,
+        adaptive: None,
+        sharing: FileSharing::Single,
     };
     
     // High-performance configuration
@@ -209,6 +226,11 @@ fn demo_configuration_patterns() {
         enabled: true,
         buffer_capacity: 5000,
         string_pool_size: 2000,
+        deduplicate: false,
+        channel_capacity: None,
+        backpressure: BackpressurePolicy::Block,
+        adaptive: None,
+        sharing: FileSharing::Single,
     };
     
     println!("✓ Configuration patterns demonstrated");
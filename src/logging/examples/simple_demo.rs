@@ -170,6 +170,7 @@ fn configuration_examples() {
             enabled: true,
             buffer_capacity: 5000,
             string_pool_size: 2000,
+            deduplicate: false,
         };
         
         init_with_file_and_batching("high_perf.log", batch_config)
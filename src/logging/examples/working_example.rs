@@ -22,7 +22,7 @@ fn example_basic_usage() -> Result<(), LoggerError> {
     
     let log_file = "simple_example.log";
     
-    let config = LogConfig {
+    let _config = LogConfig {
         console: true,
         file: true,
         colors: false,
@@ -34,6 +34,11 @@ fn example_basic_usage() -> Result<(), LoggerError> {
         enabled: true,
         buffer_capacity: 20,
         string_pool_size: 10,
+        deduplicate: false,
+        channel_capacity: None,
+        backpressure: BackpressurePolicy::Block,
+        adaptive: None,
+        sharing: FileSharing::Single,
     };
     
     // Create logger with file output
@@ -42,7 +47,7 @@ fn example_basic_usage() -> Result<(), LoggerError> {
         .with_file(log_file)?;
     
     // Create some log messages manually
-    let messages = vec![
+    let messages = [
         ("Application started", log::Level::Info),
         ("Configuration loaded", log::Level::Debug),
         ("Processing request 1", log::Level::Info),
@@ -88,6 +93,7 @@ fn high_performance_example() -> Result<(), Box<dyn std::error::Error>> {
         enabled: true,
         buffer_capacity: 10,
         string_pool_size: 5,
+        durability: Durability::Flush,
     };
     
     let logger = HighPerformanceLogger::new("high_performance_example.log", config)?;
@@ -0,0 +1,265 @@
+use log::{LevelFilter, Metadata, Record};
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::batching::LogMessage;
+use crate::config::{BatchConfig, ColorTheme, LogLevel, LoggerError, RotationPolicy};
+use crate::format::{DefaultFormatter, LogFormatter};
+use crate::sinks::{pooled_message, LogSink, SinkLevel};
+use crate::writers::{BatchWriter, DeliveryStats, FileWriter, NetworkProtocol, NetworkStats, NetworkWriter};
+
+/// Commands processed by the tokio writer task
+enum AsyncCommand {
+    Message(LogMessage),
+    Flush(oneshot::Sender<io::Result<()>>),
+    Reopen(oneshot::Sender<io::Result<()>>),
+    Shutdown(oneshot::Sender<()>),
+}
+
+/// Sink whose file or network output is driven by a tokio task
+///
+/// Use this instead of `FileSink`/`NetworkSink` inside async servers: no
+/// dedicated thread is started, and blocking writes run on tokio's blocking
+/// pool. Keep an `AsyncSinkHandle` to await flushes:
+///
+/// ```rust,no_run
+/// # async fn run() -> Result<(), artifice_logging::LoggerError> {
+/// use artifice_logging::{AsyncSink, BatchConfig, LoggerBuilder};
+///
+/// let sink = AsyncSink::file("server.log", BatchConfig::default())?;
+/// let handle = sink.handle();
+/// let _guard = LoggerBuilder::new().console(false).sink(sink).init()?;
+///
+/// log::info!("Matchmaking server started");
+/// handle.flush().await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Constructors must be called from within a tokio runtime. Dropping the
+/// `LoggerGuard` only requests shutdown, so await `AsyncSinkHandle::shutdown`
+/// before the runtime exits to be sure the last messages are written.
+pub struct AsyncSink {
+    formatter: Box<dyn LogFormatter>,
+    level: SinkLevel,
+    stats: Option<Arc<NetworkStats>>,
+    handle: AsyncSinkHandle,
+}
+
+/// Cloneable handle for awaiting an `AsyncSink`'s writer task
+#[derive(Clone)]
+pub struct AsyncSinkHandle {
+    sender: mpsc::UnboundedSender<AsyncCommand>,
+}
+
+impl AsyncSink {
+    /// Create (or truncate) `path` and write to it from a tokio task
+    pub fn file<P: AsRef<Path>>(path: P, batch_config: BatchConfig) -> Result<Self, LoggerError> {
+        Self::rotating_file(path, RotationPolicy::Never, batch_config)
+    }
+
+    /// Write date-stamped files derived from `path` from a tokio task
+    pub fn rotating_file<P: AsRef<Path>>(
+        path: P,
+        policy: RotationPolicy,
+        batch_config: BatchConfig,
+    ) -> Result<Self, LoggerError> {
+        Ok(Self::spawn(FileWriter::rotating(path, policy, batch_config)?, None))
+    }
+
+    /// Ship logs to `address` from a tokio task; the connection is made lazily
+    pub fn network<A: Into<String>>(address: A, protocol: NetworkProtocol, batch_config: BatchConfig) -> Self {
+        let writer = NetworkWriter::new(address, protocol, batch_config);
+        let stats = writer.stats();
+        Self::spawn(writer, Some(stats))
+    }
+
+    fn spawn<W: BatchWriter + Send + 'static>(writer: W, stats: Option<Arc<NetworkStats>>) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(writer_task(writer, receiver));
+
+        Self {
+            formatter: Box::new(DefaultFormatter::new(false)),
+            level: SinkLevel::new(LevelFilter::Trace),
+            stats,
+            handle: AsyncSinkHandle { sender },
+        }
+    }
+
+    /// Use a custom formatter for this sink
+    pub fn with_formatter<F: LogFormatter + 'static>(mut self, formatter: F) -> Self {
+        self.formatter = Box::new(formatter);
+        self
+    }
+
+    /// Only accept records at or above `level`
+    pub fn with_level(self, level: LogLevel) -> Self {
+        self.level.set(level.into());
+        self
+    }
+
+    /// Get a handle for awaiting flushes and shutdown
+    pub fn handle(&self) -> AsyncSinkHandle {
+        self.handle.clone()
+    }
+}
+
+impl AsyncSinkHandle {
+    /// Wait until everything logged so far has been written
+    pub async fn flush(&self) -> Result<(), LoggerError> {
+        let (reply, done) = oneshot::channel();
+        self.sender
+            .send(AsyncCommand::Flush(reply))
+            .map_err(|_| LoggerError::ChannelError)?;
+        done.await.map_err(|_| LoggerError::ChannelError)??;
+        Ok(())
+    }
+
+    /// Reopen the output file by path and wait until it is done
+    pub async fn reopen(&self) -> Result<(), LoggerError> {
+        let (reply, done) = oneshot::channel();
+        self.sender
+            .send(AsyncCommand::Reopen(reply))
+            .map_err(|_| LoggerError::ChannelError)?;
+        done.await.map_err(|_| LoggerError::ChannelError)??;
+        Ok(())
+    }
+
+    /// Write out everything buffered and stop the writer task
+    pub async fn shutdown(&self) {
+        let (reply, done) = oneshot::channel();
+        if self.sender.send(AsyncCommand::Shutdown(reply)).is_ok() {
+            let _ = done.await;
+        }
+    }
+}
+
+impl LogSink for AsyncSink {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level.get()
+    }
+
+    fn set_level(&self, level: LogLevel) {
+        self.level.set(level.into());
+    }
+
+    fn inherit_theme(&mut self, theme: &ColorTheme) {
+        self.formatter.inherit_theme(theme);
+    }
+
+    fn log(&self, record: &Record) {
+        let message = pooled_message(self.formatter.as_ref(), record);
+        let _ = self.handle.sender.send(AsyncCommand::Message(message));
+    }
+
+    /// Request a flush without waiting; use `AsyncSinkHandle::flush` to await it
+    fn flush(&self) {
+        let (reply, _) = oneshot::channel();
+        let _ = self.handle.sender.send(AsyncCommand::Flush(reply));
+    }
+
+    /// Request a reopen without waiting; use `AsyncSinkHandle::reopen` to await it
+    fn reopen(&self) -> Result<(), LoggerError> {
+        let (reply, _) = oneshot::channel();
+        self.handle
+            .sender
+            .send(AsyncCommand::Reopen(reply))
+            .map_err(|_| LoggerError::ChannelError)
+    }
+
+    /// Request shutdown without waiting; use `AsyncSinkHandle::shutdown` to await it
+    fn shutdown(&self) {
+        let (reply, _) = oneshot::channel();
+        let _ = self.handle.sender.send(AsyncCommand::Shutdown(reply));
+    }
+
+    fn delivery_stats(&self) -> Option<DeliveryStats> {
+        self.stats.as_ref().map(|stats| stats.snapshot())
+    }
+}
+
+/// Receive commands in bursts and apply them on the blocking pool
+async fn writer_task<W: BatchWriter + Send + 'static>(
+    mut writer: W,
+    mut receiver: mpsc::UnboundedReceiver<AsyncCommand>,
+) {
+    let interval = Duration::from_millis(writer.batch_config().flush_interval_ms.max(1));
+    let mut commands = Vec::with_capacity(100);
+
+    loop {
+        match tokio::time::timeout(interval, receiver.recv()).await {
+            Ok(Some(command)) => {
+                commands.push(command);
+                while commands.len() < 1024 {
+                    match receiver.try_recv() {
+                        Ok(command) => commands.push(command),
+                        Err(_) => break,
+                    }
+                }
+            }
+            // Every sender is gone, so nothing can be logged any more
+            Ok(None) => {
+                let _ = tokio::task::spawn_blocking(move || writer.shutdown()).await;
+                return;
+            }
+            Err(_) if !writer.should_flush() => continue,
+            Err(_) => {}
+        }
+
+        let batch = std::mem::take(&mut commands);
+        let result = tokio::task::spawn_blocking(move || {
+            let stop = apply_commands(&mut writer, batch);
+            (writer, stop)
+        })
+        .await;
+
+        match result {
+            Ok((returned, false)) => writer = returned,
+            Ok((_, true)) | Err(_) => return,
+        }
+    }
+}
+
+/// Apply a burst of commands, returning `true` once the writer was shut down
+fn apply_commands<W: BatchWriter>(writer: &mut W, commands: Vec<AsyncCommand>) -> bool {
+    if commands.is_empty() {
+        if let Err(e) = writer.flush() {
+            eprintln!("Failed to periodic flush log messages: {}", e);
+        }
+        return false;
+    }
+
+    for command in commands {
+        match command {
+            AsyncCommand::Message(message) => {
+                if let Err(e) = writer.add_message(message) {
+                    eprintln!("Failed to write log message: {}", e);
+                }
+            }
+            AsyncCommand::Flush(reply) => {
+                let _ = reply.send(writer.flush());
+            }
+            AsyncCommand::Reopen(reply) => {
+                let _ = reply.send(writer.reopen());
+            }
+            AsyncCommand::Shutdown(reply) => {
+                if let Err(e) = writer.shutdown() {
+                    eprintln!("Failed to shutdown log writer: {}", e);
+                }
+                let _ = reply.send(());
+                return true;
+            }
+        }
+    }
+
+    if writer.should_flush() {
+        if let Err(e) = writer.flush() {
+            eprintln!("Failed to flush log messages: {}", e);
+        }
+    }
+
+    false
+}
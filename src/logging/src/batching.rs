@@ -1,10 +1,122 @@
-use std::time::Instant;
+use log::Record;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{self, Write};
+use std::hash::Hasher;
+use std::io;
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering};
+use std::collections::VecDeque;
+use std::sync::{mpsc, Mutex, OnceLock, PoisonError};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use crate::config::{BackpressurePolicy, LoggerError};
+use crate::pool;
 
 /// Internal log message structure
 #[derive(Debug, Clone)]
 pub struct LogMessage {
     pub formatted_message: String,
     pub timestamp: Instant,
+    /// Identity of the record ignoring its timestamp, used for deduplication
+    pub fingerprint: u64,
+    /// Level of the record, used to route messages to per-level files
+    pub level: Option<log::Level>,
+}
+
+impl LogMessage {
+    /// Create a message for a formatted record
+    pub fn from_record(formatted_message: String, record: &Record) -> Self {
+        Self {
+            formatted_message,
+            timestamp: Instant::now(),
+            fingerprint: record_fingerprint(record),
+            level: Some(record.level()),
+        }
+    }
+
+    /// Create a message that is not part of any deduplication run
+    pub fn plain(formatted_message: String) -> Self {
+        Self {
+            formatted_message,
+            timestamp: Instant::now(),
+            fingerprint: 0,
+            level: None,
+        }
+    }
+}
+
+/// Hash level, target and message text of a record without allocating
+pub fn record_fingerprint(record: &Record) -> u64 {
+    struct HashWriter(DefaultHasher);
+
+    impl Write for HashWriter {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0.write(s.as_bytes());
+            Ok(())
+        }
+    }
+
+    let mut writer = HashWriter(DefaultHasher::new());
+    let _ = write!(writer, "{}\0{}\0{}", record.level(), record.target(), record.args());
+    writer.0.finish()
+}
+
+/// Collapses runs of identical consecutive messages into a repeat counter
+#[derive(Debug, Default)]
+pub struct Deduplicator {
+    last_fingerprint: Option<u64>,
+    last_level: Option<log::Level>,
+    repeats: u64,
+    last_repeat: Option<Instant>,
+}
+
+impl Deduplicator {
+    /// Create a deduplicator with no run in progress
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check a message against the current run
+    ///
+    /// Returns `None` if the message repeats the previous one and should be
+    /// skipped. Otherwise returns the summary of the run it ends (if any)
+    /// that must be written before the message itself.
+    pub fn observe(&mut self, message: &LogMessage) -> Option<Option<LogMessage>> {
+        if message.fingerprint != 0 && self.last_fingerprint == Some(message.fingerprint) {
+            self.repeats += 1;
+            self.last_repeat = Some(Instant::now());
+            return None;
+        }
+
+        let summary = self.take_summary();
+        self.last_fingerprint = Some(message.fingerprint);
+        self.last_level = message.level;
+        Some(summary)
+    }
+
+    /// Summary for the current run, only once it has been quiet for `quiet`
+    pub fn take_quiet_summary(&mut self, quiet: Duration) -> Option<LogMessage> {
+        match self.last_repeat {
+            Some(last) if last.elapsed() >= quiet => self.take_summary(),
+            _ => None,
+        }
+    }
+
+    /// Summary for the current run; further repeats start a new count
+    pub fn take_summary(&mut self) -> Option<LogMessage> {
+        if self.repeats == 0 {
+            return None;
+        }
+
+        let mut summary = LogMessage::plain(format!("last message repeated {} times", self.repeats));
+        // The summary belongs wherever the repeated message was routed
+        summary.level = self.last_level;
+        self.repeats = 0;
+        self.last_repeat = None;
+        Some(summary)
+    }
 }
 
 /// Commands for controlling the logging worker thread
@@ -14,10 +126,342 @@ pub enum LogCommand {
     Message(LogMessage),
     /// Force flush all pending messages
     Flush,
+    /// Flush and fsync all pending messages, then report the outcome
+    FlushSync(mpsc::Sender<io::Result<()>>),
+    /// Flush, then reopen the output file by path and report the outcome
+    Reopen(mpsc::Sender<io::Result<()>>),
     /// Shutdown the worker thread
     Shutdown,
 }
 
+/// Result of offering a message to a `BoundedQueue`
+#[derive(Debug)]
+pub enum PushOutcome {
+    /// The message was queued
+    Queued,
+    /// The message was queued after discarding the oldest one
+    DroppedOldest,
+    /// The message was discarded
+    Dropped,
+    /// The queue is full and the caller must spill the message elsewhere
+    Spill(LogMessage),
+}
+
+/// Slots a `BoundedQueue` keeps beyond its message capacity for control commands
+const CONTROL_SLOTS: usize = 16;
+
+/// Keeps the producer and consumer cursors on separate cache lines
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+struct Slot {
+    /// Ring position this slot is ready for, offset by one once it holds a command
+    sequence: AtomicUsize,
+    command: UnsafeCell<MaybeUninit<LogCommand>>,
+}
+
+/// Lock-free multi-producer ring of commands (Vyukov's bounded queue)
+struct CommandRing {
+    slots: Box<[Slot]>,
+    mask: usize,
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+}
+
+impl CommandRing {
+    fn new(capacity: usize) -> Self {
+        let size = capacity.max(2).next_power_of_two();
+        Self {
+            slots: (0..size)
+                .map(|position| Slot {
+                    sequence: AtomicUsize::new(position),
+                    command: UnsafeCell::new(MaybeUninit::uninit()),
+                })
+                .collect(),
+            mask: size - 1,
+            head: CachePadded(AtomicUsize::new(0)),
+            tail: CachePadded(AtomicUsize::new(0)),
+        }
+    }
+
+    fn try_push(&self, command: LogCommand) -> Result<(), LogCommand> {
+        let mut tail = self.tail.0.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[tail & self.mask];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+
+            match (sequence as isize).wrapping_sub(tail as isize) {
+                0 => match self.tail.0.compare_exchange_weak(tail, tail.wrapping_add(1), Ordering::Relaxed, Ordering::Relaxed) {
+                    Ok(_) => {
+                        // SAFETY: winning the tail CAS gives this thread sole access to the slot
+                        unsafe { (*slot.command.get()).write(command) };
+                        slot.sequence.store(tail.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => tail = current,
+                },
+                // The slot still holds a command from the previous lap: full
+                lag if lag < 0 => return Err(command),
+                _ => tail = self.tail.0.load(Ordering::Relaxed),
+            }
+        }
+    }
+
+    fn try_pop(&self) -> Option<LogCommand> {
+        let mut head = self.head.0.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[head & self.mask];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+
+            match (sequence as isize).wrapping_sub(head.wrapping_add(1) as isize) {
+                0 => match self.head.0.compare_exchange_weak(head, head.wrapping_add(1), Ordering::Relaxed, Ordering::Relaxed) {
+                    Ok(_) => {
+                        // SAFETY: the sequence shows the slot was written, and winning
+                        // the head CAS gives this thread sole access to it
+                        let command = unsafe { (*slot.command.get()).assume_init_read() };
+                        slot.sequence.store(head.wrapping_add(self.mask + 1), Ordering::Release);
+                        return Some(command);
+                    }
+                    Err(current) => head = current,
+                },
+                // Nothing has been written to this slot yet: empty
+                lag if lag < 0 => return None,
+                _ => head = self.head.0.load(Ordering::Relaxed),
+            }
+        }
+    }
+}
+
+// SAFETY: commands are moved in and out through the sequence protocol above,
+// so each one is only ever accessed by a single thread
+unsafe impl Send for CommandRing {}
+unsafe impl Sync for CommandRing {}
+
+impl Drop for CommandRing {
+    fn drop(&mut self) {
+        while self.try_pop().is_some() {}
+    }
+}
+
+/// Spin, then yield, then sleep while waiting on another thread
+struct Backoff(u32);
+
+impl Backoff {
+    fn snooze(&mut self) {
+        match self.0 {
+            0..=5 => (0..1 << self.0).for_each(|_| std::hint::spin_loop()),
+            6..=9 => thread::yield_now(),
+            _ => thread::sleep(Duration::from_micros(50)),
+        }
+        self.0 = (self.0 + 1).min(10);
+    }
+}
+
+/// Bounded command queue between loggers and a worker thread
+///
+/// Producers never take a lock while there is room: commands go through a
+/// lock-free ring and the worker is only woken when it has gone to sleep on an
+/// empty queue. Only messages count towards the capacity; control commands
+/// such as `Flush` and `Shutdown` are always accepted so they can never be lost.
+pub struct BoundedQueue {
+    ring: CommandRing,
+    /// Control commands `discard_oldest` took off the ring, older than everything still on it
+    displaced: Mutex<VecDeque<LogCommand>>,
+    has_displaced: AtomicBool,
+    /// Messages queued, or reserved by producers about to queue them
+    messages: AtomicUsize,
+    capacity: usize,
+    closed: AtomicBool,
+    consumer: OnceLock<Thread>,
+    consumer_parked: AtomicBool,
+}
+
+impl BoundedQueue {
+    /// Create a queue holding at most `capacity` messages
+    ///
+    /// The ring is allocated up front, so very large capacities cost memory
+    /// even while the queue is empty; capacities whose ring size overflows
+    /// `usize` are rejected.
+    pub fn new(capacity: usize) -> Result<Self, LoggerError> {
+        let capacity = capacity.max(1);
+        let slots = capacity
+            .checked_add(CONTROL_SLOTS)
+            .and_then(usize::checked_next_power_of_two)
+            .ok_or(LoggerError::InvalidBatchSize {
+                batch_size: capacity,
+                reason: "the bounded channel capacity is too large",
+            })?;
+
+        Ok(Self {
+            ring: CommandRing::new(slots),
+            displaced: Mutex::new(VecDeque::new()),
+            has_displaced: AtomicBool::new(false),
+            messages: AtomicUsize::new(0),
+            capacity,
+            closed: AtomicBool::new(false),
+            consumer: OnceLock::new(),
+            consumer_parked: AtomicBool::new(false),
+        })
+    }
+
+    /// Put a command in the ring, waiting for the worker if it is full
+    fn enqueue(&self, mut command: LogCommand) {
+        let mut backoff = Backoff(0);
+        while let Err(rejected) = self.ring.try_push(command) {
+            command = rejected;
+            backoff.snooze();
+        }
+
+        fence(Ordering::SeqCst);
+        if self.consumer_parked.swap(false, Ordering::SeqCst) {
+            if let Some(consumer) = self.consumer.get() {
+                consumer.unpark();
+            }
+        }
+    }
+
+    /// Queue a control command regardless of capacity
+    ///
+    /// Returns `false` once the queue has been closed.
+    pub fn push_command(&self, command: LogCommand) -> bool {
+        if self.closed.load(Ordering::Acquire) {
+            return false;
+        }
+        self.enqueue(command);
+        true
+    }
+
+    /// Offer a message, applying `policy` if the queue is full
+    pub fn push_message(&self, message: LogMessage, policy: &BackpressurePolicy) -> PushOutcome {
+        let mut backoff = Backoff(0);
+        let mut outcome = PushOutcome::Queued;
+
+        loop {
+            if self.closed.load(Ordering::Acquire) {
+                return PushOutcome::Dropped;
+            }
+
+            let queued = self.messages.load(Ordering::Relaxed);
+            if queued < self.capacity {
+                if self.messages.compare_exchange_weak(queued, queued + 1, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+                    break;
+                }
+                continue;
+            }
+
+            match policy {
+                BackpressurePolicy::Block => backoff.snooze(),
+                // The new message takes over the discarded message's place in the count
+                BackpressurePolicy::DropOldest if self.discard_oldest() => {
+                    outcome = PushOutcome::DroppedOldest;
+                    break;
+                }
+                BackpressurePolicy::DropOldest => backoff.snooze(),
+                BackpressurePolicy::DropNewest => return PushOutcome::Dropped,
+                BackpressurePolicy::SpillToFile(_) => return PushOutcome::Spill(message),
+            }
+        }
+
+        self.enqueue(LogCommand::Message(message));
+        outcome
+    }
+
+    /// Remove the oldest queued message, returning `false` if none was found
+    ///
+    /// Control commands taken off the ring on the way are set aside for the
+    /// worker to receive before anything still on the ring, so they keep
+    /// their place ahead of newer messages.
+    fn discard_oldest(&self) -> bool {
+        let mut displaced = self.displaced.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            match self.ring.try_pop() {
+                Some(LogCommand::Message(_)) => return true,
+                Some(command) => {
+                    displaced.push_back(command);
+                    self.has_displaced.store(true, Ordering::Release);
+                }
+                None => return false,
+            }
+        }
+    }
+
+    /// Next command set aside by `discard_oldest`, if any
+    fn take_displaced(&self) -> Option<LogCommand> {
+        if !self.has_displaced.load(Ordering::Acquire) {
+            return None;
+        }
+        let mut displaced = self.displaced.lock().unwrap_or_else(PoisonError::into_inner);
+        let command = displaced.pop_front();
+        if displaced.is_empty() {
+            self.has_displaced.store(false, Ordering::Release);
+        }
+        command
+    }
+
+    /// Next command in queue order, without waiting
+    fn try_recv(&self) -> Option<LogCommand> {
+        if let Some(command) = self.take_displaced() {
+            return Some(command);
+        }
+        let command = self.ring.try_pop()?;
+        if matches!(command, LogCommand::Message(_)) {
+            self.messages.fetch_sub(1, Ordering::Relaxed);
+        }
+        Some(command)
+    }
+
+    /// Wait up to `timeout` for the next command
+    ///
+    /// Must always be called from the same (worker) thread.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<LogCommand, mpsc::RecvTimeoutError> {
+        let consumer = self.consumer.get_or_init(thread::current);
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(command) = self.try_recv() {
+                return Ok(command);
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(mpsc::RecvTimeoutError::Timeout);
+            }
+
+            // Announce the nap, then check once more so a racing push is not missed
+            self.consumer_parked.store(true, Ordering::SeqCst);
+            fence(Ordering::SeqCst);
+            match self.try_recv() {
+                Some(command) => {
+                    self.consumer_parked.store(false, Ordering::SeqCst);
+                    return Ok(command);
+                }
+                None => {
+                    debug_assert_eq!(consumer.id(), thread::current().id());
+                    thread::park_timeout(remaining);
+                    self.consumer_parked.store(false, Ordering::SeqCst);
+                }
+            }
+        }
+    }
+
+    /// Reject further commands and discard everything still queued
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        while self.try_recv().is_some() {}
+        self.messages.store(0, Ordering::Relaxed);
+    }
+
+    /// Number of queued messages
+    pub fn len(&self) -> usize {
+        self.messages.load(Ordering::Relaxed)
+    }
+
+    /// Check if no messages are queued
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 /// High-performance commands for the advanced writer
 #[derive(Debug)]
 pub enum AdvancedLogCommand {
@@ -74,7 +518,7 @@ impl LogBatch {
     
     /// Clear the batch while maintaining capacity
     pub fn clear(&mut self) {
-        self.messages.clear();
+        pool::recycle(self.messages.drain(..));
         self.timestamps.clear();
         self.write_buffer.clear();
         
@@ -82,6 +526,13 @@ impl LogBatch {
         self.messages.reserve(self.capacity.saturating_sub(self.messages.capacity()));
         self.timestamps.reserve(self.capacity.saturating_sub(self.timestamps.capacity()));
     }
+
+    /// Recycle the `count` oldest messages, e.g. once they have been sent
+    pub fn clear_front(&mut self, count: usize) {
+        let count = count.min(self.messages.len());
+        pool::recycle(self.messages.drain(..count));
+        self.timestamps.drain(..count);
+    }
     
     /// Bulk format all messages into a single string buffer
     /// This reduces the number of system calls from N to 1
@@ -131,11 +582,22 @@ impl SimpleBatch {
     }
     
     pub fn clear(&mut self) {
-        self.messages.clear();
+        pool::recycle(self.messages.drain(..).map(|message| message.formatted_message));
         self.messages.reserve(self.capacity.saturating_sub(self.messages.capacity()));
     }
     
     pub fn messages(&self) -> &[LogMessage] {
         &self.messages
     }
+
+    /// Recycle the `count` oldest messages, e.g. once they have been written
+    pub fn clear_front(&mut self, count: usize) {
+        let count = count.min(self.messages.len());
+        pool::recycle(self.messages.drain(..count).map(|message| message.formatted_message));
+    }
+
+    /// Remove the buffered messages without recycling them
+    pub fn take(&mut self) -> Vec<LogMessage> {
+        std::mem::replace(&mut self.messages, Vec::with_capacity(self.capacity))
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use crate::batching::LogBatch;
 
 /// Performance benchmarking utilities for the logging system
@@ -19,16 +19,19 @@ impl LoggingBenchmarks {
             // Simulate batching
             let mut batch = LogBatch::new(batch_size);
             let mut write_operations = 0;
+            let mut meter = ThroughputMeter::new();
             
             for _i in 0..message_count {
-                batch.push(test_message.clone(), Instant::now());
-                
-                if batch.len() >= batch_size {
-                    // Simulate bulk write
-                    let _bulk_content = batch.format_bulk();
-                    write_operations += 1;
-                    batch.clear();
-                }
+                meter.time(|| {
+                    batch.push(test_message.clone(), Instant::now());
+                    
+                    if batch.len() >= batch_size {
+                        // Simulate bulk write
+                        let _bulk_content = batch.format_bulk();
+                        write_operations += 1;
+                        batch.clear();
+                    }
+                });
             }
             
             // Handle remaining messages
@@ -43,6 +46,7 @@ impl LoggingBenchmarks {
                      batch_size, write_operations, duration);
             println!("  Avg per message: {:.2} μs", 
                      duration.as_micros() as f64 / message_count as f64);
+            println!("  Latency: {}", meter.percentiles());
         }
     }
     
@@ -176,10 +180,176 @@ impl LoggingBenchmarks {
     }
 }
 
-/// Utility for measuring operation throughput
+/// Sub-buckets per power of two, bounding the relative error to 1/64
+const SUB_BUCKET_BITS: u32 = 7;
+const SUB_BUCKET_COUNT: usize = 1 << SUB_BUCKET_BITS;
+const SUB_BUCKET_HALF: usize = SUB_BUCKET_COUNT / 2;
+/// Exact buckets for small values plus half a range for every higher power of two
+const BUCKET_COUNT: usize = SUB_BUCKET_COUNT + (64 - SUB_BUCKET_BITS as usize) * SUB_BUCKET_HALF;
+
+/// HDR-style latency histogram with nanosecond resolution
+///
+/// Values are grouped into log-linear buckets, so every recorded latency from
+/// nanoseconds up to centuries is kept within about 1.6% of its true value in
+/// a fixed 30 KiB of counters, and recording never allocates.
+#[derive(Clone)]
+pub struct LatencyHistogram {
+    counts: Box<[u64]>,
+    total: u64,
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            counts: vec![0; BUCKET_COUNT].into_boxed_slice(),
+            total: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    /// Record a single latency sample
+    pub fn record(&mut self, latency: Duration) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        self.counts[Self::bucket_index(nanos)] += 1;
+        self.total += 1;
+        self.sum += u128::from(nanos);
+        self.min = self.min.min(nanos);
+        self.max = self.max.max(nanos);
+    }
+
+    /// Number of recorded samples
+    pub fn count(&self) -> u64 {
+        self.total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    pub fn min(&self) -> Duration {
+        if self.is_empty() {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos(self.min)
+        }
+    }
+
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max)
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.is_empty() {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.sum / u128::from(self.total)) as u64)
+        }
+    }
+
+    /// Latency below which `percentile` percent of the samples fall
+    ///
+    /// `percentile` is clamped to 0..=100; an empty histogram reports zero.
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.is_empty() {
+            return Duration::ZERO;
+        }
+
+        let fraction = percentile.clamp(0.0, 100.0) / 100.0;
+        let rank = ((fraction * self.total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let value = Self::highest_equivalent(index).clamp(self.min, self.max);
+                return Duration::from_nanos(value);
+            }
+        }
+        self.max()
+    }
+
+    /// Common tail-latency percentiles
+    pub fn percentiles(&self) -> LatencyPercentiles {
+        LatencyPercentiles {
+            p50: self.percentile(50.0),
+            p95: self.percentile(95.0),
+            p99: self.percentile(99.0),
+            p999: self.percentile(99.9),
+            max: self.max(),
+        }
+    }
+
+    /// Add all samples recorded by another histogram
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other;
+        }
+        self.total += other.total;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn reset(&mut self) {
+        self.counts.fill(0);
+        self.total = 0;
+        self.sum = 0;
+        self.min = u64::MAX;
+        self.max = 0;
+    }
+
+    fn bucket_index(nanos: u64) -> usize {
+        if nanos < SUB_BUCKET_COUNT as u64 {
+            return nanos as usize;
+        }
+        let shift = 64 - SUB_BUCKET_BITS - nanos.leading_zeros();
+        let sub_bucket = (nanos >> shift) as usize - SUB_BUCKET_HALF;
+        SUB_BUCKET_COUNT + (shift as usize - 1) * SUB_BUCKET_HALF + sub_bucket
+    }
+
+    fn highest_equivalent(index: usize) -> u64 {
+        if index < SUB_BUCKET_COUNT {
+            return index as u64;
+        }
+        let offset = index - SUB_BUCKET_COUNT;
+        let shift = (offset / SUB_BUCKET_HALF + 1) as u32;
+        let sub_bucket = (offset % SUB_BUCKET_HALF + SUB_BUCKET_HALF) as u64;
+        ((sub_bucket + 1) << shift).wrapping_sub(1)
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tail latencies summarised from a `LatencyHistogram`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencyPercentiles {
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub p999: Duration,
+    pub max: Duration,
+}
+
+impl std::fmt::Display for LatencyPercentiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "p50 {:?}, p95 {:?}, p99 {:?}, p99.9 {:?}, max {:?}",
+               self.p50, self.p95, self.p99, self.p999, self.max)
+    }
+}
+
+/// Utility for measuring operation throughput and per-operation latency
 pub struct ThroughputMeter {
     start_time: Instant,
     operation_count: usize,
+    latencies: LatencyHistogram,
 }
 
 impl ThroughputMeter {
@@ -187,6 +357,7 @@ impl ThroughputMeter {
         Self {
             start_time: Instant::now(),
             operation_count: 0,
+            latencies: LatencyHistogram::new(),
         }
     }
     
@@ -197,6 +368,28 @@ impl ThroughputMeter {
     pub fn record_operations(&mut self, count: usize) {
         self.operation_count += count;
     }
+
+    /// Record one operation that took `latency`
+    pub fn record_latency(&mut self, latency: Duration) {
+        self.operation_count += 1;
+        self.latencies.record(latency);
+    }
+
+    /// Run `operation`, recording it along with how long it took
+    pub fn time<R>(&mut self, operation: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = operation();
+        self.record_latency(start.elapsed());
+        result
+    }
+
+    pub fn latencies(&self) -> &LatencyHistogram {
+        &self.latencies
+    }
+
+    pub fn percentiles(&self) -> LatencyPercentiles {
+        self.latencies.percentiles()
+    }
     
     pub fn throughput_per_second(&self) -> f64 {
         let elapsed = self.start_time.elapsed().as_secs_f64();
@@ -213,6 +406,9 @@ impl ThroughputMeter {
         
         println!("{}: {} operations in {:?} ({:.2} ops/sec)", 
                  operation_name, self.operation_count, elapsed, throughput);
+        if !self.latencies.is_empty() {
+            println!("  Latency: {}", self.percentiles());
+        }
     }
 }
 
@@ -220,4 +416,4 @@ impl Default for ThroughputMeter {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
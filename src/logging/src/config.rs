@@ -1,4 +1,5 @@
-use std::io;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
 
 /// Configuration for console and file logging output
 #[derive(Debug, Clone)]
@@ -12,11 +13,122 @@ pub struct LogConfig {
 }
 
 impl Default for LogConfig {
+    /// Console output, colored if `ColorChoice::Auto` allows it
     fn default() -> Self {
         Self {
             console: true,
             file: false,
-            colors: true,
+            colors: ColorChoice::Auto.enabled(),
+        }
+    }
+}
+
+/// When console output should be colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal, unless `NO_COLOR` is set to a non-empty value
+    #[default]
+    Auto,
+    /// Always color, even when piped or `NO_COLOR` is set
+    Always,
+    /// Never color
+    Never,
+}
+
+impl ColorChoice {
+    /// Resolve the choice for the current process
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+                !no_color && io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// ANSI colors used for each level in colored output
+///
+/// Each entry holds SGR parameters, e.g. `"31"` for red or `"1;35"` for bold
+/// magenta:
+///
+/// ```rust
+/// use artifice_logging::{ColorTheme, LogLevel};
+///
+/// let theme = ColorTheme::default()
+///     .with_color(LogLevel::Error, "1;31")
+///     .with_color(LogLevel::Debug, "90");
+/// assert_eq!(theme.color(LogLevel::Debug), "90");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorTheme {
+    /// SGR parameters for `ERROR`
+    pub error: String,
+    /// SGR parameters for `WARN`
+    pub warn: String,
+    /// SGR parameters for `INFO`
+    pub info: String,
+    /// SGR parameters for `DEBUG`
+    pub debug: String,
+    /// SGR parameters for `TRACE`
+    pub trace: String,
+}
+
+impl ColorTheme {
+    /// Replace the color of one level
+    pub fn with_color<S: Into<String>>(mut self, level: LogLevel, sgr: S) -> Self {
+        let sgr = sgr.into();
+        match level {
+            LogLevel::Error => self.error = sgr,
+            LogLevel::Warn => self.warn = sgr,
+            LogLevel::Info => self.info = sgr,
+            LogLevel::Debug => self.debug = sgr,
+            LogLevel::Trace => self.trace = sgr,
+        }
+        self
+    }
+
+    /// SGR parameters used for `level`
+    pub fn color(&self, level: LogLevel) -> &str {
+        match level {
+            LogLevel::Error => &self.error,
+            LogLevel::Warn => &self.warn,
+            LogLevel::Info => &self.info,
+            LogLevel::Debug => &self.debug,
+            LogLevel::Trace => &self.trace,
+        }
+    }
+
+    /// Check that every color is a list of SGR parameters such as `"1;31"`
+    ///
+    /// Anything else could smuggle other escape sequences into the terminal.
+    pub fn validate(&self) -> Result<(), LoggerError> {
+        let levels = [LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Trace];
+        match levels.into_iter().find(|level| self.valid_color(*level).is_none()) {
+            Some(level) => Err(LoggerError::InvalidColor { level, sgr: self.color(level).to_string() }),
+            None => Ok(()),
+        }
+    }
+
+    /// The color of `level` if it is made of SGR parameters only
+    pub(crate) fn valid_color(&self, level: LogLevel) -> Option<&str> {
+        let sgr = self.color(level);
+        let valid = !sgr.is_empty() && sgr.bytes().all(|byte| byte.is_ascii_digit() || byte == b';');
+        valid.then_some(sgr)
+    }
+}
+
+impl Default for ColorTheme {
+    /// The colors used by `LogLevel::as_colored_str`
+    fn default() -> Self {
+        Self {
+            error: "31".to_string(),
+            warn: "33".to_string(),
+            info: "32".to_string(),
+            debug: "36".to_string(),
+            trace: "37".to_string(),
         }
     }
 }
@@ -32,8 +144,21 @@ pub struct BatchConfig {
     pub enabled: bool,
     /// Initial buffer capacity
     pub buffer_capacity: usize,
-    /// String pool size for reusing allocations
+    /// Message buffers kept for reuse between logging threads and the writer
     pub string_pool_size: usize,
+    /// Collapse consecutive identical messages into a repeat counter
+    pub deduplicate: bool,
+    /// Maximum queued messages before `backpressure` applies (`None` is unbounded)
+    ///
+    /// A capacity switches the worker channel to a lock-free ring, which
+    /// carries messages from many threads faster than the unbounded channel.
+    pub channel_capacity: Option<usize>,
+    /// What to do with new messages while a bounded channel is full
+    pub backpressure: BackpressurePolicy,
+    /// Let the worker tune `batch_size` and `flush_interval_ms` within these bounds
+    pub adaptive: Option<AdaptiveBatching>,
+    /// How file outputs coordinate with other processes writing the same file
+    pub sharing: FileSharing,
 }
 
 impl Default for BatchConfig {
@@ -44,10 +169,96 @@ impl Default for BatchConfig {
             enabled: true,
             buffer_capacity: 256,
             string_pool_size: 128,
+            deduplicate: false,
+            channel_capacity: None,
+            backpressure: BackpressurePolicy::Block,
+            adaptive: None,
+            sharing: FileSharing::Single,
+        }
+    }
+}
+
+/// Bounds within which a batch worker tunes itself to the observed load
+///
+/// The worker samples its incoming message rate and queue depth several times
+/// a second. Quiet periods shrink batches and shorten the flush interval so
+/// messages reach the output quickly; bursts and a growing backlog scale both
+/// up so each write carries more messages. The configured `batch_size` and
+/// `flush_interval_ms`, clamped to these bounds, are the starting point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdaptiveBatching {
+    /// Smallest batch used under light load
+    pub min_batch_size: usize,
+    /// Largest batch used under heavy load
+    pub max_batch_size: usize,
+    /// Flush interval used under light load (milliseconds)
+    pub min_flush_interval_ms: u64,
+    /// Flush interval used under heavy load (milliseconds)
+    pub max_flush_interval_ms: u64,
+}
+
+impl Default for AdaptiveBatching {
+    fn default() -> Self {
+        Self {
+            min_batch_size: 10,
+            max_batch_size: 1000,
+            min_flush_interval_ms: 20,
+            max_flush_interval_ms: 500,
+        }
+    }
+}
+
+impl AdaptiveBatching {
+    /// Check that batches hold messages and no minimum is above its maximum
+    pub fn validate(&self) -> Result<(), LoggerError> {
+        if self.min_batch_size == 0 {
+            return Err(LoggerError::InvalidBatchSize {
+                batch_size: 0,
+                reason: "adaptive batching allows batches of no messages",
+            });
         }
+        if self.min_batch_size > self.max_batch_size || self.min_flush_interval_ms > self.max_flush_interval_ms {
+            return Err(LoggerError::InvalidBatchSize {
+                batch_size: self.min_batch_size,
+                reason: "adaptive batching bounds have a minimum above the maximum",
+            });
+        }
+        Ok(())
     }
 }
 
+/// How a file output coordinates with other processes writing the same file
+///
+/// Both shared modes open the file for appending instead of truncating it, so
+/// e.g. a dedicated server and its tools can log to one file without their
+/// lines being cut into each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileSharing {
+    /// This process is the only writer; the file is truncated when opened
+    #[default]
+    Single,
+    /// Hold an advisory lock (`flock`) on the file while writing each batch
+    Locked,
+    /// Pack whole lines into single appending writes of at most `PIPE_BUF` bytes
+    ///
+    /// Cheaper than locking; a line longer than `PIPE_BUF` gets a write of its own.
+    AtomicAppend,
+}
+
+/// Behavior of a bounded log channel when the writer cannot keep up
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum BackpressurePolicy {
+    /// Block the logging thread until there is room
+    #[default]
+    Block,
+    /// Discard the oldest queued message to make room
+    DropOldest,
+    /// Discard the message being logged
+    DropNewest,
+    /// Write the message straight to an emergency file instead
+    SpillToFile(PathBuf),
+}
+
 /// High-performance batch configuration optimized for maximum throughput
 /// 
 /// This configuration is designed for applications that require the highest
@@ -64,6 +275,8 @@ pub struct HighPerformanceConfig {
     pub buffer_capacity: usize,
     /// Larger string pool for reusing allocations
     pub string_pool_size: usize,
+    /// How hard the writer works to get batches onto disk
+    pub durability: Durability,
 }
 
 impl Default for HighPerformanceConfig {
@@ -74,10 +287,135 @@ impl Default for HighPerformanceConfig {
             enabled: true,
             buffer_capacity: 1024,
             string_pool_size: 512,
+            durability: Durability::Flush,
         }
     }
 }
 
+/// Durability guarantee of the high-performance writer
+///
+/// `Flush` hands batches to the OS, which survives a process crash but not a
+/// power loss or kernel panic. The fsync modes also wait for the disk, trading
+/// throughput for not losing the most recent logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Durability {
+    /// Write batches to the OS without waiting for the disk
+    #[default]
+    Flush,
+    /// fsync after every batch
+    FsyncEveryBatch,
+    /// fsync at most once per interval (in milliseconds) while data is pending
+    FsyncIntervalMs(u64),
+}
+
+/// Time-based rotation schedule for file output
+///
+/// With rotation enabled, `app.log` is written as `app-2024-06-01.log` (daily)
+/// or `app-2024-06-01-13.log` (hourly), and the writer rolls over to a new
+/// file when the period changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationPolicy {
+    /// Write to a single file for the lifetime of the logger
+    #[default]
+    Never,
+    /// Start a new file every hour
+    Hourly,
+    /// Start a new file at midnight (UTC)
+    Daily,
+}
+
+impl RotationPolicy {
+    /// Get the date stamp identifying the rotation period containing `time`
+    pub fn period_stamp(&self, time: chrono::DateTime<chrono::Utc>) -> Option<String> {
+        match self {
+            RotationPolicy::Never => None,
+            RotationPolicy::Hourly => Some(time.format("%Y-%m-%d-%H").to_string()),
+            RotationPolicy::Daily => Some(time.format("%Y-%m-%d").to_string()),
+        }
+    }
+
+    /// Check whether `stamp` is exactly a period stamp this policy produces
+    pub(crate) fn is_period_stamp(&self, stamp: &str) -> bool {
+        match self {
+            RotationPolicy::Never => false,
+            // An hour alone does not parse as a time, so add the minutes
+            RotationPolicy::Hourly => chrono::NaiveDateTime::parse_from_str(&format!("{}:00", stamp), "%Y-%m-%d-%H:%M")
+                .is_ok_and(|time| time.format("%Y-%m-%d-%H").to_string() == stamp),
+            RotationPolicy::Daily => chrono::NaiveDate::parse_from_str(stamp, "%Y-%m-%d")
+                .is_ok_and(|date| date.format("%Y-%m-%d").to_string() == stamp),
+        }
+    }
+}
+
+/// Size budget for the files written by a file output
+///
+/// The budget covers the base file and the files the output's rotation policy
+/// derives from it (`app.log`, `app-2024-06-01.log`, ...); other files in the
+/// directory, such as `app-backup.log`, are never touched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskQuota {
+    /// Total size allowed across all of the output's files
+    pub max_bytes: u64,
+    /// What to do once the budget is exceeded
+    pub action: QuotaAction,
+}
+
+impl DiskQuota {
+    /// Allow `megabytes` MiB in total, deleting the oldest rotated files beyond that
+    pub fn megabytes(megabytes: u64) -> Self {
+        Self {
+            max_bytes: megabytes * 1024 * 1024,
+            action: QuotaAction::DeleteOldest,
+        }
+    }
+
+    /// Use a different action once the budget is exceeded
+    pub fn with_action(mut self, action: QuotaAction) -> Self {
+        self.action = action;
+        self
+    }
+}
+
+/// Reaction of a file output to exceeding its `DiskQuota`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuotaAction {
+    /// Delete the oldest rotated files; stop writing only if the active file
+    /// alone exceeds the budget
+    #[default]
+    DeleteOldest,
+    /// Stop writing until a new rotation period starts with room to spare
+    StopWriting,
+}
+
+/// Per-callsite rate limiting for high-frequency messages
+///
+/// Once a callsite has emitted `max_messages` records within a window, further
+/// records are dropped until the window ends; the next record from that
+/// callsite is preceded by a "suppressed N messages" summary.
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Records allowed per callsite in each window
+    pub max_messages: u32,
+    /// Window length in milliseconds
+    pub window_ms: u64,
+}
+
+impl RateLimitConfig {
+    /// Allow `max_messages` per callsite per second
+    pub fn per_second(max_messages: u32) -> Self {
+        Self {
+            max_messages,
+            window_ms: 1000,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self::per_second(10)
+    }
+}
+
 /// Log level enumeration with color support
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel {
@@ -105,7 +443,7 @@ impl LogLevel {
         }
     }
 
-    /// Get colored string representation for console output
+    /// Get colored string representation using the default `ColorTheme`
     pub fn as_colored_str(&self) -> &'static str {
         match self {
             LogLevel::Error => "\x1b[31mERROR\x1b[0m",
@@ -177,6 +515,31 @@ pub enum LoggerError {
     AlreadyInitialized,
     /// Channel communication error
     ChannelError,
+    /// Invalid logging configuration file
+    Config(String),
+    /// Batch settings that cannot work together
+    InvalidBatchSize {
+        /// The offending batch size
+        batch_size: usize,
+        /// Why it was rejected
+        reason: &'static str,
+    },
+    /// Output settings that contradict each other
+    ConflictingSinks(String),
+    /// The log file cannot be created or written
+    PathNotWritable {
+        /// The offending path
+        path: std::path::PathBuf,
+        /// Why it cannot be written
+        reason: String,
+    },
+    /// A `ColorTheme` color that is not a list of SGR parameters
+    InvalidColor {
+        /// The level the color is for
+        level: LogLevel,
+        /// The rejected color
+        sgr: String,
+    },
 }
 
 impl From<io::Error> for LoggerError {
@@ -198,6 +561,17 @@ impl std::fmt::Display for LoggerError {
             LoggerError::SetLogger(err) => write!(f, "Set logger error: {}", err),
             LoggerError::AlreadyInitialized => write!(f, "Logger already initialized"),
             LoggerError::ChannelError => write!(f, "Channel communication error"),
+            LoggerError::Config(message) => write!(f, "Config error: {}", message),
+            LoggerError::InvalidBatchSize { batch_size, reason } => {
+                write!(f, "Invalid batch size {}: {}", batch_size, reason)
+            }
+            LoggerError::ConflictingSinks(message) => write!(f, "Conflicting outputs: {}", message),
+            LoggerError::PathNotWritable { path, reason } => {
+                write!(f, "Cannot write log file {}: {}", path.display(), reason)
+            }
+            LoggerError::InvalidColor { level, sgr } => {
+                write!(f, "Invalid color {:?} for {}: expected SGR parameters such as \"1;31\"", sgr, level.as_str())
+            }
         }
     }
 }
@@ -0,0 +1,54 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+use crate::format::FieldValue;
+
+thread_local! {
+    static CONTEXT: RefCell<Vec<(String, FieldValue)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Removes a context entry (and anything pushed after it) when dropped
+///
+/// The guard is tied to the thread that created it.
+#[must_use = "the context entry is removed as soon as the guard is dropped"]
+pub struct ContextGuard {
+    depth: usize,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CONTEXT.with(|context| context.borrow_mut().truncate(self.depth));
+    }
+}
+
+/// Attach `key=value` to every record logged on this thread until the guard is dropped
+///
+/// ```rust
+/// use artifice_logging::push_context;
+///
+/// let _scene = push_context("scene", "main_menu");
+/// let _frame = push_context("frame", 1042u64);
+/// log::info!("Rendering"); // ... scene=main_menu frame=1042
+/// ```
+pub fn push_context<K: Into<String>, V: Into<FieldValue>>(key: K, value: V) -> ContextGuard {
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        let depth = context.len();
+        context.push((key.into(), value.into()));
+        ContextGuard {
+            depth,
+            _not_send: PhantomData,
+        }
+    })
+}
+
+/// Snapshot of the context entries active on this thread, outermost first
+pub fn current_context() -> Vec<(String, FieldValue)> {
+    CONTEXT.with(|context| context.borrow().clone())
+}
+
+/// Run `f` with the active context entries without cloning them
+pub fn with_context<R>(f: impl FnOnce(&[(String, FieldValue)]) -> R) -> R {
+    CONTEXT.with(|context| f(&context.borrow()))
+}
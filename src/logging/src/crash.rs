@@ -0,0 +1,265 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::atomic::{fence, AtomicPtr, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Bytes kept of each message; longer messages are cut off
+const SLOT_BYTES: usize = 512;
+const SLOT_WORDS: usize = SLOT_BYTES / 8;
+
+/// Ring the crash handlers dump; set by `CrashRing::install`
+static INSTALLED: AtomicPtr<CrashRing> = AtomicPtr::new(ptr::null_mut());
+
+/// One message, guarded by a sequence lock
+struct Slot {
+    /// Even while the slot is stable, odd while a message is copied in
+    sequence: AtomicU64,
+    len: AtomicUsize,
+    /// Message bytes packed into words so they can be read while being written
+    words: Box<[AtomicU64]>,
+}
+
+impl Slot {
+    fn new() -> Self {
+        Self {
+            sequence: AtomicU64::new(0),
+            len: AtomicUsize::new(0),
+            words: (0..SLOT_WORDS).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    fn store(&self, line: &[u8]) -> bool {
+        let sequence = self.sequence.load(Ordering::Relaxed);
+        if sequence % 2 == 1
+            || self
+                .sequence
+                .compare_exchange(sequence, sequence + 1, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+        {
+            // Another thread is filling this slot; the ring has wrapped around it
+            return false;
+        }
+        fence(Ordering::Release);
+
+        let line = &line[..line.len().min(SLOT_BYTES)];
+        for (word, chunk) in self.words.iter().zip(line.chunks(8)) {
+            let mut bytes = [0u8; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            word.store(u64::from_ne_bytes(bytes), Ordering::Relaxed);
+        }
+        self.len.store(line.len(), Ordering::Relaxed);
+        self.sequence.store(sequence + 2, Ordering::Release);
+        true
+    }
+
+    /// Copy the message into `out`, or `None` if the slot is empty or changing
+    fn load<'a>(&self, out: &'a mut [u8; SLOT_BYTES]) -> Option<&'a [u8]> {
+        let before = self.sequence.load(Ordering::Acquire);
+        if before == 0 || before % 2 == 1 {
+            return None;
+        }
+
+        let len = self.len.load(Ordering::Relaxed).min(SLOT_BYTES);
+        for (word, chunk) in self.words.iter().zip(out.chunks_mut(8)) {
+            chunk.copy_from_slice(&word.load(Ordering::Relaxed).to_ne_bytes());
+        }
+        fence(Ordering::Acquire);
+
+        (self.sequence.load(Ordering::Relaxed) == before).then_some(&out[..len])
+    }
+}
+
+/// The last log messages, kept for writing out when the process crashes
+///
+/// All memory is allocated up front and recording never blocks, so the ring
+/// can be dumped from a panic hook or a fatal-signal handler even while the
+/// writer threads are wedged. Dumps are appended to a separate file such as
+/// `crash.log` rather than going through the normal outputs.
+///
+/// ```rust,no_run
+/// use artifice_logging::crash::CrashRing;
+///
+/// let ring = CrashRing::new("crash.log", 256);
+/// ring.install();
+/// ring.record("[12:00:00.000] INFO: Loading level 3");
+/// ```
+pub struct CrashRing {
+    slots: Box<[Slot]>,
+    next: AtomicUsize,
+    path: PathBuf,
+    /// `path` prepared for `open(2)` inside a signal handler
+    #[cfg(all(unix, feature = "signals"))]
+    c_path: Option<std::ffi::CString>,
+}
+
+impl CrashRing {
+    /// Keep the last `messages` messages, dumping them to `path`
+    pub fn new<P: Into<PathBuf>>(path: P, messages: usize) -> Arc<Self> {
+        let path = path.into();
+        Arc::new(Self {
+            slots: (0..messages.max(1)).map(|_| Slot::new()).collect(),
+            next: AtomicUsize::new(0),
+            #[cfg(all(unix, feature = "signals"))]
+            c_path: {
+                use std::os::unix::ffi::OsStrExt;
+                std::ffi::CString::new(path.as_os_str().as_bytes()).ok()
+            },
+            path,
+        })
+    }
+
+    /// File the ring is dumped to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Remember a formatted message, overwriting the oldest one
+    pub fn record(&self, line: &str) {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.slots.len();
+        self.slots[index].store(line.as_bytes());
+    }
+
+    /// Hand each remembered message to `write`, oldest first
+    ///
+    /// Neither allocates nor locks, so it is safe to call from a signal handler.
+    fn each_message(&self, mut write: impl FnMut(&[u8])) {
+        let start = self.next.load(Ordering::Relaxed);
+        let mut buffer = [0u8; SLOT_BYTES];
+        for offset in 0..self.slots.len() {
+            let slot = &self.slots[(start + offset) % self.slots.len()];
+            if let Some(line) = slot.load(&mut buffer) {
+                write(line);
+                write(b"\n");
+            }
+        }
+    }
+
+    /// Append the remembered messages to the crash file under a `reason` header
+    pub fn dump(&self, reason: &str) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "=== {} ===", reason)?;
+        let mut result = Ok(());
+        self.each_message(|bytes| {
+            if result.is_ok() {
+                result = file.write_all(bytes);
+            }
+        });
+        result?;
+        file.sync_all()
+    }
+
+    /// Dump the ring from a panic hook and, with the `signals` feature, on fatal signals
+    ///
+    /// The previous panic hook and signal handlers still run afterwards. Only
+    /// one ring is dumped per process; installing another replaces it.
+    pub fn install(self: &Arc<Self>) {
+        let previous = INSTALLED.swap(Arc::into_raw(Arc::clone(self)).cast_mut(), Ordering::AcqRel);
+        if !previous.is_null() {
+            // The hooks below stay installed and now dump the new ring
+            // SAFETY: `previous` came from `Arc::into_raw` in an earlier `install`
+            drop(unsafe { Arc::from_raw(previous) });
+            return;
+        }
+
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if let Some(ring) = installed() {
+                let _ = ring.dump(&format!("panic: {}", info));
+            }
+            previous_hook(info);
+        }));
+
+        #[cfg(all(unix, feature = "signals"))]
+        if let Err(e) = signals::install() {
+            eprintln!("Failed to install crash signal handlers: {}", e);
+        }
+    }
+}
+
+/// The ring set by `CrashRing::install`
+fn installed() -> Option<&'static CrashRing> {
+    // SAFETY: installed rings are leaked and never freed while installed
+    unsafe { INSTALLED.load(Ordering::Acquire).as_ref() }
+}
+
+#[cfg(all(unix, feature = "signals"))]
+mod signals {
+    use std::io;
+    use std::sync::OnceLock;
+
+    use super::installed;
+
+    const FATAL_SIGNALS: [libc::c_int; 5] = [libc::SIGSEGV, libc::SIGBUS, libc::SIGILL, libc::SIGFPE, libc::SIGABRT];
+
+    /// Handlers that were in place before ours, restored before re-raising
+    static PREVIOUS: [OnceLock<libc::sigaction>; FATAL_SIGNALS.len()] =
+        [const { OnceLock::new() }; FATAL_SIGNALS.len()];
+
+    extern "C" fn on_fatal_signal(signal: libc::c_int) {
+        // Only async-signal-safe calls: open, write, close, sigaction, raise
+        if let Some(ring) = installed() {
+            if let Some(path) = &ring.c_path {
+                // SAFETY: `path` is a valid NUL-terminated string owned by the leaked ring
+                let fd = unsafe {
+                    libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CREAT | libc::O_APPEND, 0o644)
+                };
+                if fd >= 0 {
+                    let write = |bytes: &[u8]| {
+                        // SAFETY: `bytes` is valid for its length; short writes are tolerated
+                        unsafe { libc::write(fd, bytes.as_ptr().cast(), bytes.len()) };
+                    };
+                    write(b"=== fatal signal ");
+                    write(signal_name(signal));
+                    write(b" ===\n");
+                    ring.each_message(write);
+                    // SAFETY: `fd` was opened above
+                    unsafe { libc::close(fd) };
+                }
+            }
+        }
+
+        // Let the previous handler (or the default action) finish the process
+        if let Some(index) = FATAL_SIGNALS.iter().position(|&fatal| fatal == signal) {
+            if let Some(previous) = PREVIOUS[index].get() {
+                // SAFETY: restoring a sigaction obtained from the kernel
+                unsafe { libc::sigaction(signal, previous, std::ptr::null_mut()) };
+            }
+        }
+        // SAFETY: raise is async-signal-safe; the signal is delivered once this handler returns
+        unsafe { libc::raise(signal) };
+    }
+
+    fn signal_name(signal: libc::c_int) -> &'static [u8] {
+        match signal {
+            libc::SIGSEGV => b"SIGSEGV",
+            libc::SIGBUS => b"SIGBUS",
+            libc::SIGILL => b"SIGILL",
+            libc::SIGFPE => b"SIGFPE",
+            libc::SIGABRT => b"SIGABRT",
+            _ => b"unknown",
+        }
+    }
+
+    pub(super) fn install() -> io::Result<()> {
+        for (index, &signal) in FATAL_SIGNALS.iter().enumerate() {
+            // SAFETY: the handler only performs async-signal-safe calls
+            unsafe {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = on_fatal_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+                // Run on the alternate stack std sets up, so stack overflows are dumped too
+                action.sa_flags = libc::SA_ONSTACK;
+                libc::sigemptyset(&mut action.sa_mask);
+
+                let mut previous: libc::sigaction = std::mem::zeroed();
+                if libc::sigaction(signal, &action, &mut previous) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let _ = PREVIOUS[index].set(previous);
+            }
+        }
+        Ok(())
+    }
+}
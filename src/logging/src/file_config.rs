@@ -0,0 +1,625 @@
+use log::LevelFilter;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use toml::{Table, Value};
+
+use crate::config::{AdaptiveBatching, BatchConfig, ColorChoice, ColorTheme, DiskQuota, FileSharing, LogLevel, LoggerError, QuotaAction, RotationPolicy};
+use crate::filter::{Directive, EnvFilter};
+use crate::format::{DefaultFormatter, JsonFormatter, LineDetails, LogFormatter, TimestampClock, DEFAULT_TIMESTAMP_FORMAT};
+use crate::sinks::{ConsoleSink, FileSink, NetworkSink};
+use crate::writers::NetworkProtocol;
+use crate::{LoggerBuilder, LoggerHandle};
+
+/// Line layout selectable from a config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineFormat {
+    /// `[timestamp] LEVEL: message key=value`
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Kind of output an extra `[[sinks]]` entry creates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SinkKind {
+    /// Standard output
+    Console {
+        /// Wrap levels in ANSI color codes
+        colors: bool,
+    },
+    /// A (possibly rotating) log file
+    File {
+        /// File path, or base name when rotating
+        path: PathBuf,
+        /// Rollover schedule
+        rotation: RotationPolicy,
+        /// Size budget for the file and its rotated siblings
+        quota: Option<DiskQuota>,
+    },
+    /// A remote collector
+    Network {
+        /// `host:port` of the collector
+        address: String,
+        /// Transport used to ship batches
+        protocol: NetworkProtocol,
+    },
+    /// systemd-journald (Linux only)
+    Journald,
+}
+
+/// One `[[sinks]]` entry of a config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SinkConfig {
+    /// What the sink writes to
+    pub kind: SinkKind,
+    /// Most verbose level the sink accepts
+    pub level: LogLevel,
+    /// Line layout of the sink
+    pub format: LineFormat,
+}
+
+/// Logger settings loaded from a TOML file
+///
+/// ```toml
+/// level = "info"
+/// filter = "glfw=off"
+/// console = true
+/// colors = "auto"            # true, false or "auto" (terminal and no NO_COLOR)
+/// format = "text"            # or "json"
+/// show_target = true         # JSON lines always carry the target
+/// show_source = true         # file:line, plus module in JSON
+/// timestamps = "local"       # "utc", "local" or "relative"
+/// timestamp_format = "%H:%M:%S%.3f"
+///
+/// [levels]
+/// "artifice_engine::events" = "trace"
+///
+/// [theme]                    # ANSI SGR parameters per level
+/// error = "1;31"
+/// debug = "90"
+///
+/// [file]
+/// path = "logs/server.log"
+/// rotation = "daily"         # "never", "hourly" or "daily"
+/// max_size_mb = 500
+/// on_quota = "delete_oldest" # or "stop"
+/// sharing = "locked"         # "single", "locked" or "atomic_append"
+///
+/// [batch]
+/// batch_size = 100
+/// flush_interval_ms = 100
+/// adaptive = true            # tune within min/max_batch_size and
+/// max_batch_size = 2000      # min/max_flush_interval_ms
+///
+/// [[sinks]]
+/// type = "file"              # "console", "file", "network" or "journald"
+/// path = "logs/warnings.log"
+/// level = "warn"
+/// format = "json"
+/// ```
+///
+/// Unknown keys and malformed values are reported as `LoggerError::Config`.
+#[derive(Debug, Clone)]
+pub struct LogFileConfig {
+    /// Per-target filter built from `level`, `filter` and `[levels]`
+    pub filter: Option<EnvFilter>,
+    /// Write to standard output
+    pub console: bool,
+    /// Whether to color levels on the console
+    pub colors: ColorChoice,
+    /// Per-level colors of the text format
+    pub theme: ColorTheme,
+    /// Line layout of the console and file output
+    pub format: LineFormat,
+    /// Target and source location shown in each line
+    pub details: LineDetails,
+    /// Clock used for timestamps
+    pub clock: TimestampClock,
+    /// Custom `chrono` timestamp format
+    pub timestamp_format: Option<String>,
+    /// Primary log file
+    pub file: Option<PathBuf>,
+    /// Rollover schedule of the primary log file
+    pub rotation: RotationPolicy,
+    /// Size budget of the primary log file and its rotated siblings
+    pub disk_quota: Option<DiskQuota>,
+    /// Batching of file and network output
+    pub batch: BatchConfig,
+    /// Extra sinks, in the order they are attached
+    pub sinks: Vec<SinkConfig>,
+}
+
+impl Default for LogFileConfig {
+    fn default() -> Self {
+        Self {
+            filter: None,
+            console: true,
+            colors: ColorChoice::Auto,
+            theme: ColorTheme::default(),
+            format: LineFormat::Text,
+            details: LineDetails::default(),
+            clock: TimestampClock::Utc,
+            timestamp_format: None,
+            file: None,
+            rotation: RotationPolicy::Never,
+            disk_quota: None,
+            batch: BatchConfig::default(),
+            sinks: Vec::new(),
+        }
+    }
+}
+
+impl LogFileConfig {
+    /// Read and parse a TOML config file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LoggerError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parse TOML config text
+    pub fn parse(text: &str) -> Result<Self, LoggerError> {
+        let table: Table = text.parse().map_err(|e| config_error(format!("invalid TOML: {}", e)))?;
+        let mut config = Self::default();
+        let mut filter: Option<EnvFilter> = None;
+
+        for (key, value) in &table {
+            match key.as_str() {
+                "level" => {
+                    let level = level_filter(key, value)?;
+                    filter
+                        .get_or_insert_with(EnvFilter::new)
+                        .add_directive(Directive { target: None, level });
+                }
+                "filter" => {
+                    let spec = EnvFilter::parse(string(key, value)?);
+                    let filter = filter.get_or_insert_with(EnvFilter::new);
+                    for directive in spec.directives() {
+                        filter.add_directive(directive.clone());
+                    }
+                }
+                "levels" => {
+                    let filter = filter.get_or_insert_with(EnvFilter::new);
+                    for (target, level) in table_of(key, value)? {
+                        filter.add_directive(Directive {
+                            target: Some(target.clone()),
+                            level: level_filter(target, level)?,
+                        });
+                    }
+                }
+                "console" => config.console = boolean(key, value)?,
+                "colors" => config.colors = color_choice(key, value)?,
+                "theme" => {
+                    for (level, color) in table_of(key, value)? {
+                        let parsed = match level.as_str() {
+                            "error" => LogLevel::Error,
+                            "warn" => LogLevel::Warn,
+                            "info" => LogLevel::Info,
+                            "debug" => LogLevel::Debug,
+                            "trace" => LogLevel::Trace,
+                            _ => return Err(unknown_key("[theme]", level)),
+                        };
+                        config.theme = config.theme.with_color(parsed, string(level, color)?);
+                    }
+                    config.theme.validate()?;
+                }
+                "format" => config.format = line_format(key, value)?,
+                "timestamps" => config.clock = clock(key, value)?,
+                "show_target" => config.details.target = boolean(key, value)?,
+                "show_source" => config.details.source = boolean(key, value)?,
+                "timestamp_format" => config.timestamp_format = Some(string(key, value)?.to_string()),
+                "file" => {
+                    let file = table_of(key, value)?;
+                    for (key, value) in file {
+                        match key.as_str() {
+                            "path" => config.file = Some(PathBuf::from(string(key, value)?)),
+                            "rotation" => config.rotation = rotation(key, value)?,
+                            "sharing" => config.batch.sharing = sharing(key, value)?,
+                            "max_size_mb" | "on_quota" => {}
+                            _ => return Err(unknown_key("file", key)),
+                        }
+                    }
+                    if config.file.is_none() {
+                        return Err(config_error("[file] requires a `path`"));
+                    }
+                    config.disk_quota = disk_quota(file)?;
+                }
+                "batch" => {
+                    for (key, value) in table_of(key, value)? {
+                        match key.as_str() {
+                            "enabled" => config.batch.enabled = boolean(key, value)?,
+                            "batch_size" => config.batch.batch_size = integer(key, value)? as usize,
+                            "flush_interval_ms" => config.batch.flush_interval_ms = integer(key, value)?,
+                            "deduplicate" => config.batch.deduplicate = boolean(key, value)?,
+                            "adaptive" => {
+                                config.batch.adaptive = boolean(key, value)?.then(AdaptiveBatching::default)
+                            }
+                            "min_batch_size" => {
+                                adaptive(&mut config.batch).min_batch_size = integer(key, value)? as usize
+                            }
+                            "max_batch_size" => {
+                                adaptive(&mut config.batch).max_batch_size = integer(key, value)? as usize
+                            }
+                            "min_flush_interval_ms" => {
+                                adaptive(&mut config.batch).min_flush_interval_ms = integer(key, value)?
+                            }
+                            "max_flush_interval_ms" => {
+                                adaptive(&mut config.batch).max_flush_interval_ms = integer(key, value)?
+                            }
+                            "channel_capacity" => {
+                                config.batch.channel_capacity = Some(integer(key, value)? as usize)
+                            }
+                            _ => return Err(unknown_key("batch", key)),
+                        }
+                    }
+                    if let Some(adaptive) = &config.batch.adaptive {
+                        adaptive.validate()?;
+                    }
+                }
+                "sinks" => {
+                    let Value::Array(entries) = value else {
+                        return Err(config_error("`sinks` must be an array of tables"));
+                    };
+                    for entry in entries {
+                        config.sinks.push(sink_config(table_of(key, entry)?)?);
+                    }
+                }
+                _ => return Err(unknown_key("top level", key)),
+            }
+        }
+
+        config.filter = filter;
+        Ok(config)
+    }
+
+    /// Create a builder with these settings
+    ///
+    /// Extra sinks are opened here, so missing directories are reported now.
+    pub fn to_builder(&self) -> Result<LoggerBuilder, LoggerError> {
+        let mut builder = LoggerBuilder::new()
+            .console(self.console)
+            .color_choice(self.colors)
+            .theme(self.theme.clone())
+            .show_target(self.details.target)
+            .show_source(self.details.source)
+            .batch_config(self.batch.clone())
+            .rotation(self.rotation);
+        if let Some(quota) = self.disk_quota {
+            builder = builder.disk_quota(quota);
+        }
+
+        builder = match self.clock {
+            TimestampClock::Utc => builder,
+            TimestampClock::Local => builder.local_time(true),
+            TimestampClock::Relative(_) => builder.relative_timestamps(true),
+        };
+        if let Some(format) = &self.timestamp_format {
+            builder = builder.timestamp_format(format.clone());
+        }
+        if self.format == LineFormat::Json {
+            builder = builder.formatter(self.json_formatter());
+        }
+        if let Some(filter) = &self.filter {
+            builder = builder.filter(filter.clone());
+        }
+        if let Some(path) = &self.file {
+            builder = builder.file(path.to_string_lossy());
+        }
+
+        for sink in &self.sinks {
+            builder = match &sink.kind {
+                SinkKind::Console { colors } => builder.sink(
+                    ConsoleSink::new(*colors)
+                        .with_formatter(self.sink_formatter(sink.format, *colors))
+                        .with_level(sink.level),
+                ),
+                SinkKind::File { path, rotation, quota } => {
+                    let file = match quota {
+                        Some(quota) => FileSink::with_quota(path, *rotation, *quota, self.batch.clone())?,
+                        None => FileSink::rotating(path, *rotation, self.batch.clone())?,
+                    };
+                    builder.sink(
+                        file.with_formatter(self.sink_formatter(sink.format, false))
+                            .with_level(sink.level),
+                    )
+                }
+                SinkKind::Network { address, protocol } => builder.sink(
+                    NetworkSink::new(address.clone(), *protocol, self.batch.clone())?
+                        .with_formatter(self.sink_formatter(sink.format, false))
+                        .with_level(sink.level),
+                ),
+                SinkKind::Journald => self.journald_sink(builder, sink.level)?,
+            };
+        }
+
+        Ok(builder)
+    }
+
+    /// Apply the settings that can change while the logger runs
+    ///
+    /// Target filters and sink levels are updated in place; outputs, formats
+    /// and rotation only take effect on the next start.
+    pub fn apply_levels(&self, handle: &LoggerHandle) {
+        match &self.filter {
+            Some(filter) => handle.set_filter(filter.clone()),
+            None => handle.clear_filter(),
+        }
+        for (index, sink) in self.sinks.iter().enumerate() {
+            handle.set_sink_level(index, sink.level);
+        }
+    }
+
+    fn json_formatter(&self) -> JsonFormatter {
+        JsonFormatter::new().with_details(LineDetails { target: true, source: self.details.source })
+    }
+
+    fn sink_formatter(&self, format: LineFormat, colors: bool) -> impl LogFormatter + 'static {
+        let formatter: Box<dyn LogFormatter> = match format {
+            LineFormat::Json => Box::new(self.json_formatter()),
+            LineFormat::Text => Box::new(
+                DefaultFormatter::new(colors)
+                    .with_theme(self.theme.clone())
+                    .with_details(self.details)
+                    .with_timestamp_format(
+                        self.timestamp_format.as_deref().unwrap_or(DEFAULT_TIMESTAMP_FORMAT),
+                    )
+                    .with_clock(self.clock),
+            ),
+        };
+        SelectedFormatter(formatter)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn journald_sink(&self, builder: LoggerBuilder, level: LogLevel) -> Result<LoggerBuilder, LoggerError> {
+        Ok(builder.sink(crate::sinks::JournaldSink::new()?.with_level(level)))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn journald_sink(&self, _builder: LoggerBuilder, _level: LogLevel) -> Result<LoggerBuilder, LoggerError> {
+        Err(config_error("journald sinks are only available on Linux"))
+    }
+}
+
+/// Formatter chosen by a `format` entry
+struct SelectedFormatter(Box<dyn LogFormatter>);
+
+impl LogFormatter for SelectedFormatter {
+    fn format(&self, record: &log::Record) -> String {
+        self.0.format(record)
+    }
+
+    fn format_into(&self, record: &log::Record, out: &mut String) {
+        self.0.format_into(record, out);
+    }
+
+    fn inherit_theme(&mut self, theme: &ColorTheme) {
+        self.0.inherit_theme(theme);
+    }
+}
+
+/// Reloads a config file whenever it changes
+///
+/// Dropping the watcher stops the polling thread.
+pub struct ConfigWatcher {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl ConfigWatcher {
+    /// Poll `path` every `interval` and apply its levels to `handle` when it changes
+    ///
+    /// Files that fail to parse are reported through the logger and otherwise
+    /// ignored, leaving the previous levels in place.
+    pub fn spawn<P: Into<PathBuf>>(handle: LoggerHandle, path: P, interval: Duration) -> Self {
+        let path = path.into();
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        let thread = thread::spawn(move || {
+            let mut last_modified = modified(&path);
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let current = modified(&path);
+                if current.is_none() || current == last_modified {
+                    continue;
+                }
+                last_modified = current;
+
+                match LogFileConfig::load(&path) {
+                    Ok(config) => {
+                        config.apply_levels(&handle);
+                        log::info!("Reloaded logging config from {}", path.display());
+                    }
+                    Err(e) => log::warn!("Failed to reload logging config {}: {}", path.display(), e),
+                }
+            }
+        });
+
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn sink_config(table: &Table) -> Result<SinkConfig, LoggerError> {
+    let kind = match table.get("type") {
+        Some(value) => string("type", value)?,
+        None => return Err(config_error("each [[sinks]] entry requires a `type`")),
+    };
+
+    let mut level = LogLevel::Trace;
+    let mut format = LineFormat::Text;
+    let mut colors = false;
+    let mut path = None;
+    let mut rotation_policy = RotationPolicy::Never;
+    let mut address = None;
+    let mut protocol = NetworkProtocol::Tcp;
+
+    for (key, value) in table {
+        match key.as_str() {
+            "type" => {}
+            "level" => {
+                level = match level_filter(key, value)? {
+                    LevelFilter::Off => return Err(config_error("sink levels cannot be \"off\"")),
+                    level => level.into(),
+                }
+            }
+            "format" => format = line_format(key, value)?,
+            "colors" if kind == "console" => colors = boolean(key, value)?,
+            "path" if kind == "file" => path = Some(PathBuf::from(string(key, value)?)),
+            "rotation" if kind == "file" => rotation_policy = rotation(key, value)?,
+            "max_size_mb" | "on_quota" if kind == "file" => {}
+            "address" if kind == "network" => address = Some(string(key, value)?.to_string()),
+            "protocol" if kind == "network" => {
+                protocol = match string(key, value)? {
+                    "tcp" => NetworkProtocol::Tcp,
+                    "udp" => NetworkProtocol::Udp,
+                    other => return Err(invalid_value(key, other)),
+                }
+            }
+            _ => return Err(unknown_key(&format!("{} sink", kind), key)),
+        }
+    }
+
+    let kind = match kind {
+        "console" => SinkKind::Console { colors },
+        "file" => SinkKind::File {
+            path: path.ok_or_else(|| config_error("file sinks require a `path`"))?,
+            rotation: rotation_policy,
+            quota: disk_quota(table)?,
+        },
+        "network" => SinkKind::Network {
+            address: address.ok_or_else(|| config_error("network sinks require an `address`"))?,
+            protocol,
+        },
+        "journald" => SinkKind::Journald,
+        other => return Err(invalid_value("type", other)),
+    };
+
+    Ok(SinkConfig { kind, level, format })
+}
+
+fn config_error<S: Into<String>>(message: S) -> LoggerError {
+    LoggerError::Config(message.into())
+}
+
+fn unknown_key(section: &str, key: &str) -> LoggerError {
+    config_error(format!("unknown key `{}` in {}", key, section))
+}
+
+fn invalid_value(key: &str, value: &str) -> LoggerError {
+    config_error(format!("invalid value {:?} for `{}`", value, key))
+}
+
+fn string<'a>(key: &str, value: &'a Value) -> Result<&'a str, LoggerError> {
+    value
+        .as_str()
+        .ok_or_else(|| config_error(format!("`{}` must be a string", key)))
+}
+
+fn boolean(key: &str, value: &Value) -> Result<bool, LoggerError> {
+    value
+        .as_bool()
+        .ok_or_else(|| config_error(format!("`{}` must be true or false", key)))
+}
+
+fn integer(key: &str, value: &Value) -> Result<u64, LoggerError> {
+    value
+        .as_integer()
+        .and_then(|number| u64::try_from(number).ok())
+        .ok_or_else(|| config_error(format!("`{}` must be a non-negative integer", key)))
+}
+
+fn table_of<'a>(key: &str, value: &'a Value) -> Result<&'a Table, LoggerError> {
+    value
+        .as_table()
+        .ok_or_else(|| config_error(format!("`{}` must be a table", key)))
+}
+
+fn level_filter(key: &str, value: &Value) -> Result<LevelFilter, LoggerError> {
+    let text = string(key, value)?;
+    text.parse().map_err(|_| invalid_value(key, text))
+}
+
+fn color_choice(key: &str, value: &Value) -> Result<ColorChoice, LoggerError> {
+    match value {
+        Value::Boolean(true) => Ok(ColorChoice::Always),
+        Value::Boolean(false) => Ok(ColorChoice::Never),
+        _ => match string(key, value).map_err(|_| config_error(format!("`{}` must be true, false or \"auto\"", key)))? {
+            "auto" => Ok(ColorChoice::Auto),
+            other => Err(invalid_value(key, other)),
+        },
+    }
+}
+
+fn line_format(key: &str, value: &Value) -> Result<LineFormat, LoggerError> {
+    match string(key, value)? {
+        "text" => Ok(LineFormat::Text),
+        "json" => Ok(LineFormat::Json),
+        other => Err(invalid_value(key, other)),
+    }
+}
+
+fn clock(key: &str, value: &Value) -> Result<TimestampClock, LoggerError> {
+    match string(key, value)? {
+        "utc" => Ok(TimestampClock::Utc),
+        "local" => Ok(TimestampClock::Local),
+        "relative" => Ok(TimestampClock::relative()),
+        other => Err(invalid_value(key, other)),
+    }
+}
+
+/// Read the optional `max_size_mb` / `on_quota` pair of a file section
+/// Adaptive bounds of `batch`, switching adaptive batching on with the defaults
+fn adaptive(batch: &mut BatchConfig) -> &mut AdaptiveBatching {
+    batch.adaptive.get_or_insert_with(AdaptiveBatching::default)
+}
+
+fn disk_quota(table: &Table) -> Result<Option<DiskQuota>, LoggerError> {
+    let action = match table.get("on_quota") {
+        Some(value) => match string("on_quota", value)? {
+            "delete_oldest" => QuotaAction::DeleteOldest,
+            "stop" => QuotaAction::StopWriting,
+            other => return Err(invalid_value("on_quota", other)),
+        },
+        None => QuotaAction::DeleteOldest,
+    };
+
+    match table.get("max_size_mb") {
+        Some(value) => Ok(Some(DiskQuota::megabytes(integer("max_size_mb", value)?).with_action(action))),
+        None if table.contains_key("on_quota") => Err(config_error("`on_quota` requires `max_size_mb`")),
+        None => Ok(None),
+    }
+}
+
+fn sharing(key: &str, value: &Value) -> Result<FileSharing, LoggerError> {
+    match string(key, value)? {
+        "single" => Ok(FileSharing::Single),
+        "locked" => Ok(FileSharing::Locked),
+        "atomic_append" => Ok(FileSharing::AtomicAppend),
+        other => Err(invalid_value(key, other)),
+    }
+}
+
+fn rotation(key: &str, value: &Value) -> Result<RotationPolicy, LoggerError> {
+    match string(key, value)? {
+        "never" => Ok(RotationPolicy::Never),
+        "hourly" => Ok(RotationPolicy::Hourly),
+        "daily" => Ok(RotationPolicy::Daily),
+        other => Err(invalid_value(key, other)),
+    }
+}
@@ -0,0 +1,129 @@
+use log::{LevelFilter, Metadata};
+
+/// A single `target=level` entry of a filter spec
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directive {
+    /// Target prefix the directive applies to (`None` matches every target)
+    pub target: Option<String>,
+    /// Most verbose level allowed for matching targets
+    pub level: LevelFilter,
+}
+
+/// Per-target level filter using the `RUST_LOG` syntax
+///
+/// A spec is a comma separated list of directives:
+/// - `info` sets the default level
+/// - `artifice_engine::events=trace` sets the level for a target prefix
+/// - `glfw` (target without a level) enables everything for that target
+///
+/// The most specific (longest) matching target prefix wins. Entries that
+/// cannot be parsed are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvFilter {
+    directives: Vec<Directive>,
+}
+
+impl EnvFilter {
+    /// Create an empty filter that allows everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a `RUST_LOG`-style spec such as `info,artifice_engine::events=trace,glfw=off`
+    pub fn parse(spec: &str) -> Self {
+        let mut filter = Self::new();
+
+        for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let directive = match part.split_once('=') {
+                Some((target, level)) => match parse_level(level.trim()) {
+                    Some(level) => Directive {
+                        target: Some(target.trim().to_string()),
+                        level,
+                    },
+                    None => continue,
+                },
+                None => match parse_level(part) {
+                    Some(level) => Directive { target: None, level },
+                    None => Directive {
+                        target: Some(part.to_string()),
+                        level: LevelFilter::Trace,
+                    },
+                },
+            };
+            filter.add_directive(directive);
+        }
+
+        filter
+    }
+
+    /// Read and parse the `RUST_LOG` environment variable, if set
+    pub fn from_env() -> Option<Self> {
+        std::env::var("RUST_LOG").ok().map(|spec| Self::parse(&spec))
+    }
+
+    /// Add a directive, replacing any existing one for the same target
+    pub fn add_directive(&mut self, directive: Directive) {
+        match self.directives.iter_mut().find(|existing| existing.target == directive.target) {
+            Some(existing) => existing.level = directive.level,
+            None => self.directives.push(directive),
+        }
+    }
+
+    /// Get the parsed directives
+    pub fn directives(&self) -> &[Directive] {
+        &self.directives
+    }
+
+    /// Level allowed for `target` by the most specific matching directive
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        let mut best: Option<&Directive> = None;
+
+        for directive in &self.directives {
+            let specificity = match &directive.target {
+                Some(prefix) if target.starts_with(prefix.as_str()) => prefix.len(),
+                Some(_) => continue,
+                None => 0,
+            };
+
+            let best_specificity = best
+                .and_then(|d| d.target.as_ref())
+                .map_or(0, |prefix| prefix.len());
+
+            if best.is_none() || specificity > best_specificity {
+                best = Some(directive);
+            }
+        }
+
+        // No default directive means only explicitly listed targets pass
+        match best {
+            Some(directive) => directive.level,
+            None if self.directives.is_empty() => LevelFilter::Trace,
+            None => LevelFilter::Off,
+        }
+    }
+
+    /// Check whether a record with this metadata passes the filter
+    pub fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    /// Most verbose level any directive allows, suitable for `log::set_max_level`
+    pub fn max_level(&self) -> LevelFilter {
+        if self.directives.is_empty() {
+            return LevelFilter::Trace;
+        }
+
+        self.directives
+            .iter()
+            .map(|directive| directive.level)
+            .max()
+            .unwrap_or(LevelFilter::Off)
+    }
+}
+
+fn parse_level(level: &str) -> Option<LevelFilter> {
+    if level.is_empty() {
+        return None;
+    }
+    level.parse().ok()
+}
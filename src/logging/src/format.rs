@@ -0,0 +1,479 @@
+use log::kv::{self, VisitSource};
+use log::Record;
+use std::fmt::{self, Write};
+use std::time::Instant;
+
+use crate::config::{ColorTheme, LogLevel};
+use crate::context;
+
+/// Turns a log record into the line written to console and file output
+///
+/// Implement this to add custom fields, change the timestamp format or drop
+/// color codes, then install it with `LoggerBuilder::formatter` or
+/// `ArtificeLogger::with_formatter`.
+pub trait LogFormatter: Send + Sync {
+    /// Format a single record (without a trailing newline)
+    fn format(&self, record: &Record) -> String;
+
+    /// Append the formatted record to `out`, reusing its allocation
+    ///
+    /// The logger calls this with pooled buffers; override it to keep the
+    /// per-message path free of heap allocations.
+    fn format_into(&self, record: &Record, out: &mut String) {
+        out.push_str(&self.format(record));
+    }
+
+    /// Take the logger's `ColorTheme` unless this formatter has colors of its own
+    ///
+    /// Formatters without colored output ignore it.
+    fn inherit_theme(&mut self, _theme: &ColorTheme) {}
+}
+
+/// Timestamp format used by the default output
+pub const DEFAULT_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// Clock used to timestamp log lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampClock {
+    /// Wall-clock time in UTC
+    #[default]
+    Utc,
+    /// Wall-clock time in the system's local timezone
+    Local,
+    /// Monotonic time elapsed since the given instant, e.g. `+12.345s`
+    ///
+    /// The `chrono` format string is ignored for relative timestamps.
+    Relative(Instant),
+}
+
+impl TimestampClock {
+    /// Relative timestamps counted from now
+    pub fn relative() -> Self {
+        TimestampClock::Relative(Instant::now())
+    }
+
+    /// Render the current time with the given `chrono` format string
+    pub fn now(&self, format: &str) -> String {
+        match self {
+            TimestampClock::Utc => chrono::Utc::now().format(format).to_string(),
+            TimestampClock::Local => chrono::Local::now().format(format).to_string(),
+            TimestampClock::Relative(start) => format!("+{:.3}s", start.elapsed().as_secs_f64()),
+        }
+    }
+
+    /// Append the current time to `out` without an intermediate `String`
+    pub fn write_now(&self, format: &str, out: &mut String) {
+        let mark = out.len();
+        let _ = match self {
+            // Zoned timestamps copy their offset name into a `String`, so try the
+            // naive time first and only fall back for formats using `%z`/`%Z`
+            TimestampClock::Utc => {
+                let now = chrono::Utc::now();
+                now.naive_utc().format(format).write_to(out).or_else(|_| {
+                    out.truncate(mark);
+                    now.format(format).write_to(out)
+                })
+            }
+            TimestampClock::Local => {
+                let now = chrono::Local::now();
+                now.naive_local().format(format).write_to(out).or_else(|_| {
+                    out.truncate(mark);
+                    now.format(format).write_to(out)
+                })
+            }
+            TimestampClock::Relative(start) => write!(out, "+{:.3}s", start.elapsed().as_secs_f64()),
+        };
+    }
+}
+
+/// Optional record metadata included in formatted lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LineDetails {
+    /// Include the record's target, e.g. `artifice_engine::events`
+    pub target: bool,
+    /// Include the `file:line` the record was logged from
+    pub source: bool,
+}
+
+impl LineDetails {
+    /// Write ` target file:line` for the enabled parts
+    fn write_text(&self, record: &Record, out: &mut String) {
+        if self.target {
+            out.push(' ');
+            out.push_str(record.target());
+        }
+        if self.source {
+            if let Some(file) = record.file() {
+                out.push(' ');
+                out.push_str(file);
+                if let Some(line) = record.line() {
+                    let _ = write!(out, ":{}", line);
+                }
+            }
+        }
+    }
+}
+
+/// Built-in formatter producing `[2024-06-01 13:45:00.123] INFO: message`
+///
+/// With `LineDetails` enabled the level is followed by the target and source
+/// location: `[..] INFO artifice_engine::events src/events/core.rs:120: message`.
+#[derive(Debug, Clone)]
+pub struct DefaultFormatter {
+    /// Wrap the level in ANSI color codes
+    pub colors: bool,
+    /// Colors used for each level when `colors` is set
+    pub theme: ColorTheme,
+    /// Target and source location shown after the level
+    pub details: LineDetails,
+    /// `chrono` format string used for the timestamp
+    pub timestamp_format: String,
+    /// Clock the timestamp is read from
+    pub clock: TimestampClock,
+}
+
+impl DefaultFormatter {
+    /// Create the default formatter with or without colored levels
+    pub fn new(colors: bool) -> Self {
+        Self {
+            colors,
+            theme: ColorTheme::default(),
+            details: LineDetails::default(),
+            timestamp_format: DEFAULT_TIMESTAMP_FORMAT.to_string(),
+            clock: TimestampClock::Utc,
+        }
+    }
+
+    /// Use a custom `chrono` timestamp format
+    pub fn with_timestamp_format<S: Into<String>>(mut self, format: S) -> Self {
+        self.timestamp_format = format.into();
+        self
+    }
+
+    /// Read timestamps from a different clock
+    pub fn with_clock(mut self, clock: TimestampClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Color levels with a custom theme
+    pub fn with_theme(mut self, theme: ColorTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Show the target and/or source location of each record
+    pub fn with_details(mut self, details: LineDetails) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+impl Default for DefaultFormatter {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl LogFormatter for DefaultFormatter {
+    fn format(&self, record: &Record) -> String {
+        let theme = self.colors.then_some(&self.theme);
+        format_default(record, theme, self.details, &self.timestamp_format, &self.clock)
+    }
+
+    fn format_into(&self, record: &Record, out: &mut String) {
+        let theme = self.colors.then_some(&self.theme);
+        write_default(out, record, theme, self.details, &self.timestamp_format, &self.clock);
+    }
+
+    fn inherit_theme(&mut self, theme: &ColorTheme) {
+        if self.theme == ColorTheme::default() {
+            self.theme = theme.clone();
+        }
+    }
+}
+
+/// Shared implementation of the default line layout
+///
+/// Structured fields and scoped context entries are appended as `key=value` pairs.
+pub(crate) fn format_default(
+    record: &Record,
+    theme: Option<&ColorTheme>,
+    details: LineDetails,
+    timestamp_format: &str,
+    clock: &TimestampClock,
+) -> String {
+    let mut line = String::new();
+    write_default(&mut line, record, theme, details, timestamp_format, clock);
+    line
+}
+
+/// Append the default line layout to `line` without allocating
+///
+/// Only records carrying structured fields need a temporary allocation.
+pub(crate) fn write_default(
+    line: &mut String,
+    record: &Record,
+    theme: Option<&ColorTheme>,
+    details: LineDetails,
+    timestamp_format: &str,
+    clock: &TimestampClock,
+) {
+    let level = LogLevel::from(record.level());
+
+    line.push('[');
+    clock.write_now(timestamp_format, line);
+    // Colors that are not plain SGR parameters are left out rather than sent to the terminal
+    let _ = match theme.and_then(|theme| theme.valid_color(level)) {
+        Some(sgr) => write!(line, "] \x1b[{}m{}\x1b[0m", sgr, level.as_str()),
+        None => write!(line, "] {}", level.as_str()),
+    };
+    details.write_text(record, line);
+    let _ = write!(line, ": {}", record.args());
+
+    let mut push_pair = |key: &str, value: &FieldValue| match value {
+        FieldValue::Str(text) if text.contains(char::is_whitespace) => {
+            let _ = write!(line, " {}={:?}", key, text);
+        }
+        value => {
+            let _ = write!(line, " {}={}", key, value);
+        }
+    };
+
+    if record.key_values().count() > 0 {
+        for (key, value) in record_fields(record) {
+            push_pair(&key, &value);
+        }
+    }
+    context::with_context(|entries| {
+        for (key, value) in entries {
+            push_pair(key, value);
+        }
+    });
+}
+
+/// Formatter producing one JSON object per line
+///
+/// Structured fields attached with the `log` key-value syntax
+/// (`info!(user_id = 42, frame = n; "Player joined")`) are kept as typed
+/// values under `"fields"`, and entries from `push_context` under `"context"`.
+#[derive(Debug, Clone)]
+pub struct JsonFormatter {
+    /// `chrono` format string used for the UTC timestamp
+    pub timestamp_format: String,
+    /// Emit `"target"`, and `"module"`/`"file"`/`"line"` for the source location
+    pub details: LineDetails,
+}
+
+impl JsonFormatter {
+    /// Create a JSON formatter with RFC 3339 timestamps and the target included
+    pub fn new() -> Self {
+        Self {
+            timestamp_format: "%Y-%m-%dT%H:%M:%S%.3fZ".to_string(),
+            details: LineDetails { target: true, source: false },
+        }
+    }
+
+    /// Choose which record metadata is emitted
+    pub fn with_details(mut self, details: LineDetails) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+impl Default for JsonFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogFormatter for JsonFormatter {
+    fn format(&self, record: &Record) -> String {
+        let mut line = String::with_capacity(128);
+        line.push_str("{\"timestamp\":");
+        push_json_str(&mut line, &chrono::Utc::now().format(&self.timestamp_format).to_string());
+        line.push_str(",\"level\":");
+        push_json_str(&mut line, LogLevel::from(record.level()).as_str());
+        if self.details.target {
+            line.push_str(",\"target\":");
+            push_json_str(&mut line, record.target());
+        }
+        if self.details.source {
+            if let Some(module) = record.module_path() {
+                line.push_str(",\"module\":");
+                push_json_str(&mut line, module);
+            }
+            if let Some(file) = record.file() {
+                line.push_str(",\"file\":");
+                push_json_str(&mut line, file);
+            }
+            if let Some(number) = record.line() {
+                let _ = write!(line, ",\"line\":{}", number);
+            }
+        }
+        line.push_str(",\"message\":");
+        push_json_str(&mut line, &record.args().to_string());
+
+        push_json_object(&mut line, "fields", &record_fields(record));
+        context::with_context(|entries| push_json_object(&mut line, "context", entries));
+
+        line.push('}');
+        line
+    }
+}
+
+fn push_json_object(out: &mut String, name: &str, entries: &[(String, FieldValue)]) {
+    if entries.is_empty() {
+        return;
+    }
+
+    out.push(',');
+    push_json_str(out, name);
+    out.push_str(":{");
+    for (i, (key, value)) in entries.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        push_json_str(out, key);
+        out.push(':');
+        match value {
+            FieldValue::Str(text) => push_json_str(out, text),
+            FieldValue::F64(number) if !number.is_finite() => out.push_str("null"),
+            other => {
+                let _ = write!(out, "{}", other);
+            }
+        }
+    }
+    out.push('}');
+}
+
+fn push_json_str(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// A structured field value captured from a record
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    /// Boolean value
+    Bool(bool),
+    /// Signed integer value
+    I64(i64),
+    /// Unsigned integer too large for `I64`
+    U64(u64),
+    /// Floating point value
+    F64(f64),
+    /// Any other value, rendered through `Display`
+    Str(String),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Bool(value) => write!(f, "{}", value),
+            FieldValue::I64(value) => write!(f, "{}", value),
+            FieldValue::U64(value) => write!(f, "{}", value),
+            FieldValue::F64(value) => write!(f, "{}", value),
+            FieldValue::Str(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+macro_rules! field_value_from {
+    ($($ty:ty => $variant:ident as $target:ty),* $(,)?) => {
+        $(
+            impl From<$ty> for FieldValue {
+                fn from(value: $ty) -> Self {
+                    FieldValue::$variant(value as $target)
+                }
+            }
+        )*
+    };
+}
+
+field_value_from! {
+    i8 => I64 as i64,
+    i16 => I64 as i64,
+    i32 => I64 as i64,
+    i64 => I64 as i64,
+    isize => I64 as i64,
+    u8 => U64 as u64,
+    u16 => U64 as u64,
+    u32 => U64 as u64,
+    u64 => U64 as u64,
+    usize => U64 as u64,
+    f32 => F64 as f64,
+    f64 => F64 as f64,
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Bool(value)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        FieldValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        FieldValue::Str(value)
+    }
+}
+
+impl From<kv::Value<'_>> for FieldValue {
+    fn from(value: kv::Value<'_>) -> Self {
+        if let Some(value) = value.to_bool() {
+            FieldValue::Bool(value)
+        } else if let Some(value) = value.to_i64() {
+            FieldValue::I64(value)
+        } else if let Some(value) = value.to_u64() {
+            FieldValue::U64(value)
+        } else if let Some(value) = value.to_f64() {
+            FieldValue::F64(value)
+        } else {
+            FieldValue::Str(value.to_string())
+        }
+    }
+}
+
+/// Collect the structured key-value fields attached to a record
+pub fn record_fields(record: &Record) -> Vec<(String, FieldValue)> {
+    struct Collector(Vec<(String, FieldValue)>);
+
+    impl<'kvs> VisitSource<'kvs> for Collector {
+        fn visit_pair(&mut self, key: kv::Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+            self.0.push((key.as_str().to_string(), value.into()));
+            Ok(())
+        }
+    }
+
+    let mut collector = Collector(Vec::new());
+    let _ = record.key_values().visit(&mut collector);
+    collector.0
+}
+
+impl<F> LogFormatter for F
+where
+    F: Fn(&Record) -> String + Send + Sync,
+{
+    fn format(&self, record: &Record) -> String {
+        self(record)
+    }
+}
@@ -10,7 +10,8 @@
 //! use log::{info, warn, error};
 //!
 //! // Initialize with default settings
-//! init().expect("Failed to initialize logger");
+//! // Keep the guard alive so buffered output is flushed on exit
+//! let _guard = init().expect("Failed to initialize logger");
 //!
 //! // Log messages
 //! info!("Application started");
@@ -18,16 +19,36 @@
 //! error!("This is an error");
 //! ```
 //!
-//! ## Advanced Usage
+//! ## Structured Fields
+//!
+//! Key-value pairs use the `log` crate syntax and are rendered as `key=value`
+//! by the default formatter or as typed JSON by `JsonFormatter`:
+//!
+//! ```rust
+//! log::info!(user_id = 42, scene = "menu"; "Player joined");
+//! ```
+//!
+//! ## Scoped Context
+//!
+//! Entries pushed with `push_context` are appended to every record logged on
+//! the current thread until the returned guard is dropped:
 //!
 //! ```rust
-//! use artifice_logging::LoggerBuilder;
+//! let _frame = artifice_logging::push_context("frame", 1042u64);
+//! log::debug!("Culling pass done"); // ... frame=1042
+//! ```
 //!
-//! LoggerBuilder::new()
+//! ## Advanced Usage
+//!
+//! ```rust,no_run
+//! use artifice_logging::{LoggerBuilder, RotationPolicy};
+//!
+//! let _guard = LoggerBuilder::new()
 //!     .console(true)
 //!     .file("app.log")
 //!     .colors(true)
 //!     .batch_size(100)
+//!     .rotation(RotationPolicy::Daily)
 //!     .init()
 //!     .expect("Failed to initialize logger");
 //! ```
@@ -35,32 +56,74 @@
 use log::{Log, Metadata, Record};
 use std::fs::File;
 use std::sync::mpsc;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
 use std::thread;
-use std::time::Instant;
 
 // Module declarations
 pub mod config;
 pub mod batching;
 pub mod writers;
 pub mod benchmarks;
+pub mod format;
+pub mod sinks;
+pub mod filter;
+pub mod ratelimit;
+pub mod context;
+pub mod file_config;
+pub mod scope;
+pub mod crash;
+mod pool;
+#[cfg(feature = "tokio")]
+pub mod async_sink;
+#[cfg(all(unix, feature = "signals"))]
+pub mod signal;
 
 // Re-export public types
-pub use config::{LogConfig, BatchConfig, HighPerformanceConfig, LogLevel, LoggerError};
-pub use benchmarks::{LoggingBenchmarks, ThroughputMeter};
+pub use config::{LogConfig, BatchConfig, HighPerformanceConfig, LogLevel, LoggerError, RotationPolicy, RateLimitConfig, BackpressurePolicy, DiskQuota, QuotaAction, Durability, ColorChoice, ColorTheme, AdaptiveBatching, FileSharing};
+pub use benchmarks::{LatencyHistogram, LatencyPercentiles, LoggingBenchmarks, ThroughputMeter};
+pub use format::{LogFormatter, DefaultFormatter, JsonFormatter, FieldValue, TimestampClock, LineDetails};
+pub use sinks::{LogSink, ConsoleSink, FileSink, NetworkSink, RoutedFileSink, BatchSink, FormattedRecord, WriterSink};
+#[cfg(target_os = "linux")]
+pub use sinks::JournaldSink;
+pub use writers::{NetworkProtocol, DeliveryStats, LoggerStats, WriteErrorHandler};
+pub use filter::{EnvFilter, Directive};
+pub use ratelimit::{RateLimiter, RateLimitDecision};
+pub use context::{push_context, current_context, with_context, ContextGuard};
+pub use scope::ScopeTimer;
+pub use crash::CrashRing;
+pub use file_config::{LogFileConfig, SinkConfig, SinkKind, LineFormat, ConfigWatcher};
+#[cfg(feature = "tokio")]
+pub use async_sink::{AsyncSink, AsyncSinkHandle};
+#[cfg(all(unix, feature = "signals"))]
+pub use signal::SighupReopener;
 
 // Re-export log macros for convenience
 pub use log::{trace, debug, info, warn, error};
 
-use batching::{LogMessage, LogCommand, AdvancedLogCommand};
-use writers::{file_worker_thread, high_performance_worker_thread, FileWriter, HighPerformanceFileWriter};
+use batching::{LogMessage, AdvancedLogCommand};
+use writers::{high_performance_worker_thread, FileWriter, HighPerformanceFileWriter, WorkerHandle};
 
 /// Main logger implementation supporting both standard and high-performance modes
+///
+/// The console and file outputs described by `LogConfig` are always available;
+/// any number of extra `LogSink`s can be attached alongside them, each with
+/// its own formatter and level filter.
 pub struct ArtificeLogger {
     config: LogConfig,
     batch_config: BatchConfig,
-    file_sender: Option<mpsc::Sender<LogCommand>>,
-    _file_thread: Option<thread::JoinHandle<()>>,
+    rotation: RotationPolicy,
+    disk_quota: Option<DiskQuota>,
+    formatter: Option<Box<dyn LogFormatter>>,
+    timestamp_format: String,
+    clock: TimestampClock,
+    theme: ColorTheme,
+    details: LineDetails,
+    sinks: Vec<Box<dyn LogSink>>,
+    filter: RwLock<Option<EnvFilter>>,
+    rate_limiter: Option<RateLimiter>,
+    file_worker: Option<WorkerHandle>,
+    write_error: Option<WriteErrorHandler>,
+    crash_ring: Option<Arc<CrashRing>>,
 }
 
 impl ArtificeLogger {
@@ -69,23 +132,37 @@ impl ArtificeLogger {
         Self {
             config: LogConfig::default(),
             batch_config: BatchConfig::default(),
-            file_sender: None,
-            _file_thread: None,
+            rotation: RotationPolicy::Never,
+            disk_quota: None,
+            formatter: None,
+            timestamp_format: format::DEFAULT_TIMESTAMP_FORMAT.to_string(),
+            clock: TimestampClock::Utc,
+            theme: ColorTheme::default(),
+            details: LineDetails::default(),
+            sinks: Vec::new(),
+            filter: RwLock::new(None),
+            rate_limiter: None,
+            file_worker: None,
+            write_error: None,
+            crash_ring: None,
         }
     }
 
     /// Enable file logging with the specified path
+    ///
+    /// When a rotation policy is set, `path` is used as the base name for the
+    /// date-stamped files (`app.log` -> `app-2024-06-01.log`).
     pub fn with_file<P: AsRef<std::path::Path>>(mut self, path: P) -> Result<Self, LoggerError> {
-        let file = File::create(path)?;
-        let file_writer = FileWriter::new(file, self.batch_config.clone());
-        
-        let (sender, receiver) = mpsc::channel();
-        let thread_handle = thread::spawn(move || {
-            file_worker_thread(file_writer, receiver);
-        });
+        let mut file_writer = FileWriter::rotating(path, self.rotation, self.batch_config.clone())?;
+        if let Some(quota) = self.disk_quota {
+            file_writer = file_writer.with_quota(quota);
+        }
         
-        self.file_sender = Some(sender);
-        self._file_thread = Some(thread_handle);
+        let file_worker = WorkerHandle::spawn(file_writer)?;
+        if let Some(handler) = &self.write_error {
+            file_worker.on_write_error(Arc::clone(handler));
+        }
+        self.file_worker = Some(file_worker);
         self.config.file = true;
         
         Ok(self)
@@ -97,6 +174,175 @@ impl ArtificeLogger {
         self
     }
 
+    /// Set the time-based rotation policy (must be called before `with_file`)
+    pub fn with_rotation(mut self, policy: RotationPolicy) -> Self {
+        self.rotation = policy;
+        self
+    }
+
+    /// Limit the total size of the log files (must be called before `with_file`)
+    pub fn with_disk_quota(mut self, quota: DiskQuota) -> Self {
+        self.disk_quota = Some(quota);
+        self
+    }
+
+    /// Replace the built-in message format
+    pub fn with_formatter<F: LogFormatter + 'static>(mut self, formatter: F) -> Self {
+        self.formatter = Some(Box::new(formatter));
+        self
+    }
+
+    /// Only log records allowed by a per-target filter
+    pub fn with_filter(mut self, filter: EnvFilter) -> Self {
+        self.filter = RwLock::new(Some(filter));
+        self
+    }
+
+    /// Use a custom `chrono` format string for the built-in timestamp
+    pub fn with_timestamp_format<S: Into<String>>(mut self, format: S) -> Self {
+        self.timestamp_format = format.into();
+        self
+    }
+
+    /// Choose the clock the built-in timestamp is read from
+    pub fn with_clock(mut self, clock: TimestampClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Color levels with a custom theme
+    ///
+    /// Covers the built-in format and every sink whose formatter keeps the
+    /// default colors, including sinks attached later.
+    pub fn with_theme(mut self, theme: ColorTheme) -> Self {
+        for sink in &mut self.sinks {
+            sink.inherit_theme(&theme);
+        }
+        self.theme = theme;
+        self
+    }
+
+    /// Show the target and/or source location in the built-in format
+    pub fn with_details(mut self, details: LineDetails) -> Self {
+        self.details = details;
+        self
+    }
+
+    /// Cap how many records each callsite may emit per time window
+    pub fn with_rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limiter = Some(RateLimiter::new(config));
+        self
+    }
+
+    /// Call `handler` whenever a background write fails, e.g. on a full disk
+    ///
+    /// Covers the log file and every batching sink, including ones attached
+    /// later. Messages a failed file write leaves behind are printed to
+    /// standard error instead, and the file is tried again after a second.
+    /// The handler runs on the failing worker thread and may log through this
+    /// logger: while it runs, a full channel drops its messages instead of
+    /// blocking and `flush_sync` returns `LoggerError::ChannelError` instead
+    /// of waiting for the worker. Without a handler failures are printed to
+    /// standard error.
+    pub fn on_write_error<F: Fn(LoggerError) + Send + Sync + 'static>(mut self, handler: F) -> Self {
+        self.set_write_error(Arc::new(handler));
+        self
+    }
+
+    fn set_write_error(&mut self, handler: WriteErrorHandler) {
+        if let Some(worker) = &self.file_worker {
+            worker.on_write_error(Arc::clone(&handler));
+        }
+        for sink in &self.sinks {
+            sink.on_write_error(Arc::clone(&handler));
+        }
+        self.write_error = Some(handler);
+    }
+
+    /// Keep the last `messages` lines in memory and dump them to `path` on a crash
+    ///
+    /// The lines are written synchronously from a panic hook and, with the
+    /// `signals` feature, from fatal-signal handlers, so they reach the crash
+    /// file even when the writer threads are stuck. See `CrashRing`.
+    pub fn with_crash_log<P: Into<std::path::PathBuf>>(mut self, path: P, messages: usize) -> Self {
+        let ring = CrashRing::new(path, messages);
+        ring.install();
+        self.crash_ring = Some(ring);
+        self
+    }
+
+    /// Attach an additional output sink
+    pub fn with_sink<S: LogSink + 'static>(mut self, sink: S) -> Self {
+        self.add_sink(Box::new(sink));
+        self
+    }
+
+    /// Attach an additional output sink to an existing logger
+    pub fn add_sink(&mut self, mut sink: Box<dyn LogSink>) {
+        if let Some(handler) = &self.write_error {
+            sink.on_write_error(Arc::clone(handler));
+        }
+        sink.inherit_theme(&self.theme);
+        self.sinks.push(sink);
+    }
+
+    /// Number of attached extra sinks
+    pub fn sink_count(&self) -> usize {
+        self.sinks.len()
+    }
+
+    /// Change the level of the sink at `index` (in the order sinks were attached)
+    ///
+    /// Returns `false` if there is no such sink.
+    pub fn set_sink_level(&self, index: usize, level: LogLevel) -> bool {
+        match self.sinks.get(index) {
+            Some(sink) => {
+                sink.set_level(level);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Current per-target filter, if any
+    pub fn filter(&self) -> Option<EnvFilter> {
+        self.filter.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Replace the per-target filter while the logger is in use
+    pub fn set_filter(&self, filter: Option<EnvFilter>) {
+        *self.filter.write().unwrap_or_else(PoisonError::into_inner) = filter;
+    }
+
+    /// Messages discarded by the backpressure policy or disk quotas across all file and network outputs
+    pub fn dropped_messages(&self) -> u64 {
+        let primary = self.file_worker.as_ref().map_or(0, WorkerHandle::dropped);
+        primary + self.sinks.iter().map(|sink| sink.dropped_messages()).sum::<u64>()
+    }
+
+    /// Combined pipeline counters of the primary file worker and all batching sinks
+    pub fn stats(&self) -> LoggerStats {
+        self.file_worker
+            .iter()
+            .map(WorkerHandle::stats)
+            .chain(self.sinks.iter().filter_map(|sink| sink.stats()))
+            .fold(LoggerStats::default(), LoggerStats::merge)
+    }
+
+    /// Combined delivery statistics of all network sinks
+    pub fn delivery_stats(&self) -> DeliveryStats {
+        self.sinks
+            .iter()
+            .filter_map(|sink| sink.delivery_stats())
+            .fold(DeliveryStats::default(), |total, stats| DeliveryStats {
+                sent_messages: total.sent_messages + stats.sent_messages,
+                sent_batches: total.sent_batches + stats.sent_batches,
+                dropped_messages: total.dropped_messages + stats.dropped_messages,
+                failed_sends: total.failed_sends + stats.failed_sends,
+                reconnects: total.reconnects + stats.reconnects,
+            })
+    }
+
     /// Update logger configuration
     pub fn set_config(&mut self, config: LogConfig) {
         self.config = config;
@@ -112,48 +358,136 @@ impl ArtificeLogger {
         &self.batch_config
     }
 
-    fn format_message(&self, record: &Record) -> String {
-        let level_str = if self.config.colors {
-            LogLevel::from(record.level()).as_colored_str()
-        } else {
-            LogLevel::from(record.level()).as_str()
-        };
+    fn format_into(&self, record: &Record, out: &mut String) {
+        match &self.formatter {
+            Some(formatter) => formatter.format_into(record, out),
+            None => {
+                let theme = self.config.colors.then_some(&self.theme);
+                format::write_default(out, record, theme, self.details, &self.timestamp_format, &self.clock);
+            }
+        }
+    }
+
+    /// Send a record that passed filtering to every enabled output
+    fn write_record(&self, record: &Record) {
+        let to_file = self.config.file && self.file_worker.is_some();
+
+        if self.config.console || to_file || self.crash_ring.is_some() {
+            let mut formatted = pool::take();
+            self.format_into(record, &mut formatted);
+
+            if let Some(ring) = &self.crash_ring {
+                ring.record(&formatted);
+            }
+
+            if self.config.console {
+                println!("{}", formatted);
+            }
 
-        format!("[{}] {}: {}", 
-                chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f"),
-                level_str,
-                record.args())
+            match &self.file_worker {
+                Some(worker) if to_file => worker.send(LogMessage::from_record(formatted, record)),
+                _ => pool::put(formatted),
+            }
+        }
+
+        for sink in &self.sinks {
+            if sink.enabled(record.metadata()) {
+                sink.log(record);
+            }
+        }
     }
 
     /// Force flush all pending log messages
     pub fn flush(&self) {
-        if let Some(sender) = &self.file_sender {
-            let _ = sender.send(LogCommand::Flush);
+        if let Some(worker) = &self.file_worker {
+            worker.flush();
+        }
+
+        for sink in &self.sinks {
+            sink.flush();
+        }
+    }
+
+    /// Flush all pending log messages and block until they are written and fsynced
+    pub fn flush_sync(&self) -> Result<(), LoggerError> {
+        if let Some(worker) = &self.file_worker {
+            worker.flush_sync()?;
+        }
+
+        for sink in &self.sinks {
+            sink.flush_sync()?;
+        }
+
+        Ok(())
+    }
+
+    /// Reopen every log file by path, keeping buffered messages
+    ///
+    /// Call this after an external tool such as logrotate has renamed the
+    /// files; later messages go to fresh files at the configured paths.
+    /// Every output is reopened even if one fails; the first error is returned.
+    pub fn reopen(&self) -> Result<(), LoggerError> {
+        let primary = self.file_worker.as_ref().map_or(Ok(()), WorkerHandle::reopen);
+        self.sinks.iter().map(|sink| sink.reopen()).fold(primary, Result::and)
+    }
+
+    /// Write out all buffered messages and stop the writer threads
+    ///
+    /// Blocks until every worker has finished; messages logged afterwards
+    /// only reach the console.
+    pub fn shutdown(&self) {
+        if let Some(worker) = &self.file_worker {
+            worker.shutdown();
+        }
+
+        for sink in &self.sinks {
+            sink.shutdown();
         }
     }
 }
 
+impl Default for ArtificeLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Log for ArtificeLogger {
-    fn enabled(&self, _metadata: &Metadata) -> bool {
-        true
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.filter
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .is_none_or(|filter| filter.enabled(metadata))
     }
 
     fn log(&self, record: &Record) {
-        let formatted = self.format_message(record);
-
-        if self.config.console {
-            println!("{}", formatted);
+        if !self.enabled(record.metadata()) {
+            return;
         }
 
-        if self.config.file {
-            if let Some(sender) = &self.file_sender {
-                let message = LogMessage {
-                    formatted_message: formatted,
-                    timestamp: Instant::now(),
-                };
-                let _ = sender.send(LogCommand::Message(message));
+        if let Some(limiter) = &self.rate_limiter {
+            match limiter.check(record) {
+                RateLimitDecision::Allow => {}
+                RateLimitDecision::Suppress => return,
+                RateLimitDecision::AllowAfterSuppressed(count) => {
+                    let location = match (record.file(), record.line()) {
+                        (Some(file), Some(line)) => format!("{}:{}", file, line),
+                        _ => record.target().to_string(),
+                    };
+                    self.write_record(&Record::builder()
+                        .args(format_args!("suppressed {} messages from {}", count, location))
+                        .level(record.level())
+                        .target(record.target())
+                        .module_path(record.module_path())
+                        .file(record.file())
+                        .line(record.line())
+                        .build());
+                }
             }
         }
+
+        self.write_record(record);
     }
 
     fn flush(&self) {
@@ -161,28 +495,146 @@ impl Log for ArtificeLogger {
     }
 }
 
-impl Drop for ArtificeLogger {
+// Global logger instance
+static LOGGER: Mutex<Option<ArtificeLogger>> = Mutex::new(None);
+
+/// Keeps the global logger's writer threads alive
+///
+/// Dropping the guard flushes everything still buffered and joins the writer
+/// threads, so hold it until the end of `main`.
+#[must_use = "dropping the guard immediately shuts down file output"]
+pub struct LoggerGuard {
+    logger: &'static ArtificeLogger,
+}
+
+impl LoggerGuard {
+    /// Access the installed global logger
+    pub fn logger(&self) -> &'static ArtificeLogger {
+        self.logger
+    }
+
+    /// Get a handle for changing levels while the application runs
+    pub fn handle(&self) -> LoggerHandle {
+        LoggerHandle { logger: self.logger }
+    }
+}
+
+impl Drop for LoggerGuard {
     fn drop(&mut self) {
-        if let Some(sender) = &self.file_sender {
-            let _ = sender.send(LogCommand::Shutdown);
+        self.logger.shutdown();
+    }
+}
+
+/// Runtime control over the levels of the installed logger
+///
+/// The handle is `Copy`, so it can be stored wherever it is needed, e.g. in a
+/// debug-key handler:
+///
+/// ```rust
+/// use artifice_logging::{LoggerBuilder, LogLevel};
+///
+/// let guard = LoggerBuilder::new().console(false).init().unwrap();
+/// let handle = guard.handle();
+///
+/// handle.set_target_level("artifice_engine::events", LogLevel::Trace);
+/// handle.set_level(LogLevel::Warn);
+/// ```
+///
+/// Filter changes also update the global `log` max level so the new levels take
+/// effect immediately. Sink levels cannot let through more than the global level.
+#[derive(Clone, Copy)]
+pub struct LoggerHandle {
+    logger: &'static ArtificeLogger,
+}
+
+impl LoggerHandle {
+    /// Set the level used for targets without a more specific directive
+    pub fn set_level(&self, level: LogLevel) {
+        self.update_filter(|filter| match filter {
+            Some(filter) => filter.add_directive(Directive { target: None, level: level.into() }),
+            None => log::set_max_level(level.into()),
+        });
+    }
+
+    /// Set the level for a target prefix, e.g. `"artifice_engine::events"`
+    pub fn set_target_level<T: Into<String>>(&self, target: T, level: LogLevel) {
+        self.update_filter(|filter| {
+            let filter = filter.get_or_insert_with(|| {
+                let mut filter = EnvFilter::new();
+                filter.add_directive(Directive { target: None, level: log::max_level() });
+                filter
+            });
+            filter.add_directive(Directive { target: Some(target.into()), level: level.into() });
+        });
+    }
+
+    /// Replace the whole per-target filter
+    pub fn set_filter(&self, filter: EnvFilter) {
+        self.update_filter(|current| *current = Some(filter));
+    }
+
+    /// Replace the per-target filter with a `RUST_LOG`-style spec
+    pub fn parse_filters(&self, spec: &str) {
+        self.set_filter(EnvFilter::parse(spec));
+    }
+
+    /// Remove the per-target filter, letting every level through
+    pub fn clear_filter(&self) {
+        self.update_filter(|current| *current = None);
+        log::set_max_level(log::LevelFilter::Trace);
+    }
+
+    /// Current per-target filter, if any
+    pub fn filter(&self) -> Option<EnvFilter> {
+        self.logger.filter()
+    }
+
+    /// Change the level of an attached sink; see `ArtificeLogger::set_sink_level`
+    pub fn set_sink_level(&self, index: usize, level: LogLevel) -> bool {
+        self.logger.set_sink_level(index, level)
+    }
+
+    /// Number of attached extra sinks
+    pub fn sink_count(&self) -> usize {
+        self.logger.sink_count()
+    }
+
+    /// Reopen the log files by path; see `ArtificeLogger::reopen`
+    pub fn reopen(&self) -> Result<(), LoggerError> {
+        self.logger.reopen()
+    }
+
+    /// Snapshot of the logging pipeline's counters; see `ArtificeLogger::stats`
+    pub fn stats(&self) -> LoggerStats {
+        self.logger.stats()
+    }
+
+    fn update_filter(&self, update: impl FnOnce(&mut Option<EnvFilter>)) {
+        let mut filter = self.logger.filter.write().unwrap_or_else(PoisonError::into_inner);
+        update(&mut filter);
+        if let Some(filter) = filter.as_ref() {
+            log::set_max_level(filter.max_level());
         }
     }
 }
 
-// Global logger instance
-static LOGGER: Mutex<Option<ArtificeLogger>> = Mutex::new(None);
+/// Install `logger` as the global `log` backend
+fn install(logger: ArtificeLogger, max_level: log::LevelFilter) -> Result<LoggerGuard, LoggerError> {
+    let logger: &'static ArtificeLogger = Box::leak(Box::new(logger));
+    log::set_logger(logger)?;
+    log::set_max_level(max_level);
+    Ok(LoggerGuard { logger })
+}
 
 /// Initialize logger with custom configuration
-pub fn init_with_config(config: LogConfig) -> Result<(), LoggerError> {
+pub fn init_with_config(config: LogConfig) -> Result<LoggerGuard, LoggerError> {
     let mut logger = ArtificeLogger::new();
     logger.set_config(config);
-    log::set_logger(Box::leak(Box::new(logger)))?;
-    log::set_max_level(log::LevelFilter::Trace);
-    Ok(())
+    install(logger, log::LevelFilter::Trace)
 }
 
 /// Initialize logger with default settings
-pub fn init() -> Result<(), LoggerError> {
+pub fn init() -> Result<LoggerGuard, LoggerError> {
     init_with_config(LogConfig::default())
 }
 
@@ -190,13 +642,11 @@ pub fn init() -> Result<(), LoggerError> {
 pub fn init_with_file<P: AsRef<std::path::Path>>(
     path: P, 
     _config: LogConfig
-) -> Result<(), LoggerError> {
+) -> Result<LoggerGuard, LoggerError> {
     let logger = ArtificeLogger::new()
         .with_file(path)?;
     
-    log::set_logger(Box::leak(Box::new(logger)))?;
-    log::set_max_level(log::LevelFilter::Trace);
-    Ok(())
+    install(logger, log::LevelFilter::Trace)
 }
 
 /// Initialize logger with file output and custom batching
@@ -204,14 +654,12 @@ pub fn init_with_file_and_batching<P: AsRef<std::path::Path>>(
     path: P,
     _config: LogConfig,
     batch_config: BatchConfig,
-) -> Result<(), LoggerError> {
+) -> Result<LoggerGuard, LoggerError> {
     let logger = ArtificeLogger::new()
         .with_batch_config(batch_config)
         .with_file(path)?;
     
-    log::set_logger(Box::leak(Box::new(logger)))?;
-    log::set_max_level(log::LevelFilter::Trace);
-    Ok(())
+    install(logger, log::LevelFilter::Trace)
 }
 
 /// Set the global log level
@@ -234,7 +682,10 @@ pub fn flush() {
 }
 
 /// Initialize logger from environment variables
-pub fn init_from_env() -> Result<(), LoggerError> {
+///
+/// Besides the `ARTIFICE_LOG_*` variables, `RUST_LOG` is honored using the
+/// standard `info,artifice_engine::events=trace,glfw=off` directive syntax.
+pub fn init_from_env() -> Result<LoggerGuard, LoggerError> {
     let mut config = LogConfig::default();
     let mut batch_config = BatchConfig::default();
     let mut file_path: Option<String> = None;
@@ -244,8 +695,9 @@ pub fn init_from_env() -> Result<(), LoggerError> {
         config.console = console.parse().unwrap_or(true);
     }
 
+    // Anything but `true`/`false` (e.g. `auto`) falls back to detection
     if let Ok(colors) = std::env::var("ARTIFICE_LOG_COLORS") {
-        config.colors = colors.parse().unwrap_or(true);
+        config.colors = colors.parse().unwrap_or_else(|_| ColorChoice::Auto.enabled());
     }
 
     if let Ok(path) = std::env::var("ARTIFICE_LOG_FILE") {
@@ -269,18 +721,54 @@ pub fn init_from_env() -> Result<(), LoggerError> {
         batch_config.enabled = batching.parse().unwrap_or(true);
     }
 
-    // Initialize logger
-    match file_path {
-        Some(path) => init_with_file_and_batching(path, config, batch_config),
-        None => init_with_config(config),
-    }
+    let mut builder = LoggerBuilder::new().batch_config(batch_config);
+    builder.config = config;
+    builder.file_path = file_path;
+    builder.filter = EnvFilter::from_env();
+
+    builder.init()
+}
+
+/// Initialize logger from a TOML config file such as `logging.toml`
+///
+/// See `LogFileConfig` for the file layout. Use `init_from_file_watched` to
+/// pick up level changes without restarting.
+pub fn init_from_file<P: AsRef<std::path::Path>>(path: P) -> Result<LoggerGuard, LoggerError> {
+    LogFileConfig::load(path)?.to_builder()?.init()
+}
+
+/// Initialize logger from a TOML config file and reload its levels when it changes
+///
+/// The file is checked every `interval`; keep the returned watcher alive as
+/// long as reloading should happen.
+pub fn init_from_file_watched<P: AsRef<std::path::Path>>(
+    path: P,
+    interval: std::time::Duration,
+) -> Result<(LoggerGuard, ConfigWatcher), LoggerError> {
+    let guard = init_from_file(&path)?;
+    let watcher = ConfigWatcher::spawn(guard.handle(), path.as_ref(), interval);
+    Ok((guard, watcher))
 }
 
 /// Builder pattern for logger configuration
 pub struct LoggerBuilder {
     config: LogConfig,
     batch_config: BatchConfig,
+    rotation: RotationPolicy,
+    disk_quota: Option<DiskQuota>,
+    formatter: Option<Box<dyn LogFormatter>>,
+    timestamp_format: Option<String>,
+    clock: TimestampClock,
+    theme: ColorTheme,
+    details: LineDetails,
+    sinks: Vec<Box<dyn LogSink>>,
+    filter: Option<EnvFilter>,
+    rate_limit: Option<RateLimitConfig>,
     file_path: Option<String>,
+    journald: bool,
+    write_error: Option<WriteErrorHandler>,
+    crash_log: Option<(std::path::PathBuf, usize)>,
+    writers: Vec<Box<dyn BatchSink>>,
 }
 
 impl LoggerBuilder {
@@ -289,10 +777,30 @@ impl LoggerBuilder {
         Self {
             config: LogConfig::default(),
             batch_config: BatchConfig::default(),
+            rotation: RotationPolicy::Never,
+            disk_quota: None,
+            formatter: None,
+            timestamp_format: None,
+            clock: TimestampClock::Utc,
+            theme: ColorTheme::default(),
+            details: LineDetails::default(),
+            sinks: Vec::new(),
+            filter: None,
+            rate_limit: None,
             file_path: None,
+            journald: false,
+            write_error: None,
+            crash_log: None,
+            writers: Vec::new(),
         }
     }
 
+    /// Replace the console/file/colors settings at once
+    pub fn config(mut self, config: LogConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     /// Enable/disable console output
     pub fn console(mut self, enabled: bool) -> Self {
         self.config.console = enabled;
@@ -307,11 +815,41 @@ impl LoggerBuilder {
     }
 
     /// Enable/disable colored output
+    ///
+    /// By default colors are used only when stdout is a terminal and
+    /// `NO_COLOR` is unset; see `ColorChoice`.
     pub fn colors(mut self, enabled: bool) -> Self {
         self.config.colors = enabled;
         self
     }
 
+    /// Decide whether to color output, e.g. `ColorChoice::Auto` to re-check the terminal
+    pub fn color_choice(mut self, choice: ColorChoice) -> Self {
+        self.config.colors = choice.enabled();
+        self
+    }
+
+    /// Per-level ANSI colors used when colored output is enabled
+    ///
+    /// Also colors sinks whose formatter keeps the default colors; `build`
+    /// rejects colors that are not SGR parameters.
+    pub fn theme(mut self, theme: ColorTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Include each record's target after the level in the built-in format
+    pub fn show_target(mut self, enabled: bool) -> Self {
+        self.details.target = enabled;
+        self
+    }
+
+    /// Include the `file:line` each record was logged from in the built-in format
+    pub fn show_source(mut self, enabled: bool) -> Self {
+        self.details.source = enabled;
+        self
+    }
+
     /// Set batch size
     pub fn batch_size(mut self, size: usize) -> Self {
         self.batch_config.batch_size = size;
@@ -330,83 +868,340 @@ impl LoggerBuilder {
         self
     }
 
+    /// Call `handler` when a background write fails; see `ArtificeLogger::on_write_error`
+    pub fn on_write_error<F: Fn(LoggerError) + Send + Sync + 'static>(mut self, handler: F) -> Self {
+        self.write_error = Some(Arc::new(handler));
+        self
+    }
+
+    /// Dump the last `messages` lines to `path` on a panic or fatal signal; see `ArtificeLogger::with_crash_log`
+    pub fn crash_log<P: Into<std::path::PathBuf>>(mut self, path: P, messages: usize) -> Self {
+        self.crash_log = Some((path.into(), messages));
+        self
+    }
+
+    /// Let the file worker scale batch size and flush interval with the load
+    pub fn adaptive_batching(mut self, bounds: AdaptiveBatching) -> Self {
+        self.batch_config.adaptive = Some(bounds);
+        self
+    }
+
     /// Set custom batch configuration
     pub fn batch_config(mut self, config: BatchConfig) -> Self {
         self.batch_config = config;
         self
     }
 
-    /// Initialize the logger with the configured settings
-    pub fn init(self) -> Result<(), LoggerError> {
-        match self.file_path {
-            Some(path) => init_with_file_and_batching(path, self.config, self.batch_config),
-            None => init_with_config(self.config),
-        }
+    /// Share the log file with other processes; see `FileSharing`
+    pub fn file_sharing(mut self, sharing: FileSharing) -> Self {
+        self.batch_config.sharing = sharing;
+        self
     }
-}
 
-impl Default for LoggerBuilder {
-    fn default() -> Self {
-        Self::new()
+    /// Roll the log file over on a time schedule
+    pub fn rotation(mut self, policy: RotationPolicy) -> Self {
+        self.rotation = policy;
+        self
     }
-}
-
-/// High-performance logger for maximum throughput scenarios
-pub struct HighPerformanceLogger {
-    sender: mpsc::Sender<AdvancedLogCommand>,
-    _thread: thread::JoinHandle<()>,
-}
 
-impl HighPerformanceLogger {
-    /// Create a new high-performance logger
-    pub fn new<P: AsRef<std::path::Path>>(
-        path: P, 
-        config: HighPerformanceConfig
-    ) -> Result<Self, LoggerError> {
-        let file = File::create(path)?;
-        let writer = HighPerformanceFileWriter::new(file, config);
-        
-        let (sender, receiver) = mpsc::channel();
-        let thread_handle = thread::spawn(move || {
-            high_performance_worker_thread(writer, receiver);
-        });
-        
-        Ok(Self {
-            sender,
-            _thread: thread_handle,
-        })
+    /// Limit the total size of the log file and its rotated siblings
+    pub fn disk_quota(mut self, quota: DiskQuota) -> Self {
+        self.disk_quota = Some(quota);
+        self
     }
-    
-    /// Log a pre-formatted message for maximum performance
-    pub fn log_fast(&self, message: String) -> Result<(), LoggerError> {
-        self.sender.send(AdvancedLogCommand::Message(message))
-            .map_err(|_| LoggerError::ChannelError)
+
+    /// Use a custom formatter for all output
+    pub fn formatter<F: LogFormatter + 'static>(mut self, formatter: F) -> Self {
+        self.formatter = Some(Box::new(formatter));
+        self
     }
-    
-    /// Force flush all pending messages
-    pub fn flush(&self) -> Result<(), LoggerError> {
-        self.sender.send(AdvancedLogCommand::Flush)
-            .map_err(|_| LoggerError::ChannelError)
+
+    /// Filter records per target
+    pub fn filter(mut self, filter: EnvFilter) -> Self {
+        self.filter = Some(filter);
+        self
     }
-}
 
-impl Drop for HighPerformanceLogger {
-    fn drop(&mut self) {
-        let _ = self.sender.send(AdvancedLogCommand::Shutdown);
+    /// Filter records using a `RUST_LOG`-style spec
+    pub fn parse_filters(self, spec: &str) -> Self {
+        self.filter(EnvFilter::parse(spec))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::writers::HighPerformanceFileWriter;
-    use log::Log;
-    use std::fs;
-    use std::io::Read;
-    use std::sync::{Arc, Barrier, Once};
-    use std::thread;
-    use std::time::{Duration, Instant};
+    /// Limit how often the same callsite can log
+    pub fn rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limit = Some(config);
+        self
+    }
 
+    /// Attach an additional output sink with its own formatter and filter
+    pub fn sink<S: LogSink + 'static>(mut self, sink: S) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Attach a user-defined output, batched like the log file
+    ///
+    /// The writer gets its own worker thread using the final batch
+    /// configuration; wrap it in a `WriterSink` yourself to pick a different
+    /// one, a formatter or a level.
+    pub fn writer<W: BatchSink + 'static>(mut self, writer: W) -> Self {
+        self.writers.push(Box::new(writer));
+        self
+    }
+
+    /// Also send records to systemd-journald (Linux only)
+    ///
+    /// On other platforms `build` fails with an `Unsupported` IO error.
+    pub fn journald(mut self, enabled: bool) -> Self {
+        self.journald = enabled;
+        self
+    }
+
+    /// Set the `chrono` format string of the built-in timestamp
+    pub fn timestamp_format<S: Into<String>>(mut self, format: S) -> Self {
+        self.timestamp_format = Some(format.into());
+        self
+    }
+
+    /// Stamp lines with local time instead of UTC
+    pub fn local_time(mut self, enabled: bool) -> Self {
+        self.clock = if enabled { TimestampClock::Local } else { TimestampClock::Utc };
+        self
+    }
+
+    /// Stamp lines with the monotonic time elapsed since the logger was built
+    pub fn relative_timestamps(mut self, enabled: bool) -> Self {
+        self.clock = if enabled { TimestampClock::relative() } else { TimestampClock::Utc };
+        self
+    }
+
+    /// Check the settings for contradictions before anything is opened
+    ///
+    /// Called by `build` and `init`; use it directly to report configuration
+    /// problems early, e.g. while loading settings.
+    pub fn validate(&self) -> Result<(), LoggerError> {
+        self.theme.validate()?;
+
+        let batch = &self.batch_config;
+        if batch.enabled && batch.batch_size == 0 {
+            return Err(LoggerError::InvalidBatchSize {
+                batch_size: 0,
+                reason: "batching is enabled but batches hold no messages",
+            });
+        }
+        if let Some(adaptive) = &batch.adaptive {
+            adaptive.validate()?;
+        }
+        if batch.channel_capacity == Some(0) {
+            return Err(LoggerError::InvalidBatchSize {
+                batch_size: batch.batch_size,
+                reason: "the bounded channel has a capacity of 0",
+            });
+        }
+
+        match (&self.file_path, self.config.file) {
+            (Some(path), false) => {
+                return Err(LoggerError::ConflictingSinks(format!(
+                    "file path {:?} is set but file output is disabled",
+                    path
+                )))
+            }
+            (None, true) => {
+                return Err(LoggerError::ConflictingSinks(
+                    "file output is enabled but no file path is set".to_string(),
+                ))
+            }
+            _ => {}
+        }
+
+        if let Some(path) = &self.file_path {
+            check_writable(std::path::Path::new(path))?;
+        }
+
+        Ok(())
+    }
+
+    /// Build the logger without installing it globally
+    pub fn build(self) -> Result<ArtificeLogger, LoggerError> {
+        self.validate()?;
+        let mut logger = ArtificeLogger::new()
+            .with_batch_config(self.batch_config)
+            .with_rotation(self.rotation);
+        logger.disk_quota = self.disk_quota;
+        logger.formatter = self.formatter;
+        if let Some(format) = self.timestamp_format {
+            logger.timestamp_format = format;
+        }
+        logger.clock = match self.clock {
+            TimestampClock::Relative(_) => TimestampClock::relative(),
+            clock => clock,
+        };
+        logger.theme = self.theme;
+        logger.details = self.details;
+        for sink in self.sinks {
+            logger.add_sink(sink);
+        }
+        for writer in self.writers {
+            let sink = WriterSink::new(writer, logger.batch_config.clone())?;
+            logger.add_sink(Box::new(sink));
+        }
+        logger.filter = RwLock::new(self.filter);
+        logger.rate_limiter = self.rate_limit.map(RateLimiter::new);
+
+        if self.journald {
+            #[cfg(target_os = "linux")]
+            logger.add_sink(Box::new(JournaldSink::new()?));
+            #[cfg(not(target_os = "linux"))]
+            return Err(LoggerError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "journald output is only available on Linux",
+            )));
+        }
+
+        if let Some(path) = self.file_path {
+            logger = logger.with_file(path)?;
+        }
+        if let Some(handler) = self.write_error {
+            logger.set_write_error(handler);
+        }
+        if let Some((path, messages)) = self.crash_log {
+            logger = logger.with_crash_log(path, messages);
+        }
+        logger.set_config(self.config);
+
+        Ok(logger)
+    }
+
+    /// Initialize the logger with the configured settings
+    ///
+    /// Keep the returned guard alive; dropping it flushes and stops file output.
+    pub fn init(self) -> Result<LoggerGuard, LoggerError> {
+        let max_level = self
+            .filter
+            .as_ref()
+            .map_or(log::LevelFilter::Trace, EnvFilter::max_level);
+        install(self.build()?, max_level)
+    }
+}
+
+/// Check that `path` can be opened for writing without creating it
+fn check_writable(path: &std::path::Path) -> Result<(), LoggerError> {
+    let not_writable = |reason: String| LoggerError::PathNotWritable {
+        path: path.to_path_buf(),
+        reason,
+    };
+
+    if let Ok(metadata) = std::fs::metadata(path) {
+        if metadata.is_dir() {
+            return Err(not_writable("it is a directory".to_string()));
+        }
+        if metadata.permissions().readonly() {
+            return Err(not_writable("the file is read-only".to_string()));
+        }
+        return Ok(());
+    }
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+    match std::fs::metadata(dir) {
+        Ok(metadata) if !metadata.is_dir() => Err(not_writable(format!("{} is not a directory", dir.display()))),
+        Ok(metadata) if metadata.permissions().readonly() => {
+            Err(not_writable(format!("directory {} is read-only", dir.display())))
+        }
+        Ok(_) => Ok(()),
+        Err(e) => Err(not_writable(format!("cannot access directory {}: {}", dir.display(), e))),
+    }
+}
+
+impl Default for LoggerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// High-performance logger for maximum throughput scenarios
+pub struct HighPerformanceLogger {
+    sender: mpsc::Sender<AdvancedLogCommand>,
+    _thread: thread::JoinHandle<()>,
+}
+
+impl HighPerformanceLogger {
+    /// Create a new high-performance logger
+    pub fn new<P: AsRef<std::path::Path>>(
+        path: P, 
+        config: HighPerformanceConfig
+    ) -> Result<Self, LoggerError> {
+        let file = File::create(path)?;
+        let writer = HighPerformanceFileWriter::new(file, config);
+        
+        let (sender, receiver) = mpsc::channel();
+        let thread_handle = thread::spawn(move || {
+            high_performance_worker_thread(writer, receiver);
+        });
+        
+        Ok(Self {
+            sender,
+            _thread: thread_handle,
+        })
+    }
+    
+    /// Log a pre-formatted message for maximum performance
+    pub fn log_fast(&self, message: String) -> Result<(), LoggerError> {
+        self.sender.send(AdvancedLogCommand::Message(message))
+            .map_err(|_| LoggerError::ChannelError)
+    }
+    
+    /// Force flush all pending messages
+    pub fn flush(&self) -> Result<(), LoggerError> {
+        self.sender.send(AdvancedLogCommand::Flush)
+            .map_err(|_| LoggerError::ChannelError)
+    }
+}
+
+impl Drop for HighPerformanceLogger {
+    fn drop(&mut self) {
+        let _ = self.sender.send(AdvancedLogCommand::Shutdown);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writers::HighPerformanceFileWriter;
+    use log::Log;
+    use std::fs;
+    use std::io::Read;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Barrier, Mutex, Once};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+
+    /// Counts heap allocations made by threads that opted in
+    struct CountingAllocator;
+
+    thread_local! {
+        static COUNT_ALLOCATIONS: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+    }
+    static ALLOCATIONS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+            if COUNT_ALLOCATIONS.try_with(|count| count.get()).unwrap_or(false) {
+                ALLOCATIONS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+            std::alloc::System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+            std::alloc::System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
 
     static INIT: Once = Once::new();
 
@@ -467,7 +1262,7 @@ mod tests {
         
         let mut logger = ArtificeLogger::new();
         logger.set_config(_config);
-        assert_eq!(logger.get_config().console, true);
+        assert!(logger.get_config().console);
     }
 
     #[test]
@@ -490,6 +1285,11 @@ mod tests {
             enabled: false, // Disable batching for immediate writes
             buffer_capacity: 100,
             string_pool_size: 50,
+            deduplicate: false,
+            channel_capacity: None,
+            backpressure: BackpressurePolicy::Block,
+            adaptive: None,
+            sharing: FileSharing::Single,
         };
         
         // Create logger with file output
@@ -550,6 +1350,11 @@ mod tests {
             enabled: true,
             buffer_capacity: 100,
             string_pool_size: 50,
+            deduplicate: false,
+            channel_capacity: None,
+            backpressure: BackpressurePolicy::Block,
+            adaptive: None,
+            sharing: FileSharing::Single,
         };
         
         let logger = ArtificeLogger::new()
@@ -605,6 +1410,11 @@ mod tests {
             enabled: true,
             buffer_capacity: 200,
             string_pool_size: 100,
+            deduplicate: false,
+            channel_capacity: None,
+            backpressure: BackpressurePolicy::Block,
+            adaptive: None,
+            sharing: FileSharing::Single,
         };
         
         let logger: Arc<ArtificeLogger> = Arc::new(
@@ -687,6 +1497,11 @@ mod tests {
             enabled: true,
             buffer_capacity: 1000,
             string_pool_size: 500,
+            deduplicate: false,
+            channel_capacity: None,
+            backpressure: BackpressurePolicy::Block,
+            adaptive: None,
+            sharing: FileSharing::Single,
         };
         
         let logger = ArtificeLogger::new()
@@ -786,6 +1601,11 @@ mod tests {
                 enabled: true,
                 buffer_capacity: batch_size * 2,
                 string_pool_size: batch_size,
+                deduplicate: false,
+                channel_capacity: None,
+                backpressure: BackpressurePolicy::Block,
+                adaptive: None,
+                sharing: FileSharing::Single,
             };
             
             let logger = ArtificeLogger::new()
@@ -816,6 +1636,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn bench_channel_transports() {
+        println!("=== Channel Transport Benchmarks ===");
+
+        let threads = 8;
+        let per_thread = 2000;
+
+        // The default unbounded mpsc channel against the lock-free ring a capacity selects
+        for (name, channel_capacity) in [("unbounded channel", None), ("bounded ring", Some(8192))] {
+            let log_file = std::env::temp_dir().join(format!("bench_transport_{}.log", channel_capacity.is_some()));
+            let _ = fs::remove_file(&log_file);
+
+            let batch_config = BatchConfig { batch_size: 200, channel_capacity, ..BatchConfig::default() };
+            let mut logger = ArtificeLogger::new().with_batch_config(batch_config).with_file(&log_file).unwrap();
+            logger.set_config(LogConfig { console: false, file: true, colors: false });
+            let logger: &'static ArtificeLogger = Box::leak(Box::new(logger));
+
+            let start = Instant::now();
+            let producers: Vec<_> = (0..threads)
+                .map(|thread| {
+                    thread::spawn(move || {
+                        for i in 0..per_thread {
+                            logger.log(&log::Record::builder()
+                                .args(format_args!("thread {} message {}", thread, i))
+                                .level(log::Level::Info)
+                                .target("bench")
+                                .build());
+                        }
+                    })
+                })
+                .collect();
+            for producer in producers {
+                producer.join().unwrap();
+            }
+            let enqueued = start.elapsed();
+            logger.flush_sync().unwrap();
+            let total = start.elapsed();
+
+            let messages = (threads * per_thread) as f64;
+            println!(
+                "{}: {:.2} messages/sec enqueued, {:.2} messages/sec written ({:?} total)",
+                name,
+                messages / enqueued.as_secs_f64(),
+                messages / total.as_secs_f64(),
+                total
+            );
+            assert_eq!(logger.stats().messages_written, (threads * per_thread) as u64);
+
+            logger.shutdown();
+            let _ = fs::remove_file(&log_file);
+        }
+    }
+
     #[test]
     fn bench_memory_patterns() {
         println!("=== Memory Pattern Benchmarks ===");
@@ -902,6 +1775,7 @@ mod tests {
                 enabled: true,
                 buffer_capacity: 200,
                 string_pool_size: pool_size,
+                durability: Durability::Flush,
             };
             
             let mut writer = HighPerformanceFileWriter::new(file, config);
@@ -1022,6 +1896,11 @@ mod tests {
                 enabled: false, // Disable batching
                 buffer_capacity: 200,
                 string_pool_size: 100,
+                deduplicate: false,
+                channel_capacity: None,
+                backpressure: BackpressurePolicy::Block,
+                adaptive: None,
+                sharing: FileSharing::Single,
             };
         
             let logger = ArtificeLogger::new()
@@ -1054,4 +1933,1453 @@ mod tests {
             // Cleanup
             let _ = fs::remove_file(&log_file);
         }
-    }
\ No newline at end of file
+
+    #[test]
+    fn test_rotation_file_naming() {
+        use chrono::TimeZone;
+        let time = chrono::Utc.with_ymd_and_hms(2024, 6, 1, 13, 45, 0).unwrap();
+
+        assert_eq!(RotationPolicy::Never.period_stamp(time), None);
+        assert_eq!(RotationPolicy::Daily.period_stamp(time).as_deref(), Some("2024-06-01"));
+        assert_eq!(RotationPolicy::Hourly.period_stamp(time).as_deref(), Some("2024-06-01-13"));
+
+        let path = writers::rotated_path(std::path::Path::new("logs/app.log"), "2024-06-01");
+        assert_eq!(path, std::path::PathBuf::from("logs/app-2024-06-01.log"));
+        let path = writers::rotated_path(std::path::Path::new("app"), "2024-06-01");
+        assert_eq!(path, std::path::PathBuf::from("app-2024-06-01"));
+    }
+
+    #[test]
+    fn test_daily_rotation_writes_stamped_file() {
+        let temp_dir = std::env::temp_dir();
+        let base = temp_dir.join("test_daily_rotation.log");
+
+        let logger = ArtificeLogger::new()
+            .with_rotation(RotationPolicy::Daily)
+            .with_file(&base)
+            .unwrap();
+
+        let stamp = RotationPolicy::Daily.period_stamp(chrono::Utc::now()).unwrap();
+        let rotated = writers::rotated_path(&base, &stamp);
+
+        logger.log(&log::Record::builder()
+            .args(format_args!("Rotated message"))
+            .level(log::Level::Info)
+            .target("rotation_test")
+            .build());
+        logger.flush_sync().unwrap();
+
+        let content = fs::read_to_string(&rotated).unwrap();
+        assert!(content.contains("Rotated message"));
+        assert!(!base.exists());
+
+        let _ = fs::remove_file(&rotated);
+    }
+
+    #[test]
+    fn test_custom_formatter() {
+        let temp_dir = std::env::temp_dir();
+        let log_file = temp_dir.join("test_custom_formatter.log");
+        let _ = fs::remove_file(&log_file);
+
+        let logger = LoggerBuilder::new()
+            .console(false)
+            .file(log_file.to_str().unwrap())
+            .formatter(|record: &log::Record| format!("{}|{}|{}", record.level(), record.target(), record.args()))
+            .build()
+            .unwrap();
+
+        logger.log(&log::Record::builder()
+            .args(format_args!("Formatted message"))
+            .level(log::Level::Warn)
+            .target("formatter_test")
+            .build());
+        logger.flush_sync().unwrap();
+
+        let content = fs::read_to_string(&log_file).unwrap();
+        assert!(content.contains("WARN|formatter_test|Formatted message"));
+
+        let plain = DefaultFormatter::new(false).with_timestamp_format("%H");
+        let line = plain.format(&log::Record::builder()
+            .args(format_args!("Plain"))
+            .level(log::Level::Info)
+            .build());
+        assert!(line.ends_with("] INFO: Plain"));
+        assert_eq!(line.find(']'), Some(3));
+
+        let _ = fs::remove_file(&log_file);
+    }
+
+    #[test]
+    fn test_multiple_sinks() {
+        let temp_dir = std::env::temp_dir();
+        let all_file = temp_dir.join("test_sinks_all.log");
+        let warn_file = temp_dir.join("test_sinks_warn.log");
+        let _ = fs::remove_file(&all_file);
+        let _ = fs::remove_file(&warn_file);
+
+        let logger = LoggerBuilder::new()
+            .console(false)
+            .sink(FileSink::new(&all_file, BatchConfig::default()).unwrap())
+            .sink(FileSink::new(&warn_file, BatchConfig::default())
+                .unwrap()
+                .with_level(LogLevel::Warn)
+                .with_formatter(|record: &log::Record| format!("!! {}", record.args())))
+            .build()
+            .unwrap();
+        assert_eq!(logger.sink_count(), 2);
+
+        for (level, text) in [(log::Level::Debug, "debug line"), (log::Level::Error, "error line")] {
+            logger.log(&log::Record::builder()
+                .args(format_args!("{}", text))
+                .level(level)
+                .target("sink_test")
+                .build());
+        }
+        logger.flush_sync().unwrap();
+
+        let all = fs::read_to_string(&all_file).unwrap();
+        assert!(all.contains("DEBUG: debug line"));
+        assert!(all.contains("ERROR: error line"));
+
+        let warn = fs::read_to_string(&warn_file).unwrap();
+        assert!(!warn.contains("debug line"));
+        assert!(warn.contains("!! error line"));
+
+        let _ = fs::remove_file(&all_file);
+        let _ = fs::remove_file(&warn_file);
+    }
+
+    #[test]
+    fn test_env_filter_parsing() {
+        let filter = EnvFilter::parse("info,artifice_engine::events=trace,glfw=off,renderer");
+        assert_eq!(filter.directives().len(), 4);
+        assert_eq!(filter.level_for("artifice_engine::window"), log::LevelFilter::Info);
+        assert_eq!(filter.level_for("artifice_engine::events::core"), log::LevelFilter::Trace);
+        assert_eq!(filter.level_for("glfw"), log::LevelFilter::Off);
+        assert_eq!(filter.level_for("renderer"), log::LevelFilter::Trace);
+        assert_eq!(filter.max_level(), log::LevelFilter::Trace);
+
+        // Without a default directive only listed targets pass
+        let filter = EnvFilter::parse("artifice_engine=debug, bogus=loud");
+        assert_eq!(filter.directives().len(), 1);
+        assert_eq!(filter.level_for("other_crate"), log::LevelFilter::Off);
+        assert_eq!(filter.max_level(), log::LevelFilter::Debug);
+
+        let metadata = log::Metadata::builder()
+            .level(log::Level::Trace)
+            .target("artifice_engine::input")
+            .build();
+        assert!(!filter.enabled(&metadata));
+        assert!(EnvFilter::new().enabled(&metadata));
+    }
+
+    #[test]
+    fn test_network_sink_tcp() {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let receiver = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            BufReader::new(stream)
+                .lines()
+                .take(3)
+                .map(|line| line.unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        let mut logger = ArtificeLogger::new()
+            .with_sink(NetworkSink::new(address, NetworkProtocol::Tcp, BatchConfig::default()).unwrap());
+        logger.set_config(LogConfig { console: false, file: false, colors: false });
+
+        for i in 0..3 {
+            logger.log(&log::Record::builder()
+                .args(format_args!("Network message {}", i))
+                .level(log::Level::Info)
+                .target("network_test")
+                .build());
+        }
+        logger.flush();
+
+        let lines = receiver.join().unwrap();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[2].contains("Network message 2"));
+
+        thread::sleep(Duration::from_millis(50));
+        let stats = logger.delivery_stats();
+        assert_eq!(stats.sent_messages, 3);
+        assert_eq!(stats.reconnects, 1);
+        assert_eq!(stats.dropped_messages, 0);
+    }
+
+    #[test]
+    fn test_network_resends_only_undelivered_messages() {
+        use crate::writers::{BatchWriter, NetworkWriter};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let batch_config = BatchConfig { batch_size: 100, ..BatchConfig::default() };
+        let mut writer = NetworkWriter::new(address, NetworkProtocol::Tcp, batch_config);
+        let stats = writer.stats();
+        // Far larger than the socket buffers, so the send is still going when the peer leaves
+        let huge = "x".repeat(32 << 20);
+        for line in ["first", huge.as_str(), "last"] {
+            BatchWriter::add_message(&mut writer, LogMessage::plain(line.to_string())).unwrap();
+        }
+
+        let collector = thread::spawn(move || {
+            // The first connection takes one line and then resets mid-batch
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut first = [0; 6];
+            stream.read_exact(&mut first).unwrap();
+            assert_eq!(&first, b"first\n");
+            drop(stream);
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut rest = Vec::new();
+            stream.read_to_end(&mut rest).unwrap();
+            rest
+        });
+
+        assert!(BatchWriter::flush(&mut writer).is_err());
+        assert_eq!(BatchWriter::pending(&writer), 2);
+        assert_eq!(stats.snapshot().sent_messages, 1);
+
+        // Past the reconnect backoff, only the undelivered lines go out again
+        thread::sleep(Duration::from_millis(150));
+        BatchWriter::flush(&mut writer).unwrap();
+        BatchWriter::shutdown(&mut writer).unwrap();
+        drop(writer);
+        let rest = collector.join().unwrap();
+        assert_eq!(rest.len(), huge.len() + "\nlast\n".len());
+        assert!(rest.ends_with(b"x\nlast\n"));
+        let stats = stats.snapshot();
+        assert_eq!((stats.sent_messages, stats.sent_batches, stats.reconnects), (3, 1, 2));
+    }
+
+    #[test]
+    fn test_structured_fields() {
+        let user_id = 42;
+        let build = |formatter: &dyn LogFormatter| {
+            let fields: &[(&str, log::kv::Value)] = &[
+                ("user_id", log::kv::Value::from(user_id)),
+                ("scene", log::kv::Value::from("main menu")),
+                ("ready", log::kv::Value::from(true)),
+            ];
+            formatter.format(&log::Record::builder()
+                .args(format_args!("Player \"joined\""))
+                .level(log::Level::Info)
+                .target("kv_test")
+                .key_values(&fields)
+                .build())
+        };
+
+        let text = build(&DefaultFormatter::new(false));
+        assert!(text.ends_with("INFO: Player \"joined\" user_id=42 scene=\"main menu\" ready=true"));
+
+        let json = build(&JsonFormatter::new());
+        assert!(json.starts_with("{\"timestamp\":"));
+        assert!(json.contains("\"message\":\"Player \\\"joined\\\"\""));
+        assert!(json.ends_with("\"fields\":{\"user_id\":42,\"scene\":\"main menu\",\"ready\":true}}"));
+    }
+
+    #[test]
+    fn test_rate_limiting() {
+        let limiter = RateLimiter::new(RateLimitConfig { max_messages: 3, window_ms: 50 });
+        let record = |line| log::Record::builder()
+            .args(format_args!("per-frame trace"))
+            .level(log::Level::Trace)
+            .target("rate_test")
+            .file(Some("src/engine.rs"))
+            .line(Some(line))
+            .build();
+
+        let decisions: Vec<_> = (0..5).map(|_| limiter.check(&record(10))).collect();
+        assert_eq!(&decisions[..3], &[RateLimitDecision::Allow; 3]);
+        assert_eq!(&decisions[3..], &[RateLimitDecision::Suppress; 2]);
+
+        // Other callsites have their own budget
+        assert_eq!(limiter.check(&record(11)), RateLimitDecision::Allow);
+
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(limiter.check(&record(10)), RateLimitDecision::AllowAfterSuppressed(2));
+        assert_eq!(limiter.check(&record(10)), RateLimitDecision::Allow);
+    }
+
+    #[test]
+    fn test_duplicate_message_collapsing() {
+        let temp_dir = std::env::temp_dir();
+        let log_file = temp_dir.join("test_dedup.log");
+        let _ = fs::remove_file(&log_file);
+
+        let batch_config = BatchConfig {
+            deduplicate: true,
+            ..BatchConfig::default()
+        };
+        let logger = ArtificeLogger::new()
+            .with_batch_config(batch_config)
+            .with_formatter(|record: &log::Record| record.args().to_string())
+            .with_file(&log_file)
+            .unwrap();
+
+        let log_line = |text: &str| {
+            logger.log(&log::Record::builder()
+                .args(format_args!("{}", text))
+                .level(log::Level::Warn)
+                .target("dedup_test")
+                .build());
+        };
+        for _ in 0..121 {
+            log_line("Texture missing");
+        }
+        log_line("Frame done");
+        log_line("Frame done");
+
+        logger.flush_sync().unwrap();
+
+        let content = fs::read_to_string(&log_file).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines, [
+            "Texture missing",
+            "last message repeated 120 times",
+            "Frame done",
+            "last message repeated 1 times",
+        ]);
+
+        let _ = fs::remove_file(&log_file);
+    }
+
+    #[test]
+    fn test_shutdown_writes_pending_messages() {
+        let temp_dir = std::env::temp_dir();
+        let log_file = temp_dir.join("test_shutdown_join.log");
+        let _ = fs::remove_file(&log_file);
+
+        let batch_config = BatchConfig {
+            batch_size: 1000,
+            flush_interval_ms: 10_000,
+            ..BatchConfig::default()
+        };
+        let logger = ArtificeLogger::new()
+            .with_batch_config(batch_config)
+            .with_file(&log_file)
+            .unwrap();
+
+        logger.log(&log::Record::builder()
+            .args(format_args!("Last words"))
+            .level(log::Level::Error)
+            .target("shutdown_test")
+            .build());
+
+        // Dropping joins the writer thread, so no sleep is needed
+        drop(logger);
+
+        let content = fs::read_to_string(&log_file).unwrap();
+        assert!(content.contains("Last words"));
+
+        let _ = fs::remove_file(&log_file);
+    }
+
+    #[test]
+    fn test_flush_sync_waits_for_writer() {
+        let log_file = std::env::temp_dir().join("test_flush_sync.log");
+        let _ = fs::remove_file(&log_file);
+
+        // Neither the batch size nor the interval would flush during the test
+        let batch_config = BatchConfig { batch_size: 1000, flush_interval_ms: 60_000, ..BatchConfig::default() };
+        let logger = ArtificeLogger::new().with_batch_config(batch_config).with_file(&log_file).unwrap();
+
+        for i in 0..3 {
+            logger.log(&log::Record::builder()
+                .args(format_args!("Synced {}", i))
+                .level(log::Level::Info)
+                .target("flush_sync_test")
+                .build());
+        }
+        logger.flush_sync().unwrap();
+
+        let content = fs::read_to_string(&log_file).unwrap();
+        assert_eq!(content.lines().filter(|line| line.contains("Synced")).count(), 3);
+
+        // A stopped worker cannot acknowledge, so the wait must not hang
+        logger.shutdown();
+        assert!(matches!(logger.flush_sync(), Err(LoggerError::ChannelError)));
+
+        let _ = fs::remove_file(&log_file);
+    }
+
+    #[test]
+    fn test_bounded_queue_backpressure() {
+        use crate::batching::{BoundedQueue, LogCommand, PushOutcome};
+
+        let message = |text: &str| LogMessage::plain(text.to_string());
+        let received = |queue: &BoundedQueue| match queue.recv_timeout(Duration::from_millis(1)) {
+            Ok(LogCommand::Message(message)) => message.formatted_message,
+            other => panic!("expected a message, got {:?}", other),
+        };
+
+        let queue = BoundedQueue::new(2).unwrap();
+        assert!(matches!(queue.push_message(message("a"), &BackpressurePolicy::DropNewest), PushOutcome::Queued));
+        assert!(matches!(queue.push_message(message("b"), &BackpressurePolicy::DropNewest), PushOutcome::Queued));
+        assert!(matches!(queue.push_message(message("c"), &BackpressurePolicy::DropNewest), PushOutcome::Dropped));
+        assert!(matches!(queue.push_message(message("d"), &BackpressurePolicy::DropOldest), PushOutcome::DroppedOldest));
+
+        // Control commands are never subject to the capacity
+        assert!(queue.push_command(LogCommand::Flush));
+        assert_eq!(queue.len(), 2);
+
+        let spill = BackpressurePolicy::SpillToFile("emergency.log".into());
+        match queue.push_message(message("e"), &spill) {
+            PushOutcome::Spill(spilled) => assert_eq!(spilled.formatted_message, "e"),
+            other => panic!("expected spill, got {:?}", other),
+        }
+
+        assert_eq!(received(&queue), "b");
+        assert_eq!(received(&queue), "d");
+        assert!(matches!(queue.recv_timeout(Duration::from_millis(1)), Ok(LogCommand::Flush)));
+        assert!(queue.recv_timeout(Duration::from_millis(1)).is_err());
+
+        queue.close();
+        assert!(!queue.push_command(LogCommand::Flush));
+        assert!(matches!(queue.push_message(message("f"), &BackpressurePolicy::Block), PushOutcome::Dropped));
+
+        // Dropping the oldest message skips a control command ahead of it without moving it back
+        let queue = BoundedQueue::new(2).unwrap();
+        assert!(queue.push_command(LogCommand::Flush));
+        queue.push_message(message("g"), &BackpressurePolicy::DropOldest);
+        queue.push_message(message("h"), &BackpressurePolicy::DropOldest);
+        assert!(matches!(queue.push_message(message("i"), &BackpressurePolicy::DropOldest), PushOutcome::DroppedOldest));
+        assert!(matches!(queue.recv_timeout(Duration::from_millis(1)), Ok(LogCommand::Flush)));
+        assert_eq!(received(&queue), "h");
+        assert_eq!(received(&queue), "i");
+
+        // A ring for this many messages cannot even be sized
+        assert!(matches!(BoundedQueue::new(usize::MAX), Err(LoggerError::InvalidBatchSize { .. })));
+        let huge = BatchConfig { channel_capacity: Some(usize::MAX), ..BatchConfig::default() };
+        let path = std::env::temp_dir().join("huge_channel.log");
+        assert!(matches!(FileSink::new(&path, huge), Err(LoggerError::InvalidBatchSize { .. })));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_bounded_channel_logging() {
+        let temp_dir = std::env::temp_dir();
+        let log_file = temp_dir.join("test_bounded_channel.log");
+        let _ = fs::remove_file(&log_file);
+
+        let batch_config = BatchConfig {
+            channel_capacity: Some(4),
+            backpressure: BackpressurePolicy::Block,
+            adaptive: None,
+            sharing: FileSharing::Single,
+            ..BatchConfig::default()
+        };
+        let logger = ArtificeLogger::new()
+            .with_batch_config(batch_config)
+            .with_file(&log_file)
+            .unwrap();
+
+        for i in 0..50 {
+            logger.log(&log::Record::builder()
+                .args(format_args!("Bounded message {}", i))
+                .level(log::Level::Info)
+                .target("bounded_test")
+                .build());
+        }
+        logger.flush_sync().unwrap();
+
+        // Blocking never loses messages
+        let content = fs::read_to_string(&log_file).unwrap();
+        assert_eq!(content.lines().count(), 50);
+        assert_eq!(logger.dropped_messages(), 0);
+
+        let _ = fs::remove_file(&log_file);
+    }
+
+    #[test]
+    fn test_timestamp_clocks() {
+        let record = log::Record::builder()
+            .args(format_args!("Tick"))
+            .level(log::Level::Info)
+            .build();
+
+        let relative = DefaultFormatter::new(false).with_clock(TimestampClock::relative());
+        let line = relative.format(&record);
+        assert!(line.starts_with("[+0.0"), "{}", line);
+        assert!(line.ends_with("s] INFO: Tick"));
+
+        let local = DefaultFormatter::new(false)
+            .with_clock(TimestampClock::Local)
+            .with_timestamp_format("%Y");
+        let year = chrono::Local::now().format("%Y").to_string();
+        assert!(local.format(&record).starts_with(&format!("[{}]", year)));
+
+        let logger = LoggerBuilder::new()
+            .console(false)
+            .colors(false)
+            .timestamp_format("%H:%M")
+            .local_time(true)
+            .build()
+            .unwrap();
+        let mut line = String::new();
+        logger.format_into(&record, &mut line);
+        assert_eq!(line.find(']'), Some(6));
+    }
+
+    #[test]
+    fn test_scoped_context() {
+        let record = log::Record::builder()
+            .args(format_args!("Rendering"))
+            .level(log::Level::Debug)
+            .build();
+        let text = DefaultFormatter::new(false);
+
+        {
+            let _scene = push_context("scene", "main menu");
+            {
+                let _frame = push_context("frame", 1042u64);
+                assert!(text.format(&record).ends_with("DEBUG: Rendering scene=\"main menu\" frame=1042"));
+
+                let json = JsonFormatter::new().format(&record);
+                assert!(json.ends_with(",\"context\":{\"scene\":\"main menu\",\"frame\":1042}}"));
+            }
+            assert_eq!(current_context(), vec![("scene".to_string(), FieldValue::from("main menu"))]);
+        }
+
+        assert!(current_context().is_empty());
+        assert!(text.format(&record).ends_with("DEBUG: Rendering"));
+
+        // Context is per thread
+        let _session = push_context("session", 7);
+        let other = thread::spawn(current_context).join().unwrap();
+        assert!(other.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_journald_sink_encoding() {
+        use std::os::unix::net::UnixDatagram;
+
+        let socket_path = std::env::temp_dir().join(format!("artifice_journal_{}.sock", std::process::id()));
+        let _ = fs::remove_file(&socket_path);
+        let journal = UnixDatagram::bind(&socket_path).unwrap();
+
+        let sink = JournaldSink::with_socket(&socket_path)
+            .unwrap()
+            .with_identifier("artifice-test")
+            .with_level(LogLevel::Info);
+        let mut logger = ArtificeLogger::new().with_sink(sink);
+        logger.set_config(LogConfig { console: false, file: false, colors: false });
+
+        let _session = push_context("session", 7);
+        logger.log(&log::Record::builder()
+            .args(format_args!("Player joined\nlobby"))
+            .level(log::Level::Warn)
+            .target("artifice::net")
+            .key_values(&[("user-id", 42)])
+            .build());
+        logger.log(&log::Record::builder()
+            .args(format_args!("filtered"))
+            .level(log::Level::Debug)
+            .build());
+
+        let mut buf = [0u8; 1024];
+        let len = journal.recv(&mut buf).unwrap();
+        let entry = &buf[..len];
+
+        let mut expected = b"PRIORITY=4\nMESSAGE\n".to_vec();
+        expected.extend_from_slice(&19u64.to_le_bytes());
+        expected.extend_from_slice(b"Player joined\nlobby\nSYSLOG_IDENTIFIER=artifice-test\nTARGET=artifice::net\n");
+        assert!(entry.starts_with(&expected));
+        assert!(entry.ends_with(b"USER_ID=42\nSESSION=7\n"));
+
+        journal.set_nonblocking(true).unwrap();
+        assert!(journal.recv(&mut buf).is_err());
+        assert_eq!(logger.dropped_messages(), 0);
+
+        let _ = fs::remove_file(&socket_path);
+    }
+
+    #[test]
+    fn test_runtime_level_changes() {
+        let log_file = std::env::temp_dir().join("runtime_levels.log");
+        let _ = fs::remove_file(&log_file);
+
+        let sink = FileSink::new(&log_file, BatchConfig::default())
+            .unwrap()
+            .with_level(LogLevel::Info);
+        let mut logger = ArtificeLogger::new()
+            .with_sink(sink)
+            .with_filter(EnvFilter::parse("info"));
+        logger.set_config(LogConfig { console: false, file: false, colors: false });
+        let handle = LoggerHandle { logger: Box::leak(Box::new(logger)) };
+
+        let record = |level, target, text| {
+            handle.logger.log(&log::Record::builder()
+                .args(format_args!("{}", text))
+                .level(level)
+                .target(target)
+                .build());
+        };
+
+        record(log::Level::Debug, "game::net", "hidden by filter");
+        handle.set_target_level("game::net", LogLevel::Trace);
+        record(log::Level::Debug, "game::net", "hidden by sink");
+        assert!(handle.set_sink_level(0, LogLevel::Debug));
+        assert!(!handle.set_sink_level(1, LogLevel::Debug));
+        record(log::Level::Debug, "game::net", "visible");
+        record(log::Level::Debug, "game::render", "hidden by default level");
+
+        let filter = handle.filter().unwrap();
+        assert_eq!(filter.level_for("game::net::sync"), log::LevelFilter::Trace);
+        assert_eq!(filter.level_for("game::render"), log::LevelFilter::Info);
+        // The global `log` max level is shared with tests running in parallel, so
+        // check the level the handle derives it from instead
+        assert_eq!(filter.max_level(), log::LevelFilter::Trace);
+
+        handle.logger.flush_sync().unwrap();
+        let mut contents = String::new();
+        fs::File::open(&log_file).unwrap().read_to_string(&mut contents).unwrap();
+        assert!(contents.contains("visible"));
+        assert!(!contents.contains("hidden"));
+
+        handle.logger.shutdown();
+        let _ = fs::remove_file(&log_file);
+    }
+
+    #[test]
+    fn test_config_file_and_reload() {
+        let config = LogFileConfig::parse(r#"
+            level = "info"
+            filter = "glfw=off"
+            console = false
+            format = "json"
+            timestamps = "local"
+
+            [levels]
+            "game::net" = "debug"
+
+            [batch]
+            batch_size = 10
+            deduplicate = true
+            max_batch_size = 200
+
+            [[sinks]]
+            type = "console"
+            level = "warn"
+        "#).unwrap();
+
+        let filter = config.filter.clone().unwrap();
+        assert_eq!(filter.level_for("game::render"), log::LevelFilter::Info);
+        assert_eq!(filter.level_for("game::net"), log::LevelFilter::Debug);
+        assert_eq!(filter.level_for("glfw"), log::LevelFilter::Off);
+        assert!(!config.console);
+        assert_eq!(config.format, LineFormat::Json);
+        assert_eq!(config.clock, TimestampClock::Local);
+        assert_eq!(config.batch.batch_size, 10);
+        assert!(config.batch.deduplicate);
+        assert_eq!(config.batch.adaptive, Some(AdaptiveBatching { max_batch_size: 200, ..AdaptiveBatching::default() }));
+        assert_eq!(config.sinks, vec![SinkConfig {
+            kind: SinkKind::Console { colors: false },
+            level: LogLevel::Warn,
+            format: LineFormat::Text,
+        }]);
+
+        assert!(matches!(LogFileConfig::parse("levle = \"info\""), Err(LoggerError::Config(_))));
+        assert!(matches!(LogFileConfig::parse("[[sinks]]\ntype = \"file\""), Err(LoggerError::Config(_))));
+        assert!(matches!(LogFileConfig::parse("level = \"loud\""), Err(LoggerError::Config(_))));
+
+        // Watching applies level changes to a running logger
+        let path = std::env::temp_dir().join("artifice_reload_test.toml");
+        fs::write(&path, "level = \"info\"\n").unwrap();
+        let logger = LogFileConfig::load(&path).unwrap().to_builder().unwrap().console(false).build().unwrap();
+        let handle = LoggerHandle { logger: Box::leak(Box::new(logger)) };
+        let _watcher = ConfigWatcher::spawn(handle, &path, Duration::from_millis(10));
+
+        thread::sleep(Duration::from_millis(20));
+        fs::write(&path, "level = \"info\"\n[levels]\n\"game::net\" = \"trace\"\n").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while handle.filter().unwrap().level_for("game::net") != log::LevelFilter::Trace {
+            assert!(Instant::now() < deadline, "config change was not picked up");
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(handle.filter().unwrap().level_for("game::render"), log::LevelFilter::Info);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_disk_quota() {
+        use crate::writers::{rotated_path, BatchWriter};
+
+        let dir = std::env::temp_dir().join(format!("artifice_quota_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let old = std::time::SystemTime::now() - Duration::from_secs(3600);
+        let names = ["game-2024-01-01.log", "game-2024-01-02.log", "other.log", "game-backup.log", "game-2024-01-03-05.log"];
+        for (i, name) in names.iter().enumerate() {
+            let file = fs::File::create(dir.join(name)).unwrap();
+            file.set_len(600).unwrap();
+            file.set_modified(old + Duration::from_secs(i as u64)).unwrap();
+        }
+
+        let batch_config = BatchConfig { enabled: false, ..BatchConfig::default() };
+        let line = "x".repeat(99);
+
+        // Oldest rotated files are deleted; unrelated files, and files another
+        // rotation policy would have written, are left alone
+        let mut writer = FileWriter::rotating(dir.join("game.log"), RotationPolicy::Daily, batch_config.clone())
+            .unwrap()
+            .with_quota(DiskQuota { max_bytes: 1000, action: QuotaAction::DeleteOldest });
+        assert!(!dir.join("game-2024-01-01.log").exists());
+        assert!(dir.join("game-2024-01-02.log").exists());
+
+        for _ in 0..5 {
+            writer.add_message(LogMessage::plain(line.clone())).unwrap();
+        }
+        assert!(!dir.join("game-2024-01-02.log").exists());
+        assert!(dir.join("other.log").exists());
+        assert!(dir.join("game-backup.log").exists());
+        assert!(dir.join("game-2024-01-03-05.log").exists());
+        let today = RotationPolicy::Daily.period_stamp(chrono::Utc::now()).unwrap();
+        assert_eq!(fs::metadata(rotated_path(&dir.join("game.log"), &today)).unwrap().len(), 500);
+        assert_eq!(writer.take_dropped(), 0);
+
+        // Stopping keeps the file at the size where the budget ran out and counts what it discards
+        let mut writer = FileWriter::rotating(dir.join("stop.log"), RotationPolicy::Never, batch_config)
+            .unwrap()
+            .with_quota(DiskQuota { max_bytes: 150, action: QuotaAction::StopWriting });
+        for _ in 0..3 {
+            writer.add_message(LogMessage::plain(line.clone())).unwrap();
+        }
+        assert_eq!(fs::metadata(dir.join("stop.log")).unwrap().len(), 200);
+        assert_eq!(writer.take_dropped(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_high_performance_durability_modes() {
+        for (i, durability) in [Durability::Flush, Durability::FsyncEveryBatch, Durability::FsyncIntervalMs(60_000)]
+            .into_iter()
+            .enumerate()
+        {
+            let log_file = std::env::temp_dir().join(format!("hp_durability_{}.log", i));
+            let config = HighPerformanceConfig { batch_size: 2, durability, ..HighPerformanceConfig::default() };
+            let mut writer = HighPerformanceFileWriter::new(fs::File::create(&log_file).unwrap(), config);
+
+            for n in 0..3 {
+                writer.add_message(format!("durable {}", n)).unwrap();
+            }
+            writer.sync_if_due().unwrap();
+            assert_eq!(fs::read_to_string(&log_file).unwrap().lines().count(), 2);
+
+            writer.shutdown().unwrap();
+            assert_eq!(fs::read_to_string(&log_file).unwrap().lines().count(), 3);
+
+            let _ = fs::remove_file(&log_file);
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_async_sink() {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let log_file = std::env::temp_dir().join("async_sink.log");
+
+        runtime.block_on(async {
+            let batch_config = BatchConfig { flush_interval_ms: 60_000, ..BatchConfig::default() };
+            let sink = AsyncSink::file(&log_file, batch_config).unwrap().with_level(LogLevel::Info);
+            let handle = sink.handle();
+            let mut logger = ArtificeLogger::new().with_sink(sink);
+            logger.set_config(LogConfig { console: false, file: false, colors: false });
+
+            for level in [log::Level::Info, log::Level::Debug, log::Level::Warn] {
+                logger.log(&log::Record::builder()
+                    .args(format_args!("async {}", level))
+                    .level(level)
+                    .build());
+            }
+
+            handle.flush().await.unwrap();
+            let contents = fs::read_to_string(&log_file).unwrap();
+            assert!(contents.contains("async INFO") && contents.contains("async WARN"));
+            assert!(!contents.contains("async DEBUG"));
+
+            handle.shutdown().await;
+            assert!(matches!(handle.flush().await, Err(LoggerError::ChannelError)));
+        });
+
+        let _ = fs::remove_file(&log_file);
+    }
+
+    #[test]
+    fn test_routed_file_sink() {
+        let dir = std::env::temp_dir();
+        let (app_log, error_log) = (dir.join("routed_app.log"), dir.join("routed_errors.log"));
+
+        let batch_config = BatchConfig { deduplicate: true, ..BatchConfig::default() };
+        let sink = RoutedFileSink::new([(&app_log, LogLevel::Info), (&error_log, LogLevel::Warn)], batch_config).unwrap();
+        let mut logger = ArtificeLogger::new().with_sink(sink);
+        logger.set_config(LogConfig { console: false, file: false, colors: false });
+
+        for (level, text) in [
+            (log::Level::Debug, "cache miss"),
+            (log::Level::Info, "level loaded"),
+            (log::Level::Warn, "slow frame"),
+            (log::Level::Warn, "slow frame"),
+            (log::Level::Error, "shader failed"),
+        ] {
+            logger.log(&log::Record::builder().args(format_args!("{}", text)).level(level).build());
+        }
+        logger.flush_sync().unwrap();
+
+        let app = fs::read_to_string(&app_log).unwrap();
+        let errors = fs::read_to_string(&error_log).unwrap();
+        assert!(!app.contains("cache miss"));
+        assert_eq!(app.lines().count(), 4);
+        assert!(!errors.contains("level loaded"));
+        assert_eq!(errors.lines().count(), 3);
+        assert!(errors.contains("last message repeated 1 times") && errors.contains("shader failed"));
+
+        logger.shutdown();
+        let _ = fs::remove_file(&app_log);
+        let _ = fs::remove_file(&error_log);
+    }
+
+    #[test]
+    fn test_builder_validation() {
+        // A batch size kept while batching is switched off is unused, not a contradiction
+        assert!(LoggerBuilder::new().batching(false).batch_size(500).validate().is_ok());
+        assert!(matches!(
+            LoggerBuilder::new().batch_size(0).validate(),
+            Err(LoggerError::InvalidBatchSize { batch_size: 0, .. })
+        ));
+
+        let err = LoggerBuilder::new()
+            .file("app.log")
+            .config(LogConfig { console: true, file: false, colors: true })
+            .validate()
+            .unwrap_err();
+        assert!(matches!(err, LoggerError::ConflictingSinks(_)));
+        assert!(err.to_string().contains("app.log"));
+
+        let dir = std::env::temp_dir();
+        match LoggerBuilder::new().file(dir.to_string_lossy()).build() {
+            Err(LoggerError::PathNotWritable { path, .. }) => assert_eq!(path, dir),
+            _ => panic!("a directory must not be accepted as log file"),
+        }
+        let missing = dir.join("artifice_missing_dir").join("app.log");
+        assert!(matches!(
+            LoggerBuilder::new().file(missing.to_string_lossy()).validate(),
+            Err(LoggerError::PathNotWritable { .. })
+        ));
+
+        assert!(LoggerBuilder::new().batch_size(50).file(dir.join("valid.log").to_string_lossy()).validate().is_ok());
+    }
+
+    #[test]
+    fn test_logger_stats() {
+        let path = std::env::temp_dir().join("stats_sink.log");
+        let batch_config = BatchConfig { batch_size: 2, flush_interval_ms: 10_000, ..BatchConfig::default() };
+        let mut logger = ArtificeLogger::new().with_sink(FileSink::new(&path, batch_config).unwrap());
+        logger.set_config(LogConfig { console: false, file: false, colors: false });
+        let idle = LoggerStats { batch_size: 2, flush_interval: Duration::from_secs(10), ..LoggerStats::default() };
+        assert_eq!(logger.stats(), idle);
+
+        for i in 0..5 {
+            logger.log(&log::Record::builder().args(format_args!("frame {}", i)).level(log::Level::Info).build());
+        }
+        logger.flush_sync().unwrap();
+
+        let stats = logger.stats();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(stats.messages_received, 5);
+        assert_eq!(stats.messages_written, 5);
+        assert_eq!(stats.bytes_written, contents.len() as u64);
+        assert_eq!(stats.queue_depth, 0);
+        assert_eq!(stats.dropped_messages, 0);
+        // Two full batches of two, then the remainder on flush
+        assert_eq!(stats.batches_written, 3);
+        assert!((stats.average_batch_size() - 5.0 / 3.0).abs() < f64::EPSILON);
+        assert!(stats.max_flush_latency <= stats.total_flush_time);
+        assert!(stats.average_flush_latency() <= stats.max_flush_latency);
+
+        logger.shutdown();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_pooled_logging_does_not_allocate() {
+        let path = std::env::temp_dir().join("pooled_logging.log");
+        // A pool far larger than the burst keeps other tests from draining it
+        let batch_config = BatchConfig {
+            batch_size: 32,
+            channel_capacity: Some(1024),
+            string_pool_size: 4096,
+            ..BatchConfig::default()
+        };
+        let mut logger = ArtificeLogger::new().with_batch_config(batch_config).with_file(&path).unwrap();
+        logger.set_config(LogConfig { console: false, file: true, colors: false });
+
+        let log_frames = |logger: &ArtificeLogger, count: usize| {
+            for frame in 0..count {
+                logger.log(&log::Record::builder()
+                    .args(format_args!("frame {} rendered", frame))
+                    .level(log::Level::Info)
+                    .build());
+            }
+        };
+
+        // Warm up: buffers cycle through the writer and back into the pool
+        log_frames(&logger, 256);
+        logger.flush_sync().unwrap();
+
+        COUNT_ALLOCATIONS.with(|count| count.set(true));
+        log_frames(&logger, 64);
+        COUNT_ALLOCATIONS.with(|count| count.set(false));
+
+        assert_eq!(ALLOCATIONS.load(std::sync::atomic::Ordering::Relaxed), 0);
+
+        logger.shutdown();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 320);
+        assert!(contents.contains("frame 63 rendered"));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_bounded_queue_concurrent_producers() {
+        use crate::batching::{BoundedQueue, LogCommand};
+
+        let queue = Arc::new(BoundedQueue::new(64).unwrap());
+        let producers: Vec<_> = (0..8)
+            .map(|producer| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    for i in 0..1000 {
+                        let message = LogMessage::plain(format!("{} {}", producer, i));
+                        queue.push_message(message, &BackpressurePolicy::Block);
+                    }
+                })
+            })
+            .collect();
+
+        // Every message arrives exactly once and in order per producer
+        let mut next = [0; 8];
+        for _ in 0..8000 {
+            match queue.recv_timeout(Duration::from_secs(5)) {
+                Ok(LogCommand::Message(message)) => {
+                    let (producer, i) = message.formatted_message.split_once(' ').unwrap();
+                    let producer: usize = producer.parse().unwrap();
+                    assert_eq!(i.parse::<usize>().unwrap(), next[producer]);
+                    next[producer] += 1;
+                }
+                other => panic!("expected a message, got {:?}", other),
+            }
+        }
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        assert_eq!(next, [1000; 8]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_color_theme() {
+        let record = log::Record::builder()
+            .args(format_args!("Low on memory"))
+            .level(log::Level::Warn)
+            .build();
+        let format = |logger: &ArtificeLogger| {
+            let mut line = String::new();
+            logger.format_into(&record, &mut line);
+            line
+        };
+
+        let themed = LoggerBuilder::new()
+            .console(false)
+            .color_choice(ColorChoice::Always)
+            .theme(ColorTheme::default().with_color(LogLevel::Warn, "1;35"))
+            .build()
+            .unwrap();
+        assert!(format(&themed).contains("] \x1b[1;35mWARN\x1b[0m: Low on memory"));
+
+        let plain = LoggerBuilder::new().console(false).color_choice(ColorChoice::Never).build().unwrap();
+        assert!(format(&plain).contains("] WARN: Low on memory"));
+
+        let config = LogFileConfig::parse("colors = \"auto\"\n[theme]\nwarn = \"93\"\n").unwrap();
+        assert_eq!(config.colors, ColorChoice::Auto);
+        assert_eq!(config.theme.color(LogLevel::Warn), "93");
+        assert!(LogFileConfig::parse("colors = \"sometimes\"").is_err());
+        assert!(LogFileConfig::parse("[theme]\nfatal = \"31\"").is_err());
+
+        // Sinks keeping the default colors follow the logger's theme, others keep their own
+        let themed_path = std::env::temp_dir().join("theme_sink.log");
+        let own_path = std::env::temp_dir().join("theme_own_sink.log");
+        let logger = LoggerBuilder::new()
+            .console(false)
+            .theme(ColorTheme::default().with_color(LogLevel::Warn, "1;35"))
+            .sink(FileSink::new(&themed_path, BatchConfig::default()).unwrap().with_formatter(DefaultFormatter::new(true)))
+            .sink(FileSink::new(&own_path, BatchConfig::default()).unwrap().with_formatter(
+                DefaultFormatter::new(true).with_theme(ColorTheme::default().with_color(LogLevel::Warn, "93")),
+            ))
+            .build()
+            .unwrap();
+        logger.log(&record);
+        logger.flush_sync().unwrap();
+        assert!(fs::read_to_string(&themed_path).unwrap().contains("\x1b[1;35mWARN"));
+        assert!(fs::read_to_string(&own_path).unwrap().contains("\x1b[93mWARN"));
+        logger.shutdown();
+        let _ = fs::remove_file(&themed_path);
+        let _ = fs::remove_file(&own_path);
+
+        // Anything but SGR parameters could inject other escape sequences
+        let hostile = ColorTheme::default().with_color(LogLevel::Warn, "31m\x1b]0;title\x07");
+        assert!(matches!(
+            LoggerBuilder::new().console(false).theme(hostile.clone()).build(),
+            Err(LoggerError::InvalidColor { level: LogLevel::Warn, .. })
+        ));
+        assert!(LogFileConfig::parse("[theme]\nerror = \"1;31;\\u001b[2J\"").is_err());
+        let unchecked = DefaultFormatter::new(true).with_theme(hostile);
+        assert!(unchecked.format(&record).contains("] WARN: Low on memory"));
+    }
+
+    #[test]
+    fn test_target_and_source_details() {
+        let record = log::Record::builder()
+            .args(format_args!("Swapchain recreated"))
+            .level(log::Level::Info)
+            .target("artifice_engine::render")
+            .module_path(Some("artifice_engine::render::vulkan"))
+            .file(Some("src/render/vulkan.rs"))
+            .line(Some(88))
+            .build();
+
+        let logger = LoggerBuilder::new()
+            .console(false)
+            .colors(false)
+            .show_target(true)
+            .show_source(true)
+            .build()
+            .unwrap();
+        let mut line = String::new();
+        logger.format_into(&record, &mut line);
+        assert!(line.ends_with("] INFO artifice_engine::render src/render/vulkan.rs:88: Swapchain recreated"));
+
+        let source_only = DefaultFormatter::new(false).with_details(LineDetails { target: false, source: true });
+        assert!(source_only.format(&record).ends_with("] INFO src/render/vulkan.rs:88: Swapchain recreated"));
+
+        let json = JsonFormatter::new().with_details(LineDetails { target: true, source: true }).format(&record);
+        assert!(json.contains(r#""target":"artifice_engine::render","module":"artifice_engine::render::vulkan","file":"src/render/vulkan.rs","line":88,"#));
+        assert!(!JsonFormatter::new().format(&record).contains("\"file\""));
+
+        let config = LogFileConfig::parse("show_target = true\nshow_source = true\n").unwrap();
+        assert_eq!(config.details, LineDetails { target: true, source: true });
+    }
+
+    #[test]
+    fn test_reopen_after_rename() {
+        let dir = std::env::temp_dir();
+        let (path, rotated) = (dir.join("reopen_test.log"), dir.join("reopen_test.log.1"));
+        let _ = fs::remove_file(&rotated);
+
+        let mut logger = ArtificeLogger::new().with_file(&path).unwrap();
+        logger.set_config(LogConfig { console: false, file: true, colors: false });
+        let log_line = |logger: &ArtificeLogger, text: &str| {
+            logger.log(&log::Record::builder().args(format_args!("{}", text)).level(log::Level::Info).build());
+        };
+
+        // Buffered messages logged before the rename still land in the old file
+        log_line(&logger, "before rotation");
+        fs::rename(&path, &rotated).unwrap();
+        logger.reopen().unwrap();
+        log_line(&logger, "after rotation");
+        logger.flush_sync().unwrap();
+
+        assert!(fs::read_to_string(&rotated).unwrap().contains("before rotation"));
+        let current = fs::read_to_string(&path).unwrap();
+        assert!(current.contains("after rotation") && !current.contains("before rotation"));
+
+        logger.shutdown();
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&rotated);
+    }
+
+    #[cfg(all(unix, feature = "signals"))]
+    #[test]
+    fn test_sighup_reopens_files() {
+        let dir = std::env::temp_dir();
+        let (path, rotated) = (dir.join("sighup_test.log"), dir.join("sighup_test.log.1"));
+        let _ = fs::remove_file(&rotated);
+
+        let mut logger = ArtificeLogger::new().with_file(&path).unwrap();
+        logger.set_config(LogConfig { console: false, file: true, colors: false });
+        let handle = LoggerHandle { logger: Box::leak(Box::new(logger)) };
+        let _reopener = SighupReopener::install(handle).unwrap();
+
+        fs::rename(&path, &rotated).unwrap();
+        unsafe { libc::raise(libc::SIGHUP) };
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !path.exists() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(path.exists(), "SIGHUP did not recreate the log file");
+
+        handle.logger.shutdown();
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&rotated);
+    }
+
+    #[test]
+    fn test_latency_histogram_percentiles() {
+        let mut histogram = LatencyHistogram::new();
+        for micros in 1..=1000u64 {
+            histogram.record(Duration::from_micros(micros));
+        }
+
+        assert_eq!(histogram.count(), 1000);
+        assert_eq!(histogram.min(), Duration::from_micros(1));
+        assert_eq!(histogram.max(), Duration::from_micros(1000));
+
+        let within = |actual: Duration, expected_micros: u64| {
+            let expected = expected_micros as f64 * 1000.0;
+            (actual.as_nanos() as f64 - expected).abs() / expected < 0.02
+        };
+        let percentiles = histogram.percentiles();
+        assert!(within(percentiles.p50, 500), "{:?}", percentiles);
+        assert!(within(percentiles.p95, 950), "{:?}", percentiles);
+        assert!(within(percentiles.p99, 990), "{:?}", percentiles);
+        assert!(within(percentiles.p999, 999), "{:?}", percentiles);
+        assert_eq!(percentiles.max, Duration::from_micros(1000));
+
+        let mut meter = ThroughputMeter::new();
+        meter.record_latency(Duration::from_nanos(42));
+        assert_eq!(meter.time(|| 7), 7);
+        assert_eq!(meter.latencies().count(), 2);
+        assert_eq!(meter.latencies().min(), Duration::from_nanos(42));
+    }
+
+    #[test]
+    fn test_adaptive_batching_scales_with_load() {
+        let path = std::env::temp_dir().join("adaptive_sink.log");
+        let bounds = AdaptiveBatching {
+            min_batch_size: 4,
+            max_batch_size: 400,
+            min_flush_interval_ms: 10,
+            max_flush_interval_ms: 200,
+        };
+        let batch_config = BatchConfig { batch_size: 1, adaptive: Some(bounds), ..BatchConfig::default() };
+        let mut logger = ArtificeLogger::new().with_sink(FileSink::new(&path, batch_config).unwrap());
+        logger.set_config(LogConfig { console: false, file: false, colors: false });
+
+        // Configured values are clamped into the bounds before the first message
+        let stats = logger.stats();
+        assert_eq!(stats.batch_size, 4);
+        assert_eq!(stats.flush_interval, Duration::from_millis(100));
+
+        // A sustained burst scales batches up towards the maximum
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while logger.stats().batch_size < 100 && Instant::now() < deadline {
+            for i in 0..2000 {
+                logger.log(&log::Record::builder().args(format_args!("burst {}", i)).level(log::Level::Info).build());
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        let busy = logger.stats();
+        assert!(busy.batch_size >= 100, "{:?}", busy);
+        assert!(busy.flush_interval > Duration::from_millis(10), "{:?}", busy);
+
+        // Going quiet brings them back down to the minimum
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while logger.stats().batch_size > 4 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(50));
+        }
+        let quiet = logger.stats();
+        assert_eq!(quiet.batch_size, 4);
+        assert_eq!(quiet.flush_interval, Duration::from_millis(10));
+
+        logger.flush_sync().unwrap();
+        assert_eq!(logger.stats().messages_written, logger.stats().messages_received);
+        logger.shutdown();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_adaptive_batching_rejects_inverted_bounds() {
+        use crate::batching::LogCommand;
+
+        let inverted = AdaptiveBatching { min_batch_size: 10, max_batch_size: 5, ..AdaptiveBatching::default() };
+        let reject = |result: Result<(), LoggerError>| {
+            assert!(matches!(result, Err(LoggerError::InvalidBatchSize { batch_size: 10, .. })), "{:?}", result);
+        };
+        reject(inverted.validate());
+        reject(LoggerBuilder::new().adaptive_batching(inverted.clone()).validate());
+        let slow = AdaptiveBatching { min_flush_interval_ms: 600, ..AdaptiveBatching::default() };
+        assert!(slow.validate().is_err());
+        assert!(AdaptiveBatching { min_batch_size: 0, ..AdaptiveBatching::default() }.validate().is_err());
+
+        // Sinks starting their own worker check the bounds too instead of panicking
+        let path = std::env::temp_dir().join("inverted_bounds.log");
+        let batch_config = BatchConfig { adaptive: Some(inverted.clone()), ..BatchConfig::default() };
+        reject(FileSink::new(&path, batch_config.clone()).map(drop));
+        reject(LogFileConfig::parse("[batch]\nmax_batch_size = 5").map(drop));
+
+        // The bare worker thread has no way to report them, so it runs within the minimums
+        let (sender, receiver) = mpsc::channel();
+        let writer = FileWriter::new(fs::File::create(&path).unwrap(), batch_config);
+        let worker = thread::spawn(move || writers::file_worker_thread(writer, receiver));
+        sender.send(LogCommand::Message(LogMessage::plain("kept\n".to_string()))).unwrap();
+        sender.send(LogCommand::Shutdown).unwrap();
+        worker.join().unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("kept"));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_write_error_handler_and_fallback() {
+        // Every write to /dev/full fails with "no space left on device"
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&errors);
+        let batch_config = BatchConfig { batch_size: 2, flush_interval_ms: 10_000, ..BatchConfig::default() };
+        let mut logger = ArtificeLogger::new()
+            .on_write_error(move |error| seen.lock().unwrap().push(error))
+            .with_sink(FileSink::new("/dev/full", batch_config).unwrap());
+        logger.set_config(LogConfig { console: false, file: false, colors: false });
+
+        for i in 0..2 {
+            logger.log(&log::Record::builder().args(format_args!("lost {}", i)).level(log::Level::Error).build());
+        }
+        // Waits for the worker; fsync on a device fails too, which is reported as well
+        let _ = logger.flush_sync();
+
+        // The full batch failed, was reported and went to the console instead
+        let errors = errors.lock().unwrap();
+        assert!(matches!(errors.first(), Some(LoggerError::Io(e)) if e.raw_os_error() == Some(28)), "{:?}", errors);
+        let stats = logger.stats();
+        assert_eq!(stats.messages_received, 2);
+        assert_eq!(stats.messages_written, 0);
+        assert_eq!(stats.queue_depth, 0);
+        logger.shutdown();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_failed_write_hands_back_only_unwritten_lines() {
+        use crate::writers::BatchWriter;
+        use std::io::pipe;
+        use std::os::fd::OwnedFd;
+
+        // A pipe takes the first line, then blocks on a line larger than its
+        // buffer until the reader goes away and the write fails part way
+        let (mut reader, pipe_writer) = pipe().unwrap();
+        let file = fs::File::from(OwnedFd::from(pipe_writer));
+        let batch_config = BatchConfig { batch_size: 100, ..BatchConfig::default() };
+        let mut writer = FileWriter::new(file, batch_config);
+        let huge = "x".repeat(1 << 20);
+        for line in ["first", huge.as_str(), "last"] {
+            BatchWriter::add_message(&mut writer, LogMessage::plain(line.to_string())).unwrap();
+        }
+        let closer = thread::spawn(move || {
+            let mut first = [0; 6];
+            reader.read_exact(&mut first).unwrap();
+            assert_eq!(&first, b"first\n");
+        });
+
+        assert!(BatchWriter::flush(&mut writer).is_err());
+        closer.join().unwrap();
+        let unwritten = BatchWriter::take_unwritten(&mut writer).unwrap();
+        let unwritten: Vec<&str> = unwritten.iter().map(|message| message.formatted_message.as_str()).collect();
+        assert_eq!(unwritten, [huge.as_str(), "last"]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_write_error_handler_can_log() {
+        // The handler logs through the failing sink while its channel is full
+        let batch_config = BatchConfig {
+            batch_size: 1,
+            channel_capacity: Some(1),
+            ..BatchConfig::default()
+        };
+        let sink = Arc::new(FileSink::new("/dev/full", batch_config).unwrap());
+        let weak = Arc::downgrade(&sink);
+        let first = AtomicBool::new(true);
+        let (results, handled) = std::sync::mpsc::channel();
+        sink.on_write_error(Arc::new(move |_| {
+            let Some(sink) = weak.upgrade().filter(|_| first.swap(false, Ordering::SeqCst)) else {
+                return;
+            };
+            for i in 0..4 {
+                sink.log(&log::Record::builder().args(format_args!("handler {}", i)).level(log::Level::Warn).build());
+            }
+            let _ = results.send(sink.flush_sync());
+        }));
+
+        for i in 0..4 {
+            sink.log(&log::Record::builder().args(format_args!("lost {}", i)).level(log::Level::Error).build());
+        }
+        // Without the guard the worker would wait for itself here
+        let result = handled.recv_timeout(Duration::from_secs(5)).expect("the handler deadlocked");
+        assert!(matches!(result, Err(LoggerError::ChannelError)));
+        drop(sink);
+    }
+
+    #[test]
+    fn test_shared_file_lines_stay_whole() {
+        for sharing in [FileSharing::Locked, FileSharing::AtomicAppend] {
+            let path = std::env::temp_dir().join(format!("shared_{:?}.log", sharing));
+            fs::write(&path, "previous run\n").unwrap();
+
+            // Separate file descriptions contend like separate processes
+            let batch_config = BatchConfig { batch_size: 64, sharing, ..BatchConfig::default() };
+            let writers: Vec<_> = (0..4)
+                .map(|id| {
+                    let sink = FileSink::new(&path, batch_config.clone()).unwrap();
+                    thread::spawn(move || {
+                        let padding = "x".repeat(100 + id * 300);
+                        for i in 0..500 {
+                            sink.log(&log::Record::builder()
+                                .args(format_args!("writer={} line={} {}", id, i, padding))
+                                .level(log::Level::Info)
+                                .build());
+                        }
+                        sink.flush_sync().unwrap();
+                    })
+                })
+                .collect();
+            for writer in writers {
+                writer.join().unwrap();
+            }
+
+            let contents = fs::read_to_string(&path).unwrap();
+            let lines: Vec<&str> = contents.lines().collect();
+            assert_eq!(lines[0], "previous run");
+            assert_eq!(lines.len(), 1 + 4 * 500);
+            for line in &lines[1..] {
+                let (_, message) = line.split_once("INFO: writer=").unwrap();
+                let id: usize = message[..1].parse().unwrap();
+                assert!(message.ends_with(&"x".repeat(100 + id * 300)), "{}", line);
+                assert_eq!(message.matches("writer=").count(), 0, "{}", line);
+            }
+            let _ = fs::remove_file(&path);
+        }
+    }
+
+    #[test]
+    fn test_log_scope_timing() {
+        let path = std::env::temp_dir().join("log_scope.log");
+        let logger = LoggerBuilder::new()
+            .console(false)
+            .file(path.to_string_lossy())
+            .show_target(true)
+            .build()
+            .unwrap();
+        let logger: &'static ArtificeLogger = Box::leak(Box::new(logger));
+
+        let mut measured = Duration::ZERO;
+        {
+            let _scope = ScopeTimer::new("scope_test_load")
+                .at(scope::Callsite { module_path: module_path!(), file: file!(), line: line!() })
+                .with_logger(logger)
+                .on_exit(|elapsed| measured = elapsed)
+                .enter();
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(measured >= Duration::from_millis(5));
+
+        let elapsed = ScopeTimer::new("scope_test_manual")
+            .with_level(log::Level::Info)
+            .with_logger(logger)
+            .enter()
+            .finish();
+        logger.flush_sync().unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 4, "{}", contents);
+        assert!(lines[0].contains("DEBUG artifice_logging::tests: scope_test_load started"), "{}", lines[0]);
+        assert!(lines[1].contains("scope_test_load finished in"), "{}", lines[1]);
+        assert!(lines[1].ends_with(&format!("elapsed_us={}", measured.as_micros())), "{}", lines[1]);
+        assert!(lines[2].contains("INFO artifice_logging::scope: scope_test_manual started"), "{}", lines[2]);
+        assert!(lines[3].ends_with(&format!("elapsed_us={}", elapsed.as_micros())), "{}", lines[3]);
+
+        logger.shutdown();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_crash_ring_keeps_latest_messages() {
+        let path = std::env::temp_dir().join("crash_ring.log");
+        let _ = fs::remove_file(&path);
+
+        let ring = CrashRing::new(&path, 3);
+        for i in 0..5 {
+            ring.record(&format!("message {}", i));
+        }
+        ring.record(&"x".repeat(2000));
+        ring.dump("test crash").unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 4, "{}", contents);
+        assert_eq!(lines[0], "=== test crash ===");
+        assert_eq!(&lines[1..3], ["message 3", "message 4"]);
+        // Lines longer than a slot are cut off rather than dropped
+        assert!(lines[3].len() < 2000 && lines[3].bytes().all(|b| b == b'x'), "{}", lines[3]);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_batch_sink_receives_batches() {
+        #[derive(Clone, Default)]
+        struct Collector {
+            batches: Arc<Mutex<Vec<Vec<String>>>>,
+            shut_down: Arc<AtomicBool>,
+        }
+
+        impl BatchSink for Collector {
+            fn write_batch(&mut self, records: &[FormattedRecord]) -> std::io::Result<()> {
+                let lines = records.iter().map(|record| record.line.clone()).collect();
+                self.batches.lock().unwrap().push(lines);
+                Ok(())
+            }
+
+            fn shutdown(&mut self) -> std::io::Result<()> {
+                self.shut_down.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+
+        let collector = Collector::default();
+        let logger = LoggerBuilder::new()
+            .console(false)
+            .batch_size(3)
+            .flush_interval_ms(10_000)
+            .writer(collector.clone())
+            .build()
+            .unwrap();
+        let logger: &'static ArtificeLogger = Box::leak(Box::new(logger));
+
+        for i in 0..7 {
+            logger.log(&Record::builder().args(format_args!("custom {}", i)).level(log::Level::Info).build());
+        }
+        logger.flush_sync().unwrap();
+
+        let batches = collector.batches.lock().unwrap().clone();
+        assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), [3, 3, 1]);
+        assert!(batches[2][0].ends_with("INFO: custom 6"), "{}", batches[2][0]);
+
+        logger.shutdown();
+        assert!(collector.shut_down.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,88 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// Initial capacity of freshly allocated message buffers
+const BUFFER_CAPACITY: usize = 256;
+/// Buffers that grew beyond this are freed instead of pooled
+const MAX_RETAINED_CAPACITY: usize = 4096;
+/// Buffers a producer thread keeps without touching the shared pool
+const LOCAL_LIMIT: usize = 32;
+/// Buffers moved from the shared pool to a producer thread at once
+const REFILL_COUNT: usize = 16;
+
+/// Buffers handed back by the writer threads, waiting for a producer
+static SHARED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// Upper bound on `SHARED`, raised by `reserve`
+static SHARED_CAPACITY: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static LOCAL: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Grow the shared pool to hold at least `buffers` pre-allocated strings
+///
+/// Called with `string_pool_size` whenever a batching worker starts.
+pub(crate) fn reserve(buffers: usize) {
+    if SHARED_CAPACITY.fetch_max(buffers, Ordering::Relaxed) >= buffers {
+        return;
+    }
+
+    let mut shared = SHARED.lock().unwrap_or_else(PoisonError::into_inner);
+    let missing = buffers.saturating_sub(shared.len());
+    shared.reserve(missing);
+    while shared.len() < buffers {
+        shared.push(String::with_capacity(BUFFER_CAPACITY));
+    }
+}
+
+/// Take an empty buffer for formatting a message
+///
+/// Comes from the calling thread's cache, which is refilled from the buffers
+/// recycled by the writer threads; only allocates when both are empty.
+pub(crate) fn take() -> String {
+    LOCAL
+        .try_with(|local| {
+            let mut local = local.borrow_mut();
+            if local.is_empty() {
+                let mut shared = SHARED.lock().unwrap_or_else(PoisonError::into_inner);
+                let start = shared.len().saturating_sub(REFILL_COUNT);
+                local.extend(shared.drain(start..));
+            }
+            local.pop()
+        })
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| String::with_capacity(BUFFER_CAPACITY))
+}
+
+/// Return a buffer that was not sent anywhere to the calling thread's cache
+pub(crate) fn put(mut buffer: String) {
+    if buffer.capacity() > MAX_RETAINED_CAPACITY {
+        return;
+    }
+
+    buffer.clear();
+    let _ = LOCAL.try_with(|local| {
+        let mut local = local.borrow_mut();
+        if local.len() < LOCAL_LIMIT {
+            local.push(buffer);
+        }
+    });
+}
+
+/// Hand written messages back to the shared pool
+pub(crate) fn recycle<I: IntoIterator<Item = String>>(buffers: I) {
+    let capacity = SHARED_CAPACITY.load(Ordering::Relaxed);
+    let mut shared = SHARED.lock().unwrap_or_else(PoisonError::into_inner);
+
+    for mut buffer in buffers {
+        if shared.len() >= capacity {
+            break;
+        }
+        if buffer.capacity() <= MAX_RETAINED_CAPACITY {
+            buffer.clear();
+            shared.push(buffer);
+        }
+    }
+}
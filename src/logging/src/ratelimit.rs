@@ -0,0 +1,106 @@
+use log::Record;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::RateLimitConfig;
+
+/// Entries idle for this many windows are forgotten once the table grows large
+const STALE_WINDOWS: u32 = 4;
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Outcome of checking a record against the rate limiter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    /// Log the record
+    Allow,
+    /// Log the record, after reporting how many were suppressed in the previous window
+    AllowAfterSuppressed(u64),
+    /// Drop the record
+    Suppress,
+}
+
+struct CallsiteState {
+    window_start: Instant,
+    count: u32,
+    suppressed: u64,
+}
+
+/// Tracks per-callsite message counts for `RateLimitConfig`
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    callsites: Mutex<HashMap<u64, CallsiteState>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter with no recorded history
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            callsites: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the active rate limit configuration
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Records from the same file and line share a budget; records without
+    /// location information are grouped by their message text instead
+    fn callsite_key(record: &Record) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        record.level().hash(&mut hasher);
+        record.target().hash(&mut hasher);
+        match (record.file(), record.line()) {
+            (Some(file), Some(line)) => (file, line).hash(&mut hasher),
+            _ => record.args().to_string().hash(&mut hasher),
+        }
+        hasher.finish()
+    }
+
+    /// Count a record against its callsite budget
+    pub fn check(&self, record: &Record) -> RateLimitDecision {
+        let key = Self::callsite_key(record);
+        let window = Duration::from_millis(self.config.window_ms);
+        let now = Instant::now();
+
+        let mut callsites = match self.callsites.lock() {
+            Ok(callsites) => callsites,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if callsites.len() > PRUNE_THRESHOLD {
+            let stale = window * STALE_WINDOWS;
+            callsites.retain(|_, state| now.duration_since(state.window_start) < stale);
+        }
+
+        let state = callsites.entry(key).or_insert(CallsiteState {
+            window_start: now,
+            count: 0,
+            suppressed: 0,
+        });
+
+        let mut previously_suppressed = 0;
+        if now.duration_since(state.window_start) >= window {
+            previously_suppressed = state.suppressed;
+            state.window_start = now;
+            state.count = 0;
+            state.suppressed = 0;
+        }
+
+        if state.count >= self.config.max_messages {
+            state.suppressed += 1;
+            return RateLimitDecision::Suppress;
+        }
+
+        state.count += 1;
+        if previously_suppressed > 0 {
+            RateLimitDecision::AllowAfterSuppressed(previously_suppressed)
+        } else {
+            RateLimitDecision::Allow
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+use log::{kv, Level, Log, Record};
+
+/// Where a timed scope was opened, captured by `log_scope!`
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct Callsite {
+    pub module_path: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+}
+
+/// Logs when a scope is entered and, on drop, how long it took
+///
+/// Usually created through `log_scope!`; build one by hand to pick the level
+/// or to end the scope early. Timers built by hand log under the
+/// `artifice_logging::scope` target rather than the caller's module.
+///
+/// ```rust
+/// use artifice_logging::ScopeTimer;
+///
+/// let timer = ScopeTimer::new("load_assets").with_level(log::Level::Info).enter();
+/// // ... load ...
+/// let elapsed = timer.finish();
+/// ```
+#[must_use = "the scope is timed until the timer is dropped"]
+pub struct ScopeTimer<F: FnOnce(Duration) = fn(Duration)> {
+    name: Cow<'static, str>,
+    level: Level,
+    callsite: Option<Callsite>,
+    logger: Option<&'static dyn Log>,
+    on_exit: Option<F>,
+    start: Instant,
+    finished: bool,
+}
+
+impl ScopeTimer {
+    /// Start timing `name`, logging at debug level
+    pub fn new<N: Into<Cow<'static, str>>>(name: N) -> Self {
+        Self {
+            name: name.into(),
+            level: Level::Debug,
+            callsite: None,
+            logger: None,
+            on_exit: None,
+            start: Instant::now(),
+            finished: false,
+        }
+    }
+}
+
+impl<F: FnOnce(Duration)> ScopeTimer<F> {
+    /// Log entry and exit at `level` instead of debug
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Also pass the elapsed time to `on_exit` when the scope ends
+    pub fn on_exit<G: FnOnce(Duration)>(mut self, on_exit: G) -> ScopeTimer<G> {
+        // `self` is consumed without logging its exit
+        self.finished = true;
+        ScopeTimer {
+            name: std::mem::take(&mut self.name),
+            level: self.level,
+            callsite: self.callsite,
+            logger: self.logger,
+            on_exit: Some(on_exit),
+            start: self.start,
+            finished: false,
+        }
+    }
+
+    /// Send the records to `logger` instead of the global logger
+    pub fn with_logger(mut self, logger: &'static dyn Log) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// Attribute the records to the code that opened the scope
+    #[doc(hidden)]
+    pub fn at(mut self, callsite: Callsite) -> Self {
+        self.callsite = Some(callsite);
+        self
+    }
+
+    /// Log that the scope was entered and restart the clock
+    pub fn enter(mut self) -> Self {
+        self.emit(format_args!("{} started", self.name), &[]);
+        self.start = Instant::now();
+        self
+    }
+
+    /// Time elapsed since the scope was entered
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// End the scope now, returning how long it took
+    pub fn finish(mut self) -> Duration {
+        self.exit()
+    }
+
+    fn exit(&mut self) -> Duration {
+        self.finished = true;
+        let elapsed = self.start.elapsed();
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.emit(
+            format_args!("{} finished in {:?}", self.name, elapsed),
+            &[("elapsed_us", kv::Value::from(micros))],
+        );
+        if let Some(on_exit) = self.on_exit.take() {
+            on_exit(elapsed);
+        }
+        elapsed
+    }
+
+    fn emit(&self, args: std::fmt::Arguments, fields: &[(&str, kv::Value)]) {
+        let logger = match self.logger {
+            Some(logger) => logger,
+            None if self.level > log::max_level() => return,
+            None => log::logger(),
+        };
+
+        let mut record = Record::builder();
+        record.args(args).level(self.level).key_values(&fields);
+        match self.callsite {
+            Some(callsite) => record
+                .target(callsite.module_path)
+                .module_path_static(Some(callsite.module_path))
+                .file_static(Some(callsite.file))
+                .line(Some(callsite.line)),
+            None => record.target(module_path!()),
+        };
+
+        let record = record.build();
+        if logger.enabled(record.metadata()) {
+            logger.log(&record);
+        }
+    }
+}
+
+impl<F: FnOnce(Duration)> Drop for ScopeTimer<F> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.exit();
+        }
+    }
+}
+
+/// Log entry and exit of the enclosing scope with the time spent in it
+///
+/// Records go out at debug level with the caller's module as target; the
+/// exit record carries an `elapsed_us` field. An optional closure also
+/// receives the elapsed `Duration`, e.g. to feed the engine's metrics.
+///
+/// ```rust
+/// use artifice_logging::log_scope;
+///
+/// fn load_assets() {
+///     log_scope!("load_assets");
+///     // ... "load_assets started" ... "load_assets finished in 12.3ms elapsed_us=12345"
+/// }
+///
+/// let mut slowest = std::time::Duration::ZERO;
+/// {
+///     log_scope!("upload_textures", |elapsed| slowest = slowest.max(elapsed));
+/// }
+/// ```
+#[macro_export]
+macro_rules! log_scope {
+    ($name:expr) => {
+        let _log_scope = $crate::ScopeTimer::new($name)
+            .at($crate::scope::Callsite { module_path: module_path!(), file: file!(), line: line!() })
+            .enter();
+    };
+    ($name:expr, $on_exit:expr) => {
+        let _log_scope = $crate::ScopeTimer::new($name)
+            .at($crate::scope::Callsite { module_path: module_path!(), file: file!(), line: line!() })
+            .on_exit($on_exit)
+            .enter();
+    };
+}
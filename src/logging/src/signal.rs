@@ -0,0 +1,73 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::LoggerHandle;
+
+/// How often the reopener checks whether SIGHUP arrived
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Set by the signal handler, cleared by the reopener thread
+static SIGHUP_RECEIVED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_sighup(_: libc::c_int) {
+    // Only async-signal-safe work here; the reopener thread does the rest
+    SIGHUP_RECEIVED.store(true, Ordering::SeqCst);
+}
+
+/// Reopens the log files whenever the process receives SIGHUP
+///
+/// This is the usual contract with logrotate: rotate the file, then
+/// `postrotate kill -HUP <pid>`. Dropping the reopener stops reacting to
+/// the signal; the handler itself stays installed so SIGHUP no longer
+/// terminates the process.
+pub struct SighupReopener {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl SighupReopener {
+    /// Install a SIGHUP handler and reopen `handle`'s files on every signal
+    ///
+    /// Only one reopener should exist per process.
+    pub fn install(handle: LoggerHandle) -> io::Result<Self> {
+        // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_sighup as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            if libc::sigaction(libc::SIGHUP, &action, std::ptr::null_mut()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(POLL_INTERVAL) {
+                if SIGHUP_RECEIVED.swap(false, Ordering::SeqCst) {
+                    match handle.reopen() {
+                        Ok(()) => log::info!("Reopened log files after SIGHUP"),
+                        Err(e) => eprintln!("Failed to reopen log files after SIGHUP: {}", e),
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for SighupReopener {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
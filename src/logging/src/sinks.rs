@@ -0,0 +1,753 @@
+use log::{Level, LevelFilter, Metadata, Record};
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+#[cfg(target_os = "linux")]
+use std::{os::unix::net::UnixDatagram, path::PathBuf, sync::atomic::AtomicU64};
+
+use crate::batching::LogMessage;
+use crate::config::{BatchConfig, ColorTheme, DiskQuota, LogLevel, LoggerError, RotationPolicy};
+#[cfg(target_os = "linux")]
+use crate::{context, format::{record_fields, FieldValue}};
+use crate::format::{DefaultFormatter, LogFormatter};
+use crate::pool;
+use crate::writers::{
+    open_log_file, DeliveryStats, FileWriter, LoggerStats, NetworkProtocol, NetworkStats, NetworkWriter, RoutedFileWriter, SinkWriter, WorkerHandle,
+    WriteErrorHandler,
+};
+
+/// An output destination attached to an `ArtificeLogger`
+///
+/// Each sink decides which records it accepts and how they are formatted, so
+/// a logger can feed e.g. a colored console, a verbose debug file and a
+/// warnings-only file at the same time.
+pub trait LogSink: Send + Sync {
+    /// Whether this sink wants records with the given metadata
+    fn enabled(&self, metadata: &Metadata) -> bool;
+
+    /// Change the most verbose level this sink accepts while it is in use
+    ///
+    /// Sinks without a level of their own ignore this.
+    fn set_level(&self, _level: LogLevel) {}
+
+    /// Color with the logger's `ColorTheme` unless the sink's formatter has its own
+    ///
+    /// Called when the sink is attached to a logger; sinks without a
+    /// formatter ignore it.
+    fn inherit_theme(&mut self, _theme: &ColorTheme) {}
+
+    /// Write a record that passed `enabled`
+    fn log(&self, record: &Record);
+
+    /// Flush any buffered output
+    fn flush(&self);
+
+    /// Flush and block until buffered output is durably written
+    fn flush_sync(&self) -> Result<(), LoggerError> {
+        self.flush();
+        Ok(())
+    }
+
+    /// Reopen output files by path, e.g. after logrotate moved them away
+    fn reopen(&self) -> Result<(), LoggerError> {
+        Ok(())
+    }
+
+    /// Write out everything buffered and stop any background work
+    fn shutdown(&self) {
+        self.flush();
+    }
+
+    /// Messages discarded by the backpressure policy
+    fn dropped_messages(&self) -> u64 {
+        0
+    }
+
+    /// Delivery counters for sinks that ship logs remotely
+    fn delivery_stats(&self) -> Option<DeliveryStats> {
+        None
+    }
+
+    /// Pipeline counters for sinks served by a batching worker thread
+    fn stats(&self) -> Option<LoggerStats> {
+        None
+    }
+
+    /// Report failed background writes to `handler` instead of standard error
+    ///
+    /// Only sinks served by a batching worker thread report failures; others ignore this.
+    fn on_write_error(&self, _handler: WriteErrorHandler) {}
+}
+
+/// A record rendered by a sink's formatter, as handed to a `BatchSink`
+#[derive(Debug, Clone)]
+pub struct FormattedRecord {
+    /// The formatted line, without a trailing newline
+    pub line: String,
+    /// Level of the record; `None` for lines the logger adds itself, such as repeat summaries
+    pub level: Option<Level>,
+    /// When the record was logged
+    pub timestamp: Instant,
+}
+
+/// The writing end of a user-defined output, fed batches by a worker thread
+///
+/// Implement this to send logs somewhere the crate does not cover (a
+/// database, an RPC service, an in-game overlay) and attach it with
+/// `WriterSink` or `LoggerBuilder::writer`. Batching, level filtering,
+/// formatting, backpressure and stats are handled by the logger; the sink
+/// only writes. When `write_batch` fails the batch is reported through
+/// `on_write_error` and printed to standard error instead.
+///
+/// ```rust
+/// use artifice_logging::{BatchSink, FormattedRecord, LoggerBuilder};
+///
+/// struct Overlay {
+///     lines: Vec<String>,
+/// }
+///
+/// impl BatchSink for Overlay {
+///     fn write_batch(&mut self, records: &[FormattedRecord]) -> std::io::Result<()> {
+///         self.lines.extend(records.iter().map(|record| record.line.clone()));
+///         Ok(())
+///     }
+/// }
+///
+/// let logger = LoggerBuilder::new().console(false).writer(Overlay { lines: Vec::new() }).build()?;
+/// # Ok::<(), artifice_logging::LoggerError>(())
+/// ```
+pub trait BatchSink: Send {
+    /// Write a batch of records, oldest first
+    fn write_batch(&mut self, records: &[FormattedRecord]) -> io::Result<()>;
+
+    /// Push written records out of any buffer of the sink's own
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Release the output once the final batch has been written
+    fn shutdown(&mut self) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+impl<S: BatchSink + ?Sized> BatchSink for Box<S> {
+    fn write_batch(&mut self, records: &[FormattedRecord]) -> io::Result<()> {
+        (**self).write_batch(records)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        (**self).shutdown()
+    }
+}
+
+/// Format `record` into a pooled buffer ready to send to a worker
+pub(crate) fn pooled_message(formatter: &dyn LogFormatter, record: &Record) -> LogMessage {
+    let mut line = pool::take();
+    formatter.format_into(record, &mut line);
+    LogMessage::from_record(line, record)
+}
+
+/// Level filter that can be changed through a shared reference
+pub(crate) struct SinkLevel(AtomicUsize);
+
+impl SinkLevel {
+    pub(crate) fn new(level: LevelFilter) -> Self {
+        Self(AtomicUsize::new(level as usize))
+    }
+
+    pub(crate) fn get(&self) -> LevelFilter {
+        LevelFilter::iter()
+            .nth(self.0.load(Ordering::Relaxed))
+            .unwrap_or(LevelFilter::Trace)
+    }
+
+    pub(crate) fn set(&self, level: LevelFilter) {
+        self.0.store(level as usize, Ordering::Relaxed);
+    }
+}
+
+/// Sink writing formatted records to stdout
+pub struct ConsoleSink {
+    formatter: Box<dyn LogFormatter>,
+    level: SinkLevel,
+}
+
+impl ConsoleSink {
+    /// Create a console sink using the default format
+    pub fn new(colors: bool) -> Self {
+        Self {
+            formatter: Box::new(DefaultFormatter::new(colors)),
+            level: SinkLevel::new(LevelFilter::Trace),
+        }
+    }
+
+    /// Use a custom formatter for this sink
+    pub fn with_formatter<F: LogFormatter + 'static>(mut self, formatter: F) -> Self {
+        self.formatter = Box::new(formatter);
+        self
+    }
+
+    /// Only accept records at or above `level`
+    pub fn with_level(self, level: LogLevel) -> Self {
+        self.level.set(level.into());
+        self
+    }
+}
+
+impl Default for ConsoleSink {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl LogSink for ConsoleSink {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level.get()
+    }
+
+    fn set_level(&self, level: LogLevel) {
+        self.level.set(level.into());
+    }
+
+    fn inherit_theme(&mut self, theme: &ColorTheme) {
+        self.formatter.inherit_theme(theme);
+    }
+
+    fn log(&self, record: &Record) {
+        let mut line = pool::take();
+        self.formatter.format_into(record, &mut line);
+        println!("{}", line);
+        pool::put(line);
+    }
+
+    fn flush(&self) {}
+}
+
+/// Sink writing formatted records to a file through a batching worker thread
+pub struct FileSink {
+    formatter: Box<dyn LogFormatter>,
+    level: SinkLevel,
+    worker: WorkerHandle,
+}
+
+impl FileSink {
+    /// Create (or truncate) `path` and write to it
+    ///
+    /// With a shared `BatchConfig::sharing` mode the file is appended to instead.
+    pub fn new<P: AsRef<Path>>(path: P, batch_config: BatchConfig) -> Result<Self, LoggerError> {
+        let file = open_log_file(path.as_ref(), batch_config.sharing)?;
+        Self::from_writer(FileWriter::new(file, batch_config))
+    }
+
+    /// Write to date-stamped files derived from `path`, rolling over per `policy`
+    pub fn rotating<P: AsRef<Path>>(
+        path: P,
+        policy: RotationPolicy,
+        batch_config: BatchConfig,
+    ) -> Result<Self, LoggerError> {
+        Self::from_writer(FileWriter::rotating(path, policy, batch_config)?)
+    }
+
+    /// Like `rotating`, keeping the files within a total size budget
+    pub fn with_quota<P: AsRef<Path>>(
+        path: P,
+        policy: RotationPolicy,
+        quota: DiskQuota,
+        batch_config: BatchConfig,
+    ) -> Result<Self, LoggerError> {
+        Self::from_writer(FileWriter::rotating(path, policy, batch_config)?.with_quota(quota))
+    }
+
+    fn from_writer(writer: FileWriter) -> Result<Self, LoggerError> {
+        Ok(Self {
+            formatter: Box::new(DefaultFormatter::new(false)),
+            level: SinkLevel::new(LevelFilter::Trace),
+            worker: WorkerHandle::spawn(writer)?,
+        })
+    }
+
+    /// Use a custom formatter for this sink
+    pub fn with_formatter<F: LogFormatter + 'static>(mut self, formatter: F) -> Self {
+        self.formatter = Box::new(formatter);
+        self
+    }
+
+    /// Only accept records at or above `level`
+    pub fn with_level(self, level: LogLevel) -> Self {
+        self.level.set(level.into());
+        self
+    }
+}
+
+impl LogSink for FileSink {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level.get()
+    }
+
+    fn set_level(&self, level: LogLevel) {
+        self.level.set(level.into());
+    }
+
+    fn inherit_theme(&mut self, theme: &ColorTheme) {
+        self.formatter.inherit_theme(theme);
+    }
+
+    fn log(&self, record: &Record) {
+        self.worker.send(pooled_message(self.formatter.as_ref(), record));
+    }
+
+    fn flush(&self) {
+        self.worker.flush();
+    }
+
+    fn flush_sync(&self) -> Result<(), LoggerError> {
+        self.worker.flush_sync()
+    }
+
+    fn reopen(&self) -> Result<(), LoggerError> {
+        self.worker.reopen()
+    }
+
+    fn shutdown(&self) {
+        self.worker.shutdown();
+    }
+
+    fn dropped_messages(&self) -> u64 {
+        self.worker.dropped()
+    }
+
+    fn stats(&self) -> Option<LoggerStats> {
+        Some(self.worker.stats())
+    }
+
+    fn on_write_error(&self, handler: WriteErrorHandler) {
+        self.worker.on_write_error(handler);
+    }
+}
+
+/// Sink writing records to several files by level, served by one worker thread
+///
+/// ```rust,no_run
+/// use artifice_logging::{BatchConfig, LogLevel, RoutedFileSink};
+///
+/// let sink = RoutedFileSink::new(
+///     [("app.log", LogLevel::Trace), ("errors.log", LogLevel::Warn)],
+///     BatchConfig::default(),
+/// )?;
+/// # Ok::<(), artifice_logging::LoggerError>(())
+/// ```
+pub struct RoutedFileSink {
+    formatter: Box<dyn LogFormatter>,
+    level: SinkLevel,
+    worker: WorkerHandle,
+}
+
+impl RoutedFileSink {
+    /// Create (or truncate) each file, writing records at or above its level to it
+    pub fn new<P, I>(routes: I, batch_config: BatchConfig) -> Result<Self, LoggerError>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = (P, LogLevel)>,
+    {
+        Self::rotating(routes, RotationPolicy::Never, batch_config)
+    }
+
+    /// Like `new`, rolling every file over per `policy`
+    pub fn rotating<P, I>(routes: I, policy: RotationPolicy, batch_config: BatchConfig) -> Result<Self, LoggerError>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = (P, LogLevel)>,
+    {
+        let mut writer = RoutedFileWriter::new(batch_config.clone());
+        for (path, level) in routes {
+            writer = writer.route(level.into(), FileWriter::rotating(path, policy, batch_config.clone())?);
+        }
+
+        Ok(Self {
+            formatter: Box::new(DefaultFormatter::new(false)),
+            level: SinkLevel::new(writer.max_level()),
+            worker: WorkerHandle::spawn(writer)?,
+        })
+    }
+
+    /// Use a custom formatter for this sink
+    pub fn with_formatter<F: LogFormatter + 'static>(mut self, formatter: F) -> Self {
+        self.formatter = Box::new(formatter);
+        self
+    }
+
+    /// Only accept records at or above `level`, whatever the routes allow
+    pub fn with_level(self, level: LogLevel) -> Self {
+        self.level.set(level.into());
+        self
+    }
+}
+
+impl LogSink for RoutedFileSink {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level.get()
+    }
+
+    fn set_level(&self, level: LogLevel) {
+        self.level.set(level.into());
+    }
+
+    fn inherit_theme(&mut self, theme: &ColorTheme) {
+        self.formatter.inherit_theme(theme);
+    }
+
+    fn log(&self, record: &Record) {
+        self.worker.send(pooled_message(self.formatter.as_ref(), record));
+    }
+
+    fn flush(&self) {
+        self.worker.flush();
+    }
+
+    fn flush_sync(&self) -> Result<(), LoggerError> {
+        self.worker.flush_sync()
+    }
+
+    fn reopen(&self) -> Result<(), LoggerError> {
+        self.worker.reopen()
+    }
+
+    fn shutdown(&self) {
+        self.worker.shutdown();
+    }
+
+    fn dropped_messages(&self) -> u64 {
+        self.worker.dropped()
+    }
+
+    fn stats(&self) -> Option<LoggerStats> {
+        Some(self.worker.stats())
+    }
+
+    fn on_write_error(&self, handler: WriteErrorHandler) {
+        self.worker.on_write_error(handler);
+    }
+}
+
+/// Sink shipping formatted records to a remote collector over TCP or UDP
+pub struct NetworkSink {
+    formatter: Box<dyn LogFormatter>,
+    level: SinkLevel,
+    stats: Arc<NetworkStats>,
+    worker: WorkerHandle,
+}
+
+impl NetworkSink {
+    /// Ship logs to `address` (e.g. `"127.0.0.1:5140"`); the connection is made lazily
+    ///
+    /// Fails only if `batch_config` asks for a channel that cannot be allocated.
+    pub fn new<A: Into<String>>(
+        address: A,
+        protocol: NetworkProtocol,
+        batch_config: BatchConfig,
+    ) -> Result<Self, LoggerError> {
+        let writer = NetworkWriter::new(address, protocol, batch_config);
+        let stats = writer.stats();
+
+        Ok(Self {
+            formatter: Box::new(DefaultFormatter::new(false)),
+            level: SinkLevel::new(LevelFilter::Trace),
+            stats,
+            worker: WorkerHandle::spawn(writer)?,
+        })
+    }
+
+    /// Use a custom formatter for this sink
+    pub fn with_formatter<F: LogFormatter + 'static>(mut self, formatter: F) -> Self {
+        self.formatter = Box::new(formatter);
+        self
+    }
+
+    /// Only accept records at or above `level`
+    pub fn with_level(self, level: LogLevel) -> Self {
+        self.level.set(level.into());
+        self
+    }
+
+    /// Current delivery counters
+    pub fn stats(&self) -> DeliveryStats {
+        self.stats.snapshot()
+    }
+}
+
+impl LogSink for NetworkSink {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level.get()
+    }
+
+    fn set_level(&self, level: LogLevel) {
+        self.level.set(level.into());
+    }
+
+    fn inherit_theme(&mut self, theme: &ColorTheme) {
+        self.formatter.inherit_theme(theme);
+    }
+
+    fn log(&self, record: &Record) {
+        self.worker.send(pooled_message(self.formatter.as_ref(), record));
+    }
+
+    fn flush(&self) {
+        self.worker.flush();
+    }
+
+    fn flush_sync(&self) -> Result<(), LoggerError> {
+        self.worker.flush_sync()
+    }
+
+    fn shutdown(&self) {
+        self.worker.shutdown();
+    }
+
+    fn dropped_messages(&self) -> u64 {
+        self.worker.dropped()
+    }
+
+    fn stats(&self) -> Option<LoggerStats> {
+        Some(self.worker.stats())
+    }
+
+    fn on_write_error(&self, handler: WriteErrorHandler) {
+        self.worker.on_write_error(handler);
+    }
+
+    fn delivery_stats(&self) -> Option<DeliveryStats> {
+        Some(self.stats.snapshot())
+    }
+}
+
+/// Sink feeding a user-defined `BatchSink` through a batching worker thread
+pub struct WriterSink {
+    formatter: Box<dyn LogFormatter>,
+    level: SinkLevel,
+    worker: WorkerHandle,
+}
+
+impl WriterSink {
+    /// Batch records for `writer` according to `batch_config`
+    ///
+    /// Fails only if `batch_config` asks for a channel that cannot be allocated.
+    pub fn new<W: BatchSink + 'static>(writer: W, batch_config: BatchConfig) -> Result<Self, LoggerError> {
+        Ok(Self {
+            formatter: Box::new(DefaultFormatter::new(false)),
+            level: SinkLevel::new(LevelFilter::Trace),
+            worker: WorkerHandle::spawn(SinkWriter::new(writer, batch_config))?,
+        })
+    }
+
+    /// Use a custom formatter for this sink
+    pub fn with_formatter<F: LogFormatter + 'static>(mut self, formatter: F) -> Self {
+        self.formatter = Box::new(formatter);
+        self
+    }
+
+    /// Only accept records at or above `level`
+    pub fn with_level(self, level: LogLevel) -> Self {
+        self.level.set(level.into());
+        self
+    }
+}
+
+impl LogSink for WriterSink {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level.get()
+    }
+
+    fn set_level(&self, level: LogLevel) {
+        self.level.set(level.into());
+    }
+
+    fn inherit_theme(&mut self, theme: &ColorTheme) {
+        self.formatter.inherit_theme(theme);
+    }
+
+    fn log(&self, record: &Record) {
+        self.worker.send(pooled_message(self.formatter.as_ref(), record));
+    }
+
+    fn flush(&self) {
+        self.worker.flush();
+    }
+
+    fn flush_sync(&self) -> Result<(), LoggerError> {
+        self.worker.flush_sync()
+    }
+
+    fn shutdown(&self) {
+        self.worker.shutdown();
+    }
+
+    fn dropped_messages(&self) -> u64 {
+        self.worker.dropped()
+    }
+
+    fn stats(&self) -> Option<LoggerStats> {
+        Some(self.worker.stats())
+    }
+
+    fn on_write_error(&self, handler: WriteErrorHandler) {
+        self.worker.on_write_error(handler);
+    }
+}
+
+/// Socket journald listens on for the native protocol
+#[cfg(target_os = "linux")]
+pub const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Sink sending records to systemd-journald over its native protocol
+///
+/// Levels map to syslog priorities, and structured fields and scoped context
+/// entries become upper-cased journal fields (`user_id` -> `USER_ID`), so they
+/// can be queried with `journalctl USER_ID=42`.
+#[cfg(target_os = "linux")]
+pub struct JournaldSink {
+    socket: UnixDatagram,
+    socket_path: PathBuf,
+    identifier: String,
+    level: SinkLevel,
+    dropped: AtomicU64,
+}
+
+#[cfg(target_os = "linux")]
+impl JournaldSink {
+    /// Connect to the system journal, tagging entries with the executable name
+    pub fn new() -> Result<Self, LoggerError> {
+        Self::with_socket(JOURNALD_SOCKET)
+    }
+
+    /// Send to a different journald socket
+    pub fn with_socket<P: Into<PathBuf>>(path: P) -> Result<Self, LoggerError> {
+        let identifier = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "artifice".to_string());
+
+        Ok(Self {
+            socket: UnixDatagram::unbound()?,
+            socket_path: path.into(),
+            identifier,
+            level: SinkLevel::new(LevelFilter::Trace),
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    /// Set the `SYSLOG_IDENTIFIER` entries are tagged with
+    pub fn with_identifier<S: Into<String>>(mut self, identifier: S) -> Self {
+        self.identifier = identifier.into();
+        self
+    }
+
+    /// Only accept records at or above `level`
+    pub fn with_level(self, level: LogLevel) -> Self {
+        self.level.set(level.into());
+        self
+    }
+
+    fn encode(&self, record: &Record) -> Vec<u8> {
+        let mut entry = Vec::with_capacity(256);
+        push_journal_field(&mut entry, "PRIORITY", journald_priority(record.level()));
+        push_journal_field(&mut entry, "MESSAGE", &record.args().to_string());
+        push_journal_field(&mut entry, "SYSLOG_IDENTIFIER", &self.identifier);
+        push_journal_field(&mut entry, "TARGET", record.target());
+        if let Some(module) = record.module_path() {
+            push_journal_field(&mut entry, "CODE_MODULE", module);
+        }
+        if let Some(file) = record.file() {
+            push_journal_field(&mut entry, "CODE_FILE", file);
+        }
+        if let Some(line) = record.line() {
+            push_journal_field(&mut entry, "CODE_LINE", &line.to_string());
+        }
+
+        let mut push_pair = |key: &str, value: &FieldValue| {
+            push_journal_field(&mut entry, &journal_field_name(key), &value.to_string());
+        };
+        for (key, value) in record_fields(record) {
+            push_pair(&key, &value);
+        }
+        context::with_context(|entries| {
+            for (key, value) in entries {
+                push_pair(key, value);
+            }
+        });
+
+        entry
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl LogSink for JournaldSink {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level.get()
+    }
+
+    fn set_level(&self, level: LogLevel) {
+        self.level.set(level.into());
+    }
+
+    fn log(&self, record: &Record) {
+        if self.socket.send_to(&self.encode(record), &self.socket_path).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn flush(&self) {}
+
+    fn dropped_messages(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Syslog priority journald expects for a log level
+#[cfg(target_os = "linux")]
+fn journald_priority(level: log::Level) -> &'static str {
+    match level {
+        log::Level::Error => "3",
+        log::Level::Warn => "4",
+        log::Level::Info => "6",
+        log::Level::Debug | log::Level::Trace => "7",
+    }
+}
+
+/// Map a field key onto journald's `[A-Z0-9_]` naming rules
+///
+/// Names must start with a letter (a leading underscore marks trusted fields),
+/// so other keys get an `F_` prefix.
+#[cfg(target_os = "linux")]
+fn journal_field_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert_str(0, "F_");
+    }
+    name.truncate(64);
+    name
+}
+
+/// Append one field, using the length-prefixed form for multi-line values
+#[cfg(target_os = "linux")]
+fn push_journal_field(entry: &mut Vec<u8>, name: &str, value: &str) {
+    entry.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
@@ -1,17 +1,200 @@
-use std::fs::File;
+use log::LevelFilter;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
 use std::io::{self, BufWriter, Write};
-use std::sync::mpsc;
-use std::time::{Duration, Instant};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, PoisonError, RwLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::batching::{LogBatch, LogCommand, AdvancedLogCommand, LogMessage, SimpleBatch};
-use crate::config::{BatchConfig, HighPerformanceConfig};
+use crate::batching::{
+    BoundedQueue, Deduplicator, LogBatch, LogCommand, AdvancedLogCommand, LogMessage, PushOutcome,
+    SimpleBatch,
+};
+use crate::config::{
+    AdaptiveBatching, BackpressurePolicy, BatchConfig, DiskQuota, Durability, FileSharing, HighPerformanceConfig, LoggerError, QuotaAction,
+    RotationPolicy,
+};
+use crate::pool;
+use crate::sinks::{BatchSink, FormattedRecord};
+
+/// Build the date-stamped path for a rotation period (`app.log` -> `app-2024-06-01.log`)
+pub fn rotated_path(base_path: &Path, stamp: &str) -> PathBuf {
+    let stem = base_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let file_name = match base_path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, stamp, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, stamp),
+    };
+
+    base_path.with_file_name(file_name)
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Open a log file, appending to it when other processes may share it
+pub(crate) fn open_log_file(path: &Path, sharing: FileSharing) -> io::Result<File> {
+    match sharing {
+        FileSharing::Single => File::create(path),
+        FileSharing::Locked | FileSharing::AtomicAppend => open_append(path),
+    }
+}
+
+/// Largest write the OS appends without interleaving other writers (`PIPE_BUF`)
+#[cfg(target_os = "linux")]
+const ATOMIC_APPEND_LIMIT: usize = 4096;
+/// Largest write the OS appends without interleaving other writers (POSIX minimum `PIPE_BUF`)
+#[cfg(not(target_os = "linux"))]
+const ATOMIC_APPEND_LIMIT: usize = 512;
+
+/// Most bytes a `FileSharing::Single` or `Locked` output hands the OS in one write
+const WRITE_CHUNK: usize = 8 * 1024;
+
+/// Write `messages` as lines as `sharing` asks for
+///
+/// Lines are packed into chunks of whole lines and `written` counts the bytes
+/// that reached the file, also when a write fails part way through.
+fn write_lines(
+    writer: &mut BufWriter<File>,
+    chunk: &mut Vec<u8>,
+    messages: &[LogMessage],
+    sharing: FileSharing,
+    written: &mut u64,
+) -> io::Result<()> {
+    let limit = match sharing {
+        FileSharing::Single => WRITE_CHUNK,
+        FileSharing::Locked => {
+            writer.get_ref().lock()?;
+            let result = write_lines(writer, chunk, messages, FileSharing::Single, written);
+            let unlocked = writer.get_ref().unlock();
+            result?;
+            return unlocked;
+        }
+        // Each chunk in one `write` so no other writer lands inside it
+        FileSharing::AtomicAppend => ATOMIC_APPEND_LIMIT,
+    };
+
+    writer.flush()?;
+    let file = writer.get_mut();
+    chunk.clear();
+    for message in messages {
+        let line = message.formatted_message.len() + 1;
+        if !chunk.is_empty() && chunk.len() + line > limit {
+            write_counted(file, chunk, written)?;
+            chunk.clear();
+        }
+        chunk.extend_from_slice(message.formatted_message.as_bytes());
+        chunk.push(b'\n');
+    }
+    if !chunk.is_empty() {
+        write_counted(file, chunk, written)?;
+        chunk.clear();
+    }
+    Ok(())
+}
+
+/// `write_all` that adds the bytes `out` accepted to `written`, even when it fails
+fn write_counted(out: &mut impl Write, mut bytes: &[u8], written: &mut u64) -> io::Result<()> {
+    while !bytes.is_empty() {
+        match out.write(bytes) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(count) => {
+                *written += count as u64;
+                bytes = &bytes[count..];
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// How many lines of the given lengths lie completely within the first `written` bytes
+///
+/// Each line is followed by a newline, which the lengths leave out.
+fn complete_lines(lengths: impl IntoIterator<Item = usize>, written: u64) -> usize {
+    let mut end = 0;
+    lengths
+        .into_iter()
+        .take_while(|len| {
+            end += *len as u64 + 1;
+            end <= written
+        })
+        .count()
+}
+
+/// Files belonging to an output: `base_path` itself and the siblings `policy` rotated it into
+fn log_files(base_path: &Path, policy: RotationPolicy) -> Vec<(PathBuf, u64, SystemTime)> {
+    let dir = match base_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let file_name = base_path.file_name().map(|name| name.to_string_lossy().into_owned());
+    let prefix = match base_path.file_stem() {
+        Some(stem) => format!("{}-", stem.to_string_lossy()),
+        None => return Vec::new(),
+    };
+    let suffix = base_path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // Only names `rotated_path` builds, so `app-backup.log` and the like are left alone
+            let stamp = name.strip_prefix(&prefix).and_then(|rest| rest.strip_suffix(&suffix));
+            Some(&name) == file_name.as_ref() || stamp.is_some_and(|stamp| policy.is_period_stamp(stamp))
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|metadata| metadata.is_file())?;
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            Some((entry.path(), metadata.len(), modified))
+        })
+        .collect()
+}
+
+/// Tracks how much of a `DiskQuota` an output has used
+struct QuotaState {
+    quota: DiskQuota,
+    used_bytes: u64,
+    exhausted: bool,
+    warned: bool,
+}
+
+/// Tracks the active date-stamped file for time-based rotation
+struct RotationState {
+    base_path: PathBuf,
+    policy: RotationPolicy,
+    current_stamp: String,
+}
 
 /// Standard file writer with basic batching capabilities
 pub struct FileWriter {
     writer: BufWriter<File>,
     batch: SimpleBatch,
+    /// Scratch space for `FileSharing::AtomicAppend` writes
+    chunk: Vec<u8>,
     batch_config: BatchConfig,
     last_flush: Instant,
+    rotation: Option<RotationState>,
+    base_path: Option<PathBuf>,
+    quota: Option<QuotaState>,
+    /// Messages discarded since `take_dropped` was last called
+    dropped: u64,
 }
 
 impl FileWriter {
@@ -22,9 +205,178 @@ impl FileWriter {
         Self {
             writer,
             batch,
+            chunk: Vec::new(),
             batch_config,
             last_flush: Instant::now(),
+            rotation: None,
+            base_path: None,
+            quota: None,
+            dropped: 0,
+        }
+    }
+
+    /// Create a writer that rolls over to a new date-stamped file each rotation period
+    ///
+    /// With `RotationPolicy::Never` this behaves like `FileWriter::new` on `base_path`.
+    pub fn rotating<P: AsRef<Path>>(
+        base_path: P,
+        policy: RotationPolicy,
+        batch_config: BatchConfig,
+    ) -> io::Result<Self> {
+        let base_path = base_path.as_ref().to_path_buf();
+
+        let stamp = match policy.period_stamp(chrono::Utc::now()) {
+            Some(stamp) => stamp,
+            None => {
+                let file = open_log_file(&base_path, batch_config.sharing)?;
+                let mut writer = Self::new(file, batch_config);
+                writer.base_path = Some(base_path);
+                return Ok(writer);
+            }
+        };
+
+        let file = open_append(&rotated_path(&base_path, &stamp))?;
+        let mut writer = Self::new(file, batch_config);
+        writer.base_path = Some(base_path.clone());
+        writer.rotation = Some(RotationState {
+            base_path,
+            policy,
+            current_stamp: stamp,
+        });
+
+        Ok(writer)
+    }
+
+    /// Keep this writer's files within `quota`
+    ///
+    /// Only writers created with `rotating` know their file names; the quota
+    /// is ignored for writers built from an open `File`.
+    pub fn with_quota(mut self, quota: DiskQuota) -> Self {
+        if self.base_path.is_some() {
+            self.quota = Some(QuotaState {
+                quota,
+                used_bytes: 0,
+                exhausted: false,
+                warned: false,
+            });
+            self.enforce_quota();
+        }
+        self
+    }
+
+    /// Path of the file currently being written
+    fn current_path(&self) -> Option<PathBuf> {
+        match &self.rotation {
+            Some(state) => Some(rotated_path(&state.base_path, &state.current_stamp)),
+            None => self.base_path.clone(),
+        }
+    }
+
+    /// Re-measure the output's files and apply the quota action if over budget
+    fn enforce_quota(&mut self) {
+        let current = self.current_path();
+        let policy = self.rotation.as_ref().map_or(RotationPolicy::Never, |state| state.policy);
+        let (Some(base_path), Some(state)) = (&self.base_path, &mut self.quota) else {
+            return;
+        };
+
+        let mut files = log_files(base_path, policy);
+        let mut used: u64 = files.iter().map(|(_, size, _)| size).sum();
+
+        if used > state.quota.max_bytes && state.quota.action == QuotaAction::DeleteOldest {
+            files.sort_by_key(|(_, _, modified)| *modified);
+            for (path, size, _) in files {
+                if used <= state.quota.max_bytes {
+                    break;
+                }
+                if Some(&path) != current.as_ref() && fs::remove_file(&path).is_ok() {
+                    used -= size;
+                }
+            }
+        }
+
+        state.used_bytes = used;
+        state.exhausted = used > state.quota.max_bytes;
+        if state.exhausted && !state.warned {
+            state.warned = true;
+            eprintln!(
+                "WARNING: log files at {} exceed the disk quota of {} bytes; file logging is paused",
+                base_path.display(),
+                state.quota.max_bytes
+            );
+        }
+    }
+
+    /// Account for freshly written bytes, re-checking the quota when over budget
+    fn record_written(&mut self, bytes: u64) {
+        if let Some(state) = &mut self.quota {
+            state.used_bytes += bytes;
+            if state.used_bytes > state.quota.max_bytes {
+                self.enforce_quota();
+            }
+        }
+    }
+
+    fn quota_exhausted(&self) -> bool {
+        self.quota.as_ref().is_some_and(|state| state.exhausted)
+    }
+
+    /// Flush pending messages and reopen the current file by path
+    ///
+    /// After an external tool such as logrotate has moved the file away,
+    /// writing continues in a fresh file at the original path. Writers built
+    /// from an open `File` have no path to reopen and are only flushed.
+    pub fn reopen(&mut self) -> io::Result<()> {
+        self.flush()?;
+        self.writer.flush()?;
+
+        if let Some(path) = self.current_path() {
+            self.writer = BufWriter::with_capacity(8 * 1024, open_append(&path)?);
+            if self.quota.is_some() {
+                self.enforce_quota();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Hand back the messages a failed write left behind
+    ///
+    /// Lines that reached the file before the failure were already taken out
+    /// of the batch by `flush`, so only the rest is handed back.
+    fn take_unwritten(&mut self) -> Vec<LogMessage> {
+        self.batch.take()
+    }
+
+    /// Roll over to the next file if the rotation period has changed
+    fn rotate_if_needed(&mut self) -> io::Result<()> {
+        let (next_path, next_stamp) = match &self.rotation {
+            Some(state) => match state.policy.period_stamp(chrono::Utc::now()) {
+                Some(stamp) if stamp != state.current_stamp => {
+                    (rotated_path(&state.base_path, &stamp), stamp)
+                }
+                _ => return Ok(()),
+            },
+            None => return Ok(()),
+        };
+
+        // Pending messages belong to the period that is ending
+        self.flush()?;
+        self.writer.flush()?;
+
+        let file = open_append(&next_path)?;
+        self.writer = BufWriter::with_capacity(8 * 1024, file);
+
+        if let Some(state) = &mut self.rotation {
+            state.current_stamp = next_stamp;
         }
+
+        // A new period may have room again once old files are gone
+        if self.quota.is_some() {
+            self.enforce_quota();
+        }
+
+        Ok(())
     }
     
     fn should_flush(&self) -> bool {
@@ -37,12 +389,15 @@ impl FileWriter {
     }
     
     pub fn add_message(&mut self, message: LogMessage) -> io::Result<()> {
-        if !self.batch_config.enabled {
-            // Immediate write for non-batched mode
-            writeln!(self.writer, "{}", message.formatted_message)?;
-            return self.writer.flush();
+        self.rotate_if_needed()?;
+
+        if self.quota_exhausted() {
+            self.dropped += 1;
+            pool::recycle(Some(message.formatted_message));
+            return Ok(());
         }
-        
+
+        // Without batching `should_flush` writes the message right away
         self.batch.push(message);
         
         if self.should_flush() {
@@ -57,22 +412,159 @@ impl FileWriter {
             return Ok(());
         }
         
-        for message in self.batch.messages() {
-            writeln!(self.writer, "{}", message.formatted_message)?;
+        if self.quota_exhausted() {
+            self.dropped += self.batch.len() as u64;
+            self.batch.clear();
+            return Ok(());
+        }
+
+        let sharing = self.batch_config.sharing;
+        let mut written = 0;
+        let result = write_lines(&mut self.writer, &mut self.chunk, self.batch.messages(), sharing, &mut written);
+        self.record_written(written);
+        if let Err(e) = result {
+            // Keep only the lines that did not make it for `take_unwritten`
+            let lengths = self.batch.messages().iter().map(|message| message.formatted_message.len());
+            self.batch.clear_front(complete_lines(lengths, written));
+            return Err(e);
         }
-        
-        self.writer.flush()?;
         self.batch.clear();
         self.last_flush = Instant::now();
         
         Ok(())
     }
     
+    /// Flush pending messages and wait for the OS to commit them to disk
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.flush()?;
+        self.writer.flush()?;
+        self.writer.get_ref().sync_all()
+    }
+    
     pub fn shutdown(&mut self) -> io::Result<()> {
         self.flush()
     }
 }
 
+/// Writes each message to every file whose level accepts it
+///
+/// Lets e.g. `app.log` receive everything and `errors.log` only warnings and
+/// errors while a single worker thread serves both files. Messages without a
+/// level (such as dedup summaries of unrouted messages) go to every file.
+pub struct RoutedFileWriter {
+    routes: Vec<(LevelFilter, FileWriter)>,
+    batch_config: BatchConfig,
+}
+
+impl RoutedFileWriter {
+    /// Create a writer with no files; add them with `route`
+    pub fn new(batch_config: BatchConfig) -> Self {
+        Self {
+            routes: Vec::new(),
+            batch_config,
+        }
+    }
+
+    /// Also write messages at or above `level` to `writer`
+    pub fn route(mut self, level: LevelFilter, writer: FileWriter) -> Self {
+        self.routes.push((level, writer));
+        self
+    }
+
+    /// Most verbose level any route accepts
+    pub fn max_level(&self) -> LevelFilter {
+        self.routes
+            .iter()
+            .map(|(level, _)| *level)
+            .max()
+            .unwrap_or(LevelFilter::Off)
+    }
+
+    fn each(&mut self, mut apply: impl FnMut(&mut FileWriter) -> io::Result<()>) -> io::Result<()> {
+        let mut result = Ok(());
+        for (_, writer) in &mut self.routes {
+            if let Err(e) = apply(writer) {
+                result = Err(e);
+            }
+        }
+        result
+    }
+}
+
+impl BatchWriter for RoutedFileWriter {
+    fn add_message(&mut self, message: LogMessage) -> io::Result<()> {
+        let mut targets = self
+            .routes
+            .iter_mut()
+            .filter(|(level, _)| message.level.is_none_or(|message_level| message_level <= *level))
+            .map(|(_, writer)| writer)
+            .peekable();
+
+        let mut result = Ok(());
+        while let Some(writer) = targets.next() {
+            let copy = match targets.peek() {
+                Some(_) => message.clone(),
+                None => return writer.add_message(message).and(result),
+            };
+            if let Err(e) = writer.add_message(copy) {
+                result = Err(e);
+            }
+        }
+        result
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.each(FileWriter::flush)
+    }
+
+    fn should_flush(&self) -> bool {
+        self.routes.iter().any(|(_, writer)| writer.should_flush())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.each(FileWriter::sync)
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        self.each(FileWriter::shutdown)
+    }
+
+    fn batch_config(&self) -> &BatchConfig {
+        &self.batch_config
+    }
+
+    fn retune(&mut self, batch_size: usize, flush_interval_ms: u64) {
+        self.batch_config.batch_size = batch_size;
+        self.batch_config.flush_interval_ms = flush_interval_ms;
+        for (_, writer) in &mut self.routes {
+            BatchWriter::retune(writer, batch_size, flush_interval_ms);
+        }
+    }
+
+    fn pending(&self) -> usize {
+        // Routed copies of one message all flush together, so track the largest batch
+        self.routes.iter().map(|(_, writer)| writer.batch.len()).max().unwrap_or(0)
+    }
+
+    fn reopen(&mut self) -> io::Result<()> {
+        self.each(FileWriter::reopen)
+    }
+
+    fn take_dropped(&mut self) -> u64 {
+        // Like `pending`, count a message dropped by several routes once
+        self.routes.iter_mut().map(|(_, writer)| std::mem::take(&mut writer.dropped)).max().unwrap_or(0)
+    }
+
+    fn take_unwritten(&mut self) -> Option<Vec<LogMessage>> {
+        // Only routes that still fail give up their messages; a message routed to
+        // several of them is delivered once by keeping the longest list
+        self.routes
+            .iter_mut()
+            .filter_map(|(_, writer)| writer.flush().is_err().then(|| writer.take_unwritten()))
+            .max_by_key(Vec::len)
+    }
+}
+
 /// High-performance file writer optimized for maximum throughput
 /// 
 /// This writer uses advanced techniques including:
@@ -85,6 +577,8 @@ pub struct HighPerformanceFileWriter {
     batch: LogBatch,
     config: HighPerformanceConfig,
     last_flush: Instant,
+    last_sync: Instant,
+    unsynced: bool,
     
     // String pool for reusing allocations
     string_pool: Vec<String>,
@@ -107,6 +601,8 @@ impl HighPerformanceFileWriter {
             batch,
             config,
             last_flush: Instant::now(),
+            last_sync: Instant::now(),
+            unsynced: false,
             string_pool,
             pool_index: 0,
         }
@@ -143,7 +639,8 @@ impl HighPerformanceFileWriter {
         if !self.config.enabled {
             // Immediate write for non-batched mode
             writeln!(self.writer, "{}", message)?;
-            return self.writer.flush();
+            self.writer.flush()?;
+            return self.written();
         }
         
         let timestamp = Instant::now();
@@ -172,36 +669,1101 @@ impl HighPerformanceFileWriter {
         self.reset_string_pool();
         self.last_flush = Instant::now();
         
+        self.written()
+    }
+
+    /// Apply the durability mode to data just handed to the OS
+    fn written(&mut self) -> io::Result<()> {
+        match self.config.durability {
+            Durability::Flush => Ok(()),
+            Durability::FsyncEveryBatch => self.writer.get_ref().sync_data(),
+            Durability::FsyncIntervalMs(_) => {
+                self.unsynced = true;
+                self.sync_if_due()
+            }
+        }
+    }
+
+    /// fsync pending data once the `FsyncIntervalMs` interval has elapsed
+    pub fn sync_if_due(&mut self) -> io::Result<()> {
+        let Durability::FsyncIntervalMs(interval_ms) = self.config.durability else {
+            return Ok(());
+        };
+
+        if self.unsynced && self.last_sync.elapsed() >= Duration::from_millis(interval_ms) {
+            self.writer.get_ref().sync_data()?;
+            self.unsynced = false;
+            self.last_sync = Instant::now();
+        }
+
         Ok(())
     }
     
     pub fn shutdown(&mut self) -> io::Result<()> {
+        self.flush()?;
+
+        if self.unsynced {
+            self.writer.get_ref().sync_all()?;
+            self.unsynced = false;
+        }
+
+        Ok(())
+    }
+}
+
+/// Transport used by `NetworkWriter`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkProtocol {
+    /// Stream batches over a persistent connection, reconnecting with backoff
+    Tcp,
+    /// Send each message as a single datagram
+    Udp,
+}
+
+/// Delivery counters shared between a `NetworkWriter` and its sink
+#[derive(Debug, Default)]
+pub struct NetworkStats {
+    sent_messages: AtomicU64,
+    sent_batches: AtomicU64,
+    dropped_messages: AtomicU64,
+    failed_sends: AtomicU64,
+    reconnects: AtomicU64,
+}
+
+/// Counters a batch worker updates while it runs
+#[derive(Debug, Default)]
+pub(crate) struct PipelineStats {
+    enqueued: AtomicU64,
+    received: AtomicU64,
+    /// Messages the writer discarded itself, e.g. over a disk quota
+    dropped: AtomicU64,
+    written: AtomicU64,
+    bytes_written: AtomicU64,
+    batches: AtomicU64,
+    flush_nanos: AtomicU64,
+    max_flush_nanos: AtomicU64,
+    batch_size: AtomicU64,
+    flush_interval_ms: AtomicU64,
+}
+
+impl PipelineStats {
+    fn new(batch_config: &BatchConfig) -> Self {
+        let stats = Self::default();
+        match BatchTuner::new(batch_config) {
+            Some(tuner) => stats.record_tuning(tuner.batch_size, tuner.flush_interval_ms),
+            None => stats.record_tuning(batch_config.batch_size, batch_config.flush_interval_ms),
+        }
+        stats
+    }
+
+    fn record_tuning(&self, batch_size: usize, flush_interval_ms: u64) {
+        self.batch_size.store(batch_size as u64, Ordering::Relaxed);
+        self.flush_interval_ms.store(flush_interval_ms, Ordering::Relaxed);
+    }
+
+    fn record_batch(&self, messages: u64, bytes: u64, latency: Duration) {
+        let nanos = latency.as_nanos().min(u64::MAX as u128) as u64;
+        self.written.fetch_add(messages, Ordering::Relaxed);
+        self.bytes_written.fetch_add(bytes, Ordering::Relaxed);
+        self.batches.fetch_add(1, Ordering::Relaxed);
+        self.flush_nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.max_flush_nanos.fetch_max(nanos, Ordering::Relaxed);
+    }
+}
+
+/// Health snapshot of a logging pipeline
+///
+/// Counts cover every batching worker of a logger (the primary file plus
+/// file and network sinks); console output is written directly and not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoggerStats {
+    /// Messages taken off the channel by the worker threads
+    pub messages_received: u64,
+    /// Messages handed to the output by completed batch writes
+    pub messages_written: u64,
+    /// Bytes of those messages, including line endings
+    pub bytes_written: u64,
+    /// Messages discarded by the backpressure policy or an exhausted disk quota
+    pub dropped_messages: u64,
+    /// Messages waiting in the worker queues
+    pub queue_depth: u64,
+    /// Batch writes completed
+    pub batches_written: u64,
+    /// Time spent in batch writes
+    pub total_flush_time: Duration,
+    /// Slowest single batch write
+    pub max_flush_latency: Duration,
+    /// Batch size currently in use; the largest of merged pipelines
+    pub batch_size: u64,
+    /// Flush interval currently in use; the longest of merged pipelines
+    pub flush_interval: Duration,
+}
+
+impl LoggerStats {
+    /// Mean number of messages per batch write
+    pub fn average_batch_size(&self) -> f64 {
+        match self.batches_written {
+            0 => 0.0,
+            batches => self.messages_written as f64 / batches as f64,
+        }
+    }
+
+    /// Mean duration of a batch write
+    pub fn average_flush_latency(&self) -> Duration {
+        match u32::try_from(self.batches_written) {
+            Ok(0) => Duration::ZERO,
+            Ok(batches) => self.total_flush_time / batches,
+            Err(_) => Duration::from_nanos((self.total_flush_time.as_nanos() / self.batches_written as u128) as u64),
+        }
+    }
+
+    /// Combine the counters of two pipelines
+    pub fn merge(self, other: LoggerStats) -> LoggerStats {
+        LoggerStats {
+            messages_received: self.messages_received + other.messages_received,
+            messages_written: self.messages_written + other.messages_written,
+            bytes_written: self.bytes_written + other.bytes_written,
+            dropped_messages: self.dropped_messages + other.dropped_messages,
+            queue_depth: self.queue_depth + other.queue_depth,
+            batches_written: self.batches_written + other.batches_written,
+            total_flush_time: self.total_flush_time + other.total_flush_time,
+            max_flush_latency: self.max_flush_latency.max(other.max_flush_latency),
+            batch_size: self.batch_size.max(other.batch_size),
+            flush_interval: self.flush_interval.max(other.flush_interval),
+        }
+    }
+}
+
+/// Point-in-time copy of `NetworkStats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeliveryStats {
+    /// Messages successfully handed to the network
+    pub sent_messages: u64,
+    /// Batches successfully written
+    pub sent_batches: u64,
+    /// Messages discarded because the pending buffer overflowed
+    pub dropped_messages: u64,
+    /// Send or connect attempts that failed
+    pub failed_sends: u64,
+    /// Successful (re)connections
+    pub reconnects: u64,
+}
+
+impl NetworkStats {
+    /// Take a snapshot of the current counters
+    pub fn snapshot(&self) -> DeliveryStats {
+        DeliveryStats {
+            sent_messages: self.sent_messages.load(Ordering::Relaxed),
+            sent_batches: self.sent_batches.load(Ordering::Relaxed),
+            dropped_messages: self.dropped_messages.load(Ordering::Relaxed),
+            failed_sends: self.failed_sends.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+        }
+    }
+}
+
+enum NetworkConnection {
+    Tcp(TcpStream),
+    Udp(UdpSocket),
+}
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Batching writer that ships formatted messages to a remote log collector
+///
+/// Messages stay buffered while the collector is unreachable; once the buffer
+/// exceeds four times `buffer_capacity` the oldest messages are dropped.
+pub struct NetworkWriter {
+    protocol: NetworkProtocol,
+    address: String,
+    connection: Option<NetworkConnection>,
+    batch: LogBatch,
+    batch_config: BatchConfig,
+    last_flush: Instant,
+    stats: Arc<NetworkStats>,
+    backoff: Duration,
+    next_attempt: Instant,
+}
+
+impl NetworkWriter {
+    pub fn new<A: Into<String>>(address: A, protocol: NetworkProtocol, batch_config: BatchConfig) -> Self {
+        Self {
+            protocol,
+            address: address.into(),
+            connection: None,
+            batch: LogBatch::new(batch_config.buffer_capacity),
+            batch_config,
+            last_flush: Instant::now(),
+            stats: Arc::new(NetworkStats::default()),
+            backoff: INITIAL_BACKOFF,
+            next_attempt: Instant::now(),
+        }
+    }
+
+    /// Shared handle to the delivery counters
+    pub fn stats(&self) -> Arc<NetworkStats> {
+        Arc::clone(&self.stats)
+    }
+
+    fn resolve(&self) -> io::Result<SocketAddr> {
+        self.address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "log collector address did not resolve"))
+    }
+
+    fn connect(&self) -> io::Result<NetworkConnection> {
+        let addr = self.resolve()?;
+        match self.protocol {
+            NetworkProtocol::Tcp => {
+                let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+                stream.set_nodelay(true)?;
+                Ok(NetworkConnection::Tcp(stream))
+            }
+            NetworkProtocol::Udp => {
+                let local: SocketAddr = if addr.is_ipv4() {
+                    ([0, 0, 0, 0], 0).into()
+                } else {
+                    ([0u16; 8], 0).into()
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(addr)?;
+                Ok(NetworkConnection::Udp(socket))
+            }
+        }
+    }
+
+    /// Make sure a connection exists, respecting the reconnect backoff
+    fn ensure_connected(&mut self) -> bool {
+        if self.connection.is_some() {
+            return true;
+        }
+        if Instant::now() < self.next_attempt {
+            return false;
+        }
+
+        match self.connect() {
+            Ok(connection) => {
+                self.connection = Some(connection);
+                self.backoff = INITIAL_BACKOFF;
+                self.stats.reconnects.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Err(_) => {
+                self.connection_failed();
+                false
+            }
+        }
+    }
+
+    fn connection_failed(&mut self) {
+        self.connection = None;
+        self.stats.failed_sends.fetch_add(1, Ordering::Relaxed);
+        self.next_attempt = Instant::now() + self.backoff;
+        self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+    }
+
+    /// Send the batch, counting in `sent` the messages that went out before any failure
+    ///
+    /// A TCP message cut off part way counts as unsent; the collector sees
+    /// the fragment end with the old connection and the whole line again
+    /// on the next one.
+    fn send_batch(&mut self, sent: &mut usize) -> io::Result<()> {
+        match &mut self.connection {
+            Some(NetworkConnection::Tcp(stream)) => {
+                let bulk_content = self.batch.format_bulk();
+                let mut written = 0;
+                let result = write_counted(stream, bulk_content.as_bytes(), &mut written).and_then(|()| stream.flush());
+                *sent = complete_lines(self.batch.messages.iter().map(String::len), written);
+                result
+            }
+            Some(NetworkConnection::Udp(socket)) => {
+                for message in &self.batch.messages {
+                    socket.send(message.as_bytes())?;
+                    *sent += 1;
+                }
+                Ok(())
+            }
+            None => Err(io::Error::new(io::ErrorKind::NotConnected, "log collector not connected")),
+        }
+    }
+
+    /// Drop the oldest messages once the pending buffer is over its limit
+    fn enforce_pending_limit(&mut self) {
+        let limit = self.batch_config.buffer_capacity.max(self.batch_config.batch_size) * 4;
+        let excess = self.batch.len().saturating_sub(limit);
+        if excess > 0 {
+            self.batch.messages.drain(..excess);
+            self.batch.timestamps.drain(..excess);
+            self.stats.dropped_messages.fetch_add(excess as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn should_flush(&self) -> bool {
+        if !self.batch_config.enabled {
+            return true;
+        }
+
+        self.batch.len() >= self.batch_config.batch_size ||
+        self.last_flush.elapsed() >= Duration::from_millis(self.batch_config.flush_interval_ms)
+    }
+
+    pub fn add_message(&mut self, message: LogMessage) -> io::Result<()> {
+        self.batch.push(message.formatted_message, message.timestamp);
+        self.enforce_pending_limit();
+
+        if self.should_flush() {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Send all pending messages; on failure those not yet sent are kept for the next attempt
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+
+        if self.batch.is_empty() || !self.ensure_connected() {
+            return Ok(());
+        }
+
+        let mut sent = 0;
+        let result = self.send_batch(&mut sent);
+        // Whatever went out is not sent again, even if the rest failed
+        self.stats.sent_messages.fetch_add(sent as u64, Ordering::Relaxed);
+        self.batch.clear_front(sent);
+        match result {
+            Ok(()) => {
+                self.stats.sent_batches.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(e) => {
+                self.connection_failed();
+                Err(e)
+            }
+        }
+    }
+
+    pub fn shutdown(&mut self) -> io::Result<()> {
+        // Give an unreachable collector one last chance before giving up
+        self.next_attempt = Instant::now();
+        self.flush()?;
+
+        let undelivered = self.batch.len() as u64;
+        if undelivered > 0 {
+            self.stats.dropped_messages.fetch_add(undelivered, Ordering::Relaxed);
+            self.batch.clear();
+        }
+
+        Ok(())
+    }
+}
+
+/// Writers driven by the standard batching worker thread
+pub(crate) trait BatchWriter {
+    fn add_message(&mut self, message: LogMessage) -> io::Result<()>;
+    fn flush(&mut self) -> io::Result<()>;
+    fn should_flush(&self) -> bool;
+    fn sync(&mut self) -> io::Result<()>;
+    fn shutdown(&mut self) -> io::Result<()>;
+    fn batch_config(&self) -> &BatchConfig;
+    /// Switch to a new batch size and flush interval while running
+    fn retune(&mut self, batch_size: usize, flush_interval_ms: u64);
+    /// Messages buffered and not yet written
+    fn pending(&self) -> usize;
+    /// Reopen file outputs by path; other outputs only flush
+    fn reopen(&mut self) -> io::Result<()> {
+        self.flush()
+    }
+    /// Messages a failed write left behind, for delivery elsewhere
+    ///
+    /// `None` when the writer keeps and retries them itself.
+    fn take_unwritten(&mut self) -> Option<Vec<LogMessage>> {
+        None
+    }
+    /// Messages discarded since the last call, e.g. because a disk quota ran out
+    fn take_dropped(&mut self) -> u64 {
+        0
+    }
+}
+
+impl BatchWriter for FileWriter {
+    fn add_message(&mut self, message: LogMessage) -> io::Result<()> {
+        FileWriter::add_message(self, message)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        FileWriter::flush(self)
+    }
+
+    fn should_flush(&self) -> bool {
+        FileWriter::should_flush(self)
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        FileWriter::sync(self)
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        FileWriter::shutdown(self)
+    }
+
+    fn batch_config(&self) -> &BatchConfig {
+        &self.batch_config
+    }
+
+    fn retune(&mut self, batch_size: usize, flush_interval_ms: u64) {
+        self.batch_config.batch_size = batch_size;
+        self.batch_config.flush_interval_ms = flush_interval_ms;
+    }
+
+    fn pending(&self) -> usize {
+        self.batch.len()
+    }
+
+    fn reopen(&mut self) -> io::Result<()> {
+        FileWriter::reopen(self)
+    }
+
+    fn take_unwritten(&mut self) -> Option<Vec<LogMessage>> {
+        Some(FileWriter::take_unwritten(self))
+    }
+
+    fn take_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.dropped)
+    }
+}
+
+impl BatchWriter for NetworkWriter {
+    fn add_message(&mut self, message: LogMessage) -> io::Result<()> {
+        NetworkWriter::add_message(self, message)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        NetworkWriter::flush(self)
+    }
+
+    fn should_flush(&self) -> bool {
+        NetworkWriter::should_flush(self)
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        // Nothing to fsync remotely; a sync flush must not leave messages behind
+        NetworkWriter::flush(self)?;
+        match self.batch.is_empty() {
+            true => Ok(()),
+            false => Err(io::Error::new(io::ErrorKind::NotConnected, "log collector unreachable")),
+        }
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        NetworkWriter::shutdown(self)
+    }
+
+    fn batch_config(&self) -> &BatchConfig {
+        &self.batch_config
+    }
+
+    fn retune(&mut self, batch_size: usize, flush_interval_ms: u64) {
+        self.batch_config.batch_size = batch_size;
+        self.batch_config.flush_interval_ms = flush_interval_ms;
+    }
+
+    fn pending(&self) -> usize {
+        self.batch.len()
+    }
+}
+
+/// Batches messages for a user-supplied `BatchSink`
+pub(crate) struct SinkWriter<S> {
+    sink: S,
+    batch: Vec<FormattedRecord>,
+    batch_config: BatchConfig,
+    last_flush: Instant,
+}
+
+impl<S: BatchSink> SinkWriter<S> {
+    pub(crate) fn new(sink: S, batch_config: BatchConfig) -> Self {
+        Self {
+            sink,
+            batch: Vec::with_capacity(batch_config.buffer_capacity),
+            batch_config,
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// Hand the batch to the sink, keeping it buffered if the sink fails
+    fn write_batch(&mut self) -> io::Result<()> {
+        if !self.batch.is_empty() {
+            self.sink.write_batch(&self.batch)?;
+            pool::recycle(self.batch.drain(..).map(|record| record.line));
+        }
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+}
+
+impl<S: BatchSink> BatchWriter for SinkWriter<S> {
+    fn add_message(&mut self, message: LogMessage) -> io::Result<()> {
+        self.batch.push(FormattedRecord {
+            line: message.formatted_message,
+            level: message.level,
+            timestamp: message.timestamp,
+        });
+
+        if self.should_flush() {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_batch()?;
+        self.sink.flush()
+    }
+
+    fn should_flush(&self) -> bool {
+        !self.batch_config.enabled
+            || self.batch.len() >= self.batch_config.batch_size
+            || self.last_flush.elapsed() >= Duration::from_millis(self.batch_config.flush_interval_ms)
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
         self.flush()
     }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        self.write_batch()?;
+        self.sink.shutdown()
+    }
+
+    fn batch_config(&self) -> &BatchConfig {
+        &self.batch_config
+    }
+
+    fn retune(&mut self, batch_size: usize, flush_interval_ms: u64) {
+        self.batch_config.batch_size = batch_size;
+        self.batch_config.flush_interval_ms = flush_interval_ms;
+    }
+
+    fn pending(&self) -> usize {
+        self.batch.len()
+    }
+
+    fn take_unwritten(&mut self) -> Option<Vec<LogMessage>> {
+        let unwritten = self.batch.drain(..).map(|record| LogMessage {
+            formatted_message: record.line,
+            timestamp: record.timestamp,
+            fingerprint: 0,
+            level: record.level,
+        });
+        Some(unwritten.collect())
+    }
+}
+
+/// Callback told about failed writes; see `ArtificeLogger::on_write_error`
+pub type WriteErrorHandler = Arc<dyn Fn(LoggerError) + Send + Sync>;
+
+/// Where a worker reports write failures, settable while it runs
+#[derive(Default)]
+pub(crate) struct ErrorReporter(RwLock<Option<WriteErrorHandler>>);
+
+impl ErrorReporter {
+    fn set(&self, handler: WriteErrorHandler) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Some(handler);
+    }
+
+    fn report(&self, error: io::Error) {
+        let handler = self.0.read().unwrap_or_else(PoisonError::into_inner).clone();
+        match handler {
+            Some(handler) => {
+                let outer = REPORTING.replace(true);
+                handler(LoggerError::Io(error));
+                REPORTING.set(outer);
+            }
+            None => eprintln!("Failed to write log messages: {}", error),
+        }
+    }
+}
+
+thread_local! {
+    /// Set while this thread runs a write error handler
+    ///
+    /// The handler runs on a worker thread, which cannot drain its own channel
+    /// or answer its own requests until the handler returns, so logging from it
+    /// must never wait for a worker.
+    static REPORTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// How long a writer is bypassed after a failed write before it is tried again
+const FALLBACK_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Last-resort output for messages a writer failed to deliver
+fn write_to_console(messages: &[LogMessage]) {
+    for message in messages {
+        eprintln!("{}", message.formatted_message);
+    }
+}
+
+/// Receiving end of a worker's command channel
+pub(crate) enum CommandReceiver {
+    Unbounded(mpsc::Receiver<LogCommand>),
+    Bounded(Arc<BoundedQueue>),
+}
+
+impl CommandReceiver {
+    fn recv_timeout(&self, timeout: Duration) -> Result<LogCommand, mpsc::RecvTimeoutError> {
+        match self {
+            CommandReceiver::Unbounded(receiver) => receiver.recv_timeout(timeout),
+            CommandReceiver::Bounded(queue) => queue.recv_timeout(timeout),
+        }
+    }
+
+    /// Messages waiting to be received, where the channel can tell
+    fn len(&self) -> usize {
+        match self {
+            CommandReceiver::Unbounded(_) => 0,
+            CommandReceiver::Bounded(queue) => queue.len(),
+        }
+    }
+}
+
+enum CommandSender {
+    Unbounded(mpsc::Sender<LogCommand>),
+    Bounded(Arc<BoundedQueue>),
+}
+
+/// Channel and thread of a running batch worker
+///
+/// The channel is unbounded by default, so logging never blocks or drops
+/// messages. With `BatchConfig::channel_capacity` set, messages travel through
+/// a lock-free `BoundedQueue` instead, which is faster under contention, and
+/// `BatchConfig::backpressure` decides what happens once it is full.
+/// Dropping the handle shuts the worker down and waits for it to finish
+/// writing everything it has buffered.
+pub(crate) struct WorkerHandle {
+    sender: CommandSender,
+    policy: BackpressurePolicy,
+    spill_file: Mutex<Option<File>>,
+    dropped: AtomicU64,
+    stats: Arc<PipelineStats>,
+    errors: Arc<ErrorReporter>,
+    thread: Mutex<Option<thread::JoinHandle<()>>>,
+}
+
+impl WorkerHandle {
+    /// Start a worker thread for `writer`; fails if the channel capacity cannot be allocated
+    pub(crate) fn spawn<W: BatchWriter + Send + 'static>(writer: W) -> Result<Self, LoggerError> {
+        if let Some(adaptive) = &writer.batch_config().adaptive {
+            adaptive.validate()?;
+        }
+        pool::reserve(writer.batch_config().string_pool_size);
+        let policy = writer.batch_config().backpressure.clone();
+        let (sender, receiver) = match writer.batch_config().channel_capacity {
+            Some(capacity) => {
+                let queue = Arc::new(BoundedQueue::new(capacity)?);
+                (CommandSender::Bounded(Arc::clone(&queue)), CommandReceiver::Bounded(queue))
+            }
+            None => {
+                let (sender, receiver) = mpsc::channel();
+                (CommandSender::Unbounded(sender), CommandReceiver::Unbounded(receiver))
+            }
+        };
+
+        let stats = Arc::new(PipelineStats::new(writer.batch_config()));
+        let worker_stats = Arc::clone(&stats);
+        let errors = Arc::new(ErrorReporter::default());
+        let worker_errors = Arc::clone(&errors);
+        let thread_handle = thread::spawn(move || {
+            batch_worker_thread(writer, receiver, &worker_stats, &worker_errors);
+        });
+
+        Ok(Self {
+            sender,
+            policy,
+            spill_file: Mutex::new(None),
+            dropped: AtomicU64::new(0),
+            stats,
+            errors,
+            thread: Mutex::new(Some(thread_handle)),
+        })
+    }
+
+    fn command(&self, command: LogCommand) -> bool {
+        match &self.sender {
+            CommandSender::Unbounded(sender) => sender.send(command).is_ok(),
+            CommandSender::Bounded(queue) => queue.push_command(command),
+        }
+    }
+
+    pub(crate) fn send(&self, message: LogMessage) {
+        self.stats.enqueued.fetch_add(1, Ordering::Relaxed);
+        match &self.sender {
+            CommandSender::Unbounded(sender) => {
+                if sender.send(LogCommand::Message(message)).is_err() {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            CommandSender::Bounded(queue) => {
+                // Blocking in a write error handler could wait for the very worker running it
+                let policy = match &self.policy {
+                    BackpressurePolicy::Block if REPORTING.get() => &BackpressurePolicy::DropNewest,
+                    policy => policy,
+                };
+                match queue.push_message(message, policy) {
+                    PushOutcome::Queued => {}
+                    PushOutcome::DroppedOldest | PushOutcome::Dropped => {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    PushOutcome::Spill(message) => {
+                        if self.spill(&message).is_err() {
+                            self.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Append a message to the emergency file, bypassing the worker
+    fn spill(&self, message: &LogMessage) -> io::Result<()> {
+        let path = match &self.policy {
+            BackpressurePolicy::SpillToFile(path) => path,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "no spill file configured")),
+        };
+
+        let mut spill_file = match self.spill_file.lock() {
+            Ok(file) => file,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if spill_file.is_none() {
+            *spill_file = Some(open_append(path)?);
+        }
+
+        match spill_file.as_mut() {
+            Some(file) => writeln!(file, "{}", message.formatted_message),
+            None => Ok(()),
+        }
+    }
+
+    /// Messages discarded because the channel was full or the writer could not take them
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed) + self.stats.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Snapshot of the worker's pipeline counters
+    pub(crate) fn stats(&self) -> LoggerStats {
+        let stats = &self.stats;
+        let received = stats.received.load(Ordering::Relaxed);
+        let dropped = self.dropped();
+        let queue_depth = match &self.sender {
+            CommandSender::Bounded(queue) => queue.len() as u64,
+            CommandSender::Unbounded(_) => stats
+                .enqueued
+                .load(Ordering::Relaxed)
+                .saturating_sub(received + self.dropped.load(Ordering::Relaxed)),
+        };
+
+        LoggerStats {
+            messages_received: received,
+            messages_written: stats.written.load(Ordering::Relaxed),
+            bytes_written: stats.bytes_written.load(Ordering::Relaxed),
+            dropped_messages: dropped,
+            queue_depth,
+            batches_written: stats.batches.load(Ordering::Relaxed),
+            total_flush_time: Duration::from_nanos(stats.flush_nanos.load(Ordering::Relaxed)),
+            max_flush_latency: Duration::from_nanos(stats.max_flush_nanos.load(Ordering::Relaxed)),
+            batch_size: stats.batch_size.load(Ordering::Relaxed),
+            flush_interval: Duration::from_millis(stats.flush_interval_ms.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Report the worker's write failures to `handler`
+    pub(crate) fn on_write_error(&self, handler: WriteErrorHandler) {
+        self.errors.set(handler);
+    }
+
+    pub(crate) fn flush(&self) {
+        self.command(LogCommand::Flush);
+    }
+
+    /// Ask the worker to flush and wait for its acknowledgement
+    pub(crate) fn flush_sync(&self) -> Result<(), LoggerError> {
+        if REPORTING.get() {
+            return Err(LoggerError::ChannelError);
+        }
+        let (reply, done) = mpsc::channel();
+        if !self.command(LogCommand::FlushSync(reply)) {
+            return Err(LoggerError::ChannelError);
+        }
+        done.recv().map_err(|_| LoggerError::ChannelError)??;
+        Ok(())
+    }
+
+    /// Ask the worker to reopen its files by path and wait until it has
+    pub(crate) fn reopen(&self) -> Result<(), LoggerError> {
+        if REPORTING.get() {
+            return Err(LoggerError::ChannelError);
+        }
+        let (reply, done) = mpsc::channel();
+        if !self.command(LogCommand::Reopen(reply)) {
+            return Err(LoggerError::ChannelError);
+        }
+        done.recv().map_err(|_| LoggerError::ChannelError)??;
+        Ok(())
+    }
+
+    /// Stop the worker and join it; later messages are discarded
+    pub(crate) fn shutdown(&self) {
+        self.command(LogCommand::Shutdown);
+
+        let handle = match self.thread.lock() {
+            Ok(mut thread) => thread.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        };
+        if let Some(handle) = handle {
+            let _ = handle.join();
+        }
+
+        if let CommandSender::Bounded(queue) = &self.sender {
+            queue.close();
+        }
+    }
+}
+
+impl Drop for WorkerHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
 }
 
 /// Standard file worker thread for regular performance requirements
 pub fn file_worker_thread(
-    mut file_writer: FileWriter,
+    file_writer: FileWriter,
+    receiver: mpsc::Receiver<LogCommand>,
+) {
+    let stats = PipelineStats::new(&file_writer.batch_config);
+    batch_worker_thread(file_writer, CommandReceiver::Unbounded(receiver), &stats, &ErrorReporter::default());
+}
+
+/// Worker thread shipping messages through a `NetworkWriter`
+pub fn network_worker_thread(
+    network_writer: NetworkWriter,
     receiver: mpsc::Receiver<LogCommand>,
+) {
+    let stats = PipelineStats::new(&network_writer.batch_config);
+    batch_worker_thread(network_writer, CommandReceiver::Unbounded(receiver), &stats, &ErrorReporter::default());
+}
+
+/// Wraps a `BatchWriter`, records each batch it writes into `PipelineStats`
+/// and falls back to the console while it is failing
+struct MeteredWriter<'a, W> {
+    writer: W,
+    /// Sizes of the messages the writer is still holding, oldest first
+    pending: VecDeque<u64>,
+    stats: &'a PipelineStats,
+    errors: &'a ErrorReporter,
+    /// Messages go to the console instead of the writer until then
+    fallback_until: Option<Instant>,
+}
+
+impl<W: BatchWriter> MeteredWriter<'_, W> {
+    fn add_message(&mut self, message: LogMessage) {
+        if self.fallback_until.is_some_and(|until| Instant::now() < until) {
+            write_to_console(std::slice::from_ref(&message));
+            pool::recycle(Some(message.formatted_message));
+            return;
+        }
+
+        self.pending.push_back(message.formatted_message.len() as u64 + 1);
+        let _ = self.run(|writer| writer.add_message(message));
+    }
+
+    /// Run `op` on the writer, reporting a failure and rescuing what it left behind
+    fn run(&mut self, op: impl FnOnce(&mut W) -> io::Result<()>) -> io::Result<()> {
+        let result = self.measure(op);
+        if let Err(e) = &result {
+            let error = match e.raw_os_error() {
+                Some(code) => io::Error::from_raw_os_error(code),
+                None => io::Error::new(e.kind(), e.to_string()),
+            };
+            self.fail(error);
+        }
+        result
+    }
+
+    /// Report a failed write and move the messages it left behind to the console
+    fn fail(&mut self, error: io::Error) {
+        self.errors.report(error);
+        if let Some(unwritten) = self.writer.take_unwritten() {
+            self.pending.clear();
+            write_to_console(&unwritten);
+            pool::recycle(unwritten.into_iter().map(|message| message.formatted_message));
+            self.fallback_until = Some(Instant::now() + FALLBACK_RETRY_INTERVAL);
+        }
+    }
+
+    /// Run `op`, counting whatever it wrote as one batch
+    fn measure<R>(&mut self, op: impl FnOnce(&mut W) -> R) -> R {
+        let started = Instant::now();
+        let result = op(&mut self.writer);
+        let remaining = self.writer.pending().min(self.pending.len());
+        let finished = self.pending.len() - remaining;
+        // A writer drops either the message just added or the whole batch, so the newest go
+        let dropped = (self.writer.take_dropped() as usize).min(finished);
+        if dropped > 0 {
+            self.pending.truncate(self.pending.len() - dropped);
+            self.stats.dropped.fetch_add(dropped as u64, Ordering::Relaxed);
+        }
+        let written = finished - dropped;
+
+        if written > 0 {
+            let bytes = self.pending.drain(..written).sum();
+            self.stats.record_batch(written as u64, bytes, started.elapsed());
+        }
+        result
+    }
+}
+
+/// How often an adaptive worker re-evaluates its batch size and flush interval
+const TUNING_WINDOW: Duration = Duration::from_millis(250);
+
+/// Scales a worker's batch size and flush interval with the observed load
+struct BatchTuner {
+    bounds: AdaptiveBatching,
+    batch_size: usize,
+    flush_interval_ms: u64,
+    window_start: Instant,
+    window_messages: u64,
+    /// Smoothed incoming message rate, per second
+    rate: f64,
+}
+
+impl BatchTuner {
+    fn new(batch_config: &BatchConfig) -> Option<Self> {
+        let mut bounds = batch_config.adaptive.clone()?;
+        // Workers started without `WorkerHandle::spawn` skip validation, and
+        // `clamp` panics on inverted bounds
+        bounds.max_batch_size = bounds.max_batch_size.max(bounds.min_batch_size);
+        bounds.max_flush_interval_ms = bounds.max_flush_interval_ms.max(bounds.min_flush_interval_ms);
+        Some(Self {
+            batch_size: batch_config.batch_size.clamp(bounds.min_batch_size, bounds.max_batch_size),
+            flush_interval_ms: batch_config
+                .flush_interval_ms
+                .clamp(bounds.min_flush_interval_ms, bounds.max_flush_interval_ms),
+            bounds,
+            window_start: Instant::now(),
+            window_messages: 0,
+            rate: 0.0,
+        })
+    }
+
+    fn observe_message(&mut self) {
+        self.window_messages += 1;
+    }
+
+    /// New batch size and flush interval once a window has closed and they changed
+    fn tune(&mut self, queue_depth: usize) -> Option<(usize, u64)> {
+        let elapsed = self.window_start.elapsed();
+        if elapsed < TUNING_WINDOW {
+            return None;
+        }
+        let sample = self.window_messages as f64 / elapsed.as_secs_f64();
+        // Weighted towards the latest window so the tuner follows load changes within a second or two
+        self.rate = self.rate * 0.25 + sample * 0.75;
+        self.window_start = Instant::now();
+        self.window_messages = 0;
+
+        let bounds = &self.bounds;
+        // Enough messages to fill a batch over the longest interval
+        let mut target = (self.rate * bounds.max_flush_interval_ms as f64 / 1000.0) as usize;
+        // A backlog means the writer is falling behind: write more per batch
+        if queue_depth > self.batch_size {
+            target = target.max(self.batch_size.saturating_mul(2));
+        }
+        let batch_size = target.clamp(bounds.min_batch_size, bounds.max_batch_size);
+
+        // Light load flushes soon to keep latency low; heavy load fills batches by size anyway
+        let batch_range = bounds.max_batch_size.saturating_sub(bounds.min_batch_size).max(1);
+        let load = (batch_size - bounds.min_batch_size) as f64 / batch_range as f64;
+        let interval_range = bounds.max_flush_interval_ms.saturating_sub(bounds.min_flush_interval_ms);
+        let flush_interval_ms = bounds.min_flush_interval_ms + (interval_range as f64 * load).round() as u64;
+
+        if (batch_size, flush_interval_ms) == (self.batch_size, self.flush_interval_ms) {
+            return None;
+        }
+        self.batch_size = batch_size;
+        self.flush_interval_ms = flush_interval_ms;
+        Some((batch_size, flush_interval_ms))
+    }
+}
+
+fn batch_worker_thread<W: BatchWriter>(
+    mut writer: W,
+    receiver: CommandReceiver,
+    stats: &PipelineStats,
+    errors: &ErrorReporter,
 ) {
     let mut should_shutdown = false;
+    let mut timeout = Duration::from_millis(writer.batch_config().flush_interval_ms);
+    let mut dedup = writer.batch_config().deduplicate.then(Deduplicator::new);
+    let mut tuner = BatchTuner::new(writer.batch_config());
+    if let Some(tuner) = &tuner {
+        writer.retune(tuner.batch_size, tuner.flush_interval_ms);
+        stats.record_tuning(tuner.batch_size, tuner.flush_interval_ms);
+        timeout = Duration::from_millis(tuner.flush_interval_ms);
+    }
+    let mut writer = MeteredWriter { writer, pending: VecDeque::new(), stats, errors, fallback_until: None };
     
     while !should_shutdown {
-        let timeout = Duration::from_millis(file_writer.batch_config.flush_interval_ms);
-        
+        if let Some((batch_size, flush_interval_ms)) = tuner.as_mut().and_then(|tuner| tuner.tune(receiver.len())) {
+            writer.writer.retune(batch_size, flush_interval_ms);
+            stats.record_tuning(batch_size, flush_interval_ms);
+            timeout = Duration::from_millis(flush_interval_ms);
+        }
+
         match receiver.recv_timeout(timeout) {
             Ok(command) => {
                 match command {
                     LogCommand::Message(message) => {
-                        if let Err(e) = file_writer.add_message(message) {
-                            eprintln!("Failed to write log message: {}", e);
+                        stats.received.fetch_add(1, Ordering::Relaxed);
+                        if let Some(tuner) = &mut tuner {
+                            tuner.observe_message();
+                        }
+                        let summary = match &mut dedup {
+                            Some(dedup) => match dedup.observe(&message) {
+                                Some(summary) => summary,
+                                None => {
+                                    pool::recycle(Some(message.formatted_message));
+                                    continue;
+                                }
+                            },
+                            None => None,
+                        };
+
+                        for message in summary.into_iter().chain(Some(message)) {
+                            writer.add_message(message);
                         }
                     }
                     LogCommand::Flush => {
-                        if let Err(e) = file_writer.flush() {
-                            eprintln!("Failed to flush log messages: {}", e);
+                        if let Some(summary) = dedup.as_mut().and_then(Deduplicator::take_summary) {
+                            writer.add_message(summary);
+                        }
+                        let _ = writer.run(W::flush);
+                    }
+                    LogCommand::FlushSync(reply) => {
+                        if let Some(summary) = dedup.as_mut().and_then(Deduplicator::take_summary) {
+                            writer.add_message(summary);
                         }
+                        let _ = reply.send(writer.run(W::sync));
+                    }
+                    LogCommand::Reopen(reply) => {
+                        if let Some(summary) = dedup.as_mut().and_then(Deduplicator::take_summary) {
+                            writer.add_message(summary);
+                        }
+                        let _ = reply.send(writer.run(W::reopen));
                     }
                     LogCommand::Shutdown => {
                         should_shutdown = true;
@@ -209,11 +1771,14 @@ pub fn file_worker_thread(
                 }
             }
             Err(mpsc::RecvTimeoutError::Timeout) => {
+                // A run of repeats that has gone quiet gets its summary now
+                if let Some(summary) = dedup.as_mut().and_then(|dedup| dedup.take_quiet_summary(timeout)) {
+                    writer.add_message(summary);
+                }
+
                 // Periodic flush check
-                if file_writer.should_flush() {
-                    if let Err(e) = file_writer.flush() {
-                        eprintln!("Failed to periodic flush log messages: {}", e);
-                    }
+                if writer.writer.should_flush() {
+                    let _ = writer.run(W::flush);
                 }
             }
             Err(mpsc::RecvTimeoutError::Disconnected) => {
@@ -223,9 +1788,10 @@ pub fn file_worker_thread(
     }
     
     // Final flush on shutdown
-    if let Err(e) = file_writer.shutdown() {
-        eprintln!("Failed to shutdown file writer: {}", e);
+    if let Some(summary) = dedup.as_mut().and_then(Deduplicator::take_summary) {
+        writer.add_message(summary);
     }
+    let _ = writer.run(W::shutdown);
 }
 
 /// High-performance worker thread optimized for maximum throughput
@@ -281,6 +1847,9 @@ pub fn high_performance_worker_thread(
                         eprintln!("Failed to periodic flush log messages: {}", e);
                     }
                 }
+                if let Err(e) = file_writer.sync_if_due() {
+                    eprintln!("Failed to sync log file: {}", e);
+                }
             }
             Err(mpsc::RecvTimeoutError::Disconnected) => {
                 should_shutdown = true;
@@ -0,0 +1,455 @@
+//! Rendering helpers for applications drawing with OpenGL
+//!
+//! Everything here calls OpenGL, so it must run on the engine's thread
+//! with a context current, as in `Application::init` and `render`. Code
+//! that should not depend on OpenGL draws through the `RenderDevice` the
+//! engine hands to `Application::set_render_device` instead.
+
+pub mod device;
+pub mod opengl;
+
+pub use device::{
+    BlendMode, BufferDesc, BufferHandle, BufferKind, BufferUsage, DeviceInfo, IndexFormat, PipelineDesc, PipelineHandle,
+    PrimitiveTopology, RenderContext, RenderDevice, RenderError, ShaderSource, SharedRenderDevice, TextureDesc,
+    TextureFormat, TextureHandle, UniformData, VertexAttribute, VertexFormat, VertexLayout,
+};
+pub use opengl::OpenGlDevice;
+
+use crate::assets::{self, ShaderStage};
+use crate::io::ImageBuffer;
+use artifice_logging::{debug, warn};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Why a shader program could not be built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderError {
+    /// A source file could not be read
+    Read { path: String, error: String },
+    /// The source holds a NUL byte, which OpenGL cannot take
+    InvalidSource { stage: ShaderStage },
+    /// A stage failed to compile; `log` is the driver's message, usually with line numbers
+    Compile { stage: ShaderStage, log: String },
+    /// The compiled stages failed to link into a program
+    Link { log: String },
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderError::Read { path, error } => write!(f, "Failed to read shader {}: {}", path, error),
+            ShaderError::InvalidSource { stage } => write!(f, "{:?} shader source contains a NUL byte", stage),
+            ShaderError::Compile { stage, log } => write!(f, "{:?} shader failed to compile: {}", stage, log),
+            ShaderError::Link { log } => write!(f, "Shader program failed to link: {}", log),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+/// A value `Shader::set_uniform` can set
+pub trait UniformValue {
+    /// Set the uniform at `location` of the program in use
+    fn apply(&self, location: i32);
+}
+
+impl UniformValue for f32 {
+    fn apply(&self, location: i32) {
+        unsafe { gl::Uniform1f(location, *self) }
+    }
+}
+
+impl UniformValue for i32 {
+    fn apply(&self, location: i32) {
+        unsafe { gl::Uniform1i(location, *self) }
+    }
+}
+
+impl UniformValue for u32 {
+    fn apply(&self, location: i32) {
+        unsafe { gl::Uniform1ui(location, *self) }
+    }
+}
+
+impl UniformValue for bool {
+    fn apply(&self, location: i32) {
+        unsafe { gl::Uniform1i(location, *self as i32) }
+    }
+}
+
+impl UniformValue for [f32; 2] {
+    fn apply(&self, location: i32) {
+        unsafe { gl::Uniform2f(location, self[0], self[1]) }
+    }
+}
+
+impl UniformValue for [f32; 3] {
+    fn apply(&self, location: i32) {
+        unsafe { gl::Uniform3f(location, self[0], self[1], self[2]) }
+    }
+}
+
+impl UniformValue for [f32; 4] {
+    fn apply(&self, location: i32) {
+        unsafe { gl::Uniform4f(location, self[0], self[1], self[2], self[3]) }
+    }
+}
+
+impl UniformValue for glam::Vec2 {
+    fn apply(&self, location: i32) {
+        self.to_array().apply(location)
+    }
+}
+
+impl UniformValue for glam::Vec3 {
+    fn apply(&self, location: i32) {
+        self.to_array().apply(location)
+    }
+}
+
+impl UniformValue for glam::Vec4 {
+    fn apply(&self, location: i32) {
+        self.to_array().apply(location)
+    }
+}
+
+impl UniformValue for glam::Mat4 {
+    fn apply(&self, location: i32) {
+        unsafe { gl::UniformMatrix4fv(location, 1, gl::FALSE, self.to_cols_array().as_ptr()) }
+    }
+}
+
+/// A linked OpenGL shader program
+///
+/// Uniform locations are looked up once per name and cached. The program
+/// is deleted on drop, so drop it while its context is still current.
+#[derive(Debug)]
+pub struct Shader {
+    program: u32,
+    uniforms: UniformLocations,
+}
+
+impl Shader {
+    /// Build a program from vertex and fragment shader source
+    pub fn from_sources(vertex: &str, fragment: &str) -> Result<Self, ShaderError> {
+        Self::from_stages(&[(ShaderStage::Vertex, vertex), (ShaderStage::Fragment, fragment)])
+    }
+
+    /// Build a program from the source of each stage
+    pub fn from_stages(stages: &[(ShaderStage, &str)]) -> Result<Self, ShaderError> {
+        let mut shaders = Vec::with_capacity(stages.len());
+        for (stage, source) in stages {
+            match compile(*stage, source) {
+                Ok(shader) => shaders.push(shader),
+                Err(e) => {
+                    unsafe { shaders.iter().for_each(|shader| gl::DeleteShader(*shader)) };
+                    return Err(e);
+                }
+            }
+        }
+
+        unsafe {
+            let program = gl::CreateProgram();
+            for shader in &shaders {
+                gl::AttachShader(program, *shader);
+            }
+            gl::LinkProgram(program);
+            // The program keeps what it needs of the stages once linked
+            for shader in shaders {
+                gl::DetachShader(program, shader);
+                gl::DeleteShader(shader);
+            }
+
+            let mut linked = 0;
+            gl::GetProgramiv(program, gl::LINK_STATUS, &mut linked);
+            if linked == 0 {
+                let log = program_log(program);
+                gl::DeleteProgram(program);
+                return Err(ShaderError::Link { log });
+            }
+            debug!("Linked shader program {} from {} stages", program, stages.len());
+            Ok(Shader { program, uniforms: UniformLocations::default() })
+        }
+    }
+
+    /// Build a program from vertex and fragment shader files
+    pub fn from_files(vertex: impl AsRef<Path>, fragment: impl AsRef<Path>) -> Result<Self, ShaderError> {
+        let read = |path: &Path| {
+            fs::read_to_string(path).map_err(|e| ShaderError::Read { path: path.display().to_string(), error: e.to_string() })
+        };
+        Self::from_sources(&read(vertex.as_ref())?, &read(fragment.as_ref())?)
+    }
+
+    /// Build a program from shaders loaded by the asset server, one per stage
+    pub fn from_assets(shaders: &[&assets::Shader]) -> Result<Self, ShaderError> {
+        let stages: Vec<(ShaderStage, &str)> = shaders.iter().map(|shader| (shader.stage(), shader.source())).collect();
+        Self::from_stages(&stages)
+    }
+
+    /// OpenGL name of the program
+    pub fn id(&self) -> u32 {
+        self.program
+    }
+
+    /// Use the program for the following draw calls
+    pub fn bind(&self) {
+        unsafe { gl::UseProgram(self.program) }
+    }
+
+    /// Location of the uniform called `name`, or `None` if the program has no such active uniform
+    ///
+    /// Compilers drop uniforms the shader never reads, so a declared uniform can be missing too.
+    pub fn uniform_location(&mut self, name: &str) -> Option<i32> {
+        self.uniforms.location(self.program, name, |program, name| unsafe {
+            gl::GetUniformLocation(program, name.as_ptr())
+        })
+    }
+
+    /// Bind the program and set the uniform called `name`
+    ///
+    /// Returns false if there is no such uniform; that is only logged the first time.
+    pub fn set_uniform(&mut self, name: &str, value: impl UniformValue) -> bool {
+        let Some(location) = self.uniform_location(name) else { return false };
+        self.bind();
+        value.apply(location);
+        true
+    }
+
+    /// Let go of a program that died with its context, so dropping the shader deletes nothing
+    pub(crate) fn forget_program(&mut self) {
+        self.program = 0;
+        self.uniforms.clear();
+    }
+}
+
+/// Uniform locations of a program, looked up once per name
+#[derive(Debug, Default)]
+struct UniformLocations {
+    /// Location of each uniform asked for, -1 for names the program lacks
+    locations: HashMap<String, i32>,
+}
+
+impl UniformLocations {
+    /// Location of `name` in `program`, asking `lookup` only the first time the name comes up
+    ///
+    /// Missing uniforms are remembered as -1 and warned about once.
+    fn location(&mut self, program: u32, name: &str, lookup: impl FnOnce(u32, &CStr) -> i32) -> Option<i32> {
+        if let Some(&location) = self.locations.get(name) {
+            return (location >= 0).then_some(location);
+        }
+        let location = match CString::new(name) {
+            Ok(c_name) => lookup(program, &c_name),
+            Err(_) => -1,
+        };
+        if location < 0 {
+            warn!("Shader program {} has no active uniform '{}'", program, name);
+        }
+        self.locations.insert(name.to_string(), location);
+        (location >= 0).then_some(location)
+    }
+
+    fn clear(&mut self) {
+        self.locations.clear();
+    }
+}
+
+impl Drop for Shader {
+    /// Deleting program 0 is silently ignored by OpenGL
+    fn drop(&mut self) {
+        unsafe { gl::DeleteProgram(self.program) }
+    }
+}
+
+/// How a texture is sampled between its pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Blend neighbouring pixels, for photos and scaled artwork
+    Linear,
+    /// Take the closest pixel, keeping pixel art sharp
+    Nearest,
+}
+
+/// An RGBA image uploaded to the GPU
+///
+/// Row 0 of the image is at texture coordinate 0. Like `Shader`, it is
+/// deleted on drop and must be dropped while its context is current.
+#[derive(Debug)]
+pub struct Texture2D {
+    texture: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Texture2D {
+    /// Upload `rgba`, four bytes per pixel, row by row
+    pub fn from_rgba(width: u32, height: u32, rgba: &[u8]) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err(format!("Texture size {}x{} is empty", width, height));
+        }
+        if rgba.len() != width as usize * height as usize * 4 {
+            return Err(format!("{} bytes do not make a {}x{} RGBA texture", rgba.len(), width, height));
+        }
+        let mut texture = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA8 as i32,
+                width as i32,
+                height as i32,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                rgba.as_ptr() as *const _,
+            );
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+        }
+        let texture = Texture2D { texture, width, height };
+        texture.set_filter(TextureFilter::Linear);
+        Ok(texture)
+    }
+
+    pub fn from_image(image: &ImageBuffer) -> Self {
+        Self::from_rgba(image.width(), image.height(), image.rgba()).expect("ImageBuffer holds a whole RGBA image")
+    }
+
+    /// Upload a texture loaded by the asset server
+    pub fn from_asset(texture: &assets::Texture) -> Self {
+        Self::from_image(texture.image())
+    }
+
+    /// OpenGL name of the texture
+    pub fn id(&self) -> u32 {
+        self.texture
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn set_filter(&self, filter: TextureFilter) {
+        let filter = match filter {
+            TextureFilter::Linear => gl::LINEAR,
+            TextureFilter::Nearest => gl::NEAREST,
+        } as i32;
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter);
+        }
+    }
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteTextures(1, &self.texture) }
+    }
+}
+
+fn gl_stage(stage: ShaderStage) -> gl::types::GLenum {
+    match stage {
+        ShaderStage::Vertex => gl::VERTEX_SHADER,
+        ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+        ShaderStage::Geometry => gl::GEOMETRY_SHADER,
+        ShaderStage::Compute => gl::COMPUTE_SHADER,
+    }
+}
+
+fn compile(stage: ShaderStage, source: &str) -> Result<u32, ShaderError> {
+    let source = CString::new(source).map_err(|_| ShaderError::InvalidSource { stage })?;
+    unsafe {
+        let shader = gl::CreateShader(gl_stage(stage));
+        gl::ShaderSource(shader, 1, &source.as_ptr(), std::ptr::null());
+        gl::CompileShader(shader);
+
+        let mut compiled = 0;
+        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut compiled);
+        if compiled == 0 {
+            let mut length = 0;
+            gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut length);
+            let mut log = vec![0u8; length.max(0) as usize];
+            gl::GetShaderInfoLog(shader, length, std::ptr::null_mut(), log.as_mut_ptr() as *mut _);
+            gl::DeleteShader(shader);
+            return Err(ShaderError::Compile { stage, log: log_text(log) });
+        }
+        Ok(shader)
+    }
+}
+
+unsafe fn program_log(program: u32) -> String {
+    let mut length = 0;
+    gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut length);
+    let mut log = vec![0u8; length.max(0) as usize];
+    gl::GetProgramInfoLog(program, length, std::ptr::null_mut(), log.as_mut_ptr() as *mut _);
+    log_text(log)
+}
+
+/// An info log as text, without its terminating NUL and trailing newlines
+fn log_text(log: Vec<u8>) -> String {
+    let text = String::from_utf8_lossy(&log);
+    let text = text.trim_end_matches(['\0', '\n', '\r', ' ']);
+    if text.is_empty() {
+        "no log from the driver".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_logs_and_errors_read_cleanly() {
+        assert_eq!(log_text(b"0:3(12): error: `colour' undeclared\n\0".to_vec()), "0:3(12): error: `colour' undeclared");
+        assert_eq!(log_text(vec![0]), "no log from the driver");
+
+        let error = ShaderError::Compile { stage: ShaderStage::Fragment, log: "0:1: syntax error".to_string() };
+        assert_eq!(error.to_string(), "Fragment shader failed to compile: 0:1: syntax error");
+        assert_eq!(gl_stage(ShaderStage::Compute), gl::COMPUTE_SHADER);
+    }
+
+    #[test]
+    fn test_uniform_locations_are_looked_up_once() {
+        let mut uniforms = UniformLocations::default();
+        let mut lookups = Vec::new();
+        let mut location = |uniforms: &mut UniformLocations, name: &str| {
+            uniforms.location(7, name, |program, name| {
+                lookups.push((program, name.to_str().unwrap().to_string()));
+                if name.to_bytes() == b"u_color" { 3 } else { -1 }
+            })
+        };
+        assert_eq!(location(&mut uniforms, "u_color"), Some(3));
+        assert_eq!(location(&mut uniforms, "u_color"), Some(3));
+        // Missing and unnameable uniforms are remembered as -1 rather than asked about every frame
+        assert_eq!(location(&mut uniforms, "u_unused"), None);
+        assert_eq!(location(&mut uniforms, "u_unused"), None);
+        assert_eq!(location(&mut uniforms, "bad\0name"), None);
+        assert_eq!(location(&mut uniforms, "bad\0name"), None);
+        assert_eq!(lookups, [(7, "u_color".to_string()), (7, "u_unused".to_string())]);
+
+        // A program rebuilt after a context switch asks again
+        uniforms.clear();
+        assert_eq!(uniforms.location(8, "u_color", |_, _| 5), Some(5));
+    }
+
+    #[test]
+    fn test_bad_sources_fail_before_reaching_opengl() {
+        let error = Shader::from_stages(&[(ShaderStage::Vertex, "void main() {}\0")]).unwrap_err();
+        assert_eq!(error, ShaderError::InvalidSource { stage: ShaderStage::Vertex });
+        let error = Shader::from_files("no/such/shader.vert", "no/such/shader.frag").unwrap_err();
+        assert!(matches!(error, ShaderError::Read { ref path, .. } if path == "no/such/shader.vert"));
+        assert!(error.to_string().starts_with("Failed to read shader no/such/shader.vert: "));
+    }
+}
@@ -0,0 +1,527 @@
+//! Graphics API independent access to the GPU
+//!
+//! A `RenderDevice` creates buffers, textures and pipelines and hands out
+//! handles to them; the `RenderContext` from `begin_frame` draws with them.
+//! Code written against these traits runs on every graphics API the engine
+//! implements them for, currently OpenGL through `OpenGlDevice`.
+//!
+//! Handles outlive window backend switches: the engine has the device
+//! recreate every resource in the new context, under the same handles.
+
+use super::{ShaderError, TextureFilter};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// The device the engine shares with the application
+///
+/// `Send` so applications can keep it, not so other threads can draw.
+pub type SharedRenderDevice = Arc<Mutex<dyn RenderDevice + Send>>;
+
+/// A buffer created by a `RenderDevice`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BufferHandle(pub(crate) u32);
+
+/// A texture created by a `RenderDevice`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextureHandle(pub(crate) u32);
+
+/// A pipeline created by a `RenderDevice`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PipelineHandle(pub(crate) u32);
+
+/// Why a device could not do what was asked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderError {
+    /// A pipeline's shaders failed to compile or link
+    Shader(ShaderError),
+    /// The handle names no resource of this device, e.g. because it was destroyed
+    UnknownHandle(String),
+    /// A description, or the data passed with it, does not add up
+    Invalid(String),
+    /// The graphics API cannot do this, e.g. SPIR-V shaders on OpenGL
+    Unsupported(String),
+    /// A draw was issued before the binding it needs, e.g. without a pipeline
+    MissingBinding(&'static str),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::Shader(e) => write!(f, "{}", e),
+            RenderError::UnknownHandle(handle) => write!(f, "{} does not exist on this device", handle),
+            RenderError::Invalid(reason) => write!(f, "{}", reason),
+            RenderError::Unsupported(what) => write!(f, "Not supported by this device: {}", what),
+            RenderError::MissingBinding(binding) => write!(f, "Cannot draw without a {} set", binding),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RenderError::Shader(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ShaderError> for RenderError {
+    fn from(error: ShaderError) -> Self {
+        RenderError::Shader(error)
+    }
+}
+
+/// What a device runs on and what it can do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Graphics API, e.g. "OpenGL"
+    pub api: String,
+    pub version: String,
+    /// GPU and driver, as the API names them
+    pub renderer: String,
+    /// Largest texture width or height
+    pub max_texture_size: u32,
+    /// Textures a draw can sample at once
+    pub max_texture_slots: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferKind {
+    Vertex,
+    Index,
+    Uniform,
+}
+
+/// How often a buffer's contents change, so the device can place it well
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferUsage {
+    /// Written once and drawn many times
+    Static,
+    /// Rewritten often, e.g. every frame
+    Dynamic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferDesc {
+    pub kind: BufferKind,
+    pub usage: BufferUsage,
+    /// Size in bytes
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    Rgba8,
+    /// One 8-bit channel, e.g. glyph coverage in a font atlas
+    R8,
+}
+
+impl TextureFormat {
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            TextureFormat::Rgba8 => 4,
+            TextureFormat::R8 => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureDesc {
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+    pub filter: TextureFilter,
+}
+
+impl TextureDesc {
+    /// An RGBA texture with linear filtering
+    pub fn rgba(width: u32, height: u32) -> Self {
+        Self { width, height, format: TextureFormat::Rgba8, filter: TextureFilter::Linear }
+    }
+
+    /// Bytes of pixel data the texture holds, row by row without padding
+    pub fn byte_len(&self) -> usize {
+        self.width as usize * self.height as usize * self.format.bytes_per_pixel()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VertexFormat {
+    Float,
+    Float2,
+    Float3,
+    Float4,
+}
+
+impl VertexFormat {
+    pub fn components(self) -> u32 {
+        match self {
+            VertexFormat::Float => 1,
+            VertexFormat::Float2 => 2,
+            VertexFormat::Float3 => 3,
+            VertexFormat::Float4 => 4,
+        }
+    }
+
+    /// Size in bytes
+    pub fn size(self) -> usize {
+        self.components() as usize * std::mem::size_of::<f32>()
+    }
+}
+
+/// One input of the vertex shader, read from each vertex
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexAttribute {
+    /// The shader's `layout (location = ...)`
+    pub location: u32,
+    pub format: VertexFormat,
+    /// Bytes from the start of the vertex
+    pub offset: usize,
+}
+
+/// How the vertices in a vertex buffer are laid out
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VertexLayout {
+    /// Bytes from one vertex to the next
+    pub stride: usize,
+    pub attributes: Vec<VertexAttribute>,
+}
+
+impl VertexLayout {
+    pub fn new(stride: usize) -> Self {
+        Self { stride, attributes: Vec::new() }
+    }
+
+    pub fn attribute(mut self, location: u32, format: VertexFormat, offset: usize) -> Self {
+        self.attributes.push(VertexAttribute { location, format, offset });
+        self
+    }
+
+    /// Check that every attribute lies within the stride and has a location of its own
+    pub fn validate(&self) -> Result<(), RenderError> {
+        for (index, attribute) in self.attributes.iter().enumerate() {
+            if attribute.offset + attribute.format.size() > self.stride {
+                return Err(RenderError::Invalid(format!(
+                    "Vertex attribute at location {} ends at byte {}, past the {} byte stride",
+                    attribute.location,
+                    attribute.offset + attribute.format.size(),
+                    self.stride
+                )));
+            }
+            if self.attributes[..index].iter().any(|earlier| earlier.location == attribute.location) {
+                return Err(RenderError::Invalid(format!("Vertex location {} is used twice", attribute.location)));
+            }
+        }
+        Ok(())
+    }
+
+    /// How many whole vertices a buffer of `size` bytes holds for this layout
+    pub fn vertex_capacity(&self, size: usize) -> usize {
+        let footprint =
+            self.attributes.iter().map(|attribute| attribute.offset + attribute.format.size()).max().unwrap_or(0);
+        match size.checked_sub(footprint) {
+            None => 0,
+            Some(_) if self.stride == 0 => usize::MAX,
+            Some(rest) => rest / self.stride + 1,
+        }
+    }
+}
+
+/// The shaders of a pipeline, in the form a graphics API takes them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderSource {
+    /// GLSL for the vertex and fragment stages
+    Glsl { vertex: String, fragment: String },
+    /// SPIR-V for the vertex and fragment stages, as Vulkan and wgpu take them
+    SpirV { vertex: Vec<u32>, fragment: Vec<u32> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrimitiveTopology {
+    #[default]
+    Triangles,
+    Lines,
+    Points,
+}
+
+/// How drawn colours combine with what is already there
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// Replace it
+    #[default]
+    Opaque,
+    /// Mix by the drawn alpha
+    Alpha,
+    /// Add to it, for glows and particles
+    Additive,
+}
+
+/// Everything about drawing that is fixed when a pipeline is created
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineDesc {
+    pub shader: ShaderSource,
+    pub layout: VertexLayout,
+    pub topology: PrimitiveTopology,
+    pub blend: BlendMode,
+}
+
+impl PipelineDesc {
+    /// A pipeline drawing opaque triangles
+    pub fn new(shader: ShaderSource, layout: VertexLayout) -> Self {
+        Self { shader, layout, topology: PrimitiveTopology::default(), blend: BlendMode::default() }
+    }
+
+    pub fn topology(mut self, topology: PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFormat {
+    U16,
+    U32,
+}
+
+impl IndexFormat {
+    /// Size in bytes
+    pub fn size(self) -> usize {
+        match self {
+            IndexFormat::U16 => 2,
+            IndexFormat::U32 => 4,
+        }
+    }
+}
+
+/// A value for a shader uniform
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UniformData {
+    Float(f32),
+    /// Also selects the texture slot a sampler reads
+    Int(i32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+    /// Column major
+    Mat4([f32; 16]),
+}
+
+impl From<f32> for UniformData {
+    fn from(value: f32) -> Self {
+        UniformData::Float(value)
+    }
+}
+
+impl From<i32> for UniformData {
+    fn from(value: i32) -> Self {
+        UniformData::Int(value)
+    }
+}
+
+impl From<[f32; 2]> for UniformData {
+    fn from(value: [f32; 2]) -> Self {
+        UniformData::Vec2(value)
+    }
+}
+
+impl From<[f32; 3]> for UniformData {
+    fn from(value: [f32; 3]) -> Self {
+        UniformData::Vec3(value)
+    }
+}
+
+impl From<[f32; 4]> for UniformData {
+    fn from(value: [f32; 4]) -> Self {
+        UniformData::Vec4(value)
+    }
+}
+
+impl From<glam::Vec2> for UniformData {
+    fn from(value: glam::Vec2) -> Self {
+        UniformData::Vec2(value.to_array())
+    }
+}
+
+impl From<glam::Vec3> for UniformData {
+    fn from(value: glam::Vec3) -> Self {
+        UniformData::Vec3(value.to_array())
+    }
+}
+
+impl From<glam::Vec4> for UniformData {
+    fn from(value: glam::Vec4) -> Self {
+        UniformData::Vec4(value.to_array())
+    }
+}
+
+impl From<glam::Mat4> for UniformData {
+    fn from(value: glam::Mat4) -> Self {
+        UniformData::Mat4(value.to_cols_array())
+    }
+}
+
+/// Draws one frame with a device's resources
+///
+/// Bindings persist from one draw to the next. Binding a handle the device
+/// does not know logs a warning and leaves that binding empty, so the next
+/// draw fails with `RenderError::MissingBinding`.
+pub trait RenderContext {
+    /// Part of the window to draw to, in pixels from its bottom left corner
+    fn set_viewport(&mut self, x: i32, y: i32, width: u32, height: u32);
+    fn clear(&mut self, color: [f32; 4]);
+    fn set_pipeline(&mut self, pipeline: PipelineHandle);
+    fn set_vertex_buffer(&mut self, buffer: BufferHandle);
+    fn set_index_buffer(&mut self, buffer: BufferHandle, format: IndexFormat);
+    /// Sample `texture` through `slot`; point a sampler uniform at it with `UniformData::Int(slot)`
+    fn set_texture(&mut self, slot: u32, texture: TextureHandle);
+    /// Set a uniform of the current pipeline by name
+    fn set_uniform(&mut self, name: &str, value: UniformData);
+    /// Draw `vertex_count` vertices of the vertex buffer from `first_vertex`
+    fn draw(&mut self, first_vertex: u32, vertex_count: u32) -> Result<(), RenderError>;
+    /// Draw the vertices that `index_count` indices of the index buffer point at, from `first_index`
+    fn draw_indexed(&mut self, first_index: u32, index_count: u32) -> Result<(), RenderError>;
+}
+
+/// Creates GPU resources and draws with them, whatever the graphics API
+///
+/// Like the window it draws to, a device may only be used on the engine's
+/// thread. Resources live until destroyed or until the device is dropped.
+pub trait RenderDevice {
+    fn info(&self) -> &DeviceInfo;
+
+    /// Create a buffer of `desc.size` bytes, starting with `contents` if given
+    fn create_buffer(&mut self, desc: BufferDesc, contents: Option<&[u8]>) -> Result<BufferHandle, RenderError>;
+    /// Replace part of a buffer's contents, starting `offset` bytes in
+    fn write_buffer(&mut self, buffer: BufferHandle, offset: usize, data: &[u8]) -> Result<(), RenderError>;
+    /// Returns false if the buffer does not exist
+    fn destroy_buffer(&mut self, buffer: BufferHandle) -> bool;
+
+    /// Create a texture, filled with `pixels` if given, row by row from the top
+    fn create_texture(&mut self, desc: TextureDesc, pixels: Option<&[u8]>) -> Result<TextureHandle, RenderError>;
+    /// Replace all of a texture's pixels
+    fn write_texture(&mut self, texture: TextureHandle, pixels: &[u8]) -> Result<(), RenderError>;
+    /// Returns false if the texture does not exist
+    fn destroy_texture(&mut self, texture: TextureHandle) -> bool;
+
+    fn create_pipeline(&mut self, desc: PipelineDesc) -> Result<PipelineHandle, RenderError>;
+    /// Returns false if the pipeline does not exist
+    fn destroy_pipeline(&mut self, pipeline: PipelineHandle) -> bool;
+
+    /// Start drawing to the current window; the drawing is submitted when the context is dropped
+    fn begin_frame(&mut self) -> Box<dyn RenderContext + '_>;
+
+    /// Recreate every resource after the window backend switched, keeping their handles
+    ///
+    /// The engine calls this once the new context is current. Returns what
+    /// could not be recreated; handles of those resources stay unknown.
+    fn recreate_resources(&mut self) -> Vec<String>;
+}
+
+/// Check that writing `len` bytes at `offset` stays within `size` bytes
+pub(crate) fn check_range(what: &str, size: usize, offset: usize, len: usize) -> Result<(), RenderError> {
+    match offset.checked_add(len) {
+        Some(end) if end <= size => Ok(()),
+        _ => Err(RenderError::Invalid(format!("Writing {} bytes at {} overruns the {} byte {}", len, offset, size, what))),
+    }
+}
+
+/// Check that drawing `count` vertices from `first` stays within the `capacity` a vertex buffer holds
+pub(crate) fn check_vertices(capacity: usize, first: u32, count: u32) -> Result<(), RenderError> {
+    match (first as usize).checked_add(count as usize) {
+        Some(end) if end <= capacity => Ok(()),
+        _ => Err(RenderError::Invalid(format!(
+            "Drawing {} vertices from {} overruns the {} the vertex buffer holds",
+            count, first, capacity
+        ))),
+    }
+}
+
+/// Check that `count` indices from `first` lie within `indices`, and that each points at one of `capacity` vertices
+pub(crate) fn check_indices(
+    indices: &[u8],
+    format: IndexFormat,
+    first: u32,
+    count: u32,
+    capacity: usize,
+) -> Result<(), RenderError> {
+    let range = (first as usize).checked_mul(format.size()).and_then(|start| {
+        let end = (count as usize).checked_mul(format.size())?.checked_add(start)?;
+        indices.get(start..end)
+    });
+    let Some(range) = range else {
+        return Err(RenderError::Invalid(format!(
+            "Drawing {} indices from {} overruns the {} byte index buffer",
+            count,
+            first,
+            indices.len()
+        )));
+    };
+    let out_of_range = match format {
+        IndexFormat::U16 => range
+            .chunks_exact(2)
+            .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]) as usize)
+            .find(|&index| index >= capacity),
+        IndexFormat::U32 => range
+            .chunks_exact(4)
+            .map(|bytes| u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+            .find(|&index| index >= capacity),
+    };
+    match out_of_range {
+        Some(index) => Err(RenderError::Invalid(format!(
+            "Index {} points past the {} vertices the vertex buffer holds",
+            index, capacity
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptions_are_validated() {
+        let layout = VertexLayout::new(20).attribute(0, VertexFormat::Float3, 0).attribute(1, VertexFormat::Float2, 12);
+        assert_eq!(layout.validate(), Ok(()));
+        let overrun = layout.clone().attribute(2, VertexFormat::Float, 20);
+        assert!(matches!(overrun.validate(), Err(RenderError::Invalid(reason)) if reason.contains("past the 20 byte stride")));
+        let reused = layout.attribute(1, VertexFormat::Float, 0);
+        assert!(reused.validate().is_err());
+
+        assert_eq!(TextureDesc { format: TextureFormat::R8, ..TextureDesc::rgba(8, 4) }.byte_len(), 32);
+        assert_eq!(check_range("buffer", 64, 60, 4), Ok(()));
+        assert!(check_range("buffer", 64, 61, 4).is_err());
+        assert!(check_range("buffer", 64, usize::MAX, 1).is_err());
+
+        assert_eq!(UniformData::from(glam::Vec3::new(1.0, 2.0, 3.0)), UniformData::Vec3([1.0, 2.0, 3.0]));
+        assert_eq!(UniformData::from(glam::Mat4::IDENTITY), UniformData::Mat4(glam::Mat4::IDENTITY.to_cols_array()));
+        assert_eq!(RenderError::MissingBinding("pipeline").to_string(), "Cannot draw without a pipeline set");
+    }
+
+    #[test]
+    fn test_draws_stay_within_their_buffers() {
+        // The last vertex only needs its attributes, not a whole stride
+        let layout = VertexLayout::new(20).attribute(0, VertexFormat::Float3, 0);
+        assert_eq!(layout.vertex_capacity(60), 3);
+        assert_eq!(layout.vertex_capacity(52), 3);
+        assert_eq!(layout.vertex_capacity(51), 2);
+        assert_eq!(layout.vertex_capacity(8), 0);
+        assert_eq!(check_vertices(3, 1, 2), Ok(()));
+        assert!(matches!(check_vertices(3, 2, 2), Err(RenderError::Invalid(reason)) if reason.contains("overruns")));
+        assert!(check_vertices(3, u32::MAX, u32::MAX).is_err());
+
+        let indices: Vec<u8> = [0u16, 1, 2, 2, 3, 0].iter().flat_map(|index| index.to_ne_bytes()).collect();
+        assert_eq!(check_indices(&indices, IndexFormat::U16, 0, 6, 4), Ok(()));
+        assert_eq!(check_indices(&indices, IndexFormat::U16, 0, 3, 3), Ok(()));
+        let stray = check_indices(&indices, IndexFormat::U16, 0, 6, 3);
+        assert!(matches!(stray, Err(RenderError::Invalid(reason)) if reason.contains("Index 3")));
+        assert!(check_indices(&indices, IndexFormat::U16, 4, 3, 4).is_err());
+        assert!(check_indices(&indices, IndexFormat::U32, 0, 4, 4).is_err());
+        assert!(check_indices(&indices, IndexFormat::U32, u32::MAX, u32::MAX, 4).is_err());
+    }
+}
@@ -0,0 +1,505 @@
+use super::device::{
+    check_indices, check_range, check_vertices, BlendMode, BufferDesc, BufferHandle, BufferKind, BufferUsage,
+    DeviceInfo, IndexFormat, PipelineDesc, PipelineHandle, PrimitiveTopology, RenderContext, RenderDevice, RenderError,
+    ShaderSource, TextureDesc, TextureFormat, TextureHandle, UniformData,
+};
+use super::{Shader, TextureFilter, UniformValue};
+use artifice_logging::{debug, info, warn};
+use std::collections::HashMap;
+use std::ffi::CStr;
+
+impl UniformValue for UniformData {
+    fn apply(&self, location: i32) {
+        match self {
+            UniformData::Float(value) => value.apply(location),
+            UniformData::Int(value) => value.apply(location),
+            UniformData::Vec2(value) => value.apply(location),
+            UniformData::Vec3(value) => value.apply(location),
+            UniformData::Vec4(value) => value.apply(location),
+            UniformData::Mat4(value) => unsafe { gl::UniformMatrix4fv(location, 1, gl::FALSE, value.as_ptr()) },
+        }
+    }
+}
+
+struct GlBuffer {
+    desc: BufferDesc,
+    name: u32,
+    /// Copy of the contents, to refill the buffer after a context switch
+    contents: Vec<u8>,
+}
+
+struct GlTexture {
+    desc: TextureDesc,
+    name: u32,
+    /// Copy of the pixels, if any were given, to refill the texture after a context switch
+    pixels: Option<Vec<u8>>,
+}
+
+struct GlPipeline {
+    desc: PipelineDesc,
+    shader: Shader,
+    vertex_array: u32,
+}
+
+/// `RenderDevice` drawing with the current OpenGL context
+///
+/// Keeps a copy of every buffer and texture's contents so it can recreate
+/// them after a backend switch.
+pub struct OpenGlDevice {
+    info: DeviceInfo,
+    buffers: HashMap<BufferHandle, GlBuffer>,
+    textures: HashMap<TextureHandle, GlTexture>,
+    pipelines: HashMap<PipelineHandle, GlPipeline>,
+    next_handle: u32,
+}
+
+impl OpenGlDevice {
+    /// Create a device for the current context; fails if no OpenGL functions are loaded
+    pub fn new() -> Result<Self, RenderError> {
+        if !gl::GetString::is_loaded() || !gl::CreateProgram::is_loaded() {
+            return Err(RenderError::Unsupported("no OpenGL context is loaded".to_string()));
+        }
+        let info = query_info();
+        info!("OpenGL render device on {} ({})", info.renderer, info.version);
+        Ok(Self { info, buffers: HashMap::new(), textures: HashMap::new(), pipelines: HashMap::new(), next_handle: 1 })
+    }
+
+    fn next_handle(&mut self) -> u32 {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        handle
+    }
+}
+
+impl RenderDevice for OpenGlDevice {
+    fn info(&self) -> &DeviceInfo {
+        &self.info
+    }
+
+    fn create_buffer(&mut self, desc: BufferDesc, contents: Option<&[u8]>) -> Result<BufferHandle, RenderError> {
+        let mut copy = vec![0; desc.size];
+        if let Some(contents) = contents {
+            check_range("buffer", desc.size, 0, contents.len())?;
+            copy[..contents.len()].copy_from_slice(contents);
+        }
+        let name = upload_buffer(&desc, &copy);
+        let handle = BufferHandle(self.next_handle());
+        debug!("Created {:?} {:?} of {} bytes as {}", desc.kind, handle, desc.size, name);
+        self.buffers.insert(handle, GlBuffer { desc, name, contents: copy });
+        Ok(handle)
+    }
+
+    fn write_buffer(&mut self, buffer: BufferHandle, offset: usize, data: &[u8]) -> Result<(), RenderError> {
+        let gl_buffer = self.buffers.get_mut(&buffer).ok_or_else(|| RenderError::UnknownHandle(format!("{:?}", buffer)))?;
+        check_range("buffer", gl_buffer.desc.size, offset, data.len())?;
+        gl_buffer.contents[offset..offset + data.len()].copy_from_slice(data);
+        unsafe {
+            // The copy target leaves the vertex array bindings alone
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, gl_buffer.name);
+            gl::BufferSubData(gl::COPY_WRITE_BUFFER, offset as isize, data.len() as isize, data.as_ptr() as *const _);
+        }
+        Ok(())
+    }
+
+    fn destroy_buffer(&mut self, buffer: BufferHandle) -> bool {
+        let Some(gl_buffer) = self.buffers.remove(&buffer) else { return false };
+        unsafe { gl::DeleteBuffers(1, &gl_buffer.name) };
+        true
+    }
+
+    fn create_texture(&mut self, desc: TextureDesc, pixels: Option<&[u8]>) -> Result<TextureHandle, RenderError> {
+        if desc.width == 0 || desc.height == 0 || desc.width.max(desc.height) > self.info.max_texture_size {
+            return Err(RenderError::Invalid(format!(
+                "Texture size {}x{} is empty or above the limit of {}",
+                desc.width, desc.height, self.info.max_texture_size
+            )));
+        }
+        if let Some(pixels) = pixels {
+            check_pixels(&desc, pixels)?;
+        }
+        let name = upload_texture(&desc, pixels);
+        let handle = TextureHandle(self.next_handle());
+        debug!("Created {}x{} {:?} texture {:?} as {}", desc.width, desc.height, desc.format, handle, name);
+        self.textures.insert(handle, GlTexture { desc, name, pixels: pixels.map(<[u8]>::to_vec) });
+        Ok(handle)
+    }
+
+    fn write_texture(&mut self, texture: TextureHandle, pixels: &[u8]) -> Result<(), RenderError> {
+        let gl_texture = self.textures.get_mut(&texture).ok_or_else(|| RenderError::UnknownHandle(format!("{:?}", texture)))?;
+        check_pixels(&gl_texture.desc, pixels)?;
+        let (_, format) = texture_formats(gl_texture.desc.format);
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, gl_texture.name);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                0,
+                0,
+                gl_texture.desc.width as i32,
+                gl_texture.desc.height as i32,
+                format,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr() as *const _,
+            );
+        }
+        gl_texture.pixels = Some(pixels.to_vec());
+        Ok(())
+    }
+
+    fn destroy_texture(&mut self, texture: TextureHandle) -> bool {
+        let Some(gl_texture) = self.textures.remove(&texture) else { return false };
+        unsafe { gl::DeleteTextures(1, &gl_texture.name) };
+        true
+    }
+
+    fn create_pipeline(&mut self, desc: PipelineDesc) -> Result<PipelineHandle, RenderError> {
+        desc.layout.validate()?;
+        let (shader, vertex_array) = build_pipeline(&desc)?;
+        let handle = PipelineHandle(self.next_handle());
+        debug!("Created pipeline {:?} with shader program {}", handle, shader.id());
+        self.pipelines.insert(handle, GlPipeline { desc, shader, vertex_array });
+        Ok(handle)
+    }
+
+    fn destroy_pipeline(&mut self, pipeline: PipelineHandle) -> bool {
+        let Some(gl_pipeline) = self.pipelines.remove(&pipeline) else { return false };
+        unsafe { gl::DeleteVertexArrays(1, &gl_pipeline.vertex_array) };
+        true
+    }
+
+    fn begin_frame(&mut self) -> Box<dyn RenderContext + '_> {
+        Box::new(OpenGlContext { device: self, pipeline: None, vertex_buffer: None, index_buffer: None })
+    }
+
+    fn recreate_resources(&mut self) -> Vec<String> {
+        // The old objects died with the old context, so nothing is deleted
+        self.info = query_info();
+        let mut errors = Vec::new();
+        for gl_buffer in self.buffers.values_mut() {
+            gl_buffer.name = upload_buffer(&gl_buffer.desc, &gl_buffer.contents);
+        }
+        for gl_texture in self.textures.values_mut() {
+            gl_texture.name = upload_texture(&gl_texture.desc, gl_texture.pixels.as_deref());
+        }
+        self.pipelines.retain(|handle, gl_pipeline| {
+            // Deleting the dead program could hit an object of the new context with the same name
+            gl_pipeline.shader.forget_program();
+            match build_pipeline(&gl_pipeline.desc) {
+                Ok((shader, vertex_array)) => {
+                    gl_pipeline.shader = shader;
+                    gl_pipeline.vertex_array = vertex_array;
+                    true
+                }
+                Err(e) => {
+                    warn!("Failed to recreate pipeline {:?}: {}", handle, e);
+                    errors.push(format!("{:?}: {}", handle, e));
+                    false
+                }
+            }
+        });
+        info!(
+            "Recreated {} buffers, {} textures and {} pipelines on {}",
+            self.buffers.len(),
+            self.textures.len(),
+            self.pipelines.len(),
+            self.info.renderer
+        );
+        errors
+    }
+}
+
+impl Drop for OpenGlDevice {
+    fn drop(&mut self) {
+        for handle in self.buffers.keys().copied().collect::<Vec<_>>() {
+            self.destroy_buffer(handle);
+        }
+        for handle in self.textures.keys().copied().collect::<Vec<_>>() {
+            self.destroy_texture(handle);
+        }
+        for handle in self.pipelines.keys().copied().collect::<Vec<_>>() {
+            self.destroy_pipeline(handle);
+        }
+    }
+}
+
+/// Draws immediately; OpenGL has nothing to submit at the end
+struct OpenGlContext<'a> {
+    device: &'a mut OpenGlDevice,
+    pipeline: Option<PipelineHandle>,
+    vertex_buffer: Option<BufferHandle>,
+    index_buffer: Option<(BufferHandle, IndexFormat)>,
+}
+
+impl OpenGlContext<'_> {
+    /// Bind the vertex buffer to the pipeline's attributes for a draw
+    fn prepare(&self) -> Result<&GlPipeline, RenderError> {
+        let pipeline = self.pipeline.and_then(|handle| self.device.pipelines.get(&handle));
+        let pipeline = pipeline.ok_or(RenderError::MissingBinding("pipeline"))?;
+        let buffer = self.vertex_buffer.and_then(|handle| self.device.buffers.get(&handle));
+        let buffer = buffer.ok_or(RenderError::MissingBinding("vertex buffer"))?;
+        let layout = &pipeline.desc.layout;
+        unsafe {
+            gl::BindVertexArray(pipeline.vertex_array);
+            gl::BindBuffer(gl::ARRAY_BUFFER, buffer.name);
+            for attribute in &layout.attributes {
+                gl::VertexAttribPointer(
+                    attribute.location,
+                    attribute.format.components() as i32,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    layout.stride as i32,
+                    attribute.offset as *const _,
+                );
+            }
+        }
+        Ok(pipeline)
+    }
+
+    /// How many vertices of the bound vertex buffer the current pipeline can read
+    fn vertex_capacity(&self) -> Result<usize, RenderError> {
+        let pipeline = self.pipeline.and_then(|handle| self.device.pipelines.get(&handle));
+        let pipeline = pipeline.ok_or(RenderError::MissingBinding("pipeline"))?;
+        let buffer = self.vertex_buffer.and_then(|handle| self.device.buffers.get(&handle));
+        let buffer = buffer.ok_or(RenderError::MissingBinding("vertex buffer"))?;
+        Ok(pipeline.desc.layout.vertex_capacity(buffer.desc.size))
+    }
+
+    /// The buffer behind `handle` if it exists and is of `kind`, else a warning
+    fn buffer_of_kind(&self, handle: BufferHandle, kind: BufferKind) -> Option<BufferHandle> {
+        match self.device.buffers.get(&handle) {
+            Some(buffer) if buffer.desc.kind == kind => Some(handle),
+            Some(buffer) => {
+                warn!("{:?} is a {:?} buffer, not a {:?} buffer", handle, buffer.desc.kind, kind);
+                None
+            }
+            None => {
+                warn!("{:?} does not exist on this device", handle);
+                None
+            }
+        }
+    }
+}
+
+impl RenderContext for OpenGlContext<'_> {
+    fn set_viewport(&mut self, x: i32, y: i32, width: u32, height: u32) {
+        unsafe { gl::Viewport(x, y, width as i32, height as i32) }
+    }
+
+    fn clear(&mut self, color: [f32; 4]) {
+        unsafe {
+            gl::ClearColor(color[0], color[1], color[2], color[3]);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+    }
+
+    fn set_pipeline(&mut self, pipeline: PipelineHandle) {
+        let Some(gl_pipeline) = self.device.pipelines.get(&pipeline) else {
+            warn!("{:?} does not exist on this device", pipeline);
+            self.pipeline = None;
+            return;
+        };
+        gl_pipeline.shader.bind();
+        unsafe {
+            match gl_pipeline.desc.blend {
+                BlendMode::Opaque => gl::Disable(gl::BLEND),
+                BlendMode::Alpha => {
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                }
+                BlendMode::Additive => {
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::SRC_ALPHA, gl::ONE);
+                }
+            }
+        }
+        self.pipeline = Some(pipeline);
+    }
+
+    fn set_vertex_buffer(&mut self, buffer: BufferHandle) {
+        self.vertex_buffer = self.buffer_of_kind(buffer, BufferKind::Vertex);
+    }
+
+    fn set_index_buffer(&mut self, buffer: BufferHandle, format: IndexFormat) {
+        self.index_buffer = self.buffer_of_kind(buffer, BufferKind::Index).map(|buffer| (buffer, format));
+    }
+
+    fn set_texture(&mut self, slot: u32, texture: TextureHandle) {
+        if slot >= self.device.info.max_texture_slots {
+            warn!("Texture slot {} is beyond the {} this device has", slot, self.device.info.max_texture_slots);
+            return;
+        }
+        let Some(gl_texture) = self.device.textures.get(&texture) else {
+            warn!("{:?} does not exist on this device", texture);
+            return;
+        };
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + slot);
+            gl::BindTexture(gl::TEXTURE_2D, gl_texture.name);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+    }
+
+    fn set_uniform(&mut self, name: &str, value: UniformData) {
+        let Some(gl_pipeline) = self.pipeline.and_then(|handle| self.device.pipelines.get_mut(&handle)) else {
+            warn!("Cannot set uniform '{}' without a pipeline set", name);
+            return;
+        };
+        gl_pipeline.shader.set_uniform(name, value);
+    }
+
+    fn draw(&mut self, first_vertex: u32, vertex_count: u32) -> Result<(), RenderError> {
+        check_vertices(self.vertex_capacity()?, first_vertex, vertex_count)?;
+        let pipeline = self.prepare()?;
+        unsafe { gl::DrawArrays(gl_topology(pipeline.desc.topology), first_vertex as i32, vertex_count as i32) };
+        Ok(())
+    }
+
+    fn draw_indexed(&mut self, first_index: u32, index_count: u32) -> Result<(), RenderError> {
+        let (index_buffer, format) = self.index_buffer.ok_or(RenderError::MissingBinding("index buffer"))?;
+        let index_buffer = self.device.buffers.get(&index_buffer).ok_or(RenderError::MissingBinding("index buffer"))?;
+        // The driver would read whatever lies past either buffer
+        check_indices(&index_buffer.contents, format, first_index, index_count, self.vertex_capacity()?)?;
+        let index_name = index_buffer.name;
+        let pipeline = self.prepare()?;
+        let index_type = match format {
+            IndexFormat::U16 => gl::UNSIGNED_SHORT,
+            IndexFormat::U32 => gl::UNSIGNED_INT,
+        };
+        unsafe {
+            // Part of the vertex array's state, so bound after it
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, index_name);
+            gl::DrawElements(
+                gl_topology(pipeline.desc.topology),
+                index_count as i32,
+                index_type,
+                (first_index as usize * format.size()) as *const _,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Drop for OpenGlContext<'_> {
+    /// Leave no vertex array bound for raw OpenGL code drawing after the frame
+    fn drop(&mut self) {
+        unsafe { gl::BindVertexArray(0) }
+    }
+}
+
+fn query_info() -> DeviceInfo {
+    let string = |name| unsafe {
+        let value = gl::GetString(name);
+        if value.is_null() {
+            "unknown".to_string()
+        } else {
+            CStr::from_ptr(value as *const _).to_string_lossy().into_owned()
+        }
+    };
+    let integer = |name| {
+        let mut value = 0;
+        unsafe { gl::GetIntegerv(name, &mut value) };
+        value.max(0) as u32
+    };
+    DeviceInfo {
+        api: "OpenGL".to_string(),
+        version: string(gl::VERSION),
+        renderer: string(gl::RENDERER),
+        max_texture_size: integer(gl::MAX_TEXTURE_SIZE),
+        max_texture_slots: integer(gl::MAX_TEXTURE_IMAGE_UNITS),
+    }
+}
+
+fn upload_buffer(desc: &BufferDesc, contents: &[u8]) -> u32 {
+    let usage = match desc.usage {
+        BufferUsage::Static => gl::STATIC_DRAW,
+        BufferUsage::Dynamic => gl::DYNAMIC_DRAW,
+    };
+    let mut name = 0;
+    unsafe {
+        gl::GenBuffers(1, &mut name);
+        gl::BindBuffer(gl::COPY_WRITE_BUFFER, name);
+        gl::BufferData(gl::COPY_WRITE_BUFFER, contents.len() as isize, contents.as_ptr() as *const _, usage);
+    }
+    name
+}
+
+/// Internal and pixel format of a texture format
+fn texture_formats(format: TextureFormat) -> (u32, u32) {
+    match format {
+        TextureFormat::Rgba8 => (gl::RGBA8, gl::RGBA),
+        TextureFormat::R8 => (gl::R8, gl::RED),
+    }
+}
+
+fn check_pixels(desc: &TextureDesc, pixels: &[u8]) -> Result<(), RenderError> {
+    if pixels.len() == desc.byte_len() {
+        Ok(())
+    } else {
+        Err(RenderError::Invalid(format!(
+            "{} bytes do not fill a {}x{} {:?} texture",
+            pixels.len(),
+            desc.width,
+            desc.height,
+            desc.format
+        )))
+    }
+}
+
+fn upload_texture(desc: &TextureDesc, pixels: Option<&[u8]>) -> u32 {
+    let (internal_format, format) = texture_formats(desc.format);
+    let filter = match desc.filter {
+        TextureFilter::Linear => gl::LINEAR,
+        TextureFilter::Nearest => gl::NEAREST,
+    } as i32;
+    let mut name = 0;
+    unsafe {
+        gl::GenTextures(1, &mut name);
+        gl::BindTexture(gl::TEXTURE_2D, name);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            internal_format as i32,
+            desc.width as i32,
+            desc.height as i32,
+            0,
+            format,
+            gl::UNSIGNED_BYTE,
+            pixels.map_or(std::ptr::null(), |pixels| pixels.as_ptr() as *const _),
+        );
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+    }
+    name
+}
+
+/// Link the pipeline's program and make the vertex array its attributes are read through
+fn build_pipeline(desc: &PipelineDesc) -> Result<(Shader, u32), RenderError> {
+    let shader = match &desc.shader {
+        ShaderSource::Glsl { vertex, fragment } => Shader::from_sources(vertex, fragment)?,
+        ShaderSource::SpirV { .. } => {
+            return Err(RenderError::Unsupported("SPIR-V shaders, OpenGL pipelines take GLSL".to_string()))
+        }
+    };
+    let mut vertex_array = 0;
+    unsafe {
+        gl::GenVertexArrays(1, &mut vertex_array);
+        gl::BindVertexArray(vertex_array);
+        for attribute in &desc.layout.attributes {
+            gl::EnableVertexAttribArray(attribute.location);
+        }
+        gl::BindVertexArray(0);
+    }
+    Ok((shader, vertex_array))
+}
+
+fn gl_topology(topology: PrimitiveTopology) -> u32 {
+    match topology {
+        PrimitiveTopology::Triangles => gl::TRIANGLES,
+        PrimitiveTopology::Lines => gl::LINES,
+        PrimitiveTopology::Points => gl::POINTS,
+    }
+}
@@ -0,0 +1,515 @@
+//! Batched drawing of rectangles, sprites, lines and text glyphs
+//!
+//! `Renderer2D` collects quads between `begin` and `end` and draws them in
+//! as few draw calls as it can: a batch holds up to `MAX_QUADS` quads using
+//! up to `MAX_TEXTURE_SLOTS` textures. Quads are drawn in the order they were
+//! submitted, later ones on top.
+//!
+//! Create the renderer once the window's context is current, e.g. in
+//! `Layer::attach`, and draw from `Layer::render`:
+//!
+//! ```ignore
+//! fn render(&mut self) {
+//!     self.renderer.begin(&self.camera);
+//!     self.renderer.draw_rect(Vec2::ZERO, Vec2::splat(100.0), [1.0, 0.5, 0.2, 1.0]);
+//!     self.renderer.draw_line(Vec2::ZERO, Vec2::new(200.0, 80.0), 2.0, [1.0; 4]);
+//!     self.renderer.end();
+//! }
+//! ```
+
+use crate::renderer::{Shader, ShaderError, Texture2D};
+use artifice_logging::warn;
+use glam::{Mat4, Vec2};
+use std::mem;
+
+/// Most quads drawn in one draw call
+pub const MAX_QUADS: usize = 10_000;
+/// Most textures one batch can sample; OpenGL 3.3 guarantees this many units
+pub const MAX_TEXTURE_SLOTS: usize = 16;
+
+const VERTEX_SOURCE: &str = "#version 330 core
+layout (location = 0) in vec3 a_position;
+layout (location = 1) in vec4 a_color;
+layout (location = 2) in vec2 a_tex_coords;
+layout (location = 3) in float a_texture_slot;
+layout (location = 4) in float a_coverage;
+
+uniform mat4 u_view_projection;
+
+out vec4 v_color;
+out vec2 v_tex_coords;
+flat out int v_texture_slot;
+out float v_coverage;
+
+void main() {
+    v_color = a_color;
+    v_tex_coords = a_tex_coords;
+    v_texture_slot = int(a_texture_slot + 0.5);
+    v_coverage = a_coverage;
+    gl_Position = u_view_projection * vec4(a_position, 1.0);
+}";
+
+/// Corners of the unit quad around the origin, counter-clockwise from the bottom left
+const QUAD_CORNERS: [Vec2; 4] = [Vec2::new(-0.5, -0.5), Vec2::new(0.5, -0.5), Vec2::new(0.5, 0.5), Vec2::new(-0.5, 0.5)];
+
+/// An RGBA colour with components from 0 to 1
+pub type Color = [f32; 4];
+
+/// Shows a rectangle of the 2D world, `width` by `height` world units at zoom 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrthographicCamera {
+    size: Vec2,
+    position: Vec2,
+    rotation: f32,
+    zoom: f32,
+}
+
+impl OrthographicCamera {
+    /// Camera centred on the origin; pass the window size in pixels for one world unit per pixel
+    pub fn new(width: f32, height: f32) -> Self {
+        Self { size: Vec2::new(width, height), position: Vec2::ZERO, rotation: 0.0, zoom: 1.0 }
+    }
+
+    /// Change the visible area, e.g. after a `WindowResize` event; the centre stays put
+    pub fn resize(&mut self, width: f32, height: f32) {
+        self.size = Vec2::new(width, height);
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// World position at the centre of the view
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+    }
+
+    /// Rotation in radians, counter-clockwise
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Values above 1 magnify; zooms that are not positive and finite are ignored
+    pub fn set_zoom(&mut self, zoom: f32) {
+        if zoom.is_finite() && zoom > 0.0 {
+            self.zoom = zoom;
+        } else {
+            warn!("Ignoring camera zoom {}, it must be positive", zoom);
+        }
+    }
+
+    /// Maps world positions to OpenGL clip space
+    pub fn view_projection(&self) -> Mat4 {
+        let half = self.size / (2.0 * self.zoom);
+        let projection = Mat4::orthographic_rh_gl(-half.x, half.x, -half.y, half.y, -1.0, 1.0);
+        let view = Mat4::from_rotation_z(-self.rotation) * Mat4::from_translation(-self.position.extend(0.0));
+        projection * view
+    }
+
+    /// World position under a point of a `viewport` sized window, in pixels from its top left corner
+    ///
+    /// Turns mouse positions into world positions.
+    pub fn screen_to_world(&self, screen: Vec2, viewport: Vec2) -> Vec2 {
+        let ndc = Vec2::new(screen.x / viewport.x * 2.0 - 1.0, 1.0 - screen.y / viewport.y * 2.0);
+        self.view_projection().inverse().project_point3(ndc.extend(0.0)).truncate()
+    }
+}
+
+/// Part of a texture, in texture coordinates from its top left corner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureRegion {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl TextureRegion {
+    /// The whole texture
+    pub const FULL: TextureRegion = TextureRegion { min: Vec2::ZERO, max: Vec2::ONE };
+
+    /// A rectangle of `texture` in pixels, such as a sprite sheet frame or a glyph in a font atlas
+    pub fn from_pixels(texture: &Texture2D, x: u32, y: u32, width: u32, height: u32) -> Self {
+        let texture_size = Vec2::new(texture.width() as f32, texture.height() as f32);
+        TextureRegion {
+            min: Vec2::new(x as f32, y as f32) / texture_size,
+            max: Vec2::new((x + width) as f32, (y + height) as f32) / texture_size,
+        }
+    }
+
+    /// Texture coordinates of `QUAD_CORNERS`; the top of the region goes to the top of the quad
+    fn corners(&self) -> [[f32; 2]; 4] {
+        [[self.min.x, self.max.y], [self.max.x, self.max.y], [self.max.x, self.min.y], [self.min.x, self.min.y]]
+    }
+}
+
+/// One corner of a quad as the batch shader reads it
+///
+/// Glyphs use the same layout as sprites: a font atlas is a texture whose
+/// red channel holds each glyph's coverage, marked by `coverage` being 1.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuadVertex {
+    pub position: [f32; 3],
+    pub color: Color,
+    pub tex_coords: [f32; 2],
+    /// Batch texture slot to sample; slot 0 is plain white
+    pub texture_slot: f32,
+    /// 1 if the texture's red channel is coverage to multiply `color`'s alpha by, else 0
+    pub coverage: f32,
+}
+
+/// Transform putting the unit quad at `center` with `size` and `rotation`
+fn rect_transform(center: Vec2, size: Vec2, rotation: f32) -> Mat4 {
+    Mat4::from_translation(center.extend(0.0)) * Mat4::from_rotation_z(rotation) * Mat4::from_scale(size.extend(1.0))
+}
+
+/// Transform for a line from `from` to `to` drawn as a quad `thickness` wide
+fn line_transform(from: Vec2, to: Vec2, thickness: f32) -> Mat4 {
+    let along = to - from;
+    rect_transform((from + to) / 2.0, Vec2::new(along.length(), thickness), along.y.atan2(along.x))
+}
+
+/// Vertices of the unit quad moved by `transform`, sampling slot 0 until the batch assigns one
+fn quad_vertices(transform: Mat4, color: Color, region: TextureRegion, coverage: bool) -> [QuadVertex; 4] {
+    let tex_coords = region.corners();
+    std::array::from_fn(|corner| QuadVertex {
+        position: transform.transform_point3(QUAD_CORNERS[corner].extend(0.0)).to_array(),
+        color,
+        tex_coords: tex_coords[corner],
+        texture_slot: 0.0,
+        coverage: if coverage { 1.0 } else { 0.0 },
+    })
+}
+
+fn fragment_source() -> String {
+    let cases: String = (0..MAX_TEXTURE_SLOTS)
+        .map(|slot| format!("        case {0}: texel = texture(u_textures[{0}], v_tex_coords); break;\n", slot))
+        .collect();
+    format!(
+        "#version 330 core
+in vec4 v_color;
+in vec2 v_tex_coords;
+flat in int v_texture_slot;
+in float v_coverage;
+
+uniform sampler2D u_textures[{}];
+
+out vec4 frag_color;
+
+void main() {{
+    // Sampler arrays may only be indexed with constants in GLSL 3.30
+    vec4 texel = vec4(1.0);
+    switch (v_texture_slot) {{
+{}    }}
+    if (v_coverage > 0.5) {{
+        texel = vec4(1.0, 1.0, 1.0, texel.r);
+    }}
+    frag_color = texel * v_color;
+    if (frag_color.a <= 0.0) {{
+        discard;
+    }}
+}}",
+        MAX_TEXTURE_SLOTS, cases
+    )
+}
+
+/// Quads waiting to be drawn and the textures they sample
+#[derive(Debug)]
+struct Batch {
+    vertices: Vec<QuadVertex>,
+    /// Texture bound to each slot, the white texture first
+    textures: Vec<u32>,
+}
+
+impl Batch {
+    fn new(white_texture: u32) -> Self {
+        Self { vertices: Vec::with_capacity(MAX_QUADS * 4), textures: vec![white_texture] }
+    }
+
+    fn quads(&self) -> usize {
+        self.vertices.len() / 4
+    }
+
+    fn is_full(&self) -> bool {
+        self.quads() >= MAX_QUADS
+    }
+
+    /// Slot of `texture` in this batch, or `None` if it is not in the batch and every slot is taken
+    fn slot(&mut self, texture: u32) -> Option<usize> {
+        if let Some(slot) = self.textures.iter().position(|bound| *bound == texture) {
+            return Some(slot);
+        }
+        if self.textures.len() == MAX_TEXTURE_SLOTS {
+            return None;
+        }
+        self.textures.push(texture);
+        Some(self.textures.len() - 1)
+    }
+
+    fn clear(&mut self) {
+        self.vertices.clear();
+        self.textures.truncate(1);
+    }
+}
+
+/// What the last frame took to draw
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Renderer2DStats {
+    pub draw_calls: u32,
+    pub quads: u32,
+}
+
+/// Draws quads in batches
+///
+/// Textures passed to the draw methods must stay alive until `end`, which
+/// is when they are sampled. Drawing enables alpha blending.
+#[derive(Debug)]
+pub struct Renderer2D {
+    shader: Shader,
+    white_texture: Texture2D,
+    vertex_array: u32,
+    vertex_buffer: u32,
+    index_buffer: u32,
+    batch: Batch,
+    stats: Renderer2DStats,
+}
+
+impl Renderer2D {
+    /// Set up the buffers and shader; the window's context must be current
+    pub fn new() -> Result<Self, ShaderError> {
+        let mut shader = Shader::from_sources(VERTEX_SOURCE, &fragment_source())?;
+        for slot in 0..MAX_TEXTURE_SLOTS {
+            shader.set_uniform(&format!("u_textures[{}]", slot), slot as i32);
+        }
+        let white_texture = Texture2D::from_rgba(1, 1, &[255; 4]).expect("1x1 RGBA texture is valid");
+
+        let indices: Vec<u32> = (0..MAX_QUADS as u32)
+            .flat_map(|quad| [0, 1, 2, 2, 3, 0].map(|corner| quad * 4 + corner))
+            .collect();
+        let (mut vertex_array, mut vertex_buffer, mut index_buffer) = (0, 0, 0);
+        unsafe {
+            gl::GenVertexArrays(1, &mut vertex_array);
+            gl::BindVertexArray(vertex_array);
+
+            gl::GenBuffers(1, &mut vertex_buffer);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vertex_buffer);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (MAX_QUADS * 4 * mem::size_of::<QuadVertex>()) as isize,
+                std::ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+
+            gl::GenBuffers(1, &mut index_buffer);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, index_buffer);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                (indices.len() * mem::size_of::<u32>()) as isize,
+                indices.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+
+            let attributes = [
+                (3, mem::offset_of!(QuadVertex, position)),
+                (4, mem::offset_of!(QuadVertex, color)),
+                (2, mem::offset_of!(QuadVertex, tex_coords)),
+                (1, mem::offset_of!(QuadVertex, texture_slot)),
+                (1, mem::offset_of!(QuadVertex, coverage)),
+            ];
+            for (location, (components, offset)) in attributes.into_iter().enumerate() {
+                gl::VertexAttribPointer(
+                    location as u32,
+                    components,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    mem::size_of::<QuadVertex>() as i32,
+                    offset as *const _,
+                );
+                gl::EnableVertexAttribArray(location as u32);
+            }
+            gl::BindVertexArray(0);
+        }
+
+        let batch = Batch::new(white_texture.id());
+        Ok(Self { shader, white_texture, vertex_array, vertex_buffer, index_buffer, batch, stats: Renderer2DStats::default() })
+    }
+
+    /// Start a frame seen through `camera`
+    pub fn begin(&mut self, camera: &OrthographicCamera) {
+        self.batch.clear();
+        self.stats = Renderer2DStats::default();
+        self.shader.set_uniform("u_view_projection", camera.view_projection());
+    }
+
+    /// Draw everything submitted since `begin`
+    pub fn end(&mut self) {
+        self.flush();
+    }
+
+    /// Draw calls and quads since the last `begin`
+    pub fn stats(&self) -> Renderer2DStats {
+        self.stats
+    }
+
+    pub fn draw_rect(&mut self, center: Vec2, size: Vec2, color: Color) {
+        self.draw_quad(rect_transform(center, size, 0.0), None, TextureRegion::FULL, color);
+    }
+
+    /// `rotation` in radians, counter-clockwise around the centre
+    pub fn draw_rotated_rect(&mut self, center: Vec2, size: Vec2, rotation: f32, color: Color) {
+        self.draw_quad(rect_transform(center, size, rotation), None, TextureRegion::FULL, color);
+    }
+
+    /// Draw all of `texture`, its colours multiplied by `tint`
+    pub fn draw_sprite(&mut self, center: Vec2, size: Vec2, texture: &Texture2D, tint: Color) {
+        self.draw_quad(rect_transform(center, size, 0.0), Some(texture), TextureRegion::FULL, tint);
+    }
+
+    pub fn draw_line(&mut self, from: Vec2, to: Vec2, thickness: f32, color: Color) {
+        self.draw_quad(line_transform(from, to, thickness), None, TextureRegion::FULL, color);
+    }
+
+    /// Draw one glyph of a font atlas whose red channel is coverage; `position` is its bottom left corner
+    pub fn draw_glyph(&mut self, position: Vec2, size: Vec2, atlas: &Texture2D, region: TextureRegion, color: Color) {
+        let transform = rect_transform(position + size / 2.0, size, 0.0);
+        self.submit(quad_vertices(transform, color, region, true), Some(atlas));
+    }
+
+    /// Draw the unit quad around the origin moved by `transform`, sampling `region` of `texture` if given
+    pub fn draw_quad(&mut self, transform: Mat4, texture: Option<&Texture2D>, region: TextureRegion, color: Color) {
+        self.submit(quad_vertices(transform, color, region, false), texture);
+    }
+
+    /// Add a quad, starting a new batch if this one has no room for it
+    fn submit(&mut self, mut vertices: [QuadVertex; 4], texture: Option<&Texture2D>) {
+        if self.batch.is_full() {
+            self.flush();
+        }
+        let texture = texture.unwrap_or(&self.white_texture).id();
+        let slot = match self.batch.slot(texture) {
+            Some(slot) => slot,
+            None => {
+                self.flush();
+                self.batch.slot(texture).expect("An empty batch has free texture slots")
+            }
+        };
+        vertices.iter_mut().for_each(|vertex| vertex.texture_slot = slot as f32);
+        self.batch.vertices.extend_from_slice(&vertices);
+    }
+
+    fn flush(&mut self) {
+        if self.batch.vertices.is_empty() {
+            return;
+        }
+        let quads = self.batch.quads();
+        self.shader.bind();
+        unsafe {
+            for (slot, texture) in self.batch.textures.iter().enumerate() {
+                gl::ActiveTexture(gl::TEXTURE0 + slot as u32);
+                gl::BindTexture(gl::TEXTURE_2D, *texture);
+            }
+            gl::ActiveTexture(gl::TEXTURE0);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vertex_buffer);
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                0,
+                (self.batch.vertices.len() * mem::size_of::<QuadVertex>()) as isize,
+                self.batch.vertices.as_ptr() as *const _,
+            );
+
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::BindVertexArray(self.vertex_array);
+            gl::DrawElements(gl::TRIANGLES, (quads * 6) as i32, gl::UNSIGNED_INT, std::ptr::null());
+            gl::BindVertexArray(0);
+        }
+        self.stats.draw_calls += 1;
+        self.stats.quads += quads as u32;
+        self.batch.clear();
+    }
+}
+
+impl Drop for Renderer2D {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vertex_array);
+            gl::DeleteBuffers(1, &self.vertex_buffer);
+            gl::DeleteBuffers(1, &self.index_buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::{Vec3, Vec4};
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(actual.abs_diff_eq(expected, 1e-4), "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_camera_maps_view_to_clip_space() {
+        let mut camera = OrthographicCamera::new(800.0, 600.0);
+        let clip = |camera: &OrthographicCamera, world: Vec2| camera.view_projection().project_point3(world.extend(0.0)).truncate();
+        assert_near(clip(&camera, Vec2::new(400.0, 300.0)), Vec2::ONE);
+
+        camera.set_position(Vec2::new(100.0, 0.0));
+        camera.set_zoom(2.0);
+        camera.set_zoom(0.0);
+        assert_near(clip(&camera, Vec2::new(300.0, -150.0)), Vec2::new(1.0, -1.0));
+        // The top left pixel of the window is the top left of the view
+        assert_near(camera.screen_to_world(Vec2::ZERO, Vec2::new(800.0, 600.0)), Vec2::new(-100.0, 150.0));
+
+        camera.set_rotation(std::f32::consts::FRAC_PI_2);
+        // Turning the camera left turns the world right
+        assert_near(clip(&camera, Vec2::new(100.0, 200.0)), Vec2::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn test_quads_and_lines_become_vertices() {
+        let region = TextureRegion { min: Vec2::new(0.25, 0.0), max: Vec2::new(0.5, 0.5) };
+        let quad = quad_vertices(rect_transform(Vec2::new(10.0, 20.0), Vec2::new(4.0, 2.0), 0.0), [1.0; 4], region, true);
+        let positions: Vec<[f32; 3]> = quad.iter().map(|vertex| vertex.position).collect();
+        assert_eq!(positions, [[8.0, 19.0, 0.0], [12.0, 19.0, 0.0], [12.0, 21.0, 0.0], [8.0, 21.0, 0.0]]);
+        // The region's top row lands on the quad's top edge
+        assert_eq!(quad[0].tex_coords, [0.25, 0.5]);
+        assert_eq!(quad[3].tex_coords, [0.25, 0.0]);
+        assert_eq!(quad[0].coverage, 1.0);
+
+        let line = quad_vertices(line_transform(Vec2::ZERO, Vec2::new(0.0, 10.0), 2.0), [1.0; 4], TextureRegion::FULL, false);
+        let corners: Vec<Vec2> = line.iter().map(|vertex| Vec3::from(vertex.position).truncate()).collect();
+        assert_near(corners[0], Vec2::new(1.0, 0.0));
+        assert_near(corners[2], Vec2::new(-1.0, 10.0));
+        assert_eq!(Vec4::from(line[0].color), Vec4::ONE);
+    }
+
+    #[test]
+    fn test_batch_shares_texture_slots() {
+        let mut batch = Batch::new(1);
+        assert_eq!(batch.slot(1), Some(0));
+        assert_eq!(batch.slot(7), Some(1));
+        assert_eq!(batch.slot(7), Some(1));
+        for texture in 8..8 + MAX_TEXTURE_SLOTS as u32 - 2 {
+            assert!(batch.slot(texture).is_some());
+        }
+        assert_eq!(batch.slot(100), None);
+        assert_eq!(batch.slot(7), Some(1));
+
+        batch.clear();
+        assert_eq!(batch.slot(100), Some(1));
+        assert!(fragment_source().contains(&format!("case {}:", MAX_TEXTURE_SLOTS - 1)));
+    }
+}
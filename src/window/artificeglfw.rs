@@ -4,9 +4,13 @@
 use crate::events::core::{*, EventData};
 use crate::input::keyboard::key_translation;
 use crate::input::mouse::mouse_translation;
+use crate::input::gamepad::GamepadInfo;
+use crate::input::hotplug::GamepadHotplug;
+use crate::window::keysym;
 use crate::io::*;
 use glfw::{Action, Context, GlfwReceiver, Key, WindowHint as GlfwWindowHint};
 use artifice_logging::{debug, error, info, trace, warn};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::any::Any;
 
@@ -19,6 +23,20 @@ pub struct GlfwWindow {
     glfw_window: glfw::PWindow,
     event_receiver: GlfwReceiver<(f64, glfw::WindowEvent)>,
     event_callback: Option<Arc<Mutex<dyn FnMut(Event) + Send + 'static>>>,
+    gamepad_hotplug: GamepadHotplug,
+    cursor_mode: CursorMode,
+    cursor_icon: CursorIcon,
+    /// Custom cursors not currently set; GLFW owns the one that is
+    custom_cursors: HashMap<u32, glfw::Cursor>,
+    next_cursor_id: u32,
+    /// Last cursor position while captured, to turn positions into raw deltas
+    captured_cursor: Option<(f64, f64)>,
+    fullscreen: FullscreenMode,
+    /// Where the window was before it left windowed mode
+    windowed_rect: (Position, Size),
+    size_limits: (Option<Size>, Option<Size>),
+    aspect_ratio: Option<(u32, u32)>,
+    present_timer: PresentTimer,
 }
 
 impl GlfwWindow {
@@ -53,6 +71,17 @@ impl GlfwWindow {
             glfw_window: window,
             event_receiver: events,
             event_callback: None,
+            gamepad_hotplug: GamepadHotplug::default(),
+            cursor_mode: CursorMode::Normal,
+            cursor_icon: CursorIcon::Arrow,
+            custom_cursors: HashMap::new(),
+            next_cursor_id: 0,
+            captured_cursor: None,
+            fullscreen: FullscreenMode::Windowed,
+            windowed_rect: (position, Size::from((width, height))),
+            size_limits: (None, None),
+            aspect_ratio: None,
+            present_timer: PresentTimer::new(),
         }
     }
 
@@ -65,7 +94,32 @@ impl GlfwWindow {
         // Initialize GLFW
         let mut glfw = glfw::init(glfw::fail_on_errors).expect("Failed to initialize GLFW");
 
-        // Apply window hints
+        Self::apply_hints(&mut glfw, hints);
+
+        //Create a new GLFW window
+        let (window, events) = glfw
+            .create_window(width, height, title, glfw::WindowMode::Windowed)
+            .expect("Failed to create GLFW window");
+
+        Self::from_window(glfw, window, events, title)
+    }
+
+    /// Open a window whose OpenGL context shares objects with this window's
+    pub fn create_shared(&self, width: u32, height: u32, title: &str, hints: &[WindowHint]) -> Result<Self, String> {
+        info!("Creating GLFW window sharing {}'s context: {} ({}x{})", self.title, title, width, height);
+        let mut glfw = self.glfw.clone();
+        Self::apply_hints(&mut glfw, hints);
+        let (window, events) = self
+            .glfw_window
+            .create_shared(width, height, title, glfw::WindowMode::Windowed)
+            .ok_or_else(|| format!("Failed to create GLFW window '{}' with a shared context", title))?;
+        Ok(Self::from_window(glfw, window, events, title))
+    }
+
+    /// Turn the hints into GLFW hints for the next window created
+    fn apply_hints(glfw: &mut glfw::Glfw, hints: &[WindowHint]) {
+        // Hints are global in GLFW; start from the defaults, not the previous window's
+        glfw.default_window_hints();
         for hint in hints {
             match hint {
                 WindowHint::Resizable(value) => glfw.window_hint(GlfwWindowHint::Resizable(*value)),
@@ -103,17 +157,22 @@ impl GlfwWindow {
                 WindowHint::OpenGLForwardCompat(value) => {
                     glfw.window_hint(GlfwWindowHint::OpenGlForwardCompat(*value))
                 }
+                // GLFW 3.3 only has the X11 class; its Wayland windows get no app ID
+                WindowHint::AppId(app_id) => {
+                    glfw.window_hint(GlfwWindowHint::X11ClassName(Some(app_id.clone())));
+                    glfw.window_hint(GlfwWindowHint::X11InstanceName(Some(app_id.clone())));
+                }
             }
         }
+    }
 
-        //Create a new GLFW window
-        let (mut window, events) = glfw
-            .create_window(width, height, title, glfw::WindowMode::Windowed)
-            .expect("Failed to create GLFW window");
+    /// Show a freshly created window and make its context current
+    fn from_window(glfw: glfw::Glfw, mut window: glfw::PWindow, events: GlfwReceiver<(f64, glfw::WindowEvent)>, title: &str) -> Self {
+        let (width, height) = window.get_size();
+        let (width, height) = (width as u32, height as u32);
 
-        //Make the window context current
+        //Make the window context current; GLFW already showed it unless hinted otherwise
         window.set_all_polling(true);
-        window.show();
         window.make_current();
 
         // Initialize OpenGL
@@ -131,14 +190,94 @@ impl GlfwWindow {
             glfw_window: window,
             event_receiver: events,
             event_callback: None,
+            gamepad_hotplug: GamepadHotplug::default(),
+            cursor_mode: CursorMode::Normal,
+            cursor_icon: CursorIcon::Arrow,
+            custom_cursors: HashMap::new(),
+            next_cursor_id: 0,
+            captured_cursor: None,
+            fullscreen: FullscreenMode::Windowed,
+            windowed_rect: (position, Size::from((width, height))),
+            size_limits: (None, None),
+            aspect_ratio: None,
+            present_timer: PresentTimer::new(),
+        }
+    }
+
+    /// Hand `cursor` to GLFW, keeping the custom cursor it replaces for later
+    fn replace_cursor(&mut self, cursor: glfw::Cursor, icon: CursorIcon) {
+        if let (Some(previous), CursorIcon::Custom(id)) = (self.glfw_window.set_cursor(Some(cursor)), self.cursor_icon) {
+            self.custom_cursors.insert(id, previous);
         }
+        self.cursor_icon = icon;
     }
 }
 
+/// Joysticks GLFW currently reports as present
+fn present_gamepads(glfw: &glfw::Glfw) -> Vec<GamepadInfo> {
+    (0..=glfw::ffi::JOYSTICK_LAST)
+        .filter_map(glfw::JoystickId::from_i32)
+        .map(|id| glfw.get_joystick(id))
+        .filter(|joystick| joystick.is_present())
+        .map(|joystick| GamepadInfo {
+            id: joystick.id as u32,
+            name: joystick
+                .get_gamepad_name()
+                .or_else(|| joystick.get_name())
+                .unwrap_or_else(|| format!("Joystick {}", joystick.id as u32)),
+            guid: joystick.get_guid(),
+            // GLFW does not report battery levels
+            battery: None,
+        })
+        .collect()
+}
+
+/// Keys whose symbol depends on the keyboard layout
+const PRINTABLE_KEYS: [Key; 50] = [
+    Key::Space, Key::Apostrophe, Key::Comma, Key::Minus, Key::Period, Key::Slash,
+    Key::Num0, Key::Num1, Key::Num2, Key::Num3, Key::Num4, Key::Num5, Key::Num6, Key::Num7, Key::Num8, Key::Num9,
+    Key::Semicolon, Key::Equal,
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+    Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    Key::LeftBracket, Key::Backslash, Key::RightBracket, Key::GraveAccent, Key::World1, Key::World2,
+];
+
+/// Key code for the symbol `key` types in the current layout
+///
+/// GLFW names keys by their position on a US keyboard; the layout's symbol
+/// is used instead when it has a key code, so Z on a German keyboard is the
+/// key labelled Z. Other keys keep their position.
+fn layout_key(key: Key, scancode: glfw::Scancode) -> KeyCode {
+    glfw::get_key_name(Some(key), Some(scancode))
+        .as_deref()
+        .and_then(single_char)
+        .and_then(keysym::char_to_keycode)
+        .unwrap_or_else(|| key_translation::from_glfw_key(key))
+}
+
+/// The only character of `name`, if it has exactly one
+fn single_char(name: &str) -> Option<char> {
+    let mut chars = name.chars();
+    chars.next().filter(|_| chars.next().is_none())
+}
+
+/// Whether `point`, in screen coordinates, lies on `monitor`
+fn covers(monitor: &glfw::Monitor, point: (i32, i32)) -> bool {
+    let (x, y) = monitor.get_pos();
+    monitor.get_video_mode().is_some_and(|mode| {
+        (x..x + mode.width as i32).contains(&point.0) && (y..y + mode.height as i32).contains(&point.1)
+    })
+}
+
 impl Window for GlfwWindow {
     /// Updates the window (swaps buffers, polls events)
     fn update(&mut self) {
-        self.glfw_window.swap_buffers();
+        if self.present_timer.needs_refresh_rate() {
+            let refresh_rate = self.refresh_rate();
+            self.present_timer.set_refresh_rate(refresh_rate);
+        }
+        let window = &mut self.glfw_window;
+        self.present_timer.time_swap(|| window.swap_buffers());
         self.glfw.poll_events();
     }
 
@@ -146,9 +285,9 @@ impl Window for GlfwWindow {
         // Process all pending events from GLFW
         for (_, event) in glfw::flush_messages(&self.event_receiver) {
             match event {
-                glfw::WindowEvent::Key(key, _, action, mods) => {
+                glfw::WindowEvent::Key(key, scancode, action, mods) => {
                     // Convert GLFW key to our key code
-                    let key_code = key_translation::from_glfw_key(key);
+                    let key_code = layout_key(key, scancode);
                     let key_action = key_translation::from_glfw_action(action);
                     let key_mods = key_translation::from_glfw_mods(mods);
 
@@ -202,6 +341,18 @@ impl Window for GlfwWindow {
                     }
                 }
                 glfw::WindowEvent::CursorPos(x, y) => {
+                    // A disabled GLFW cursor reports unbounded positions; their
+                    // differences are the raw motion
+                    if self.cursor_mode == CursorMode::Captured {
+                        let previous = self.captured_cursor.replace((x, y));
+                        if let (Some((last_x, last_y)), Some(callback)) = (previous, &self.event_callback) {
+                            let motion_event = MouseRawMotionEvent { delta_x: x - last_x, delta_y: y - last_y };
+                            let event = Event::new(EventData::MouseRawMotion(motion_event));
+                            let mut callback = callback.lock().unwrap();
+                            callback(event);
+                        }
+                    }
+
                     // Create and dispatch mouse move event
                     if let Some(callback) = &self.event_callback {
                         let move_event = MouseMoveEvent { x, y };
@@ -251,6 +402,15 @@ impl Window for GlfwWindow {
                 _ => {}
             }
         }
+
+        // GLFW only reports joysticks when asked, so compare against the last scan
+        let glfw = &self.glfw;
+        for event in self.gamepad_hotplug.poll(|| present_gamepads(glfw)) {
+            if let Some(callback) = &self.event_callback {
+                let mut callback = callback.lock().unwrap();
+                callback(event);
+            }
+        }
     }
 
     fn set_should_close(&mut self) {
@@ -289,6 +449,48 @@ impl Window for GlfwWindow {
         self.glfw_window.set_title(title);
     }
 
+    fn set_visible(&mut self, visible: bool) {
+        debug!("{} GLFW window", if visible { "Showing" } else { "Hiding" });
+        if visible {
+            self.glfw_window.show();
+        } else {
+            self.glfw_window.hide();
+        }
+    }
+
+    fn is_visible(&self) -> bool {
+        self.glfw_window.is_visible()
+    }
+
+    fn capture_framebuffer(&mut self) -> Result<ImageBuffer, String> {
+        // In pixels, which differ from the window size on high-DPI screens
+        let (width, height) = self.glfw_window.get_framebuffer_size();
+        self.glfw_window.make_current();
+        crate::io::image::read_gl_framebuffer(width as u32, height as u32)
+    }
+
+    fn set_opacity(&mut self, opacity: f32) {
+        debug!("Setting GLFW window opacity to {}", opacity);
+        self.glfw_window.set_opacity(opacity.clamp(0.0, 1.0));
+    }
+
+    fn opacity(&self) -> f32 {
+        self.glfw_window.get_opacity()
+    }
+
+    fn set_icon(&mut self, images: &[IconImage]) {
+        debug!("Setting GLFW window icon from {} images", images.len());
+        // Same memory layout as the cursor images
+        let images = images
+            .iter()
+            .map(|image| {
+                let pixels = image.rgba().chunks_exact(4).map(|pixel| u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]])).collect();
+                glfw::PixelImage { width: image.width(), height: image.height(), pixels }
+            })
+            .collect();
+        self.glfw_window.set_icon_from_pixels(images);
+    }
+
     fn get_event_callback(&self) -> Option<Arc<Mutex<dyn FnMut(Event) + Send + 'static>>> {
         self.event_callback.clone()
     }
@@ -297,11 +499,254 @@ impl Window for GlfwWindow {
         self.event_callback = Some(callback);
     }
 
+    fn set_cursor_mode(&mut self, mode: CursorMode) {
+        debug!("Setting GLFW cursor mode to {:?}", mode);
+        let glfw_mode = match mode {
+            CursorMode::Normal => glfw::CursorMode::Normal,
+            CursorMode::Hidden => glfw::CursorMode::Hidden,
+            CursorMode::Captured => glfw::CursorMode::Disabled,
+            // GLFW 3.3 cannot keep a visible cursor inside the window
+            CursorMode::Confined => {
+                warn!("Confining the cursor is not supported by GLFW, it can leave the window");
+                glfw::CursorMode::Normal
+            }
+        };
+        self.glfw_window.set_cursor_mode(glfw_mode);
+
+        // Raw motion skips the desktop's pointer acceleration; GLFW only applies it to a disabled cursor
+        let raw = mode == CursorMode::Captured && self.glfw.supports_raw_motion();
+        if mode == CursorMode::Captured && !raw {
+            warn!("Raw mouse motion is not supported, captured motion will be accelerated");
+        }
+        self.glfw_window.set_raw_mouse_motion(raw);
+
+        self.cursor_mode = mode;
+        self.captured_cursor = None;
+    }
+
+    fn cursor_mode(&self) -> CursorMode {
+        self.cursor_mode
+    }
+
+    fn set_cursor(&mut self, icon: CursorIcon) {
+        // GLFW 3.3 has six standard shapes; the rest use the nearest of them
+        let shape = match icon {
+            CursorIcon::Arrow | CursorIcon::NotAllowed | CursorIcon::Wait => glfw::StandardCursor::Arrow,
+            CursorIcon::IBeam => glfw::StandardCursor::IBeam,
+            CursorIcon::Hand => glfw::StandardCursor::Hand,
+            CursorIcon::Crosshair | CursorIcon::ResizeAll => glfw::StandardCursor::Crosshair,
+            CursorIcon::ResizeHorizontal | CursorIcon::ResizeNwSe => glfw::StandardCursor::HResize,
+            CursorIcon::ResizeVertical | CursorIcon::ResizeNeSw => glfw::StandardCursor::VResize,
+            CursorIcon::Custom(_) if icon == self.cursor_icon => return,
+            CursorIcon::Custom(id) => {
+                let Some(cursor) = self.custom_cursors.remove(&id) else {
+                    warn!("Unknown custom cursor {}, keeping {:?}", id, self.cursor_icon);
+                    return;
+                };
+                debug!("Setting GLFW cursor to {:?}", icon);
+                self.replace_cursor(cursor, icon);
+                return;
+            }
+        };
+        debug!("Setting GLFW cursor to {:?} ({:?})", icon, shape);
+        self.replace_cursor(glfw::Cursor::standard(shape), icon);
+    }
+
+    fn cursor(&self) -> CursorIcon {
+        self.cursor_icon
+    }
+
+    fn create_cursor(&mut self, image: &CursorImage, hotspot: (u32, u32)) -> Result<CursorIcon, String> {
+        image.check_hotspot(hotspot)?;
+        // GLFW reads each word back as RGBA bytes in memory order
+        let pixels = image.rgba().chunks_exact(4).map(|pixel| u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]])).collect();
+        let pixels = glfw::PixelImage { width: image.width(), height: image.height(), pixels };
+        let cursor = glfw::Cursor::create_from_pixels(pixels, hotspot.0, hotspot.1);
+
+        let id = self.next_cursor_id;
+        self.next_cursor_id += 1;
+        self.custom_cursors.insert(id, cursor);
+        debug!("Created GLFW cursor {} from {}x{} image", id, image.width(), image.height());
+        Ok(CursorIcon::Custom(id))
+    }
+
+    fn set_size_limits(&mut self, min: Option<Size>, max: Option<Size>) {
+        debug!("Setting GLFW size limits to {:?}..{:?}", min, max);
+        self.glfw_window.set_size_limits(min.map(|min| min.0), min.map(|min| min.1), max.map(|max| max.0), max.map(|max| max.1));
+        self.size_limits = (min, max);
+    }
+
+    fn size_limits(&self) -> (Option<Size>, Option<Size>) {
+        self.size_limits
+    }
+
+    fn set_aspect_ratio(&mut self, ratio: Option<(u32, u32)>) {
+        debug!("Setting GLFW aspect ratio to {:?}", ratio);
+        let dont_care = glfw::ffi::DONT_CARE as u32;
+        let (width, height) = ratio.unwrap_or((dont_care, dont_care));
+        self.glfw_window.set_aspect_ratio(width, height);
+        self.aspect_ratio = ratio;
+    }
+
+    fn aspect_ratio(&self) -> Option<(u32, u32)> {
+        self.aspect_ratio
+    }
+
+    fn set_maximized(&mut self, maximized: bool) {
+        debug!("{} GLFW window", if maximized { "Maximizing" } else { "Restoring" });
+        if maximized {
+            self.glfw_window.maximize();
+        } else {
+            self.glfw_window.restore();
+        }
+    }
+
+    fn is_maximized(&self) -> bool {
+        self.glfw_window.is_maximized()
+    }
+
+    fn set_ime_cursor_area(&mut self, _position: Position, _size: Size) {
+        // GLFW has no IME API; the input method places its window on its own
+    }
+
+    fn set_fullscreen(&mut self, mode: FullscreenMode) {
+        if mode == self.fullscreen {
+            return;
+        }
+        if self.fullscreen == FullscreenMode::Windowed {
+            self.windowed_rect = (Position::from(self.glfw_window.get_pos()), Size::from(self.glfw_window.get_size()));
+        }
+        debug!("Setting GLFW fullscreen mode to {:?}", mode);
+
+        let (position, size) = self.windowed_rect;
+        let (x, y) = self.glfw_window.get_pos();
+        let (width, height) = self.glfw_window.get_size();
+        let center = (x + width / 2, y + height / 2);
+        let window = &mut self.glfw_window;
+        let applied = self.glfw.with_connected_monitors(|_, monitors| match mode {
+            FullscreenMode::Windowed => {
+                window.set_decorated(true);
+                window.set_monitor(glfw::WindowMode::Windowed, position.0, position.1, size.0, size.1, None);
+                true
+            }
+            FullscreenMode::Borderless => {
+                // Cover the monitor under the window's center with a plain window, so the video mode stays
+                let Some(monitor) = monitors.iter().find(|monitor| covers(monitor, center)).or(monitors.first()) else { return false };
+                let Some(current) = monitor.get_video_mode() else { return false };
+                let (mx, my) = monitor.get_pos();
+                window.set_decorated(false);
+                window.set_monitor(glfw::WindowMode::Windowed, mx, my, current.width, current.height, None);
+                true
+            }
+            FullscreenMode::Exclusive { monitor, video_mode } => {
+                let Some(monitor) = monitors.get(monitor) else { return false };
+                window.set_monitor(
+                    glfw::WindowMode::FullScreen(monitor),
+                    0,
+                    0,
+                    video_mode.width,
+                    video_mode.height,
+                    Some(video_mode.refresh_rate),
+                );
+                true
+            }
+        });
+
+        if applied {
+            self.fullscreen = mode;
+        } else {
+            warn!("No monitor for GLFW fullscreen mode {:?}", mode);
+        }
+    }
+
+    fn fullscreen(&self) -> FullscreenMode {
+        self.fullscreen
+    }
+
+    fn video_modes(&mut self, monitor: usize) -> Vec<VideoMode> {
+        self.glfw.with_connected_monitors(|_, monitors| {
+            monitors.get(monitor).map_or_else(Vec::new, |monitor| {
+                monitor
+                    .get_video_modes()
+                    .iter()
+                    .map(|mode| VideoMode { width: mode.width, height: mode.height, refresh_rate: mode.refresh_rate })
+                    .collect()
+            })
+        })
+    }
+
+    fn monitor(&mut self) -> Option<usize> {
+        let (x, y) = self.glfw_window.get_pos();
+        let (width, height) = self.glfw_window.get_size();
+        let center = (x + width / 2, y + height / 2);
+        self.glfw.with_connected_monitors(|_, monitors| monitors.iter().position(|monitor| covers(monitor, center)))
+    }
+
+    fn set_vsync(&mut self, enabled: bool) {
+        debug!("Setting GLFW vsync to {}", enabled);
+        // The swap interval belongs to the current context
+        self.glfw_window.make_current();
+        let interval = if enabled { glfw::SwapInterval::Sync(1) } else { glfw::SwapInterval::None };
+        self.glfw.set_swap_interval(interval);
+    }
+
+    fn refresh_rate(&mut self) -> Option<u32> {
+        let (x, y) = self.glfw_window.get_pos();
+        let (width, height) = self.glfw_window.get_size();
+        let center = (x + width / 2, y + height / 2);
+        self.glfw.with_connected_monitors(|_, monitors| {
+            let monitor = monitors.iter().find(|monitor| covers(monitor, center))?;
+            monitor.get_video_mode().map(|mode| mode.refresh_rate)
+        })
+    }
+
+    fn present_stats(&self) -> Option<PresentStats> {
+        Some(self.present_timer.stats())
+    }
+
+    fn clipboard_text(&mut self) -> Option<String> {
+        self.glfw_window.get_clipboard_string()
+    }
+
+    fn set_clipboard_text(&mut self, text: &str) {
+        trace!("Setting GLFW clipboard to {} bytes", text.len());
+        self.glfw_window.set_clipboard_string(text);
+    }
+
+    fn key_names(&self) -> HashMap<KeyCode, String> {
+        let mut names = HashMap::new();
+        for key in PRINTABLE_KEYS {
+            if let Some(character) = glfw::get_key_name(Some(key), None).as_deref().and_then(single_char) {
+                let key_code = keysym::char_to_keycode(character).unwrap_or_else(|| key_translation::from_glfw_key(key));
+                names.entry(key_code).or_insert_with(|| keysym::key_label(character));
+            }
+        }
+        names
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
 }
 
+#[cfg(feature = "vulkan")]
+impl VulkanWindow for GlfwWindow {
+    fn required_instance_extensions(&self) -> Vec<std::ffi::CString> {
+        let Some(extensions) = self.glfw.get_required_instance_extensions() else {
+            warn!("GLFW found no Vulkan loader");
+            return Vec::new();
+        };
+        extensions.into_iter().filter_map(|name| std::ffi::CString::new(name).ok()).collect()
+    }
+
+    fn create_surface(&self, _entry: &ash::Entry, instance: &ash::Instance) -> ash::prelude::VkResult<ash::vk::SurfaceKHR> {
+        let mut surface = ash::vk::SurfaceKHR::null();
+        self.glfw_window
+            .create_window_surface(instance.handle(), std::ptr::null(), &mut surface)
+            .result_with_success(surface)
+    }
+}
+
 impl OpenGLWindow for GlfwWindow {
     fn is_current(&self) -> bool {
         self.glfw_window.is_current()
@@ -335,4 +780,8 @@ impl OpenGLWindow for GlfwWindow {
             }
         }
     }
+
+    fn create_shared_window(&mut self, width: u32, height: u32, title: &str, hints: &[WindowHint]) -> Result<Box<dyn Window>, String> {
+        Ok(Box::new(self.create_shared(width, height, title, hints)?))
+    }
 }
@@ -1,8 +1,14 @@
-use crate::events::core::{Event, EventData, EventQueue};
-use crate::io::{Window, WindowHint, Size, Position};
-use crate::window::factory::{WindowFactory, WindowBackendRegistry};
+use crate::events::core::{
+    Event, EventData, EventQueue, GamepadButton, GamepadButtonEvent, KeyAction, KeyEvent, MouseButtonEvent,
+};
+use crate::error::panic_message;
+use crate::input::InputSnapshot;
+use crate::io::{CursorMode, FullscreenMode, Window, WindowHint, Size, Position};
+use crate::window::factory::{WindowFactory, WindowBackendRegistry, WindowFeature};
+use crate::window::gpu_resources::GpuResourceRegistry;
 use artifice_logging::{debug, info, warn, error};
 use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -41,6 +47,13 @@ pub struct WindowState {
     pub title: String,
     pub should_close: bool,
     pub hints: Vec<WindowHint>,
+    pub cursor_mode: CursorMode,
+    pub fullscreen: FullscreenMode,
+    pub size_limits: (Option<Size>, Option<Size>),
+    pub aspect_ratio: Option<(u32, u32)>,
+    pub opacity: f32,
+    /// Keys and buttons held when the switch started, pressed again on the new window
+    pub held_input: InputSnapshot,
 }
 
 impl WindowState {
@@ -51,13 +64,80 @@ impl WindowState {
             title: window.title().to_string(),
             should_close: window.should_close(),
             hints: Vec::new(), // Would need window to expose hints
+            cursor_mode: window.cursor_mode(),
+            fullscreen: window.fullscreen(),
+            size_limits: window.size_limits(),
+            aspect_ratio: window.aspect_ratio(),
+            opacity: window.opacity(),
+            held_input: InputSnapshot::default(), // Filled in from the input manager
         }
     }
 
+    /// Synthetic presses for the held input that `replayed` events did not touch
+    ///
+    /// The new backend has never seen these keys and buttons go down, so
+    /// without the presses applications tracking them from events would
+    /// lose the hold. Input pressed or released while the switch ran is
+    /// left to the replayed events.
+    pub fn held_input_events(&self, replayed: &[Event]) -> Vec<Event> {
+        let held = &self.held_input;
+        let mods = held.mods;
+        let keys = held
+            .keys
+            .iter()
+            .filter(|key| !replayed.iter().any(|event| event.as_key_event().is_some_and(|e| e.key == **key)))
+            .map(|&key| EventData::Key(KeyEvent { key, action: KeyAction::Press, mods }));
+        let mouse_buttons = held
+            .mouse_buttons
+            .iter()
+            .filter(|button| {
+                !replayed.iter().any(|event| event.as_mouse_button_event().is_some_and(|e| e.button == **button))
+            })
+            .map(|&button| EventData::MouseButton(MouseButtonEvent { button, action: KeyAction::Press, mods }));
+        let gamepad_buttons = held.gamepads.iter().flat_map(|gamepad| {
+            gamepad
+                .buttons
+                .iter()
+                .map(|&button| GamepadButton::from(button))
+                .filter(|button| {
+                    !replayed.iter().any(|event| {
+                        event
+                            .as_gamepad_button_event()
+                            .is_some_and(|e| e.gamepad_id == gamepad.id && e.button == *button)
+                    })
+                })
+                .map(|button| {
+                    EventData::GamepadButton(GamepadButtonEvent {
+                        gamepad_id: gamepad.id,
+                        button,
+                        action: KeyAction::Press,
+                        mods,
+                    })
+                })
+        });
+        keys.chain(mouse_buttons).chain(gamepad_buttons).map(Event::new).collect()
+    }
+
     pub fn apply_to_window(&self, window: &mut dyn Window) {
+        // Limits first, so the restored size is not clamped by the new backend's defaults
+        if self.size_limits.0.is_some() || self.size_limits.1.is_some() {
+            window.set_size_limits(self.size_limits.0, self.size_limits.1);
+        }
+        if self.aspect_ratio.is_some() {
+            window.set_aspect_ratio(self.aspect_ratio);
+        }
         window.set_size(self.size);
         window.set_position(self.position);
         window.set_title(&self.title);
+        if self.cursor_mode != CursorMode::Normal {
+            window.set_cursor_mode(self.cursor_mode);
+        }
+        if self.opacity < 1.0 {
+            window.set_opacity(self.opacity);
+        }
+        if self.fullscreen != FullscreenMode::Windowed {
+            window.set_fullscreen(self.fullscreen);
+        }
         if self.should_close {
             window.set_should_close();
         }
@@ -72,6 +152,8 @@ pub enum WindowBackendHotswapStatus {
     Completed,
     Failed(String),
     Cancelled,
+    /// The switch failed partway and the previous backend was put back
+    RolledBack(String),
 }
 
 /// Result of a hot reload operation
@@ -85,6 +167,27 @@ pub struct WindowBackendHotswapResult {
     pub errors: Vec<String>,
 }
 
+/// Outcome of trying a backend with a hidden test window before switching to it
+#[derive(Debug, Clone)]
+pub struct BackendReport {
+    pub backend: String,
+    /// Whether a factory is registered under the name
+    pub registered: bool,
+    pub version: Option<String>,
+    pub supported_features: Vec<WindowFeature>,
+    /// Whether the hidden test window could be created
+    pub window_created: bool,
+    /// Time taken to create and close the test window
+    pub duration: Duration,
+    pub error: Option<String>,
+}
+
+impl BackendReport {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
 /// Event buffer for storing events during backend transitions
 pub struct EventBuffer {
     events: Vec<Event>,
@@ -148,7 +251,12 @@ pub struct WindowBackendHotswapManager {
     event_buffer: EventBuffer,
     preserved_state: Option<WindowState>,
     switch_start_time: Option<Instant>,
-    validation_cache: HashMap<String, bool>,
+    /// Backend in use when the current switch started, to roll back to
+    rollback_backend: Option<String>,
+    /// Reports of backends that passed a dry run
+    validation_cache: HashMap<String, BackendReport>,
+    /// OpenGL objects to recreate once a switch has a new context current
+    gpu_resources: Arc<Mutex<GpuResourceRegistry>>,
 }
 
 impl WindowBackendHotswapManager {
@@ -167,7 +275,9 @@ impl WindowBackendHotswapManager {
             event_buffer,
             preserved_state: None,
             switch_start_time: None,
+            rollback_backend: None,
             validation_cache: HashMap::new(),
+            gpu_resources: Arc::new(Mutex::new(GpuResourceRegistry::new())),
         }
     }
 
@@ -203,21 +313,53 @@ impl WindowBackendHotswapManager {
         self.registry.available_backends()
     }
 
-    /// Validate that a backend is available and functional
-    pub fn validate_backend(&mut self, backend_name: &str) -> bool {
-        if let Some(&cached_result) = self.validation_cache.get(backend_name) {
-            return cached_result;
+    /// Dry run a backend by creating and closing a hidden test window on it
+    ///
+    /// Passing reports are cached until `clear_validation_cache`; a backend
+    /// that failed is tried again next time. OpenGL backends make the test
+    /// window's context current, so make the main window current again
+    /// before rendering; `Engine::validate_backend` does this.
+    pub fn validate_backend(&mut self, backend_name: &str) -> BackendReport {
+        if let Some(report) = self.validation_cache.get(backend_name) {
+            return report.clone();
         }
 
-        let is_valid = self.registry.get_backend_info(backend_name).is_some();
-        
-        // Additional validation could include:
-        // - Testing window creation
-        // - Checking system requirements
-        // - Verifying driver availability
-        
-        self.validation_cache.insert(backend_name.to_string(), is_valid);
-        is_valid
+        let start = Instant::now();
+        let mut report = BackendReport {
+            backend: backend_name.to_string(),
+            registered: false,
+            version: None,
+            supported_features: Vec::new(),
+            window_created: false,
+            duration: Duration::ZERO,
+            error: None,
+        };
+        match self.registry.get_backend_info(backend_name) {
+            Some(info) => {
+                report.registered = true;
+                report.version = info.version;
+                report.supported_features = info.supported_features;
+                let hints = [WindowHint::Visible(false)];
+                match self.create_window_with_backend(backend_name, 64, 64, "Backend validation", &hints) {
+                    Ok(window) => {
+                        report.window_created = true;
+                        drop(window);
+                    }
+                    Err(e) => report.error = Some(e),
+                }
+            }
+            None => report.error = Some(format!("Backend '{}' is not registered", backend_name)),
+        }
+        report.duration = start.elapsed();
+
+        match &report.error {
+            None => {
+                debug!("Backend '{}' passed validation in {:?}", backend_name, report.duration);
+                self.validation_cache.insert(backend_name.to_string(), report.clone());
+            }
+            Some(e) => warn!("Backend '{}' failed validation: {}", backend_name, e),
+        }
+        report
     }
 
     /// Start a hot reload operation to switch to a new backend
@@ -226,11 +368,6 @@ impl WindowBackendHotswapManager {
             return Err("Hot reload already in progress".to_string());
         }
 
-        // Validate target backend
-        if self.config.validate_backend && !self.validate_backend(target_backend) {
-            return Err(format!("Backend '{}' is not available or invalid", target_backend));
-        }
-
         // Don't reload to the same backend
         if let Some(ref current) = self.current_backend {
             if current == target_backend {
@@ -238,6 +375,14 @@ impl WindowBackendHotswapManager {
             }
         }
 
+        // Validate target backend
+        if self.config.validate_backend {
+            let report = self.validate_backend(target_backend);
+            if let Some(e) = report.error {
+                return Err(format!("Backend '{}' is not available or invalid: {}", target_backend, e));
+            }
+        }
+
         info!("Starting hot reload to backend: {}", target_backend);
 
         // Preserve current window state
@@ -256,10 +401,21 @@ impl WindowBackendHotswapManager {
         // Update status
         self.status = WindowBackendHotswapStatus::InProgress;
         self.switch_start_time = Some(Instant::now());
+        self.rollback_backend = self.current_backend.clone();
 
         Ok(())
     }
 
+    /// Remember the input held as the switch starts, to press it again on the new window
+    ///
+    /// Call after `start_reload`; without state preservation there is
+    /// nothing to keep it in and the input is ignored.
+    pub fn preserve_held_input(&mut self, held: InputSnapshot) {
+        if let Some(ref mut state) = self.preserved_state {
+            state.held_input = held;
+        }
+    }
+
     /// Complete a hot reload operation with the new window
     pub fn complete_reload(&mut self, target_backend: &str, new_window: &mut dyn Window) -> WindowBackendHotswapResult {
         let start_time = self.switch_start_time.unwrap_or_else(Instant::now);
@@ -292,18 +448,34 @@ impl WindowBackendHotswapManager {
         }
 
         // Replay buffered events
-        if self.config.buffer_events {
-            let buffered_events = self.event_buffer.drain();
-            if !buffered_events.is_empty() {
-                // Set up event callback to replay events
-                if let Some(callback) = new_window.get_event_callback() {
-                    for event in buffered_events {
-                        if let Ok(mut cb) = callback.lock() {
-                            cb(event);
+        let buffered_events = if self.config.buffer_events { self.event_buffer.drain() } else { Vec::new() };
+        if !buffered_events.is_empty() {
+            // Set up event callback to replay events
+            if let Some(callback) = new_window.get_event_callback() {
+                for event in buffered_events.iter().cloned() {
+                    if let Ok(mut cb) = callback.lock() {
+                        cb(event);
+                    }
+                }
+            }
+            debug!("Replayed {} buffered events", events_buffered);
+        }
+
+        // Press what is still held again, so its release on the new window has a matching press
+        if let Some(ref state) = self.preserved_state {
+            let presses = state.held_input_events(&buffered_events);
+            if !presses.is_empty() {
+                match new_window.get_event_callback() {
+                    Some(callback) => {
+                        debug!("Re-emitting {} presses of held input", presses.len());
+                        for event in presses {
+                            if let Ok(mut cb) = callback.lock() {
+                                cb(event);
+                            }
                         }
                     }
+                    None => errors.push(format!("{} held inputs were not re-emitted: the new window has no event callback", presses.len())),
                 }
-                debug!("Replayed {} buffered events", events_buffered);
             }
         }
 
@@ -344,6 +516,26 @@ impl WindowBackendHotswapManager {
         true
     }
 
+    /// Give up on the switch in progress or just completed and return to the previous backend
+    ///
+    /// Only the manager's bookkeeping goes back; the caller keeps or
+    /// restores the previous window. `target_backend` loses its passing
+    /// validation, since the dry run was wrong about it. Returns the
+    /// backend rolled back to.
+    pub fn rollback_reload(&mut self, target_backend: &str, error: impl Into<String>) -> Option<String> {
+        let error = error.into();
+        self.current_backend = self.rollback_backend.take();
+        self.validation_cache.remove(target_backend);
+        warn!("Rolling back switch to '{}', returning to {:?}: {}", target_backend, self.current_backend, error);
+
+        self.status = WindowBackendHotswapStatus::RolledBack(error);
+        self.preserved_state = None;
+        self.switch_start_time = None;
+        self.event_buffer.set_enabled(false);
+        self.event_buffer.clear();
+        self.current_backend.clone()
+    }
+
     /// Handle an event during hot reload (for buffering)
     pub fn handle_event(&mut self, event: Event) -> bool {
         if self.is_reloading() && self.config.buffer_events {
@@ -361,14 +553,20 @@ impl WindowBackendHotswapManager {
         title: &str,
         hints: &[WindowHint],
     ) -> Result<Box<dyn Window>, String> {
-        if hints.is_empty() {
-            self.registry
-                .create_window(backend_name, width, height, title)
-                .ok_or_else(|| format!("Failed to create window with backend '{}'", backend_name))
-        } else {
-            self.registry
-                .create_window_with_hints(backend_name, width, height, title, hints)
-                .ok_or_else(|| format!("Failed to create window with backend '{}'", backend_name))
+        // Backends panic when they cannot open a display or create a context
+        let created = panic::catch_unwind(AssertUnwindSafe(|| {
+            if hints.is_empty() {
+                self.registry.create_window(backend_name, width, height, title)
+            } else {
+                self.registry.create_window_with_hints(backend_name, width, height, title, hints)
+            }
+        }));
+        match created {
+            Ok(Some(window)) => Ok(window),
+            Ok(None) => Err(format!("Failed to create window with backend '{}'", backend_name)),
+            Err(payload) => {
+                Err(format!("Failed to create window with backend '{}': {}", backend_name, panic_message(payload.as_ref())))
+            }
         }
     }
 
@@ -401,6 +599,19 @@ impl WindowBackendHotswapManager {
     pub fn registry_mut(&mut self) -> &mut WindowBackendRegistry {
         &mut self.registry
     }
+
+    /// Shared handle to the OpenGL objects recreated after each switch
+    pub fn gpu_resources(&self) -> Arc<Mutex<GpuResourceRegistry>> {
+        self.gpu_resources.clone()
+    }
+
+    /// Recreate the registered OpenGL objects after `complete_reload`, with the new context current
+    pub fn restore_gpu_resources(&mut self) -> Vec<String> {
+        match self.gpu_resources.lock() {
+            Ok(mut resources) => resources.restore(),
+            Err(_) => vec!["GPU resource registry is poisoned".to_string()],
+        }
+    }
 }
 
 /// Statistics about the hot reload manager
@@ -524,4 +735,258 @@ impl WindowBackendHotswapFactory {
     pub fn create_custom(registry: WindowBackendRegistry, config: WindowBackendHotswapConfig) -> WindowBackendHotswapManager {
         WindowBackendHotswapManager::with_config(registry, config)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::core::{KeyCode, KeyMod, MouseButton};
+    use crate::input::GamepadSnapshot;
+    use crate::io::{CursorIcon, VideoMode};
+
+    /// Factory whose windows fail to open, as backends do without a display
+    struct BrokenFactory;
+
+    impl WindowFactory for BrokenFactory {
+        fn create_window(&self, _width: u32, _height: u32, _title: &str) -> Box<dyn Window> {
+            panic!("Failed to open display")
+        }
+
+        fn create_window_with_hints(&self, _width: u32, _height: u32, _title: &str, _hints: &[WindowHint]) -> Box<dyn Window> {
+            panic!("Failed to open display")
+        }
+
+        fn supports_feature(&self, _feature: WindowFeature) -> bool {
+            false
+        }
+
+        fn backend_name(&self) -> &str {
+            "Broken"
+        }
+    }
+
+    /// Window that only keeps what is set on it, like a backend without a display
+    struct StubWindow {
+        size: Size,
+        position: Position,
+        title: String,
+        should_close: bool,
+        cursor_mode: CursorMode,
+        fullscreen: FullscreenMode,
+        opacity: f32,
+    }
+
+    impl StubWindow {
+        fn new() -> Self {
+            Self {
+                size: Size(640, 480),
+                position: Position(0, 0),
+                title: String::new(),
+                should_close: false,
+                cursor_mode: CursorMode::Normal,
+                fullscreen: FullscreenMode::Windowed,
+                opacity: 1.0,
+            }
+        }
+    }
+
+    impl Window for StubWindow {
+        fn update(&mut self) {}
+
+        fn process_events(&mut self) {}
+
+        fn set_should_close(&mut self) {
+            self.should_close = true;
+        }
+
+        fn should_close(&self) -> bool {
+            self.should_close
+        }
+
+        fn set_position(&mut self, position: Position) {
+            self.position = position;
+        }
+
+        fn position(&self) -> &Position {
+            &self.position
+        }
+
+        fn set_size(&mut self, size: Size) {
+            self.size = size;
+        }
+
+        fn size(&self) -> &Size {
+            &self.size
+        }
+
+        fn title(&self) -> &str {
+            &self.title
+        }
+
+        fn set_title(&mut self, title: &str) {
+            self.title = title.to_string();
+        }
+
+        fn set_cursor_mode(&mut self, mode: CursorMode) {
+            self.cursor_mode = mode;
+        }
+
+        fn cursor_mode(&self) -> CursorMode {
+            self.cursor_mode
+        }
+
+        fn set_fullscreen(&mut self, mode: FullscreenMode) {
+            self.fullscreen = mode;
+        }
+
+        fn fullscreen(&self) -> FullscreenMode {
+            self.fullscreen
+        }
+
+        fn set_opacity(&mut self, opacity: f32) {
+            self.opacity = opacity;
+        }
+
+        fn opacity(&self) -> f32 {
+            self.opacity
+        }
+
+        fn get_event_callback(&self) -> Option<Arc<Mutex<dyn FnMut(Event) + Send + 'static>>> {
+            None
+        }
+
+        fn set_event_callback(&mut self, _callback: Arc<Mutex<dyn FnMut(Event) + Send + 'static>>) {}
+
+        fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_window_state_carries_cursor_mode() {
+        let mut old = StubWindow::new();
+        old.set_title("Game");
+        old.set_cursor_mode(CursorMode::Captured);
+        let state = WindowState::capture_from_window(&old);
+
+        // A captured cursor stays captured on the new backend
+        let mut new = StubWindow::new();
+        state.apply_to_window(&mut new);
+        assert_eq!(new.cursor_mode(), CursorMode::Captured);
+        assert_eq!(new.title(), "Game");
+
+        old.set_cursor_mode(CursorMode::Confined);
+        let mut new = StubWindow::new();
+        WindowState::capture_from_window(&old).apply_to_window(&mut new);
+        assert_eq!(new.cursor_mode(), CursorMode::Confined);
+
+        old.show_cursor();
+        let mut new = StubWindow::new();
+        WindowState::capture_from_window(&old).apply_to_window(&mut new);
+        assert_eq!(new.cursor_mode(), CursorMode::Normal);
+    }
+
+    #[test]
+    fn test_hide_and_show_cursor_switch_mode() {
+        let mut window = StubWindow::new();
+        window.hide_cursor();
+        assert_eq!(window.cursor_mode(), CursorMode::Hidden);
+        window.set_cursor_mode(CursorMode::Captured);
+        window.show_cursor();
+        assert_eq!(window.cursor_mode(), CursorMode::Normal);
+        assert_eq!(window.cursor(), CursorIcon::Arrow);
+    }
+
+    #[test]
+    fn test_toggle_fullscreen_returns_to_windowed() {
+        let mut window = StubWindow::new();
+        window.toggle_fullscreen();
+        assert_eq!(window.fullscreen(), FullscreenMode::Borderless);
+        window.toggle_fullscreen();
+        assert_eq!(window.fullscreen(), FullscreenMode::Windowed);
+
+        // Leaving exclusive fullscreen goes back to a window, not to borderless
+        let video_mode = VideoMode { width: 1920, height: 1080, refresh_rate: 144 };
+        window.set_fullscreen(FullscreenMode::Exclusive { monitor: 1, video_mode });
+        window.toggle_fullscreen();
+        assert_eq!(window.fullscreen(), FullscreenMode::Windowed);
+    }
+
+    #[test]
+    fn test_window_state_carries_fullscreen() {
+        let video_mode = VideoMode { width: 1280, height: 720, refresh_rate: 60 };
+        let mut old = StubWindow::new();
+        old.set_fullscreen(FullscreenMode::Exclusive { monitor: 0, video_mode });
+
+        let mut new = StubWindow::new();
+        WindowState::capture_from_window(&old).apply_to_window(&mut new);
+        assert_eq!(new.fullscreen(), FullscreenMode::Exclusive { monitor: 0, video_mode });
+    }
+
+    #[test]
+    fn test_window_state_carries_opacity() {
+        let mut old = StubWindow::new();
+        old.set_opacity(0.75);
+        let mut new = StubWindow::new();
+        WindowState::capture_from_window(&old).apply_to_window(&mut new);
+        assert_eq!(new.opacity(), 0.75);
+    }
+
+    #[test]
+    fn test_dry_run_reports_failing_backend() {
+        let mut registry = WindowBackendRegistry::new();
+        registry.register_factory("broken".to_string(), Box::new(BrokenFactory));
+        let mut manager = WindowBackendHotswapManager::new(registry);
+
+        let report = manager.validate_backend("broken");
+        assert!(report.registered && !report.window_created);
+        assert_eq!(report.error.as_deref(), Some("Failed to create window with backend 'broken': Failed to open display"));
+        // Failures are not cached, so the backend is tried again
+        assert_eq!(manager.get_stats().validation_cache_size, 0);
+
+        let report = manager.validate_backend("missing");
+        assert!(!report.registered && !report.is_ok());
+
+        assert_eq!(manager.rollback_reload("broken", "no display"), None);
+        assert_eq!(manager.status(), &WindowBackendHotswapStatus::RolledBack("no display".to_string()));
+    }
+
+    #[test]
+    fn test_held_input_pressed_again_unless_replayed() {
+        let state = WindowState {
+            size: Size::from((800, 600)),
+            position: Position::from((0, 0)),
+            title: String::new(),
+            should_close: false,
+            hints: Vec::new(),
+            cursor_mode: CursorMode::Normal,
+            fullscreen: FullscreenMode::Windowed,
+            size_limits: (None, None),
+            aspect_ratio: None,
+            opacity: 1.0,
+            held_input: InputSnapshot {
+                keys: vec![KeyCode::W, KeyCode::LeftShift],
+                mouse_buttons: vec![MouseButton::Left],
+                gamepads: vec![GamepadSnapshot {
+                    id: 1,
+                    buttons: vec![crate::input::gamepad::GamepadButton::A],
+                    axes: Vec::new(),
+                }],
+                ..Default::default()
+            },
+        };
+        // Shift was let go while the switch ran
+        let replayed = [Event::new(EventData::Key(KeyEvent {
+            key: KeyCode::LeftShift,
+            action: KeyAction::Release,
+            mods: KeyMod::new(),
+        }))];
+
+        let presses = state.held_input_events(&replayed);
+        assert_eq!(presses.len(), 3);
+        assert!(matches!(presses[0].as_key_event(), Some(e) if e.key == KeyCode::W && e.action == KeyAction::Press));
+        assert!(matches!(presses[1].as_mouse_button_event(), Some(e) if e.button == MouseButton::Left));
+        assert!(
+            matches!(presses[2].as_gamepad_button_event(), Some(e) if e.gamepad_id == 1 && e.button == GamepadButton::A)
+        );
+    }
+}
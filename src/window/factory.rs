@@ -1,9 +1,11 @@
 use crate::io::{Window, WindowHint};
+use crate::window::headless::HeadlessWindowFactory;
 #[cfg(feature = "wayland")]
 use crate::window::wayland::WaylandWindowFactory;
 #[cfg(feature = "x11")]
 use crate::window::x11::X11WindowFactory;
 use std::collections::HashMap;
+use std::fmt;
 use artifice_logging::{debug, info, warn};
 
 /// Trait for creating windows with different backends
@@ -68,7 +70,7 @@ impl WindowFactory for GlfwWindowFactory {
     fn supports_feature(&self, feature: WindowFeature) -> bool {
         match feature {
             WindowFeature::OpenGL => true,
-            WindowFeature::Vulkan => true, // GLFW supports Vulkan
+            WindowFeature::Vulkan => cfg!(feature = "vulkan"),
             WindowFeature::MultiWindow => true,
             WindowFeature::HighDPI => true,
             WindowFeature::Fullscreen => true,
@@ -106,6 +108,7 @@ impl WindowBackendRegistry {
         
         // Register default backends
         registry.register_factory("glfw".to_string(), Box::new(GlfwWindowFactory));
+        registry.register_factory("headless".to_string(), Box::new(HeadlessWindowFactory));
         
         // Register Wayland backend if available
         #[cfg(all(feature = "wayland", target_os = "linux"))]
@@ -211,6 +214,50 @@ impl WindowBackendRegistry {
     pub fn default_backend(&self) -> Option<&String> {
         self.default_backend.as_ref()
     }
+
+    /// Backends in the order they are tried: the default first, then the rest by name
+    pub fn preference_order(&self) -> Vec<&String> {
+        let mut backends: Vec<&String> = self.factories.keys().collect();
+        backends.sort_by_key(|name| (Some(*name) != self.default_backend.as_ref(), *name));
+        backends
+    }
+
+    /// Pick the first backend, in preference order, that supports every feature in `required`
+    pub fn select_backend(&self, required: &[WindowFeature]) -> BackendSelection {
+        let mut selection = BackendSelection::default();
+        for name in self.preference_order() {
+            let factory = &self.factories[name];
+            let missing: Vec<WindowFeature> =
+                required.iter().copied().filter(|&feature| !factory.supports_feature(feature)).collect();
+            if missing.is_empty() {
+                selection.backend = Some(name.clone());
+                break;
+            }
+            debug!("Skipping window backend {}: lacks {:?}", name, missing);
+            selection.skipped.push(SkippedBackend { name: name.clone(), missing });
+        }
+        selection
+    }
+
+    /// Create a window on the first backend supporting `required`, see `select_backend`
+    ///
+    /// Fails with the selection report when no backend qualifies.
+    pub fn create_window_with_features(
+        &self,
+        width: u32,
+        height: u32,
+        title: &str,
+        hints: &[WindowHint],
+        required: &[WindowFeature],
+    ) -> Result<Box<dyn Window>, String> {
+        let selection = self.select_backend(required);
+        let Some(backend) = &selection.backend else {
+            return Err(format!("No window backend supports {:?}: {}", required, selection));
+        };
+        info!("Selected window backend: {}", selection);
+        self.create_window_with_hints(backend, width, height, title, hints)
+            .ok_or_else(|| format!("Window backend {} disappeared from the registry", backend))
+    }
 }
 
 impl Default for WindowBackendRegistry {
@@ -219,6 +266,36 @@ impl Default for WindowBackendRegistry {
     }
 }
 
+/// Which backend `WindowBackendRegistry::select_backend` picked, and why it passed over the others
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackendSelection {
+    /// `None` when no registered backend supports every required feature
+    pub backend: Option<String>,
+    /// Backends tried before the chosen one, in order
+    pub skipped: Vec<SkippedBackend>,
+}
+
+/// A backend passed over during selection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedBackend {
+    pub name: String,
+    /// Required features it lacks
+    pub missing: Vec<WindowFeature>,
+}
+
+impl fmt::Display for BackendSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.backend {
+            Some(backend) => write!(f, "{}", backend)?,
+            None => write!(f, "none")?,
+        }
+        for skipped in &self.skipped {
+            write!(f, "; skipped {} (lacks {:?})", skipped.name, skipped.missing)?;
+        }
+        Ok(())
+    }
+}
+
 /// Information about a window backend
 #[derive(Debug, Clone)]
 pub struct BackendInfo {
@@ -250,14 +327,87 @@ pub fn create_default_registry() -> WindowBackendRegistry {
     WindowBackendRegistry::new()
 }
 
-/// Helper function to create a window with automatic backend selection
-pub fn create_window_auto(width: u32, height: u32, title: &str) -> Option<Box<dyn Window>> {
-    let registry = create_default_registry();
-    registry.create_default_window(width, height, title)
+/// Helper function to create a window on the first backend supporting `required`
+///
+/// Backends are tried default first; the error lists why each was skipped.
+pub fn create_window_auto(width: u32, height: u32, title: &str, required: &[WindowFeature]) -> Result<Box<dyn Window>, String> {
+    create_window_auto_with_hints(width, height, title, &[], required)
 }
 
-/// Helper function to create a window with hints and automatic backend selection
-pub fn create_window_auto_with_hints(width: u32, height: u32, title: &str, hints: &[WindowHint]) -> Option<Box<dyn Window>> {
+/// Helper function to create a window with hints on the first backend supporting `required`
+pub fn create_window_auto_with_hints(
+    width: u32,
+    height: u32,
+    title: &str,
+    hints: &[WindowHint],
+    required: &[WindowFeature],
+) -> Result<Box<dyn Window>, String> {
     let registry = create_default_registry();
-    registry.create_default_window_with_hints(width, height, title, hints)
+    registry.create_window_with_features(width, height, title, hints, required)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Factory that only reports features; selection never creates windows
+    struct FeatureFactory(&'static [WindowFeature]);
+
+    impl WindowFactory for FeatureFactory {
+        fn create_window(&self, _width: u32, _height: u32, _title: &str) -> Box<dyn Window> {
+            panic!("FeatureFactory only reports features and cannot create windows")
+        }
+
+        fn create_window_with_hints(&self, _width: u32, _height: u32, _title: &str, _hints: &[WindowHint]) -> Box<dyn Window> {
+            panic!("FeatureFactory only reports features and cannot create windows")
+        }
+
+        fn supports_feature(&self, feature: WindowFeature) -> bool {
+            self.0.contains(&feature)
+        }
+
+        fn backend_name(&self) -> &str {
+            "Test"
+        }
+    }
+
+    #[test]
+    fn test_vulkan_support_follows_the_feature() {
+        // Windowing backends create Vulkan surfaces only when built with ash
+        let registry = WindowBackendRegistry::new();
+        for backend in registry.available_backends().into_iter().filter(|name| *name != "headless") {
+            assert_eq!(registry.backend_supports_feature(backend, WindowFeature::Vulkan), cfg!(feature = "vulkan"), "{}", backend);
+        }
+        assert!(!registry.backend_supports_feature("headless", WindowFeature::Vulkan));
+
+        let selection = registry.select_backend(&[WindowFeature::Vulkan]);
+        assert_eq!(selection.backend.is_some(), cfg!(feature = "vulkan"));
+    }
+
+    #[test]
+    fn test_select_backend_skips_backends_lacking_features() {
+        let mut registry = WindowBackendRegistry::new();
+        registry.register_factory("vulkan-only".to_string(), Box::new(FeatureFactory(&[WindowFeature::Vulkan])));
+        registry.register_factory("a-minimal".to_string(), Box::new(FeatureFactory(&[])));
+        registry.set_default_backend("vulkan-only");
+        let order: Vec<&str> = registry.preference_order().into_iter().map(String::as_str).collect();
+        assert_eq!(order[..3], ["vulkan-only", "a-minimal", "glfw"]);
+
+        let selection = registry.select_backend(&[WindowFeature::Vulkan]);
+        assert_eq!(selection, BackendSelection { backend: Some("vulkan-only".to_string()), skipped: Vec::new() });
+
+        let selection = registry.select_backend(&[WindowFeature::OpenGL]);
+        assert_eq!(selection.backend.as_deref(), Some("glfw"));
+        assert_eq!(
+            selection.skipped,
+            [
+                SkippedBackend { name: "vulkan-only".to_string(), missing: vec![WindowFeature::OpenGL] },
+                SkippedBackend { name: "a-minimal".to_string(), missing: vec![WindowFeature::OpenGL] },
+            ]
+        );
+
+        let selection = registry.select_backend(&[WindowFeature::DirectX]);
+        assert_eq!(selection.backend, None);
+        assert!(selection.to_string().starts_with("none; skipped vulkan-only (lacks [DirectX]); skipped a-minimal"));
+    }
 }
\ No newline at end of file
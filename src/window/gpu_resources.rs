@@ -0,0 +1,349 @@
+use crate::io::ImageBuffer;
+use artifice_logging::{debug, info, warn};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fmt;
+
+/// Stable handle to a resource in a `GpuResourceRegistry`
+///
+/// The OpenGL name behind it changes whenever the resource is recreated in
+/// a new context, so look the name up each time rather than caching it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GpuResourceId(u32);
+
+/// OpenGL names of registered resources in the current context
+#[derive(Debug, Default)]
+pub struct GpuResourceNames {
+    names: HashMap<GpuResourceId, u32>,
+}
+
+impl GpuResourceNames {
+    pub fn get(&self, id: GpuResourceId) -> Option<u32> {
+        self.names.get(&id).copied()
+    }
+}
+
+/// Creates a custom object in the current context and returns its OpenGL name
+///
+/// Receives the names of the resources registered before it, already
+/// recreated, so a vertex array can bind the buffers it was built from.
+pub type GpuResourceCallback = Box<dyn FnMut(&GpuResourceNames) -> Result<u32, String> + Send>;
+
+/// What it takes to recreate a resource in a fresh context
+enum GpuResource {
+    /// RGBA 2D texture, filtered with `filter` in both directions
+    Texture { image: ImageBuffer, filter: u32 },
+    Buffer { target: u32, data: Vec<u8>, usage: u32 },
+    /// Program linked from a vertex and a fragment shader
+    Shader { vertex: String, fragment: String },
+    Custom { label: String, create: GpuResourceCallback },
+}
+
+impl GpuResource {
+    /// Create the resource in the current context
+    fn create(&mut self, names: &GpuResourceNames) -> Result<u32, String> {
+        match self {
+            GpuResource::Texture { image, filter } => Ok(upload_texture(image, *filter)),
+            GpuResource::Buffer { target, data, usage } => Ok(upload_buffer(*target, data, *usage)),
+            GpuResource::Shader { vertex, fragment } => link_program(vertex, fragment),
+            GpuResource::Custom { create, .. } => create(names),
+        }
+    }
+
+    /// Free the resource's object `name` in the current context; custom objects are left to their owner
+    fn delete(&self, name: u32) {
+        unsafe {
+            match self {
+                GpuResource::Texture { .. } => gl::DeleteTextures(1, &name),
+                GpuResource::Buffer { .. } => gl::DeleteBuffers(1, &name),
+                GpuResource::Shader { .. } => gl::DeleteProgram(name),
+                GpuResource::Custom { .. } => {}
+            }
+        }
+    }
+}
+
+impl fmt::Debug for GpuResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuResource::Texture { image, .. } => write!(f, "Texture({}x{})", image.width(), image.height()),
+            GpuResource::Buffer { data, .. } => write!(f, "Buffer({} bytes)", data.len()),
+            GpuResource::Shader { .. } => write!(f, "Shader"),
+            GpuResource::Custom { label, .. } => write!(f, "Custom({})", label),
+        }
+    }
+}
+
+/// OpenGL objects that outlive the context they were made in
+///
+/// Switching window backends destroys the OpenGL context and every object
+/// in it. Resources registered here keep a CPU-side description, or a
+/// callback for custom objects, and are recreated in registration order
+/// once the new context is current. Registering needs a current context.
+///
+/// The engine hands the registry to `Application::set_gpu_resources` and
+/// restores it after each backend switch.
+///
+/// ```ignore
+/// let mut resources = resources.lock().unwrap();
+/// let vbo = resources.register_buffer(gl::ARRAY_BUFFER, vertex_bytes, gl::STATIC_DRAW)?;
+/// let vao = resources.register_custom("triangle vao", move |names| {
+///     Ok(build_vao(names.get(vbo).unwrap()))
+/// })?;
+/// // ... when drawing:
+/// gl::BindVertexArray(resources.name(vao).unwrap());
+/// ```
+#[derive(Debug, Default)]
+pub struct GpuResourceRegistry {
+    resources: Vec<(GpuResourceId, GpuResource)>,
+    names: GpuResourceNames,
+    next_id: u32,
+}
+
+impl GpuResourceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Upload `image` as a texture filtered with `filter`, e.g. `gl::LINEAR`
+    pub fn register_texture(&mut self, image: ImageBuffer, filter: u32) -> Result<GpuResourceId, String> {
+        self.register(GpuResource::Texture { image, filter })
+    }
+
+    /// Upload `data` into a buffer for `target`, e.g. `gl::ARRAY_BUFFER` with `gl::STATIC_DRAW`
+    ///
+    /// Keep the copy current with `update_buffer` if the buffer changes.
+    pub fn register_buffer(&mut self, target: u32, data: Vec<u8>, usage: u32) -> Result<GpuResourceId, String> {
+        self.register(GpuResource::Buffer { target, data, usage })
+    }
+
+    /// Compile and link a program from GLSL sources
+    pub fn register_shader(&mut self, vertex: impl Into<String>, fragment: impl Into<String>) -> Result<GpuResourceId, String> {
+        self.register(GpuResource::Shader { vertex: vertex.into(), fragment: fragment.into() })
+    }
+
+    /// Track an object the registry cannot describe, made by `create` now and after every switch
+    pub fn register_custom(
+        &mut self,
+        label: impl Into<String>,
+        create: impl FnMut(&GpuResourceNames) -> Result<u32, String> + Send + 'static,
+    ) -> Result<GpuResourceId, String> {
+        self.register(GpuResource::Custom { label: label.into(), create: Box::new(create) })
+    }
+
+    /// Replace a buffer's contents, on the GPU and in the copy it is restored from
+    pub fn update_buffer(&mut self, id: GpuResourceId, new_data: Vec<u8>) -> Result<(), String> {
+        let name = self.name(id);
+        let Some((_, GpuResource::Buffer { target, data, usage })) = self.resources.iter_mut().find(|(key, _)| *key == id) else {
+            return Err(format!("{:?} is not a registered buffer", id));
+        };
+        if let Some(name) = name {
+            unsafe {
+                gl::BindBuffer(*target, name);
+                gl::BufferData(*target, new_data.len() as isize, new_data.as_ptr() as *const _, *usage);
+            }
+        }
+        *data = new_data;
+        Ok(())
+    }
+
+    /// OpenGL name of `id` in the current context
+    pub fn name(&self, id: GpuResourceId) -> Option<u32> {
+        self.names.get(id)
+    }
+
+    pub fn names(&self) -> &GpuResourceNames {
+        &self.names
+    }
+
+    /// Stop tracking `id`, deleting its object unless it is custom
+    pub fn remove(&mut self, id: GpuResourceId) -> bool {
+        let Some(index) = self.resources.iter().position(|(key, _)| *key == id) else { return false };
+        let (_, resource) = self.resources.remove(index);
+        if let Some(name) = self.names.names.remove(&id) {
+            resource.delete(name);
+        }
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Recreate every resource in the current context, returning the errors
+    ///
+    /// The old names died with the old context, so nothing is deleted. A
+    /// resource that fails keeps no name and later ones can check for that.
+    pub fn restore(&mut self) -> Vec<String> {
+        if self.resources.is_empty() {
+            return Vec::new();
+        }
+        info!("Restoring {} GPU resources", self.resources.len());
+        self.names.names.clear();
+        let mut errors = Vec::new();
+        for (id, resource) in &mut self.resources {
+            match resource.create(&self.names) {
+                Ok(name) => {
+                    debug!("Restored {:?} {:?} as {}", resource, id, name);
+                    self.names.names.insert(*id, name);
+                }
+                Err(e) => {
+                    warn!("Failed to restore {:?} {:?}: {}", resource, id, e);
+                    errors.push(format!("{:?}: {}", resource, e));
+                }
+            }
+        }
+        errors
+    }
+
+    fn register(&mut self, mut resource: GpuResource) -> Result<GpuResourceId, String> {
+        let name = resource.create(&self.names)?;
+        let id = GpuResourceId(self.next_id);
+        self.next_id += 1;
+        debug!("Registered {:?} as {:?}", resource, id);
+        self.names.names.insert(id, name);
+        self.resources.push((id, resource));
+        Ok(id)
+    }
+}
+
+fn upload_texture(image: &ImageBuffer, filter: u32) -> u32 {
+    let mut texture = 0;
+    unsafe {
+        gl::GenTextures(1, &mut texture);
+        gl::BindTexture(gl::TEXTURE_2D, texture);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter as i32);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            gl::RGBA8 as i32,
+            image.width() as i32,
+            image.height() as i32,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            image.rgba().as_ptr() as *const _,
+        );
+    }
+    texture
+}
+
+fn upload_buffer(target: u32, data: &[u8], usage: u32) -> u32 {
+    let mut buffer = 0;
+    unsafe {
+        gl::GenBuffers(1, &mut buffer);
+        gl::BindBuffer(target, buffer);
+        gl::BufferData(target, data.len() as isize, data.as_ptr() as *const _, usage);
+    }
+    buffer
+}
+
+fn link_program(vertex: &str, fragment: &str) -> Result<u32, String> {
+    unsafe {
+        let vertex = compile_shader(gl::VERTEX_SHADER, vertex)?;
+        let fragment = match compile_shader(gl::FRAGMENT_SHADER, fragment) {
+            Ok(fragment) => fragment,
+            Err(e) => {
+                gl::DeleteShader(vertex);
+                return Err(e);
+            }
+        };
+        let program = gl::CreateProgram();
+        gl::AttachShader(program, vertex);
+        gl::AttachShader(program, fragment);
+        gl::LinkProgram(program);
+        // The program keeps what it needs from the shaders
+        gl::DeleteShader(vertex);
+        gl::DeleteShader(fragment);
+
+        let mut linked = 0;
+        gl::GetProgramiv(program, gl::LINK_STATUS, &mut linked);
+        if linked == 0 {
+            let log = info_log(program, gl::GetProgramiv, gl::GetProgramInfoLog);
+            gl::DeleteProgram(program);
+            return Err(format!("Program linking failed: {}", log));
+        }
+        Ok(program)
+    }
+}
+
+unsafe fn compile_shader(kind: u32, source: &str) -> Result<u32, String> {
+    let source = CString::new(source).map_err(|_| "Shader source contains a NUL byte".to_string())?;
+    let shader = gl::CreateShader(kind);
+    gl::ShaderSource(shader, 1, &source.as_ptr(), std::ptr::null());
+    gl::CompileShader(shader);
+    let mut compiled = 0;
+    gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut compiled);
+    if compiled == 0 {
+        let log = info_log(shader, gl::GetShaderiv, gl::GetShaderInfoLog);
+        gl::DeleteShader(shader);
+        return Err(format!("Shader compilation failed: {}", log));
+    }
+    Ok(shader)
+}
+
+/// Info log of a shader or program
+unsafe fn info_log(
+    object: u32,
+    get_iv: unsafe fn(u32, u32, *mut i32),
+    get_log: unsafe fn(u32, i32, *mut i32, *mut std::ffi::c_char),
+) -> String {
+    let mut length = 0;
+    get_iv(object, gl::INFO_LOG_LENGTH, &mut length);
+    let mut log = vec![0u8; length.max(1) as usize];
+    get_log(object, length, std::ptr::null_mut(), log.as_mut_ptr() as *mut _);
+    String::from_utf8_lossy(&log).trim_end_matches('\0').trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_restore_recreates_in_order_with_new_names() {
+        let mut registry = GpuResourceRegistry::new();
+        let counter = Arc::new(AtomicU32::new(10));
+        let next_name = {
+            let counter = counter.clone();
+            move |_: &GpuResourceNames| Ok(counter.fetch_add(1, Ordering::SeqCst))
+        };
+        let buffer = registry.register_custom("buffer", next_name.clone()).unwrap();
+        let vao = registry
+            .register_custom("vao", move |names: &GpuResourceNames| {
+                // Built from the buffer, so it must come back after it
+                let buffer = names.get(buffer).ok_or("buffer missing")?;
+                Ok(buffer + 100)
+            })
+            .unwrap();
+        let other = registry.register_custom("other", next_name).unwrap();
+        assert_eq!((registry.name(buffer), registry.name(vao), registry.name(other)), (Some(10), Some(110), Some(11)));
+
+        assert!(registry.restore().is_empty());
+        assert_eq!((registry.name(buffer), registry.name(vao), registry.name(other)), (Some(12), Some(112), Some(13)));
+
+        assert!(registry.remove(other));
+        assert!(!registry.remove(other));
+        assert_eq!((registry.len(), registry.name(other)), (2, None));
+        assert!(registry.update_buffer(vao, Vec::new()).is_err());
+
+        // A failed creation registers nothing; a failed restore leaves the resource without a name
+        assert!(registry.register_custom("refused", |_: &GpuResourceNames| Err("no context".to_string())).is_err());
+        let mut created = false;
+        let once = registry
+            .register_custom("once", move |_: &GpuResourceNames| match std::mem::replace(&mut created, true) {
+                false => Ok(1),
+                true => Err("gone".to_string()),
+            })
+            .unwrap();
+        assert_eq!(registry.restore().len(), 1);
+        assert_eq!((registry.len(), registry.name(once), registry.name(vao)), (3, None, Some(114)));
+    }
+}
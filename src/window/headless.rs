@@ -0,0 +1,120 @@
+use crate::events::core::{Event, EventData, WindowResizeEvent};
+use crate::io::{Position, Size, Window, WindowHint};
+use crate::window::factory::{WindowFactory, WindowFeature};
+use artifice_logging::info;
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+/// A window that exists only in memory, for dedicated servers and tests
+///
+/// It needs no display, never shows anything and has no rendering
+/// context. Input reaches the engine only through an `InputInjector` or
+/// posted events.
+pub struct HeadlessWindow {
+    position: Position,
+    size: Size,
+    title: String,
+    should_close: bool,
+    event_callback: Option<Arc<Mutex<dyn FnMut(Event) + Send + 'static>>>,
+}
+
+impl HeadlessWindow {
+    pub fn new(width: u32, height: u32, title: &str) -> Self {
+        info!("Creating headless window: {} ({}x{})", title, width, height);
+        Self { position: Position(0, 0), size: Size(width, height), title: title.to_string(), should_close: false, event_callback: None }
+    }
+
+    fn send_event(&self, event: Event) {
+        if let Some(callback) = &self.event_callback {
+            if let Ok(mut callback) = callback.lock() {
+                callback(event);
+            }
+        }
+    }
+}
+
+impl Window for HeadlessWindow {
+    fn update(&mut self) {}
+
+    fn process_events(&mut self) {}
+
+    fn set_should_close(&mut self) {
+        self.should_close = true;
+    }
+
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn set_position(&mut self, position: Position) {
+        self.position = position;
+    }
+
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Resizes at once and reports it, as a window manager would
+    fn set_size(&mut self, size: Size) {
+        if size.size() != self.size.size() {
+            self.size = size;
+            self.send_event(Event::new(EventData::WindowResize(WindowResizeEvent { width: size.0, height: size.1 })));
+        }
+    }
+
+    fn size(&self) -> &Size {
+        &self.size
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    fn set_app_id(&mut self, _app_id: &str) {}
+
+    fn is_visible(&self) -> bool {
+        false
+    }
+
+    fn set_vsync(&mut self, _enabled: bool) {}
+
+    fn get_event_callback(&self) -> Option<Arc<Mutex<dyn FnMut(Event) + Send + 'static>>> {
+        self.event_callback.clone()
+    }
+
+    fn set_event_callback(&mut self, callback: Arc<Mutex<dyn FnMut(Event) + Send + 'static>>) {
+        self.event_callback = Some(callback);
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Headless window factory
+///
+/// Registered as "headless". It reports no features, so selecting a backend by any required feature passes it over.
+pub struct HeadlessWindowFactory;
+
+impl WindowFactory for HeadlessWindowFactory {
+    fn create_window(&self, width: u32, height: u32, title: &str) -> Box<dyn Window> {
+        Box::new(HeadlessWindow::new(width, height, title))
+    }
+
+    /// Hints only affect how a window looks, so they are ignored
+    fn create_window_with_hints(&self, width: u32, height: u32, title: &str, _hints: &[WindowHint]) -> Box<dyn Window> {
+        Box::new(HeadlessWindow::new(width, height, title))
+    }
+
+    fn supports_feature(&self, _feature: WindowFeature) -> bool {
+        false
+    }
+
+    fn backend_name(&self) -> &str {
+        "Headless"
+    }
+}
@@ -0,0 +1,102 @@
+//! Keyboard layout helpers shared by the window backends
+//!
+//! X11 keysyms, which xkbcommon uses as well, encode Latin-1 characters as
+//! their code point and other characters as the code point plus 0x01000000.
+
+use crate::events::core::KeyCode;
+
+/// Key named by the character it types, ignoring case
+///
+/// Only characters with a `KeyCode` of their own map; backends fall back to
+/// the key's physical position for the rest.
+pub(crate) fn char_to_keycode(character: char) -> Option<KeyCode> {
+    let key = match character.to_ascii_lowercase() {
+        ' ' => KeyCode::Space,
+        '\'' => KeyCode::Apostrophe,
+        ',' => KeyCode::Comma,
+        '-' => KeyCode::Minus,
+        '.' => KeyCode::Period,
+        '/' => KeyCode::Slash,
+        ';' => KeyCode::Semicolon,
+        '=' => KeyCode::Equal,
+        '[' => KeyCode::LeftBracket,
+        '\\' => KeyCode::Backslash,
+        ']' => KeyCode::RightBracket,
+        '`' => KeyCode::GraveAccent,
+        '0' => KeyCode::Num0,
+        '1' => KeyCode::Num1,
+        '2' => KeyCode::Num2,
+        '3' => KeyCode::Num3,
+        '4' => KeyCode::Num4,
+        '5' => KeyCode::Num5,
+        '6' => KeyCode::Num6,
+        '7' => KeyCode::Num7,
+        '8' => KeyCode::Num8,
+        '9' => KeyCode::Num9,
+        'a' => KeyCode::A,
+        'b' => KeyCode::B,
+        'c' => KeyCode::C,
+        'd' => KeyCode::D,
+        'e' => KeyCode::E,
+        'f' => KeyCode::F,
+        'g' => KeyCode::G,
+        'h' => KeyCode::H,
+        'i' => KeyCode::I,
+        'j' => KeyCode::J,
+        'k' => KeyCode::K,
+        'l' => KeyCode::L,
+        'm' => KeyCode::M,
+        'n' => KeyCode::N,
+        'o' => KeyCode::O,
+        'p' => KeyCode::P,
+        'q' => KeyCode::Q,
+        'r' => KeyCode::R,
+        's' => KeyCode::S,
+        't' => KeyCode::T,
+        'u' => KeyCode::U,
+        'v' => KeyCode::V,
+        'w' => KeyCode::W,
+        'x' => KeyCode::X,
+        'y' => KeyCode::Y,
+        'z' => KeyCode::Z,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Character a keysym types, if it types one
+#[cfg_attr(not(any(feature = "x11", feature = "wayland")), allow(dead_code))]
+pub(crate) fn keysym_to_char(keysym: u32) -> Option<char> {
+    match keysym {
+        0x20..=0x7e | 0xa0..=0xff => char::from_u32(keysym),
+        0x0100_0100..=0x0110_ffff => char::from_u32(keysym - 0x0100_0000),
+        _ => None,
+    }
+}
+
+/// Key named by the character a keysym types
+#[cfg_attr(not(any(feature = "x11", feature = "wayland")), allow(dead_code))]
+pub(crate) fn keysym_to_keycode(keysym: u32) -> Option<KeyCode> {
+    keysym_to_char(keysym).and_then(char_to_keycode)
+}
+
+/// Label for a key typing `character`, as printed on keycaps
+pub(crate) fn key_label(character: char) -> String {
+    character.to_uppercase().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keysyms_map_to_the_keys_they_type() {
+        assert_eq!(keysym_to_keycode(0x61), Some(KeyCode::A)); // XK_a
+        assert_eq!(keysym_to_keycode(0x51), Some(KeyCode::Q)); // XK_Q
+        assert_eq!(keysym_to_keycode(0x26), None); // XK_ampersand, unshifted 1 on AZERTY
+        assert_eq!(keysym_to_char(0xf6), Some('ö')); // XK_odiaeresis
+        assert_eq!(keysym_to_char(0x0100_044f), Some('я')); // Unicode keysym
+        assert_eq!(keysym_to_char(0xff0d), None); // XK_Return
+        assert_eq!(key_label('ö'), "Ö");
+    }
+}
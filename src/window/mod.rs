@@ -4,12 +4,18 @@ pub mod wayland;
 #[cfg(feature = "x11")]
 pub mod x11;
 pub mod factory;
+pub(crate) mod keysym;
+#[cfg(feature = "wayland")]
+mod xkb;
 pub mod backend_hotswap;
+pub mod gpu_resources;
+pub mod headless;
+pub mod persistence;
 
 // Re-export key types for easier access
 pub use artificeglfw::GlfwWindow;
 pub use factory::{
-    WindowFactory, WindowFeature, WindowBackendRegistry, BackendInfo,
+    WindowFactory, WindowFeature, WindowBackendRegistry, BackendInfo, BackendSelection, SkippedBackend,
     GlfwWindowFactory, create_default_registry, create_window_auto, create_window_auto_with_hints
 };
 
@@ -27,6 +33,10 @@ pub use backend_hotswap::{
     WindowBackendHotswapBuilder as HotReloadBuilder,
     WindowBackendHotswapFactory as HotReloadFactory,
     WindowBackendHotswapStats as HotReloadStats,
+    BackendReport,
     WindowState,
     EventBuffer
-};
\ No newline at end of file
+};
+pub use headless::{HeadlessWindow, HeadlessWindowFactory};
+pub use gpu_resources::{GpuResourceCallback, GpuResourceId, GpuResourceNames, GpuResourceRegistry};
+pub use persistence::{SavedWindowState, WindowStatePersistence};
\ No newline at end of file
@@ -0,0 +1,165 @@
+use crate::io::{FullscreenMode, Position, Size, Window};
+use artifice_logging::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a window was and how it covered the screen when it was saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedWindowState {
+    /// Position and size of the window when neither maximized nor fullscreen
+    pub position: (i32, i32),
+    pub size: (u32, u32),
+    /// Monitor the window was on, numbered as for `Window::video_modes`
+    pub monitor: Option<usize>,
+    pub maximized: bool,
+    pub fullscreen: FullscreenMode,
+}
+
+impl SavedWindowState {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Keeps a window's placement in a file so it reopens where the user left it
+///
+/// `restore` moves a freshly opened window to the saved state and `save`
+/// writes the window's state back. While the window is maximized or
+/// fullscreen its own size says nothing about where it should return to,
+/// so `track` remembers the last plain geometry it saw.
+///
+/// The engine does this for the main window when the application names a
+/// file in `Application::window_state_file`.
+#[derive(Debug)]
+pub struct WindowStatePersistence {
+    path: PathBuf,
+    /// Last position and size seen while the window was neither maximized nor fullscreen
+    normal_rect: Option<(Position, Size)>,
+}
+
+impl WindowStatePersistence {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), normal_rect: None }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The saved state, or `None` if nothing has been saved yet
+    pub fn load(&self) -> Result<Option<SavedWindowState>, String> {
+        let json = match fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read {}: {}", self.path.display(), e)),
+        };
+        SavedWindowState::from_json(&json)
+            .map(Some)
+            .map_err(|e| format!("{}: {}", self.path.display(), e))
+    }
+
+    /// Move `window` to the saved state, if there is one
+    ///
+    /// The saved position is skipped when its monitor is gone, so the
+    /// window does not open off screen after a monitor was unplugged.
+    pub fn restore(&mut self, window: &mut dyn Window) -> Result<(), String> {
+        let Some(state) = self.load()? else {
+            debug!("No saved window state in {}", self.path.display());
+            return Ok(());
+        };
+        info!("Restoring window state from {}", self.path.display());
+
+        let size = Size::from(state.size);
+        window.set_size(size);
+        let monitor_gone = state.monitor.is_some_and(|monitor| {
+            // Backends that list no monitors at all cannot tell
+            !window.video_modes(0).is_empty() && window.video_modes(monitor).is_empty()
+        });
+        if monitor_gone {
+            info!("Monitor {:?} of the saved window state is gone, keeping the default position", state.monitor);
+        } else {
+            window.set_position(Position::from(state.position));
+        }
+        self.normal_rect = Some((*window.position(), size));
+
+        if state.maximized {
+            window.set_maximized(true);
+        }
+        if state.fullscreen != FullscreenMode::Windowed {
+            window.set_fullscreen(state.fullscreen);
+        }
+        Ok(())
+    }
+
+    /// Remember `window`'s geometry if it is neither maximized nor fullscreen
+    ///
+    /// Call after the window moves or resizes.
+    pub fn track(&mut self, window: &dyn Window) {
+        if window.fullscreen() == FullscreenMode::Windowed && !window.is_maximized() {
+            self.normal_rect = Some((*window.position(), *window.size()));
+        }
+    }
+
+    /// The state `window` is in now
+    pub fn capture(&mut self, window: &mut dyn Window) -> SavedWindowState {
+        self.track(window);
+        let (position, size) = self.normal_rect.unwrap_or((*window.position(), *window.size()));
+        SavedWindowState {
+            position: position.position(),
+            size: size.size(),
+            monitor: window.monitor(),
+            maximized: window.is_maximized(),
+            fullscreen: window.fullscreen(),
+        }
+    }
+
+    /// Write `window`'s state to the file, creating its directory if needed
+    pub fn save(&mut self, window: &mut dyn Window) -> Result<(), String> {
+        let state = self.capture(window);
+        let json = state.to_json().map_err(|e| format!("Failed to encode window state: {}", e))?;
+        if let Some(directory) = self.path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            fs::create_dir_all(directory).map_err(|e| format!("Failed to create {}: {}", directory.display(), e))?;
+        }
+        fs::write(&self.path, json).map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))?;
+        debug!("Saved window state {:?} to {}", state, self.path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::VideoMode;
+
+    #[test]
+    fn test_saved_state_round_trip_and_missing_file() {
+        let state = SavedWindowState {
+            position: (-1920, 40),
+            size: (1280, 720),
+            monitor: Some(1),
+            maximized: false,
+            fullscreen: FullscreenMode::Exclusive {
+                monitor: 1,
+                video_mode: VideoMode { width: 1920, height: 1080, refresh_rate: 144 },
+            },
+        };
+        let json = state.to_json().unwrap();
+        assert_eq!(SavedWindowState::from_json(&json).unwrap(), state);
+
+        let path = std::env::temp_dir().join(format!("artifice-window-state-{}", std::process::id())).join("window.json");
+        let persistence = WindowStatePersistence::new(&path);
+        assert_eq!(persistence.load(), Ok(None));
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, json).unwrap();
+        assert_eq!(persistence.load(), Ok(Some(state)));
+        fs::write(&path, "{").unwrap();
+        assert!(persistence.load().is_err());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
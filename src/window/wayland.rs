@@ -1,21 +1,26 @@
-use crate::events::core::{Event, EventData, KeyEvent, MouseMoveEvent, MouseButtonEvent, MouseScrollEvent, WindowResizeEvent, KeyAction, KeyCode, KeyMod, MouseButton};
-use crate::io::{Window, WindowHint, OpenGLWindow, Size, Position};
+use crate::events::core::{Event, EventData, KeyEvent, MouseMoveEvent, MouseRawMotionEvent, MouseButtonEvent, MouseScrollEvent, WindowResizeEvent, WindowCloseEvent, KeyAction, KeyCode, KeyMod, MouseButton};
+use crate::io::{Window, CursorIcon, CursorImage, CursorMode, IconImage, ImageBuffer, FullscreenMode, WindowHint, OpenGLWindow, Size, Position, constrain_size, opacity_fraction};
 use crate::window::factory::{WindowFactory, WindowFeature};
+use crate::input::hotplug::{scan_linux_joysticks, GamepadHotplug};
+use crate::window::keysym;
+use crate::window::xkb::Keymap;
 use artifice_logging::{debug, info, warn};
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
 use std::any::Any;
 
-use std::os::unix::io::BorrowedFd;
+use std::fs::File;
+use std::io::{Read, Write as _};
+use std::os::unix::fs::FileExt;
+use std::os::unix::io::{AsFd, FromRawFd, OwnedFd};
 
 // Wayland protocol imports
 use wayland_client::{
-    Connection, Dispatch, EventQueue, Proxy, QueueHandle, WEnum,
+    event_created_child, Connection, Dispatch, EventQueue, Proxy, QueueHandle, WEnum,
     protocol::{
         wl_compositor::WlCompositor,
+        wl_region::WlRegion,
         wl_surface::{self, WlSurface},
-        wl_shell::WlShell,
-        wl_shell_surface::{self, WlShellSurface},
         wl_seat::WlSeat,
         wl_pointer::{self, WlPointer},
         wl_keyboard::{self, WlKeyboard},
@@ -24,9 +29,80 @@ use wayland_client::{
         wl_buffer::{self, WlBuffer},
         wl_shm_pool::WlShmPool,
         wl_output::WlOutput,
+        wl_data_device::{self, WlDataDevice},
+        wl_data_device_manager::WlDataDeviceManager,
+        wl_data_offer::{self, WlDataOffer},
+        wl_data_source::{self, WlDataSource},
     },
     globals::{registry_queue_init, GlobalListContents},
 };
+use wayland_protocols::wp::alpha_modifier::v1::client::{
+    wp_alpha_modifier_surface_v1::WpAlphaModifierSurfaceV1,
+    wp_alpha_modifier_v1::WpAlphaModifierV1,
+};
+use wayland_protocols::wp::cursor_shape::v1::client::{
+    wp_cursor_shape_device_v1::{self, WpCursorShapeDeviceV1},
+    wp_cursor_shape_manager_v1::WpCursorShapeManagerV1,
+};
+use wayland_protocols::wp::pointer_constraints::zv1::client::{
+    zwp_confined_pointer_v1::{self, ZwpConfinedPointerV1},
+    zwp_locked_pointer_v1::{self, ZwpLockedPointerV1},
+    zwp_pointer_constraints_v1::{self, ZwpPointerConstraintsV1},
+};
+use wayland_protocols::wp::relative_pointer::zv1::client::{
+    zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1,
+    zwp_relative_pointer_v1::{self, ZwpRelativePointerV1},
+};
+use wayland_protocols::wp::text_input::zv3::client::{
+    zwp_text_input_manager_v3::ZwpTextInputManagerV3,
+    zwp_text_input_v3::{self, ZwpTextInputV3},
+};
+use wayland_protocols::xdg::decoration::zv1::client::{
+    zxdg_decoration_manager_v1::ZxdgDecorationManagerV1,
+    zxdg_toplevel_decoration_v1::{self, ZxdgToplevelDecorationV1},
+};
+use wayland_protocols::xdg::toplevel_icon::v1::client::{
+    xdg_toplevel_icon_manager_v1::{self, XdgToplevelIconManagerV1},
+    xdg_toplevel_icon_v1::XdgToplevelIconV1,
+};
+use wayland_protocols::xdg::shell::client::{
+    xdg_surface::{self, XdgSurface},
+    xdg_toplevel::{self, XdgToplevel},
+    xdg_wm_base::{self, XdgWmBase},
+};
+
+/// Toplevel state the compositor proposed, applied once its surface configure is acknowledged
+#[derive(Debug, Clone, Copy, Default)]
+struct ToplevelConfigure {
+    /// Zero in either dimension leaves the size to us
+    width: i32,
+    height: i32,
+    maximized: bool,
+    fullscreen: bool,
+}
+
+impl ToplevelConfigure {
+    fn new(width: i32, height: i32, states: &[u8]) -> Self {
+        // States arrive as an array of native-endian u32s
+        let states: Vec<_> = states
+            .chunks_exact(4)
+            .filter_map(|state| xdg_toplevel::State::try_from(u32::from_ne_bytes([state[0], state[1], state[2], state[3]])).ok())
+            .collect();
+        Self {
+            width,
+            height,
+            maximized: states.contains(&xdg_toplevel::State::Maximized),
+            fullscreen: states.contains(&xdg_toplevel::State::Fullscreen),
+        }
+    }
+}
+
+/// Cursor image on its own surface, ready to hand to the pointer
+struct CustomCursor {
+    surface: WlSurface,
+    _buffer: WlBuffer,
+    hotspot: (u32, u32),
+}
 
 /// Wayland window implementation
 pub struct WaylandWindow {
@@ -35,12 +111,75 @@ pub struct WaylandWindow {
     event_queue: EventQueue<WaylandState>,
     compositor: WlCompositor,
     surface: WlSurface,
-    shell: Option<WlShell>,
-    shell_surface: Option<WlShellSurface>,
+    /// Gives the surface its toplevel role; without xdg-shell the window is never mapped
+    wm_base: Option<XdgWmBase>,
+    xdg_surface: Option<XdgSurface>,
+    toplevel: Option<XdgToplevel>,
+    /// Buffers may only be attached once the first configure is acknowledged
+    configured: bool,
+    pending_configure: ToplevelConfigure,
+    maximized: bool,
+    /// Whether the compositor shows the window fullscreen
+    fullscreen: bool,
+    fullscreen_mode: FullscreenMode,
+    /// Size before the window left windowed mode, for compositors that leave it to us
+    windowed_size: Size,
+    size_limits: (Option<Size>, Option<Size>),
+    /// xdg-shell has no aspect hint, so configure enforces this itself
+    aspect_ratio: Option<(u32, u32)>,
+    /// Optional protocol; without it the compositor picks the icon from the app id
+    toplevel_icon_manager: Option<XdgToplevelIconManagerV1>,
+    /// Server-side decorations, on compositors that offer them; we draw none ourselves
+    decoration: Option<ZxdgToplevelDecorationV1>,
+    /// Whether the surface has a buffer attached, or gets one on the next swap
+    visible: bool,
+    /// Leave the surface's opaque region empty so the compositor blends its alpha
+    transparent: bool,
+    /// Optional protocol through which the compositor fades the whole surface
+    alpha_modifier: Option<WpAlphaModifierV1>,
+    /// Created on the first opacity change
+    alpha_modifier_surface: Option<WpAlphaModifierSurfaceV1>,
+    opacity: f32,
+    /// Current icon and its buffers, kept alive until replaced
+    icon: Option<(XdgToplevelIconV1, Vec<WlBuffer>)>,
     seat: Option<WlSeat>,
     pointer: Option<WlPointer>,
     keyboard: Option<WlKeyboard>,
     shm: Option<WlShm>,
+
+    // Cursor control; the protocols are optional and absent on some compositors
+    pointer_constraints: Option<ZwpPointerConstraintsV1>,
+    relative_pointer_manager: Option<ZwpRelativePointerManagerV1>,
+    locked_pointer: Option<ZwpLockedPointerV1>,
+    confined_pointer: Option<ZwpConfinedPointerV1>,
+    relative_pointer: Option<ZwpRelativePointerV1>,
+    cursor_mode: CursorMode,
+    cursor_icon: CursorIcon,
+    /// Sets standard cursor shapes; without it the compositor's default cursor shows
+    cursor_shape_device: Option<WpCursorShapeDeviceV1>,
+    /// Surfaces showing the images made by `create_cursor`
+    custom_cursors: HashMap<u32, CustomCursor>,
+    /// Serial of the latest pointer enter, needed to change the cursor image
+    pointer_serial: Option<u32>,
+
+    // Clipboard
+    data_device_manager: Option<WlDataDeviceManager>,
+    data_device: Option<WlDataDevice>,
+    /// Offer for the current selection, whoever owns it
+    selection_offer: Option<WlDataOffer>,
+    /// Source we set as the selection, with the text it serves
+    clipboard_source: Option<(WlDataSource, String)>,
+    /// Serial of the latest key or button event, needed to set the selection
+    input_serial: Option<u32>,
+
+    // Input method placement; the protocol is optional
+    text_input_manager: Option<ZwpTextInputManagerV3>,
+    /// Created on the first `set_ime_cursor_area`
+    text_input: Option<ZwpTextInputV3>,
+    /// Whether the compositor sent the text input an enter for our surface, and so takes its state
+    text_input_entered: bool,
+    /// Latest area set, sent again each time the text input is enabled
+    ime_cursor_area: Option<(Position, Size)>,
     
     // Window properties
     size: Size,
@@ -50,15 +189,20 @@ pub struct WaylandWindow {
     
     // Event handling
     event_callback: Option<Arc<Mutex<dyn FnMut(Event) + Send + 'static>>>,
+    gamepad_hotplug: GamepadHotplug,
     
     // State tracking
     mouse_x: f64,
     mouse_y: f64,
     keyboard_state: HashMap<u32, bool>,
     modifiers: KeyMod,
+    /// Layout sent by the compositor; keys translate by position until it arrives
+    keymap: Option<Keymap>,
     
     // Buffer management
     buffer: Option<WlBuffer>,
+    /// Shared memory behind `buffer`, kept to read the pixels back
+    buffer_file: Option<File>,
     buffer_data: Vec<u8>,
 }
 
@@ -88,7 +232,7 @@ impl WaylandWindow {
         Self::with_hints(width, height, title, &[])
     }
 
-    pub fn with_hints(width: u32, height: u32, title: &str, _hints: &[WindowHint]) -> Self {
+    pub fn with_hints(width: u32, height: u32, title: &str, hints: &[WindowHint]) -> Self {
         info!("Creating Wayland window: {} ({}x{})", title, width, height);
 
         // Connect to Wayland compositor
@@ -104,8 +248,8 @@ impl WaylandWindow {
             .bind(&event_queue.handle(), 1..=4, ())
             .expect("Failed to bind compositor");
 
-        let shell: Option<WlShell> = globals
-            .bind(&event_queue.handle(), 1..=1, ())
+        let wm_base: Option<XdgWmBase> = globals
+            .bind(&event_queue.handle(), 1..=6, ())
             .ok();
 
         let seat: Option<WlSeat> = globals
@@ -116,6 +260,38 @@ impl WaylandWindow {
             .bind(&event_queue.handle(), 1..=1, ())
             .ok();
 
+        let pointer_constraints: Option<ZwpPointerConstraintsV1> = globals
+            .bind(&event_queue.handle(), 1..=1, ())
+            .ok();
+
+        let relative_pointer_manager: Option<ZwpRelativePointerManagerV1> = globals
+            .bind(&event_queue.handle(), 1..=1, ())
+            .ok();
+
+        let data_device_manager: Option<WlDataDeviceManager> = globals
+            .bind(&event_queue.handle(), 1..=3, ())
+            .ok();
+
+        let cursor_shape_manager: Option<WpCursorShapeManagerV1> = globals
+            .bind(&event_queue.handle(), 1..=1, ())
+            .ok();
+
+        let toplevel_icon_manager: Option<XdgToplevelIconManagerV1> = globals
+            .bind(&event_queue.handle(), 1..=1, ())
+            .ok();
+
+        let decoration_manager: Option<ZxdgDecorationManagerV1> = globals
+            .bind(&event_queue.handle(), 1..=1, ())
+            .ok();
+
+        let alpha_modifier: Option<WpAlphaModifierV1> = globals
+            .bind(&event_queue.handle(), 1..=1, ())
+            .ok();
+
+        let text_input_manager: Option<ZwpTextInputManagerV3> = globals
+            .bind(&event_queue.handle(), 1..=1, ())
+            .ok();
+
         // Create surface
         let surface = compositor.create_surface(&event_queue.handle(), ());
 
@@ -124,31 +300,105 @@ impl WaylandWindow {
             event_queue,
             compositor,
             surface: surface.clone(),
-            shell: shell.clone(),
-            shell_surface: None,
+            wm_base: wm_base.clone(),
+            xdg_surface: None,
+            toplevel: None,
+            configured: false,
+            pending_configure: ToplevelConfigure::default(),
+            maximized: false,
+            fullscreen: false,
+            fullscreen_mode: FullscreenMode::Windowed,
+            windowed_size: Size(width, height),
+            size_limits: (None, None),
+            aspect_ratio: None,
+            toplevel_icon_manager,
+            icon: None,
+            decoration: None,
+            visible: true,
+            transparent: false,
+            alpha_modifier,
+            alpha_modifier_surface: None,
+            opacity: 1.0,
             seat: seat.clone(),
             pointer: None,
             keyboard: None,
             shm,
+            pointer_constraints,
+            relative_pointer_manager,
+            locked_pointer: None,
+            confined_pointer: None,
+            relative_pointer: None,
+            cursor_mode: CursorMode::Normal,
+            cursor_icon: CursorIcon::Arrow,
+            cursor_shape_device: None,
+            custom_cursors: HashMap::new(),
+            pointer_serial: None,
+            data_device_manager,
+            data_device: None,
+            selection_offer: None,
+            clipboard_source: None,
+            input_serial: None,
+            text_input_manager,
+            text_input: None,
+            text_input_entered: false,
+            ime_cursor_area: None,
             size: Size(width, height),
             position: Position(0, 0),
             title: title.to_string(),
             should_close: false,
             event_callback: None,
+            gamepad_hotplug: GamepadHotplug::default(),
             mouse_x: 0.0,
             mouse_y: 0.0,
             keyboard_state: HashMap::new(),
             modifiers: KeyMod::default(),
+            keymap: None,
             buffer: None,
+            buffer_file: None,
             buffer_data: Vec::new(),
         };
 
-        // Set up shell surface if shell is available
-        if let Some(ref shell) = shell {
-            let shell_surface = shell.get_shell_surface(&surface, &window.event_queue.handle(), ());
-            shell_surface.set_title(title.to_string());
-            shell_surface.set_toplevel();
-            window.shell_surface = Some(shell_surface);
+        // Give the surface its toplevel role
+        match wm_base {
+            Some(ref wm_base) => {
+                let xdg_surface = wm_base.get_xdg_surface(&surface, &window.event_queue.handle(), ());
+                let toplevel = xdg_surface.get_toplevel(&window.event_queue.handle(), ());
+                toplevel.set_title(title.to_string());
+                let mut decorated = true;
+                for hint in hints {
+                    match hint {
+                        WindowHint::Maximized(true) => toplevel.set_maximized(),
+                        WindowHint::Resizable(false) => {
+                            window.size_limits = (Some(Size(width, height)), Some(Size(width, height)));
+                        }
+                        WindowHint::Decorated(value) => decorated = *value,
+                        WindowHint::Visible(value) => window.visible = *value,
+                        WindowHint::Transparent(value) => window.transparent = *value,
+                        WindowHint::AppId(app_id) => toplevel.set_app_id(app_id.clone()),
+                        // Stacking and focus are the compositor's call on Wayland
+                        WindowHint::Floating(true) => warn!("Wayland windows cannot keep themselves above others"),
+                        WindowHint::Focused(false) => debug!("Leaving initial focus of the Wayland window to the compositor"),
+                        _ => {}
+                    }
+                }
+                match decoration_manager {
+                    Some(ref manager) => {
+                        let decoration = manager.get_toplevel_decoration(&toplevel, &window.event_queue.handle(), ());
+                        // Client-side means none at all, since this backend draws no frame
+                        decoration.set_mode(match decorated {
+                            true => zxdg_toplevel_decoration_v1::Mode::ServerSide,
+                            false => zxdg_toplevel_decoration_v1::Mode::ClientSide,
+                        });
+                        window.decoration = Some(decoration);
+                    }
+                    None if decorated => debug!("Compositor lacks xdg-decoration, the Wayland window has no frame"),
+                    None => {}
+                }
+                window.xdg_surface = Some(xdg_surface);
+                window.toplevel = Some(toplevel);
+                window.send_size_limits();
+            }
+            None => warn!("Compositor lacks xdg-shell, the Wayland window cannot be shown"),
         }
 
         // Set up input devices
@@ -156,6 +406,10 @@ impl WaylandWindow {
             // Get pointer
             if seat.version() >= 3 {
                 let pointer = seat.get_pointer(&window.event_queue.handle(), ());
+                if let Some(ref manager) = cursor_shape_manager {
+                    let device = manager.get_pointer(&pointer, &window.event_queue.handle(), ());
+                    window.cursor_shape_device = Some(device);
+                }
                 window.pointer = Some(pointer);
             }
 
@@ -164,14 +418,102 @@ impl WaylandWindow {
                 let keyboard = seat.get_keyboard(&window.event_queue.handle(), ());
                 window.keyboard = Some(keyboard);
             }
+
+            // Get data device for the clipboard
+            if let Some(ref manager) = window.data_device_manager {
+                let data_device = manager.get_data_device(seat, &window.event_queue.handle(), ());
+                window.data_device = Some(data_device);
+            }
         }
 
         // Initialize buffer
         window.create_buffer(width, height);
+        window.update_opaque_region();
+
+        // The first commit without a buffer asks for the initial configure
+        window.surface.commit();
+        let mut state = WaylandState::new(&mut window);
+        if let Err(e) = window.event_queue.roundtrip(&mut state) {
+            warn!("Failed to receive the initial Wayland configure: {}", e);
+        }
 
         window
     }
 
+    /// Whether the compositor agreed to a fullscreen request
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    /// Shared memory buffer holding `pixels`, premultiplied ARGB words row by row
+    fn image_buffer(&self, width: u32, height: u32, pixels: &[u32]) -> Result<WlBuffer, String> {
+        let Some(ref shm) = self.shm else {
+            return Err("Compositor offers no shared memory for images".to_string());
+        };
+        let bytes: Vec<u8> = pixels.iter().flat_map(|pixel| pixel.to_le_bytes()).collect();
+
+        let fd = create_anonymous_file(bytes.len()).map_err(|e| format!("Failed to create image buffer: {}", e))?;
+        let mut file = unsafe { File::from_raw_fd(fd) };
+        file.write_all(&bytes).map_err(|e| format!("Failed to fill image buffer: {}", e))?;
+
+        // The compositor maps the file itself, so ours closes when done here
+        let qh = self.event_queue.handle();
+        let pool = shm.create_pool(file.as_fd(), bytes.len() as i32, &qh, ());
+        let buffer = pool.create_buffer(0, width as i32, height as i32, width as i32 * 4, wl_shm::Format::Argb8888, &qh, ());
+        pool.destroy();
+        Ok(buffer)
+    }
+
+    /// Mark the whole surface opaque unless the window is transparent, sparing the compositor the blending
+    fn update_opaque_region(&self) {
+        if self.transparent {
+            self.surface.set_opaque_region(None);
+            return;
+        }
+        let region = self.compositor.create_region(&self.event_queue.handle(), ());
+        region.add(0, 0, self.size.0 as i32, self.size.1 as i32);
+        self.surface.set_opaque_region(Some(&region));
+        region.destroy();
+    }
+
+    /// Pass the size limits to the compositor; 0 leaves a bound open
+    fn send_size_limits(&self) {
+        let Some(ref toplevel) = self.toplevel else { return };
+        let (min, max) = self.size_limits;
+        let (min, max) = (min.map_or((0, 0), |min| min.size()), max.map_or((0, 0), |max| max.size()));
+        toplevel.set_min_size(min.0 as i32, min.1 as i32);
+        toplevel.set_max_size(max.0 as i32, max.1 as i32);
+        self.surface.commit();
+    }
+
+    /// Apply the toplevel state the compositor proposed, which `serial` acknowledges
+    fn configure(&mut self, serial: u32) {
+        if let Some(ref xdg_surface) = self.xdg_surface {
+            xdg_surface.ack_configure(serial);
+        }
+        self.configured = true;
+
+        let configure = self.pending_configure;
+        if configure.maximized != self.maximized || configure.fullscreen != self.fullscreen {
+            debug!("Wayland window maximized: {}, fullscreen: {}", configure.maximized, configure.fullscreen);
+        }
+        let left_fullscreen = self.fullscreen && !configure.fullscreen;
+        self.maximized = configure.maximized;
+        self.fullscreen = configure.fullscreen;
+
+        let mut size = Size(configure.width as u32, configure.height as u32);
+        // A maximized or fullscreen window has to fill the space it was given
+        if !self.maximized && !self.fullscreen {
+            let (min, max) = self.size_limits;
+            size = constrain_size(size, min, max, self.aspect_ratio);
+        }
+        if configure.width > 0 && configure.height > 0 && size.size() != self.size.size() {
+            self.set_size(size);
+        } else if (configure.width == 0 || configure.height == 0) && left_fullscreen {
+            self.set_size(self.windowed_size);
+        }
+    }
+
 
 
     fn create_buffer(&mut self, width: u32, height: u32) {
@@ -184,8 +526,8 @@ impl WaylandWindow {
                 .expect("Failed to create shared memory file");
             
             // Create shm pool
-            let borrowed_fd = unsafe { BorrowedFd::borrow_raw(fd) };
-            let pool = shm.create_pool(borrowed_fd, size as i32, &self.event_queue.handle(), ());
+            let file = unsafe { File::from_raw_fd(fd) };
+            let pool = shm.create_pool(file.as_fd(), size as i32, &self.event_queue.handle(), ());
             
             // Create buffer
             let buffer = pool.create_buffer(
@@ -201,12 +543,157 @@ impl WaylandWindow {
             // Initialize buffer data
             self.buffer_data = vec![0xFF; size as usize]; // White background
             self.buffer = Some(buffer);
+            self.buffer_file = Some(file);
             
             // Clean up pool
             pool.destroy();
         }
     }
 
+    /// Send the IME cursor area to the compositor, enabling the text input first after an enter
+    fn send_ime_cursor_area(&self, enable: bool) {
+        let (Some(text_input), Some((position, size))) = (&self.text_input, self.ime_cursor_area) else { return };
+        if !self.text_input_entered {
+            return;
+        }
+        if enable {
+            text_input.enable();
+        }
+        let clamp = |value: u32| i32::try_from(value).unwrap_or(i32::MAX);
+        text_input.set_cursor_rectangle(position.0, position.1, clamp(size.0), clamp(size.1));
+        text_input.commit();
+    }
+
+    /// Show the cursor icon, or nothing unless the mode is normal; needs a pointer enter serial
+    fn apply_cursor_image(&self) {
+        let (Some(pointer), Some(serial)) = (&self.pointer, self.pointer_serial) else { return };
+        if !self.cursor_mode.shows_cursor() {
+            pointer.set_cursor(serial, None, 0, 0);
+            return;
+        }
+        if let Some(cursor) = self.custom_cursor() {
+            pointer.set_cursor(serial, Some(&cursor.surface), cursor.hotspot.0 as i32, cursor.hotspot.1 as i32);
+            return;
+        }
+        if let Some(device) = &self.cursor_shape_device {
+            use wp_cursor_shape_device_v1::Shape;
+            let shape = match self.cursor_icon {
+                CursorIcon::Arrow => Shape::Default,
+                CursorIcon::IBeam => Shape::Text,
+                CursorIcon::Hand => Shape::Pointer,
+                CursorIcon::Crosshair => Shape::Crosshair,
+                CursorIcon::ResizeHorizontal => Shape::EwResize,
+                CursorIcon::ResizeVertical => Shape::NsResize,
+                CursorIcon::ResizeNwSe => Shape::NwseResize,
+                CursorIcon::ResizeNeSw => Shape::NeswResize,
+                CursorIcon::ResizeAll => Shape::Move,
+                CursorIcon::NotAllowed => Shape::NotAllowed,
+                CursorIcon::Wait => Shape::Wait,
+                CursorIcon::Custom(_) => Shape::Default,
+            };
+            device.set_shape(serial, shape);
+        }
+    }
+
+    /// Surface of the current icon, if it is a known custom cursor
+    fn custom_cursor(&self) -> Option<&CustomCursor> {
+        match self.cursor_icon {
+            CursorIcon::Custom(id) => self.custom_cursors.get(&id),
+            _ => None,
+        }
+    }
+
+    /// Lock the pointer in place and start receiving relative motion
+    fn capture_pointer(&mut self) {
+        let Some(pointer) = self.pointer.clone() else {
+            warn!("Cannot capture the cursor: the Wayland seat has no pointer");
+            return;
+        };
+        let qh = self.event_queue.handle();
+
+        match &self.pointer_constraints {
+            Some(constraints) => {
+                let locked = constraints.lock_pointer(
+                    &self.surface,
+                    &pointer,
+                    None,
+                    zwp_pointer_constraints_v1::Lifetime::Persistent,
+                    &qh,
+                    (),
+                );
+                self.locked_pointer = Some(locked);
+            }
+            None => warn!("Compositor lacks pointer-constraints, the cursor can leave the window"),
+        }
+        match &self.relative_pointer_manager {
+            Some(manager) => self.relative_pointer = Some(manager.get_relative_pointer(&pointer, &qh, ())),
+            None => warn!("Compositor lacks relative-pointer, no raw mouse motion will be reported"),
+        }
+    }
+
+    /// Keep the pointer inside the surface while it moves normally
+    fn confine_pointer(&mut self) {
+        let Some(pointer) = self.pointer.clone() else {
+            warn!("Cannot confine the cursor: the Wayland seat has no pointer");
+            return;
+        };
+        let Some(constraints) = &self.pointer_constraints else {
+            warn!("Compositor lacks pointer-constraints, the cursor can leave the window");
+            return;
+        };
+        let qh = self.event_queue.handle();
+        let confined = constraints.confine_pointer(
+            &self.surface,
+            &pointer,
+            None,
+            zwp_pointer_constraints_v1::Lifetime::Persistent,
+            &qh,
+            (),
+        );
+        self.confined_pointer = Some(confined);
+    }
+
+    fn release_pointer(&mut self) {
+        if let Some(locked) = self.locked_pointer.take() {
+            locked.destroy();
+        }
+        if let Some(confined) = self.confined_pointer.take() {
+            confined.destroy();
+        }
+        if let Some(relative) = self.relative_pointer.take() {
+            relative.destroy();
+        }
+    }
+
+    /// Read the selection offer in `mime_type` through a pipe
+    ///
+    /// Blocks until the owning client has written everything.
+    fn receive_offer(&self, offer: &WlDataOffer, mime_type: &str) -> Option<String> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+            warn!("Failed to create clipboard pipe: {}", std::io::Error::last_os_error());
+            return None;
+        }
+        let (mut reader, writer) = unsafe { (File::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+
+        offer.receive(mime_type.to_string(), writer.as_fd());
+        if let Err(e) = self.connection.flush() {
+            warn!("Failed to request clipboard contents: {}", e);
+            return None;
+        }
+        // Our copy of the write end must close for the read to end
+        drop(writer);
+
+        let mut bytes = Vec::new();
+        match reader.read_to_end(&mut bytes) {
+            Ok(_) => Some(String::from_utf8_lossy(&bytes).into_owned()),
+            Err(e) => {
+                warn!("Failed to read clipboard contents: {}", e);
+                None
+            }
+        }
+    }
+
     fn send_event(&mut self, event: Event) {
         if let Some(ref callback) = self.event_callback {
             if let Ok(mut cb) = callback.lock() {
@@ -215,6 +702,17 @@ impl WaylandWindow {
         }
     }
 
+    /// Translate an evdev key code by the symbol it types in the current layout
+    ///
+    /// Keys whose symbol has no key code fall back to their position.
+    fn translate_key(&self, key: u32) -> KeyCode {
+        // XKB keycodes are evdev codes offset by 8
+        self.keymap
+            .as_ref()
+            .and_then(|keymap| keysym::keysym_to_keycode(keymap.keysym(key + 8)))
+            .unwrap_or_else(|| Self::map_wayland_key_to_keycode(key))
+    }
+
     fn map_wayland_key_to_keycode(key: u32) -> KeyCode {
         // Basic key mapping - would need to be expanded for full support
         match key {
@@ -288,6 +786,15 @@ impl Window for WaylandWindow {
         if let Err(e) = self.event_queue.dispatch_pending(&mut state) {
             warn!("Failed to dispatch Wayland events: {}", e);
         }
+
+        // Wayland has no joystick protocol, so watch the kernel's joystick devices
+        for event in self.gamepad_hotplug.poll(scan_linux_joysticks) {
+            if let Some(ref callback) = self.event_callback {
+                if let Ok(mut cb) = callback.lock() {
+                    cb(event);
+                }
+            }
+        }
     }
 
     fn set_should_close(&mut self) {
@@ -311,6 +818,7 @@ impl Window for WaylandWindow {
     fn set_size(&mut self, size: Size) {
         self.size = size;
         self.create_buffer(size.0, size.1);
+        self.update_opaque_region();
         
         // Send resize event
         let event = Event::new(EventData::WindowResize(WindowResizeEvent {
@@ -335,8 +843,120 @@ impl Window for WaylandWindow {
 
     fn set_title(&mut self, title: &str) {
         self.title = title.to_string();
-        if let Some(ref shell_surface) = self.shell_surface {
-            shell_surface.set_title(title.to_string());
+        if let Some(ref toplevel) = self.toplevel {
+            toplevel.set_title(title.to_string());
+        }
+    }
+
+    fn set_app_id(&mut self, app_id: &str) {
+        debug!("Setting Wayland app ID to '{}'", app_id);
+        if let Some(ref toplevel) = self.toplevel {
+            toplevel.set_app_id(app_id.to_string());
+        }
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        if visible == self.visible {
+            return;
+        }
+        debug!("{} Wayland window", if visible { "Showing" } else { "Hiding" });
+        self.visible = visible;
+        if visible {
+            // Mapping again starts over with a commit without buffer and a new configure
+            self.surface.commit();
+        } else {
+            self.surface.attach(None, 0, 0);
+            self.surface.commit();
+            self.configured = false;
+        }
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn capture_framebuffer(&mut self) -> Result<ImageBuffer, String> {
+        let file = self.buffer_file.as_ref().ok_or("Wayland window has no buffer to capture")?;
+        let (width, height) = self.size.size();
+        let mut argb = vec![0u8; width as usize * height as usize * 4];
+        file.read_exact_at(&mut argb, 0).map_err(|e| format!("Failed to read the Wayland buffer: {}", e))?;
+
+        // Premultiplied ARGB words in little-endian order, i.e. BGRA bytes
+        let unpremultiply = |channel: u8, alpha: u8| match alpha {
+            0 => 0,
+            alpha => ((channel as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8,
+        };
+        let rgba = argb
+            .chunks_exact(4)
+            .flat_map(|pixel| {
+                let [b, g, r, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+                [unpremultiply(r, a), unpremultiply(g, a), unpremultiply(b, a), a]
+            })
+            .collect();
+        ImageBuffer::new(width, height, rgba)
+    }
+
+    fn set_opacity(&mut self, opacity: f32) {
+        let Some(ref alpha_modifier) = self.alpha_modifier else {
+            warn!("Compositor lacks wp-alpha-modifier, the Wayland window stays opaque");
+            return;
+        };
+        let opacity = opacity.clamp(0.0, 1.0);
+        debug!("Setting Wayland window opacity to {}", opacity);
+        let qh = self.event_queue.handle();
+        let modifier = self
+            .alpha_modifier_surface
+            .get_or_insert_with(|| alpha_modifier.get_surface(&self.surface, &qh, ()));
+        // The factor takes effect with the next commit
+        modifier.set_multiplier(opacity_fraction(opacity));
+        self.surface.commit();
+        self.opacity = opacity;
+    }
+
+    fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    fn set_icon(&mut self, images: &[IconImage]) {
+        let (Some(manager), Some(toplevel)) = (&self.toplevel_icon_manager, &self.toplevel) else {
+            warn!("Compositor lacks xdg-toplevel-icon, keeping the default window icon");
+            return;
+        };
+        debug!("Setting Wayland window icon from {} images", images.len());
+
+        let icon = if images.is_empty() {
+            None
+        } else {
+            let icon = manager.create_icon(&self.event_queue.handle(), ());
+            let mut buffers = Vec::new();
+            for image in images {
+                // Anything else is a protocol error
+                if image.width() != image.height() {
+                    warn!("Skipping {}x{} window icon, Wayland icons must be square", image.width(), image.height());
+                    continue;
+                }
+                match self.image_buffer(image.width(), image.height(), &image.premultiplied_argb()) {
+                    Ok(buffer) => {
+                        icon.add_buffer(&buffer, 1);
+                        buffers.push(buffer);
+                    }
+                    Err(e) => warn!("Skipping {}x{} window icon: {}", image.width(), image.height(), e),
+                }
+            }
+            if buffers.is_empty() {
+                icon.destroy();
+                warn!("None of the {} images can be a Wayland icon, keeping the current one", images.len());
+                return;
+            }
+            Some((icon, buffers))
+        };
+        manager.set_icon(toplevel, icon.as_ref().map(|(icon, _)| icon));
+        // The icon is toplevel state, applied with the next commit
+        self.surface.commit();
+
+        if let Some((old_icon, old_buffers)) = std::mem::replace(&mut self.icon, icon) {
+            old_icon.destroy();
+            old_buffers.iter().for_each(WlBuffer::destroy);
         }
     }
 
@@ -348,11 +968,217 @@ impl Window for WaylandWindow {
         self.event_callback = Some(callback);
     }
 
+    fn set_cursor_mode(&mut self, mode: CursorMode) {
+        debug!("Setting Wayland cursor mode to {:?}", mode);
+        self.release_pointer();
+
+        self.cursor_mode = mode;
+        match mode {
+            CursorMode::Normal if self.cursor_shape_device.is_none() && self.custom_cursor().is_none() => {
+                // Without cursor-shape there is no image to put back; the
+                // compositor restores its own once the pointer re-enters
+                debug!("Wayland cursor image returns when the pointer re-enters the surface");
+            }
+            CursorMode::Normal | CursorMode::Hidden => self.apply_cursor_image(),
+            CursorMode::Captured => {
+                self.apply_cursor_image();
+                self.capture_pointer();
+            }
+            CursorMode::Confined => {
+                self.apply_cursor_image();
+                self.confine_pointer();
+            }
+        }
+        self.surface.commit();
+    }
+
+    fn cursor_mode(&self) -> CursorMode {
+        self.cursor_mode
+    }
+
+    fn set_cursor(&mut self, icon: CursorIcon) {
+        debug!("Setting Wayland cursor to {:?}", icon);
+        match icon {
+            CursorIcon::Custom(id) if !self.custom_cursors.contains_key(&id) => {
+                warn!("Unknown custom cursor {}, showing the default cursor", id);
+            }
+            CursorIcon::Custom(_) => {}
+            _ if self.cursor_shape_device.is_none() => {
+                warn!("Compositor lacks cursor-shape, keeping its default cursor");
+            }
+            _ => {}
+        }
+        self.cursor_icon = icon;
+        self.apply_cursor_image();
+    }
+
+    fn cursor(&self) -> CursorIcon {
+        self.cursor_icon
+    }
+
+    fn set_fullscreen(&mut self, mode: FullscreenMode) {
+        if mode == self.fullscreen_mode {
+            return;
+        }
+        let Some(ref toplevel) = self.toplevel else {
+            warn!("Cannot change fullscreen mode without xdg-shell");
+            return;
+        };
+        debug!("Setting Wayland fullscreen mode to {:?}", mode);
+        match mode {
+            FullscreenMode::Windowed => toplevel.unset_fullscreen(),
+            FullscreenMode::Borderless => toplevel.set_fullscreen(None),
+            FullscreenMode::Exclusive { video_mode, .. } => {
+                // The compositor picks the output and scales the window to its mode
+                warn!("Wayland clients cannot switch video modes, showing {:?} fullscreen in the output's mode", video_mode);
+                toplevel.set_fullscreen(None);
+            }
+        }
+        // `is_fullscreen` follows once the compositor agrees
+        if self.fullscreen_mode == FullscreenMode::Windowed {
+            self.windowed_size = self.size;
+        }
+        self.fullscreen_mode = mode;
+    }
+
+    fn fullscreen(&self) -> FullscreenMode {
+        self.fullscreen_mode
+    }
+
+    fn set_size_limits(&mut self, min: Option<Size>, max: Option<Size>) {
+        debug!("Setting Wayland size limits to {:?}..{:?}", min, max);
+        self.size_limits = (min, max);
+        self.send_size_limits();
+    }
+
+    fn size_limits(&self) -> (Option<Size>, Option<Size>) {
+        self.size_limits
+    }
+
+    fn set_aspect_ratio(&mut self, ratio: Option<(u32, u32)>) {
+        debug!("Setting Wayland aspect ratio to {:?}", ratio);
+        self.aspect_ratio = ratio;
+        // Takes effect on the next resize
+    }
+
+    fn aspect_ratio(&self) -> Option<(u32, u32)> {
+        self.aspect_ratio
+    }
+
+    /// `is_maximized` changes once the compositor agrees
+    fn set_maximized(&mut self, maximized: bool) {
+        let Some(ref toplevel) = self.toplevel else { return };
+        debug!("Requesting Wayland window {}", if maximized { "maximized" } else { "restored" });
+        if maximized {
+            toplevel.set_maximized();
+        } else {
+            toplevel.unset_maximized();
+        }
+    }
+
+    fn is_maximized(&self) -> bool {
+        self.maximized
+    }
+
+    fn create_cursor(&mut self, image: &CursorImage, hotspot: (u32, u32)) -> Result<CursorIcon, String> {
+        image.check_hotspot(hotspot)?;
+        let (width, height) = (image.width() as i32, image.height() as i32);
+        let buffer = self.image_buffer(image.width(), image.height(), &image.premultiplied_argb())?;
+
+        let qh = self.event_queue.handle();
+        let surface = self.compositor.create_surface(&qh, ());
+        surface.attach(Some(&buffer), 0, 0);
+        surface.damage(0, 0, width, height);
+        surface.commit();
+
+        let id = self.custom_cursors.len() as u32;
+        self.custom_cursors.insert(id, CustomCursor { surface, _buffer: buffer, hotspot });
+        debug!("Created Wayland cursor {} from {}x{} image", id, width, height);
+        Ok(CursorIcon::Custom(id))
+    }
+
+    fn clipboard_text(&mut self) -> Option<String> {
+        // Reading our own source would wait on ourselves, so answer directly
+        if let Some((_, text)) = &self.clipboard_source {
+            return Some(text.clone());
+        }
+
+        let offer = self.selection_offer.as_ref()?;
+        let mime_types = offer.data::<Mutex<Vec<String>>>()?.lock().unwrap().clone();
+        let mime_type = text_mime_type(&mime_types)?;
+        self.receive_offer(offer, mime_type)
+    }
+
+    fn set_ime_cursor_area(&mut self, position: Position, size: Size) {
+        let (Some(manager), Some(seat)) = (&self.text_input_manager, &self.seat) else {
+            warn!("Compositor lacks text-input-v3, the IME places its window on its own");
+            return;
+        };
+        self.ime_cursor_area = Some((position, size));
+        if self.text_input.is_none() {
+            // The compositor answers with an enter once our surface has keyboard focus
+            debug!("Creating Wayland text input for IME placement");
+            self.text_input = Some(manager.get_text_input(seat, &self.event_queue.handle(), ()));
+            return;
+        }
+        self.send_ime_cursor_area(false);
+    }
+
+    fn key_names(&self) -> HashMap<KeyCode, String> {
+        let mut names = HashMap::new();
+        let Some(keymap) = self.keymap.as_ref() else { return names };
+        for keycode in keymap.keycodes().filter(|keycode| *keycode >= 8) {
+            let Some(character) = keysym::keysym_to_char(keymap.keysym(keycode)) else { continue };
+            let key = self.translate_key(keycode - 8);
+            if key != KeyCode::Unknown {
+                names.entry(key).or_insert_with(|| keysym::key_label(character));
+            }
+        }
+        names
+    }
+
+    fn set_clipboard_text(&mut self, text: &str) {
+        let (Some(manager), Some(device)) = (&self.data_device_manager, &self.data_device) else {
+            warn!("Compositor has no data device manager, cannot set the clipboard");
+            return;
+        };
+        // The compositor only lets a client that just had input take the selection
+        let Some(serial) = self.input_serial else {
+            warn!("Cannot set the Wayland clipboard before the window receives input");
+            return;
+        };
+
+        debug!("Taking Wayland clipboard with {} bytes", text.len());
+        let source = manager.create_data_source(&self.event_queue.handle(), ());
+        for mime_type in TEXT_MIME_TYPES {
+            source.offer(mime_type.to_string());
+        }
+        device.set_selection(Some(&source), serial);
+
+        if let Some((previous, _)) = self.clipboard_source.replace((source, text.to_string())) {
+            previous.destroy();
+        }
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
 }
 
+#[cfg(feature = "vulkan")]
+impl crate::io::VulkanWindow for WaylandWindow {
+    fn required_instance_extensions(&self) -> Vec<std::ffi::CString> {
+        vec![ash::khr::surface::NAME.to_owned(), ash::khr::wayland_surface::NAME.to_owned()]
+    }
+
+    fn create_surface(&self, entry: &ash::Entry, instance: &ash::Instance) -> ash::prelude::VkResult<ash::vk::SurfaceKHR> {
+        let info = ash::vk::WaylandSurfaceCreateInfoKHR::default()
+            .display(self.connection.backend().display_ptr().cast())
+            .surface(self.surface.id().as_ptr().cast());
+        unsafe { ash::khr::wayland_surface::Instance::new(entry, instance).create_wayland_surface(&info, None) }
+    }
+}
+
 impl OpenGLWindow for WaylandWindow {
     fn make_current(&mut self) {
         // Wayland OpenGL context management would require EGL integration
@@ -364,7 +1190,11 @@ impl OpenGLWindow for WaylandWindow {
     }
 
     fn swap_buffers(&mut self) {
-        // For a basic implementation, just attach the buffer to the surface
+        // For a basic implementation, just attach the buffer to the surface;
+        // attaching before the first configure is a protocol error
+        if !self.configured || !self.visible {
+            return;
+        }
         if let Some(ref buffer) = self.buffer {
             self.surface.attach(Some(buffer), 0, 0);
             self.surface.damage(0, 0, self.size.0 as i32, self.size.1 as i32);
@@ -405,16 +1235,19 @@ impl Dispatch<WlRegistry, GlobalListContents> for WaylandState {
     }
 }
 
-impl Dispatch<WlShell, ()> for WaylandState {
+impl Dispatch<XdgWmBase, ()> for WaylandState {
     fn event(
         _state: &mut Self,
-        _proxy: &WlShell,
-        _event: <WlShell as Proxy>::Event,
+        proxy: &XdgWmBase,
+        event: <XdgWmBase as Proxy>::Event,
         _data: &(),
         _conn: &Connection,
         _qhandle: &QueueHandle<WaylandState>,
     ) {
-        // Shell events
+        // Compositors treat clients that stop answering as hung
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            proxy.pong(serial);
+        }
     }
 }
 
@@ -431,6 +1264,74 @@ impl Dispatch<WlCompositor, ()> for WaylandState {
     }
 }
 
+impl Dispatch<WpAlphaModifierV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpAlphaModifierV1,
+        _event: <WpAlphaModifierV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Alpha modifier has no events
+    }
+}
+
+impl Dispatch<WpAlphaModifierSurfaceV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpAlphaModifierSurfaceV1,
+        _event: <WpAlphaModifierSurfaceV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Alpha modifier surfaces have no events
+    }
+}
+
+impl Dispatch<WlRegion, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlRegion,
+        _event: <WlRegion as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Regions have no events
+    }
+}
+
+impl Dispatch<ZxdgDecorationManagerV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZxdgDecorationManagerV1,
+        _event: <ZxdgDecorationManagerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Decoration manager has no events
+    }
+}
+
+impl Dispatch<ZxdgToplevelDecorationV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZxdgToplevelDecorationV1,
+        event: <ZxdgToplevelDecorationV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // The compositor may overrule the mode we asked for
+        if let zxdg_toplevel_decoration_v1::Event::Configure { mode } = event {
+            debug!("Wayland window decorations: {:?}", mode);
+        }
+    }
+}
+
 impl Dispatch<WlSurface, ()> for WaylandState {
     fn event(
         _state: &mut Self,
@@ -452,11 +1353,29 @@ impl Dispatch<WlSurface, ()> for WaylandState {
     }
 }
 
-impl Dispatch<WlShellSurface, ()> for WaylandState {
+impl Dispatch<XdgSurface, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _proxy: &XdgSurface,
+        event: <XdgSurface as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        unsafe {
+            let window = state.get_window();
+            if let xdg_surface::Event::Configure { serial } = event {
+                window.configure(serial);
+            }
+        }
+    }
+}
+
+impl Dispatch<XdgToplevel, ()> for WaylandState {
     fn event(
         state: &mut Self,
-        _proxy: &WlShellSurface,
-        event: <WlShellSurface as Proxy>::Event,
+        _proxy: &XdgToplevel,
+        event: <XdgToplevel as Proxy>::Event,
         _data: &(),
         _conn: &Connection,
         _qhandle: &QueueHandle<WaylandState>,
@@ -464,19 +1383,12 @@ impl Dispatch<WlShellSurface, ()> for WaylandState {
         unsafe {
             let window = state.get_window();
             match event {
-                wl_shell_surface::Event::Configure { edges: _, width, height } => {
-                    if width > 0 && height > 0 {
-                        window.set_size(Size(width as u32, height as u32));
-                    }
-                }
-                wl_shell_surface::Event::PopupDone => {
-                    // Handle popup done
+                xdg_toplevel::Event::Configure { width, height, states } => {
+                    window.pending_configure = ToplevelConfigure::new(width, height, &states);
                 }
-                wl_shell_surface::Event::Ping { serial } => {
-                    // Respond to ping
-                    if let Some(ref shell_surface) = window.shell_surface {
-                        shell_surface.pong(serial);
-                    }
+                xdg_toplevel::Event::Close => {
+                    window.should_close = true;
+                    window.send_event(Event::new(EventData::WindowClose(WindowCloseEvent)));
                 }
                 _ => {}
             }
@@ -509,9 +1421,13 @@ impl Dispatch<WlPointer, ()> for WaylandState {
         unsafe {
             let window = state.get_window();
             match event {
-                wl_pointer::Event::Enter { serial: _, surface: _, surface_x, surface_y } => {
+                wl_pointer::Event::Enter { serial, surface: _, surface_x, surface_y } => {
                     window.mouse_x = surface_x;
                     window.mouse_y = surface_y;
+
+                    // The cursor image is reset on every enter
+                    window.pointer_serial = Some(serial);
+                    window.apply_cursor_image();
                 }
                 wl_pointer::Event::Leave { serial: _, surface: _ } => {
                     // Mouse left the surface
@@ -526,7 +1442,8 @@ impl Dispatch<WlPointer, ()> for WaylandState {
                     }));
                     window.send_event(event);
                 }
-                wl_pointer::Event::Button { serial: _, time: _, button, state } => {
+                wl_pointer::Event::Button { serial, time: _, button, state } => {
+                    window.input_serial = Some(serial);
                     let mouse_button = WaylandWindow::map_wayland_mouse_button(button);
                     let action = match state {
                         WEnum::Value(wl_pointer::ButtonState::Pressed) => KeyAction::Press,
@@ -560,6 +1477,289 @@ impl Dispatch<WlPointer, ()> for WaylandState {
     }
 }
 
+impl Dispatch<XdgToplevelIconManagerV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &XdgToplevelIconManagerV1,
+        event: <XdgToplevelIconManagerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Preferred sizes are only a hint; every size we are given is passed on
+        if let xdg_toplevel_icon_manager_v1::Event::IconSize { size } = event {
+            debug!("Compositor prefers {}x{} window icons", size, size);
+        }
+    }
+}
+
+impl Dispatch<XdgToplevelIconV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &XdgToplevelIconV1,
+        _event: <XdgToplevelIconV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Toplevel icons have no events
+    }
+}
+
+impl Dispatch<WpCursorShapeManagerV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpCursorShapeManagerV1,
+        _event: <WpCursorShapeManagerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Cursor shape manager has no events
+    }
+}
+
+impl Dispatch<WpCursorShapeDeviceV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpCursorShapeDeviceV1,
+        _event: <WpCursorShapeDeviceV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Cursor shape devices have no events
+    }
+}
+
+impl Dispatch<ZwpTextInputManagerV3, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpTextInputManagerV3,
+        _event: <ZwpTextInputManagerV3 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Text input manager has no events
+    }
+}
+
+impl Dispatch<ZwpTextInputV3, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwpTextInputV3,
+        event: <ZwpTextInputV3 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        unsafe {
+            let window = state.get_window();
+            match event {
+                zwp_text_input_v3::Event::Enter { surface } if surface == window.surface => {
+                    // Every enter needs a fresh enable before the compositor takes our state
+                    window.text_input_entered = true;
+                    window.send_ime_cursor_area(true);
+                }
+                zwp_text_input_v3::Event::Leave { .. } => {
+                    window.text_input_entered = false;
+                    proxy.disable();
+                    proxy.commit();
+                }
+                // Composed text is dropped until the engine has text input events
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Dispatch<ZwpPointerConstraintsV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpPointerConstraintsV1,
+        _event: <ZwpPointerConstraintsV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Pointer constraints has no events
+    }
+}
+
+impl Dispatch<ZwpLockedPointerV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpLockedPointerV1,
+        event: <ZwpLockedPointerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        match event {
+            zwp_locked_pointer_v1::Event::Locked => {
+                debug!("Pointer locked");
+            }
+            zwp_locked_pointer_v1::Event::Unlocked => {
+                // A persistent lock comes back when the surface regains focus
+                debug!("Pointer unlocked");
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZwpConfinedPointerV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpConfinedPointerV1,
+        event: <ZwpConfinedPointerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        match event {
+            zwp_confined_pointer_v1::Event::Confined => {
+                debug!("Pointer confined");
+            }
+            zwp_confined_pointer_v1::Event::Unconfined => {
+                // A persistent confinement comes back when the surface regains focus
+                debug!("Pointer unconfined");
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZwpRelativePointerManagerV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpRelativePointerManagerV1,
+        _event: <ZwpRelativePointerManagerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Relative pointer manager has no events
+    }
+}
+
+impl Dispatch<ZwpRelativePointerV1, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _proxy: &ZwpRelativePointerV1,
+        event: <ZwpRelativePointerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        unsafe {
+            let window = state.get_window();
+            if let zwp_relative_pointer_v1::Event::RelativeMotion { dx_unaccel, dy_unaccel, .. } = event {
+                let event = Event::new(EventData::MouseRawMotion(MouseRawMotionEvent {
+                    delta_x: dx_unaccel,
+                    delta_y: dy_unaccel,
+                }));
+                window.send_event(event);
+            }
+        }
+    }
+}
+
+impl Dispatch<WlDataDeviceManager, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlDataDeviceManager,
+        _event: <WlDataDeviceManager as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Data device manager has no events
+    }
+}
+
+impl Dispatch<WlDataDevice, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _proxy: &WlDataDevice,
+        event: <WlDataDevice as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        unsafe {
+            let window = state.get_window();
+            match event {
+                wl_data_device::Event::Selection { id } => {
+                    // Replaces the previous selection, which the compositor no longer serves
+                    if let Some(previous) = std::mem::replace(&mut window.selection_offer, id) {
+                        previous.destroy();
+                    }
+                }
+                wl_data_device::Event::Enter { id: Some(offer), .. } => {
+                    // Drag and drop is not supported
+                    offer.destroy();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    event_created_child!(WaylandState, WlDataDevice, [
+        wl_data_device::EVT_DATA_OFFER_OPCODE => (WlDataOffer, Mutex::new(Vec::new())),
+    ]);
+}
+
+impl Dispatch<WlDataOffer, Mutex<Vec<String>>> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlDataOffer,
+        event: <WlDataOffer as Proxy>::Event,
+        data: &Mutex<Vec<String>>,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // Offers list their MIME types right after they are introduced
+        if let wl_data_offer::Event::Offer { mime_type } = event {
+            data.lock().unwrap().push(mime_type);
+        }
+    }
+}
+
+impl Dispatch<WlDataSource, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        proxy: &WlDataSource,
+        event: <WlDataSource as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        unsafe {
+            let window = state.get_window();
+            match event {
+                wl_data_source::Event::Send { mime_type, fd } => {
+                    if let Some((_, text)) = &window.clipboard_source {
+                        debug!("Sending clipboard as {}", mime_type);
+                        if let Err(e) = File::from(fd).write_all(text.as_bytes()) {
+                            warn!("Failed to send clipboard contents: {}", e);
+                        }
+                    }
+                }
+                wl_data_source::Event::Cancelled => {
+                    // Another client took the clipboard
+                    if window.clipboard_source.as_ref().is_some_and(|(source, _)| source == proxy) {
+                        window.clipboard_source = None;
+                    }
+                    proxy.destroy();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
 impl Dispatch<WlKeyboard, ()> for WaylandState {
     fn event(
         state: &mut Self,
@@ -572,14 +1772,26 @@ impl Dispatch<WlKeyboard, ()> for WaylandState {
         unsafe {
             let window = state.get_window();
             match event {
-                wl_keyboard::Event::Enter { serial: _, surface: _, keys: _ } => {
+                wl_keyboard::Event::Keymap { format, fd, size } => {
+                    if format != WEnum::Value(wl_keyboard::KeymapFormat::XkbV1) {
+                        warn!("Unsupported Wayland keymap format {:?}, keys will not follow the layout", format);
+                        return;
+                    }
+                    window.keymap = Keymap::from_fd(fd, size as usize);
+                    if window.keymap.is_none() {
+                        warn!("Failed to compile the Wayland keymap, keys will not follow the layout");
+                    }
+                }
+                wl_keyboard::Event::Enter { serial, surface: _, keys: _ } => {
                     // Keyboard focus gained
+                    window.input_serial = Some(serial);
                 }
                 wl_keyboard::Event::Leave { serial: _, surface: _ } => {
                     // Keyboard focus lost
                 }
-                wl_keyboard::Event::Key { serial: _, time: _, key, state } => {
-                    let keycode = WaylandWindow::map_wayland_key_to_keycode(key);
+                wl_keyboard::Event::Key { serial, time: _, key, state } => {
+                    window.input_serial = Some(serial);
+                    let keycode = window.translate_key(key);
                     let action = match state {
                         WEnum::Value(wl_keyboard::KeyState::Pressed) => {
                             window.keyboard_state.insert(key, true);
@@ -599,7 +1811,10 @@ impl Dispatch<WlKeyboard, ()> for WaylandState {
                     }));
                     window.send_event(event);
                 }
-                wl_keyboard::Event::Modifiers { serial: _, mods_depressed, mods_latched: _, mods_locked: _, group: _ } => {
+                wl_keyboard::Event::Modifiers { serial: _, mods_depressed, mods_latched, mods_locked, group } => {
+                    if let Some(keymap) = window.keymap.as_mut() {
+                        keymap.update_mask(mods_depressed, mods_latched, mods_locked, group);
+                    }
                     // Update modifier state
                     window.modifiers.shift = (mods_depressed & 0x01) != 0;
                     window.modifiers.control = (mods_depressed & 0x04) != 0;
@@ -669,6 +1884,14 @@ impl Dispatch<WlOutput, ()> for WaylandState {
     }
 }
 
+/// MIME types of plain text on the clipboard, most preferred first
+const TEXT_MIME_TYPES: [&str; 3] = ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain"];
+
+/// The most preferred text MIME type among those an offer advertises
+fn text_mime_type(offered: &[String]) -> Option<&'static str> {
+    TEXT_MIME_TYPES.iter().copied().find(|mime| offered.iter().any(|offered| offered == mime))
+}
+
 /// Wayland window factory
 pub struct WaylandWindowFactory;
 
@@ -684,7 +1907,7 @@ impl WindowFactory for WaylandWindowFactory {
     fn supports_feature(&self, feature: WindowFeature) -> bool {
         match feature {
             WindowFeature::OpenGL => false, // Would require EGL integration
-            WindowFeature::Vulkan => true,  // Wayland supports Vulkan
+            WindowFeature::Vulkan => cfg!(feature = "vulkan"),
             WindowFeature::MultiWindow => true,
             WindowFeature::HighDPI => true,
             WindowFeature::Fullscreen => true,
@@ -725,4 +1948,65 @@ fn create_anonymous_file(size: usize) -> Result<std::os::unix::io::RawFd, std::i
         
         Ok(fd)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offer(mime_types: &[&str]) -> Vec<String> {
+        mime_types.iter().map(|mime| mime.to_string()).collect()
+    }
+
+    #[test]
+    fn test_text_mime_type_prefers_utf8() {
+        let offered = offer(&["text/plain", "UTF8_STRING", "text/plain;charset=utf-8"]);
+        assert_eq!(text_mime_type(&offered), Some("text/plain;charset=utf-8"));
+
+        let offered = offer(&["text/html", "text/plain", "UTF8_STRING"]);
+        assert_eq!(text_mime_type(&offered), Some("UTF8_STRING"));
+
+        let offered = offer(&["text/html", "text/plain"]);
+        assert_eq!(text_mime_type(&offered), Some("text/plain"));
+    }
+
+    fn states(states: &[u32]) -> Vec<u8> {
+        states.iter().flat_map(|state| state.to_ne_bytes()).collect()
+    }
+
+    #[test]
+    fn test_toplevel_configure_reads_states() {
+        let configure = ToplevelConfigure::new(
+            1280,
+            720,
+            &states(&[xdg_toplevel::State::Activated as u32, xdg_toplevel::State::Maximized as u32]),
+        );
+        assert_eq!((configure.width, configure.height), (1280, 720));
+        assert!(configure.maximized);
+        assert!(!configure.fullscreen);
+
+        let configure = ToplevelConfigure::new(0, 0, &states(&[xdg_toplevel::State::Fullscreen as u32]));
+        assert_eq!((configure.width, configure.height), (0, 0));
+        assert!(configure.fullscreen);
+        assert!(!configure.maximized);
+    }
+
+    #[test]
+    fn test_toplevel_configure_skips_unknown_states() {
+        // An unknown state and a trailing partial value are both dropped
+        let mut bytes = states(&[9999, xdg_toplevel::State::Fullscreen as u32]);
+        bytes.extend_from_slice(&[xdg_toplevel::State::Maximized as u8, 0]);
+        let configure = ToplevelConfigure::new(800, 600, &bytes);
+        assert!(configure.fullscreen);
+        assert!(!configure.maximized);
+
+        let configure = ToplevelConfigure::new(800, 600, &[]);
+        assert!(!configure.fullscreen && !configure.maximized);
+    }
+
+    #[test]
+    fn test_text_mime_type_ignores_non_text_offers() {
+        assert_eq!(text_mime_type(&offer(&["image/png", "text/html"])), None);
+        assert_eq!(text_mime_type(&[]), None);
+    }
+}
@@ -1,18 +1,215 @@
-use crate::events::core::{Event, EventData, KeyEvent, MouseMoveEvent, MouseButtonEvent, MouseScrollEvent, WindowResizeEvent, WindowMoveEvent, WindowCloseEvent, KeyAction, KeyCode, KeyMod, MouseButton};
-use crate::io::{Window, WindowHint, OpenGLWindow, Size, Position, OpenGLProfile};
+use crate::events::core::{Event, EventData, KeyEvent, MouseMoveEvent, MouseRawMotionEvent, MouseButtonEvent, MouseScrollEvent, WindowResizeEvent, WindowMoveEvent, WindowCloseEvent, KeyAction, KeyCode, KeyMod, MouseButton};
+use crate::io::{Window, CursorIcon, CursorImage, CursorMode, IconImage, ImageBuffer, FullscreenMode, VideoMode, WindowHint, OpenGLWindow, Size, Position, OpenGLProfile, PresentStats, PresentTimer, opacity_fraction};
 use crate::window::factory::{WindowFactory, WindowFeature};
+use crate::input::hotplug::{scan_linux_joysticks, GamepadHotplug};
+use crate::window::keysym;
 use artifice_logging::{debug, info, warn, error};
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
-use std::ffi::{CString, CStr};
+use std::ffi::{CString, CStr, c_char, c_ulong};
 use std::ptr;
 use std::mem;
 use std::any::Any;
 
 // X11 and GLX bindings
+use x11::xcursor;
+use x11::xinput2;
+use x11::xrandr;
 use x11::xlib::{self, Display, Window as XWindow, XEvent, XSetWindowAttributes, XWindowAttributes};
 use x11::glx::{self, GLXContext, GLXFBConfig};
 
+/// Device ID of the core keyboard in XKB requests
+const XKB_USE_CORE_KBD: u32 = 0x0100;
+
+/// XRandR screen resources, freed on drop
+struct ScreenResources {
+    display: *mut Display,
+    ptr: *mut xrandr::XRRScreenResources,
+}
+
+impl ScreenResources {
+    fn current(display: *mut Display, root: XWindow) -> Option<Self> {
+        let ptr = unsafe { xrandr::XRRGetScreenResourcesCurrent(display, root) };
+        (!ptr.is_null()).then_some(Self { display, ptr })
+    }
+
+    /// Connected outputs showing a CRTC, with that CRTC, the primary output first
+    fn monitors(&self, root: XWindow) -> Vec<(xrandr::RROutput, xrandr::RRCrtc)> {
+        unsafe {
+            let primary = xrandr::XRRGetOutputPrimary(self.display, root);
+            let outputs = std::slice::from_raw_parts((*self.ptr).outputs, (*self.ptr).noutput as usize);
+            let mut monitors: Vec<_> = outputs
+                .iter()
+                .filter_map(|&output| {
+                    let info = xrandr::XRRGetOutputInfo(self.display, self.ptr, output);
+                    if info.is_null() {
+                        return None;
+                    }
+                    let connected = (*info).connection as i32 == xrandr::RR_Connected && (*info).crtc != 0;
+                    let monitor = connected.then_some((output, (*info).crtc));
+                    xrandr::XRRFreeOutputInfo(info);
+                    monitor
+                })
+                .collect();
+            monitors.sort_by_key(|&(output, _)| output != primary);
+            monitors
+        }
+    }
+
+    /// Modes `output` supports, with their XRandR ids
+    fn video_modes(&self, output: xrandr::RROutput) -> Vec<(xrandr::RRMode, VideoMode)> {
+        unsafe {
+            let info = xrandr::XRRGetOutputInfo(self.display, self.ptr, output);
+            if info.is_null() {
+                return Vec::new();
+            }
+            let ids = std::slice::from_raw_parts((*info).modes, (*info).nmode as usize);
+            let modes = std::slice::from_raw_parts((*self.ptr).modes, (*self.ptr).nmode as usize);
+            let video_modes = ids
+                .iter()
+                .filter_map(|id| modes.iter().find(|mode| mode.id == *id))
+                .map(|mode| (mode.id, VideoMode { width: mode.width, height: mode.height, refresh_rate: refresh_rate(mode) }))
+                .collect();
+            xrandr::XRRFreeOutputInfo(info);
+            video_modes
+        }
+    }
+}
+
+/// Refresh rate of an XRandR mode in Hz, rounded, or 0 if the mode has no timings
+fn refresh_rate(mode: &xrandr::XRRModeInfo) -> u32 {
+    let dots = mode.hTotal as u64 * mode.vTotal as u64;
+    if dots == 0 { 0 } else { ((mode.dotClock as u64 + dots / 2) / dots) as u32 }
+}
+
+/// Creation hints the window manager has to know about before the window is mapped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AppearanceHints {
+    resizable: bool,
+    decorated: bool,
+    visible: bool,
+    focused: bool,
+    floating: bool,
+    maximized: bool,
+    transparent: bool,
+}
+
+impl AppearanceHints {
+    /// Later hints override earlier ones; hints not given keep the usual window defaults
+    fn from_hints(hints: &[WindowHint]) -> Self {
+        let mut appearance = Self {
+            resizable: true,
+            decorated: true,
+            visible: true,
+            focused: true,
+            floating: false,
+            maximized: false,
+            transparent: false,
+        };
+        for hint in hints {
+            match hint {
+                WindowHint::Resizable(value) => appearance.resizable = *value,
+                WindowHint::Decorated(value) => appearance.decorated = *value,
+                WindowHint::Visible(value) => appearance.visible = *value,
+                WindowHint::Focused(value) => appearance.focused = *value,
+                WindowHint::Floating(value) => appearance.floating = *value,
+                WindowHint::Maximized(value) => appearance.maximized = *value,
+                WindowHint::Transparent(value) => appearance.transparent = *value,
+                _ => {}
+            }
+        }
+        appearance
+    }
+
+    /// Names of the `_NET_WM_STATE` atoms the window starts with
+    fn initial_wm_state(&self) -> Vec<&'static str> {
+        let mut states = Vec::new();
+        if self.floating {
+            states.push("_NET_WM_STATE_ABOVE");
+        }
+        if self.maximized {
+            states.extend(["_NET_WM_STATE_MAXIMIZED_VERT", "_NET_WM_STATE_MAXIMIZED_HORZ"]);
+        }
+        states
+    }
+
+    /// Size limits for a window created at `size`; a fixed-size window is pinned to it
+    fn size_limits(&self, size: Size) -> (Option<Size>, Option<Size>) {
+        match self.resizable {
+            true => (None, None),
+            false => (Some(size), Some(size)),
+        }
+    }
+}
+
+/// Glyph of the arrow in the X cursor font
+const XC_LEFT_PTR: u32 = 68;
+
+/// Glyph index from X11/cursorfont.h for a standard cursor, or `None` for custom cursors
+fn cursor_font_glyph(icon: CursorIcon) -> Option<u32> {
+    let glyph = match icon {
+        CursorIcon::Arrow => XC_LEFT_PTR,
+        CursorIcon::IBeam => 152,            // XC_xterm
+        CursorIcon::Hand => 60,              // XC_hand2
+        CursorIcon::Crosshair => 34,         // XC_crosshair
+        CursorIcon::ResizeHorizontal => 108, // XC_sb_h_double_arrow
+        CursorIcon::ResizeVertical => 116,   // XC_sb_v_double_arrow
+        CursorIcon::ResizeNwSe => 14,        // XC_bottom_right_corner
+        CursorIcon::ResizeNeSw => 12,        // XC_bottom_left_corner
+        CursorIcon::ResizeAll => 52,         // XC_fleur
+        CursorIcon::NotAllowed => 0,         // XC_X_cursor
+        CursorIcon::Wait => 150,             // XC_watch
+        CursorIcon::Custom(_) => return None,
+    };
+    Some(glyph)
+}
+
+/// Where over-the-spot preedit starts: the bottom left of the cursor area, clamped to X's 16-bit coordinates
+fn ime_spot_location(position: Position, size: Size) -> xlib::XPoint {
+    let clamp = |value: i64| value.clamp(i16::MIN as i64, i16::MAX as i64) as i16;
+    xlib::XPoint { x: clamp(position.0 as i64), y: clamp(position.1 as i64 + size.1 as i64) }
+}
+
+/// Set `WM_CLASS`, using `app_id` as both instance and class name
+fn set_class_hint(display: *mut Display, window: XWindow, app_id: &str) {
+    let Ok(name) = CString::new(app_id) else {
+        warn!("App ID {:?} contains a NUL byte, not setting WM_CLASS", app_id);
+        return;
+    };
+    let mut class_hint = xlib::XClassHint {
+        res_name: name.as_ptr() as *mut _,
+        res_class: name.as_ptr() as *mut _,
+    };
+    unsafe {
+        xlib::XSetClassHint(display, window, &mut class_hint);
+    }
+}
+
+impl Drop for ScreenResources {
+    fn drop(&mut self) {
+        unsafe { xrandr::XRRFreeScreenResources(self.ptr) }
+    }
+}
+
+/// Selection text too large for one property, handed to a requestor piece by piece with INCR
+struct IncrTransfer {
+    requestor: XWindow,
+    property: xlib::Atom,
+    target: xlib::Atom,
+    data: Vec<u8>,
+    /// Bytes already sent
+    sent: usize,
+}
+
+impl IncrTransfer {
+    /// The next piece of at most `max` bytes, empty once everything was sent
+    fn next_piece(&mut self, max: usize) -> &[u8] {
+        let start = self.sent;
+        self.sent = (start + max).min(self.data.len());
+        &self.data[start..self.sent]
+    }
+}
+
 /// X11 window implementation
 pub struct X11Window {
     // X11 core objects
@@ -33,15 +230,64 @@ pub struct X11Window {
     
     // Event handling
     event_callback: Option<Arc<Mutex<dyn FnMut(Event) + Send + 'static>>>,
+    gamepad_hotplug: GamepadHotplug,
     
     // State tracking
     key_map: HashMap<u32, KeyCode>,
     button_map: HashMap<u32, MouseButton>,
     modifiers: KeyMod,
+
+    // Cursor control
+    cursor_mode: CursorMode,
+    /// Major opcode of the XInput2 extension, when the server has it
+    xi_opcode: Option<i32>,
+    /// Blank cursor shown while hidden or captured, created on first use
+    invisible_cursor: xlib::Cursor,
+    cursor_icon: CursorIcon,
+    /// Font cursors of the icons shown so far, and the custom cursors
+    icon_cursors: HashMap<CursorIcon, xlib::Cursor>,
+    next_cursor_id: u32,
+
+    /// Text we offer while owning the CLIPBOARD selection
+    clipboard: Option<String>,
+    /// Text we offer while owning the PRIMARY selection
+    primary: Option<String>,
+    incr_transfers: Vec<IncrTransfer>,
+
+    /// Input method and its context, opened on the first `set_ime_cursor_area`
+    input_context: Option<(xlib::XIM, xlib::XIC)>,
+    /// Opening the input context failed; we warned once and do not retry
+    input_method_missing: bool,
+
+    // Fullscreen
+    fullscreen: FullscreenMode,
+    /// Where the window was before it left windowed mode
+    windowed_rect: (Position, Size),
+    /// CRTC switched for exclusive fullscreen and the mode it had before
+    replaced_video_mode: Option<(xrandr::RRCrtc, xrandr::RRMode)>,
+    size_limits: (Option<Size>, Option<Size>),
+    aspect_ratio: Option<(u32, u32)>,
+    visible: bool,
+    opacity: f32,
+    present_timer: PresentTimer,
     
     // Atoms for window management
     wm_delete_window: xlib::Atom,
     wm_protocols: xlib::Atom,
+    net_wm_state: xlib::Atom,
+    net_wm_state_fullscreen: xlib::Atom,
+    /// `_NET_WM_STATE_MAXIMIZED_VERT` and `_NET_WM_STATE_MAXIMIZED_HORZ`
+    net_wm_state_maximized: [xlib::Atom; 2],
+    net_wm_icon: xlib::Atom,
+    net_wm_window_opacity: xlib::Atom,
+
+    // Atoms for the clipboard
+    clipboard_atom: xlib::Atom,
+    utf8_string_atom: xlib::Atom,
+    targets_atom: xlib::Atom,
+    incr_atom: xlib::Atom,
+    /// Property on our window that selection owners write into
+    selection_property: xlib::Atom,
 }
 
 unsafe impl Send for X11Window {}
@@ -54,6 +300,11 @@ impl X11Window {
 
     pub fn with_hints(width: u32, height: u32, title: &str, hints: &[WindowHint]) -> Self {
         info!("Creating X11 window: {} ({}x{})", title, width, height);
+        Self::create(width, height, title, hints, ptr::null_mut())
+    }
+
+    /// Open the window with a GLX context that shares objects with `share_context`, if not null
+    fn create(width: u32, height: u32, title: &str, hints: &[WindowHint], share_context: GLXContext) -> Self {
 
         unsafe {
             // Open display connection
@@ -71,6 +322,8 @@ impl X11Window {
             let mut samples = 0;
             let mut double_buffer = true;
             let mut opengl_profile = OpenGLProfile::Core;
+            let appearance = AppearanceHints::from_hints(hints);
+            let mut app_id = None;
 
             for hint in hints {
                 match hint {
@@ -87,6 +340,7 @@ impl X11Window {
                     WindowHint::OpenGLProfile(profile) => {
                         opengl_profile = *profile;
                     }
+                    WindowHint::AppId(value) => app_id = Some(value.as_str()),
                     _ => {} // Other hints can be handled later
                 }
             }
@@ -131,7 +385,20 @@ impl X11Window {
                 panic!("Failed to find suitable GLX framebuffer config");
             }
 
-            let fb_config = *fb_configs;
+            // Per-pixel transparency needs a visual with an alpha channel, which X11 reports as depth 32
+            let configs = std::slice::from_raw_parts(fb_configs, fb_count as usize);
+            let argb_config = configs.iter().copied().filter(|_| appearance.transparent).find(|&config| {
+                let visual = glx::glXGetVisualFromFBConfig(display, config);
+                let argb = !visual.is_null() && (*visual).depth == 32;
+                if !visual.is_null() {
+                    xlib::XFree(visual as *mut _);
+                }
+                argb
+            });
+            if appearance.transparent && argb_config.is_none() {
+                warn!("No X11 visual with an alpha channel, the window will be opaque");
+            }
+            let fb_config = argb_config.unwrap_or(configs[0]);
 
             // Get visual info
             let visual_info = glx::glXGetVisualFromFBConfig(display, fb_config);
@@ -150,12 +417,15 @@ impl X11Window {
             // Set window attributes
             let mut swa = mem::zeroed::<XSetWindowAttributes>();
             swa.colormap = colormap;
+            // Required whenever the visual differs from the root window's, as ARGB ones do
+            swa.border_pixel = 0;
             swa.event_mask = xlib::ExposureMask 
                 | xlib::KeyPressMask | xlib::KeyReleaseMask
                 | xlib::ButtonPressMask | xlib::ButtonReleaseMask
                 | xlib::PointerMotionMask
                 | xlib::StructureNotifyMask
-                | xlib::FocusChangeMask;
+                | xlib::FocusChangeMask
+                | xlib::PropertyChangeMask;
 
             // Create window
             let window = xlib::XCreateWindow(
@@ -167,13 +437,16 @@ impl X11Window {
                 (*visual_info).depth,
                 xlib::InputOutput as u32,
                 (*visual_info).visual,
-                xlib::CWColormap | xlib::CWEventMask,
+                xlib::CWColormap | xlib::CWEventMask | xlib::CWBorderPixel,
                 &mut swa
             );
 
             // Set window title
             let c_title = CString::new(title).unwrap();
             xlib::XStoreName(display, window, c_title.as_ptr());
+            if let Some(app_id) = app_id {
+                set_class_hint(display, window, app_id);
+            }
 
             // Set up window manager protocols
             let wm_protocols_str = CString::new("WM_PROTOCOLS").unwrap();
@@ -185,6 +458,22 @@ impl X11Window {
             let mut protocols = [wm_delete_window];
             xlib::XSetWMProtocols(display, window, protocols.as_mut_ptr(), 1);
 
+            // Set up clipboard atoms
+            let intern = |name: &str| {
+                let name = CString::new(name).unwrap();
+                xlib::XInternAtom(display, name.as_ptr(), 0)
+            };
+            let clipboard_atom = intern("CLIPBOARD");
+            let utf8_string_atom = intern("UTF8_STRING");
+            let targets_atom = intern("TARGETS");
+            let incr_atom = intern("INCR");
+            let selection_property = intern("ARTIFICE_SELECTION");
+            let net_wm_state = intern("_NET_WM_STATE");
+            let net_wm_state_fullscreen = intern("_NET_WM_STATE_FULLSCREEN");
+            let net_wm_state_maximized = [intern("_NET_WM_STATE_MAXIMIZED_VERT"), intern("_NET_WM_STATE_MAXIMIZED_HORZ")];
+            let net_wm_icon = intern("_NET_WM_ICON");
+            let net_wm_window_opacity = intern("_NET_WM_WINDOW_OPACITY");
+
             // Create OpenGL context
             let context_attribs = match opengl_profile {
                 OpenGLProfile::Core => [
@@ -208,18 +497,65 @@ impl X11Window {
 
             // Try to create context with ARB extension first
             let glx_context = if let Some(create_context_attribs) = Self::get_glx_create_context_attribs_arb(display) {
-                create_context_attribs(display, fb_config, ptr::null_mut(), 1, context_attribs.as_ptr())
+                create_context_attribs(display, fb_config, share_context, 1, context_attribs.as_ptr())
             } else {
                 // Fallback to legacy context creation
-                glx::glXCreateNewContext(display, fb_config, glx::GLX_RGBA_TYPE, ptr::null_mut(), 1)
+                glx::glXCreateNewContext(display, fb_config, glx::GLX_RGBA_TYPE, share_context, 1)
             };
 
             if glx_context.is_null() {
                 panic!("Failed to create OpenGL context");
             }
 
+            // Initial window manager state has to be in place before the window is mapped
+            let initial_state: Vec<_> = appearance.initial_wm_state().into_iter().map(intern).collect();
+            if !initial_state.is_empty() {
+                xlib::XChangeProperty(
+                    display,
+                    window,
+                    net_wm_state,
+                    xlib::XA_ATOM,
+                    32,
+                    xlib::PropModeReplace,
+                    initial_state.as_ptr() as *const u8,
+                    initial_state.len() as i32,
+                );
+            }
+            if !appearance.decorated {
+                // Motif hints are the one decoration switch every window manager honors
+                const MWM_HINTS_DECORATIONS: c_ulong = 1 << 1;
+                let motif_hints: [c_ulong; 5] = [MWM_HINTS_DECORATIONS, 0, 0, 0, 0];
+                let motif_wm_hints = intern("_MOTIF_WM_HINTS");
+                xlib::XChangeProperty(
+                    display,
+                    window,
+                    motif_wm_hints,
+                    motif_wm_hints,
+                    32,
+                    xlib::PropModeReplace,
+                    motif_hints.as_ptr() as *const u8,
+                    motif_hints.len() as i32,
+                );
+            }
+            if !appearance.focused {
+                // A user time of 0 asks the window manager not to focus the window when mapped
+                let user_time: c_ulong = 0;
+                xlib::XChangeProperty(
+                    display,
+                    window,
+                    intern("_NET_WM_USER_TIME"),
+                    xlib::XA_CARDINAL,
+                    32,
+                    xlib::PropModeReplace,
+                    &user_time as *const c_ulong as *const u8,
+                    1,
+                );
+            }
+
             // Map the window
-            xlib::XMapWindow(display, window);
+            if appearance.visible {
+                xlib::XMapWindow(display, window);
+            }
 
             // Get initial window position
             let mut window_attrs = mem::zeroed::<XWindowAttributes>();
@@ -228,6 +564,9 @@ impl X11Window {
             // Free the framebuffer configs list
             xlib::XFree(fb_configs as *mut _);
 
+            // Raw motion comes from XInput2
+            let xi_opcode = Self::query_xinput2(display);
+
             let mut x11_window = X11Window {
                 display,
                 window,
@@ -240,13 +579,47 @@ impl X11Window {
                 title: title.to_string(),
                 should_close: false,
                 event_callback: None,
+                gamepad_hotplug: GamepadHotplug::default(),
                 key_map: Self::create_key_map(),
                 button_map: Self::create_button_map(),
                 modifiers: KeyMod::new(),
+                cursor_mode: CursorMode::Normal,
+                xi_opcode,
+                invisible_cursor: 0,
+                cursor_icon: CursorIcon::Arrow,
+                icon_cursors: HashMap::new(),
+                next_cursor_id: 0,
+                clipboard: None,
+                primary: None,
+                incr_transfers: Vec::new(),
+                input_context: None,
+                input_method_missing: false,
+                fullscreen: FullscreenMode::Windowed,
+                windowed_rect: (Position::from((window_attrs.x, window_attrs.y)), Size::from((width, height))),
+                replaced_video_mode: None,
+                size_limits: appearance.size_limits(Size(width, height)),
+                aspect_ratio: None,
+                visible: appearance.visible,
+                opacity: 1.0,
+                present_timer: PresentTimer::new(),
                 wm_delete_window,
                 wm_protocols,
+                net_wm_state,
+                net_wm_state_fullscreen,
+                net_wm_state_maximized,
+                net_wm_icon,
+                net_wm_window_opacity,
+                clipboard_atom,
+                utf8_string_atom,
+                targets_atom,
+                incr_atom,
+                selection_property,
             };
 
+            if !appearance.resizable {
+                x11_window.update_size_hints();
+            }
+
             // Make context current
             x11_window.make_current();
 
@@ -385,8 +758,23 @@ impl X11Window {
         map
     }
 
-    fn translate_key(&self, keycode: u32) -> KeyCode {
-        self.key_map.get(&keycode).copied().unwrap_or(KeyCode::Unknown)
+    /// Translate a keycode by the symbol it types in the keyboard group of `state`
+    ///
+    /// Keys whose symbol has no key code, such as é or a dead key, fall back
+    /// to the key at the same position on a US keyboard.
+    fn translate_key(&self, keycode: u32, state: u32) -> KeyCode {
+        // Bits 13 and 14 of the core state hold the XKB group
+        let group = ((state >> 13) & 0x3) as i32;
+        keysym::keysym_to_keycode(self.keysym(keycode, group))
+            .unwrap_or_else(|| self.key_map.get(&keycode).copied().unwrap_or(KeyCode::Unknown))
+    }
+
+    /// Unshifted keysym of `keycode` in keyboard group `group`
+    fn keysym(&self, keycode: u32, group: i32) -> u32 {
+        match u8::try_from(keycode) {
+            Ok(keycode) => unsafe { xlib::XkbKeycodeToKeysym(self.display, keycode, group, 0) as u32 },
+            Err(_) => 0,
+        }
     }
 
     fn translate_button(&self, button: u32) -> MouseButton {
@@ -410,6 +798,562 @@ impl X11Window {
         }
     }
 
+    fn query_xinput2(display: *mut Display) -> Option<i32> {
+        unsafe {
+            let name = CString::new("XInputExtension").unwrap();
+            let (mut opcode, mut event, mut error) = (0, 0, 0);
+            if xlib::XQueryExtension(display, name.as_ptr(), &mut opcode, &mut event, &mut error) == 0 {
+                warn!("XInput2 is not available, captured mouse motion will be accelerated");
+                return None;
+            }
+
+            let (mut major, mut minor) = (2, 0);
+            if xinput2::XIQueryVersion(display, &mut major, &mut minor) != xlib::Success as i32 {
+                warn!("XInput2 is not available, captured mouse motion will be accelerated");
+                return None;
+            }
+            debug!("XInput {}.{} available", major, minor);
+            Some(opcode)
+        }
+    }
+
+    /// Receive raw motion from every pointer, or stop receiving it
+    fn select_raw_motion(&mut self, enabled: bool) {
+        if self.xi_opcode.is_none() {
+            return;
+        }
+        unsafe {
+            let mut mask = [0u8; 4];
+            if enabled {
+                xinput2::XISetMask(&mut mask, xinput2::XI_RawMotion);
+            }
+            let mut event_mask = xinput2::XIEventMask {
+                deviceid: xinput2::XIAllMasterDevices,
+                mask_len: mask.len() as i32,
+                mask: mask.as_mut_ptr(),
+            };
+            // Raw events are only delivered to the root window
+            let root = xlib::XRootWindow(self.display, self.screen);
+            xinput2::XISelectEvents(self.display, root, &mut event_mask, 1);
+        }
+    }
+
+    /// Turn an XInput2 raw motion event into a `MouseRawMotion` event
+    fn raw_motion_event(&self, cookie: &mut xlib::XGenericEventCookie) -> Option<Event> {
+        if self.xi_opcode != Some(cookie.extension) || cookie.evtype != xinput2::XI_RawMotion {
+            return None;
+        }
+        unsafe {
+            if xlib::XGetEventData(self.display, cookie) == 0 {
+                return None;
+            }
+            let raw = &*(cookie.data as *const xinput2::XIRawEvent);
+            let mask = std::slice::from_raw_parts(raw.valuators.mask, raw.valuators.mask_len as usize);
+
+            // raw_values holds one value per set bit of the mask; valuators 0 and 1 are x and y
+            let mut values = raw.raw_values;
+            let mut delta = [0.0; 2];
+            for (valuator, delta) in delta.iter_mut().enumerate() {
+                if xinput2::XIMaskIsSet(mask, valuator as i32) {
+                    *delta = *values;
+                    values = values.add(1);
+                }
+            }
+            xlib::XFreeEventData(self.display, cookie);
+
+            if self.cursor_mode != CursorMode::Captured || delta == [0.0; 2] {
+                return None;
+            }
+            Some(Event::new(EventData::MouseRawMotion(MouseRawMotionEvent { delta_x: delta[0], delta_y: delta[1] })))
+        }
+    }
+
+    fn invisible_cursor(&mut self) -> xlib::Cursor {
+        if self.invisible_cursor == 0 {
+            unsafe {
+                let data = [0 as std::os::raw::c_char; 8];
+                let pixmap = xlib::XCreateBitmapFromData(self.display, self.window, data.as_ptr(), 8, 8);
+                let mut color = mem::zeroed::<xlib::XColor>();
+                self.invisible_cursor = xlib::XCreatePixmapCursor(self.display, pixmap, pixmap, &mut color, &mut color, 0, 0);
+                xlib::XFreePixmap(self.display, pixmap);
+            }
+        }
+        self.invisible_cursor
+    }
+
+    /// Cursor from the X cursor font showing `icon`, created on first use
+    fn icon_cursor(&mut self, icon: CursorIcon) -> xlib::Cursor {
+        let shape = cursor_font_glyph(icon).unwrap_or_else(|| {
+            // Custom cursors are created up front, so this one is unknown
+            warn!("Unknown cursor {:?}, showing the arrow", icon);
+            XC_LEFT_PTR
+        });
+        let display = self.display;
+        *self.icon_cursors.entry(icon).or_insert_with(|| unsafe { xlib::XCreateFontCursor(display, shape) })
+    }
+
+    /// Tell the window manager the size limits and aspect ratio to keep while resizing
+    fn update_size_hints(&self) {
+        unsafe {
+            let hints = xlib::XAllocSizeHints();
+            if hints.is_null() {
+                warn!("Failed to allocate X11 size hints");
+                return;
+            }
+            let (min, max) = self.size_limits;
+            if let Some(min) = min {
+                (*hints).flags |= xlib::PMinSize;
+                (*hints).min_width = min.0 as i32;
+                (*hints).min_height = min.1 as i32;
+            }
+            if let Some(max) = max {
+                (*hints).flags |= xlib::PMaxSize;
+                (*hints).max_width = max.0 as i32;
+                (*hints).max_height = max.1 as i32;
+            }
+            if let Some((width, height)) = self.aspect_ratio {
+                let ratio = xlib::AspectRatio { x: width as i32, y: height as i32 };
+                (*hints).flags |= xlib::PAspect;
+                (*hints).min_aspect = ratio;
+                (*hints).max_aspect = ratio;
+            }
+            xlib::XSetWMNormalHints(self.display, self.window, hints);
+            xlib::XFree(hints as *mut _);
+            xlib::XFlush(self.display);
+        }
+    }
+
+    /// Ask the window manager to add or remove the EWMH fullscreen state
+    fn set_wm_fullscreen(&self, fullscreen: bool) {
+        self.set_wm_state(fullscreen, [self.net_wm_state_fullscreen, 0]);
+    }
+
+    /// Ask the window manager to add or remove up to two EWMH states at once
+    fn set_wm_state(&self, add: bool, states: [xlib::Atom; 2]) {
+        let mut data = xlib::ClientMessageData::new();
+        data.set_long(0, add as i64); // _NET_WM_STATE_ADD or _NET_WM_STATE_REMOVE
+        data.set_long(1, states[0] as i64);
+        data.set_long(2, states[1] as i64);
+        data.set_long(3, 1); // Request from a normal application
+        let message = xlib::XClientMessageEvent {
+            type_: xlib::ClientMessage,
+            serial: 0,
+            send_event: 1,
+            display: self.display,
+            window: self.window,
+            message_type: self.net_wm_state,
+            format: 32,
+            data,
+        };
+        unsafe {
+            let root = xlib::XRootWindow(self.display, self.screen);
+            let mut event = XEvent::from(message);
+            xlib::XSendEvent(
+                self.display,
+                root,
+                0,
+                xlib::SubstructureRedirectMask | xlib::SubstructureNotifyMask,
+                &mut event,
+            );
+        }
+    }
+
+    /// Keep the pointer inside the window, showing `cursor` or, if 0, the window's cursor
+    fn grab_pointer(&self, cursor: xlib::Cursor) {
+        let event_mask = xlib::ButtonPressMask | xlib::ButtonReleaseMask | xlib::PointerMotionMask;
+        let result = unsafe {
+            xlib::XGrabPointer(
+                self.display,
+                self.window,
+                1,
+                event_mask as u32,
+                xlib::GrabModeAsync,
+                xlib::GrabModeAsync,
+                self.window,
+                cursor,
+                xlib::CurrentTime,
+            )
+        };
+        if result != xlib::GrabSuccess {
+            warn!("Failed to grab the X11 pointer (status {}), the cursor can leave the window", result);
+        }
+    }
+
+    /// EWMH states the window manager has set on the window
+    fn wm_states(&self) -> Vec<xlib::Atom> {
+        unsafe {
+            let (mut actual_type, mut format, mut items, mut remaining) = (0, 0, 0, 0);
+            let mut data = ptr::null_mut();
+            let status = xlib::XGetWindowProperty(
+                self.display,
+                self.window,
+                self.net_wm_state,
+                0,
+                1024,
+                0,
+                xlib::XA_ATOM,
+                &mut actual_type,
+                &mut format,
+                &mut items,
+                &mut remaining,
+                &mut data,
+            );
+            if status != xlib::Success as i32 || data.is_null() {
+                return Vec::new();
+            }
+            // Xlib hands out 32-bit properties as longs
+            let states = match format {
+                32 => std::slice::from_raw_parts(data as *const xlib::Atom, items as usize).to_vec(),
+                _ => Vec::new(),
+            };
+            xlib::XFree(data as *mut _);
+            states
+        }
+    }
+
+    /// Switch the CRTC behind monitor `monitor` to `video_mode` and return the monitor's origin
+    ///
+    /// The first mode replaced is kept for `restore_video_mode`.
+    fn switch_video_mode(&mut self, monitor: usize, video_mode: VideoMode) -> Result<(i32, i32), String> {
+        let root = unsafe { xlib::XRootWindow(self.display, self.screen) };
+        let resources = ScreenResources::current(self.display, root).ok_or("XRandR is not available")?;
+        let &(output, crtc) = resources.monitors(root).get(monitor).ok_or(format!("No monitor {}", monitor))?;
+        let (mode, _) = resources
+            .video_modes(output)
+            .into_iter()
+            .find(|(_, mode)| *mode == video_mode)
+            .ok_or(format!("Monitor {} has no video mode {:?}", monitor, video_mode))?;
+
+        unsafe {
+            let crtc_info = xrandr::XRRGetCrtcInfo(self.display, resources.ptr, crtc);
+            if crtc_info.is_null() {
+                return Err(format!("No CRTC info for monitor {}", monitor));
+            }
+            let info = *crtc_info;
+            let status = xrandr::XRRSetCrtcConfig(
+                self.display, resources.ptr, crtc, info.timestamp, info.x, info.y, mode, info.rotation, info.outputs, info.noutput,
+            );
+            xrandr::XRRFreeCrtcInfo(crtc_info);
+            if status != 0 {
+                return Err(format!("X server refused video mode {:?}", video_mode));
+            }
+            self.replaced_video_mode.get_or_insert((crtc, info.mode));
+            Ok((info.x, info.y))
+        }
+    }
+
+    /// Put back the video mode `switch_video_mode` replaced
+    fn restore_video_mode(&mut self) {
+        let Some((crtc, mode)) = self.replaced_video_mode.take() else { return };
+        debug!("Restoring X11 video mode {} on CRTC {}", mode, crtc);
+        let root = unsafe { xlib::XRootWindow(self.display, self.screen) };
+        let Some(resources) = ScreenResources::current(self.display, root) else { return };
+        unsafe {
+            let crtc_info = xrandr::XRRGetCrtcInfo(self.display, resources.ptr, crtc);
+            if crtc_info.is_null() {
+                return;
+            }
+            let info = *crtc_info;
+            xrandr::XRRSetCrtcConfig(
+                self.display, resources.ptr, crtc, info.timestamp, info.x, info.y, mode, info.rotation, info.outputs, info.noutput,
+            );
+            xrandr::XRRFreeCrtcInfo(crtc_info);
+        }
+    }
+
+    /// Text we offer for `selection`, CLIPBOARD or PRIMARY
+    fn owned_selection_mut(&mut self, selection: xlib::Atom) -> &mut Option<String> {
+        if selection == xlib::XA_PRIMARY {
+            &mut self.primary
+        } else {
+            &mut self.clipboard
+        }
+    }
+
+    /// Largest property the server takes in one request; bigger selections go with INCR
+    fn max_property_bytes(&self) -> usize {
+        let units = unsafe {
+            match xlib::XExtendedMaxRequestSize(self.display) {
+                0 => xlib::XMaxRequestSize(self.display),
+                units => units,
+            }
+        };
+        // Requests are counted in 4-byte units; leave room for the request header
+        (units as usize * 4).saturating_sub(1024)
+    }
+
+    /// Send the text of one of our selections to a client that asked for it
+    fn answer_selection_request(&mut self, request: &xlib::XSelectionRequestEvent) {
+        // Obsolete clients leave the property empty and expect the target to be used
+        let property = if request.property == 0 { request.target } else { request.property };
+        let mut reply = xlib::XSelectionEvent {
+            type_: xlib::SelectionNotify,
+            serial: 0,
+            send_event: 1,
+            display: self.display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property: 0,
+            time: request.time,
+        };
+
+        let text = self.owned_selection_mut(request.selection).clone();
+        unsafe {
+            match text {
+                Some(_) if request.target == self.targets_atom => {
+                    let targets = [self.targets_atom, self.utf8_string_atom, xlib::XA_STRING];
+                    xlib::XChangeProperty(
+                        self.display,
+                        request.requestor,
+                        property,
+                        xlib::XA_ATOM,
+                        32,
+                        xlib::PropModeReplace,
+                        targets.as_ptr() as *const u8,
+                        targets.len() as i32,
+                    );
+                    reply.property = property;
+                }
+                Some(text) if request.target == self.utf8_string_atom || request.target == xlib::XA_STRING => {
+                    if text.len() > self.max_property_bytes() {
+                        // Announce the size; each time the requestor deletes the property the next piece follows
+                        debug!("Sending {} bytes of selection with INCR", text.len());
+                        let size = text.len() as std::ffi::c_long;
+                        xlib::XSelectInput(self.display, request.requestor, xlib::PropertyChangeMask);
+                        xlib::XChangeProperty(
+                            self.display,
+                            request.requestor,
+                            property,
+                            self.incr_atom,
+                            32,
+                            xlib::PropModeReplace,
+                            &size as *const _ as *const u8,
+                            1,
+                        );
+                        self.incr_transfers.push(IncrTransfer {
+                            requestor: request.requestor,
+                            property,
+                            target: request.target,
+                            data: text.into_bytes(),
+                            sent: 0,
+                        });
+                    } else {
+                        xlib::XChangeProperty(
+                            self.display,
+                            request.requestor,
+                            property,
+                            request.target,
+                            8,
+                            xlib::PropModeReplace,
+                            text.as_ptr(),
+                            text.len() as i32,
+                        );
+                    }
+                    reply.property = property;
+                }
+                // Refused: the reply keeps an empty property
+                _ => debug!("Refusing selection request for target {}", request.target),
+            }
+
+            let mut event = XEvent::from(reply);
+            xlib::XSendEvent(self.display, request.requestor, 0, xlib::NoEventMask, &mut event);
+            xlib::XFlush(self.display);
+        }
+    }
+
+    /// Send the next piece of an INCR transfer once the requestor deleted the previous one
+    fn continue_incr_transfer(&mut self, notify: &xlib::XPropertyEvent) {
+        if notify.state != xlib::PropertyDelete {
+            return;
+        }
+        let Some(index) = self
+            .incr_transfers
+            .iter()
+            .position(|transfer| transfer.requestor == notify.window && transfer.property == notify.atom)
+        else {
+            return;
+        };
+        let chunk_size = self.max_property_bytes();
+        let transfer = &mut self.incr_transfers[index];
+        let (requestor, property, target) = (transfer.requestor, transfer.property, transfer.target);
+        let chunk = transfer.next_piece(chunk_size);
+        let finished = chunk.is_empty();
+        unsafe {
+            xlib::XChangeProperty(
+                self.display,
+                requestor,
+                property,
+                target,
+                8,
+                xlib::PropModeReplace,
+                chunk.as_ptr(),
+                chunk.len() as i32,
+            );
+            // An empty piece marks the end
+            if finished {
+                xlib::XSelectInput(self.display, requestor, xlib::NoEventMask);
+                debug!("Finished INCR transfer of {} bytes", self.incr_transfers[index].data.len());
+                self.incr_transfers.remove(index);
+            }
+            xlib::XFlush(self.display);
+        }
+    }
+
+    /// Text of `selection`, answered from our own copy when we own it
+    fn selection_text(&mut self, selection: xlib::Atom) -> Option<String> {
+        // Asking ourselves through the server would deadlock, so answer directly
+        let owner = unsafe { xlib::XGetSelectionOwner(self.display, selection) };
+        if owner == self.window {
+            return self.owned_selection_mut(selection).clone();
+        }
+        if owner == 0 {
+            return None;
+        }
+        self.request_selection(selection)
+    }
+
+    /// Take ownership of `selection` and offer `text` through it
+    fn take_selection(&mut self, selection: xlib::Atom, text: &str) {
+        *self.owned_selection_mut(selection) = Some(text.to_string());
+        unsafe {
+            xlib::XSetSelectionOwner(self.display, selection, self.window, xlib::CurrentTime);
+            if xlib::XGetSelectionOwner(self.display, selection) != self.window {
+                warn!("Failed to take ownership of X11 selection {}", selection);
+                *self.owned_selection_mut(selection) = None;
+            }
+        }
+    }
+
+    /// Ask the owner of `selection` for its text as UTF-8 and wait briefly for the answer
+    ///
+    /// Owners send selections too large for one property with the INCR
+    /// protocol; those are collected piece by piece.
+    fn request_selection(&self, selection: xlib::Atom) -> Option<String> {
+        unsafe {
+            xlib::XConvertSelection(
+                self.display,
+                selection,
+                self.utf8_string_atom,
+                self.selection_property,
+                self.window,
+                xlib::CurrentTime,
+            );
+            xlib::XFlush(self.display);
+        }
+
+        let Some(event) = self.wait_for_event(xlib::SelectionNotify, |_| true) else {
+            warn!("Selection owner did not answer in time");
+            return None;
+        };
+        let notify = xlib::XSelectionEvent::from(event);
+        if notify.property == 0 {
+            debug!("Selection holds no text");
+            return None;
+        }
+
+        // Deleting the property tells an INCR owner to send the first piece
+        let (kind, data) = self.take_selection_property()?;
+        if kind != self.incr_atom {
+            return data.map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+        }
+        let mut bytes = Vec::new();
+        loop {
+            let new_value = |event: &XEvent| {
+                let property = xlib::XPropertyEvent::from(*event);
+                property.atom == self.selection_property && property.state == xlib::PropertyNewValue
+            };
+            if self.wait_for_event(xlib::PropertyNotify, new_value).is_none() {
+                warn!("Selection owner stopped sending after {} bytes", bytes.len());
+                return None;
+            }
+            match self.take_selection_property()? {
+                (_, Some(chunk)) if chunk.is_empty() => break,
+                (_, Some(chunk)) => bytes.extend_from_slice(&chunk),
+                (_, None) => return None,
+            }
+        }
+        debug!("Received {} bytes of selection with INCR", bytes.len());
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Read and delete the property selection owners write into
+    ///
+    /// Returns the property's type and, for 8-bit properties, its bytes.
+    fn take_selection_property(&self) -> Option<(xlib::Atom, Option<Vec<u8>>)> {
+        unsafe {
+            let (mut actual_type, mut format, mut items, mut remaining) = (0, 0, 0, 0);
+            let mut data = ptr::null_mut();
+            let status = xlib::XGetWindowProperty(
+                self.display,
+                self.window,
+                self.selection_property,
+                0,
+                i32::MAX as i64 / 4,
+                1,
+                xlib::AnyPropertyType as xlib::Atom,
+                &mut actual_type,
+                &mut format,
+                &mut items,
+                &mut remaining,
+                &mut data,
+            );
+            if status != xlib::Success as i32 || data.is_null() {
+                return None;
+            }
+            let bytes = (format == 8).then(|| std::slice::from_raw_parts(data, items as usize).to_vec());
+            xlib::XFree(data as *mut _);
+            Some((actual_type, bytes))
+        }
+    }
+
+    /// Wait briefly for an event of type `kind` on our window that `matches`, dropping others of that type
+    /// Connect to the input method named by `XMODIFIERS` with over-the-spot preedit, whose window follows the spot location
+    fn open_input_context(&self) -> Option<(xlib::XIM, xlib::XIC)> {
+        unsafe {
+            let modifiers = CString::new("").unwrap();
+            xlib::XSetLocaleModifiers(modifiers.as_ptr());
+            let input_method = xlib::XOpenIM(self.display, ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+            if input_method.is_null() {
+                return None;
+            }
+            let style = (xlib::XIMPreeditPosition | xlib::XIMStatusNothing) as c_ulong;
+            let input_context = xlib::XCreateIC(
+                input_method,
+                xlib::XNInputStyle_0.as_ptr(),
+                style,
+                xlib::XNClientWindow_0.as_ptr(),
+                self.window,
+                xlib::XNFocusWindow_0.as_ptr(),
+                self.window,
+                ptr::null_mut::<c_char>(),
+            );
+            if input_context.is_null() {
+                xlib::XCloseIM(input_method);
+                return None;
+            }
+            xlib::XSetICFocus(input_context);
+            Some((input_method, input_context))
+        }
+    }
+
+    fn wait_for_event(&self, kind: i32, matches: impl Fn(&XEvent) -> bool) -> Option<XEvent> {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(500);
+        let mut event = unsafe { mem::zeroed::<XEvent>() };
+        loop {
+            if unsafe { xlib::XCheckTypedWindowEvent(self.display, self.window, kind, &mut event) } != 0 {
+                if matches(&event) {
+                    return Some(event);
+                }
+                continue;
+            }
+            if std::time::Instant::now() > deadline {
+                return None;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
     /// Reload OpenGL function pointers - critical for backend switching
     pub fn reload_opengl_functions(&mut self) {
         info!("Reloading OpenGL function pointers for X11 backend after context switch");
@@ -457,7 +1401,12 @@ impl Window for X11Window {
             xlib::XFlush(self.display);
         }
         // Swap buffers to display rendered content (fixes black screen issue)
-        self.swap_buffers();
+        if self.present_timer.needs_refresh_rate() {
+            let refresh_rate = self.refresh_rate();
+            self.present_timer.set_refresh_rate(refresh_rate);
+        }
+        let (display, window) = (self.display, self.window);
+        self.present_timer.time_swap(|| unsafe { glx::glXSwapBuffers(display, window) });
     }
 
     fn process_events(&mut self) {
@@ -465,13 +1414,17 @@ impl Window for X11Window {
             while xlib::XPending(self.display) > 0 {
                 let mut event = mem::zeroed::<XEvent>();
                 xlib::XNextEvent(self.display, &mut event);
+                // Keys the input method takes for composing never reach the game
+                if self.input_context.is_some() && xlib::XFilterEvent(&mut event, 0) != 0 {
+                    continue;
+                }
 
                 match event.get_type() {
                     xlib::KeyPress => {
                         let key_event = xlib::XKeyEvent::from(event);
                         self.update_modifiers(key_event.state);
                         
-                        let key_code = self.translate_key(key_event.keycode);
+                        let key_code = self.translate_key(key_event.keycode, key_event.state);
                         
                         // Handle escape key
                         if key_code == KeyCode::Escape {
@@ -493,7 +1446,7 @@ impl Window for X11Window {
                         let key_event = xlib::XKeyEvent::from(event);
                         self.update_modifiers(key_event.state);
                         
-                        let key_code = self.translate_key(key_event.keycode);
+                        let key_code = self.translate_key(key_event.keycode, key_event.state);
 
                         if let Some(callback) = &self.event_callback {
                             let key_event = KeyEvent {
@@ -642,10 +1595,46 @@ impl Window for X11Window {
                         // Window needs to be redrawn
                         // The application will handle this in its render loop
                     }
+                    xlib::SelectionRequest => {
+                        let request = xlib::XSelectionRequestEvent::from(event);
+                        self.answer_selection_request(&request);
+                    }
+                    xlib::SelectionClear => {
+                        // Another client took the selection
+                        let clear = xlib::XSelectionClearEvent::from(event);
+                        *self.owned_selection_mut(clear.selection) = None;
+                    }
+                    xlib::PropertyNotify => {
+                        let notify = xlib::XPropertyEvent::from(event);
+                        self.continue_incr_transfer(&notify);
+                    }
+                    xlib::MappingNotify => {
+                        // The keyboard layout changed; drop Xlib's cached keysyms
+                        let mut mapping = event.mapping;
+                        xlib::XRefreshKeyboardMapping(&mut mapping);
+                        debug!("X11 keyboard mapping changed");
+                    }
+                    xlib::GenericEvent => {
+                        let mut cookie = event.generic_event_cookie;
+                        if let Some(event) = self.raw_motion_event(&mut cookie) {
+                            if let Some(callback) = &self.event_callback {
+                                let mut callback = callback.lock().unwrap();
+                                callback(event);
+                            }
+                        }
+                    }
                     _ => {}
                 }
             }
         }
+
+        // X11 has no joystick API, so watch the kernel's joystick devices
+        for event in self.gamepad_hotplug.poll(scan_linux_joysticks) {
+            if let Some(callback) = &self.event_callback {
+                let mut callback = callback.lock().unwrap();
+                callback(event);
+            }
+        }
     }
 
     fn set_should_close(&mut self) {
@@ -690,6 +1679,91 @@ impl Window for X11Window {
         }
     }
 
+    fn set_app_id(&mut self, app_id: &str) {
+        debug!("Setting X11 WM_CLASS to '{}'", app_id);
+        set_class_hint(self.display, self.window, app_id);
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        debug!("{} X11 window", if visible { "Showing" } else { "Hiding" });
+        unsafe {
+            if visible {
+                xlib::XMapWindow(self.display, self.window);
+            } else {
+                xlib::XUnmapWindow(self.display, self.window);
+            }
+            xlib::XFlush(self.display);
+        }
+        self.visible = visible;
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn capture_framebuffer(&mut self) -> Result<ImageBuffer, String> {
+        self.make_current();
+        crate::io::image::read_gl_framebuffer(self.size.0, self.size.1)
+    }
+
+    fn set_opacity(&mut self, opacity: f32) {
+        let opacity = opacity.clamp(0.0, 1.0);
+        debug!("Setting X11 window opacity to {}", opacity);
+        unsafe {
+            if opacity >= 1.0 {
+                // No property at all lets the compositor skip blending the window
+                xlib::XDeleteProperty(self.display, self.window, self.net_wm_window_opacity);
+            } else {
+                // Compositors read the opacity as a fraction of 0xFFFFFFFF
+                let value = opacity_fraction(opacity) as c_ulong;
+                xlib::XChangeProperty(
+                    self.display,
+                    self.window,
+                    self.net_wm_window_opacity,
+                    xlib::XA_CARDINAL,
+                    32,
+                    xlib::PropModeReplace,
+                    &value as *const c_ulong as *const u8,
+                    1,
+                );
+            }
+            xlib::XFlush(self.display);
+        }
+        self.opacity = opacity;
+    }
+
+    fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    fn set_icon(&mut self, images: &[IconImage]) {
+        debug!("Setting X11 window icon from {} images", images.len());
+        unsafe {
+            if images.is_empty() {
+                xlib::XDeleteProperty(self.display, self.window, self.net_wm_icon);
+            } else {
+                // Width, height, then the pixels, for each size; format 32 means one long per item
+                let mut data: Vec<c_ulong> = Vec::new();
+                for image in images {
+                    data.push(image.width() as c_ulong);
+                    data.push(image.height() as c_ulong);
+                    data.extend(image.argb().into_iter().map(|pixel| pixel as c_ulong));
+                }
+                xlib::XChangeProperty(
+                    self.display,
+                    self.window,
+                    self.net_wm_icon,
+                    xlib::XA_CARDINAL,
+                    32,
+                    xlib::PropModeReplace,
+                    data.as_ptr() as *const u8,
+                    data.len() as i32,
+                );
+            }
+            xlib::XFlush(self.display);
+        }
+    }
+
     fn get_event_callback(&self) -> Option<Arc<Mutex<dyn FnMut(Event) + Send + 'static>>> {
         self.event_callback.clone()
     }
@@ -698,11 +1772,337 @@ impl Window for X11Window {
         self.event_callback = Some(callback);
     }
 
+    fn set_cursor_mode(&mut self, mode: CursorMode) {
+        debug!("Setting X11 cursor mode to {:?}", mode);
+        unsafe {
+            // Release the previous mode before applying the new one
+            if matches!(self.cursor_mode, CursorMode::Captured | CursorMode::Confined) {
+                xlib::XUngrabPointer(self.display, xlib::CurrentTime);
+            }
+            if self.cursor_mode == CursorMode::Captured {
+                self.select_raw_motion(false);
+            }
+
+            match mode {
+                CursorMode::Normal => {
+                    let cursor = self.icon_cursor(self.cursor_icon);
+                    xlib::XDefineCursor(self.display, self.window, cursor);
+                }
+                CursorMode::Hidden => {
+                    let cursor = self.invisible_cursor();
+                    xlib::XDefineCursor(self.display, self.window, cursor);
+                }
+                CursorMode::Captured => {
+                    let cursor = self.invisible_cursor();
+                    xlib::XDefineCursor(self.display, self.window, cursor);
+                    self.grab_pointer(cursor);
+                    self.select_raw_motion(true);
+                }
+                CursorMode::Confined => {
+                    let cursor = self.icon_cursor(self.cursor_icon);
+                    xlib::XDefineCursor(self.display, self.window, cursor);
+                    // No grab cursor, so later `set_cursor` calls still show
+                    self.grab_pointer(0);
+                }
+            }
+            xlib::XFlush(self.display);
+        }
+        self.cursor_mode = mode;
+    }
+
+    fn cursor_mode(&self) -> CursorMode {
+        self.cursor_mode
+    }
+
+    fn set_cursor(&mut self, icon: CursorIcon) {
+        debug!("Setting X11 cursor to {:?}", icon);
+        self.cursor_icon = icon;
+        // A hidden cursor stays hidden; the icon shows when it is shown again
+        if self.cursor_mode.shows_cursor() {
+            let cursor = self.icon_cursor(icon);
+            unsafe {
+                xlib::XDefineCursor(self.display, self.window, cursor);
+                xlib::XFlush(self.display);
+            }
+        }
+    }
+
+    fn cursor(&self) -> CursorIcon {
+        self.cursor_icon
+    }
+
+    fn set_size_limits(&mut self, min: Option<Size>, max: Option<Size>) {
+        debug!("Setting X11 size limits to {:?}..{:?}", min, max);
+        self.size_limits = (min, max);
+        self.update_size_hints();
+    }
+
+    fn size_limits(&self) -> (Option<Size>, Option<Size>) {
+        self.size_limits
+    }
+
+    fn set_aspect_ratio(&mut self, ratio: Option<(u32, u32)>) {
+        debug!("Setting X11 aspect ratio to {:?}", ratio);
+        self.aspect_ratio = ratio;
+        self.update_size_hints();
+    }
+
+    fn aspect_ratio(&self) -> Option<(u32, u32)> {
+        self.aspect_ratio
+    }
+
+    fn set_maximized(&mut self, maximized: bool) {
+        debug!("{} X11 window", if maximized { "Maximizing" } else { "Restoring" });
+        self.set_wm_state(maximized, self.net_wm_state_maximized);
+        unsafe {
+            xlib::XFlush(self.display);
+        }
+    }
+
+    fn is_maximized(&self) -> bool {
+        let states = self.wm_states();
+        self.net_wm_state_maximized.iter().all(|state| states.contains(state))
+    }
+
+    fn set_fullscreen(&mut self, mode: FullscreenMode) {
+        if mode == self.fullscreen {
+            return;
+        }
+        if self.fullscreen == FullscreenMode::Windowed {
+            self.windowed_rect = (self.position, self.size);
+        }
+        debug!("Setting X11 fullscreen mode to {:?}", mode);
+
+        self.restore_video_mode();
+        let mode = match mode {
+            FullscreenMode::Windowed => {
+                self.set_wm_fullscreen(false);
+                let (position, size) = self.windowed_rect;
+                unsafe {
+                    xlib::XMoveResizeWindow(self.display, self.window, position.0, position.1, size.0, size.1);
+                }
+                mode
+            }
+            FullscreenMode::Borderless => {
+                self.set_wm_fullscreen(true);
+                mode
+            }
+            FullscreenMode::Exclusive { monitor, video_mode } => match self.switch_video_mode(monitor, video_mode) {
+                Ok((x, y)) => {
+                    // The window manager fullscreens the window on the monitor it is on
+                    unsafe {
+                        xlib::XMoveWindow(self.display, self.window, x, y);
+                    }
+                    self.set_wm_fullscreen(true);
+                    mode
+                }
+                Err(e) => {
+                    warn!("Cannot enter exclusive fullscreen, using borderless instead: {}", e);
+                    self.set_wm_fullscreen(true);
+                    FullscreenMode::Borderless
+                }
+            },
+        };
+        unsafe {
+            xlib::XFlush(self.display);
+        }
+        self.fullscreen = mode;
+    }
+
+    fn fullscreen(&self) -> FullscreenMode {
+        self.fullscreen
+    }
+
+    fn video_modes(&mut self, monitor: usize) -> Vec<VideoMode> {
+        let root = unsafe { xlib::XRootWindow(self.display, self.screen) };
+        let Some(resources) = ScreenResources::current(self.display, root) else { return Vec::new() };
+        let Some(&(output, _)) = resources.monitors(root).get(monitor) else { return Vec::new() };
+        let mut modes: Vec<VideoMode> = resources.video_modes(output).into_iter().map(|(_, mode)| mode).collect();
+        modes.dedup();
+        modes
+    }
+
+    fn monitor(&mut self) -> Option<usize> {
+        let root = unsafe { xlib::XRootWindow(self.display, self.screen) };
+        let resources = ScreenResources::current(self.display, root)?;
+        // Under a reparenting window manager the configured position is relative to the frame
+        let (mut x, mut y, mut child) = (0, 0, 0);
+        unsafe {
+            xlib::XTranslateCoordinates(self.display, self.window, root, 0, 0, &mut x, &mut y, &mut child);
+        }
+        let center = (x + self.size.0 as i32 / 2, y + self.size.1 as i32 / 2);
+        resources.monitors(root).iter().position(|&(_, crtc)| unsafe {
+            let info = xrandr::XRRGetCrtcInfo(self.display, resources.ptr, crtc);
+            if info.is_null() {
+                return false;
+            }
+            let (cx, cy, width, height) = ((*info).x, (*info).y, (*info).width as i32, (*info).height as i32);
+            xrandr::XRRFreeCrtcInfo(info);
+            (cx..cx + width).contains(&center.0) && (cy..cy + height).contains(&center.1)
+        })
+    }
+
+    fn set_vsync(&mut self, enabled: bool) {
+        debug!("Setting X11 vsync to {}", enabled);
+        let interval = enabled as i32;
+        unsafe {
+            let extensions = glx::glXQueryExtensionsString(self.display, self.screen);
+            let extensions = if extensions.is_null() { String::new() } else { CStr::from_ptr(extensions).to_string_lossy().into_owned() };
+            let has_extension = |name: &str| extensions.split_whitespace().any(|extension| extension == name);
+            let load = |name: &str| {
+                let name = CString::new(name).unwrap();
+                glx::glXGetProcAddress(name.as_ptr() as *const u8)
+            };
+
+            // The EXT function names the drawable; the MESA one acts on the current context
+            if has_extension("GLX_EXT_swap_control") {
+                if let Some(proc_addr) = load("glXSwapIntervalEXT") {
+                    let swap_interval: unsafe extern "C" fn(*mut Display, XWindow, i32) = mem::transmute(proc_addr);
+                    swap_interval(self.display, self.window, interval);
+                    return;
+                }
+            }
+            if has_extension("GLX_MESA_swap_control") {
+                if let Some(proc_addr) = load("glXSwapIntervalMESA") {
+                    let swap_interval: unsafe extern "C" fn(u32) -> i32 = mem::transmute(proc_addr);
+                    self.make_current();
+                    swap_interval(interval as u32);
+                    return;
+                }
+            }
+        }
+        warn!("The X server offers no GLX swap control, vsync stays as the driver set it");
+    }
+
+    fn refresh_rate(&mut self) -> Option<u32> {
+        let monitor = self.monitor()?;
+        let root = unsafe { xlib::XRootWindow(self.display, self.screen) };
+        let resources = ScreenResources::current(self.display, root)?;
+        let &(_, crtc) = resources.monitors(root).get(monitor)?;
+        unsafe {
+            let info = xrandr::XRRGetCrtcInfo(self.display, resources.ptr, crtc);
+            if info.is_null() {
+                return None;
+            }
+            let mode = (*info).mode;
+            xrandr::XRRFreeCrtcInfo(info);
+            let modes = std::slice::from_raw_parts((*resources.ptr).modes, (*resources.ptr).nmode as usize);
+            modes.iter().find(|info| info.id == mode).map(refresh_rate).filter(|rate| *rate > 0)
+        }
+    }
+
+    fn present_stats(&self) -> Option<PresentStats> {
+        Some(self.present_timer.stats())
+    }
+
+    fn create_cursor(&mut self, image: &CursorImage, hotspot: (u32, u32)) -> Result<CursorIcon, String> {
+        image.check_hotspot(hotspot)?;
+        let cursor = unsafe {
+            let xcursor_image = xcursor::XcursorImageCreate(image.width() as i32, image.height() as i32);
+            if xcursor_image.is_null() {
+                return Err("Failed to allocate X11 cursor image".to_string());
+            }
+            (*xcursor_image).xhot = hotspot.0;
+            (*xcursor_image).yhot = hotspot.1;
+            let pixels = image.premultiplied_argb();
+            ptr::copy_nonoverlapping(pixels.as_ptr(), (*xcursor_image).pixels, pixels.len());
+            let cursor = xcursor::XcursorImageLoadCursor(self.display, xcursor_image);
+            xcursor::XcursorImageDestroy(xcursor_image);
+            cursor
+        };
+        if cursor == 0 {
+            return Err("X server refused the cursor image".to_string());
+        }
+
+        let icon = CursorIcon::Custom(self.next_cursor_id);
+        self.next_cursor_id += 1;
+        debug!("Created X11 cursor {:?} from {}x{} image", icon, image.width(), image.height());
+        // Freed with the font cursors when the window closes
+        self.icon_cursors.insert(icon, cursor);
+        Ok(icon)
+    }
+
+    fn clipboard_text(&mut self) -> Option<String> {
+        self.selection_text(self.clipboard_atom)
+    }
+
+    fn set_clipboard_text(&mut self, text: &str) {
+        debug!("Taking X11 clipboard with {} bytes", text.len());
+        self.take_selection(self.clipboard_atom, text);
+    }
+
+    fn primary_selection_text(&mut self) -> Option<String> {
+        self.selection_text(xlib::XA_PRIMARY)
+    }
+
+    fn set_primary_selection_text(&mut self, text: &str) {
+        debug!("Taking X11 primary selection with {} bytes", text.len());
+        self.take_selection(xlib::XA_PRIMARY, text);
+    }
+
+    fn set_ime_cursor_area(&mut self, position: Position, size: Size) {
+        if self.input_context.is_none() && !self.input_method_missing {
+            self.input_context = self.open_input_context();
+            match self.input_context {
+                Some(_) => debug!("Opened X input method context for IME placement"),
+                None => warn!("No X input method with over-the-spot preedit, the IME places its window on its own"),
+            }
+            self.input_method_missing = self.input_context.is_none();
+        }
+        let Some((_, input_context)) = self.input_context else {
+            return;
+        };
+        let mut spot = ime_spot_location(position, size);
+        unsafe {
+            let attributes = xlib::XVaCreateNestedList(
+                0,
+                xlib::XNSpotLocation_0.as_ptr(),
+                &mut spot as *mut xlib::XPoint,
+                ptr::null_mut::<c_char>(),
+            );
+            xlib::XSetICValues(input_context, xlib::XNPreeditAttributes_0.as_ptr(), attributes, ptr::null_mut::<c_char>());
+            xlib::XFree(attributes);
+        }
+    }
+
+    fn key_names(&self) -> HashMap<KeyCode, String> {
+        let mut names = HashMap::new();
+        unsafe {
+            let mut state = mem::zeroed::<xlib::XkbStateRec>();
+            xlib::XkbGetState(self.display, XKB_USE_CORE_KBD, &mut state);
+            let (mut min_keycode, mut max_keycode) = (0, 0);
+            xlib::XDisplayKeycodes(self.display, &mut min_keycode, &mut max_keycode);
+
+            for keycode in min_keycode as u32..=max_keycode as u32 {
+                let Some(character) = keysym::keysym_to_char(self.keysym(keycode, state.group as i32)) else { continue };
+                let key = self.translate_key(keycode, (state.group as u32) << 13);
+                if key != KeyCode::Unknown {
+                    names.entry(key).or_insert_with(|| keysym::key_label(character));
+                }
+            }
+        }
+        names
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
 }
 
+#[cfg(feature = "vulkan")]
+impl crate::io::VulkanWindow for X11Window {
+    fn required_instance_extensions(&self) -> Vec<CString> {
+        vec![ash::khr::surface::NAME.to_owned(), ash::khr::xlib_surface::NAME.to_owned()]
+    }
+
+    fn create_surface(&self, entry: &ash::Entry, instance: &ash::Instance) -> ash::prelude::VkResult<ash::vk::SurfaceKHR> {
+        let info = ash::vk::XlibSurfaceCreateInfoKHR::default()
+            .dpy(self.display.cast())
+            .window(self.window);
+        unsafe { ash::khr::xlib_surface::Instance::new(entry, instance).create_xlib_surface(&info, None) }
+    }
+}
+
 impl OpenGLWindow for X11Window {
     fn is_current(&self) -> bool {
         unsafe {
@@ -832,11 +2232,17 @@ impl OpenGLWindow for X11Window {
             }
         }
     }
+
+    fn create_shared_window(&mut self, width: u32, height: u32, title: &str, hints: &[WindowHint]) -> Result<Box<dyn Window>, String> {
+        info!("Creating X11 window sharing {}'s context: {} ({}x{})", self.title, title, width, height);
+        Ok(Box::new(X11Window::create(width, height, title, hints, self.glx_context)))
+    }
 }
 
 impl Drop for X11Window {
     fn drop(&mut self) {
         debug!("Dropping X11Window and cleaning up resources");
+        self.restore_video_mode();
         
         unsafe {
             // Cleanup OpenGL context first
@@ -858,6 +2264,18 @@ impl Drop for X11Window {
                 debug!("OpenGL context destroyed");
             }
 
+            if self.invisible_cursor != 0 {
+                xlib::XFreeCursor(self.display, self.invisible_cursor);
+                self.invisible_cursor = 0;
+            }
+            for (_, cursor) in self.icon_cursors.drain() {
+                xlib::XFreeCursor(self.display, cursor);
+            }
+            if let Some((input_method, input_context)) = self.input_context.take() {
+                xlib::XDestroyIC(input_context);
+                xlib::XCloseIM(input_method);
+            }
+
             // Cleanup X11 window
             if self.window != 0 {
                 debug!("Destroying X11 window");
@@ -903,7 +2321,7 @@ impl WindowFactory for X11WindowFactory {
     fn supports_feature(&self, feature: WindowFeature) -> bool {
         match feature {
             WindowFeature::OpenGL => true,
-            WindowFeature::Vulkan => false, // Not implemented yet
+            WindowFeature::Vulkan => cfg!(feature = "vulkan"),
             WindowFeature::MultiWindow => true,
             WindowFeature::HighDPI => true,
             WindowFeature::Fullscreen => true,
@@ -922,4 +2340,100 @@ impl WindowFactory for X11WindowFactory {
     fn backend_version(&self) -> Option<String> {
         Some("X11R6+".to_string())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ime_spot_sits_under_the_cursor_area() {
+        let spot = ime_spot_location(Position(40, 100), Size(2, 18));
+        assert_eq!((spot.x, spot.y), (40, 118));
+        // X coordinates are 16 bit; far-off areas stick to the edge rather than wrap around
+        let spot = ime_spot_location(Position(-70_000, i32::MAX), Size(2, u32::MAX));
+        assert_eq!((spot.x, spot.y), (i16::MIN, i16::MAX));
+    }
+
+    #[test]
+    fn test_cursor_font_glyphs() {
+        let icons = [
+            CursorIcon::Arrow,
+            CursorIcon::IBeam,
+            CursorIcon::Hand,
+            CursorIcon::Crosshair,
+            CursorIcon::ResizeHorizontal,
+            CursorIcon::ResizeVertical,
+            CursorIcon::ResizeNwSe,
+            CursorIcon::ResizeNeSw,
+            CursorIcon::ResizeAll,
+            CursorIcon::NotAllowed,
+            CursorIcon::Wait,
+        ];
+        let glyphs: Vec<u32> = icons.iter().map(|icon| cursor_font_glyph(*icon).unwrap()).collect();
+        // The font pairs each glyph with its mask at the next odd index, and XC_num_glyphs is 154
+        assert!(glyphs.iter().all(|glyph| glyph % 2 == 0 && *glyph < 154));
+        let mut distinct = glyphs.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), glyphs.len());
+
+        assert_eq!(cursor_font_glyph(CursorIcon::Arrow), Some(XC_LEFT_PTR));
+        assert_eq!(cursor_font_glyph(CursorIcon::Custom(3)), None);
+    }
+
+    #[test]
+    fn test_appearance_hints_fold_in_order() {
+        let appearance = AppearanceHints::from_hints(&[]);
+        assert!(appearance.resizable && appearance.decorated && appearance.visible && appearance.focused);
+        assert!(!appearance.floating && !appearance.maximized && !appearance.transparent);
+        assert!(appearance.initial_wm_state().is_empty());
+        assert!(matches!(appearance.size_limits(Size(800, 600)), (None, None)));
+
+        let appearance = AppearanceHints::from_hints(&[
+            WindowHint::Resizable(false),
+            WindowHint::Floating(true),
+            WindowHint::Visible(false),
+            WindowHint::Maximized(true),
+            WindowHint::Samples(4),
+            WindowHint::Visible(true),
+        ]);
+        assert!(!appearance.resizable && appearance.floating && appearance.maximized);
+        // The last of repeated hints wins
+        assert!(appearance.visible);
+        assert_eq!(
+            appearance.initial_wm_state(),
+            ["_NET_WM_STATE_ABOVE", "_NET_WM_STATE_MAXIMIZED_VERT", "_NET_WM_STATE_MAXIMIZED_HORZ"]
+        );
+        let (min, max) = appearance.size_limits(Size(800, 600));
+        assert_eq!((min.unwrap().size(), max.unwrap().size()), ((800, 600), (800, 600)));
+    }
+
+    #[test]
+    fn test_incr_transfer_sends_pieces_then_empty() {
+        let mut transfer = IncrTransfer { requestor: 1, property: 2, target: 3, data: b"0123456789".to_vec(), sent: 0 };
+        assert_eq!(transfer.next_piece(4), b"0123");
+        assert_eq!(transfer.next_piece(4), b"4567");
+        assert_eq!(transfer.next_piece(4), b"89");
+        // The empty piece that ends the transfer, however often the requestor asks
+        assert!(transfer.next_piece(4).is_empty());
+        assert!(transfer.next_piece(4).is_empty());
+        assert_eq!(transfer.sent, 10);
+    }
+
+    #[test]
+    fn test_refresh_rate_rounds_mode_timings() {
+        let mode = |dot_clock: u64, h_total: u32, v_total: u32| {
+            let mut mode: xrandr::XRRModeInfo = unsafe { mem::zeroed() };
+            mode.dotClock = dot_clock as _;
+            mode.hTotal = h_total;
+            mode.vTotal = v_total;
+            mode
+        };
+        // 1920x1080 at 60 Hz and 2560x1440 at 143.9 Hz
+        assert_eq!(refresh_rate(&mode(148_500_000, 2200, 1125)), 60);
+        assert_eq!(refresh_rate(&mode(592_250_000, 2720, 1513)), 144);
+        // Modes without timings have no rate instead of dividing by zero
+        assert_eq!(refresh_rate(&mode(148_500_000, 0, 1125)), 0);
+    }
+}
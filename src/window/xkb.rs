@@ -0,0 +1,123 @@
+//! Minimal bindings to libxkbcommon for the Wayland backend
+//!
+//! Wayland compositors send the keyboard layout as an XKB keymap; this
+//! compiles it and tracks the active group so keys translate by layout.
+
+use std::ffi::{c_char, c_int, c_void};
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::ptr;
+
+#[repr(C)]
+struct XkbContext {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct XkbKeymap {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct XkbState {
+    _private: [u8; 0],
+}
+
+const XKB_CONTEXT_NO_FLAGS: c_int = 0;
+const XKB_KEYMAP_FORMAT_TEXT_V1: c_int = 1;
+const XKB_KEYMAP_COMPILE_NO_FLAGS: c_int = 0;
+const XKB_LAYOUT_INVALID: u32 = 0xffff_ffff;
+
+#[link(name = "xkbcommon")]
+extern "C" {
+    fn xkb_context_new(flags: c_int) -> *mut XkbContext;
+    fn xkb_context_unref(context: *mut XkbContext);
+    fn xkb_keymap_new_from_string(context: *mut XkbContext, string: *const c_char, format: c_int, flags: c_int) -> *mut XkbKeymap;
+    fn xkb_keymap_unref(keymap: *mut XkbKeymap);
+    fn xkb_keymap_min_keycode(keymap: *mut XkbKeymap) -> u32;
+    fn xkb_keymap_max_keycode(keymap: *mut XkbKeymap) -> u32;
+    fn xkb_keymap_key_get_syms_by_level(keymap: *mut XkbKeymap, key: u32, layout: u32, level: u32, syms_out: *mut *const u32) -> c_int;
+    fn xkb_state_new(keymap: *mut XkbKeymap) -> *mut XkbState;
+    fn xkb_state_unref(state: *mut XkbState);
+    fn xkb_state_update_mask(
+        state: *mut XkbState,
+        depressed_mods: u32,
+        latched_mods: u32,
+        locked_mods: u32,
+        depressed_layout: u32,
+        latched_layout: u32,
+        locked_layout: u32,
+    ) -> c_int;
+    fn xkb_state_key_get_layout(state: *mut XkbState, key: u32) -> u32;
+}
+
+/// A compiled keymap and the keyboard state over it
+pub(crate) struct Keymap {
+    context: *mut XkbContext,
+    keymap: *mut XkbKeymap,
+    state: *mut XkbState,
+}
+
+impl Keymap {
+    /// Compile the XKB text keymap of `size` bytes that a compositor shared through `fd`
+    pub(crate) fn from_fd(fd: OwnedFd, size: usize) -> Option<Self> {
+        unsafe {
+            let data = libc::mmap(ptr::null_mut(), size, libc::PROT_READ, libc::MAP_PRIVATE, fd.as_raw_fd(), 0);
+            if data == libc::MAP_FAILED {
+                return None;
+            }
+            let context = xkb_context_new(XKB_CONTEXT_NO_FLAGS);
+            // The keymap text is NUL-terminated within `size`
+            let keymap = if context.is_null() {
+                ptr::null_mut()
+            } else {
+                xkb_keymap_new_from_string(context, data as *const c_char, XKB_KEYMAP_FORMAT_TEXT_V1, XKB_KEYMAP_COMPILE_NO_FLAGS)
+            };
+            libc::munmap(data as *mut c_void, size);
+
+            let state = if keymap.is_null() { ptr::null_mut() } else { xkb_state_new(keymap) };
+            let keymap = Self { context, keymap, state };
+            (!keymap.state.is_null()).then_some(keymap)
+        }
+    }
+
+    /// Apply a modifiers event from the compositor
+    pub(crate) fn update_mask(&mut self, depressed: u32, latched: u32, locked: u32, group: u32) {
+        unsafe {
+            xkb_state_update_mask(self.state, depressed, latched, locked, 0, 0, group);
+        }
+    }
+
+    /// Unshifted keysym of XKB keycode `keycode` in the active layout, 0 if none
+    pub(crate) fn keysym(&self, keycode: u32) -> u32 {
+        unsafe {
+            let layout = xkb_state_key_get_layout(self.state, keycode);
+            if layout == XKB_LAYOUT_INVALID {
+                return 0;
+            }
+            let mut syms = ptr::null();
+            let count = xkb_keymap_key_get_syms_by_level(self.keymap, keycode, layout, 0, &mut syms);
+            if count > 0 { *syms } else { 0 }
+        }
+    }
+
+    /// XKB keycodes the keymap defines
+    pub(crate) fn keycodes(&self) -> std::ops::RangeInclusive<u32> {
+        unsafe { xkb_keymap_min_keycode(self.keymap)..=xkb_keymap_max_keycode(self.keymap) }
+    }
+}
+
+impl Drop for Keymap {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.state.is_null() {
+                xkb_state_unref(self.state);
+            }
+            if !self.keymap.is_null() {
+                xkb_keymap_unref(self.keymap);
+            }
+            if !self.context.is_null() {
+                xkb_context_unref(self.context);
+            }
+        }
+    }
+}